@@ -1,17 +1,61 @@
 
 param(
-    [string]$ConfigPath
+    [string]$PipeName
 )
 
 $ErrorActionPreference = 'Stop'
+# How long the launcher has to connect once this helper is elevated.
+$ConnectTimeoutMs = 60000
+
+# One request line in, one reply line out per connection, UTF-8 JSON both ways.
+function New-HelperPipe {
+    param([string]$Name)
+
+    # Only the elevating user may connect (the launcher runs as that user, unelevated);
+    # network logons are refused outright.
+    $security = New-Object System.IO.Pipes.PipeSecurity
+    $user = [System.Security.Principal.WindowsIdentity]::GetCurrent().User
+    $security.AddAccessRule((New-Object System.IO.Pipes.PipeAccessRule($user, 'ReadWrite', 'Allow')))
+    $network = New-Object System.Security.Principal.SecurityIdentifier('S-1-5-2')
+    $security.AddAccessRule((New-Object System.IO.Pipes.PipeAccessRule($network, 'FullControl', 'Deny')))
+    New-Object System.IO.Pipes.NamedPipeServerStream($Name, 'InOut', 1, 'Byte', 'Asynchronous', 4096, 4096, $security)
+}
+
+function Wait-Client {
+    param($Pipe, [int]$TimeoutMs)
+
+    $task = $Pipe.WaitForConnectionAsync()
+    if ($TimeoutMs -gt 0) {
+        return $task.Wait($TimeoutMs)
+    }
+    $task.Wait()
+    return $true
+}
+
+function Read-Request {
+    param($Pipe)
+
+    $utf8NoBom = New-Object System.Text.UTF8Encoding($false)
+    $reader = New-Object System.IO.StreamReader($Pipe, $utf8NoBom, $false, 4096, $true)
+    $line = $reader.ReadLine()
+    if ([string]::IsNullOrWhiteSpace($line)) {
+        return $null
+    }
+    return $line | ConvertFrom-Json
+}
+
+function Close-Client {
+    param($Pipe)
+    try { $Pipe.Disconnect() } catch {}
+}
 
 function Write-Result {
     param(
+        $Pipe,
         [bool]$Ok,
         [string]$AppMountPath,
         [string]$AppRuntimePath,
-        [string]$ErrorMessage,
-        [string]$ResultPath
+        [string]$ErrorMessage
     )
 
     $payload = [ordered]@{
@@ -20,25 +64,36 @@ function Write-Result {
         app_runtime_path = $AppRuntimePath
         error = $ErrorMessage
     }
-    $json = $payload | ConvertTo-Json -Compress
+    $json = ($payload | ConvertTo-Json -Compress) + "`n"
     $utf8NoBom = New-Object System.Text.UTF8Encoding($false)
-    [System.IO.File]::WriteAllText($ResultPath, $json, $utf8NoBom)
+    $bytes = $utf8NoBom.GetBytes($json)
+    try {
+        $Pipe.Write($bytes, 0, $bytes.Length)
+        $Pipe.Flush()
+        $Pipe.WaitForPipeDrain()
+    } catch {
+        # The launcher went away; it finds out from the missing reply.
+    }
 }
 
-if (-not $ConfigPath -or -not (Test-Path $ConfigPath)) {
-    # Try to write to a fallback log if we can't determine the result path
-    $fallbackLog = "$env:TEMP\configarc_vhd_helper_error.log"
-    "Missing ConfigPath argument. Args: $($args | Out-String)" | Out-File -FilePath $fallbackLog -Append -Encoding utf8
+$fallbackLog = "$env:TEMP\configarc_vhd_helper_error.log"
+if (-not $PipeName) {
+    "Missing PipeName argument. Args: $($args | Out-String)" | Out-File -FilePath $fallbackLog -Append -Encoding utf8
     exit 1
 }
 
-# Read params from JSON
 try {
-    $paramsContent = Get-Content -Path $ConfigPath -Raw -Encoding UTF8
-    $params = $paramsContent | ConvertFrom-Json
+    $pipe = New-HelperPipe $PipeName
+    if (-not (Wait-Client $pipe $ConnectTimeoutMs)) {
+        throw 'The launcher did not connect'
+    }
+    $params = Read-Request $pipe
 } catch {
-    $fallbackLog = "$env:TEMP\configarc_vhd_helper_error.log"
-    "Failed to read/parse params JSON: $_" | Out-File -FilePath $fallbackLog -Append -Encoding utf8
+    "Failed to receive the mount request: $_" | Out-File -FilePath $fallbackLog -Append -Encoding utf8
+    exit 1
+}
+if ($null -eq $params -or $params.command -ne 'mount') {
+    Write-Result $pipe $false $null $null 'Expected a mount request'
     exit 1
 }
 
@@ -50,10 +105,14 @@ if ($null -ne $params.app_patches) {
 $appData = $params.app_data
 $option = $params.option
 $delta = $params.delta
+$deltaPath = $params.delta_path
 $repairRoot = $params.repair_root
-$result = $params.result_path
-$signal = $params.signal_path
-$done = $params.done_path
+$reuseRuntime = $params.reuse_runtime -eq $true
+$keepRuntime = $params.keep_runtime -eq $true
+$appMountPoint = if ($params.app_mount_point) { $params.app_mount_point } else { 'X:\' }
+$appDataMountPoint = if ($params.appdata_mount_point) { $params.appdata_mount_point } else { 'Y:\' }
+$optionMountPoint = if ($params.option_mount_point) { $params.option_mount_point } else { 'Z:\' }
+$mountPoints = @($appMountPoint, $appDataMountPoint, $optionMountPoint)
 
 
 try {
@@ -64,8 +123,14 @@ try {
     if (-not (Test-Path $appData)) { throw "AppData VHD not found: $appData" }
     if (-not (Test-Path $option)) { throw "Option VHD not found: $option" }
 
-    if ((Test-Path 'X:\') -or (Test-Path 'Y:\') -or (Test-Path 'Z:\')) {
-        throw 'Drive X:, Y:, or Z: is already in use. Please eject or change the assigned drives.'
+    foreach ($mountPoint in $mountPoints) {
+        if ($mountPoint -match '^[A-Za-z]:\\$') {
+            if (Test-Path $mountPoint) {
+                throw "Drive $($mountPoint.Substring(0, 2)) is already in use. Please eject or change the assigned drive."
+            }
+        } elseif (Get-ChildItem -LiteralPath $mountPoint -Force -ErrorAction SilentlyContinue | Select-Object -First 1) {
+            throw "Mount folder $mountPoint is not empty. It may still have a volume attached."
+        }
     }
 
     function Mount-ToDrive {
@@ -74,7 +139,8 @@ try {
         [string]$DrivePath
     )
 
-    Mount-DiskImage -ImagePath $ImagePath -StorageType VHD -NoDriveLetter -Passthru -Access ReadWrite -Confirm:$false -ErrorAction Stop |
+    $storageType = if ([System.IO.Path]::GetExtension($ImagePath) -ieq '.vhdx') { 'VHDX' } else { 'VHD' }
+    Mount-DiskImage -ImagePath $ImagePath -StorageType $storageType -NoDriveLetter -Passthru -Access ReadWrite -Confirm:$false -ErrorAction Stop |
         Get-Disk |
         Get-Partition |
         Where-Object { $Null -ne ($_ | Get-Volume) } |
@@ -109,17 +175,23 @@ $mountedOption = $false
             $ext = '.vhd'
         }
         $appRuntimePath = Join-Path $parentDir "$stem-runtime$ext"
+        if (-not [string]::IsNullOrWhiteSpace($deltaPath)) {
+            $appRuntimePath = $deltaPath
+        }
 
         Dismount-Image -ImagePath $appRuntimePath
-        if (Test-Path $appRuntimePath) {
-            Remove-Item $appRuntimePath -Force -ErrorAction SilentlyContinue
-        }
+        # A delta restored from a snapshot is already in place; only a fresh one is created here.
+        if (-not ($reuseRuntime -and (Test-Path $appRuntimePath))) {
+            if (Test-Path $appRuntimePath) {
+                Remove-Item $appRuntimePath -Force -ErrorAction SilentlyContinue
+            }
 
-        $dpPath = Join-Path $env:TEMP ("configarc_vhd_diskpart_{0}.txt" -f $PID)
-        $dpScript = "create vdisk file=`"$appRuntimePath`" parent=`"$appParent`"`n"
-        Set-Content -Path $dpPath -Value $dpScript -Encoding ASCII
-        & diskpart.exe /s $dpPath | Out-Null
-        Remove-Item $dpPath -Force -ErrorAction SilentlyContinue
+            $dpPath = Join-Path $env:TEMP ("configarc_vhd_diskpart_{0}.txt" -f $PID)
+            $dpScript = "create vdisk file=`"$appRuntimePath`" parent=`"$appParent`"`n"
+            Set-Content -Path $dpPath -Value $dpScript -Encoding ASCII
+            & diskpart.exe /s $dpPath | Out-Null
+            Remove-Item $dpPath -Force -ErrorAction SilentlyContinue
+        }
 
         if (-not (Test-Path $appRuntimePath)) {
             throw 'Failed to create runtime VHD'
@@ -128,28 +200,27 @@ $mountedOption = $false
         $appMountPath = $appRuntimePath
     }
 
-    Mount-ToDrive -ImagePath $appMountPath -DrivePath 'X:\'
+    Mount-ToDrive -ImagePath $appMountPath -DrivePath $appMountPoint
     $mountedApp = $true
-    Mount-ToDrive -ImagePath $appData -DrivePath 'Y:\'
+    Mount-ToDrive -ImagePath $appData -DrivePath $appDataMountPoint
     $mountedAppdata = $true
-    Mount-ToDrive -ImagePath $option -DrivePath 'Z:\'
+    Mount-ToDrive -ImagePath $option -DrivePath $optionMountPoint
     $mountedOption = $true
 
     try {
         Start-Sleep -Milliseconds 300
         $shell = New-Object -ComObject Shell.Application
+        $urls = $mountPoints | ForEach-Object { 'file:///' + ($_.TrimEnd('\') -replace '\\', '/') + '*' }
         $shell.Windows() | Where-Object {
-            $_.LocationURL -like 'file:///X:*' -or $_.LocationURL -like 'file:///X:/*' -or
-            $_.LocationURL -like 'file:///Y:*' -or $_.LocationURL -like 'file:///Y:/*' -or
-            $_.LocationURL -like 'file:///Z:*' -or $_.LocationURL -like 'file:///Z:/*'
+            $location = $_.LocationURL
+            $urls | Where-Object { $location -like $_ }
         } | ForEach-Object { $_.Quit() }
     } catch {
     }
 
-    Write-Result $true $appMountPath $appRuntimePath $null $result
+    Write-Result $pipe $true $appMountPath $appRuntimePath $null
 } catch {
     $e = $_
-    $fallbackLog = "$env:TEMP\configarc_vhd_helper_error.log"
     try {
         "$(Get-Date) - Error occurred" | Out-File -FilePath $fallbackLog -Append -Encoding utf8
         $e | Out-String | Out-File -FilePath $fallbackLog -Append -Encoding utf8
@@ -173,12 +244,25 @@ $mountedOption = $false
     if (-not [string]::IsNullOrWhiteSpace($repairRoot) -and (Test-Path $repairRoot)) {
         Remove-Item $repairRoot -Recurse -Force -ErrorAction SilentlyContinue
     }
-    Write-Result $false $null $null $_.Exception.Message $result
+    Write-Result $pipe $false $null $null $e.Exception.Message
     exit 1
 }
+Close-Client $pipe
 
-while (-not (Test-Path $signal)) {
-    Start-Sleep -Milliseconds 500
+# Serve until asked to unmount. A launcher that crashed leaves the images attached here, and
+# the next one reconnects during its stale-mount recovery.
+while ($true) {
+    [void](Wait-Client $pipe 0)
+    try {
+        $request = Read-Request $pipe
+    } catch {
+        $request = $null
+    }
+    if ($null -ne $request -and $request.command -eq 'unmount') {
+        break
+    }
+    Write-Result $pipe $false $null $null 'Expected an unmount request'
+    Close-Client $pipe
 }
 
 try {
@@ -187,7 +271,8 @@ try {
     Dismount-Image -ImagePath $appMountPath
     if ($appRuntimePath) {
         Dismount-Image -ImagePath $appRuntimePath
-        if (Test-Path $appRuntimePath) {
+        # The launcher moves a kept delta into its snapshots once this helper is done.
+        if (-not $keepRuntime -and (Test-Path $appRuntimePath)) {
             Remove-Item $appRuntimePath -Force -ErrorAction SilentlyContinue
         }
     }
@@ -197,4 +282,5 @@ try {
 } catch {
 }
 
-Set-Content -Path $done -Value '1' -Encoding ASCII
+Write-Result $pipe $true $null $null $null
+$pipe.Dispose()
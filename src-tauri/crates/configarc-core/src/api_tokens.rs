@@ -0,0 +1,240 @@
+use crate::error::ConfigError;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use thiserror::Error;
+
+const API_TOKENS_FILE_NAME: &str = "configarc_api_tokens.json";
+const TOKEN_PREFIX: &str = "cat_";
+const TOKEN_BYTES: usize = 32;
+
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+/// What an endpoint of the control API does, as far as token scopes are concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiPermission {
+    /// Listing games, profiles and status.
+    Read,
+    /// Starting and stopping games or the launch queue.
+    Launch,
+    /// Anything that changes configs, games or files on disk.
+    Manage,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiScope {
+    ReadOnly,
+    /// Enough for a remote on a tablet: it can see the game list and start games, but not
+    /// edit anything.
+    LaunchOnly,
+    Full,
+}
+
+impl ApiScope {
+    pub fn grants(self, permission: ApiPermission) -> bool {
+        match self {
+            ApiScope::ReadOnly => permission == ApiPermission::Read,
+            ApiScope::LaunchOnly => permission != ApiPermission::Manage,
+            ApiScope::Full => true,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ApiAuthError {
+    #[error("API token missing")]
+    Missing,
+    #[error("API token is unknown or revoked")]
+    Unknown,
+    #[error("API token scope {scope:?} does not allow {permission:?} endpoints")]
+    Forbidden {
+        scope: ApiScope,
+        permission: ApiPermission,
+    },
+    #[error(transparent)]
+    Store(#[from] ConfigError),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiToken {
+    pub id: String,
+    pub name: String,
+    pub scope: ApiScope,
+    pub created_at: String,
+    #[serde(default)]
+    pub last_used_at: Option<String>,
+}
+
+/// The plain token is only returned here, right after creation; the store keeps its hash.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IssuedApiToken {
+    pub token: String,
+    pub info: ApiToken,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredToken {
+    #[serde(flatten)]
+    info: ApiToken,
+    token_hash: String,
+}
+
+fn api_tokens_path() -> PathBuf {
+    Path::new(".").join(API_TOKENS_FILE_NAME)
+}
+
+fn read_store(path: &Path) -> Result<Vec<StoredToken>, ConfigError> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let data = fs::read_to_string(path)?;
+    if data.trim().is_empty() {
+        return Ok(vec![]);
+    }
+    Ok(serde_json::from_str(&data)?)
+}
+
+/// Every authorized request rewrites the store, so a crash mid-write must not lose the tokens.
+fn write_store(path: &Path, tokens: &[StoredToken]) -> Result<(), ConfigError> {
+    let json = serde_json::to_string_pretty(tokens)?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn hash_token(token: &str) -> String {
+    hex(&Sha256::digest(token.as_bytes()))
+}
+
+fn random_hex(len: usize) -> Result<String, ConfigError> {
+    let mut bytes = vec![0u8; len];
+    getrandom::getrandom(&mut bytes)
+        .map_err(|e| ConfigError::Parse(format!("Failed to generate API token: {}", e)))?;
+    Ok(hex(&bytes))
+}
+
+/// Hashes are compared without an early exit so response timing does not leak a prefix.
+fn hashes_match(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn create_at(path: &Path, name: &str, scope: ApiScope) -> Result<IssuedApiToken, ConfigError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ConfigError::Parse("API token name must not be empty".to_string()));
+    }
+    let mut tokens = read_store(path)?;
+    let token = format!("{}{}", TOKEN_PREFIX, random_hex(TOKEN_BYTES)?);
+    let info = ApiToken {
+        id: random_hex(8)?,
+        name: name.to_string(),
+        scope,
+        created_at: Utc::now().to_rfc3339(),
+        last_used_at: None,
+    };
+    tokens.push(StoredToken {
+        info: info.clone(),
+        token_hash: hash_token(&token),
+    });
+    write_store(path, &tokens)?;
+    Ok(IssuedApiToken { token, info })
+}
+
+fn revoke_at(path: &Path, id: &str) -> Result<bool, ConfigError> {
+    let mut tokens = read_store(path)?;
+    let before = tokens.len();
+    tokens.retain(|t| t.info.id != id);
+    if tokens.len() == before {
+        return Ok(false);
+    }
+    write_store(path, &tokens)?;
+    Ok(true)
+}
+
+fn authorize_at(path: &Path, token: Option<&str>, permission: ApiPermission) -> Result<ApiToken, ApiAuthError> {
+    let token = token.map(str::trim).filter(|t| !t.is_empty()).ok_or(ApiAuthError::Missing)?;
+    let hash = hash_token(token);
+    let mut tokens = read_store(path)?;
+    let stored = tokens
+        .iter_mut()
+        .find(|t| hashes_match(&t.token_hash, &hash))
+        .ok_or(ApiAuthError::Unknown)?;
+    if !stored.info.scope.grants(permission) {
+        return Err(ApiAuthError::Forbidden {
+            scope: stored.info.scope,
+            permission,
+        });
+    }
+    stored.info.last_used_at = Some(Utc::now().to_rfc3339());
+    let info = stored.info.clone();
+    write_store(path, &tokens)?;
+    Ok(info)
+}
+
+/// Issues a new token. Only its SHA-256 hash is written to disk.
+pub fn create_api_token(name: &str, scope: ApiScope) -> Result<IssuedApiToken, ConfigError> {
+    let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    create_at(&api_tokens_path(), name, scope)
+}
+
+pub fn list_api_tokens() -> Result<Vec<ApiToken>, ConfigError> {
+    let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    Ok(read_store(&api_tokens_path())?.into_iter().map(|t| t.info).collect())
+}
+
+/// Returns false when no token has that id.
+pub fn revoke_api_token(id: &str) -> Result<bool, ConfigError> {
+    let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    revoke_at(&api_tokens_path(), id)
+}
+
+/// Checks a presented token against what the endpoint needs and records its use.
+pub fn authorize_api_token(token: Option<&str>, permission: ApiPermission) -> Result<ApiToken, ApiAuthError> {
+    let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    authorize_at(&api_tokens_path(), token, permission)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enforces_scope_and_revocation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(API_TOKENS_FILE_NAME);
+        let tablet = create_at(&path, "Tablet", ApiScope::LaunchOnly).unwrap();
+        assert!(!fs::read_to_string(&path).unwrap().contains(&tablet.token));
+
+        let used = authorize_at(&path, Some(&tablet.token), ApiPermission::Launch).unwrap();
+        assert!(used.last_used_at.is_some());
+        assert!(!path.with_extension("json.tmp").exists());
+        assert_eq!(read_store(&path).unwrap()[0].info.last_used_at, used.last_used_at);
+        assert!(authorize_at(&path, Some(&tablet.token), ApiPermission::Read).is_ok());
+        assert!(matches!(
+            authorize_at(&path, Some(&tablet.token), ApiPermission::Manage),
+            Err(ApiAuthError::Forbidden { .. })
+        ));
+        assert!(matches!(authorize_at(&path, Some("cat_nope"), ApiPermission::Read), Err(ApiAuthError::Unknown)));
+        assert!(matches!(authorize_at(&path, None, ApiPermission::Read), Err(ApiAuthError::Missing)));
+
+        assert!(revoke_at(&path, &tablet.info.id).unwrap());
+        assert!(!revoke_at(&path, &tablet.info.id).unwrap());
+        assert!(matches!(
+            authorize_at(&path, Some(&tablet.token), ApiPermission::Launch),
+            Err(ApiAuthError::Unknown)
+        ));
+    }
+}
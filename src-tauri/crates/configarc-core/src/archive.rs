@@ -0,0 +1,195 @@
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const COPY_CHUNK_SIZE: usize = 4 * 1024 * 1024;
+/// Progress is reported at most this often so multi-gigabyte images do not flood listeners.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+const PARTIAL_SUFFIX: &str = "configarc-partial";
+
+#[derive(Debug, Clone, Default)]
+pub struct CopyOptions {
+    /// Caps the copy rate so a game running from the same disk keeps its bandwidth.
+    pub max_bytes_per_sec: Option<u64>,
+    pub overwrite: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CopyStage {
+    Copying,
+    Verifying,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CopyProgress {
+    pub stage: CopyStage,
+    pub processed: u64,
+    pub total: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifiedCopy {
+    pub destination: PathBuf,
+    pub bytes: u64,
+    pub sha256: String,
+}
+
+fn partial_path(dest: &Path) -> PathBuf {
+    let mut name = dest.file_name().map(|n| n.to_os_string()).unwrap_or_default();
+    name.push(format!(".{}", PARTIAL_SUFFIX));
+    dest.with_file_name(name)
+}
+
+struct Reporter<F: FnMut(CopyProgress)> {
+    on_progress: F,
+    last: Option<Instant>,
+}
+
+impl<F: FnMut(CopyProgress)> Reporter<F> {
+    fn report(&mut self, stage: CopyStage, processed: u64, total: u64) {
+        let due = self.last.is_none_or(|last| last.elapsed() >= PROGRESS_INTERVAL);
+        if due || processed == total {
+            self.last = Some(Instant::now());
+            (self.on_progress)(CopyProgress { stage, processed, total });
+        }
+    }
+}
+
+fn hash_file<F: FnMut(CopyProgress)>(path: &Path, total: u64, reporter: &mut Reporter<F>) -> Result<String, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut reader = BufReader::with_capacity(COPY_CHUNK_SIZE, file);
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; COPY_CHUNK_SIZE];
+    let mut processed = 0u64;
+    loop {
+        let read = reader.read(&mut buf).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buf[..read]);
+        processed += read as u64;
+        reporter.report(CopyStage::Verifying, processed, total);
+    }
+    Ok(hex_digest(hasher))
+}
+
+fn hex_digest(hasher: Sha256) -> String {
+    hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn copy_hashing<F: FnMut(CopyProgress)>(
+    src: &Path,
+    partial: &Path,
+    total: u64,
+    options: &CopyOptions,
+    reporter: &mut Reporter<F>,
+) -> Result<String, String> {
+    let mut reader = BufReader::with_capacity(
+        COPY_CHUNK_SIZE,
+        File::open(src).map_err(|e| format!("Failed to open {}: {}", src.display(), e))?,
+    );
+    let mut writer = File::create(partial).map_err(|e| format!("Failed to create {}: {}", partial.display(), e))?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; COPY_CHUNK_SIZE];
+    let mut copied = 0u64;
+    let started = Instant::now();
+    loop {
+        let read = reader.read(&mut buf).map_err(|e| format!("Failed to read {}: {}", src.display(), e))?;
+        if read == 0 {
+            break;
+        }
+        writer
+            .write_all(&buf[..read])
+            .map_err(|e| format!("Failed to write {}: {}", partial.display(), e))?;
+        hasher.update(&buf[..read]);
+        copied += read as u64;
+        reporter.report(CopyStage::Copying, copied, total);
+        if let Some(rate) = options.max_bytes_per_sec.filter(|rate| *rate > 0) {
+            let expected = Duration::from_secs_f64(copied as f64 / rate as f64);
+            if let Some(ahead) = expected.checked_sub(started.elapsed()) {
+                std::thread::sleep(ahead);
+            }
+        }
+    }
+    // Verification must read what reached the disk, not what is still in the write cache.
+    writer.sync_all().map_err(|e| format!("Failed to flush {}: {}", partial.display(), e))?;
+    Ok(hex_digest(hasher))
+}
+
+/// Copies `src` to `dest`, hashing the source on the way and re-reading the copy afterwards.
+/// The copy is written under a temporary name and only takes `dest`'s name once both hashes
+/// match, so an interrupted or corrupted copy never looks like a finished one.
+pub fn copy_verified(
+    src: &Path,
+    dest: &Path,
+    options: &CopyOptions,
+    on_progress: impl FnMut(CopyProgress),
+) -> Result<VerifiedCopy, String> {
+    let total = fs::metadata(src)
+        .map_err(|e| format!("Failed to read {}: {}", src.display(), e))?
+        .len();
+    if dest.exists() && !options.overwrite {
+        return Err(format!("Destination already exists: {}", dest.display()));
+    }
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let partial = partial_path(dest);
+    let mut reporter = Reporter { on_progress, last: None };
+    let result = copy_hashing(src, &partial, total, options, &mut reporter).and_then(|source_hash| {
+        let copy_hash = hash_file(&partial, total, &mut reporter)?;
+        if copy_hash != source_hash {
+            return Err(format!(
+                "Verification failed for {}: source {} but copy {}",
+                dest.display(),
+                source_hash,
+                copy_hash
+            ));
+        }
+        Ok(source_hash)
+    });
+    let sha256 = match result {
+        Ok(hash) => hash,
+        Err(err) => {
+            let _ = fs::remove_file(&partial);
+            return Err(err);
+        }
+    };
+    if dest.exists() {
+        fs::remove_file(dest).map_err(|e| format!("Failed to replace {}: {}", dest.display(), e))?;
+    }
+    fs::rename(&partial, dest).map_err(|e| format!("Failed to finish {}: {}", dest.display(), e))?;
+    Ok(VerifiedCopy {
+        destination: dest.to_path_buf(),
+        bytes: total,
+        sha256,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copies_with_matching_hash_and_refuses_to_clobber() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("SDEZ_1.40.00.vhd");
+        fs::write(&src, vec![7u8; COPY_CHUNK_SIZE + 17]).unwrap();
+        let dest = dir.path().join("archive").join("SDEZ_1.40.00.vhd");
+
+        let mut stages = Vec::new();
+        let copy = copy_verified(&src, &dest, &CopyOptions::default(), |p| stages.push(p.stage)).unwrap();
+        assert_eq!(copy.bytes, COPY_CHUNK_SIZE as u64 + 17);
+        assert_eq!(fs::read(&dest).unwrap(), fs::read(&src).unwrap());
+        assert!(!partial_path(&dest).exists());
+        assert!(stages.contains(&CopyStage::Copying) && stages.contains(&CopyStage::Verifying));
+
+        assert!(copy_verified(&src, &dest, &CopyOptions::default(), |_| {}).is_err());
+    }
+}
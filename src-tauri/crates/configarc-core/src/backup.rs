@@ -0,0 +1,577 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::{self, File};
+use std::io::{BufReader, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+const APPDATA_BACKUP_DIR_NAME: &str = "appdata_backups";
+const CHUNKS_DIR_NAME: &str = "chunks";
+const SNAPSHOTS_DIR_NAME: &str = "snapshots";
+const PARTIAL_SUFFIX: &str = ".partial.json";
+const CHUNK_SIZE: usize = 4 * 1024 * 1024;
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+/// How often an in-progress backup records what it has stored so far.
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Creating and pruning share chunks, so they must not interleave: garbage collection could
+/// otherwise delete a chunk a running backup has written but not yet recorded.
+static REPO_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupFile {
+    /// Relative to the backed up directory, '/'-separated.
+    pub path: String,
+    pub size: u64,
+    pub modified_ms: u64,
+    /// SHA-256 of each fixed-size chunk, in file order.
+    pub chunks: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupSnapshot {
+    pub id: String,
+    pub source: PathBuf,
+    pub created_at: String,
+    /// `None` while the backup was interrupted before it finished.
+    #[serde(default)]
+    pub completed_at: Option<String>,
+    pub files: Vec<BackupFile>,
+}
+
+impl BackupSnapshot {
+    pub fn total_bytes(&self) -> u64 {
+        self.files.iter().map(|f| f.size).sum()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupSummary {
+    pub id: String,
+    pub created_at: String,
+    pub completed_at: Option<String>,
+    pub file_count: usize,
+    pub total_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupProgress {
+    pub file: String,
+    pub files_done: usize,
+    pub file_count: usize,
+    pub processed: u64,
+    pub total: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupReport {
+    pub snapshot_id: String,
+    /// False when cancelled; running the backup again resumes the same snapshot.
+    pub completed: bool,
+    pub files: usize,
+    pub total_bytes: u64,
+    /// Files whose size and modification time matched an earlier snapshot and were not re-read.
+    pub unchanged_files: usize,
+    pub new_chunks: usize,
+    pub new_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreReport {
+    pub snapshot_id: String,
+    pub files: usize,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PruneReport {
+    pub removed_snapshots: Vec<String>,
+    pub removed_chunks: usize,
+    pub freed_bytes: u64,
+}
+
+/// Where the appdata backups of a game live, next to its other restore points.
+pub fn appdata_backup_root_for(segatools_root: &Path) -> PathBuf {
+    segatools_root.join(APPDATA_BACKUP_DIR_NAME)
+}
+
+fn chunk_path(repo: &Path, hash: &str) -> PathBuf {
+    let prefix = hash.get(..2).unwrap_or("00");
+    repo.join(CHUNKS_DIR_NAME).join(prefix).join(hash)
+}
+
+fn snapshots_dir(repo: &Path) -> PathBuf {
+    repo.join(SNAPSHOTS_DIR_NAME)
+}
+
+fn snapshot_path(repo: &Path, id: &str) -> PathBuf {
+    snapshots_dir(repo).join(format!("{}.json", id))
+}
+
+fn partial_path(repo: &Path, id: &str) -> PathBuf {
+    snapshots_dir(repo).join(format!("{}{}", id, PARTIAL_SUFFIX))
+}
+
+fn hex_digest(hasher: Sha256) -> String {
+    hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Writes through a temporary name so a crash never leaves a truncated file under the final one.
+fn write_atomic(path: &Path, data: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let mut tmp = path.as_os_str().to_os_string();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    let mut file = File::create(&tmp).map_err(|e| format!("Failed to create {}: {}", tmp.display(), e))?;
+    file.write_all(data)
+        .and_then(|_| file.sync_all())
+        .map_err(|e| format!("Failed to write {}: {}", tmp.display(), e))?;
+    drop(file);
+    fs::rename(&tmp, path).map_err(|e| format!("Failed to finish {}: {}", path.display(), e))
+}
+
+fn write_manifest(path: &Path, snapshot: &BackupSnapshot) -> Result<(), String> {
+    let json = serde_json::to_vec_pretty(snapshot).map_err(|e| e.to_string())?;
+    write_atomic(path, &json)
+}
+
+fn read_manifest(path: &Path) -> Result<BackupSnapshot, String> {
+    let data = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&data).map_err(|e| format!("Invalid backup manifest {}: {}", path.display(), e))
+}
+
+/// Every manifest in the repository, finished and interrupted, oldest first.
+fn read_manifests(repo: &Path) -> Result<Vec<BackupSnapshot>, String> {
+    let dir = snapshots_dir(repo);
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+    let mut snapshots = Vec::new();
+    for entry in fs::read_dir(&dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))? {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.extension().and_then(|e| e.to_str()) == Some("json") {
+            snapshots.push(read_manifest(&path)?);
+        }
+    }
+    snapshots.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+    Ok(snapshots)
+}
+
+fn manifest_path_of(repo: &Path, snapshot: &BackupSnapshot) -> PathBuf {
+    if snapshot.completed_at.is_some() {
+        snapshot_path(repo, &snapshot.id)
+    } else {
+        partial_path(repo, &snapshot.id)
+    }
+}
+
+fn modified_ms(meta: &fs::Metadata) -> u64 {
+    meta.modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+fn collect_files(root: &Path, dir: &Path, out: &mut Vec<(String, PathBuf, u64, u64)>) -> Result<(), String> {
+    let mut entries = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    entries.sort_by_key(|e| e.file_name());
+    for entry in entries {
+        let path = entry.path();
+        let meta = fs::metadata(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if meta.is_dir() {
+            collect_files(root, &path, out)?;
+        } else if meta.is_file() {
+            let rel = path
+                .strip_prefix(root)
+                .map_err(|e| e.to_string())?
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            out.push((rel, path, meta.len(), modified_ms(&meta)));
+        }
+    }
+    Ok(())
+}
+
+/// Maps a manifest path back under `dest`, refusing anything that would escape it.
+fn restore_target(dest: &Path, rel: &str) -> Result<PathBuf, String> {
+    let rel_path = Path::new(rel);
+    if rel.is_empty() || rel_path.components().any(|c| !matches!(c, Component::Normal(_))) {
+        return Err(format!("Backup manifest contains an unsafe path: {}", rel));
+    }
+    Ok(dest.join(rel_path))
+}
+
+/// Fills `buf` unless the file ends first; returns how much was read.
+fn read_chunk(reader: &mut impl Read, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+struct Reporter<F: FnMut(BackupProgress)> {
+    on_progress: F,
+    last: Option<Instant>,
+}
+
+impl<F: FnMut(BackupProgress)> Reporter<F> {
+    fn report(&mut self, progress: BackupProgress, force: bool) {
+        let due = self.last.is_none_or(|last| last.elapsed() >= PROGRESS_INTERVAL);
+        if due || force {
+            self.last = Some(Instant::now());
+            (self.on_progress)(progress);
+        }
+    }
+}
+
+fn create_locked<F: FnMut(BackupProgress)>(
+    repo: &Path,
+    source: &Path,
+    cancel: &AtomicBool,
+    on_progress: F,
+) -> Result<BackupReport, String> {
+    if !source.is_dir() {
+        return Err(format!("Appdata folder not found: {}", source.display()));
+    }
+    let mut files = Vec::new();
+    collect_files(source, source, &mut files)?;
+    let total: u64 = files.iter().map(|f| f.2).sum();
+
+    // Unchanged files are taken from the interrupted run first, then from the newest finished
+    // snapshot, as long as every chunk they point at is still in the repository.
+    let manifests = read_manifests(repo)?;
+    let resumed = manifests.iter().rev().find(|s| s.completed_at.is_none() && s.source == source);
+    let latest = manifests.iter().rev().find(|s| s.completed_at.is_some());
+    let mut known: HashMap<&str, &BackupFile> = HashMap::new();
+    for snapshot in latest.into_iter().chain(resumed) {
+        for file in &snapshot.files {
+            known.insert(file.path.as_str(), file);
+        }
+    }
+
+    let now = Utc::now();
+    let mut snapshot = match resumed {
+        Some(partial) => BackupSnapshot {
+            files: Vec::new(),
+            ..partial.clone()
+        },
+        None => {
+            let mut id = now.format("%Y%m%d-%H%M%S").to_string();
+            let mut n = 1;
+            while manifests.iter().any(|s| s.id == id) {
+                n += 1;
+                id = format!("{}-{}", now.format("%Y%m%d-%H%M%S"), n);
+            }
+            BackupSnapshot {
+                id,
+                source: source.to_path_buf(),
+                created_at: now.to_rfc3339(),
+                completed_at: None,
+                files: Vec::new(),
+            }
+        }
+    };
+    let partial = partial_path(repo, &snapshot.id);
+
+    let mut reporter = Reporter { on_progress, last: None };
+    let mut report = BackupReport {
+        snapshot_id: snapshot.id.clone(),
+        completed: false,
+        files: 0,
+        total_bytes: 0,
+        unchanged_files: 0,
+        new_chunks: 0,
+        new_bytes: 0,
+    };
+    let mut processed = 0u64;
+    let mut last_checkpoint = Instant::now();
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    let file_count = files.len();
+
+    'files: for (index, (rel, path, size, modified)) in files.into_iter().enumerate() {
+        let reusable = known.get(rel.as_str()).filter(|f| {
+            f.size == size && f.modified_ms == modified && f.chunks.iter().all(|c| chunk_path(repo, c).exists())
+        });
+        if let Some(previous) = reusable {
+            snapshot.files.push((*previous).clone());
+            report.unchanged_files += 1;
+            processed += size;
+        } else {
+            let mut reader = BufReader::with_capacity(
+                CHUNK_SIZE,
+                File::open(&path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?,
+            );
+            let mut chunks = Vec::new();
+            loop {
+                if cancel.load(Ordering::SeqCst) {
+                    break 'files;
+                }
+                let read = read_chunk(&mut reader, &mut buf)
+                    .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+                if read == 0 {
+                    break;
+                }
+                let mut hasher = Sha256::new();
+                hasher.update(&buf[..read]);
+                let hash = hex_digest(hasher);
+                let target = chunk_path(repo, &hash);
+                if !target.exists() {
+                    write_atomic(&target, &buf[..read])?;
+                    report.new_chunks += 1;
+                    report.new_bytes += read as u64;
+                }
+                chunks.push(hash);
+                processed += read as u64;
+                reporter.report(
+                    BackupProgress {
+                        file: rel.clone(),
+                        files_done: index,
+                        file_count,
+                        processed,
+                        total,
+                    },
+                    false,
+                );
+            }
+            snapshot.files.push(BackupFile {
+                path: rel.clone(),
+                size,
+                modified_ms: modified,
+                chunks,
+            });
+        }
+        reporter.report(
+            BackupProgress {
+                file: rel,
+                files_done: index + 1,
+                file_count,
+                processed,
+                total,
+            },
+            index + 1 == file_count,
+        );
+        if last_checkpoint.elapsed() >= CHECKPOINT_INTERVAL {
+            write_manifest(&partial, &snapshot)?;
+            last_checkpoint = Instant::now();
+        }
+    }
+
+    report.files = snapshot.files.len();
+    report.total_bytes = snapshot.total_bytes();
+    if cancel.load(Ordering::SeqCst) {
+        write_manifest(&partial, &snapshot)?;
+        return Ok(report);
+    }
+    snapshot.completed_at = Some(Utc::now().to_rfc3339());
+    write_manifest(&snapshot_path(repo, &snapshot.id), &snapshot)?;
+    let _ = fs::remove_file(&partial);
+    report.completed = true;
+    Ok(report)
+}
+
+/// Backs up every file under `source` into the chunk store at `repo`. Content is split into
+/// fixed-size chunks named by their SHA-256, so data shared between snapshots is stored once.
+/// A cancelled or interrupted run leaves a partial snapshot that the next run picks up.
+pub fn create_backup(
+    repo: &Path,
+    source: &Path,
+    cancel: &AtomicBool,
+    on_progress: impl FnMut(BackupProgress),
+) -> Result<BackupReport, String> {
+    let _guard = REPO_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    create_locked(repo, source, cancel, on_progress)
+}
+
+/// Newest first, including an interrupted backup that has not been resumed yet.
+pub fn list_backups(repo: &Path) -> Result<Vec<BackupSummary>, String> {
+    let mut summaries: Vec<BackupSummary> = read_manifests(repo)?
+        .into_iter()
+        .map(|s| BackupSummary {
+            total_bytes: s.total_bytes(),
+            file_count: s.files.len(),
+            id: s.id,
+            created_at: s.created_at,
+            completed_at: s.completed_at,
+        })
+        .collect();
+    summaries.reverse();
+    Ok(summaries)
+}
+
+/// Rebuilds the finished snapshot `id` under `dest`, overwriting the files it lists. Files in
+/// `dest` that the snapshot does not know about are left alone. Every chunk is checked against
+/// its hash, and each file only replaces the existing one once it has been fully rebuilt.
+pub fn restore_backup(repo: &Path, id: &str, dest: &Path) -> Result<RestoreReport, String> {
+    let path = snapshot_path(repo, id);
+    if !path.exists() {
+        return Err(format!("Backup {} not found", id));
+    }
+    let snapshot = read_manifest(&path)?;
+    if let Some(chunk) = snapshot
+        .files
+        .iter()
+        .flat_map(|f| f.chunks.iter())
+        .find(|c| !chunk_path(repo, c).exists())
+    {
+        return Err(format!("Backup {} is missing chunk {}", id, chunk));
+    }
+
+    let mut report = RestoreReport {
+        snapshot_id: snapshot.id.clone(),
+        files: 0,
+        bytes: 0,
+    };
+    for file in &snapshot.files {
+        let target = restore_target(dest, &file.path)?;
+        let mut data = Vec::with_capacity(file.size as usize);
+        for hash in &file.chunks {
+            let chunk = fs::read(chunk_path(repo, hash)).map_err(|e| format!("Failed to read chunk {}: {}", hash, e))?;
+            let mut hasher = Sha256::new();
+            hasher.update(&chunk);
+            if hex_digest(hasher) != *hash {
+                return Err(format!("Chunk {} of {} is corrupted", hash, file.path));
+            }
+            data.extend_from_slice(&chunk);
+        }
+        if data.len() as u64 != file.size {
+            return Err(format!("Restored size of {} does not match the backup", file.path));
+        }
+        write_atomic(&target, &data)?;
+        if let Ok(handle) = File::options().write(true).open(&target) {
+            let _ = handle.set_modified(UNIX_EPOCH + Duration::from_millis(file.modified_ms));
+        }
+        report.files += 1;
+        report.bytes += file.size;
+    }
+    Ok(report)
+}
+
+/// Keeps the newest `keep_last` finished snapshots and any interrupted one, then deletes the
+/// chunks none of them reference. Manifests go first, so an interruption can only leave
+/// unreferenced chunks behind, never a retained snapshot with a chunk missing.
+pub fn prune_backups(repo: &Path, keep_last: usize) -> Result<PruneReport, String> {
+    let _guard = REPO_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let manifests = read_manifests(repo)?;
+    let finished = manifests.iter().filter(|s| s.completed_at.is_some()).count();
+    let drop_count = finished.saturating_sub(keep_last);
+    let mut report = PruneReport::default();
+    let mut retained = Vec::new();
+    let mut dropped = 0;
+    for snapshot in manifests {
+        if snapshot.completed_at.is_some() && dropped < drop_count {
+            let path = manifest_path_of(repo, &snapshot);
+            fs::remove_file(&path).map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+            report.removed_snapshots.push(snapshot.id);
+            dropped += 1;
+        } else {
+            retained.push(snapshot);
+        }
+    }
+
+    let referenced: BTreeSet<&str> = retained
+        .iter()
+        .flat_map(|s| s.files.iter())
+        .flat_map(|f| f.chunks.iter().map(String::as_str))
+        .collect();
+    let chunks_dir = repo.join(CHUNKS_DIR_NAME);
+    if !chunks_dir.exists() {
+        return Ok(report);
+    }
+    for prefix in fs::read_dir(&chunks_dir).map_err(|e| format!("Failed to read {}: {}", chunks_dir.display(), e))? {
+        let prefix = prefix.map_err(|e| e.to_string())?.path();
+        if !prefix.is_dir() {
+            continue;
+        }
+        for entry in fs::read_dir(&prefix).map_err(|e| format!("Failed to read {}: {}", prefix.display(), e))? {
+            let path = entry.map_err(|e| e.to_string())?.path();
+            let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            if referenced.contains(name.as_str()) {
+                continue;
+            }
+            let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            if fs::remove_file(&path).is_ok() {
+                report.removed_chunks += 1;
+                report.freed_bytes += size;
+            }
+        }
+        let _ = fs::remove_dir(&prefix);
+    }
+    Ok(report)
+}
+
+/// Chunk hash -> location of every chunk a retained snapshot depends on, for the scrubber.
+pub fn referenced_chunks(repo: &Path) -> Result<BTreeMap<String, PathBuf>, String> {
+    let mut chunks = BTreeMap::new();
+    for snapshot in read_manifests(repo)? {
+        for hash in snapshot.files.iter().flat_map(|f| f.chunks.iter()) {
+            chunks.entry(hash.clone()).or_insert_with(|| chunk_path(repo, hash));
+        }
+    }
+    Ok(chunks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dedupes_resumes_and_prunes_without_breaking_retained_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("appdata");
+        let repo = dir.path().join("backups");
+        fs::create_dir_all(source.join("SDEZ")).unwrap();
+        fs::write(source.join("SDEZ").join("sysfile.dat"), vec![1u8; CHUNK_SIZE + 5]).unwrap();
+        fs::write(source.join("option.bin"), b"v1").unwrap();
+        let never = AtomicBool::new(false);
+
+        // A cancelled first run is picked up by the next one under the same id.
+        let cancelled = create_backup(&repo, &source, &AtomicBool::new(true), |_| {}).unwrap();
+        assert!(!cancelled.completed);
+        let first = create_backup(&repo, &source, &never, |_| {}).unwrap();
+        assert!(first.completed);
+        assert_eq!(first.snapshot_id, cancelled.snapshot_id);
+        assert!(!partial_path(&repo, &first.snapshot_id).exists());
+
+        std::thread::sleep(Duration::from_millis(1100));
+        fs::write(source.join("option.bin"), b"v2").unwrap();
+        let second = create_backup(&repo, &source, &never, |_| {}).unwrap();
+        assert_eq!(second.unchanged_files, 1);
+        assert_eq!(second.new_chunks, 1);
+        assert_eq!(list_backups(&repo).unwrap()[0].id, second.snapshot_id);
+
+        let pruned = prune_backups(&repo, 1).unwrap();
+        assert_eq!(pruned.removed_snapshots, vec![first.snapshot_id.clone()]);
+        assert_eq!(pruned.removed_chunks, 1);
+
+        let restored = dir.path().join("restored");
+        restore_backup(&repo, &second.snapshot_id, &restored).unwrap();
+        assert_eq!(fs::read(restored.join("option.bin")).unwrap(), b"v2");
+        assert_eq!(
+            fs::read(restored.join("SDEZ").join("sysfile.dat")).unwrap(),
+            fs::read(source.join("SDEZ").join("sysfile.dat")).unwrap()
+        );
+        assert!(restore_backup(&repo, &first.snapshot_id, &restored).is_err());
+    }
+}
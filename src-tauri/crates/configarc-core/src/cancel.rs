@@ -0,0 +1,124 @@
+//! Cancellation shared by long-running work: a token the caller keeps to stop an operation,
+//! and a guard that removes half-written files when the operation does not finish.
+
+use std::fs;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Message long-running calls fail with once their token is cancelled.
+pub const CANCELLED_MESSAGE: &str = "Operation cancelled";
+
+/// A cancellation flag shared between the code running an operation and the code that may
+/// stop it. Clones observe the same flag. It dereferences to the `AtomicBool` that the
+/// backup, scrub, warm-up and decrypt functions take.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// `Err(CANCELLED_MESSAGE)` once cancelled, for use with `?` between steps.
+    pub fn check(&self) -> Result<(), String> {
+        if self.is_cancelled() {
+            Err(CANCELLED_MESSAGE.to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Deref for CancellationToken {
+    type Target = AtomicBool;
+
+    fn deref(&self) -> &AtomicBool {
+        &self.0
+    }
+}
+
+/// Files and folders an operation is still writing. Unless [`PartialArtifacts::keep`] is
+/// called, they are deleted when the guard drops, so an operation that is cancelled, fails
+/// or panics leaves nothing half-written behind.
+#[derive(Debug, Default)]
+pub struct PartialArtifacts {
+    paths: Vec<PathBuf>,
+}
+
+impl PartialArtifacts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Deletes `path` on drop. Only track paths the operation created itself.
+    pub fn track(&mut self, path: impl Into<PathBuf>) {
+        self.paths.push(path.into());
+    }
+
+    /// Stops tracking `path`, e.g. once it was renamed into place.
+    pub fn release(&mut self, path: &Path) {
+        self.paths.retain(|p| p != path);
+    }
+
+    /// The operation finished; keep everything.
+    pub fn keep(mut self) {
+        self.paths.clear();
+    }
+}
+
+impl Drop for PartialArtifacts {
+    fn drop(&mut self) {
+        for path in self.paths.iter().rev() {
+            let _ = if path.is_dir() {
+                fs::remove_dir_all(path)
+            } else {
+                fs::remove_file(path)
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn clones_share_the_flag_and_guards_clean_up() {
+        let token = CancellationToken::new();
+        let observer = token.clone();
+        assert!(token.check().is_ok());
+        observer.cancel();
+        assert!(token.load(Ordering::SeqCst));
+        assert_eq!(token.check().unwrap_err(), CANCELLED_MESSAGE);
+
+        let tmp = TempDir::new().unwrap();
+        let partial = tmp.path().join("out.part");
+        let dir = tmp.path().join("extracted");
+        let done = tmp.path().join("done.bin");
+        {
+            let mut artifacts = PartialArtifacts::new();
+            fs::write(&partial, b"half").unwrap();
+            fs::create_dir_all(dir.join("nested")).unwrap();
+            artifacts.track(&partial);
+            artifacts.track(&dir);
+        }
+        assert!(!partial.exists() && !dir.exists());
+
+        let mut artifacts = PartialArtifacts::new();
+        fs::write(&done, b"all").unwrap();
+        artifacts.track(&done);
+        artifacts.keep();
+        assert!(done.exists());
+    }
+}
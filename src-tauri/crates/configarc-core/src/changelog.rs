@@ -0,0 +1,160 @@
+use serde::Serialize;
+
+/// Language the default `CHANGELOG.md` is written in.
+pub const DEFAULT_CHANGELOG_LOCALE: &str = "en";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangelogSectionKind {
+    Features,
+    Fixes,
+    Breaking,
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangelogSection {
+    pub kind: ChangelogSectionKind,
+    /// Heading as written, so localized changelogs keep their own wording.
+    pub title: String,
+    pub entries: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangelogRelease {
+    pub version: String,
+    pub date: Option<String>,
+    pub sections: Vec<ChangelogSection>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Changelog {
+    /// Locale of the document that was served; `None` when the default changelog stands in
+    /// for a locale without one of its own.
+    pub locale: Option<String>,
+    /// The requested locale had no changelog of its own.
+    pub fallback: bool,
+    pub releases: Vec<ChangelogRelease>,
+}
+
+/// Locales to try for `requested`, most specific first: "zh-CN" gives `["zh-CN", "zh"]`.
+pub fn locale_candidates(requested: &str) -> Vec<String> {
+    let requested = requested.trim().replace('_', "-");
+    if requested.is_empty() || requested.eq_ignore_ascii_case("system") {
+        return vec![];
+    }
+    let mut candidates = vec![requested.clone()];
+    if let Some((language, _)) = requested.split_once('-') {
+        candidates.push(language.to_string());
+    }
+    candidates
+}
+
+/// `CHANGELOG.md` becomes `CHANGELOG.ja.md` for locale "ja".
+pub fn localized_file_name(base: &str, locale: &str) -> String {
+    match base.rsplit_once('.') {
+        Some((stem, ext)) => format!("{}.{}.{}", stem, locale, ext),
+        None => format!("{}.{}", base, locale),
+    }
+}
+
+fn section_kind(title: &str) -> ChangelogSectionKind {
+    let lower = title.to_lowercase();
+    let has = |words: &[&str]| words.iter().any(|w| lower.contains(w));
+    if has(&["breaking", "破壊的", "破坏性", "不兼容"]) {
+        ChangelogSectionKind::Breaking
+    } else if has(&["feature", "added", "新機能", "新功能"]) {
+        ChangelogSectionKind::Features
+    } else if has(&["fix", "修正", "修复", "修復"]) {
+        ChangelogSectionKind::Fixes
+    } else {
+        ChangelogSectionKind::Other
+    }
+}
+
+fn parse_release_heading(heading: &str) -> (String, Option<String>) {
+    let (version, date) = match heading.split_once(" - ") {
+        Some((version, date)) => (version, Some(date.trim().to_string())),
+        None => (heading, None),
+    };
+    let version = version.trim().trim_start_matches('[').trim_end_matches(']');
+    (version.trim_start_matches('v').to_string(), date.filter(|d| !d.is_empty()))
+}
+
+/// Parses a Keep a Changelog style document into releases (`## [x.y.z] - date`), sections
+/// (`### Features`) and bullet entries. Indented lines continue the previous entry.
+pub fn parse_changelog(content: &str) -> Vec<ChangelogRelease> {
+    let mut releases: Vec<ChangelogRelease> = Vec::new();
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if let Some(heading) = trimmed.strip_prefix("### ") {
+            if let Some(release) = releases.last_mut() {
+                release.sections.push(ChangelogSection {
+                    kind: section_kind(heading),
+                    title: heading.trim().to_string(),
+                    entries: Vec::new(),
+                });
+            }
+            continue;
+        }
+        if let Some(heading) = trimmed.strip_prefix("## ") {
+            let (version, date) = parse_release_heading(heading);
+            releases.push(ChangelogRelease {
+                version,
+                date,
+                sections: Vec::new(),
+            });
+            continue;
+        }
+        let Some(release) = releases.last_mut() else {
+            continue;
+        };
+        if trimmed.is_empty() {
+            continue;
+        }
+        let entry = trimmed.strip_prefix("- ").or_else(|| trimmed.strip_prefix("* "));
+        if release.sections.is_empty() {
+            release.sections.push(ChangelogSection {
+                kind: ChangelogSectionKind::Other,
+                title: String::new(),
+                entries: Vec::new(),
+            });
+        }
+        let section = release.sections.last_mut().expect("section pushed above");
+        match (entry, section.entries.last_mut()) {
+            (Some(entry), _) => section.entries.push(entry.trim().to_string()),
+            (None, Some(last)) if line.starts_with(char::is_whitespace) => {
+                last.push(' ');
+                last.push_str(trimmed);
+            }
+            (None, _) => section.entries.push(trimmed.to_string()),
+        }
+    }
+    releases
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_releases_sections_and_continuations() {
+        let releases = parse_changelog(
+            "# Changelog\n\n## [0.5.1] - 2026-01-02\n\n### Features\n- Add a button\n  that wraps.\n\n### 修正\n- 修正\n\n## v0.5.0\n- Loose entry\n",
+        );
+        assert_eq!(releases.len(), 2);
+        assert_eq!(releases[0].version, "0.5.1");
+        assert_eq!(releases[0].date.as_deref(), Some("2026-01-02"));
+        assert_eq!(releases[0].sections[0].kind, ChangelogSectionKind::Features);
+        assert_eq!(releases[0].sections[0].entries, vec!["Add a button that wraps.".to_string()]);
+        assert_eq!(releases[0].sections[1].kind, ChangelogSectionKind::Fixes);
+        assert_eq!(releases[1].version, "0.5.0");
+        assert_eq!(releases[1].sections[0].kind, ChangelogSectionKind::Other);
+
+        assert_eq!(locale_candidates("zh_CN"), vec!["zh-CN".to_string(), "zh".to_string()]);
+        assert_eq!(localized_file_name("CHANGELOG.md", "ja"), "CHANGELOG.ja.md");
+    }
+}
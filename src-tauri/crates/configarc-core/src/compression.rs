@@ -0,0 +1,142 @@
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+#[cfg(target_os = "windows")]
+use std::os::windows::ffi::OsStrExt;
+#[cfg(target_os = "windows")]
+use std::os::windows::fs::MetadataExt;
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+#[cfg(target_os = "windows")]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+#[cfg(target_os = "windows")]
+const FILE_ATTRIBUTE_COMPRESSED: u32 = 0x0000_0800;
+#[cfg(target_os = "windows")]
+const INVALID_FILE_SIZE: u32 = 0xFFFF_FFFF;
+
+/// Share of the logical size NTFS (LZNT1) compression is expected to free for data that is
+/// already compressed (audio banks, movies, images, archives) and for everything else.
+const PACKED_SAVINGS_RATIO: f64 = 0.02;
+const DEFAULT_SAVINGS_RATIO: f64 = 0.35;
+const PACKED_EXTENSIONS: &[&str] = &[
+    "acb", "awb", "usm", "mp4", "wmv", "png", "jpg", "jpeg", "ogg", "mp3", "zip", "7z", "dds", "ab", "unity3d",
+];
+
+#[cfg(target_os = "windows")]
+#[link(name = "kernel32")]
+extern "system" {
+    fn GetCompressedFileSizeW(file_name: *const u16, file_size_high: *mut u32) -> u32;
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FolderCompression {
+    /// The folder itself carries the NTFS compressed attribute, so new files are compressed too.
+    pub compressed: bool,
+    pub total_files: usize,
+    pub compressed_files: usize,
+    pub logical_size: u64,
+    pub on_disk_size: u64,
+    /// Bytes saved by compression today, or expected to be saved once compressed.
+    pub estimated_savings: u64,
+}
+
+#[cfg(target_os = "windows")]
+fn is_compressed(meta: &fs::Metadata) -> bool {
+    meta.file_attributes() & FILE_ATTRIBUTE_COMPRESSED != 0
+}
+
+#[cfg(not(target_os = "windows"))]
+fn is_compressed(_meta: &fs::Metadata) -> bool {
+    false
+}
+
+#[cfg(target_os = "windows")]
+fn on_disk_size(path: &Path, logical: u64) -> u64 {
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+    let mut high: u32 = 0;
+    let low = unsafe { GetCompressedFileSizeW(wide.as_ptr(), &mut high) };
+    if low == INVALID_FILE_SIZE && high == 0 {
+        return logical;
+    }
+    ((high as u64) << 32) | low as u64
+}
+
+#[cfg(not(target_os = "windows"))]
+fn on_disk_size(_path: &Path, logical: u64) -> u64 {
+    logical
+}
+
+fn savings_ratio(path: &Path) -> f64 {
+    let packed = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|ext| PACKED_EXTENSIONS.iter().any(|p| p.eq_ignore_ascii_case(ext)))
+        .unwrap_or(false);
+    if packed {
+        PACKED_SAVINGS_RATIO
+    } else {
+        DEFAULT_SAVINGS_RATIO
+    }
+}
+
+fn walk(dir: &Path, status: &mut FolderCompression, estimate: &mut f64) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        if meta.is_dir() {
+            walk(&path, status, estimate);
+            continue;
+        }
+        let logical = meta.len();
+        status.total_files += 1;
+        status.logical_size += logical;
+        if is_compressed(&meta) {
+            status.compressed_files += 1;
+            status.on_disk_size += on_disk_size(&path, logical);
+        } else {
+            status.on_disk_size += logical;
+            *estimate += logical as f64 * savings_ratio(&path);
+        }
+    }
+}
+
+/// Reports the NTFS compression state of a folder tree with actual and estimated savings.
+pub fn folder_compression(dir: &Path) -> FolderCompression {
+    let mut status = FolderCompression {
+        compressed: fs::metadata(dir).map(|m| is_compressed(&m)).unwrap_or(false),
+        ..FolderCompression::default()
+    };
+    let mut estimate = 0.0;
+    walk(dir, &mut status, &mut estimate);
+    let actual = status.logical_size.saturating_sub(status.on_disk_size);
+    status.estimated_savings = actual + estimate as u64;
+    status
+}
+
+/// Toggles NTFS compression for a folder tree through `compact.exe`. Games read compressed
+/// files transparently, so this only trades CPU time on load for disk space.
+pub fn set_folder_compression(dir: &Path, enable: bool) -> Result<FolderCompression, String> {
+    if !dir.is_dir() {
+        return Err(format!("Folder not found: {}", dir.display()));
+    }
+    let mut cmd = Command::new("compact");
+    cmd.arg(if enable { "/c" } else { "/u" })
+        .arg(format!("/s:{}", dir.display()))
+        .args(["/i", "/q"]);
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(CREATE_NO_WINDOW);
+    let output = cmd.output().map_err(|e| format!("Failed to run compact.exe: {}", e))?;
+    if !output.status.success() {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("compact.exe failed: {} {}", stdout.trim(), stderr.trim()));
+    }
+    Ok(folder_compression(dir))
+}
@@ -0,0 +1,77 @@
+use std::ops::RangeInclusive;
+
+/// Digits in an access code, as segatools reads it from `aime.txt`.
+pub const ACCESS_CODE_LEN: usize = 20;
+/// Leading digits of access codes the launcher accepts and generates. Codes starting with 0
+/// are reserved and rejected.
+pub const ACCESS_CODE_PREFIXES: RangeInclusive<u8> = 1..=9;
+
+/// The Luhn check digit for `digits`, the access code without its last digit.
+fn check_digit(digits: &[u8]) -> u8 {
+  let sum: u32 = digits
+    .iter()
+    .rev()
+    .enumerate()
+    .map(|(i, &d)| {
+      let d = d as u32;
+      if i % 2 == 0 {
+        let doubled = d * 2;
+        if doubled > 9 {
+          doubled - 9
+        } else {
+          doubled
+        }
+      } else {
+        d
+      }
+    })
+    .sum();
+  ((10 - sum % 10) % 10) as u8
+}
+
+/// `raw` without spaces or dashes, if it is a 20-digit access code in the accepted prefix
+/// range whose last digit checks out.
+pub fn validate_access_code(raw: &str) -> Result<String, String> {
+  let cleaned: String = raw.chars().filter(|c| !c.is_whitespace() && *c != '-').collect();
+  if cleaned.len() != ACCESS_CODE_LEN || !cleaned.chars().all(|c| c.is_ascii_digit()) {
+    return Err(format!("Aime number must be exactly {} digits", ACCESS_CODE_LEN));
+  }
+  let digits: Vec<u8> = cleaned.bytes().map(|b| b - b'0').collect();
+  if !ACCESS_CODE_PREFIXES.contains(&digits[0]) {
+    return Err(format!("Aime number cannot start with {}", digits[0]));
+  }
+  let (body, last) = digits.split_at(ACCESS_CODE_LEN - 1);
+  if check_digit(body) != last[0] {
+    return Err("Aime number has a wrong check digit; check for a mistyped digit".to_string());
+  }
+  Ok(cleaned)
+}
+
+/// A random access code that passes [`validate_access_code`].
+pub fn generate_access_code() -> Result<String, String> {
+  let mut random = [0u8; ACCESS_CODE_LEN - 1];
+  getrandom::getrandom(&mut random).map_err(|e| e.to_string())?;
+  let prefixes = ACCESS_CODE_PREFIXES.end() - ACCESS_CODE_PREFIXES.start() + 1;
+  // Modulo bias is irrelevant here; these only need to be distinct, not secret.
+  let mut digits: Vec<u8> = random.iter().map(|b| b % 10).collect();
+  digits[0] = ACCESS_CODE_PREFIXES.start() + random[0] % prefixes;
+  digits.push(check_digit(&digits));
+  Ok(digits.iter().map(|d| char::from(b'0' + d)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn generated_codes_validate() {
+    for _ in 0..50 {
+      let code = generate_access_code().unwrap();
+      assert_eq!(validate_access_code(&code).unwrap(), code);
+    }
+    assert_eq!(validate_access_code("5000 0000 0000 0000 0009").unwrap(), "50000000000000000009");
+    assert!(validate_access_code("50000000000000000007").unwrap_err().contains("check digit"));
+    assert!(validate_access_code("00000000000000000000").unwrap_err().contains("start with 0"));
+    assert!(validate_access_code("1234").is_err());
+  }
+}
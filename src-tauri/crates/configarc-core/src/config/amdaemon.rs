@@ -0,0 +1,283 @@
+//! Typed views of the amdaemon JSON configs the launcher edits as forms: config_common.json,
+//! config_server.json and config_client.json. Only the network, ALL.Net auth and port settings
+//! are modelled; every other key is carried through untouched, so a save never drops settings
+//! the forms do not know about.
+
+use crate::error::ConfigError;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::net::Ipv4Addr;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NetworkProperty {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub dhcp: Option<bool>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub ip_address: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub subnet_mask: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub gateway: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub primary_dns: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub secondary_dns: Option<String>,
+  #[serde(flatten)]
+  pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NetworkConfig {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub property: Option<NetworkProperty>,
+  #[serde(flatten)]
+  pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AllnetAuthConfig {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub enable: Option<bool>,
+  /// Auth protocol version, e.g. `1.0`.
+  #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+  pub auth_type: Option<String>,
+  #[serde(flatten)]
+  pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AimeUnit {
+  /// COM port number of the card reader; range-checked by [`validate_json_config`].
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub port: Option<u64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub id: Option<u64>,
+  #[serde(flatten)]
+  pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AimeConfig {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub enable: Option<bool>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub unit: Option<Vec<AimeUnit>>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub high_baudrate: Option<bool>,
+  #[serde(flatten)]
+  pub extra: Map<String, Value>,
+}
+
+/// config_common.json.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CommonConfig {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub network: Option<NetworkConfig>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub allnet_auth: Option<AllnetAuthConfig>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub aime: Option<AimeConfig>,
+  #[serde(flatten)]
+  pub extra: Map<String, Value>,
+}
+
+/// config_server.json and config_client.json: the network settings of a linked cabinet,
+/// plus ports checked wherever they appear.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LinkConfig {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub network: Option<NetworkConfig>,
+  #[serde(flatten)]
+  pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "config", rename_all = "snake_case")]
+pub enum AmdaemonConfig {
+  Common(CommonConfig),
+  Server(LinkConfig),
+  Client(LinkConfig),
+}
+
+/// One problem in an edited config, at a dotted path such as `network.property.gateway`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JsonConfigIssue {
+  pub path: String,
+  pub message: String,
+}
+
+fn issue(path: &str, message: impl Into<String>) -> JsonConfigIssue {
+  JsonConfigIssue {
+    path: path.to_string(),
+    message: message.into(),
+  }
+}
+
+/// Kind of a config file with a typed model: `common`, `server` or `client`.
+pub fn typed_kind(name: &str) -> Option<&'static str> {
+  match name.trim().to_ascii_lowercase().as_str() {
+    "config_common.json" => Some("common"),
+    "config_server.json" => Some("server"),
+    "config_client.json" => Some("client"),
+    _ => None,
+  }
+}
+
+fn from_value<T: serde::de::DeserializeOwned>(name: &str, value: &Value) -> Result<T, ConfigError> {
+  serde_json::from_value(value.clone()).map_err(|e| ConfigError::Parse(format!("{}: {}", name.trim(), e)))
+}
+
+impl AmdaemonConfig {
+  pub fn kind(&self) -> &'static str {
+    match self {
+      AmdaemonConfig::Common(_) => "common",
+      AmdaemonConfig::Server(_) => "server",
+      AmdaemonConfig::Client(_) => "client",
+    }
+  }
+
+  /// Typed view of the file `name`; `None` for files without a typed model.
+  pub fn parse(name: &str, value: &Value) -> Option<Result<Self, ConfigError>> {
+    Some(match typed_kind(name)? {
+      "common" => from_value(name, value).map(AmdaemonConfig::Common),
+      "server" => from_value(name, value).map(AmdaemonConfig::Server),
+      _ => from_value(name, value).map(AmdaemonConfig::Client),
+    })
+  }
+
+  pub fn to_value(&self) -> Result<Value, ConfigError> {
+    Ok(match self {
+      AmdaemonConfig::Common(config) => serde_json::to_value(config)?,
+      AmdaemonConfig::Server(config) | AmdaemonConfig::Client(config) => serde_json::to_value(config)?,
+    })
+  }
+
+  fn network(&self) -> Option<&NetworkConfig> {
+    match self {
+      AmdaemonConfig::Common(config) => config.network.as_ref(),
+      AmdaemonConfig::Server(config) | AmdaemonConfig::Client(config) => config.network.as_ref(),
+    }
+  }
+}
+
+fn parse_ipv4(path: &str, value: &Option<String>, issues: &mut Vec<JsonConfigIssue>) -> Option<Ipv4Addr> {
+  let raw = value.as_deref().map(str::trim).filter(|v| !v.is_empty())?;
+  match raw.parse::<Ipv4Addr>() {
+    Ok(addr) => Some(addr),
+    Err(_) => {
+      issues.push(issue(path, format!("{} is not an IPv4 address", raw)));
+      None
+    }
+  }
+}
+
+fn validate_network(property: &NetworkProperty, issues: &mut Vec<JsonConfigIssue>) {
+  const BASE: &str = "network.property";
+  let at = |field: &str| format!("{}.{}", BASE, field);
+  let ip = parse_ipv4(&at("ip_address"), &property.ip_address, issues);
+  let mask = parse_ipv4(&at("subnet_mask"), &property.subnet_mask, issues);
+  let gateway = parse_ipv4(&at("gateway"), &property.gateway, issues);
+  parse_ipv4(&at("primary_dns"), &property.primary_dns, issues);
+  parse_ipv4(&at("secondary_dns"), &property.secondary_dns, issues);
+
+  if property.dhcp != Some(true) {
+    for (field, value) in [("ip_address", &property.ip_address), ("subnet_mask", &property.subnet_mask)] {
+      if value.as_deref().is_none_or(|v| v.trim().is_empty()) {
+        issues.push(issue(&at(field), "Required unless DHCP is enabled"));
+      }
+    }
+  }
+  if let Some(mask) = mask {
+    let bits = u32::from(mask);
+    if bits.leading_ones() + bits.trailing_zeros() != 32 || bits == 0 {
+      issues.push(issue(&at("subnet_mask"), format!("{} is not a valid subnet mask", mask)));
+    } else if let (Some(ip), Some(gateway)) = (ip, gateway) {
+      if u32::from(ip) & bits != u32::from(gateway) & bits {
+        issues.push(issue(&at("gateway"), format!("{} is outside the subnet of {}", gateway, ip)));
+      }
+    }
+  }
+}
+
+/// Every `port` or `*_port` number anywhere in the document must be a TCP/UDP port.
+fn validate_ports(path: &str, value: &Value, issues: &mut Vec<JsonConfigIssue>) {
+  match value {
+    Value::Object(map) => {
+      for (key, child) in map {
+        let child_path = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+        let is_port = key == "port" || key.ends_with("_port");
+        if is_port && !child.as_u64().is_some_and(|port| (1..=65535).contains(&port)) {
+          issues.push(issue(&child_path, "Ports must be whole numbers from 1 to 65535"));
+        } else {
+          validate_ports(&child_path, child, issues);
+        }
+      }
+    }
+    Value::Array(items) => {
+      for (index, child) in items.iter().enumerate() {
+        validate_ports(&format!("{}[{}]", path, index), child, issues);
+      }
+    }
+    _ => {}
+  }
+}
+
+/// Problems that keep the edited `value` of `name` from being saved; empty when it is fine or
+/// the file has no typed model.
+pub fn validate_json_config(name: &str, value: &Value) -> Vec<JsonConfigIssue> {
+  let config = match AmdaemonConfig::parse(name, value) {
+    None => return Vec::new(),
+    Some(Err(e)) => return vec![issue("", e.to_string())],
+    Some(Ok(config)) => config,
+  };
+  let mut issues = Vec::new();
+  validate_ports("", value, &mut issues);
+  if let Some(property) = config.network().and_then(|network| network.property.as_ref()) {
+    validate_network(property, &mut issues);
+  }
+  if let AmdaemonConfig::Common(common) = &config {
+    if let Some(auth) = &common.allnet_auth {
+      if auth.auth_type.as_deref().is_some_and(|t| t.trim().is_empty()) {
+        issues.push(issue("allnet_auth.type", "ALL.Net auth type cannot be empty"));
+      }
+    }
+  }
+  issues
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  #[test]
+  fn keeps_unknown_keys_and_rejects_bad_network_settings() {
+    let raw = json!({
+      "network": { "property": { "dhcp": false, "ip_address": "192.168.1.10", "subnet_mask": "255.255.255.0", "gateway": "192.168.1.1" } },
+      "allnet_auth": { "enable": true, "type": "1.0" },
+      "aime": { "enable": true, "unit": [{ "port": 3, "id": 1 }] },
+      "credit": { "max_credit": 24 }
+    });
+    let Some(Ok(AmdaemonConfig::Common(mut common))) = AmdaemonConfig::parse("config_common.json", &raw) else {
+      panic!("config_common.json should parse");
+    };
+    assert!(validate_json_config("config_common.json", &raw).is_empty());
+    assert_eq!(AmdaemonConfig::Common(common.clone()).to_value().unwrap(), raw);
+
+    let property = common.network.as_mut().unwrap().property.as_mut().unwrap();
+    property.gateway = Some("10.0.0.1".to_string());
+    property.subnet_mask = Some("255.0.255.0".to_string());
+    let edited = AmdaemonConfig::Common(common).to_value().unwrap();
+    let paths: Vec<String> = validate_json_config("config_common.json", &edited)
+      .into_iter()
+      .map(|issue| issue.path)
+      .collect();
+    assert_eq!(paths, ["network.property.subnet_mask"]);
+
+    let bad = json!({ "network": { "property": { "dhcp": true } }, "matching": { "port": 70000 } });
+    let paths: Vec<String> = validate_json_config("config_server.json", &bad).into_iter().map(|i| i.path).collect();
+    assert_eq!(paths, ["matching.port"]);
+    assert_eq!(validate_json_config("config_client.json", &json!({ "network": "x" })).len(), 1);
+    assert!(validate_json_config("config_hook.json", &json!({ "port": "x" })).is_empty());
+  }
+}
@@ -0,0 +1,201 @@
+//! Reachability of the servers a segatools.ini points the game at. Each endpoint of the
+//! `[dns]` section is resolved and, where it has a port, connected to over TCP, so a game that
+//! hangs at startup can be traced to the server that is not answering.
+
+use super::segatools::DnsConfig;
+use serde::{Deserialize, Serialize};
+use std::io::ErrorKind;
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_STARTUP_PORT: u16 = 80;
+pub const DEFAULT_BILLING_PORT: u16 = 8443;
+pub const DEFAULT_AIMEDB_PORT: u16 = 22345;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProbeStatus {
+  /// A TCP connection was accepted.
+  Reachable,
+  /// The host resolved; the endpoint has no port to connect to.
+  Resolved,
+  NoHost,
+  DnsFailed,
+  Refused,
+  TimedOut,
+  Unreachable,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EndpointProbe {
+  /// `startup`, `title`, `billing` or `aimedb`.
+  pub service: String,
+  pub host: String,
+  pub port: Option<u16>,
+  pub addresses: Vec<String>,
+  pub status: ProbeStatus,
+  /// Time to resolve and connect, in milliseconds.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub latency_ms: Option<u64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub error: Option<String>,
+}
+
+fn host_or_default<'a>(host: &'a str, dns: &'a DnsConfig) -> &'a str {
+  match host.trim() {
+    "" => dns.default.trim(),
+    host => host,
+  }
+}
+
+fn port_or_default(port: u32, default: u16) -> u16 {
+  u16::try_from(port).ok().filter(|port| *port != 0).unwrap_or(default)
+}
+
+/// The endpoints a game talks to, with empty hosts falling back to `default` and zero ports to
+/// the stock ones. The title server's URL is handed out by the startup server, so it is probed
+/// on the startup port, which is where private servers serve both.
+pub fn dns_endpoints(dns: &DnsConfig) -> Vec<(&'static str, String, u16)> {
+  let startup_port = port_or_default(dns.startup_port, DEFAULT_STARTUP_PORT);
+  vec![
+    ("startup", host_or_default(&dns.startup, dns).to_string(), startup_port),
+    ("title", host_or_default(&dns.title, dns).to_string(), startup_port),
+    (
+      "billing",
+      host_or_default(&dns.billing, dns).to_string(),
+      port_or_default(dns.billing_port, DEFAULT_BILLING_PORT),
+    ),
+    (
+      "aimedb",
+      host_or_default(&dns.aimedb, dns).to_string(),
+      port_or_default(dns.aimedb_port, DEFAULT_AIMEDB_PORT),
+    ),
+  ]
+}
+
+/// Resolves `host` and connects to the first address that accepts within `timeout`.
+pub fn probe_endpoint(service: &str, host: &str, port: Option<u16>, timeout: Duration) -> EndpointProbe {
+  let mut probe = EndpointProbe {
+    service: service.to_string(),
+    host: host.to_string(),
+    port,
+    addresses: Vec::new(),
+    status: ProbeStatus::NoHost,
+    latency_ms: None,
+    error: None,
+  };
+  if host.is_empty() {
+    probe.error = Some("No host is configured".to_string());
+    return probe;
+  }
+
+  let started = Instant::now();
+  let addrs: Vec<SocketAddr> = match (host, port.unwrap_or(0)).to_socket_addrs() {
+    Ok(addrs) => addrs.collect(),
+    Err(e) => {
+      probe.status = ProbeStatus::DnsFailed;
+      probe.error = Some(e.to_string());
+      return probe;
+    }
+  };
+  probe.addresses = addrs.iter().map(|addr| addr.ip().to_string()).collect();
+  if addrs.is_empty() {
+    probe.status = ProbeStatus::DnsFailed;
+    probe.error = Some(format!("{} did not resolve to any address", host));
+    return probe;
+  }
+  if port.is_none() {
+    probe.status = ProbeStatus::Resolved;
+    probe.latency_ms = Some(started.elapsed().as_millis() as u64);
+    return probe;
+  }
+
+  for addr in &addrs {
+    match TcpStream::connect_timeout(addr, timeout) {
+      Ok(_) => {
+        probe.status = ProbeStatus::Reachable;
+        probe.latency_ms = Some(started.elapsed().as_millis() as u64);
+        probe.error = None;
+        return probe;
+      }
+      Err(e) => {
+        probe.status = match e.kind() {
+          ErrorKind::ConnectionRefused => ProbeStatus::Refused,
+          ErrorKind::TimedOut | ErrorKind::WouldBlock => ProbeStatus::TimedOut,
+          _ => ProbeStatus::Unreachable,
+        };
+        probe.error = Some(format!("{}: {}", addr, e));
+      }
+    }
+  }
+  probe
+}
+
+/// Probes every endpoint of `dns` in parallel, in the order of [`dns_endpoints`].
+pub fn probe_dns_endpoints(dns: &DnsConfig, timeout: Duration) -> Vec<EndpointProbe> {
+  let endpoints = dns_endpoints(dns);
+  thread::scope(|scope| {
+    let handles: Vec<_> = endpoints
+      .iter()
+      .map(|(service, host, port)| scope.spawn(move || probe_endpoint(service, host, Some(*port), timeout)))
+      .collect();
+    handles
+      .into_iter()
+      .zip(&endpoints)
+      .map(|(handle, (service, host, port))| {
+        handle.join().unwrap_or_else(|_| EndpointProbe {
+          service: service.to_string(),
+          host: host.clone(),
+          port: Some(*port),
+          addresses: Vec::new(),
+          status: ProbeStatus::Unreachable,
+          latency_ms: None,
+          error: Some("Probe failed".to_string()),
+        })
+      })
+      .collect()
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::net::TcpListener;
+
+  #[test]
+  fn probes_configured_endpoints_with_fallbacks() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let open = listener.local_addr().unwrap().port();
+    let closed = {
+      let spare = TcpListener::bind("127.0.0.1:0").unwrap();
+      spare.local_addr().unwrap().port()
+    };
+    let dns = DnsConfig {
+      default: "127.0.0.1".to_string(),
+      billing: "invalid.invalid".to_string(),
+      startup_port: open as u32,
+      aimedb_port: closed as u32,
+      ..DnsConfig::default()
+    };
+
+    let endpoints = dns_endpoints(&dns);
+    assert_eq!(endpoints[2], ("billing", "invalid.invalid".to_string(), DEFAULT_BILLING_PORT));
+
+    let probes = probe_dns_endpoints(&dns, Duration::from_secs(2));
+    let status: Vec<(&str, ProbeStatus)> = probes.iter().map(|p| (p.service.as_str(), p.status)).collect();
+    assert_eq!(
+      status,
+      [
+        ("startup", ProbeStatus::Reachable),
+        ("title", ProbeStatus::Reachable),
+        ("billing", ProbeStatus::DnsFailed),
+        ("aimedb", ProbeStatus::Refused),
+      ]
+    );
+    assert!(probes[0].latency_ms.is_some());
+    assert_eq!(probes[0].addresses, ["127.0.0.1"]);
+    assert_eq!(probe_endpoint("router", "", None, Duration::from_secs(1)).status, ProbeStatus::NoHost);
+  }
+}
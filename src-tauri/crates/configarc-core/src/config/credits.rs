@@ -0,0 +1,138 @@
+use super::segatools::SegatoolsConfig;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CreditMode {
+  /// Start games without credits; the coin slot is disabled.
+  Freeplay,
+  /// Require coins, inserted through the IO board coin key.
+  Coin,
+}
+
+/// Default coin key used by the bundled templates (F3).
+const DEFAULT_COIN_KEY: u32 = 0x72;
+
+/// Section a title reads its DIP switches from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SwitchBlock {
+  /// ALLS titles: `[system]` dipsw1-3.
+  System,
+  /// Nu titles (AMEX board): `[gpio]` dipsw1-8.
+  Gpio,
+}
+
+/// Where a title takes the coin key from; `None` when its own IO DLL handles coins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CoinInput {
+  Io3,
+  Io4,
+  None,
+}
+
+/// Switch and coin layout of a title, keyed by canonical game name.
+fn credit_layout(game_key: &str) -> (SwitchBlock, CoinInput) {
+  match game_key {
+    "chunithm" => (SwitchBlock::System, CoinInput::Io3),
+    "diva" => (SwitchBlock::Gpio, CoinInput::None),
+    _ => (SwitchBlock::System, CoinInput::Io4),
+  }
+}
+
+fn mark_key_written(cfg: &mut SegatoolsConfig, section: &str, key: &str) {
+  let section_lower = section.to_lowercase();
+  if !cfg.present_sections.is_empty() && !cfg.present_sections.contains(&section_lower) {
+    cfg.present_sections.push(section_lower.clone());
+  }
+  let full_key = format!("{}.{}", section_lower, key.to_lowercase());
+  if !cfg.present_keys.is_empty() && !cfg.present_keys.contains(&full_key) {
+    cfg.present_keys.push(full_key);
+  }
+  let commented = format!("{}.{}", section, key);
+  cfg.commented_keys.retain(|k| k != &commented);
+}
+
+/// Maps a credit mode onto the switches and coin key of a title.
+///
+/// `game_key` is the canonical game name ("chunithm", "sinmai", "diva", ...).
+/// `dipsw1` is the LAN install switch on every supported title and must stay on for a
+/// standalone machine, otherwise the game waits for a server that never shows up. ALLS titles
+/// read it from `[system]`, Nu titles from the AMEX `[gpio]` block. Freeplay is the `[system]`
+/// switch on both; the coins a credit costs are set in the game's test menu.
+pub fn apply_credit_mode(cfg: &mut SegatoolsConfig, game_key: &str, mode: CreditMode) {
+  let (switches, coin) = credit_layout(game_key);
+  cfg.system.enable = true;
+  mark_key_written(cfg, "system", "enable");
+  match switches {
+    SwitchBlock::System => {
+      cfg.system.dipsw1 = true;
+      mark_key_written(cfg, "system", "dipsw1");
+    }
+    SwitchBlock::Gpio => {
+      cfg.gpio.enable = true;
+      cfg.gpio.dipsw1 = true;
+      mark_key_written(cfg, "gpio", "enable");
+      mark_key_written(cfg, "gpio", "dipsw1");
+    }
+  }
+
+  cfg.system.freeplay = mode == CreditMode::Freeplay;
+  mark_key_written(cfg, "system", "freeplay");
+  if mode == CreditMode::Freeplay {
+    return;
+  }
+  match coin {
+    CoinInput::Io3 => {
+      if cfg.io3.coin == 0 {
+        cfg.io3.coin = DEFAULT_COIN_KEY;
+      }
+      mark_key_written(cfg, "io3", "coin");
+    }
+    CoinInput::Io4 => {
+      cfg.io4.enable = true;
+      if cfg.io4.coin == 0 {
+        cfg.io4.coin = DEFAULT_COIN_KEY;
+      }
+      mark_key_written(cfg, "io4", "coin");
+    }
+    CoinInput::None => {}
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::config::{load_segatoools_config_from_string, render_segatoools_config};
+
+  #[test]
+  fn freeplay_enables_system_flags() {
+    let mut cfg = load_segatoools_config_from_string("[system]\nenable=0\nfreeplay=0\n").unwrap();
+    apply_credit_mode(&mut cfg, "sinmai", CreditMode::Freeplay);
+    assert!(cfg.system.enable);
+    assert!(cfg.system.freeplay);
+    assert!(cfg.present_keys.contains(&"system.dipsw1".to_string()));
+    assert!(!cfg.present_sections.contains(&"gpio".to_string()));
+  }
+
+  #[test]
+  fn coin_mode_binds_coin_key_for_title() {
+    let mut cfg = load_segatoools_config_from_string("[system]\nfreeplay=1\n[io3]\ncoin=0\n").unwrap();
+    apply_credit_mode(&mut cfg, "chunithm", CreditMode::Coin);
+    assert!(!cfg.system.freeplay);
+    assert_eq!(cfg.io3.coin, DEFAULT_COIN_KEY);
+    assert!(cfg.present_sections.contains(&"io3".to_string()));
+  }
+
+  #[test]
+  fn nu_titles_set_the_lan_switch_in_gpio() {
+    let original = "[system]\nenable=1\nfreeplay=0\n[gpio]\nenable=0\ndipsw1=0\n";
+    let mut cfg = load_segatoools_config_from_string(original).unwrap();
+    apply_credit_mode(&mut cfg, "diva", CreditMode::Freeplay);
+    let rendered = render_segatoools_config(&cfg, Some(original)).unwrap();
+    let reloaded = load_segatoools_config_from_string(&rendered).unwrap();
+    assert!(reloaded.gpio.enable);
+    assert!(reloaded.gpio.dipsw1);
+    assert!(reloaded.system.freeplay);
+    assert!(!rendered.to_lowercase().contains("coinspercredit"));
+  }
+}
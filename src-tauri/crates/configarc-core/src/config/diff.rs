@@ -0,0 +1,102 @@
+use super::{perform_save, ConfigWriter, SegatoolsConfig};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigChange {
+  pub section: String,
+  /// Key as written to segatools.ini, e.g. "portNo".
+  pub key: String,
+  /// `None` means the key is absent or commented out.
+  pub old: Option<String>,
+  pub new: Option<String>,
+}
+
+/// Captures what `perform_save` would write, in ini order, so the diff uses the same
+/// skip rules and key names as a real save.
+#[derive(Default)]
+pub(super) struct IniCollector {
+  pub(super) order: Vec<(String, String)>,
+  pub(super) values: HashMap<(String, String), Option<String>>,
+}
+
+impl IniCollector {
+  fn record(&mut self, section: &str, key: &str, value: Option<String>) {
+    let id = (section.to_string(), key.to_string());
+    if !self.values.contains_key(&id) {
+      self.order.push(id.clone());
+    }
+    self.values.insert(id, value);
+  }
+}
+
+impl ConfigWriter for IniCollector {
+  fn write_val(&mut self, section: &str, key: &str, value: &str) {
+    self.record(section, key, Some(value.to_string()));
+  }
+  fn handle_skip(&mut self, section: &str, key: &str) {
+    self.record(section, key, None);
+  }
+}
+
+pub(super) fn collect(cfg: &SegatoolsConfig) -> IniCollector {
+  let mut collector = IniCollector::default();
+  perform_save(&mut collector, cfg);
+  collector
+}
+
+/// Lists every ini key whose written value differs between `old` and `new`.
+pub fn diff_segatoools_configs(old: &SegatoolsConfig, new: &SegatoolsConfig) -> Vec<ConfigChange> {
+  let before = collect(old);
+  let after = collect(new);
+
+  let mut order = before.order.clone();
+  for id in &after.order {
+    if !before.values.contains_key(id) {
+      order.push(id.clone());
+    }
+  }
+
+  order
+    .into_iter()
+    .filter_map(|id| {
+      let old_value = before.values.get(&id).cloned().flatten();
+      let new_value = after.values.get(&id).cloned().flatten();
+      if old_value == new_value {
+        return None;
+      }
+      Some(ConfigChange {
+        section: id.0,
+        key: id.1,
+        old: old_value,
+        new: new_value,
+      })
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::config::load_segatoools_config_from_string;
+
+  #[test]
+  fn reports_changed_and_removed_keys() {
+    let old = load_segatoools_config_from_string("[system]\nenable=1\nfreeplay=0\n[dns]\ndefault=127.0.0.1\n").unwrap();
+    let mut new = old.clone();
+    new.system.freeplay = true;
+    new.dns.default.clear();
+    let changes = diff_segatoools_configs(&old, &new);
+    assert_eq!(changes.len(), 2);
+    assert!(changes.contains(&ConfigChange {
+      section: "system".to_string(),
+      key: "freeplay".to_string(),
+      old: Some("0".to_string()),
+      new: Some("1".to_string()),
+    }));
+    assert!(changes
+      .iter()
+      .any(|c| c.section == "dns" && c.key == "default" && c.new.is_none()));
+  }
+}
@@ -0,0 +1,257 @@
+use super::segatools::SegatoolsConfig;
+use crate::error::ConfigError;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Folder under a game's segatools root that holds its IO DLLs, as `store_io_dll_cmd` uses.
+pub const IO_DIR: &str = "IO";
+const CATALOG_FILE: &str = "io_dlls.json";
+
+/// Which segatools IO hook a DLL is loaded by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IoDllKind {
+  Aimeio,
+  Mai2io,
+  Chuniio,
+  Mu3io,
+}
+
+impl IoDllKind {
+  /// The section whose `path` key loads this kind of DLL.
+  pub fn section(self) -> &'static str {
+    match self {
+      IoDllKind::Aimeio => "aimeio",
+      IoDllKind::Mai2io => "mai2io",
+      IoDllKind::Chuniio => "chuniio",
+      IoDllKind::Mu3io => "mu3io",
+    }
+  }
+
+  fn path_mut(self, cfg: &mut SegatoolsConfig) -> &mut String {
+    match self {
+      IoDllKind::Aimeio => &mut cfg.aimeio.path,
+      IoDllKind::Mai2io => &mut cfg.mai2io.path,
+      IoDllKind::Chuniio => &mut cfg.chuniio.path,
+      IoDllKind::Mu3io => &mut cfg.mu3io.path,
+    }
+  }
+
+  pub fn configured_path(self, cfg: &SegatoolsConfig) -> &str {
+    match self {
+      IoDllKind::Aimeio => &cfg.aimeio.path,
+      IoDllKind::Mai2io => &cfg.mai2io.path,
+      IoDllKind::Chuniio => &cfg.chuniio.path,
+      IoDllKind::Mu3io => &cfg.mu3io.path,
+    }
+  }
+}
+
+/// A DLL kept in a game's IO library.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IoDll {
+  pub id: String,
+  pub label: String,
+  pub kind: IoDllKind,
+  /// Path relative to the segatools root, as written to the config, e.g. `IO\aimeio.dll`.
+  pub file: String,
+  pub sha256: String,
+  pub size: u64,
+  pub added_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IoDllEntry {
+  #[serde(flatten)]
+  pub dll: IoDll,
+  /// The config loads this DLL right now.
+  pub active: bool,
+  /// The file is gone or no longer matches its recorded hash.
+  pub modified: bool,
+}
+
+fn catalog_path(segatools_root: &Path) -> PathBuf {
+  segatools_root.join(IO_DIR).join(CATALOG_FILE)
+}
+
+fn same_path(a: &str, b: &str) -> bool {
+  let normalize = |p: &str| p.trim().replace('/', "\\").trim_start_matches(".\\").to_lowercase();
+  normalize(a) == normalize(b)
+}
+
+pub fn sha256_file(path: &Path) -> std::io::Result<String> {
+  let mut file = File::open(path)?;
+  let mut hasher = Sha256::new();
+  let mut buf = [0u8; 64 * 1024];
+  loop {
+    let read = file.read(&mut buf)?;
+    if read == 0 {
+      break;
+    }
+    hasher.update(&buf[..read]);
+  }
+  Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+pub fn load_io_dlls(segatools_root: &Path) -> Result<Vec<IoDll>, ConfigError> {
+  let path = catalog_path(segatools_root);
+  if !path.exists() {
+    return Ok(Vec::new());
+  }
+  Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+fn save_io_dlls(segatools_root: &Path, dlls: &[IoDll]) -> Result<(), ConfigError> {
+  let path = catalog_path(segatools_root);
+  if let Some(dir) = path.parent() {
+    fs::create_dir_all(dir)?;
+  }
+  fs::write(path, serde_json::to_string_pretty(dlls)?)?;
+  Ok(())
+}
+
+/// The library with each DLL's state against `cfg`.
+pub fn list_io_dlls(segatools_root: &Path, cfg: &SegatoolsConfig) -> Result<Vec<IoDllEntry>, ConfigError> {
+  Ok(
+    load_io_dlls(segatools_root)?
+      .into_iter()
+      .map(|dll| IoDllEntry {
+        active: is_active_io_dll(&dll, cfg),
+        modified: !io_dll_intact(segatools_root, &dll),
+        dll,
+      })
+      .collect(),
+  )
+}
+
+/// Copies `source` into the game's IO folder and records it. A DLL already in the library
+/// with the same kind and contents is relabelled instead of copied twice.
+pub fn add_io_dll(segatools_root: &Path, source: &Path, kind: IoDllKind, label: &str) -> Result<IoDll, ConfigError> {
+  if !source.is_file() {
+    return Err(ConfigError::NotFound(source.display().to_string()));
+  }
+  let sha256 = sha256_file(source)?;
+  let mut dlls = load_io_dlls(segatools_root)?;
+  let label = match label.trim() {
+    "" => source.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default(),
+    label => label.to_string(),
+  };
+  if let Some(existing) = dlls.iter_mut().find(|d| d.kind == kind && d.sha256 == sha256) {
+    existing.label = label;
+    let existing = existing.clone();
+    save_io_dlls(segatools_root, &dlls)?;
+    return Ok(existing);
+  }
+
+  let io_dir = segatools_root.join(IO_DIR);
+  fs::create_dir_all(&io_dir)?;
+  let name = source
+    .file_name()
+    .ok_or_else(|| ConfigError::Parse(format!("{} has no file name", source.display())))?;
+  let mut dest = io_dir.join(name);
+  if dest.exists() {
+    // Same file name, different DLL: keep both apart by a hash prefix.
+    dest = io_dir.join(&sha256[..8]).join(name);
+    fs::create_dir_all(dest.parent().unwrap_or(&io_dir))?;
+  }
+  fs::copy(source, &dest)?;
+
+  let relative = dest.strip_prefix(segatools_root).unwrap_or(&dest);
+  let dll = IoDll {
+    id: format!("{}-{}", kind.section(), &sha256[..12]),
+    label,
+    kind,
+    file: relative.to_string_lossy().to_string(),
+    sha256,
+    size: fs::metadata(&dest)?.len(),
+    added_at: chrono::Utc::now().to_rfc3339(),
+  };
+  dlls.push(dll.clone());
+  save_io_dlls(segatools_root, &dlls)?;
+  Ok(dll)
+}
+
+/// Drops `id` from the library and deletes its file.
+pub fn remove_io_dll(segatools_root: &Path, id: &str) -> Result<IoDll, ConfigError> {
+  let mut dlls = load_io_dlls(segatools_root)?;
+  let index = dlls
+    .iter()
+    .position(|d| d.id == id)
+    .ok_or_else(|| ConfigError::NotFound(format!("IO DLL {}", id)))?;
+  let dll = dlls.remove(index);
+  if let Err(e) = fs::remove_file(segatools_root.join(&dll.file)) {
+    if e.kind() != std::io::ErrorKind::NotFound {
+      return Err(e.into());
+    }
+  }
+  save_io_dlls(segatools_root, &dlls)?;
+  Ok(dll)
+}
+
+/// Whether the config loads `dll` right now.
+pub fn is_active_io_dll(dll: &IoDll, cfg: &SegatoolsConfig) -> bool {
+  same_path(dll.kind.configured_path(cfg), &dll.file)
+}
+
+/// Whether the copy in the library still has the contents it was added with, so a swapped
+/// DLL is never loaded under an old label.
+pub fn io_dll_intact(segatools_root: &Path, dll: &IoDll) -> bool {
+  sha256_file(&segatools_root.join(&dll.file)).is_ok_and(|hash| hash == dll.sha256)
+}
+
+/// Points the DLL's section at it, writing the key even where it was missing or commented out.
+pub fn activate_io_dll(dll: &IoDll, cfg: &mut SegatoolsConfig) {
+  *dll.kind.path_mut(cfg) = dll.file.clone();
+  let section = dll.kind.section();
+  if !cfg.present_sections.is_empty() && !cfg.present_sections.iter().any(|s| s == section) {
+    cfg.present_sections.push(section.to_string());
+  }
+  let key = format!("{}.path", section);
+  cfg.commented_keys.retain(|k| !k.eq_ignore_ascii_case(&key));
+  if !cfg.present_keys.is_empty() && !cfg.present_keys.contains(&key) {
+    cfg.present_keys.push(key);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::TempDir;
+
+  #[test]
+  fn adds_activates_and_removes_dlls() {
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path().join("segatools");
+    let first = tmp.path().join("aimeio.dll");
+    let second = tmp.path().join("other").join("aimeio.dll");
+    fs::create_dir_all(second.parent().unwrap()).unwrap();
+    fs::write(&first, b"first").unwrap();
+    fs::write(&second, b"second").unwrap();
+
+    let a = add_io_dll(&root, &first, IoDllKind::Aimeio, "Stock").unwrap();
+    let b = add_io_dll(&root, &second, IoDllKind::Aimeio, "").unwrap();
+    assert_eq!(Path::new(&a.file), Path::new(IO_DIR).join("aimeio.dll"));
+    assert_ne!(a.file, b.file);
+    assert_eq!(add_io_dll(&root, &first, IoDllKind::Aimeio, "Renamed").unwrap().id, a.id);
+
+    let mut cfg = SegatoolsConfig::default();
+    activate_io_dll(&b, &mut cfg);
+    assert_eq!(cfg.aimeio.path, b.file);
+    let listed = list_io_dlls(&root, &cfg).unwrap();
+    assert_eq!(listed.len(), 2);
+    assert_eq!(listed[0].dll.label, "Renamed");
+    assert!(!listed[0].active && listed[1].active && !listed[1].modified);
+
+    remove_io_dll(&root, &a.id).unwrap();
+    assert!(!root.join(&a.file).exists());
+    assert_eq!(load_io_dlls(&root).unwrap().len(), 1);
+
+    fs::write(root.join(&b.file), b"tampered").unwrap();
+    assert!(!io_dll_intact(&root, &b));
+  }
+}
@@ -1,9 +1,21 @@
-use crate::config::paths::active_game_dir;
+use crate::config::paths::{active_game_dir, game_dir};
 use crate::error::ConfigError;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
 use std::fs;
+use std::net::Ipv4Addr;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// JSON Schemas of the configs amdaemon is picky about, by file name. A config named
+/// `config_x.json` is checked against `config_x.schema.json`; `$ref`s name other entries.
+const BUNDLED_SCHEMAS: [(&str, &str); 4] = [
+    ("config_common.schema.json", include_str!("schemas/config_common.schema.json")),
+    ("config_server.schema.json", include_str!("schemas/config_server.schema.json")),
+    ("config_client.schema.json", include_str!("schemas/config_client.schema.json")),
+    ("network.schema.json", include_str!("schemas/network.schema.json")),
+];
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JsonConfigFile {
@@ -84,6 +96,12 @@ fn path_for_file(dir: &Path, name: &str) -> Result<PathBuf, ConfigError> {
     Ok(dir.join(clean))
 }
 
+/// Path of an amdaemon config JSON inside a game's directory, validated like the other helpers.
+pub fn json_config_path_for_game(game_id: &str, name: &str) -> Result<PathBuf, ConfigError> {
+    let dir = game_dir(game_id)?;
+    path_for_file(&dir, name)
+}
+
 pub fn load_json_config_for_active(name: &str) -> Result<Value, ConfigError> {
     let dir = active_game_dir()?;
     let path = path_for_file(&dir, name)?;
@@ -95,7 +113,17 @@ pub fn load_json_config_for_active(name: &str) -> Result<Value, ConfigError> {
     Ok(value)
 }
 
+/// Writes a config of the active game. Documents that break their bundled schema are refused,
+/// since amdaemon would not start with them.
 pub fn save_json_config_for_active(name: &str, content: &Value) -> Result<(), ConfigError> {
+    if let Some(error) = validate_json_schema(name, content).first() {
+        return Err(ConfigError::Parse(format!(
+            "{} does not match its schema at {}: {}",
+            name,
+            if error.pointer.is_empty() { "/" } else { &error.pointer },
+            error.message
+        )));
+    }
     let dir = active_game_dir()?;
     let path = path_for_file(&dir, name)?;
     if let Some(parent) = path.parent() {
@@ -105,3 +133,203 @@ pub fn save_json_config_for_active(name: &str, content: &Value) -> Result<(), Co
     fs::write(path, pretty)?;
     Ok(())
 }
+
+/// One place a config breaks its schema. `pointer` is a JSON pointer into the document,
+/// empty for the document itself.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JsonSchemaError {
+    pub pointer: String,
+    pub message: String,
+}
+
+fn schema_registry() -> &'static HashMap<&'static str, Value> {
+    static REGISTRY: OnceLock<HashMap<&'static str, Value>> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        BUNDLED_SCHEMAS
+            .iter()
+            .map(|(name, raw)| (*name, serde_json::from_str(raw).expect("bundled JSON schemas are valid JSON")))
+            .collect()
+    })
+}
+
+fn schema_for(name: &str) -> Option<&'static Value> {
+    let lower = name.trim().to_lowercase();
+    let stem = lower.strip_suffix(".json")?;
+    schema_registry().get(format!("{}.schema.json", stem).as_str())
+}
+
+/// Whether `name` has a bundled schema its saves are checked against.
+pub fn has_json_schema(name: &str) -> bool {
+    schema_for(name).is_some()
+}
+
+/// Everything in `content` that breaks the bundled schema of `name`; empty when it matches
+/// or there is no schema for the file. Supports the subset of draft-07 the bundled schemas
+/// use: `type`, `enum`, `properties`, `required`, `additionalProperties`, `items`, the
+/// length and range bounds, `format: ipv4` and `$ref` to another bundled schema.
+pub fn validate_json_schema(name: &str, content: &Value) -> Vec<JsonSchemaError> {
+    let mut errors = Vec::new();
+    if let Some(schema) = schema_for(name) {
+        check_schema(schema, content, "", &mut errors);
+    }
+    errors
+}
+
+fn schema_error(errors: &mut Vec<JsonSchemaError>, pointer: &str, message: String) {
+    errors.push(JsonSchemaError {
+        pointer: pointer.to_string(),
+        message,
+    });
+}
+
+fn child_pointer(pointer: &str, token: &str) -> String {
+    format!("{}/{}", pointer, token.replace('~', "~0").replace('/', "~1"))
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn type_matches(expected: &str, value: &Value) -> bool {
+    match expected {
+        "integer" => value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0),
+        "number" => value.is_number(),
+        other => type_name(value) == other,
+    }
+}
+
+fn check_schema(schema: &Value, value: &Value, pointer: &str, errors: &mut Vec<JsonSchemaError>) {
+    let Some(schema) = schema.as_object() else {
+        return;
+    };
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        match schema_registry().get(reference) {
+            Some(target) => check_schema(target, value, pointer, errors),
+            None => schema_error(errors, pointer, format!("Unknown schema {}", reference)),
+        }
+        return;
+    }
+
+    let expected: Vec<&str> = match schema.get("type") {
+        Some(Value::String(name)) => vec![name.as_str()],
+        Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    if !expected.is_empty() && !expected.iter().any(|name| type_matches(name, value)) {
+        let message = format!("Expected {}, found {}", expected.join(" or "), type_name(value));
+        schema_error(errors, pointer, message);
+        return;
+    }
+    if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+        if !options.contains(value) {
+            let allowed: Vec<String> = options.iter().map(Value::to_string).collect();
+            schema_error(errors, pointer, format!("Must be one of {}", allowed.join(", ")));
+        }
+    }
+    let bound = |key: &str| schema.get(key).and_then(Value::as_f64);
+
+    if let Some(number) = value.as_f64() {
+        if let Some(min) = bound("minimum").filter(|min| number < *min) {
+            schema_error(errors, pointer, format!("Must be at least {}", min));
+        }
+        if let Some(max) = bound("maximum").filter(|max| number > *max) {
+            schema_error(errors, pointer, format!("Must be at most {}", max));
+        }
+    }
+    if let Some(text) = value.as_str() {
+        let len = text.chars().count() as f64;
+        if let Some(min) = bound("minLength").filter(|min| len < *min) {
+            schema_error(errors, pointer, format!("Must be at least {} characters", min));
+        }
+        if let Some(max) = bound("maxLength").filter(|max| len > *max) {
+            schema_error(errors, pointer, format!("Must be at most {} characters", max));
+        }
+        // amdaemon leaves addresses empty when DHCP hands them out.
+        let format = schema.get("format").and_then(Value::as_str);
+        if format == Some("ipv4") && !text.trim().is_empty() && text.trim().parse::<Ipv4Addr>().is_err() {
+            schema_error(errors, pointer, format!("{} is not an IPv4 address", text));
+        }
+    }
+    if let Some(items) = value.as_array() {
+        let len = items.len() as f64;
+        if let Some(min) = bound("minItems").filter(|min| len < *min) {
+            schema_error(errors, pointer, format!("Must have at least {} items", min));
+        }
+        if let Some(max) = bound("maxItems").filter(|max| len > *max) {
+            schema_error(errors, pointer, format!("Must have at most {} items", max));
+        }
+        if let Some(item_schema) = schema.get("items") {
+            for (index, item) in items.iter().enumerate() {
+                check_schema(item_schema, item, &child_pointer(pointer, &index.to_string()), errors);
+            }
+        }
+    }
+    if let Some(map) = value.as_object() {
+        check_object(schema, map, pointer, errors);
+    }
+}
+
+fn check_object(schema: &Map<String, Value>, map: &Map<String, Value>, pointer: &str, errors: &mut Vec<JsonSchemaError>) {
+    for key in schema.get("required").and_then(Value::as_array).into_iter().flatten() {
+        if let Some(key) = key.as_str().filter(|key| !map.contains_key(*key)) {
+            schema_error(errors, pointer, format!("Missing required key {}", key));
+        }
+    }
+    let properties = schema.get("properties").and_then(Value::as_object);
+    for (key, child) in map {
+        let child_at = child_pointer(pointer, key);
+        match (properties.and_then(|p| p.get(key)), schema.get("additionalProperties")) {
+            (Some(child_schema), _) => check_schema(child_schema, child, &child_at, errors),
+            (None, Some(Value::Bool(false))) => schema_error(errors, &child_at, format!("Unknown key {}", key)),
+            (None, Some(extra)) => check_schema(extra, child, &child_at, errors),
+            (None, None) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn bundled_schemas_report_json_pointers() {
+        assert!(has_json_schema("config_common.json"));
+        assert!(!has_json_schema("config_hook.json"));
+        for (name, _) in BUNDLED_SCHEMAS {
+            assert!(schema_registry()[name].is_object());
+        }
+
+        let good = json!({
+            "network": { "property": { "dhcp": true, "ip_address": "" } },
+            "aime": { "enable": true, "unit": [{ "port": 3, "id": 1 }] },
+            "unknown_section": { "kept": true }
+        });
+        assert!(validate_json_schema("config_common.json", &good).is_empty());
+
+        let bad = json!({
+            "network": { "property": { "gateway": "192.168.1.300" } },
+            "credit": { "max_credit": "24" },
+            "aime": { "unit": [{ "port": 0 }] }
+        });
+        let mut pointers: Vec<String> = validate_json_schema("config_common.json", &bad)
+            .into_iter()
+            .map(|error| error.pointer)
+            .collect();
+        pointers.sort();
+        assert_eq!(
+            pointers,
+            ["/aime/unit/0/port", "/credit/max_credit", "/network/property/gateway"]
+        );
+        assert_eq!(validate_json_schema("config_server.json", &json!([]))[0].pointer, "");
+        assert!(validate_json_schema("config_hook.json", &json!([])).is_empty());
+    }
+}
@@ -0,0 +1,332 @@
+use super::segatools::SegatoolsConfig;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Names of the virtual-key codes a binding can take, as shown by the config editor
+/// (`src/utils/vkCodes.ts` carries the same table).
+const VK_NAMES: &[(u32, &str)] = &[
+  (0x08, "Backspace"),
+  (0x09, "Tab"),
+  (0x0D, "Enter"),
+  (0x10, "Shift"),
+  (0x11, "Ctrl"),
+  (0x12, "Alt"),
+  (0x13, "Pause"),
+  (0x14, "CapsLock"),
+  (0x1B, "Esc"),
+  (0x20, "Space"),
+  (0x21, "PageUp"),
+  (0x22, "PageDown"),
+  (0x23, "End"),
+  (0x24, "Home"),
+  (0x25, "Left"),
+  (0x26, "Up"),
+  (0x27, "Right"),
+  (0x28, "Down"),
+  (0x2D, "Insert"),
+  (0x2E, "Delete"),
+  (0x30, "0"),
+  (0x31, "1"),
+  (0x32, "2"),
+  (0x33, "3"),
+  (0x34, "4"),
+  (0x35, "5"),
+  (0x36, "6"),
+  (0x37, "7"),
+  (0x38, "8"),
+  (0x39, "9"),
+  (0x41, "A"),
+  (0x42, "B"),
+  (0x43, "C"),
+  (0x44, "D"),
+  (0x45, "E"),
+  (0x46, "F"),
+  (0x47, "G"),
+  (0x48, "H"),
+  (0x49, "I"),
+  (0x4A, "J"),
+  (0x4B, "K"),
+  (0x4C, "L"),
+  (0x4D, "M"),
+  (0x4E, "N"),
+  (0x4F, "O"),
+  (0x50, "P"),
+  (0x51, "Q"),
+  (0x52, "R"),
+  (0x53, "S"),
+  (0x54, "T"),
+  (0x55, "U"),
+  (0x56, "V"),
+  (0x57, "W"),
+  (0x58, "X"),
+  (0x59, "Y"),
+  (0x5A, "Z"),
+  (0x5B, "LWin"),
+  (0x5C, "RWin"),
+  (0x60, "Num 0"),
+  (0x61, "Num 1"),
+  (0x62, "Num 2"),
+  (0x63, "Num 3"),
+  (0x64, "Num 4"),
+  (0x65, "Num 5"),
+  (0x66, "Num 6"),
+  (0x67, "Num 7"),
+  (0x68, "Num 8"),
+  (0x69, "Num 9"),
+  (0x6A, "Multiply"),
+  (0x6B, "Add"),
+  (0x6C, "Separator"),
+  (0x6D, "Subtract"),
+  (0x6E, "Decimal"),
+  (0x6F, "Divide"),
+  (0x70, "F1"),
+  (0x71, "F2"),
+  (0x72, "F3"),
+  (0x73, "F4"),
+  (0x74, "F5"),
+  (0x75, "F6"),
+  (0x76, "F7"),
+  (0x77, "F8"),
+  (0x78, "F9"),
+  (0x79, "F10"),
+  (0x7A, "F11"),
+  (0x7B, "F12"),
+  (0x90, "NumLock"),
+  (0x91, "ScrollLock"),
+  (0xA0, "LShift"),
+  (0xA1, "RShift"),
+  (0xA2, "LCtrl"),
+  (0xA3, "RCtrl"),
+  (0xA4, "LAlt"),
+  (0xA5, "RAlt"),
+  (0xBA, ";"),
+  (0xBB, "="),
+  (0xBC, ","),
+  (0xBD, "-"),
+  (0xBE, "."),
+  (0xBF, "/"),
+  (0xC0, "`"),
+  (0xDB, "["),
+  (0xDC, "\\"),
+  (0xDD, "]"),
+  (0xDE, "'"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyName {
+  pub code: u32,
+  pub name: String,
+}
+
+/// A key setting of the segatools config, e.g. `io4.coin`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyBinding {
+  pub field: String,
+  pub code: u32,
+}
+
+/// Settings bound to the same key, so one keypress triggers all of them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyConflict {
+  pub code: u32,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub name: Option<String>,
+  pub fields: Vec<String>,
+}
+
+pub fn key_name(code: u32) -> Option<&'static str> {
+  VK_NAMES.iter().find(|(c, _)| *c == code).map(|(_, name)| *name)
+}
+
+/// Virtual-key code of a name from [`key_names`], ignoring case, or of a `0x`-prefixed code.
+pub fn key_code(name: &str) -> Option<u32> {
+  let name = name.trim();
+  if let Some(hex) = name.strip_prefix("0x").or_else(|| name.strip_prefix("0X")) {
+    return u32::from_str_radix(hex, 16).ok().filter(|code| (1..=0xFE).contains(code));
+  }
+  VK_NAMES
+    .iter()
+    .find(|(_, n)| n.eq_ignore_ascii_case(name))
+    .map(|(code, _)| *code)
+}
+
+pub fn key_names() -> Vec<KeyName> {
+  VK_NAMES
+    .iter()
+    .map(|(code, name)| KeyName { code: *code, name: name.to_string() })
+    .collect()
+}
+
+fn section_active(cfg: &SegatoolsConfig, section: &str) -> bool {
+  if !cfg.present_sections.is_empty() && !cfg.present_sections.iter().any(|s| s == section) {
+    return false;
+  }
+  match section {
+    "aime" => cfg.aime.enable,
+    "gpio" => cfg.gpio.enable,
+    "io4" => cfg.io4.enable,
+    "button" => cfg.button.enable,
+    "slider" => cfg.slider.enable,
+    _ => true,
+  }
+}
+
+/// Key settings in effect for `cfg`: those in sections the file has and that are enabled.
+/// Unbound keys (0) are left out.
+pub fn key_bindings(cfg: &SegatoolsConfig) -> Vec<KeyBinding> {
+  let b = &cfg.button;
+  let s = &cfg.slider;
+  let fields: Vec<(&str, &str, u32)> = vec![
+    ("aime", "scan", cfg.aime.scan),
+    ("gpio", "sw1", cfg.gpio.sw1),
+    ("gpio", "sw2", cfg.gpio.sw2),
+    ("io4", "test", cfg.io4.test),
+    ("io4", "service", cfg.io4.service),
+    ("io4", "coin", cfg.io4.coin),
+    ("io3", "test", cfg.io3.test),
+    ("io3", "service", cfg.io3.service),
+    ("io3", "coin", cfg.io3.coin),
+    ("io3", "ir", cfg.io3.ir),
+    ("button", "p1Btn1", b.p1_btn1),
+    ("button", "p1Btn2", b.p1_btn2),
+    ("button", "p1Btn3", b.p1_btn3),
+    ("button", "p1Btn4", b.p1_btn4),
+    ("button", "p1Btn5", b.p1_btn5),
+    ("button", "p1Btn6", b.p1_btn6),
+    ("button", "p1Btn7", b.p1_btn7),
+    ("button", "p1Btn8", b.p1_btn8),
+    ("button", "p1Select", b.p1_select),
+    ("button", "p2Btn1", b.p2_btn1),
+    ("button", "p2Btn2", b.p2_btn2),
+    ("button", "p2Btn3", b.p2_btn3),
+    ("button", "p2Btn4", b.p2_btn4),
+    ("button", "p2Btn5", b.p2_btn5),
+    ("button", "p2Btn6", b.p2_btn6),
+    ("button", "p2Btn7", b.p2_btn7),
+    ("button", "p2Btn8", b.p2_btn8),
+    ("button", "p2Select", b.p2_select),
+    ("slider", "cell1", s.cell1),
+    ("slider", "cell2", s.cell2),
+    ("slider", "cell3", s.cell3),
+    ("slider", "cell4", s.cell4),
+    ("slider", "cell5", s.cell5),
+    ("slider", "cell6", s.cell6),
+    ("slider", "cell7", s.cell7),
+    ("slider", "cell8", s.cell8),
+    ("slider", "cell9", s.cell9),
+    ("slider", "cell10", s.cell10),
+    ("slider", "cell11", s.cell11),
+    ("slider", "cell12", s.cell12),
+    ("slider", "cell13", s.cell13),
+    ("slider", "cell14", s.cell14),
+    ("slider", "cell15", s.cell15),
+    ("slider", "cell16", s.cell16),
+    ("slider", "cell17", s.cell17),
+    ("slider", "cell18", s.cell18),
+    ("slider", "cell19", s.cell19),
+    ("slider", "cell20", s.cell20),
+    ("slider", "cell21", s.cell21),
+    ("slider", "cell22", s.cell22),
+    ("slider", "cell23", s.cell23),
+    ("slider", "cell24", s.cell24),
+    ("slider", "cell25", s.cell25),
+    ("slider", "cell26", s.cell26),
+    ("slider", "cell27", s.cell27),
+    ("slider", "cell28", s.cell28),
+    ("slider", "cell29", s.cell29),
+    ("slider", "cell30", s.cell30),
+    ("slider", "cell31", s.cell31),
+    ("slider", "cell32", s.cell32),
+    ("ir", "ir1", cfg.ir.ir1),
+    ("ir", "ir2", cfg.ir.ir2),
+    ("ir", "ir3", cfg.ir.ir3),
+    ("ir", "ir4", cfg.ir.ir4),
+    ("ir", "ir5", cfg.ir.ir5),
+    ("ir", "ir6", cfg.ir.ir6),
+  ];
+  fields
+    .into_iter()
+    .filter(|(section, _, code)| *code != 0 && section_active(cfg, section))
+    .map(|(section, key, code)| KeyBinding { field: format!("{}.{}", section, key), code })
+    .collect()
+}
+
+/// Keys bound to more than one setting, in the order they first appear.
+pub fn key_conflicts(cfg: &SegatoolsConfig) -> Vec<KeyConflict> {
+  let mut conflicts: Vec<KeyConflict> = Vec::new();
+  for binding in key_bindings(cfg) {
+    match conflicts.iter_mut().find(|c| c.code == binding.code) {
+      Some(conflict) => conflict.fields.push(binding.field),
+      None => conflicts.push(KeyConflict {
+        code: binding.code,
+        name: key_name(binding.code).map(str::to_string),
+        fields: vec![binding.field],
+      }),
+    }
+  }
+  conflicts.retain(|c| c.fields.len() > 1);
+  conflicts
+}
+
+#[cfg(target_os = "windows")]
+#[link(name = "user32")]
+extern "system" {
+  fn GetAsyncKeyState(vkey: i32) -> i16;
+}
+
+/// Waits up to `timeout` for a key to go down and returns its code, `None` on timeout.
+/// Keys already held when called (like the Enter that started the capture) only count once
+/// released and pressed again. Shift, Ctrl and Alt come back as their left/right codes.
+#[cfg(target_os = "windows")]
+pub fn capture_key(timeout: Duration) -> Result<Option<u32>, String> {
+  // Mouse buttons (0x01-0x06) are not keys segatools can bind.
+  let candidates = || (0x08u32..=0xFE).filter(|code| !(0x10..=0x12).contains(code));
+  let is_down = |code: u32| unsafe { GetAsyncKeyState(code as i32) } as u16 & 0x8000 != 0;
+  let mut held: Vec<u32> = candidates().filter(|code| is_down(*code)).collect();
+  let deadline = std::time::Instant::now() + timeout;
+  while std::time::Instant::now() < deadline {
+    for code in candidates() {
+      let down = is_down(code);
+      if held.contains(&code) {
+        if !down {
+          held.retain(|c| *c != code);
+        }
+      } else if down {
+        return Ok(Some(code));
+      }
+    }
+    std::thread::sleep(Duration::from_millis(10));
+  }
+  Ok(None)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn capture_key(_timeout: Duration) -> Result<Option<u32>, String> {
+  Err("Key capture is only available on Windows".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::config::load_segatoools_config_from_string;
+
+  #[test]
+  fn finds_conflicts_in_active_sections() {
+    assert_eq!(key_name(0x72), Some("F3"));
+    assert_eq!(key_code("num 5"), Some(0x65));
+    assert_eq!(key_code("0x0d"), Some(0x0D));
+    assert_eq!(key_code("Hyper"), None);
+
+    let cfg = load_segatoools_config_from_string(
+      "[io4]\nenable=1\ntest=0x70\nservice=0x71\ncoin=0x70\n[gpio]\nenable=0\nsw1=0x70\n[button]\nenable=1\np1Btn1=0x41\np1Btn2=0x41\n",
+    )
+    .unwrap();
+    let conflicts = key_conflicts(&cfg);
+    assert_eq!(conflicts.len(), 2);
+    assert_eq!(conflicts[0].fields, vec!["io4.test", "io4.coin"]);
+    assert_eq!(conflicts[0].name.as_deref(), Some("F1"));
+    assert_eq!(conflicts[1].fields, vec!["button.p1Btn1", "button.p1Btn2"]);
+    // io3 and slider keep their defaults but the file has no such sections.
+    assert!(key_bindings(&cfg).iter().all(|b| !b.field.starts_with("io3.")));
+  }
+}
@@ -4,10 +4,22 @@ use std::fs;
 use std::path::Path;
 use std::collections::HashSet;
 
+pub mod aime;
+pub mod amdaemon;
+pub mod connectivity;
+pub mod credits;
+pub mod diff;
+pub mod io_dlls;
+pub mod keybindings;
 pub mod paths;
 pub mod profiles;
+pub mod provenance;
+pub mod qr;
 pub mod segatools;
+pub mod share;
+pub mod slots;
 pub mod templates;
+pub mod transaction;
 pub mod json_configs;
 
 pub use segatools::SegatoolsConfig;
@@ -58,23 +70,20 @@ trait ConfigWriter {
     fn handle_skip(&mut self, section: &str, key: &str);
 }
 
-impl ConfigWriter for Ini {
-    fn write_val(&mut self, section: &str, key: &str, value: &str) {
-        self.set(section, key, Some(value.to_string()));
-    }
-    fn handle_skip(&mut self, _section: &str, _key: &str) {
-        // Do nothing
-    }
-}
-
+/// Round-trip writer: edits values in place against the original file text so comments,
+/// blank lines, commented-out keys and section order shipped with segatools survive a save.
 struct IniUpdater {
     lines: Vec<String>,
+    line_ending: &'static str,
+    trailing_newline: bool,
 }
 
 impl IniUpdater {
     fn new(content: &str) -> Self {
         Self {
             lines: content.lines().map(|s| s.to_string()).collect(),
+            line_ending: if content.contains("\r\n") { "\r\n" } else { "\n" },
+            trailing_newline: content.is_empty() || content.ends_with('\n'),
         }
     }
 
@@ -88,32 +97,41 @@ impl IniUpdater {
         None
     }
 
+    /// Line range of a section body, excluding the header.
+    fn section_body(&self, section_idx: usize) -> std::ops::Range<usize> {
+        let start = section_idx + 1;
+        let end = (start..self.lines.len())
+            .find(|&i| is_section_header(&self.lines[i]))
+            .unwrap_or(self.lines.len());
+        start..end
+    }
+
+    fn find_key_line(&self, section_idx: usize, key: &str, commented: bool) -> Option<usize> {
+        self.section_body(section_idx).find(|&i| {
+            matches!(parse_line_key(&self.lines[i]), Some((k, c)) if c == commented && k.eq_ignore_ascii_case(key))
+        })
+    }
+
     fn set(&mut self, section: &str, key: &str, value: &str) {
         if let Some(section_idx) = self.find_section_line(section) {
-            let mut insert_idx = section_idx + 1;
-            let mut found = false;
-            
-            for i in (section_idx + 1)..self.lines.len() {
-                let line = &self.lines[i];
-                let trimmed = line.trim();
-                if trimmed.starts_with('[') && trimmed.ends_with(']') {
-                    insert_idx = i;
-                    break;
-                }
-                
-                if let Some((k, _)) = parse_line_key(line) {
-                    if k.eq_ignore_ascii_case(key) {
-                        self.lines[i] = format!("{}={}", key, value);
-                        found = true;
-                        break;
-                    }
-                }
-                insert_idx = i + 1;
-            }
-            
-            if !found {
-                self.lines.insert(insert_idx, format!("{}={}", key, value));
+            // Prefer the live key; otherwise revive a commented-out one where it already sits.
+            let existing = self
+                .find_key_line(section_idx, key, false)
+                .or_else(|| self.find_key_line(section_idx, key, true));
+            if let Some(i) = existing {
+                self.lines[i] = replace_line_value(&self.lines[i], value);
+                return;
             }
+
+            // Append after the last non-blank line so the gap before the next section is kept.
+            let body = self.section_body(section_idx);
+            let insert_idx = body
+                .clone()
+                .rev()
+                .find(|&i| !self.lines[i].trim().is_empty())
+                .map(|i| i + 1)
+                .unwrap_or(body.start);
+            self.lines.insert(insert_idx, format!("{}={}", key, value));
         } else {
             if !self.lines.is_empty() && !self.lines.last().unwrap().trim().is_empty() {
                 self.lines.push("".to_string());
@@ -125,27 +143,48 @@ impl IniUpdater {
 
     fn comment_out(&mut self, section: &str, key: &str) {
         if let Some(section_idx) = self.find_section_line(section) {
-             for i in (section_idx + 1)..self.lines.len() {
-                let line = &self.lines[i];
-                let trimmed = line.trim();
-                if trimmed.starts_with('[') && trimmed.ends_with(']') {
-                    break;
-                }
-                
-                if let Some((k, is_commented)) = parse_line_key(line) {
+            for i in self.section_body(section_idx) {
+                if let Some((k, false)) = parse_line_key(&self.lines[i]) {
                     if k.eq_ignore_ascii_case(key) {
-                        if !is_commented {
-                            self.lines[i] = format!(";{}", line);
-                        }
-                        return;
+                        self.lines[i] = format!(";{}", self.lines[i]);
                     }
                 }
             }
         }
     }
-    
-    fn to_string(&self) -> String {
-        self.lines.join("\n")
+
+    fn render(&self) -> String {
+        let mut out = self.lines.join(self.line_ending);
+        if self.trailing_newline && !out.is_empty() {
+            out.push_str(self.line_ending);
+        }
+        out
+    }
+}
+
+fn is_section_header(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.starts_with('[') && trimmed.ends_with(']')
+}
+
+/// Rewrites the value of a `key=value` line, keeping its indentation, key casing and the
+/// spacing around `=`. A leading comment marker is dropped, which re-enables the key.
+fn replace_line_value(line: &str, value: &str) -> String {
+    let indent_len = line.len() - line.trim_start().len();
+    let (indent, rest) = line.split_at(indent_len);
+    let rest = rest
+        .strip_prefix(';')
+        .or_else(|| rest.strip_prefix('#'))
+        .map(|r| r.trim_start())
+        .unwrap_or(rest);
+    match rest.find('=') {
+        Some(idx) => {
+            let key_part = &rest[..idx];
+            let after = &rest[idx + 1..];
+            let spacing = &after[..after.len() - after.trim_start().len()];
+            format!("{}{}={}{}", indent, key_part, spacing, value)
+        }
+        None => format!("{}{}={}", indent, rest.trim(), value),
     }
 }
 
@@ -167,7 +206,7 @@ fn parse_line_key(line: &str) -> Option<(String, bool)> {
     
     if content.starts_with(';') || content.starts_with('#') {
         is_commented = true;
-        content = &content[1..].trim();
+        content = content[1..].trim();
     }
     
     if let Some(idx) = content.find('=') {
@@ -204,7 +243,8 @@ fn prune_existing_content(content: &str, cfg: &SegatoolsConfig) -> String {
       continue;
     }
 
-    if let Some((key, _)) = parse_line_key(line) {
+    // Commented-out keys are documentation as far as the round-trip writer is concerned.
+    if let Some((key, false)) = parse_line_key(line) {
       if !current_section.is_empty() && managed_sections.contains(&current_section) {
         let full_key = format!("{}.{}", current_section, key.to_lowercase());
         if !allowed.contains(&full_key) {
@@ -216,7 +256,44 @@ fn prune_existing_content(content: &str, cfg: &SegatoolsConfig) -> String {
     lines.push(line.to_string());
   }
 
-  lines.join("\n")
+  let line_ending = if content.contains("\r\n") { "\r\n" } else { "\n" };
+  let mut pruned = lines.join(line_ending);
+  if content.ends_with('\n') {
+    pruned.push_str(line_ending);
+  }
+  pruned
+}
+
+/// Narrows `cfg` so saving it only touches the given sections; every other section of the
+/// target file is left as it is. Returns the sections that remain. A config that records
+/// which sections it contains drops requested sections it does not have, so they are not
+/// overwritten with defaults.
+pub fn retain_sections(cfg: &mut SegatoolsConfig, sections: &[String]) -> Vec<String> {
+  let mut requested: Vec<String> = Vec::new();
+  for section in sections {
+    let section = section.trim().to_lowercase();
+    if !section.is_empty() && !requested.contains(&section) {
+      requested.push(section);
+    }
+  }
+  if !cfg.present_sections.is_empty() {
+    requested.retain(|section| cfg.present_sections.contains(section));
+  }
+  let in_sections = |full_key: &String| {
+    full_key
+      .split_once('.')
+      .map(|(section, _)| requested.iter().any(|s| s.eq_ignore_ascii_case(section)))
+      .unwrap_or(false)
+  };
+  let tracks_keys = !cfg.present_keys.is_empty();
+  cfg.present_keys.retain(|k| in_sections(k));
+  cfg.commented_keys.retain(|k| in_sections(k));
+  if tracks_keys && cfg.present_keys.is_empty() {
+    // An empty key list means "write everything", which is the opposite of what is wanted.
+    requested.clear();
+  }
+  cfg.present_sections = requested.clone();
+  requested
 }
 
 fn should_write_key(present_keys: &[String], section: &str, key: &str) -> bool {
@@ -653,6 +730,39 @@ fn perform_save(writer: &mut dyn ConfigWriter, cfg: &SegatoolsConfig) {
     );
   }
 
+  if should_save("cmio") {
+    save_helper("cmio", vec![("path", cfg.cmio.path.clone())]);
+  }
+
+  if should_save("printer") {
+    save_helper("printer",
+      vec![
+        ("enable", bool_to_string(cfg.printer.enable)),
+        ("serial_no", cfg.printer.serial_no.clone()),
+        ("mainFwPath", cfg.printer.main_fw_path.clone()),
+        ("dspFwPath", cfg.printer.dsp_fw_path.clone()),
+        ("paramFwPath", cfg.printer.param_fw_path.clone()),
+        ("printerOutPath", cfg.printer.printer_out_path.clone()),
+      ],
+    );
+  }
+
+  if should_save("mercuryio") {
+    save_helper("mercuryio", vec![("path", cfg.mercuryio.path.clone())]);
+  }
+
+  if should_save("divaio") {
+    save_helper("divaio", vec![("path", cfg.divaio.path.clone())]);
+  }
+
+}
+
+/// Renders `cfg` the way a save would: against `existing` with unmanaged keys pruned.
+pub(crate) fn render_segatoools_for_save(existing: &str, cfg: &SegatoolsConfig) -> String {
+  let content = prune_existing_content(existing, cfg);
+  let mut updater = IniUpdater::new(&content);
+  perform_save(&mut updater, cfg);
+  updater.render()
 }
 
 pub fn save_segatoools_config(path: &Path, cfg: &SegatoolsConfig) -> Result<(), ConfigError> {
@@ -660,21 +770,12 @@ pub fn save_segatoools_config(path: &Path, cfg: &SegatoolsConfig) -> Result<(),
     fs::create_dir_all(dir)?;
   }
 
-  if cfg.present_sections.is_empty() {
-      let mut ini = Ini::new();
-      perform_save(&mut ini, cfg);
-      ini.write(path.to_string_lossy().as_ref()).map_err(ConfigError::Io)?;
+  let content = if path.exists() {
+      fs::read_to_string(path).map_err(ConfigError::Io)?
   } else {
-      let content = if path.exists() {
-          fs::read_to_string(path).map_err(ConfigError::Io)?
-      } else {
-          String::new()
-      };
-      let content = prune_existing_content(&content, cfg);
-      let mut updater = IniUpdater::new(&content);
-      perform_save(&mut updater, cfg);
-      fs::write(path, updater.to_string()).map_err(ConfigError::Io)?;
-  }
+      String::new()
+  };
+  fs::write(path, render_segatoools_for_save(&content, cfg)).map_err(ConfigError::Io)?;
   Ok(())
 }
 
@@ -682,12 +783,12 @@ pub fn render_segatoools_config(cfg: &SegatoolsConfig, existing_content: Option<
   let base = existing_content.unwrap_or("");
   let mut updater = IniUpdater::new(base);
   perform_save(&mut updater, cfg);
-  Ok(updater.to_string())
+  Ok(updater.render())
 }
 
 pub fn load_segatoools_config_from_string(content: &str) -> Result<SegatoolsConfig, ConfigError> {
   let mut parser = Ini::new();
-  parser.read(content.to_string()).map_err(|e| ConfigError::Parse(e))?;
+  parser.read(content.to_string()).map_err(ConfigError::Parse)?;
 
   let mut cfg = SegatoolsConfig::default();
 
@@ -737,7 +838,7 @@ pub fn load_segatoools_config_from_string(content: &str) -> Result<SegatoolsConf
       let slider_map = map.get("slider");
       for i in 1..=32 {
           let key = format!("cell{}", i);
-          let is_present = slider_map.map_or(false, |m| m.contains_key(&key));
+          let is_present = slider_map.is_some_and(|m| m.contains_key(&key));
           
           if !is_present {
               let full_key = format!("slider.{}", key);
@@ -750,7 +851,7 @@ pub fn load_segatoools_config_from_string(content: &str) -> Result<SegatoolsConf
       let ir_map = map.get("ir");
       for i in 1..=6 {
           let key = format!("ir{}", i);
-          let is_present = ir_map.map_or(false, |m| m.contains_key(&key));
+          let is_present = ir_map.is_some_and(|m| m.contains_key(&key));
           
           if !is_present {
               let full_key = format!("ir.{}", key);
@@ -918,6 +1019,19 @@ pub fn load_segatoools_config_from_string(content: &str) -> Result<SegatoolsConf
 
   cfg.mu3io.path = read_string(&parser, "mu3io", "path", &cfg.mu3io.path);
 
+  cfg.cmio.path = read_string(&parser, "cmio", "path", &cfg.cmio.path);
+
+  cfg.printer.enable = read_bool(&parser, "printer", "enable", cfg.printer.enable);
+  cfg.printer.serial_no = read_string(&parser, "printer", "serial_no", &cfg.printer.serial_no);
+  cfg.printer.main_fw_path = read_string(&parser, "printer", "mainFwPath", &cfg.printer.main_fw_path);
+  cfg.printer.dsp_fw_path = read_string(&parser, "printer", "dspFwPath", &cfg.printer.dsp_fw_path);
+  cfg.printer.param_fw_path = read_string(&parser, "printer", "paramFwPath", &cfg.printer.param_fw_path);
+  cfg.printer.printer_out_path = read_string(&parser, "printer", "printerOutPath", &cfg.printer.printer_out_path);
+
+  cfg.mercuryio.path = read_string(&parser, "mercuryio", "path", &cfg.mercuryio.path);
+
+  cfg.divaio.path = read_string(&parser, "divaio", "path", &cfg.divaio.path);
+
   cfg.io3.test = read_u32(&parser, "io3", "test", cfg.io3.test);
   cfg.io3.service = read_u32(&parser, "io3", "service", cfg.io3.service);
   cfg.io3.coin = read_u32(&parser, "io3", "coin", cfg.io3.coin);
@@ -975,3 +1089,42 @@ pub fn load_segatoools_config(path: &Path) -> Result<SegatoolsConfig, ConfigErro
 pub fn default_segatoools_config() -> SegatoolsConfig {
   SegatoolsConfig::default()
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn retained_sections_leave_the_rest_of_the_file_alone() {
+    let live = "[dns]\ndefault=127.0.0.1\n[gfx]\nwindowed=1\n";
+    let mut profile = load_segatoools_config_from_string("[dns]\ndefault=10.0.0.1\n[gfx]\nwindowed=0\n").unwrap();
+    let applied = retain_sections(&mut profile, &["DNS".to_string(), "keychip".to_string()]);
+    assert_eq!(applied, vec!["dns".to_string()]);
+    let rendered = render_segatoools_for_save(live, &profile);
+    assert!(rendered.contains("default=10.0.0.1"));
+    assert!(rendered.contains("windowed=1"));
+  }
+
+  #[test]
+  fn round_trip_keeps_comments_and_order() {
+    let original = "; Shipped with segatools\r\n[system]\r\n; Enable ALLS/Nu system emulation\r\nenable = 1\r\n;freeplay=1\r\n\r\n[gpio]\r\ndipsw1=1\r\n";
+    let mut cfg = load_segatoools_config_from_string(original).unwrap();
+    cfg.system.enable = false;
+    let rendered = render_segatoools_config(&cfg, Some(original)).unwrap();
+    assert!(rendered.starts_with("; Shipped with segatools\r\n[system]\r\n; Enable ALLS/Nu system emulation\r\n"));
+    assert!(rendered.contains("enable = 0\r\n"));
+    assert!(rendered.contains("\r\n;freeplay=1\r\n\r\n[gpio]\r\n"));
+    assert!(rendered.ends_with("\r\n"));
+  }
+
+  #[test]
+  fn cardmaker_template_round_trips_printer_section() {
+    let mut cfg = load_segatoools_config_from_string(templates::CM_TEMPLATE).unwrap();
+    assert!(cfg.present_sections.iter().any(|s| s == "printer"));
+    assert_eq!(cfg.printer.printer_out_path, "DEVICE\\print");
+    cfg.printer.printer_out_path = "D:\\cards".to_string();
+    let rendered = render_segatoools_config(&cfg, Some(templates::CM_TEMPLATE)).unwrap();
+    assert!(rendered.contains("printerOutPath=D:\\cards"));
+    assert!(rendered.contains("[cmio]"));
+  }
+}
@@ -25,6 +25,13 @@ pub fn set_active_game_id(id: &str) -> Result<(), ConfigError> {
   Ok(())
 }
 
+pub fn clear_active_game_id() -> Result<(), ConfigError> {
+  match fs::remove_file(active_game_file()) {
+    Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+    _ => Ok(()),
+  }
+}
+
 pub fn game_dir(game_id: &str) -> Result<PathBuf, ConfigError> {
   let games = store::list_games().map_err(|e| ConfigError::Parse(e.to_string()))?;
   let game = games
@@ -1,85 +1,450 @@
 use super::paths::{profiles_dir_for_active, profiles_dir_for_game};
-use super::SegatoolsConfig;
+use super::{load_segatoools_config_from_string, render_segatoools_config, SegatoolsConfig};
 use crate::error::ConfigError;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
 use std::fs;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConfigProfile {
   pub id: String,
   pub name: String,
   pub description: Option<String>,
+  /// Profile whose settings this one inherits. Only keys listed in `segatools.present_keys`
+  /// override the base; an empty list means the profile carries a complete config.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub base_profile_id: Option<String>,
   pub segatools: SegatoolsConfig,
+  /// Configs for the game's named segatools.ini slots, keyed by slot name. `segatools` is
+  /// always the default slot.
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  pub segatools_slots: BTreeMap<String, SegatoolsConfig>,
+  /// amdaemon config JSONs written alongside segatools.ini, keyed by file name.
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  pub json_configs: BTreeMap<String, Value>,
   pub created_at: String,
   pub updated_at: String,
 }
 
-fn profiles_path(game_id: Option<&str>) -> Result<std::path::PathBuf, ConfigError> {
+const MAX_PROFILE_INHERITANCE_DEPTH: usize = 8;
+const LEGACY_PROFILES_FILE: &str = "configarc_profiles.json";
+const PROFILES_DIR: &str = "profiles";
+const PROFILE_FILE: &str = "profile.json";
+const NOTES_FILE: &str = "notes.md";
+const ASSETS_DIR: &str = "assets";
+
+/// A file kept with a profile and copied into the game directory when the profile is applied,
+/// such as `DEVICE/aime.txt` or a custom amdaemon JSON.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileAsset {
+  /// Path relative to the game directory, with forward slashes.
+  pub target: String,
+  pub source: PathBuf,
+  pub size: u64,
+}
+
+/// Persistence for one game's profiles. Stores that keep a folder per profile return it from
+/// `profile_dir`; notes and assets live there and are unavailable otherwise.
+pub trait ProfileStore {
+  fn list(&self) -> Result<Vec<ConfigProfile>, ConfigError>;
+  fn save(&self, profile: &ConfigProfile) -> Result<(), ConfigError>;
+  fn delete(&self, id: &str) -> Result<(), ConfigError>;
+  fn profile_dir(&self, _id: &str) -> Result<Option<PathBuf>, ConfigError> {
+    Ok(None)
+  }
+}
+
+/// The original layout: every profile of a game in one `configarc_profiles.json`.
+pub struct JsonFileStore {
+  path: PathBuf,
+}
+
+impl JsonFileStore {
+  pub fn new(path: PathBuf) -> Self {
+    Self { path }
+  }
+
+  fn write_all(&self, profiles: &[ConfigProfile]) -> Result<(), ConfigError> {
+    if let Some(parent) = self.path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    fs::write(&self.path, serde_json::to_string_pretty(profiles)?)?;
+    Ok(())
+  }
+}
+
+impl ProfileStore for JsonFileStore {
+  fn list(&self) -> Result<Vec<ConfigProfile>, ConfigError> {
+    if !self.path.exists() {
+      return Ok(vec![]);
+    }
+    let data = fs::read_to_string(&self.path)?;
+    if data.trim().is_empty() {
+      return Ok(vec![]);
+    }
+    Ok(serde_json::from_str(&data)?)
+  }
+
+  fn save(&self, profile: &ConfigProfile) -> Result<(), ConfigError> {
+    let mut profiles = self.list()?;
+    profiles.retain(|p| p.id != profile.id);
+    profiles.push(profile.clone());
+    self.write_all(&profiles)
+  }
+
+  fn delete(&self, id: &str) -> Result<(), ConfigError> {
+    let mut profiles = self.list()?;
+    let before = profiles.len();
+    profiles.retain(|p| p.id != id);
+    if profiles.len() == before {
+      return Err(ConfigError::NotFound(id.to_string()));
+    }
+    self.write_all(&profiles)
+  }
+}
+
+/// One folder per profile: `<id>/profile.json`, an optional `notes.md` and an `assets/` tree
+/// mirroring the game directory.
+pub struct FolderStore {
+  root: PathBuf,
+}
+
+impl FolderStore {
+  pub fn new(root: PathBuf) -> Self {
+    Self { root }
+  }
+
+  fn folder(&self, id: &str) -> Result<PathBuf, ConfigError> {
+    let valid = !id.is_empty()
+      && id != "."
+      && id != ".."
+      && id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+      return Err(ConfigError::Parse(format!("Invalid profile id: {}", id)));
+    }
+    Ok(self.root.join(id))
+  }
+
+  /// Moves profiles out of a legacy store the first time the folder layout is used, then
+  /// renames the legacy file so it is not read again.
+  fn migrate_from(&self, legacy: &JsonFileStore) -> Result<(), ConfigError> {
+    if !legacy.path.exists() {
+      return Ok(());
+    }
+    for profile in legacy.list()? {
+      if !self.folder(&profile.id)?.join(PROFILE_FILE).exists() {
+        self.save(&profile)?;
+      }
+    }
+    fs::rename(&legacy.path, legacy.path.with_extension("json.migrated"))?;
+    Ok(())
+  }
+}
+
+impl ProfileStore for FolderStore {
+  fn list(&self) -> Result<Vec<ConfigProfile>, ConfigError> {
+    let entries = match fs::read_dir(&self.root) {
+      Ok(entries) => entries,
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+      Err(e) => return Err(e.into()),
+    };
+    let mut profiles = Vec::new();
+    for entry in entries.flatten() {
+      let path = entry.path().join(PROFILE_FILE);
+      if path.is_file() {
+        profiles.push(serde_json::from_str::<ConfigProfile>(&fs::read_to_string(&path)?)?);
+      }
+    }
+    profiles.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+    Ok(profiles)
+  }
+
+  fn save(&self, profile: &ConfigProfile) -> Result<(), ConfigError> {
+    let folder = self.folder(&profile.id)?;
+    fs::create_dir_all(&folder)?;
+    fs::write(folder.join(PROFILE_FILE), serde_json::to_string_pretty(profile)?)?;
+    Ok(())
+  }
+
+  fn delete(&self, id: &str) -> Result<(), ConfigError> {
+    let folder = self.folder(id)?;
+    if !folder.join(PROFILE_FILE).exists() {
+      return Err(ConfigError::NotFound(id.to_string()));
+    }
+    fs::remove_dir_all(folder)?;
+    Ok(())
+  }
+
+  fn profile_dir(&self, id: &str) -> Result<Option<PathBuf>, ConfigError> {
+    Ok(Some(self.folder(id)?))
+  }
+}
+
+fn profile_store(game_id: Option<&str>) -> Result<Box<dyn ProfileStore>, ConfigError> {
   let dir = match game_id {
     Some(id) => profiles_dir_for_game(id)?,
     None => profiles_dir_for_active()?,
   };
-  Ok(dir.join("configarc_profiles.json"))
+  let store = FolderStore::new(dir.join(PROFILES_DIR));
+  store.migrate_from(&JsonFileStore::new(dir.join(LEGACY_PROFILES_FILE)))?;
+  Ok(Box::new(store))
 }
 
 pub fn list_profiles(game_id: Option<&str>) -> Result<Vec<ConfigProfile>, ConfigError> {
-  let path = profiles_path(game_id)?;
-  if !path.exists() {
-    return Ok(vec![]);
-  }
-  let data = fs::read_to_string(&path)?;
-  if data.trim().is_empty() {
-    return Ok(vec![]);
-  }
-  let profiles: Vec<ConfigProfile> = serde_json::from_str(&data)?;
-  Ok(profiles)
+  profile_store(game_id)?.list()
 }
 
+/// Loads a profile with its `base_profile_id` chain resolved.
 pub fn load_profile(id: &str, game_id: Option<&str>) -> Result<ConfigProfile, ConfigError> {
   let profiles = list_profiles(game_id)?;
-  profiles
-    .into_iter()
+  resolve_profile(&profiles, id, &mut Vec::new())
+}
+
+fn resolve_profile(
+  profiles: &[ConfigProfile],
+  id: &str,
+  chain: &mut Vec<String>,
+) -> Result<ConfigProfile, ConfigError> {
+  if chain.iter().any(|seen| seen == id) {
+    return Err(ConfigError::Parse(format!(
+      "Profile inheritance cycle: {} -> {}",
+      chain.join(" -> "),
+      id
+    )));
+  }
+  if chain.len() >= MAX_PROFILE_INHERITANCE_DEPTH {
+    return Err(ConfigError::Parse(format!("Profile inheritance is nested too deeply at {}", id)));
+  }
+  let profile = profiles
+    .iter()
     .find(|p| p.id == id)
-    .ok_or_else(|| ConfigError::NotFound(format!("Profile {}", id)))
+    .cloned()
+    .ok_or_else(|| ConfigError::NotFound(format!("Profile {}", id)))?;
+  let Some(base_id) = profile.base_profile_id.clone().filter(|b| !b.trim().is_empty()) else {
+    return Ok(profile);
+  };
+  chain.push(id.to_string());
+  let base = resolve_profile(profiles, &base_id, chain)?;
+  merge_profiles(&base, profile)
+}
+
+fn merge_segatools(base: &SegatoolsConfig, overlay: &SegatoolsConfig) -> Result<SegatoolsConfig, ConfigError> {
+  let base_text = render_segatoools_config(base, None)?;
+  let merged_text = render_segatoools_config(overlay, Some(&base_text))?;
+  load_segatoools_config_from_string(&merged_text)
+}
+
+/// Layers `overlay` on top of `base`. The merge runs through the ini writer, so sections and
+/// keys follow the base file order, overlay keys win and keys the overlay comments out stay
+/// commented. Config slots are merged the same way per slot, JSON configs per file name.
+pub fn merge_profiles(base: &ConfigProfile, overlay: ConfigProfile) -> Result<ConfigProfile, ConfigError> {
+  let segatools = merge_segatools(&base.segatools, &overlay.segatools)?;
+  let mut segatools_slots = base.segatools_slots.clone();
+  for (slot, cfg) in &overlay.segatools_slots {
+    let merged = match base.segatools_slots.get(slot) {
+      Some(base_cfg) => merge_segatools(base_cfg, cfg)?,
+      None => cfg.clone(),
+    };
+    segatools_slots.insert(slot.clone(), merged);
+  }
+  let mut json_configs = base.json_configs.clone();
+  json_configs.extend(overlay.json_configs.clone());
+  Ok(ConfigProfile {
+    segatools,
+    segatools_slots,
+    json_configs,
+    ..overlay
+  })
 }
 
 pub fn save_profile(profile: &ConfigProfile) -> Result<(), ConfigError> {
-  let mut profiles = list_profiles(None)?;
-  profiles.retain(|p| p.id != profile.id);
-  profiles.push(profile.clone());
+  profile_store(None)?.save(profile)
+}
 
-  let path = profiles_path(None)?;
-  if let Some(parent) = path.parent() {
-    fs::create_dir_all(parent)?;
+pub fn save_profile_for_game(profile: &ConfigProfile, game_id: &str) -> Result<(), ConfigError> {
+  profile_store(Some(game_id))?.save(profile)
+}
+
+pub fn delete_profile(id: &str) -> Result<(), ConfigError> {
+  profile_store(None)?.delete(id)
+}
+
+fn existing_profile_dir(store: &dyn ProfileStore, id: &str) -> Result<PathBuf, ConfigError> {
+  let dir = store
+    .profile_dir(id)?
+    .ok_or_else(|| ConfigError::Parse("This profile store cannot hold notes or assets".to_string()))?;
+  if !dir.join(PROFILE_FILE).exists() {
+    return Err(ConfigError::NotFound(format!("Profile {}", id)));
+  }
+  Ok(dir)
+}
+
+/// Normalizes an asset target to a forward-slash path inside the game directory.
+fn clean_asset_target(target: &str) -> Result<String, ConfigError> {
+  let normalized = target.trim().replace('\\', "/");
+  let parts: Vec<&str> = normalized.split('/').filter(|p| !p.is_empty() && *p != ".").collect();
+  let escapes = parts.iter().any(|p| *p == ".." || p.contains(':'));
+  if parts.is_empty() || escapes || normalized.starts_with('/') {
+    return Err(ConfigError::Parse(format!("Invalid asset path: {}", target)));
+  }
+  Ok(parts.join("/"))
+}
+
+fn collect_assets(dir: &Path, prefix: &str, out: &mut Vec<ProfileAsset>) -> Result<(), ConfigError> {
+  for entry in fs::read_dir(dir)?.flatten() {
+    let path = entry.path();
+    let name = entry.file_name().to_string_lossy().to_string();
+    let target = if prefix.is_empty() { name } else { format!("{}/{}", prefix, name) };
+    if path.is_dir() {
+      collect_assets(&path, &target, out)?;
+    } else {
+      let size = entry.metadata()?.len();
+      out.push(ProfileAsset { target, source: path, size });
+    }
   }
-  let json = serde_json::to_string_pretty(&profiles)?;
-  fs::write(path, json)?;
   Ok(())
 }
 
-pub fn save_profile_for_game(profile: &ConfigProfile, game_id: &str) -> Result<(), ConfigError> {
-  let mut profiles = list_profiles(Some(game_id))?;
-  profiles.retain(|p| p.id != profile.id);
-  profiles.push(profile.clone());
+/// Assets of a profile and, through `base_profile_id`, of the profiles it inherits from.
+/// A profile's own asset wins over a base asset with the same target.
+pub fn profile_assets(id: &str, game_id: Option<&str>) -> Result<Vec<ProfileAsset>, ConfigError> {
+  let store = profile_store(game_id)?;
+  let profiles = store.list()?;
+  let mut chain = Vec::new();
+  let mut next = Some(id.to_string());
+  while let Some(current) = next {
+    if chain.contains(&current) || chain.len() >= MAX_PROFILE_INHERITANCE_DEPTH {
+      break;
+    }
+    next = profiles
+      .iter()
+      .find(|p| p.id == current)
+      .and_then(|p| p.base_profile_id.clone())
+      .filter(|b| !b.trim().is_empty());
+    chain.push(current);
+  }
 
-  let path = profiles_path(Some(game_id))?;
+  let mut assets: BTreeMap<String, ProfileAsset> = BTreeMap::new();
+  for profile_id in chain.iter().rev() {
+    let Some(dir) = store.profile_dir(profile_id)? else {
+      return Ok(vec![]);
+    };
+    let assets_dir = dir.join(ASSETS_DIR);
+    if !assets_dir.is_dir() {
+      continue;
+    }
+    let mut own = Vec::new();
+    collect_assets(&assets_dir, "", &mut own)?;
+    assets.extend(own.into_iter().map(|asset| (asset.target.to_lowercase(), asset)));
+  }
+  Ok(assets.into_values().collect())
+}
+
+pub fn save_profile_asset(id: &str, game_id: Option<&str>, target: &str, contents: &[u8]) -> Result<ProfileAsset, ConfigError> {
+  let store = profile_store(game_id)?;
+  let target = clean_asset_target(target)?;
+  let path = existing_profile_dir(store.as_ref(), id)?.join(ASSETS_DIR).join(&target);
   if let Some(parent) = path.parent() {
     fs::create_dir_all(parent)?;
   }
-  let json = serde_json::to_string_pretty(&profiles)?;
-  fs::write(path, json)?;
+  fs::write(&path, contents)?;
+  Ok(ProfileAsset { target, source: path, size: contents.len() as u64 })
+}
+
+pub fn delete_profile_asset(id: &str, game_id: Option<&str>, target: &str) -> Result<(), ConfigError> {
+  let store = profile_store(game_id)?;
+  let target = clean_asset_target(target)?;
+  let path = existing_profile_dir(store.as_ref(), id)?.join(ASSETS_DIR).join(&target);
+  if !path.is_file() {
+    return Err(ConfigError::NotFound(format!("Asset {}", target)));
+  }
+  fs::remove_file(path)?;
   Ok(())
 }
 
-pub fn delete_profile(id: &str) -> Result<(), ConfigError> {
-  let mut profiles = list_profiles(None)?;
-  let before = profiles.len();
-  profiles.retain(|p| p.id != id);
-  if profiles.len() == before {
-    return Err(ConfigError::NotFound(id.to_string()));
-  }
-  let path = profiles_path(None)?;
-  let json = serde_json::to_string_pretty(&profiles)?;
-  fs::write(path, json)?;
+pub fn load_profile_notes(id: &str, game_id: Option<&str>) -> Result<String, ConfigError> {
+  let store = profile_store(game_id)?;
+  let path = existing_profile_dir(store.as_ref(), id)?.join(NOTES_FILE);
+  match fs::read_to_string(path) {
+    Ok(notes) => Ok(notes),
+    Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+    Err(e) => Err(e.into()),
+  }
+}
+
+/// Writes `notes.md` for a profile; empty notes remove the file.
+pub fn save_profile_notes(id: &str, game_id: Option<&str>, notes: &str) -> Result<(), ConfigError> {
+  let store = profile_store(game_id)?;
+  let path = existing_profile_dir(store.as_ref(), id)?.join(NOTES_FILE);
+  if notes.trim().is_empty() {
+    if path.exists() {
+      fs::remove_file(path)?;
+    }
+    return Ok(());
+  }
+  fs::write(path, notes)?;
   Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn profile(id: &str, base: Option<&str>, ini: &str) -> ConfigProfile {
+    ConfigProfile {
+      id: id.to_string(),
+      name: id.to_string(),
+      description: None,
+      base_profile_id: base.map(str::to_string),
+      segatools: load_segatoools_config_from_string(ini).unwrap(),
+      segatools_slots: BTreeMap::new(),
+      json_configs: BTreeMap::new(),
+      created_at: String::new(),
+      updated_at: String::new(),
+    }
+  }
+
+  #[test]
+  fn overlay_keys_win_and_base_keys_survive() {
+    let profiles = vec![
+      profile("base", None, "[dns]\ndefault=10.0.0.1\n[keychip]\nid=A69E-01A88888888\n[system]\nfreeplay=0\n"),
+      profile("game", Some("base"), "[system]\nfreeplay=1\n"),
+    ];
+    let resolved = resolve_profile(&profiles, "game", &mut Vec::new()).unwrap();
+    assert_eq!(resolved.segatools.dns.default, "10.0.0.1");
+    assert_eq!(resolved.segatools.keychip.id, "A69E-01A88888888");
+    assert!(resolved.segatools.system.freeplay);
+    assert_eq!(resolved.base_profile_id.as_deref(), Some("base"));
+  }
+
+  #[test]
+  fn inheritance_cycles_are_rejected() {
+    let profiles = vec![
+      profile("a", Some("b"), "[system]\nfreeplay=1\n"),
+      profile("b", Some("a"), "[system]\nfreeplay=0\n"),
+    ];
+    assert!(resolve_profile(&profiles, "a", &mut Vec::new()).is_err());
+  }
+
+  #[test]
+  fn folder_store_migrates_legacy_profiles_and_keeps_assets_inside() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let legacy = JsonFileStore::new(dir.path().join(LEGACY_PROFILES_FILE));
+    legacy.save(&profile("home", None, "[system]\nfreeplay=1\n")).unwrap();
+
+    let store = FolderStore::new(dir.path().join(PROFILES_DIR));
+    store.migrate_from(&legacy).unwrap();
+    assert!(!legacy.path.exists());
+    assert_eq!(store.list().unwrap()[0].id, "home");
+    assert!(store.profile_dir("../home").is_err());
+
+    assert_eq!(clean_asset_target("DEVICE\\aime.txt").unwrap(), "DEVICE/aime.txt");
+    assert!(clean_asset_target("../segatools.ini").is_err());
+    assert!(clean_asset_target("C:/Windows/win.ini").is_err());
+
+    store.delete("home").unwrap();
+    assert!(store.list().unwrap().is_empty());
+  }
+}
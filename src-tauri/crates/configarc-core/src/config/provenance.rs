@@ -0,0 +1,236 @@
+use super::diff::{collect, diff_segatoools_configs};
+use super::paths::segatools_root_for_game_id;
+use super::{load_segatoools_config_from_string, render_segatoools_config, IniUpdater, SegatoolsConfig};
+use crate::error::ConfigError;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+const PROVENANCE_FILE_NAME: &str = "segatools.provenance.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeySource {
+  /// Unchanged from the game's segatools template.
+  Template,
+  /// Edited by hand, in the editor or outside the launcher.
+  User,
+  /// Written by applying a named profile.
+  Profile,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyOrigin {
+  pub source: KeySource,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub profile_id: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub profile_name: Option<String>,
+  pub updated_at: String,
+}
+
+impl KeyOrigin {
+  pub fn user() -> Self {
+    Self {
+      source: KeySource::User,
+      profile_id: None,
+      profile_name: None,
+      updated_at: Utc::now().to_rfc3339(),
+    }
+  }
+
+  pub fn profile(id: &str, name: &str) -> Self {
+    Self {
+      source: KeySource::Profile,
+      profile_id: Some(id.to_string()),
+      profile_name: Some(name.to_string()),
+      updated_at: Utc::now().to_rfc3339(),
+    }
+  }
+}
+
+/// Where a game's segatools.ini keys came from, keyed by `section.key` in lower case. Only
+/// keys that differ from the template are listed; a missing entry means "template".
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigProvenance {
+  #[serde(default)]
+  pub keys: BTreeMap<String, KeyOrigin>,
+}
+
+/// One editor field: its value, the template default and where the value came from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyProvenance {
+  pub section: String,
+  /// Key as written to segatools.ini, e.g. "portNo".
+  pub key: String,
+  /// `None` means the key is absent or commented out.
+  pub value: Option<String>,
+  pub default: Option<String>,
+  pub modified: bool,
+  pub source: KeySource,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub profile_id: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub profile_name: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub updated_at: Option<String>,
+}
+
+fn key_id(section: &str, key: &str) -> String {
+  format!("{}.{}", section.to_lowercase(), key.to_lowercase())
+}
+
+fn template_value(template: &SegatoolsConfig, section: &str, key: &str) -> Option<String> {
+  let values = collect(template).values;
+  values
+    .iter()
+    .find(|((s, k), _)| s.eq_ignore_ascii_case(section) && k.eq_ignore_ascii_case(key))
+    .and_then(|(_, value)| value.clone())
+}
+
+impl ConfigProvenance {
+  /// Attributes every key that changed between `before` and `after` to `origin`. Keys that
+  /// end up back at their template value lose their entry.
+  pub fn record(
+    &mut self,
+    before: &SegatoolsConfig,
+    after: &SegatoolsConfig,
+    template: &SegatoolsConfig,
+    origin: &KeyOrigin,
+  ) {
+    let defaults = collect(template).values;
+    for change in diff_segatoools_configs(before, after) {
+      let default = defaults
+        .get(&(change.section.clone(), change.key.clone()))
+        .cloned()
+        .flatten();
+      let id = key_id(&change.section, &change.key);
+      if change.new == default {
+        self.keys.remove(&id);
+      } else {
+        self.keys.insert(id, origin.clone());
+      }
+    }
+  }
+
+  /// Describes every key `cfg` writes. A key that differs from the template without a
+  /// recorded origin was edited outside the launcher and counts as a user edit.
+  pub fn report(&self, cfg: &SegatoolsConfig, template: &SegatoolsConfig) -> Vec<KeyProvenance> {
+    let current = collect(cfg);
+    let defaults = collect(template).values;
+    current
+      .order
+      .into_iter()
+      .map(|(section, key)| {
+        let value = current.values.get(&(section.clone(), key.clone())).cloned().flatten();
+        let default = defaults.get(&(section.clone(), key.clone())).cloned().flatten();
+        let modified = value != default;
+        let origin = self.keys.get(&key_id(&section, &key)).filter(|_| modified);
+        KeyProvenance {
+          source: match origin {
+            Some(origin) => origin.source,
+            None if modified => KeySource::User,
+            None => KeySource::Template,
+          },
+          profile_id: origin.and_then(|o| o.profile_id.clone()),
+          profile_name: origin.and_then(|o| o.profile_name.clone()),
+          updated_at: origin.map(|o| o.updated_at.clone()),
+          section,
+          key,
+          value,
+          default,
+          modified,
+        }
+      })
+      .collect()
+  }
+
+  pub fn forget(&mut self, section: &str, key: &str) {
+    self.keys.remove(&key_id(section, key));
+  }
+}
+
+/// Puts a single key back to its template value, or comments it out when the template
+/// leaves it unset. Every other key of `cfg` is kept.
+pub fn reset_key_to_template(
+  cfg: &SegatoolsConfig,
+  template: &SegatoolsConfig,
+  section: &str,
+  key: &str,
+) -> Result<SegatoolsConfig, ConfigError> {
+  let rendered = render_segatoools_config(cfg, None)?;
+  let mut updater = IniUpdater::new(&rendered);
+  match template_value(template, section, key) {
+    Some(value) => updater.set(section, key, &value),
+    None => updater.comment_out(section, key),
+  }
+  let mut reset = load_segatoools_config_from_string(&updater.render())?;
+  // Sections the config manages stay managed even when the reset leaves one empty.
+  reset.present_sections = cfg.present_sections.clone();
+  Ok(reset)
+}
+
+pub fn provenance_path_for_game_id(game_id: &str) -> PathBuf {
+  segatools_root_for_game_id(game_id).join(PROVENANCE_FILE_NAME)
+}
+
+pub fn load_provenance(game_id: &str) -> Result<ConfigProvenance, ConfigError> {
+  let path = provenance_path_for_game_id(game_id);
+  if !path.exists() {
+    return Ok(ConfigProvenance::default());
+  }
+  let data = fs::read_to_string(&path)?;
+  Ok(serde_json::from_str(&data)?)
+}
+
+pub fn save_provenance(game_id: &str, provenance: &ConfigProvenance) -> Result<(), ConfigError> {
+  let path = provenance_path_for_game_id(game_id);
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent)?;
+  }
+  fs::write(&path, serde_json::to_string_pretty(provenance)?)?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const TEMPLATE: &str = "[dns]\ndefault=127.0.0.1\n[system]\nenable=1\nfreeplay=0\n";
+
+  #[test]
+  fn tracks_origins_and_resets_single_keys() {
+    let template = load_segatoools_config_from_string(TEMPLATE).unwrap();
+    let mut provenance = ConfigProvenance::default();
+
+    let mut profiled = template.clone();
+    profiled.dns.default = "10.0.0.2".to_string();
+    provenance.record(&template, &profiled, &template, &KeyOrigin::profile("home", "Home"));
+    let mut edited = profiled.clone();
+    edited.system.freeplay = true;
+    provenance.record(&profiled, &edited, &template, &KeyOrigin::user());
+    assert_eq!(provenance.keys.len(), 2);
+
+    let report = provenance.report(&edited, &template);
+    let dns = report.iter().find(|k| k.section == "dns" && k.key == "default").unwrap();
+    assert_eq!((dns.source, dns.profile_name.as_deref()), (KeySource::Profile, Some("Home")));
+    assert_eq!(dns.default.as_deref(), Some("127.0.0.1"));
+    let freeplay = report.iter().find(|k| k.key == "freeplay").unwrap();
+    assert!(freeplay.modified);
+    assert_eq!(freeplay.source, KeySource::User);
+    let enable = report.iter().find(|k| k.section == "system" && k.key == "enable").unwrap();
+    assert!(!enable.modified);
+    assert_eq!(enable.source, KeySource::Template);
+
+    let reset = reset_key_to_template(&edited, &template, "dns", "default").unwrap();
+    assert_eq!(reset.dns.default, "127.0.0.1");
+    assert!(reset.system.freeplay);
+    provenance.record(&edited, &reset, &template, &KeyOrigin::user());
+    assert!(!provenance.keys.contains_key("dns.default"));
+    assert!(provenance.keys.contains_key("system.freeplay"));
+  }
+}
@@ -0,0 +1,122 @@
+use super::segatools::{DnsConfig, GfxConfig, SegatoolsConfig};
+use crate::error::ShareError;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+
+/// Leads every QR backup; the digit is the format version.
+pub const QR_PREFIX: &str = "cfgarcq1.";
+/// Bytes a version 40 QR code holds at the lowest error correction level in byte mode.
+pub const MAX_QR_PAYLOAD_LEN: usize = 2953;
+/// Card number digits kept in a backup, enough to tell cards apart on the other machine.
+const CARD_SUFFIX_LEN: usize = 4;
+const MAX_BACKUP_BYTES: u64 = 64 * 1024;
+
+/// A saved card without its access code: the name and the last digits of the number.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CardAlias {
+  pub name: String,
+  pub number_suffix: String,
+}
+
+/// The settings a QR backup carries between machines. Keychip IDs, card numbers and local
+/// paths never go in, so a photographed code gives nothing away.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QrBackup {
+  pub dns: DnsConfig,
+  pub gfx: GfxConfig,
+  #[serde(default)]
+  pub cards: Vec<CardAlias>,
+}
+
+impl QrBackup {
+  /// Backup of `cfg` and of `cards`, given as `(name, number)` pairs.
+  pub fn new<'a>(cfg: &SegatoolsConfig, cards: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+    let cards = cards
+      .into_iter()
+      .map(|(name, number)| {
+        let digits: Vec<char> = number.chars().filter(|c| c.is_ascii_digit()).collect();
+        CardAlias {
+          name: name.trim().to_string(),
+          number_suffix: digits[digits.len().saturating_sub(CARD_SUFFIX_LEN)..].iter().collect(),
+        }
+      })
+      .collect();
+    Self {
+      dns: cfg.dns.clone(),
+      gfx: cfg.gfx.clone(),
+      cards,
+    }
+  }
+}
+
+/// `backup` as deflated, URL-safe text behind [`QR_PREFIX`]. Fails when the result would not
+/// fit in one QR code, which only a very long card list gets to.
+pub fn encode_qr_backup(backup: &QrBackup) -> Result<String, ShareError> {
+  let json = serde_json::to_vec(backup).map_err(|_| ShareError::Malformed)?;
+  let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+  encoder.write_all(&json)?;
+  let payload = format!("{}{}", QR_PREFIX, URL_SAFE_NO_PAD.encode(encoder.finish()?));
+  if payload.len() > MAX_QR_PAYLOAD_LEN {
+    return Err(ShareError::TooLarge(MAX_QR_PAYLOAD_LEN));
+  }
+  Ok(payload)
+}
+
+/// Reverses [`encode_qr_backup`]. Accepts what a scanner reads, surrounding whitespace included.
+pub fn decode_qr_backup(payload: &str) -> Result<QrBackup, ShareError> {
+  let encoded = payload.trim().strip_prefix(QR_PREFIX).ok_or(ShareError::Malformed)?;
+  let compressed = URL_SAFE_NO_PAD.decode(encoded).map_err(|_| ShareError::Malformed)?;
+  let mut json = Vec::new();
+  DeflateDecoder::new(compressed.as_slice())
+    .take(MAX_BACKUP_BYTES + 1)
+    .read_to_end(&mut json)
+    .map_err(|_| ShareError::Malformed)?;
+  if json.len() as u64 > MAX_BACKUP_BYTES {
+    return Err(ShareError::Malformed);
+  }
+  serde_json::from_slice(&json).map_err(|_| ShareError::Malformed)
+}
+
+/// Copies the `[dns]` and `[gfx]` sections of `backup` onto `cfg`.
+pub fn apply_qr_backup(cfg: &mut SegatoolsConfig, backup: &QrBackup) {
+  cfg.dns = backup.dns.clone();
+  cfg.gfx = backup.gfx.clone();
+  for section in ["dns", "gfx"] {
+    if !cfg.present_sections.is_empty() && !cfg.present_sections.iter().any(|s| s == section) {
+      cfg.present_sections.push(section.to_string());
+    }
+    cfg.commented_keys.retain(|k| !k.to_lowercase().starts_with(&format!("{}.", section)));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn round_trips_without_card_numbers() {
+    let mut cfg = SegatoolsConfig::default();
+    cfg.dns.default = "10.0.0.2".to_string();
+    cfg.gfx.windowed = true;
+    cfg.keychip.id = "A69E-01A88888888".to_string();
+    let backup = QrBackup::new(&cfg, [("Main card", "0123 4567 8901 2345 6789")]);
+    assert_eq!(backup.cards[0].number_suffix, "6789");
+
+    let payload = encode_qr_backup(&backup).unwrap();
+    assert!(payload.starts_with(QR_PREFIX) && payload.len() <= MAX_QR_PAYLOAD_LEN);
+    let decoded = decode_qr_backup(&format!(" {}\n", payload)).unwrap();
+    assert_eq!(decoded.dns.default, "10.0.0.2");
+    assert_eq!(decoded.cards, backup.cards);
+
+    let mut target = SegatoolsConfig::default();
+    let keychip_before = target.keychip.id.clone();
+    apply_qr_backup(&mut target, &decoded);
+    assert!(target.gfx.windowed);
+    assert_eq!(target.keychip.id, keychip_before);
+    assert!(matches!(decode_qr_backup("cfgarcq1.!!"), Err(ShareError::Malformed)));
+  }
+}
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SegatoolsConfig {
   pub aimeio: AimeioConfig,
@@ -37,6 +37,14 @@ pub struct SegatoolsConfig {
   pub slider: SliderConfig,
   pub ir: IrConfig,
   #[serde(default)]
+  pub cmio: CmIoConfig,
+  #[serde(default)]
+  pub printer: PrinterConfig,
+  #[serde(default)]
+  pub mercuryio: MercuryIoConfig,
+  #[serde(default)]
+  pub divaio: DivaIoConfig,
+  #[serde(default)]
   pub present_sections: Vec<String>,
   #[serde(default)]
   pub commented_keys: Vec<String>,
@@ -44,61 +52,12 @@ pub struct SegatoolsConfig {
   pub present_keys: Vec<String>,
 }
 
-impl Default for SegatoolsConfig {
-  fn default() -> Self {
-    SegatoolsConfig {
-      present_sections: vec![],
-      commented_keys: vec![],
-      aimeio: AimeioConfig::default(),
-      aime: AimeConfig::default(),
-      vfd: VfdConfig::default(),
-      amvideo: AmvideoConfig::default(),
-      clock: ClockConfig::default(),
-      dns: DnsConfig::default(),
-      ds: DsConfig::default(),
-      eeprom: EepromConfig::default(),
-      gpio: GpioConfig::default(),
-      gfx: GfxConfig::default(),
-      hwmon: HwmonConfig::default(),
-      jvs: JvsConfig::default(),
-      io4: Io4Config::default(),
-      keychip: KeychipConfig::default(),
-      netenv: NetenvConfig::default(),
-      pcbid: PcbidConfig::default(),
-      sram: SramConfig::default(),
-      vfs: VfsConfig::default(),
-      epay: EpayConfig::default(),
-      openssl: OpensslConfig::default(),
-      system: SystemConfig::default(),
-      led15070: Led15070Config::default(),
-      unity: UnityConfig::default(),
-      mai2io: Mai2IoConfig::default(),
-      chuniio: ChuniIoConfig::default(),
-      mu3io: Mu3IoConfig::default(),
-      button: ButtonConfig::default(),
-      touch: TouchConfig::default(),
-      led15093: Led15093Config::default(),
-      led: LedConfig::default(),
-      io3: Io3Config::default(),
-      slider: SliderConfig::default(),
-      ir: IrConfig::default(),
-      present_keys: vec![],
-    }
-  }
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Mai2IoConfig {
   pub path: String,
 }
 
-impl Default for Mai2IoConfig {
-  fn default() -> Self {
-    Self { path: String::new() }
-  }
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ButtonConfig {
@@ -173,19 +132,13 @@ impl Default for TouchConfig {
   }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AimeioConfig {
   /// Path to third-party AIME IO driver. Empty uses built-in emulation.
   pub path: String,
 }
 
-impl Default for AimeioConfig {
-  fn default() -> Self {
-    Self { path: String::new() }
-  }
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AimeConfig {
@@ -776,7 +729,7 @@ impl Default for LedConfig {
   }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ChuniIoConfig {
   pub path: String,
@@ -784,30 +737,12 @@ pub struct ChuniIoConfig {
   pub path64: String,
 }
 
-impl Default for ChuniIoConfig {
-  fn default() -> Self {
-    Self {
-      path: String::new(),
-      path32: String::new(),
-      path64: String::new(),
-    }
-  }
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Mu3IoConfig {
   pub path: String,
 }
 
-impl Default for Mu3IoConfig {
-  fn default() -> Self {
-    Self {
-      path: String::new(),
-    }
-  }
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Io3Config {
@@ -858,7 +793,7 @@ impl Default for SliderConfig {
   }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct IrConfig {
   pub ir1: u32,
@@ -869,10 +804,47 @@ pub struct IrConfig {
   pub ir6: u32,
 }
 
-impl Default for IrConfig {
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CmIoConfig {
+  pub path: String,
+}
+
+/// CardMaker's card printer emulation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrinterConfig {
+  pub enable: bool,
+  pub serial_no: String,
+  pub main_fw_path: String,
+  pub dsp_fw_path: String,
+  pub param_fw_path: String,
+  /// Folder the printed card images are written to.
+  pub printer_out_path: String,
+}
+
+impl Default for PrinterConfig {
   fn default() -> Self {
     Self {
-      ir1: 0, ir2: 0, ir3: 0, ir4: 0, ir5: 0, ir6: 0,
+      enable: true,
+      serial_no: "5A-A123".to_string(),
+      main_fw_path: "DEVICE\\printer_main_fw.bin".to_string(),
+      dsp_fw_path: "DEVICE\\printer_dsp_fw.bin".to_string(),
+      param_fw_path: "DEVICE\\printer_param_fw.bin".to_string(),
+      printer_out_path: "DEVICE\\print".to_string(),
     }
   }
 }
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MercuryIoConfig {
+  pub path: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DivaIoConfig {
+  pub path: String,
+}
+
@@ -0,0 +1,106 @@
+use crate::error::ShareError;
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use std::io::{Read, Write};
+
+/// Leads every share blob; the digit is the format version and is bound into the ciphertext.
+pub const SHARE_PREFIX: &str = "cfgarc1.";
+pub const MIN_PASSPHRASE_LEN: usize = 8;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+// Argon2id at 19 MiB and two passes, the OWASP baseline; about a quarter second per import.
+const KDF_MEMORY_KIB: u32 = 19 * 1024;
+const KDF_PASSES: u32 = 2;
+/// Upper bound for a decompressed bundle, so a crafted blob cannot inflate without limit.
+const MAX_BUNDLE_BYTES: u64 = 1024 * 1024;
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], ShareError> {
+  let params = Params::new(KDF_MEMORY_KIB, KDF_PASSES, 1, Some(32)).map_err(|e| ShareError::Crypto(e.to_string()))?;
+  let mut key = [0u8; 32];
+  Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+    .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+    .map_err(|e| ShareError::Crypto(e.to_string()))?;
+  Ok(key)
+}
+
+fn cipher(passphrase: &str, salt: &[u8]) -> Result<Aes256Gcm, ShareError> {
+  let key = derive_key(passphrase, salt)?;
+  Aes256Gcm::new_from_slice(&key).map_err(|e| ShareError::Crypto(e.to_string()))
+}
+
+/// Compresses `bundle` and encrypts it under `passphrase`. The result is URL-safe, so it can
+/// be pasted as-is or carried in the fragment of a link, which browsers never send to a server.
+pub fn seal_share(bundle: &[u8], passphrase: &str) -> Result<String, ShareError> {
+  if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+    return Err(ShareError::WeakPassphrase(MIN_PASSPHRASE_LEN));
+  }
+  let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+  encoder.write_all(bundle)?;
+  let compressed = encoder.finish()?;
+
+  let mut salt = [0u8; SALT_LEN];
+  let mut nonce = [0u8; NONCE_LEN];
+  getrandom::getrandom(&mut salt).map_err(|e| ShareError::Crypto(e.to_string()))?;
+  getrandom::getrandom(&mut nonce).map_err(|e| ShareError::Crypto(e.to_string()))?;
+  let sealed = cipher(passphrase, &salt)?
+    .encrypt(
+      Nonce::from_slice(&nonce),
+      Payload { msg: &compressed, aad: SHARE_PREFIX.as_bytes() },
+    )
+    .map_err(|e| ShareError::Crypto(e.to_string()))?;
+
+  let mut raw = Vec::with_capacity(SALT_LEN + NONCE_LEN + sealed.len());
+  raw.extend_from_slice(&salt);
+  raw.extend_from_slice(&nonce);
+  raw.extend_from_slice(&sealed);
+  Ok(format!("{}{}", SHARE_PREFIX, URL_SAFE_NO_PAD.encode(raw)))
+}
+
+/// Reverses [`seal_share`]. Accepts the bare blob or any link whose fragment carries it.
+pub fn open_share(share: &str, passphrase: &str) -> Result<Vec<u8>, ShareError> {
+  let share = share.trim();
+  let blob = share.rsplit_once('#').map(|(_, fragment)| fragment).unwrap_or(share);
+  let encoded = blob.strip_prefix(SHARE_PREFIX).ok_or(ShareError::Malformed)?;
+  let raw = URL_SAFE_NO_PAD.decode(encoded).map_err(|_| ShareError::Malformed)?;
+  if raw.len() < SALT_LEN + NONCE_LEN + TAG_LEN {
+    return Err(ShareError::Malformed);
+  }
+  let (salt, rest) = raw.split_at(SALT_LEN);
+  let (nonce, sealed) = rest.split_at(NONCE_LEN);
+  let compressed = cipher(passphrase, salt)?
+    .decrypt(Nonce::from_slice(nonce), Payload { msg: sealed, aad: SHARE_PREFIX.as_bytes() })
+    .map_err(|_| ShareError::Decrypt)?;
+
+  let mut bundle = Vec::new();
+  DeflateDecoder::new(compressed.as_slice())
+    .take(MAX_BUNDLE_BYTES + 1)
+    .read_to_end(&mut bundle)?;
+  if bundle.len() as u64 > MAX_BUNDLE_BYTES {
+    return Err(ShareError::Malformed);
+  }
+  Ok(bundle)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn round_trips_through_links_and_rejects_wrong_passphrases() {
+    let bundle = br#"{"name":"Home","segatools":{"dns":{"default":"10.0.0.2"}}}"#;
+    let share = seal_share(bundle, "correct horse").unwrap();
+    assert!(share.starts_with(SHARE_PREFIX));
+    assert!(!share.contains("10.0.0.2"));
+
+    let link = format!("https://example.invalid/profile#{}", share);
+    assert_eq!(open_share(&link, "correct horse").unwrap(), bundle);
+    assert!(matches!(open_share(&share, "wrong horse"), Err(ShareError::Decrypt)));
+    assert!(matches!(open_share("cfgarc1.AAAA", "correct horse"), Err(ShareError::Malformed)));
+    assert!(matches!(seal_share(bundle, "short"), Err(ShareError::WeakPassphrase(_))));
+  }
+}
@@ -0,0 +1,175 @@
+//! Named segatools.ini slots. Some setups run the two halves of a game with different
+//! configs, e.g. a 64-bit amdaemon next to a 32-bit game; each extra config lives beside
+//! segatools.ini as `segatools.<slot>.ini` in the game's segatools root.
+
+use super::paths::{segatoools_path_for_game_id, segatools_root_for_game_id};
+use crate::error::ConfigError;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The game's own segatools.ini.
+pub const DEFAULT_SLOT: &str = "default";
+/// Slot handed to amdaemon when a launch starts it before the game.
+pub const AMDAEMON_SLOT: &str = "amdaemon";
+/// Names that would collide with files the launcher writes next to segatools.ini.
+const RESERVED_SLOTS: &[&str] = &["launch"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigSlot {
+  pub name: String,
+  pub path: String,
+  pub exists: bool,
+}
+
+/// True for no slot, a blank name or `default`: the game's own segatools.ini.
+pub fn is_default(slot: Option<&str>) -> bool {
+  slot.map(str::trim).is_none_or(|s| s.is_empty() || s.eq_ignore_ascii_case(DEFAULT_SLOT))
+}
+
+/// Lower-cased slot name, or an error for names that cannot be part of a file name.
+pub fn clean_slot_name(name: &str) -> Result<String, ConfigError> {
+  let name = name.trim().to_ascii_lowercase();
+  let valid = !name.is_empty()
+    && name.len() <= 32
+    && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+    && !RESERVED_SLOTS.contains(&name.as_str());
+  if !valid {
+    return Err(ConfigError::Parse(format!("Invalid config slot name: {}", name)));
+  }
+  Ok(name)
+}
+
+fn slot_file_name(slot: &str) -> String {
+  format!("segatools.{}.ini", slot)
+}
+
+/// Slot name of a `segatools.<slot>.ini` file name.
+fn slot_from_file_name(name: &str) -> Option<String> {
+  let lower = name.to_ascii_lowercase();
+  let slot = lower.strip_prefix("segatools.")?.strip_suffix(".ini")?;
+  clean_slot_name(slot).ok().filter(|s| s != DEFAULT_SLOT)
+}
+
+/// Where `slot` lives under a segatools root; `None` and `default` mean segatools.ini.
+pub fn slot_path_in(root: &Path, slot: Option<&str>) -> Result<PathBuf, ConfigError> {
+  if is_default(slot) {
+    return Ok(root.join("segatools.ini"));
+  }
+  Ok(root.join(slot_file_name(&clean_slot_name(slot.unwrap_or_default())?)))
+}
+
+pub fn segatoools_path_for_slot(game_id: &str, slot: Option<&str>) -> Result<PathBuf, ConfigError> {
+  if is_default(slot) {
+    return segatoools_path_for_game_id(game_id);
+  }
+  slot_path_in(&segatools_root_for_game_id(game_id), slot)
+}
+
+/// File names of the extra slots under `root`, sorted by slot name.
+pub fn slot_file_names(root: &Path) -> Vec<String> {
+  let mut names: Vec<String> = fs::read_dir(root)
+    .map(|entries| {
+      entries
+        .flatten()
+        .filter(|entry| entry.path().is_file())
+        .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+        .filter(|name| slot_from_file_name(name).is_some())
+        .collect()
+    })
+    .unwrap_or_default();
+  names.sort_by_key(|name| name.to_ascii_lowercase());
+  names
+}
+
+/// The default slot first, then every extra slot under `root`.
+pub fn list_slots_in(root: &Path) -> Vec<ConfigSlot> {
+  let default = root.join("segatools.ini");
+  let mut slots = vec![ConfigSlot {
+    name: DEFAULT_SLOT.to_string(),
+    path: default.to_string_lossy().into_owned(),
+    exists: default.exists(),
+  }];
+  slots.extend(slot_file_names(root).into_iter().filter_map(|file| {
+    Some(ConfigSlot {
+      name: slot_from_file_name(&file)?,
+      path: root.join(&file).to_string_lossy().into_owned(),
+      exists: true,
+    })
+  }));
+  slots
+}
+
+pub fn list_slots_for_game(game_id: &str) -> Vec<ConfigSlot> {
+  list_slots_in(&segatools_root_for_game_id(game_id))
+}
+
+/// Creates `name` as a copy of the `from` slot (segatools.ini when omitted).
+pub fn create_slot_in(root: &Path, name: &str, from: Option<&str>) -> Result<ConfigSlot, ConfigError> {
+  let name = clean_slot_name(name)?;
+  if name == DEFAULT_SLOT {
+    return Err(ConfigError::Parse("The default slot always exists".to_string()));
+  }
+  let target = slot_path_in(root, Some(&name))?;
+  if target.exists() {
+    return Err(ConfigError::Parse(format!("Config slot {} already exists", name)));
+  }
+  let source = slot_path_in(root, from)?;
+  if !source.exists() {
+    return Err(ConfigError::NotFound(format!("{} not found. Please deploy first.", source.display())));
+  }
+  fs::copy(&source, &target)?;
+  Ok(ConfigSlot {
+    name,
+    path: target.to_string_lossy().into_owned(),
+    exists: true,
+  })
+}
+
+pub fn delete_slot_in(root: &Path, name: &str) -> Result<(), ConfigError> {
+  if is_default(Some(name)) {
+    return Err(ConfigError::Parse("The default slot cannot be deleted".to_string()));
+  }
+  let path = slot_path_in(root, Some(name))?;
+  if !path.exists() {
+    return Err(ConfigError::NotFound(format!("Config slot {} not found", name)));
+  }
+  fs::remove_file(path)?;
+  Ok(())
+}
+
+/// The amdaemon slot under `root`, when the game has one.
+pub fn amdaemon_slot_path(root: &Path) -> Option<PathBuf> {
+  slot_path_in(root, Some(AMDAEMON_SLOT)).ok().filter(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::TempDir;
+
+  #[test]
+  fn slots_live_beside_segatools_ini() {
+    let dir = TempDir::new().unwrap();
+    let root = dir.path();
+    fs::write(root.join("segatools.ini"), "[vfs]\n").unwrap();
+    fs::write(root.join("segatools_launch.ini"), "[vfs]\n").unwrap();
+    assert!(amdaemon_slot_path(root).is_none());
+
+    let slot = create_slot_in(root, "AmDaemon", None).unwrap();
+    assert_eq!(slot.name, "amdaemon");
+    assert_eq!(fs::read_to_string(root.join("segatools.amdaemon.ini")).unwrap(), "[vfs]\n");
+    assert_eq!(amdaemon_slot_path(root), Some(root.join("segatools.amdaemon.ini")));
+    assert!(create_slot_in(root, "amdaemon", None).is_err());
+    assert!(create_slot_in(root, "../x", None).is_err());
+    assert!(create_slot_in(root, "launch", None).is_err());
+
+    let names: Vec<String> = list_slots_in(root).into_iter().map(|s| s.name).collect();
+    assert_eq!(names, ["default", "amdaemon"]);
+    assert_eq!(slot_path_in(root, Some("default")).unwrap(), root.join("segatools.ini"));
+
+    assert!(delete_slot_in(root, DEFAULT_SLOT).is_err());
+    delete_slot_in(root, "amdaemon").unwrap();
+    assert_eq!(list_slots_in(root).len(), 1);
+  }
+}
@@ -615,3 +615,355 @@ right3=0x4C ; L
 leftMenu=0x55   ; U
 rightMenu=0x4F  ; O
 "#;
+
+pub const CM_TEMPLATE: &str = r#"; -----------------------------------------------------------------------------
+; Path settings
+; -----------------------------------------------------------------------------
+
+[vfs]
+; Insert the path to the game AMFS directory here (contains ICF1 and ICF2)
+amfs=
+; Insert the path to the game Option directory here (contains Axxx directories)
+option=
+; Create an empty directory somewhere and insert the path here.
+; This directory may be shared between multiple SEGA games.
+; NOTE: This has nothing to do with Windows %APPDATA%.
+appdata=
+
+; -----------------------------------------------------------------------------
+; Device settings
+; -----------------------------------------------------------------------------
+
+[aime]
+; Enable Aime card reader assembly emulation. Disable to use a real SEGA Aime
+; reader.
+enable=1
+aimePath=DEVICE\aime.txt
+
+; Virtual-key code. If this button is **held** then the emulated IC card reader
+; emulates an IC card in its proximity. A variety of different IC cards can be
+; emulated; the exact choice of card that is emulated depends on the presence or
+; absence of the configured card ID files. Default is the Return key.
+scan=0x0D
+
+[vfd]
+; Enable VFD emulation. Disable to use a real VFD
+; GP1232A02A FUTABA assembly.
+enable=1
+
+; -----------------------------------------------------------------------------
+; Network settings
+; -----------------------------------------------------------------------------
+
+[dns]
+; Insert the hostname or IP address of the server you wish to use here.
+; Note that 127.0.0.1, localhost etc are specifically rejected.
+default=127.0.0.1
+
+[netenv]
+; Simulate an ideal LAN environment. This may interfere with head-to-head play.
+; SEGA games are somewhat picky about their LAN environment, so leaving this
+; setting enabled is recommended.
+enable=1
+
+; -----------------------------------------------------------------------------
+; Board settings
+; -----------------------------------------------------------------------------
+
+[keychip]
+; Keychip serial number. Keychip serials observed in the wild follow this
+; pattern: `A\d{2}(E|X)-(01|20)[ABCDU]\d{8}`.
+id=A69E-01A88888888
+
+; The /24 LAN subnet that the emulated keychip will tell the game to expect.
+; If you disable netenv then you must set this to your LAN's IP subnet, and
+; that subnet must start with 192.168.
+subnet=192.168.162.0
+
+[pcbid]
+; Set the Windows host name. This should be an ALLS MAIN ID, without the
+; hyphen (which is not a valid character in a Windows host name).
+serialNo=ACAE01A99999999
+
+[system]
+; Enable ALLS system settings.
+enable=1
+
+; Enable freeplay mode. This will disable the coin slot and set the game to
+; freeplay.
+freeplay=0
+
+; LAN Install: If multiple machines are present on the same LAN then set 
+; this to 1 on exactly one machine and set this to 0 on all others.
+dipsw1=1
+
+; -----------------------------------------------------------------------------
+; Misc. hook settings
+; -----------------------------------------------------------------------------
+
+[gfx]
+; Enables the graphics hook.
+enable=1
+; Force the game to run windowed.
+windowed=1
+; Add a frame to the game window if running windowed.
+framed=0
+; Select the monitor to run the game on. (Fullscreen only, 0 =primary screen)
+monitor=0
+
+[unity]
+; Enable Unity hook. This will allow you to run custom .NET code before the game
+enable=1
+
+; Path to a .NET DLL that should run before the game. Useful for loading
+; modding frameworks such as BepInEx.
+targetAssembly=
+
+; -----------------------------------------------------------------------------
+; Printer and LED settings
+; -----------------------------------------------------------------------------
+
+[printer]
+; Enable emulation of the CHC-C330 card printer. Printed cards are written to
+; printerOutPath as images.
+enable=1
+serial_no=5A-A123
+mainFwPath=DEVICE\printer_main_fw.bin
+dspFwPath=DEVICE\printer_dsp_fw.bin
+paramFwPath=DEVICE\printer_param_fw.bin
+printerOutPath=DEVICE\print
+
+[led15093]
+; Enable emulation of the 15093-06 controlled lights on the card reader.
+enable=1
+
+; -----------------------------------------------------------------------------
+; Custom IO settings
+; -----------------------------------------------------------------------------
+
+[aimeio]
+; To use a custom card reader IO DLL enter its path here.
+; Leave empty if you want to use Segatools built-in keyboard input.
+path=
+
+[cmio]
+; To use a custom CardMaker IO DLL enter its path here.
+; Leave empty if you want to use Segatools built-in keyboard input.
+path=
+
+; -----------------------------------------------------------------------------
+; Input settings
+; -----------------------------------------------------------------------------
+
+; Keyboard bindings are specified as hexadecimal (prefixed with 0x) or decimal
+; (not prefixed with 0x) virtual-key codes, a list of which can be found here:
+;
+; https://docs.microsoft.com/en-us/windows/win32/inputdev/virtual-key-codes
+
+[io4]
+; Test button virtual-key code. Default is the F1 key.
+test=0x70
+; Service button virtual-key code. Default is the F2 key.
+service=0x71
+; Keyboard button to increment coin counter. Default is the F3 key.
+coin=0x72
+"#;
+
+pub const MERCURY_TEMPLATE: &str = r#"; -----------------------------------------------------------------------------
+; Path settings
+; -----------------------------------------------------------------------------
+
+[vfs]
+; Insert the path to the game AMFS directory here (contains ICF1 and ICF2)
+amfs=
+; Insert the path to the game Option directory here (contains Axxx directories)
+option=
+; Create an empty directory somewhere and insert the path here.
+; This directory may be shared between multiple SEGA games.
+; NOTE: This has nothing to do with Windows %APPDATA%.
+appdata=
+
+; -----------------------------------------------------------------------------
+; Device settings
+; -----------------------------------------------------------------------------
+
+[aime]
+; Enable Aime card reader assembly emulation. Disable to use a real SEGA Aime
+; reader.
+enable=1
+aimePath=DEVICE\aime.txt
+
+; Virtual-key code. If this button is **held** then the emulated IC card reader
+; emulates an IC card in its proximity. A variety of different IC cards can be
+; emulated; the exact choice of card that is emulated depends on the presence or
+; absence of the configured card ID files. Default is the Return key.
+scan=0x0D
+
+; -----------------------------------------------------------------------------
+; Network settings
+; -----------------------------------------------------------------------------
+
+[dns]
+; Insert the hostname or IP address of the server you wish to use here.
+; Note that 127.0.0.1, localhost etc are specifically rejected.
+default=127.0.0.1
+
+[netenv]
+; Simulate an ideal LAN environment. This may interfere with head-to-head play.
+; SEGA games are somewhat picky about their LAN environment, so leaving this
+; setting enabled is recommended.
+enable=1
+
+; -----------------------------------------------------------------------------
+; Board settings
+; -----------------------------------------------------------------------------
+
+[keychip]
+; Keychip serial number. Keychip serials observed in the wild follow this
+; pattern: `A\d{2}(E|X)-(01|20)[ABCDU]\d{8}`.
+id=A69E-01A88888888
+
+; The /24 LAN subnet that the emulated keychip will tell the game to expect.
+; If you disable netenv then you must set this to your LAN's IP subnet, and
+; that subnet must start with 192.168.
+subnet=192.168.162.0
+
+[pcbid]
+; Set the Windows host name. This should be an ALLS MAIN ID, without the
+; hyphen (which is not a valid character in a Windows host name).
+serialNo=ACAE01A99999999
+
+[system]
+; Enable ALLS system settings.
+enable=1
+
+; Enable freeplay mode. This will disable the coin slot and set the game to
+; freeplay.
+freeplay=0
+
+; LAN Install: If multiple machines are present on the same LAN then set 
+; this to 1 on exactly one machine and set this to 0 on all others.
+dipsw1=1
+
+; -----------------------------------------------------------------------------
+; Custom IO settings
+; -----------------------------------------------------------------------------
+
+[aimeio]
+; To use a custom card reader IO DLL enter its path here.
+; Leave empty if you want to use Segatools built-in keyboard input.
+path=
+
+[mercuryio]
+; To use a custom WACCA IO DLL (touch ring and LEDs) enter its path here.
+; Leave empty if you want to use Segatools built-in keyboard/mouse input.
+path=
+
+; -----------------------------------------------------------------------------
+; Input settings
+; -----------------------------------------------------------------------------
+
+; Keyboard bindings are specified as hexadecimal (prefixed with 0x) or decimal
+; (not prefixed with 0x) virtual-key codes, a list of which can be found here:
+;
+; https://docs.microsoft.com/en-us/windows/win32/inputdev/virtual-key-codes
+
+[io4]
+; Test button virtual-key code. Default is the F1 key.
+test=0x70
+; Service button virtual-key code. Default is the F2 key.
+service=0x71
+; Keyboard button to increment coin counter. Default is the F3 key.
+coin=0x72
+"#;
+
+pub const DIVA_TEMPLATE: &str = r#"; -----------------------------------------------------------------------------
+; Path settings
+; -----------------------------------------------------------------------------
+
+[vfs]
+; Insert the path to the game AMFS directory here (contains ICF1 and ICF2)
+amfs=
+; Insert the path to the game Option directory here (contains Axxx directories)
+option=
+; Create an empty directory somewhere and insert the path here.
+; This directory may be shared between multiple SEGA games.
+; NOTE: This has nothing to do with Windows %APPDATA%.
+appdata=
+
+; -----------------------------------------------------------------------------
+; Device settings
+; -----------------------------------------------------------------------------
+
+[aime]
+; Enable Aime card reader assembly emulation. Disable to use a real SEGA Aime
+; reader.
+enable=1
+aimePath=DEVICE\aime.txt
+
+; Virtual-key code. If this button is **held** then the emulated IC card reader
+; emulates an IC card in its proximity. A variety of different IC cards can be
+; emulated; the exact choice of card that is emulated depends on the presence or
+; absence of the configured card ID files. Default is the Return key.
+scan=0x0D
+
+; -----------------------------------------------------------------------------
+; Network settings
+; -----------------------------------------------------------------------------
+
+[dns]
+; Insert the hostname or IP address of the server you wish to use here.
+; Note that 127.0.0.1, localhost etc are specifically rejected.
+default=127.0.0.1
+
+[netenv]
+; Simulate an ideal LAN environment. This may interfere with head-to-head play.
+; SEGA games are somewhat picky about their LAN environment, so leaving this
+; setting enabled is recommended.
+enable=1
+
+; -----------------------------------------------------------------------------
+; Board settings
+; -----------------------------------------------------------------------------
+
+[keychip]
+; Keychip serial number. Keychip serials observed in the wild follow this
+; pattern: `A\d{2}(E|X)-(01|20)[ABCDU]\d{8}`.
+id=A69E-01A88888888
+
+; The /24 LAN subnet that the emulated keychip will tell the game to expect.
+; If you disable netenv then you must set this to your LAN's IP subnet, and
+; that subnet must start with 192.168.
+subnet=192.168.162.0
+
+[pcbid]
+; Set the Windows host name. This should be an ALLS MAIN ID, without the
+; hyphen (which is not a valid character in a Windows host name).
+serialNo=ACAE01A99999999
+
+[system]
+; Enable ALLS system settings.
+enable=1
+
+; Enable freeplay mode. This will disable the coin slot and set the game to
+; freeplay.
+freeplay=0
+
+; LAN Install: If multiple machines are present on the same LAN then set 
+; this to 1 on exactly one machine and set this to 0 on all others.
+dipsw1=1
+
+; -----------------------------------------------------------------------------
+; Custom IO settings
+; -----------------------------------------------------------------------------
+
+[aimeio]
+; To use a custom card reader IO DLL enter its path here.
+; Leave empty if you want to use Segatools built-in keyboard input.
+path=
+
+[divaio]
+; To use a custom Project DIVA IO DLL (buttons and touch slider) enter its
+; path here. Leave empty if you want to use Segatools built-in keyboard input.
+path=
+"#;
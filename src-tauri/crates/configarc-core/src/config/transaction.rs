@@ -0,0 +1,170 @@
+use super::{render_segatoools_for_save, SegatoolsConfig};
+use crate::error::ConfigError;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const STAGED_SUFFIX: &str = "configarc-staged";
+const ROLLBACK_SUFFIX: &str = "configarc-rollback";
+
+/// Stages writes to several config files and applies them together: either every file ends up
+/// with its new contents or all of them keep their previous contents.
+///
+/// New contents are written next to their targets first, so the apply phase only consists of
+/// renames on the same volume.
+#[derive(Default)]
+pub struct FileTransaction {
+  writes: Vec<(PathBuf, Vec<u8>)>,
+}
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+  let mut name = path.file_name().map(|n| n.to_os_string()).unwrap_or_default();
+  name.push(format!(".{}", suffix));
+  path.with_file_name(name)
+}
+
+impl FileTransaction {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.writes.is_empty()
+  }
+
+  /// Queues `contents` for `path`; staging the same path again replaces the earlier contents.
+  pub fn stage(&mut self, path: impl Into<PathBuf>, contents: impl Into<Vec<u8>>) {
+    let path = path.into();
+    let contents = contents.into();
+    match self.writes.iter_mut().find(|(p, _)| *p == path) {
+      Some(existing) => existing.1 = contents,
+      None => self.writes.push((path, contents)),
+    }
+  }
+
+  /// Contents queued for `path`, if any.
+  pub fn staged(&self, path: &Path) -> Option<&[u8]> {
+    self
+      .writes
+      .iter()
+      .find(|(p, _)| p == path)
+      .map(|(_, contents)| contents.as_slice())
+  }
+
+  /// Queues a segatools.ini save, rendered the same way as `save_segatoools_config` against the
+  /// contents already staged for `path` or, failing that, the file on disk.
+  pub fn stage_segatoools(&mut self, path: &Path, cfg: &SegatoolsConfig) -> Result<(), ConfigError> {
+    let base = match self.staged(path) {
+      Some(staged) => String::from_utf8_lossy(staged).to_string(),
+      None if path.exists() => fs::read_to_string(path)?,
+      None => String::new(),
+    };
+    let rendered = render_segatoools_for_save(&base, cfg);
+    self.stage(path, rendered);
+    Ok(())
+  }
+
+  pub fn stage_json(&mut self, path: &Path, value: &Value) -> Result<(), ConfigError> {
+    let pretty = serde_json::to_string_pretty(value)?;
+    self.stage(path, pretty);
+    Ok(())
+  }
+
+  pub fn commit(self) -> Result<(), ConfigError> {
+    // Phase 1: write every staged file beside its target. Nothing is visible yet.
+    let mut staged = Vec::new();
+    for (target, contents) in &self.writes {
+      let result = (|| -> Result<PathBuf, ConfigError> {
+        if let Some(parent) = target.parent() {
+          fs::create_dir_all(parent)?;
+        }
+        let tmp = sibling(target, STAGED_SUFFIX);
+        fs::write(&tmp, contents)?;
+        Ok(tmp)
+      })();
+      match result {
+        Ok(tmp) => staged.push((target.clone(), tmp)),
+        Err(err) => {
+          for (_, tmp) in &staged {
+            let _ = fs::remove_file(tmp);
+          }
+          let _ = fs::remove_file(sibling(target, STAGED_SUFFIX));
+          return Err(err);
+        }
+      }
+    }
+
+    // Phase 2: move originals aside and swap the staged files in.
+    let mut applied: Vec<(PathBuf, Option<PathBuf>)> = Vec::new();
+    for (target, tmp) in &staged {
+      let result = (|| -> Result<Option<PathBuf>, ConfigError> {
+        let rollback = if target.exists() {
+          let rollback = sibling(target, ROLLBACK_SUFFIX);
+          fs::rename(target, &rollback)?;
+          Some(rollback)
+        } else {
+          None
+        };
+        if let Err(err) = fs::rename(tmp, target) {
+          if let Some(rollback) = &rollback {
+            let _ = fs::rename(rollback, target);
+          }
+          return Err(err.into());
+        }
+        Ok(rollback)
+      })();
+      match result {
+        Ok(rollback) => applied.push((target.clone(), rollback)),
+        Err(err) => {
+          for (target, rollback) in applied.iter().rev() {
+            match rollback {
+              Some(rollback) => {
+                let _ = fs::remove_file(target);
+                let _ = fs::rename(rollback, target);
+              }
+              None => {
+                let _ = fs::remove_file(target);
+              }
+            }
+          }
+          for (_, tmp) in &staged {
+            let _ = fs::remove_file(tmp);
+          }
+          return Err(err);
+        }
+      }
+    }
+
+    // Phase 3: the originals are no longer needed.
+    for (_, rollback) in applied {
+      if let Some(rollback) = rollback {
+        let _ = fs::remove_file(rollback);
+      }
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn failed_commit_leaves_every_file_untouched() {
+    let dir = tempfile::tempdir().unwrap();
+    let ini = dir.path().join("segatools.ini");
+    fs::write(&ini, "[system]\nenable=1\n").unwrap();
+    // A file where a parent directory should be makes staging the second write fail.
+    let blocker = dir.path().join("bin");
+    fs::write(&blocker, "").unwrap();
+
+    let mut txn = FileTransaction::new();
+    txn.stage(&ini, "[system]\nenable=0\n");
+    txn.stage(blocker.join("config_common.json"), "{}");
+    assert!(txn.commit().is_err());
+
+    assert_eq!(fs::read_to_string(&ini).unwrap(), "[system]\nenable=1\n");
+    assert!(!sibling(&ini, STAGED_SUFFIX).exists());
+    assert!(!sibling(&ini, ROLLBACK_SUFFIX).exists());
+  }
+}
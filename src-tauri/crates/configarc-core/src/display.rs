@@ -0,0 +1,227 @@
+use crate::config::SegatoolsConfig;
+use crate::platform::{powershell_support, PowerShellSupport};
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+#[cfg(target_os = "windows")]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+const SCREEN_WIDTH_ARG: &str = "-screen-width";
+const SCREEN_HEIGHT_ARG: &str = "-screen-height";
+
+/// Prints one tab-separated line per display attached to the desktop, in the order Windows
+/// numbers them: index, device, adapter, width, height, refresh rate, primary flag.
+/// `EnumDisplaySettings` is the only source of the current refresh rate per display, and
+/// reaching it takes `Add-Type`, so this needs full language mode.
+const MONITOR_SCRIPT: &str = r#"Add-Type -TypeDefinition @'
+using System;
+using System.Runtime.InteropServices;
+public static class ConfigArcDisplays {
+    [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+    public struct DisplayDevice {
+        public int cb;
+        [MarshalAs(UnmanagedType.ByValTStr, SizeConst = 32)] public string DeviceName;
+        [MarshalAs(UnmanagedType.ByValTStr, SizeConst = 128)] public string DeviceString;
+        public int StateFlags;
+        [MarshalAs(UnmanagedType.ByValTStr, SizeConst = 128)] public string DeviceID;
+        [MarshalAs(UnmanagedType.ByValTStr, SizeConst = 128)] public string DeviceKey;
+    }
+    [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+    public struct DevMode {
+        [MarshalAs(UnmanagedType.ByValTStr, SizeConst = 32)] public string dmDeviceName;
+        public short dmSpecVersion, dmDriverVersion, dmSize, dmDriverExtra;
+        public int dmFields, dmPositionX, dmPositionY, dmDisplayOrientation, dmDisplayFixedOutput;
+        public short dmColor, dmDuplex, dmYResolution, dmTTOption, dmCollate;
+        [MarshalAs(UnmanagedType.ByValTStr, SizeConst = 32)] public string dmFormName;
+        public short dmLogPixels;
+        public int dmBitsPerPel, dmPelsWidth, dmPelsHeight, dmDisplayFlags, dmDisplayFrequency;
+        public int dmICMMethod, dmICMIntent, dmMediaType, dmDitherType, dmReserved1, dmReserved2, dmPanningWidth, dmPanningHeight;
+    }
+    [DllImport("user32.dll", CharSet = CharSet.Unicode)]
+    static extern bool EnumDisplayDevices(string device, uint index, ref DisplayDevice info, uint flags);
+    [DllImport("user32.dll", CharSet = CharSet.Unicode)]
+    static extern bool EnumDisplaySettings(string device, int mode, ref DevMode info);
+    public static void Print() {
+        int index = 0;
+        for (uint i = 0; ; i++) {
+            var device = new DisplayDevice();
+            device.cb = Marshal.SizeOf(device);
+            if (!EnumDisplayDevices(null, i, ref device, 0)) break;
+            if ((device.StateFlags & 1) == 0) continue;
+            var mode = new DevMode();
+            mode.dmSize = (short)Marshal.SizeOf(mode);
+            if (!EnumDisplaySettings(device.DeviceName, -1, ref mode)) continue;
+            Console.WriteLine(string.Join("\t", index, device.DeviceName, device.DeviceString, mode.dmPelsWidth, mode.dmPelsHeight, mode.dmDisplayFrequency, (device.StateFlags & 4) != 0));
+            index++;
+        }
+    }
+}
+'@
+[ConfigArcDisplays]::Print()"#;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorInfo {
+    /// What `[gfx] monitor` takes for this display.
+    pub index: u32,
+    /// `\\.\DISPLAY2`.
+    pub device_name: String,
+    /// Adapter driving the display, such as "NVIDIA GeForce GTX 1060".
+    pub adapter: String,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_rate: Option<u32>,
+    pub primary: bool,
+}
+
+impl MonitorInfo {
+    pub fn is_portrait(&self) -> bool {
+        self.height > self.width
+    }
+}
+
+/// A named display setup of a game, e.g. "Portrait cab" on the second monitor. Applying it
+/// writes `[gfx] monitor` and `windowed` and the game's `-screen-width`/`-screen-height`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisplayPreset {
+    pub name: String,
+    pub monitor: u32,
+    #[serde(default)]
+    pub windowed: bool,
+    pub width: u32,
+    pub height: u32,
+}
+
+pub fn validate_display_preset(preset: &DisplayPreset) -> Result<(), String> {
+    if preset.name.trim().is_empty() {
+        return Err("Display preset needs a name".to_string());
+    }
+    if preset.width == 0 || preset.height == 0 {
+        return Err(format!("{}x{} is not a resolution", preset.width, preset.height));
+    }
+    Ok(())
+}
+
+/// Monitors in the script output of [`MONITOR_SCRIPT`]; malformed lines are skipped.
+pub fn parse_monitors(output: &str) -> Vec<MonitorInfo> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.trim_end_matches('\r').split('\t').map(str::trim).collect();
+            let [index, device_name, adapter, width, height, refresh, primary] = fields.as_slice() else {
+                return None;
+            };
+            Some(MonitorInfo {
+                index: index.parse().ok()?,
+                device_name: device_name.to_string(),
+                adapter: adapter.to_string(),
+                width: width.parse().ok()?,
+                height: height.parse().ok()?,
+                // 0 and 1 stand for the hardware default rate.
+                refresh_rate: refresh.parse().ok().filter(|hz| *hz > 1),
+                primary: primary.eq_ignore_ascii_case("true"),
+            })
+        })
+        .collect()
+}
+
+/// Displays attached to the desktop. Empty off Windows or without full-language PowerShell.
+pub fn list_monitors() -> Vec<MonitorInfo> {
+    if powershell_support() != PowerShellSupport::Full {
+        return Vec::new();
+    }
+    let mut cmd = Command::new("powershell");
+    cmd.args(["-NoProfile", "-Command", MONITOR_SCRIPT]);
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(CREATE_NO_WINDOW);
+    match cmd.output() {
+        Ok(output) => parse_monitors(&String::from_utf8_lossy(&output.stdout)),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// `args` with `-screen-width` and `-screen-height` set to `width` and `height`, in place
+/// of any earlier values, whether written as two arguments or as `-screen-width=1080`.
+pub fn set_screen_args(args: &mut Vec<String>, width: u32, height: u32) {
+    let mut previous = std::mem::take(args).into_iter();
+    while let Some(arg) = previous.next() {
+        let flag = arg.split('=').next().unwrap_or_default();
+        if flag.eq_ignore_ascii_case(SCREEN_WIDTH_ARG) || flag.eq_ignore_ascii_case(SCREEN_HEIGHT_ARG) {
+            if !arg.contains('=') {
+                previous.next();
+            }
+            continue;
+        }
+        args.push(arg);
+    }
+    args.extend([
+        SCREEN_WIDTH_ARG.to_string(),
+        width.to_string(),
+        SCREEN_HEIGHT_ARG.to_string(),
+        height.to_string(),
+    ]);
+}
+
+/// Applies `preset` to a game's segatools config and launch arguments. `monitor` and
+/// `windowed` are written even where the file had them missing or commented out.
+pub fn apply_display_preset(cfg: &mut SegatoolsConfig, launch_args: &mut Vec<String>, preset: &DisplayPreset) {
+    cfg.gfx.monitor = preset.monitor;
+    cfg.gfx.windowed = preset.windowed;
+    if !cfg.present_sections.is_empty() && !cfg.present_sections.iter().any(|s| s == "gfx") {
+        cfg.present_sections.push("gfx".to_string());
+    }
+    for key in ["gfx.monitor", "gfx.windowed"] {
+        cfg.commented_keys.retain(|k| !k.eq_ignore_ascii_case(key));
+        if !cfg.present_keys.is_empty() && !cfg.present_keys.iter().any(|k| k == key) {
+            cfg.present_keys.push(key.to_string());
+        }
+    }
+    set_screen_args(launch_args, preset.width, preset.height);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{load_segatoools_config_from_string, render_segatoools_config};
+
+    #[test]
+    fn preset_replaces_screen_args() {
+        let monitors = parse_monitors("0\t\\\\.\\DISPLAY1\tIntel UHD\t1920\t1080\t60\tTrue\r\n1\t\\\\.\\DISPLAY2\tIntel UHD\t1080\t1920\t1\tFalse\r\nbad line\n");
+        assert_eq!(monitors.len(), 2);
+        assert!(monitors[0].primary && !monitors[0].is_portrait());
+        assert!(monitors[1].is_portrait());
+        assert_eq!(monitors[1].refresh_rate, None);
+
+        let preset = DisplayPreset {
+            name: "Portrait cab".to_string(),
+            monitor: 1,
+            windowed: false,
+            width: 1080,
+            height: 1920,
+        };
+        let ini = "[gfx]\nenable=1\nwindowed=1\n;monitor=0\n";
+        let mut cfg = load_segatoools_config_from_string(ini).unwrap();
+        let mut args = vec![
+            "-screen-fullscreen".to_string(),
+            "0".to_string(),
+            "-screen-width".to_string(),
+            "1920".to_string(),
+            "-SCREEN-HEIGHT=1080".to_string(),
+            "-popupwindow".to_string(),
+        ];
+        apply_display_preset(&mut cfg, &mut args, &preset);
+        assert_eq!(cfg.gfx.monitor, 1);
+        assert!(!cfg.gfx.windowed);
+        let rendered = render_segatoools_config(&cfg, Some(ini)).unwrap();
+        let reloaded = load_segatoools_config_from_string(&rendered).unwrap();
+        assert_eq!(reloaded.gfx.monitor, 1, "{}", rendered);
+        assert!(!reloaded.gfx.windowed);
+        assert_eq!(
+            args,
+            ["-screen-fullscreen", "0", "-popupwindow", "-screen-width", "1080", "-screen-height", "1920"]
+        );
+        assert!(validate_display_preset(&DisplayPreset { width: 0, ..preset }).is_err());
+    }
+}
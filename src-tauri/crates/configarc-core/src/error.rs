@@ -12,6 +12,22 @@ pub enum ConfigError {
     NotFound(String),
 }
 
+#[derive(Debug, Error)]
+pub enum ShareError {
+    #[error("Passphrase must be at least {0} characters")]
+    WeakPassphrase(usize),
+    #[error("Not a ConfigArc share link")]
+    Malformed,
+    #[error("Wrong passphrase or damaged share link")]
+    Decrypt,
+    #[error("Too much to fit in a QR code (over {0} bytes)")]
+    TooLarge(usize),
+    #[error("Encryption error: {0}")]
+    Crypto(String),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
 #[derive(Debug, Error)]
 pub enum GameError {
     #[error("IO error: {0}")]
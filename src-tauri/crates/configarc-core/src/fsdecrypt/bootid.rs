@@ -0,0 +1,167 @@
+use std::fmt::Display;
+
+use chrono::{Datelike, NaiveDateTime, Timelike};
+
+#[allow(non_snake_case)]
+pub mod ContainerType {
+    pub const OS: u8 = 0x00;
+    pub const APP: u8 = 0x01;
+    pub const OPTION: u8 = 0x02;
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct Timestamp {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    unk1: u8,
+}
+
+impl Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:<04}{:<02}{:<02}{:<02}{:<02}{:<02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+impl Timestamp {
+    pub fn from_datetime(datetime: &NaiveDateTime) -> Self {
+        Timestamp {
+            year: datetime.year() as u16,
+            month: datetime.month() as u8,
+            day: datetime.day() as u8,
+            hour: datetime.hour() as u8,
+            minute: datetime.minute() as u8,
+            second: datetime.second() as u8,
+            unk1: 0,
+        }
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        out.extend(self.year.to_le_bytes());
+        out.extend([self.month, self.day, self.hour, self.minute, self.second, self.unk1]);
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct Version {
+    pub release: u8,
+    pub minor: u8,
+    pub major: u16,
+}
+
+impl Version {
+    fn write(&self, out: &mut Vec<u8>) {
+        out.extend([self.release, self.minor]);
+        out.extend(self.major.to_le_bytes());
+    }
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub union GameVersion {
+    pub version: Version,
+    pub option: [u8; 4],
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct BootId {
+    pub crc32: u32,
+    pub length: u32,
+    pub signature: [u8; 4],
+    unk1: u8,
+    pub container_type: u8,
+    pub sequence_number: u8,
+    pub use_custom_iv: bool,
+    pub game_id: [u8; 4],
+    pub target_timestamp: Timestamp,
+    pub target_version: GameVersion,
+    pub block_count: u64,
+    pub block_size: u64,
+    pub header_block_count: u64,
+    unk2: u64,
+    pub os_id: [u8; 3],
+    pub os_generation: u8,
+    pub source_timestamp: Timestamp,
+    pub source_version: Version,
+    pub os_version: Version,
+    pub padding: [u8; 8],
+}
+
+pub const BOOTID_SIZE: usize = std::mem::size_of::<BootId>();
+const BOOTID_SIGNATURE: [u8; 4] = *b"BTID";
+
+impl BootId {
+    /// Header of an option container whose body is `block_count - header_block_count` blocks
+    /// of `block_size` bytes, encrypted with the shared option key and IV.
+    pub fn option(
+        game_id: [u8; 4],
+        option_id: [u8; 4],
+        timestamp: Timestamp,
+        block_size: u64,
+        header_block_count: u64,
+        block_count: u64,
+    ) -> Self {
+        let zero_version = Version { release: 0, minor: 0, major: 0 };
+        BootId {
+            crc32: 0,
+            length: BOOTID_SIZE as u32,
+            signature: BOOTID_SIGNATURE,
+            unk1: 0,
+            container_type: ContainerType::OPTION,
+            sequence_number: 0,
+            use_custom_iv: false,
+            game_id,
+            target_timestamp: timestamp,
+            target_version: GameVersion { option: option_id },
+            block_count,
+            block_size,
+            header_block_count,
+            unk2: 0,
+            os_id: [0; 3],
+            os_generation: 0,
+            source_timestamp: timestamp,
+            source_version: zero_version,
+            os_version: zero_version,
+            padding: [0; 8],
+        }
+    }
+
+    /// On-disk form before encryption, with `crc32` computed over every byte after it.
+    pub fn to_bytes(self) -> [u8; BOOTID_SIZE] {
+        let mut out = Vec::with_capacity(BOOTID_SIZE);
+        out.extend(0u32.to_le_bytes());
+        out.extend(self.length.to_le_bytes());
+        out.extend(self.signature);
+        out.extend([self.unk1, self.container_type, self.sequence_number, self.use_custom_iv as u8]);
+        out.extend(self.game_id);
+        self.target_timestamp.write(&mut out);
+        out.extend(unsafe { self.target_version.option });
+        out.extend(self.block_count.to_le_bytes());
+        out.extend(self.block_size.to_le_bytes());
+        out.extend(self.header_block_count.to_le_bytes());
+        out.extend(self.unk2.to_le_bytes());
+        out.extend(self.os_id);
+        out.push(self.os_generation);
+        self.source_timestamp.write(&mut out);
+        self.source_version.write(&mut out);
+        self.os_version.write(&mut out);
+        out.extend(self.padding);
+        out.resize(BOOTID_SIZE, 0);
+
+        let crc32 = crc32fast::hash(&out[4..]);
+        out[..4].copy_from_slice(&crc32.to_le_bytes());
+        let mut bytes = [0u8; BOOTID_SIZE];
+        bytes.copy_from_slice(&out);
+        bytes
+    }
+}
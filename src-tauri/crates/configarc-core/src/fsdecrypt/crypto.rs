@@ -6,6 +6,7 @@ pub const NTFS_HEADER: [u8; 16] = hex!("eb52904e544653202020200010010000");
 pub const EXFAT_HEADER: [u8; 16] = hex!("eb769045584641542020200000000000");
 
 pub type Aes128CbcDec = cbc::Decryptor<aes::Aes128Dec>;
+pub type Aes128CbcEnc = cbc::Encryptor<aes::Aes128Enc>;
 
 #[derive(Clone)]
 pub struct GameKeys {
@@ -0,0 +1,670 @@
+//! Minimal exFAT formatter for option images. Every file and directory gets one contiguous
+//! run of clusters, chained in the FAT, so the image reads like one the upstream tools make.
+
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Timelike, Utc};
+
+const SECTOR_SHIFT: u8 = 9;
+const SECTOR_SIZE: u64 = 1 << SECTOR_SHIFT;
+const CLUSTER_SHIFT: u8 = 3;
+const CLUSTER_SIZE: u64 = SECTOR_SIZE << CLUSTER_SHIFT;
+/// Boot sectors of the main and backup boot regions.
+const BOOT_REGION_SECTORS: u64 = 12;
+/// First FAT sector, past both boot regions.
+const FAT_OFFSET: u64 = 32;
+const FIRST_CLUSTER: u32 = 2;
+const ENTRY_SIZE: usize = 32;
+const NAME_CHARS_PER_ENTRY: usize = 15;
+const MAX_NAME_LEN: usize = 255;
+
+const ENTRY_BITMAP: u8 = 0x81;
+const ENTRY_UPCASE: u8 = 0x82;
+const ENTRY_FILE: u8 = 0x85;
+const ENTRY_STREAM: u8 = 0xC0;
+const ENTRY_NAME: u8 = 0xC1;
+const ATTR_DIRECTORY: u16 = 0x10;
+const ATTR_ARCHIVE: u16 = 0x20;
+/// Stream extension flag: the entry has a cluster allocation, found through the FAT.
+const ALLOCATION_POSSIBLE: u8 = 0x01;
+/// UTC offset field with its valid bit set and an offset of zero.
+const UTC_OFFSET_VALID: u8 = 0x80;
+
+struct Node {
+    name: Vec<u16>,
+    source: PathBuf,
+    modified: SystemTime,
+    /// File length, or the size of a directory's entry area.
+    length: u64,
+    /// `Some` for directories.
+    children: Option<Vec<Node>>,
+    first_cluster: u32,
+}
+
+impl Node {
+    fn entry_count(&self) -> usize {
+        2 + self.name.len().div_ceil(NAME_CHARS_PER_ENTRY)
+    }
+}
+
+fn clusters_for(bytes: u64) -> u64 {
+    bytes.div_ceil(CLUSTER_SIZE)
+}
+
+/// Bytes a directory needs for `entries` 32-byte entries; never less than one cluster.
+fn directory_length(entries: usize) -> u64 {
+    clusters_for((entries.max(1) * ENTRY_SIZE) as u64) * CLUSTER_SIZE
+}
+
+/// Reads `dir` into a tree, sorted by name so the same folder always builds the same image.
+fn scan(dir: &Path) -> Result<Vec<Node>> {
+    let mut entries: Vec<_> = fs::read_dir(dir)?.collect::<std::io::Result<_>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    let mut nodes = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let source = entry.path();
+        let file_name = entry.file_name();
+        let name_str = file_name
+            .to_str()
+            .ok_or_else(|| anyhow!("File name is not valid Unicode: {}", source.display()))?;
+        let name: Vec<u16> = name_str.encode_utf16().collect();
+        if name.len() > MAX_NAME_LEN {
+            return Err(anyhow!("File name is longer than {MAX_NAME_LEN} characters: {}", source.display()));
+        }
+        let meta = fs::metadata(&source)?;
+        let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        let node = if meta.is_dir() {
+            let children = scan(&source)?;
+            let entries = children.iter().map(Node::entry_count).sum();
+            Node {
+                name,
+                source,
+                modified,
+                length: directory_length(entries),
+                children: Some(children),
+                first_cluster: 0,
+            }
+        } else {
+            Node {
+                name,
+                source,
+                modified,
+                length: meta.len(),
+                children: None,
+                first_cluster: 0,
+            }
+        };
+        nodes.push(node);
+    }
+    Ok(nodes)
+}
+
+fn tree_clusters(nodes: &[Node]) -> u64 {
+    nodes
+        .iter()
+        .map(|node| clusters_for(node.length) + node.children.as_deref().map(tree_clusters).unwrap_or(0))
+        .sum()
+}
+
+fn tree_file_bytes(nodes: &[Node]) -> u64 {
+    nodes
+        .iter()
+        .map(|node| match &node.children {
+            Some(children) => tree_file_bytes(children),
+            None => node.length,
+        })
+        .sum()
+}
+
+/// Hands out contiguous cluster runs in order and remembers them for the FAT.
+struct Allocator {
+    next: u32,
+    runs: Vec<(u32, u32)>,
+}
+
+impl Allocator {
+    fn allocate(&mut self, length: u64) -> u32 {
+        let count = clusters_for(length) as u32;
+        if count == 0 {
+            return 0;
+        }
+        let first = self.next;
+        self.next += count;
+        self.runs.push((first, count));
+        first
+    }
+
+    fn assign(&mut self, nodes: &mut [Node]) {
+        for node in nodes {
+            node.first_cluster = self.allocate(node.length);
+            if let Some(children) = node.children.as_mut() {
+                self.assign(children);
+            }
+        }
+    }
+}
+
+/// Up-case table in its compressed form: identity below `a`, `a`-`z` to `A`-`Z`, then
+/// identity for the rest of the BMP. A 0xFFFF entry means "the next N characters map to
+/// themselves".
+fn upcase_table() -> Vec<u8> {
+    let mut table: Vec<u16> = vec![0xFFFF, u16::from(b'a')];
+    table.extend(u16::from(b'A')..=u16::from(b'Z'));
+    table.extend([0xFFFF, (0x1_0000 - 0x7B) as u16]);
+    table.iter().flat_map(|unit| unit.to_le_bytes()).collect()
+}
+
+fn upcase(unit: u16) -> u16 {
+    if (u16::from(b'a')..=u16::from(b'z')).contains(&unit) {
+        unit - 0x20
+    } else {
+        unit
+    }
+}
+
+/// The rotate-and-add checksum exFAT uses for the boot region and the up-case table.
+fn checksum32(bytes: &[u8], skip: &[usize]) -> u32 {
+    bytes
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !skip.contains(i))
+        .fold(0u32, |sum, (_, byte)| sum.rotate_right(1).wrapping_add(u32::from(*byte)))
+}
+
+fn checksum16(bytes: &[u8], skip: &[usize]) -> u16 {
+    bytes
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !skip.contains(i))
+        .fold(0u16, |sum, (_, byte)| sum.rotate_right(1).wrapping_add(u16::from(*byte)))
+}
+
+fn name_hash(name: &[u16]) -> u16 {
+    name.iter().map(|unit| upcase(*unit)).fold(0u16, |hash, unit| {
+        let hash = hash.rotate_right(1).wrapping_add(unit & 0xFF);
+        hash.rotate_right(1).wrapping_add(unit >> 8)
+    })
+}
+
+/// exFAT timestamp (DOS date and time) and its 10 ms increment, in UTC.
+fn exfat_timestamp(time: SystemTime) -> (u32, u8) {
+    let time: DateTime<Utc> = time.into();
+    let year = (time.year().clamp(1980, 2107) - 1980) as u32;
+    let stamp = (year << 25)
+        | (time.month() << 21)
+        | (time.day() << 16)
+        | (time.hour() << 11)
+        | (time.minute() << 5)
+        | (time.second() / 2);
+    let increment = (time.second() % 2) * 100 + time.nanosecond().min(999_999_999) / 10_000_000;
+    (stamp, increment as u8)
+}
+
+fn put(buf: &mut [u8], offset: usize, bytes: &[u8]) {
+    buf[offset..offset + bytes.len()].copy_from_slice(bytes);
+}
+
+/// File, stream extension and file name entries for one node, with the set checksum.
+fn entry_set(node: &Node) -> Vec<u8> {
+    let mut set = vec![0u8; node.entry_count() * ENTRY_SIZE];
+    let (stamp, increment) = exfat_timestamp(node.modified);
+    let attributes = if node.children.is_some() { ATTR_DIRECTORY } else { ATTR_ARCHIVE };
+
+    set[0] = ENTRY_FILE;
+    set[1] = (node.entry_count() - 1) as u8;
+    put(&mut set, 4, &attributes.to_le_bytes());
+    for offset in [8, 12, 16] {
+        put(&mut set, offset, &stamp.to_le_bytes());
+    }
+    set[20] = increment;
+    set[21] = increment;
+    set[22..25].fill(UTC_OFFSET_VALID);
+
+    let stream = &mut set[ENTRY_SIZE..2 * ENTRY_SIZE];
+    stream[0] = ENTRY_STREAM;
+    // An empty file owns no clusters, and readers reject a chain that starts at cluster 0.
+    stream[1] = if node.first_cluster == 0 { 0 } else { ALLOCATION_POSSIBLE };
+    stream[3] = node.name.len() as u8;
+    put(stream, 4, &name_hash(&node.name).to_le_bytes());
+    put(stream, 8, &node.length.to_le_bytes());
+    put(stream, 20, &node.first_cluster.to_le_bytes());
+    put(stream, 24, &node.length.to_le_bytes());
+
+    for (i, chunk) in node.name.chunks(NAME_CHARS_PER_ENTRY).enumerate() {
+        let entry = &mut set[(2 + i) * ENTRY_SIZE..(3 + i) * ENTRY_SIZE];
+        entry[0] = ENTRY_NAME;
+        for (j, unit) in chunk.iter().enumerate() {
+            put(entry, 2 + j * 2, &unit.to_le_bytes());
+        }
+    }
+
+    let checksum = checksum16(&set, &[2, 3]);
+    put(&mut set, 2, &checksum.to_le_bytes());
+    set
+}
+
+struct Geometry {
+    volume_length: u64,
+    fat_length: u64,
+    heap_offset: u64,
+    cluster_count: u32,
+    root_cluster: u32,
+}
+
+impl Geometry {
+    fn cluster_offset(&self, cluster: u32) -> u64 {
+        (self.heap_offset + (u64::from(cluster - FIRST_CLUSTER) << CLUSTER_SHIFT)) * SECTOR_SIZE
+    }
+}
+
+/// Main boot sector, eight extended boot sectors, OEM and reserved sectors, and the checksum
+/// sector: the 12-sector region written twice at the start of the volume.
+fn boot_region(geometry: &Geometry, serial: u32) -> Vec<u8> {
+    let sector = SECTOR_SIZE as usize;
+    let mut region = vec![0u8; BOOT_REGION_SECTORS as usize * sector];
+    put(&mut region, 0, &[0xEB, 0x76, 0x90]);
+    put(&mut region, 3, b"EXFAT   ");
+    put(&mut region, 72, &geometry.volume_length.to_le_bytes());
+    put(&mut region, 80, &(FAT_OFFSET as u32).to_le_bytes());
+    put(&mut region, 84, &(geometry.fat_length as u32).to_le_bytes());
+    put(&mut region, 88, &(geometry.heap_offset as u32).to_le_bytes());
+    put(&mut region, 92, &geometry.cluster_count.to_le_bytes());
+    put(&mut region, 96, &geometry.root_cluster.to_le_bytes());
+    put(&mut region, 100, &serial.to_le_bytes());
+    put(&mut region, 104, &0x0100u16.to_le_bytes());
+    region[108] = SECTOR_SHIFT;
+    region[109] = CLUSTER_SHIFT;
+    region[110] = 1;
+    region[111] = 0x80;
+    // Every cluster is allocated: the volume is sized to fit its contents exactly.
+    region[112] = 100;
+    put(&mut region, 510, &[0x55, 0xAA]);
+    for index in 1..=8 {
+        put(&mut region, (index + 1) * sector - 4, &[0x00, 0x00, 0x55, 0xAA]);
+    }
+    // VolumeFlags and PercentInUse change at runtime and are left out of the checksum.
+    let checksum = checksum32(&region[..11 * sector], &[106, 107, 112]);
+    for offset in (11 * sector..12 * sector).step_by(4) {
+        put(&mut region, offset, &checksum.to_le_bytes());
+    }
+    region
+}
+
+fn write_at(writer: &mut BufWriter<File>, offset: u64, bytes: &[u8]) -> Result<()> {
+    writer.seek(SeekFrom::Start(offset))?;
+    writer.write_all(bytes)?;
+    Ok(())
+}
+
+fn write_directory(
+    writer: &mut BufWriter<File>,
+    geometry: &Geometry,
+    cluster: u32,
+    prefix: &[u8],
+    nodes: &[Node],
+    copied: &mut u64,
+    report: &mut dyn FnMut(u64),
+) -> Result<()> {
+    let mut entries = prefix.to_vec();
+    for node in nodes {
+        entries.extend(entry_set(node));
+    }
+    write_at(writer, geometry.cluster_offset(cluster), &entries)?;
+
+    for node in nodes {
+        match &node.children {
+            Some(children) => write_directory(writer, geometry, node.first_cluster, &[], children, copied, report)?,
+            None if node.length > 0 => {
+                writer.seek(SeekFrom::Start(geometry.cluster_offset(node.first_cluster)))?;
+                let mut source = File::open(&node.source)?.take(node.length);
+                let before = *copied;
+                super::copy_reporting(&mut source, writer, copied, report)?;
+                if *copied - before != node.length {
+                    return Err(anyhow!("{} changed while the image was built", node.source.display()));
+                }
+            }
+            None => {}
+        }
+    }
+    Ok(())
+}
+
+/// Formats an exFAT image at `output` holding the contents of `source_dir`, reporting copied
+/// file bytes against their total. Returns the image size, a multiple of the cluster size.
+pub(super) fn build_exfat_image(source_dir: &Path, output: &Path, report: &mut dyn FnMut(u64, u64)) -> Result<u64> {
+    let mut nodes = scan(source_dir)?;
+    let upcase = upcase_table();
+    let root_entries = 2 + nodes.iter().map(Node::entry_count).sum::<usize>();
+    let root_length = directory_length(root_entries);
+    let data_clusters = clusters_for(upcase.len() as u64) + clusters_for(root_length) + tree_clusters(&nodes);
+
+    // The bitmap covers every cluster, itself included.
+    let mut bitmap_clusters = 1;
+    loop {
+        let needed = clusters_for((data_clusters + bitmap_clusters).div_ceil(8));
+        if needed <= bitmap_clusters {
+            break;
+        }
+        bitmap_clusters = needed;
+    }
+    let cluster_count = data_clusters + bitmap_clusters;
+    let cluster_count =
+        u32::try_from(cluster_count).map_err(|_| anyhow!("Folder is too large for an exFAT option image"))?;
+    let bitmap_length = u64::from(cluster_count).div_ceil(8);
+
+    let mut allocator = Allocator {
+        next: FIRST_CLUSTER,
+        runs: Vec::new(),
+    };
+    let bitmap_cluster = allocator.allocate(bitmap_length);
+    let upcase_cluster = allocator.allocate(upcase.len() as u64);
+    let root_cluster = allocator.allocate(root_length);
+    allocator.assign(&mut nodes);
+
+    let fat_length = ((u64::from(cluster_count) + 2) * 4).div_ceil(SECTOR_SIZE);
+    let sectors_per_cluster = 1u64 << CLUSTER_SHIFT;
+    let heap_offset = (FAT_OFFSET + fat_length).div_ceil(sectors_per_cluster) * sectors_per_cluster;
+    let geometry = Geometry {
+        volume_length: heap_offset + (u64::from(cluster_count) << CLUSTER_SHIFT),
+        fat_length,
+        heap_offset,
+        cluster_count,
+        root_cluster,
+    };
+    let image_size = geometry.volume_length * SECTOR_SIZE;
+
+    let file = File::create(output)?;
+    file.set_len(image_size)?;
+    let mut writer = BufWriter::with_capacity(0x40000, file);
+
+    let serial = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs() as u32)
+        .unwrap_or(0);
+    let boot = boot_region(&geometry, serial);
+    write_at(&mut writer, 0, &boot)?;
+    write_at(&mut writer, BOOT_REGION_SECTORS * SECTOR_SIZE, &boot)?;
+
+    let mut fat = vec![0u32; cluster_count as usize + 2];
+    fat[0] = 0xFFFF_FFF8;
+    fat[1] = 0xFFFF_FFFF;
+    for (first, count) in &allocator.runs {
+        for cluster in *first..first + count - 1 {
+            fat[cluster as usize] = cluster + 1;
+        }
+        fat[(first + count - 1) as usize] = 0xFFFF_FFFF;
+    }
+    let fat: Vec<u8> = fat.iter().flat_map(|entry| entry.to_le_bytes()).collect();
+    write_at(&mut writer, FAT_OFFSET * SECTOR_SIZE, &fat)?;
+
+    let mut bitmap = vec![0xFFu8; bitmap_length as usize];
+    let spare_bits = bitmap_length * 8 - u64::from(cluster_count);
+    if let Some(last) = bitmap.last_mut() {
+        *last >>= spare_bits;
+    }
+    write_at(&mut writer, geometry.cluster_offset(bitmap_cluster), &bitmap)?;
+    write_at(&mut writer, geometry.cluster_offset(upcase_cluster), &upcase)?;
+
+    let mut prefix = vec![0u8; 2 * ENTRY_SIZE];
+    prefix[0] = ENTRY_BITMAP;
+    put(&mut prefix, 20, &bitmap_cluster.to_le_bytes());
+    put(&mut prefix, 24, &bitmap_length.to_le_bytes());
+    prefix[ENTRY_SIZE] = ENTRY_UPCASE;
+    put(&mut prefix, ENTRY_SIZE + 4, &checksum32(&upcase, &[]).to_le_bytes());
+    put(&mut prefix, ENTRY_SIZE + 20, &upcase_cluster.to_le_bytes());
+    put(&mut prefix, ENTRY_SIZE + 24, &(upcase.len() as u64).to_le_bytes());
+
+    let total = tree_file_bytes(&nodes);
+    let mut copied = 0u64;
+    write_directory(
+        &mut writer,
+        &geometry,
+        root_cluster,
+        &prefix,
+        &nodes,
+        &mut copied,
+        &mut |bytes| report(bytes, total),
+    )?;
+    writer.flush()?;
+    Ok(image_size)
+}
+
+/// Stream extension flag: the clusters are contiguous and the FAT holds no chain for them.
+const NO_FAT_CHAIN: u8 = 0x02;
+/// Folder depth at which extraction gives up, so a directory pointing at an ancestor ends.
+const MAX_DEPTH: usize = 64;
+
+/// The parts of a volume's boot sector the reader needs, in bytes.
+struct Volume {
+    file: File,
+    cluster_shift: u32,
+    fat_offset: u64,
+    heap_offset: u64,
+    cluster_count: u32,
+    root_cluster: u32,
+}
+
+/// A file or directory as its entry set describes it.
+struct Entry {
+    name: String,
+    directory: bool,
+    modified: SystemTime,
+    accessed: SystemTime,
+    first_cluster: u32,
+    length: u64,
+    /// Bytes written so far; the rest of `length` reads as zeros.
+    valid_length: u64,
+    contiguous: bool,
+}
+
+impl Volume {
+    fn open(path: &Path) -> Result<Self> {
+        let mut file = File::open(path)?;
+        let mut boot = [0u8; SECTOR_SIZE as usize];
+        file.read_exact(&mut boot)?;
+        if &boot[3..11] != b"EXFAT   " {
+            return Err(anyhow!("{} is not an exFAT image", path.display()));
+        }
+        let le32 = |offset: usize| u32::from_le_bytes(boot[offset..offset + 4].try_into().unwrap());
+        let sector_shift = u32::from(boot[108]);
+        let cluster_shift = sector_shift + u32::from(boot[109]);
+        if !(9..=12).contains(&sector_shift) || cluster_shift > 25 {
+            return Err(anyhow!("{} has an invalid exFAT geometry", path.display()));
+        }
+        Ok(Self {
+            file,
+            cluster_shift,
+            fat_offset: u64::from(le32(80)) << sector_shift,
+            heap_offset: u64::from(le32(88)) << sector_shift,
+            cluster_count: le32(92),
+            root_cluster: le32(96),
+        })
+    }
+
+    fn cluster_offset(&self, cluster: u32) -> Result<u64> {
+        if cluster < FIRST_CLUSTER || cluster - FIRST_CLUSTER >= self.cluster_count {
+            return Err(anyhow!("Cluster {cluster} is outside the volume"));
+        }
+        Ok(self.heap_offset + (u64::from(cluster - FIRST_CLUSTER) << self.cluster_shift))
+    }
+
+    /// Byte ranges holding `length` bytes from `first`, merged where clusters are adjacent.
+    /// Without a length (the root directory) the FAT chain alone decides where it ends.
+    fn extents(&mut self, first: u32, length: Option<u64>, contiguous: bool) -> Result<Vec<(u64, u64)>> {
+        let cluster_size = 1u64 << self.cluster_shift;
+        let mut extents: Vec<(u64, u64)> = Vec::new();
+        let mut remaining = length.unwrap_or(u64::MAX);
+        let mut cluster = first;
+        let mut visited = 0u32;
+        while remaining > 0 {
+            if visited > self.cluster_count {
+                return Err(anyhow!("Cluster chain from {first} loops"));
+            }
+            visited += 1;
+            let offset = self.cluster_offset(cluster)?;
+            let take = remaining.min(cluster_size);
+            match extents.last_mut() {
+                Some((start, len)) if *start + *len == offset => *len += take,
+                _ => extents.push((offset, take)),
+            }
+            remaining -= take;
+            if remaining == 0 {
+                break;
+            }
+            cluster = if contiguous {
+                cluster + 1
+            } else {
+                let mut next = [0u8; 4];
+                self.file.seek(SeekFrom::Start(self.fat_offset + u64::from(cluster) * 4))?;
+                self.file.read_exact(&mut next)?;
+                match u32::from_le_bytes(next) {
+                    0xFFFF_FFFF if length.is_none() => break,
+                    next => next,
+                }
+            };
+        }
+        Ok(extents)
+    }
+
+    fn read_extents(&mut self, extents: &[(u64, u64)]) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        for (offset, len) in extents {
+            self.file.seek(SeekFrom::Start(*offset))?;
+            (&mut self.file).take(*len).read_to_end(&mut data)?;
+        }
+        Ok(data)
+    }
+}
+
+/// Reverses [`exfat_timestamp`] for the timestamp at `offset` of a file entry, whose 10 ms
+/// increment and UTC offset sit at `increment` and `utc_offset`.
+fn entry_time(entry: &[u8], offset: usize, increment: Option<usize>, utc_offset: usize) -> SystemTime {
+    let stamp = u32::from_le_bytes(entry[offset..offset + 4].try_into().unwrap());
+    let increment_ms = increment.map_or(0, |i| i64::from(entry[i]) * 10);
+    // Seven-bit signed count of 15 minute steps east of UTC, valid when the top bit is set.
+    let offset_minutes = if entry[utc_offset] & UTC_OFFSET_VALID != 0 {
+        i64::from(((entry[utc_offset] << 1) as i8) >> 1) * 15
+    } else {
+        0
+    };
+    chrono::NaiveDate::from_ymd_opt(1980 + (stamp >> 25) as i32, (stamp >> 21) & 0xF, (stamp >> 16) & 0x1F)
+        .and_then(|date| date.and_hms_opt((stamp >> 11) & 0x1F, (stamp >> 5) & 0x3F, (stamp & 0x1F) * 2))
+        .map(|local| {
+            let utc = local.and_utc() - chrono::Duration::minutes(offset_minutes)
+                + chrono::Duration::milliseconds(increment_ms);
+            SystemTime::from(utc)
+        })
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+}
+
+/// File and directory entries of a directory's contents, skipping everything else.
+fn parse_directory(data: &[u8]) -> Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+    let mut sets = data.chunks_exact(ENTRY_SIZE).enumerate();
+    while let Some((index, entry)) = sets.next() {
+        match entry[0] {
+            0x00 => break,
+            ENTRY_FILE => {}
+            _ => continue,
+        }
+        let secondary = usize::from(entry[1]);
+        let set = data
+            .get(index * ENTRY_SIZE..(index + 1 + secondary) * ENTRY_SIZE)
+            .filter(|_| secondary >= 2)
+            .ok_or_else(|| anyhow!("Truncated exFAT entry set"))?;
+        if checksum16(set, &[2, 3]) != u16::from_le_bytes([entry[2], entry[3]]) {
+            return Err(anyhow!("exFAT entry set has a bad checksum"));
+        }
+        let stream = &set[ENTRY_SIZE..2 * ENTRY_SIZE];
+        if stream[0] != ENTRY_STREAM {
+            return Err(anyhow!("exFAT file entry without a stream extension"));
+        }
+        let name_len = usize::from(stream[3]);
+        let units: Vec<u16> = set[2 * ENTRY_SIZE..]
+            .chunks_exact(ENTRY_SIZE)
+            .filter(|name| name[0] == ENTRY_NAME)
+            .flat_map(|name| name[2..].chunks_exact(2).map(|unit| u16::from_le_bytes([unit[0], unit[1]])))
+            .take(name_len)
+            .collect();
+        let name = String::from_utf16(&units).map_err(|_| anyhow!("exFAT file name is not valid UTF-16"))?;
+        if units.len() != name_len || matches!(name.as_str(), "" | "." | "..") || name.contains(['/', '\\', '\0']) {
+            return Err(anyhow!("exFAT entry has an unusable name: {name:?}"));
+        }
+        let attributes = u16::from_le_bytes([entry[4], entry[5]]);
+        let allocated = stream[1] & ALLOCATION_POSSIBLE != 0;
+        let length = if allocated { u64::from_le_bytes(stream[24..32].try_into().unwrap()) } else { 0 };
+        let valid_length = u64::from_le_bytes(stream[8..16].try_into().unwrap()).min(length);
+        entries.push(Entry {
+            name,
+            directory: attributes & ATTR_DIRECTORY != 0,
+            modified: entry_time(entry, 12, Some(21), 23),
+            accessed: entry_time(entry, 16, None, 24),
+            first_cluster: if allocated { u32::from_le_bytes(stream[20..24].try_into().unwrap()) } else { 0 },
+            length,
+            valid_length,
+            contiguous: stream[1] & NO_FAT_CHAIN != 0,
+        });
+        for _ in 0..secondary {
+            sets.next();
+        }
+    }
+    Ok(entries)
+}
+
+fn extract_directory(
+    volume: &mut Volume,
+    entries: Vec<Entry>,
+    output_dir: &Path,
+    depth: usize,
+    copied: &mut u64,
+    report: &mut dyn FnMut(u64),
+) -> Result<()> {
+    if depth > MAX_DEPTH {
+        return Err(anyhow!("exFAT folders nest deeper than {MAX_DEPTH} levels"));
+    }
+    for entry in entries {
+        let dest = output_dir.join(&entry.name);
+        let stored = if entry.directory { entry.length } else { entry.valid_length };
+        let extents = match entry.first_cluster {
+            0 => Vec::new(),
+            _ if stored == 0 => Vec::new(),
+            first => volume.extents(first, Some(stored), entry.contiguous)?,
+        };
+        if entry.directory {
+            fs::create_dir_all(&dest)?;
+            let children = parse_directory(&volume.read_extents(&extents)?)?;
+            extract_directory(volume, children, &dest, depth + 1, copied, report)?;
+            continue;
+        }
+        let mut file = File::create(&dest)?;
+        {
+            let mut writer = BufWriter::with_capacity(0x40000, &mut file);
+            for (offset, len) in &extents {
+                volume.file.seek(SeekFrom::Start(*offset))?;
+                super::copy_reporting(&mut (&mut volume.file).take(*len), &mut writer, copied, report)?;
+            }
+            writer.flush()?;
+        }
+        file.set_len(entry.length)?;
+        file.set_times(fs::FileTimes::new().set_modified(entry.modified).set_accessed(entry.accessed))?;
+    }
+    Ok(())
+}
+
+/// Extracts every file of the exFAT image at `image` below `output_dir`, reporting copied
+/// file bytes. Files without clusters, as [`build_exfat_image`] writes empty ones, come out
+/// empty.
+pub(super) fn extract_exfat_image(image: &Path, output_dir: &Path, report: &mut dyn FnMut(u64)) -> Result<()> {
+    let mut volume = Volume::open(image)?;
+    let root = volume.extents(volume.root_cluster, None, false)?;
+    let entries = parse_directory(&volume.read_extents(&root)?)?;
+    fs::create_dir_all(output_dir)?;
+    let mut copied = 0u64;
+    extract_directory(&mut volume, entries, output_dir, 0, &mut copied, report)
+}
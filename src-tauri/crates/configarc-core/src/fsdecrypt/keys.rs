@@ -1,7 +1,7 @@
-use crate::fsdecrypt::crypto::GameKeys;
+use crate::fsdecrypt::{crypto::GameKeys, keystore};
 use anyhow::{anyhow, Result};
 use reqwest::blocking::Client;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -11,24 +11,24 @@ const DEFAULT_KEYS_FILE: &str = "fsdecrypt_keys.json";
 const KEYS_TIMEOUT_SECS: u64 = 30;
 const KEYS_CONNECT_TIMEOUT_SECS: u64 = 10;
 
-#[derive(Debug, Deserialize)]
-struct KeyPair {
-    key: String,
-    iv: String,
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct KeyPair {
+    pub(super) key: String,
+    pub(super) iv: String,
 }
 
-#[derive(Debug, Deserialize)]
-struct GameKeyEntry {
-    key: String,
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct GameKeyEntry {
+    pub(super) key: String,
     #[serde(default)]
-    iv: Option<String>,
+    pub(super) iv: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
-struct KeyFile {
-    bootid: KeyPair,
-    option: KeyPair,
-    games: HashMap<String, GameKeyEntry>,
+#[derive(Debug, Clone, Deserialize)]
+pub(super) struct KeyFile {
+    pub(super) bootid: KeyPair,
+    pub(super) option: KeyPair,
+    pub(super) games: HashMap<String, GameKeyEntry>,
 }
 
 #[derive(Clone)]
@@ -37,7 +37,7 @@ pub struct FsDecryptKeys {
     pub bootid_iv: [u8; 16],
     pub option_key: [u8; 16],
     pub option_iv: [u8; 16],
-    games: HashMap<String, GameKeys>,
+    pub(super) games: HashMap<String, GameKeys>,
 }
 
 #[derive(Clone)]
@@ -46,7 +46,7 @@ pub struct KeySourceInfo {
     pub game_count: usize,
 }
 
-fn decode_hex_16(label: &str, raw: &str) -> Result<[u8; 16]> {
+pub(super) fn decode_hex_16(label: &str, raw: &str) -> Result<[u8; 16]> {
     let cleaned = raw.trim().trim_start_matches("0x");
     let bytes = hex::decode(cleaned)
         .map_err(|e| anyhow!("Invalid hex for {label}: {e}"))?;
@@ -61,23 +61,13 @@ fn decode_hex_16(label: &str, raw: &str) -> Result<[u8; 16]> {
     Ok(arr)
 }
 
-fn read_keys_from_file(path: &Path) -> Result<(FsDecryptKeys, KeySourceInfo)> {
+fn read_key_file(path: &Path) -> Result<KeyFile> {
     let content = fs::read_to_string(path)
         .map_err(|e| anyhow!("Failed to read keys from {}: {e}", path.display()))?;
-    let parsed: KeyFile = serde_json::from_str(&content)
-        .map_err(|e| anyhow!("Failed to parse keys json: {e}"))?;
-    let keys = parse_key_file(parsed)?;
-    let game_count = keys.games.len();
-    Ok((
-        keys,
-        KeySourceInfo {
-            source: format!("local:{}", path.display()),
-            game_count,
-        },
-    ))
+    serde_json::from_str(&content).map_err(|e| anyhow!("Failed to parse keys json: {e}"))
 }
 
-fn read_keys_from_url(url: &str) -> Result<(FsDecryptKeys, KeySourceInfo)> {
+fn download_key_file(url: &str) -> Result<KeyFile> {
     let client = Client::builder()
         .timeout(Duration::from_secs(KEYS_TIMEOUT_SECS))
         .connect_timeout(Duration::from_secs(KEYS_CONNECT_TIMEOUT_SECS))
@@ -90,9 +80,33 @@ fn read_keys_from_url(url: &str) -> Result<(FsDecryptKeys, KeySourceInfo)> {
         return Err(anyhow!("Failed to download keys json: {}", resp.status()));
     }
     let text = resp.text().map_err(|e| anyhow!("Failed to read keys json: {e}"))?;
-    let parsed: KeyFile = serde_json::from_str(&text)
-        .map_err(|e| anyhow!("Failed to parse keys json: {e}"))?;
-    let keys = parse_key_file(parsed)?;
+    serde_json::from_str(&text).map_err(|e| anyhow!("Failed to parse keys json: {e}"))
+}
+
+/// Reads a key file from an `http(s)://` URL or a local path.
+pub(super) fn fetch_key_file(source: &str) -> Result<KeyFile> {
+    let source = source.trim();
+    if source.starts_with("http://") || source.starts_with("https://") {
+        download_key_file(source)
+    } else {
+        read_key_file(Path::new(source))
+    }
+}
+
+fn read_keys_from_file(path: &Path) -> Result<(FsDecryptKeys, KeySourceInfo)> {
+    let keys = parse_key_file(read_key_file(path)?)?;
+    let game_count = keys.games.len();
+    Ok((
+        keys,
+        KeySourceInfo {
+            source: format!("local:{}", path.display()),
+            game_count,
+        },
+    ))
+}
+
+fn read_keys_from_url(url: &str) -> Result<(FsDecryptKeys, KeySourceInfo)> {
+    let keys = parse_key_file(download_key_file(url)?)?;
     let game_count = keys.games.len();
     Ok((
         keys,
@@ -103,7 +117,7 @@ fn read_keys_from_url(url: &str) -> Result<(FsDecryptKeys, KeySourceInfo)> {
     ))
 }
 
-fn parse_key_file(parsed: KeyFile) -> Result<FsDecryptKeys> {
+pub(super) fn parse_key_file(parsed: KeyFile) -> Result<FsDecryptKeys> {
     let bootid_key = decode_hex_16("bootid.key", &parsed.bootid.key)?;
     let bootid_iv = decode_hex_16("bootid.iv", &parsed.bootid.iv)?;
     let option_key = decode_hex_16("option.key", &parsed.option.key)?;
@@ -148,7 +162,12 @@ fn resolve_local_keys_file() -> Result<PathBuf> {
     ))
 }
 
+/// Keys from the local key store when it holds the BootID and option keys, else from
+/// `key_url`, else from a key file next to the app.
 pub fn load_keys(key_url: Option<&str>) -> Result<(FsDecryptKeys, KeySourceInfo)> {
+    if let Some(loaded) = keystore::load_stored_keys()? {
+        return Ok(loaded);
+    }
     if let Some(url) = key_url {
         let trimmed = url.trim();
         if !trimmed.is_empty() {
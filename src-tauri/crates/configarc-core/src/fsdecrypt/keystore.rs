@@ -0,0 +1,240 @@
+//! Local, encrypted key database. Keys imported from a key file or URL are kept here so
+//! decryption works offline; [`load_keys`](super::load_keys) prefers it over the URL.
+//!
+//! On Windows the database is sealed with DPAPI for the current user, so a copied file is
+//! useless elsewhere. Other platforms use AES-256-GCM under a random key kept in an
+//! owner-only file next to the database.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::sealed;
+
+use super::keys::{decode_hex_16, fetch_key_file, parse_key_file, FsDecryptKeys, GameKeyEntry, KeyPair, KeySourceInfo};
+
+const KEY_STORE_FILE: &str = "fsdecrypt_keys.db";
+/// Leads every database file; the digit is the format version.
+const KEY_STORE_MAGIC: &[u8] = b"CAKS1";
+const SEAL_ENTROPY: &[u8] = b"ConfigArc fsdecrypt key store";
+
+/// What the store holds, without any key material.
+#[derive(Serialize, Clone)]
+pub struct KeyStoreStatus {
+    pub path: String,
+    pub exists: bool,
+    pub has_bootid_key: bool,
+    pub has_option_key: bool,
+    /// Game and OS ids with a stored key, sorted.
+    pub game_ids: Vec<String>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct StoredKeys {
+    #[serde(default)]
+    bootid: Option<KeyPair>,
+    #[serde(default)]
+    option: Option<KeyPair>,
+    #[serde(default)]
+    games: BTreeMap<String, GameKeyEntry>,
+}
+
+fn key_store_path() -> PathBuf {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(KEY_STORE_FILE)
+}
+
+fn read_store(path: &Path) -> Result<Option<StoredKeys>> {
+    let raw = match fs::read(path) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(anyhow!("Failed to read key store {}: {e}", path.display())),
+    };
+    let body = raw
+        .strip_prefix(KEY_STORE_MAGIC)
+        .ok_or_else(|| anyhow!("{} is not a key store", path.display()))?;
+    let plain = sealed::open(body, path, SEAL_ENTROPY)
+        .map_err(|e| anyhow!("{e}. Remove {} and import the keys again.", path.display()))?;
+    Ok(Some(serde_json::from_slice(&plain).map_err(|e| anyhow!("Failed to parse key store: {e}"))?))
+}
+
+fn write_store(path: &Path, store: &StoredKeys) -> Result<()> {
+    let plain = serde_json::to_vec(store)?;
+    let body = sealed::seal(&plain, path, SEAL_ENTROPY).map_err(|e| anyhow!(e))?;
+    let tmp = path.with_extension("db.tmp");
+    fs::write(&tmp, [KEY_STORE_MAGIC, body.as_slice()].concat())?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+fn status_of(path: &Path, store: Option<&StoredKeys>) -> KeyStoreStatus {
+    KeyStoreStatus {
+        path: path.to_string_lossy().into_owned(),
+        exists: store.is_some(),
+        has_bootid_key: store.is_some_and(|s| s.bootid.is_some()),
+        has_option_key: store.is_some_and(|s| s.option.is_some()),
+        game_ids: store.map(|s| s.games.keys().cloned().collect()).unwrap_or_default(),
+    }
+}
+
+fn update_store(path: &Path, change: impl FnOnce(&mut StoredKeys) -> Result<()>) -> Result<KeyStoreStatus> {
+    let mut store = read_store(path)?.unwrap_or_default();
+    change(&mut store)?;
+    write_store(path, &store)?;
+    Ok(status_of(path, Some(&store)))
+}
+
+fn import_into(path: &Path, source: &str) -> Result<KeyStoreStatus> {
+    let file = fetch_key_file(source)?;
+    // Parsing validates every key before anything is stored.
+    parse_key_file(file.clone())?;
+    update_store(path, |store| {
+        store.bootid = Some(file.bootid);
+        store.option = Some(file.option);
+        for (id, entry) in file.games {
+            store.games.insert(id.trim().to_uppercase(), entry);
+        }
+        Ok(())
+    })
+}
+
+fn set_game_key_in(path: &Path, game_id: &str, key: &str, iv: Option<&str>) -> Result<KeyStoreStatus> {
+    let id = game_id.trim().to_uppercase();
+    if id.is_empty() {
+        return Err(anyhow!("Game id is empty"));
+    }
+    decode_hex_16(&format!("{id}.key"), key)?;
+    if let Some(iv) = iv {
+        decode_hex_16(&format!("{id}.iv"), iv)?;
+    }
+    update_store(path, |store| {
+        store.games.insert(
+            id,
+            GameKeyEntry {
+                key: key.trim().to_string(),
+                iv: iv.map(|iv| iv.trim().to_string()),
+            },
+        );
+        Ok(())
+    })
+}
+
+fn remove_game_key_in(path: &Path, game_id: &str) -> Result<KeyStoreStatus> {
+    let id = game_id.trim().to_uppercase();
+    update_store(path, |store| {
+        store
+            .games
+            .remove(&id)
+            .map(|_| ())
+            .ok_or_else(|| anyhow!("No stored key for {id}"))
+    })
+}
+
+fn load_from(path: &Path) -> Result<Option<(FsDecryptKeys, KeySourceInfo)>> {
+    let Some(store) = read_store(path)? else {
+        return Ok(None);
+    };
+    let (Some(bootid), Some(option)) = (store.bootid, store.option) else {
+        return Ok(None);
+    };
+    let keys = parse_key_file(super::keys::KeyFile {
+        bootid,
+        option,
+        games: store.games.into_iter().collect(),
+    })?;
+    let game_count = keys.games.len();
+    Ok(Some((
+        keys,
+        KeySourceInfo {
+            source: format!("store:{}", path.display()),
+            game_count,
+        },
+    )))
+}
+
+/// Stored keys, when the store exists and holds the BootID and option keys.
+pub(super) fn load_stored_keys() -> Result<Option<(FsDecryptKeys, KeySourceInfo)>> {
+    load_from(&key_store_path())
+}
+
+/// Whether [`load_keys`](super::load_keys) will use the store rather than a key URL.
+pub fn key_store_ready() -> bool {
+    matches!(read_store(&key_store_path()), Ok(Some(store)) if store.bootid.is_some() && store.option.is_some())
+}
+
+pub fn key_store_status() -> Result<KeyStoreStatus> {
+    let path = key_store_path();
+    let store = read_store(&path)?;
+    Ok(status_of(&path, store.as_ref()))
+}
+
+/// Merges a key file from an `http(s)://` URL or a local path into the store. Its BootID and
+/// option keys replace the stored ones; game keys are added or replaced per id.
+pub fn import_keys(source: &str) -> Result<KeyStoreStatus> {
+    import_into(&key_store_path(), source)
+}
+
+pub fn set_game_key(game_id: &str, key: &str, iv: Option<&str>) -> Result<KeyStoreStatus> {
+    set_game_key_in(&key_store_path(), game_id, key, iv)
+}
+
+pub fn remove_game_key(game_id: &str) -> Result<KeyStoreStatus> {
+    remove_game_key_in(&key_store_path(), game_id)
+}
+
+/// Deletes the store, e.g. when it was sealed on another machine and can no longer be read.
+pub fn clear_key_store() -> Result<KeyStoreStatus> {
+    let path = key_store_path();
+    if let Err(e) = fs::remove_file(&path) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            return Err(e.into());
+        }
+    }
+    sealed::remove_key_file(&path)?;
+    Ok(status_of(&path, None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const KEY: &str = "00112233445566778899aabbccddeeff";
+
+    #[test]
+    fn imports_edits_and_loads_sealed_keys() {
+        let dir = TempDir::new().unwrap();
+        let store = dir.path().join(KEY_STORE_FILE);
+        let source = dir.path().join("keys.json");
+        fs::write(
+            &source,
+            format!(
+                r#"{{"bootid":{{"key":"{KEY}","iv":"{KEY}"}},"option":{{"key":"{KEY}","iv":"{KEY}"}},"games":{{"sdez":{{"key":"{KEY}"}}}}}}"#
+            ),
+        )
+        .unwrap();
+
+        assert!(load_from(&store).unwrap().is_none());
+        let status = import_into(&store, source.to_str().unwrap()).unwrap();
+        assert_eq!(status.game_ids, vec!["SDEZ"]);
+        assert!(!String::from_utf8_lossy(&fs::read(&store).unwrap()).contains(KEY));
+
+        set_game_key_in(&store, "sdgb", KEY, Some(KEY)).unwrap();
+        assert!(set_game_key_in(&store, "sddt", "zz", None).is_err());
+        let status = remove_game_key_in(&store, "SDEZ").unwrap();
+        assert_eq!(status.game_ids, vec!["SDGB"]);
+
+        let (keys, info) = load_from(&store).unwrap().unwrap();
+        assert!(info.source.starts_with("store:"));
+        assert!(keys.game_keys_for("sdgb").is_some_and(|k| k.iv.is_some()));
+        assert!(keys.game_keys_for("sdez").is_none());
+    }
+}
@@ -0,0 +1,252 @@
+//! SHA-256 manifests of extracted outputs. Each extraction leaves `<output>.manifest.json`
+//! beside its output, so a dump copied to another machine can be re-hashed and compared
+//! against what the extraction wrote.
+
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+const MANIFEST_SUFFIX: &str = ".manifest.json";
+const MANIFEST_VERSION: u32 = 1;
+const HASH_CHUNK_SIZE: usize = 4 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ManifestEntry {
+    /// Relative to the output folder with `/` separators; the file name for a single-file output.
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputManifest {
+    pub version: u32,
+    /// File name of the container the output was extracted from.
+    pub source: String,
+    pub created_at: String,
+    pub entries: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DriftKind {
+    Modified,
+    Missing,
+    /// Present in the output but not in the manifest.
+    Added,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ManifestDrift {
+    pub path: String,
+    pub kind: DriftKind,
+    pub expected_size: Option<u64>,
+    pub actual_size: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OutputVerification {
+    pub output: String,
+    pub manifest: String,
+    pub checked_files: usize,
+    pub checked_bytes: u64,
+    /// Empty when the output matches its manifest.
+    pub drift: Vec<ManifestDrift>,
+}
+
+/// Where the manifest of an extracted file or folder lives.
+pub fn manifest_path_for(output: &Path) -> PathBuf {
+    let mut name = output.file_name().map(|n| n.to_os_string()).unwrap_or_default();
+    name.push(MANIFEST_SUFFIX);
+    output.with_file_name(name)
+}
+
+/// Files of an output keyed by manifest path, with their sizes.
+fn output_files(output: &Path) -> Result<BTreeMap<String, (PathBuf, u64)>> {
+    let mut files = BTreeMap::new();
+    let meta = fs::metadata(output).with_context(|| format!("Output not found: {}", output.display()))?;
+    if meta.is_file() {
+        let name = output
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .ok_or_else(|| anyhow!("Output has no file name: {}", output.display()))?;
+        files.insert(name, (output.to_path_buf(), meta.len()));
+        return Ok(files);
+    }
+    let mut pending = vec![output.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            let path = entry.path();
+            if file_type.is_dir() {
+                pending.push(path);
+            } else if file_type.is_file() {
+                let relative = path
+                    .strip_prefix(output)?
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                files.insert(relative, (path, entry.metadata()?.len()));
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn hash_file(path: &Path, report: &mut dyn FnMut(u64)) -> Result<String> {
+    let mut file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; HASH_CHUNK_SIZE];
+    loop {
+        let read = file.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buf[..read]);
+        report(read as u64);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Hashes every file of `output` and writes its manifest. `report` gets bytes hashed and the
+/// output's total size.
+pub(super) fn write_manifest(output: &Path, source: &Path, report: &mut dyn FnMut(u64, u64)) -> Result<PathBuf> {
+    let files = output_files(output)?;
+    let total: u64 = files.values().map(|(_, size)| size).sum();
+    let mut processed = 0u64;
+    let mut entries = Vec::with_capacity(files.len());
+    for (relative, (path, size)) in files {
+        let sha256 = hash_file(&path, &mut |read| {
+            processed += read;
+            report(processed, total);
+        })?;
+        entries.push(ManifestEntry {
+            path: relative,
+            size,
+            sha256,
+        });
+    }
+    let manifest = OutputManifest {
+        version: MANIFEST_VERSION,
+        source: source
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        entries,
+    };
+    let manifest_path = manifest_path_for(output);
+    fs::write(&manifest_path, serde_json::to_vec_pretty(&manifest)?)?;
+    Ok(manifest_path)
+}
+
+/// Re-hashes an extracted output and reports every file that no longer matches its manifest.
+/// Files whose size already differs are not hashed.
+pub fn verify_output(output: &Path, mut report: Option<&mut dyn FnMut(u64, u64)>) -> Result<OutputVerification> {
+    let manifest_path = manifest_path_for(output);
+    let data = fs::read(&manifest_path).with_context(|| format!("No manifest at {}", manifest_path.display()))?;
+    let manifest: OutputManifest = serde_json::from_slice(&data)?;
+    let mut files = output_files(output)?;
+
+    let mut drift = Vec::new();
+    let mut to_hash = Vec::new();
+    for entry in manifest.entries {
+        match files.remove(&entry.path) {
+            None => drift.push(ManifestDrift {
+                path: entry.path,
+                kind: DriftKind::Missing,
+                expected_size: Some(entry.size),
+                actual_size: None,
+            }),
+            Some((_, size)) if size != entry.size => drift.push(ManifestDrift {
+                path: entry.path,
+                kind: DriftKind::Modified,
+                expected_size: Some(entry.size),
+                actual_size: Some(size),
+            }),
+            Some((path, _)) => to_hash.push((path, entry)),
+        }
+    }
+    drift.extend(files.into_iter().map(|(path, (_, size))| ManifestDrift {
+        path,
+        kind: DriftKind::Added,
+        expected_size: None,
+        actual_size: Some(size),
+    }));
+
+    let total: u64 = to_hash.iter().map(|(_, entry)| entry.size).sum();
+    let mut processed = 0u64;
+    for (path, entry) in &to_hash {
+        let sha256 = hash_file(path, &mut |read| {
+            processed += read;
+            if let Some(report) = report.as_mut() {
+                report(processed, total);
+            }
+        })?;
+        if sha256 != entry.sha256 {
+            drift.push(ManifestDrift {
+                path: entry.path.clone(),
+                kind: DriftKind::Modified,
+                expected_size: Some(entry.size),
+                actual_size: Some(entry.size),
+            });
+        }
+    }
+    drift.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(OutputVerification {
+        output: output.to_string_lossy().into_owned(),
+        manifest: manifest_path.to_string_lossy().into_owned(),
+        checked_files: to_hash.len(),
+        checked_bytes: total,
+        drift,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn verification_reports_modified_missing_and_added_files() {
+        let dir = TempDir::new().unwrap();
+        let output = dir.path().join("A001");
+        fs::create_dir_all(output.join("music")).unwrap();
+        fs::write(output.join("music").join("a.bin"), b"aaaa").unwrap();
+        fs::write(output.join("b.bin"), b"bbbb").unwrap();
+        fs::write(output.join("c.bin"), b"cccc").unwrap();
+
+        let manifest = write_manifest(&output, Path::new("A001.opt"), &mut |_, _| {}).unwrap();
+        assert_eq!(manifest, dir.path().join("A001.manifest.json"));
+        assert!(verify_output(&output, None).unwrap().drift.is_empty());
+
+        fs::write(output.join("music").join("a.bin"), b"axaa").unwrap();
+        fs::remove_file(output.join("b.bin")).unwrap();
+        fs::write(output.join("d.bin"), b"dd").unwrap();
+        let verification = verify_output(&output, None).unwrap();
+        let kinds: Vec<(&str, DriftKind)> = verification
+            .drift
+            .iter()
+            .map(|d| (d.path.as_str(), d.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ("b.bin", DriftKind::Missing),
+                ("d.bin", DriftKind::Added),
+                ("music/a.bin", DriftKind::Modified),
+            ]
+        );
+        assert_eq!(verification.checked_files, 2);
+    }
+}
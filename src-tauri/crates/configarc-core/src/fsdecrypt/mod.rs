@@ -0,0 +1,1257 @@
+//! Decryption of encrypted app (`.app`) and option (`.opt`) containers into plain VHD or
+//! exFAT/NTFS images, with optional extraction, and the reverse for option folders. Keys are
+//! loaded from a key URL.
+
+use std::{
+    any::Any,
+    fs::{create_dir_all, File, FileTimes},
+    io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+use aes::{
+    cipher::{block_padding::NoPadding, BlockDecryptMut, BlockEncryptMut, InnerIvInit, KeyInit, KeyIvInit},
+    Aes128Dec, Aes128Enc,
+};
+use anyhow::{anyhow, Result};
+use ntfs::{
+    indexes::NtfsFileNameIndex,
+    structured_values::{NtfsFileNamespace, NtfsStandardInformation},
+    Ntfs, NtfsAttributeType, NtfsFile, NtfsTime,
+};
+use serde::Serialize;
+
+use crate::cancel::{PartialArtifacts, CANCELLED_MESSAGE};
+use crate::platform::{crypto_acceleration, CryptoAcceleration};
+
+use self::{
+    bootid::{BootId, ContainerType, Timestamp, Version, BOOTID_SIZE},
+    crypto::{
+        calculate_file_iv, calculate_page_iv, Aes128CbcDec, Aes128CbcEnc, GameKeys, EXFAT_HEADER, NTFS_HEADER,
+    },
+    keys::{load_keys, FsDecryptKeys},
+    manifest::write_manifest,
+    output::{check_free_space, clear_outputs, Extraction, NameFields, OutputPlanner, OutputTarget},
+};
+
+mod bootid;
+mod crypto;
+mod exfat;
+mod keys;
+mod keystore;
+mod manifest;
+mod output;
+
+pub use self::keystore::{
+    clear_key_store, import_keys, key_store_ready, key_store_status, remove_game_key, set_game_key, KeyStoreStatus,
+};
+pub use self::manifest::{
+    manifest_path_for, verify_output, DriftKind, ManifestDrift, ManifestEntry, OutputManifest, OutputVerification,
+};
+pub use self::output::{validate_name_template, DecryptOutputOptions, OverwritePolicy};
+
+const PAGE_SIZE: u64 = 4096;
+
+#[derive(Serialize, Clone)]
+pub struct DecryptResult {
+    pub input: String,
+    pub output: Option<String>,
+    pub container_type: Option<String>,
+    pub extracted: bool,
+    /// Output already existed and the overwrite policy said to leave it; `output` points at it.
+    pub skipped: bool,
+    pub warnings: Vec<String>,
+    pub failed: bool,
+    pub error: Option<String>,
+    /// Size of the encrypted container.
+    pub input_bytes: u64,
+    /// Bytes written by the decrypt stage and the time it took.
+    pub decrypted_bytes: u64,
+    pub decrypt_ms: u64,
+    /// Decrypt throughput in bytes per second.
+    pub throughput_bps: u64,
+    pub extract_ms: u64,
+    /// Time spent hashing the extracted output into its manifest.
+    pub hash_ms: u64,
+    /// Wall time for the whole file, from opening it to the manifest.
+    pub total_ms: u64,
+    /// Key the container was decrypted with: the OS or game id, or `OPTION` for the shared
+    /// option key.
+    pub key_id: Option<String>,
+    /// Whether the IV was derived from the first page rather than taken from the key file.
+    pub derived_iv: bool,
+    /// SHA-256 manifest written beside an extracted output.
+    pub manifest: Option<String>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct DecryptSummary {
+    pub results: Vec<DecryptResult>,
+    pub key_source: String,
+    pub key_game_count: usize,
+    /// Hardware paths the run's AES and CRC32 work could use.
+    pub acceleration: CryptoAcceleration,
+    /// Worker threads the run used.
+    pub workers: usize,
+    /// Wall time of the run, and the decrypted bytes of all files over it.
+    pub elapsed_ms: u64,
+    pub decrypted_bytes: u64,
+    pub throughput_bps: u64,
+    /// The run was cancelled; files it had not finished report the cancellation as their error.
+    pub cancelled: bool,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DecryptStage {
+    Decrypt,
+    Extract,
+    /// Hashing the extracted output into its manifest.
+    Hash,
+}
+
+/// `percent`, `processed` and `total` cover the decrypt stage of the whole batch; the `file_*`
+/// fields cover the current file's `stage`. `file_total` is 0 when the size is not known
+/// up front, as for exFAT option images being extracted.
+#[derive(Serialize, Clone)]
+pub struct DecryptProgress {
+    pub percent: u8,
+    pub processed: u64,
+    pub total: u64,
+    pub current_file: usize,
+    pub total_files: usize,
+    pub stage: DecryptStage,
+    pub file_processed: u64,
+    pub file_total: u64,
+}
+
+/// What a container holds, read from its BootID header alone. Fields are `None` when the
+/// header could not be decrypted; `error` then says why.
+#[derive(Serialize, Clone, Default)]
+pub struct ContainerInfo {
+    pub input: String,
+    pub container_type: Option<String>,
+    /// Game id for APP and OPTION containers, OS id for OS containers.
+    pub id: Option<String>,
+    /// Target version, or the option name (e.g. `A001`) for OPTION containers.
+    pub version: Option<String>,
+    /// Version a patch container upgrades from; only set when `sequence_number` is above 0.
+    pub source_version: Option<String>,
+    pub timestamp: Option<String>,
+    pub sequence_number: Option<u8>,
+    pub output_size: Option<u64>,
+    pub output_name: Option<String>,
+    /// Whether the loaded keys can decrypt the container body.
+    pub key_available: bool,
+    pub error: Option<String>,
+}
+
+/// Result of [`build_option_container`].
+#[derive(Serialize, Clone)]
+pub struct OptionBuildResult {
+    pub output: String,
+    pub game_id: String,
+    pub option_id: String,
+    /// Size of the exFAT image the container carries.
+    pub image_size: u64,
+    pub build_ms: u64,
+}
+
+#[derive(Serialize, Clone)]
+pub struct KeyStatus {
+    pub key_source: String,
+    pub key_game_count: usize,
+}
+
+fn panic_message(err: Box<dyn Any + Send>) -> String {
+    if let Some(msg) = err.downcast_ref::<&str>() {
+        (*msg).to_string()
+    } else if let Some(msg) = err.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// `std::io::copy` that reports the running byte count in `copied` after every chunk.
+fn copy_reporting(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+    copied: &mut u64,
+    report: &mut dyn FnMut(u64),
+) -> Result<()> {
+    let mut buf = vec![0u8; 0x40000];
+    loop {
+        let read = match reader.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(read) => read,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+        writer.write_all(&buf[..read])?;
+        *copied = copied.saturating_add(read as u64);
+        report(*copied);
+    }
+}
+
+fn extract_exfat_contents(exfat_path: &Path, report: &mut dyn FnMut(u64, u64)) -> Result<PathBuf> {
+    let output_dir = exfat_path.with_extension("");
+    exfat::extract_exfat_image(exfat_path, &output_dir, &mut |bytes| report(bytes, 0))?;
+    Ok(output_dir)
+}
+
+fn ntfs_time_to_system_time(ntfs_time: NtfsTime) -> SystemTime {
+    let intervals_since_windows_epoch = ntfs_time.nt_timestamp();
+    let intervals_since_unix_epoch = intervals_since_windows_epoch - 116_444_736_000_000_000;
+    let nanos_since_unix_epoch = intervals_since_unix_epoch * 100;
+
+    SystemTime::UNIX_EPOCH + Duration::from_nanos(nanos_since_unix_epoch)
+}
+
+fn extract_internal_vhd(
+    image_path: &Path,
+    sequence_number: u8,
+    report: &mut dyn FnMut(u64, u64),
+) -> Result<PathBuf> {
+    let vhd_filename = format!("internal_{sequence_number}.vhd");
+    let output_path = image_path.with_extension("vhd");
+
+    let mut fs = File::open(image_path)?;
+    let mut ntfs = Ntfs::new(&mut fs)?;
+    ntfs.read_upcase_table(&mut fs)?;
+
+    let root_directory = ntfs.root_directory(&mut fs)?;
+    let index = root_directory.directory_index(&mut fs)?;
+    let mut finder = index.finder();
+    let entry = NtfsFileNameIndex::find(&mut finder, &ntfs, &mut fs, &vhd_filename)
+        .ok_or_else(|| anyhow!("could not find VHD {vhd_filename}"))??;
+    let file = entry.to_file(&ntfs, &mut fs)?;
+    let data_item = file
+        .data(&mut fs, "")
+        .ok_or_else(|| anyhow!("file data does not exist"))??;
+    let data_attribute = data_item.to_attribute()?;
+    let data_len = data_attribute.value_length();
+    let mut data_value = data_attribute.value(&mut fs)?.attach(&mut fs);
+
+    let mut output_file = File::create(&output_path)?;
+    let mut writer = BufWriter::with_capacity(256 * 1024, &mut output_file);
+
+    let mut copied = 0u64;
+    copy_reporting(&mut data_value, &mut writer, &mut copied, &mut |bytes| report(bytes, data_len))?;
+    writer.flush()?;
+    drop(writer);
+
+    if let Some(times) = ntfs_file_times(&file, &mut fs)? {
+        output_file.set_times(times)?;
+    }
+
+    Ok(output_path)
+}
+
+/// Access and modification times from a file's standard information attribute.
+fn ntfs_file_times(file: &NtfsFile, fs: &mut File) -> Result<Option<FileTimes>> {
+    let mut attributes_iterator = file.attributes();
+
+    while let Some(attribute) = attributes_iterator.next(fs) {
+        let attribute = attribute?;
+        let attribute = attribute.to_attribute()?;
+
+        if let Ok(NtfsAttributeType::StandardInformation) = attribute.ty() {
+            let info = attribute.resident_structured_value::<NtfsStandardInformation>()?;
+
+            return Ok(Some(
+                FileTimes::new()
+                    .set_accessed(ntfs_time_to_system_time(info.access_time()))
+                    .set_modified(ntfs_time_to_system_time(info.modification_time())),
+            ));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Names of a file's data streams; the unnamed main stream is `""`.
+fn ntfs_stream_names(file: &NtfsFile, fs: &mut File) -> Result<Vec<String>> {
+    let mut names: Vec<String> = Vec::new();
+    let mut attributes_iterator = file.attributes();
+
+    while let Some(attribute) = attributes_iterator.next(fs) {
+        let attribute = attribute?;
+        let attribute = attribute.to_attribute()?;
+
+        if let Ok(NtfsAttributeType::Data) = attribute.ty() {
+            // Fragmented streams repeat their attribute in the attribute list.
+            let name = attribute.name()?.to_string_lossy();
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+
+    Ok(names)
+}
+
+fn copy_ntfs_stream(
+    file: &NtfsFile,
+    fs: &mut File,
+    stream: &str,
+    dest_path: &Path,
+    copied: &mut u64,
+    report: &mut dyn FnMut(u64),
+) -> Result<()> {
+    let data_item = file
+        .data(fs, stream)
+        .ok_or_else(|| anyhow!("data stream {stream:?} does not exist"))??;
+    let data_attribute = data_item.to_attribute()?;
+    let mut data_value = data_attribute.value(fs)?.attach(fs);
+
+    let mut writer = BufWriter::with_capacity(256 * 1024, File::create(dest_path)?);
+    copy_reporting(&mut data_value, &mut writer, copied, report)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Record number, name and directory flag of each entry in `dir`, skipping DOS 8.3 aliases
+/// (the long name is listed too) and the directory's own entry.
+fn ntfs_children(dir: &NtfsFile, fs: &mut File) -> Result<Vec<(u64, String, bool)>> {
+    let index = dir.directory_index(fs)?;
+    let mut entries = index.entries();
+    let mut children = Vec::new();
+
+    while let Some(entry) = entries.next(fs) {
+        let entry = entry?;
+        let Some(key) = entry.key() else {
+            continue;
+        };
+        let file_name = key?;
+        if matches!(file_name.namespace(), NtfsFileNamespace::Dos) {
+            continue;
+        }
+        let record_number = entry.file_reference().file_record_number();
+        if record_number == dir.file_record_number() {
+            continue;
+        }
+        children.push((record_number, file_name.name().to_string_lossy(), file_name.is_directory()));
+    }
+
+    Ok(children)
+}
+
+fn extract_ntfs_directory(
+    ntfs: &Ntfs,
+    fs: &mut File,
+    dir: &NtfsFile,
+    output_dir: &Path,
+    is_root: bool,
+    copied: &mut u64,
+    report: &mut dyn FnMut(u64),
+) -> Result<()> {
+    for (record_number, name, is_directory) in ntfs_children(dir, fs)? {
+        // The root holds the filesystem's own metadata files ($MFT, $Bitmap, ...).
+        if (is_root && name.starts_with('$')) || name == "." || name == ".." || name.contains(['/', '\\']) {
+            continue;
+        }
+        let file = ntfs.file(fs, record_number)?;
+        let dest_path = output_dir.join(&name);
+
+        if is_directory {
+            create_dir_all(&dest_path)?;
+            extract_ntfs_directory(ntfs, fs, &file, &dest_path, false, copied, report)?;
+        } else {
+            for stream in ntfs_stream_names(&file, fs)? {
+                if stream.is_empty() {
+                    copy_ntfs_stream(&file, fs, &stream, &dest_path, copied, report)?;
+                } else {
+                    // Alternate data streams keep their `file:stream` name, which NTFS targets
+                    // store as a stream again and other filesystems as a sibling file.
+                    let mut stream_path = dest_path.clone().into_os_string();
+                    stream_path.push(format!(":{stream}"));
+                    copy_ntfs_stream(&file, fs, &stream, Path::new(&stream_path), copied, report)?;
+                }
+            }
+        }
+
+        // Directories only accept new times once their children are written; opening one
+        // fails on Windows, so their times are best effort.
+        if let Some(times) = ntfs_file_times(&file, fs)? {
+            if let Ok(dest) = File::options().write(!is_directory).read(is_directory).open(&dest_path) {
+                let _ = dest.set_times(times);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Extracts every file and directory of an NTFS image, with alternate data streams and
+/// timestamps, into a folder named after the image. Used for OS containers and APP layouts
+/// that do not carry a single `internal_N.vhd`.
+fn extract_ntfs_contents(image_path: &Path, report: &mut dyn FnMut(u64, u64)) -> Result<PathBuf> {
+    let output_dir = image_path.with_extension("");
+
+    let mut fs = File::open(image_path)?;
+    let mut ntfs = Ntfs::new(&mut fs)?;
+    ntfs.read_upcase_table(&mut fs)?;
+    let root_directory = ntfs.root_directory(&mut fs)?;
+
+    create_dir_all(&output_dir)?;
+    let mut copied = 0u64;
+    extract_ntfs_directory(&ntfs, &mut fs, &root_directory, &output_dir, true, &mut copied, &mut |bytes| {
+        report(bytes, 0)
+    })?;
+
+    Ok(output_dir)
+}
+
+fn normalize_id(bytes: &[u8]) -> Result<String> {
+    let raw = std::str::from_utf8(bytes).map_err(|e| anyhow!("invalid id: {e}"))?;
+    Ok(raw.trim_matches(char::from(0)).trim().to_string())
+}
+
+fn read_bootid_from_reader(reader: &mut BufReader<File>, keys: &FsDecryptKeys) -> Result<BootId> {
+    let mut bootid_bytes = [0u8; std::mem::size_of::<BootId>()];
+    reader.read_exact(&mut bootid_bytes)?;
+
+    let bootid_cipher =
+        Aes128CbcDec::new_from_slices(&keys.bootid_key, &keys.bootid_iv).map_err(|e| anyhow!(e))?;
+    bootid_cipher
+        .clone()
+        .decrypt_padded_mut::<NoPadding>(&mut bootid_bytes)
+        .map_err(|e| anyhow!("Could not decrypt BootID: {e:#?}"))?;
+
+    Ok(unsafe { std::ptr::read_unaligned(bootid_bytes.as_ptr() as *const BootId) })
+}
+
+fn output_size_from_bootid(bootid: &BootId) -> u64 {
+    bootid
+        .block_count
+        .saturating_sub(bootid.header_block_count)
+        .saturating_mul(bootid.block_size)
+}
+
+fn container_type_name(container_type: u8) -> &'static str {
+    match container_type {
+        ContainerType::OS => "OS",
+        ContainerType::APP => "APP",
+        ContainerType::OPTION => "OPTION",
+        _ => "UNKNOWN",
+    }
+}
+
+fn format_version(version: &Version) -> String {
+    format!("{}.{:02}.{:02}", version.major, version.minor, version.release)
+}
+
+fn format_timestamp(ts: &Timestamp) -> String {
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        ts.year, ts.month, ts.day, ts.hour, ts.minute, ts.second
+    )
+}
+
+/// Name of the decrypted image, built from the BootID the way the upstream tool names it.
+fn output_filename(bootid: &BootId) -> Result<String> {
+    let os_id = normalize_id(&bootid.os_id)?;
+    let game_id = normalize_id(&bootid.game_id)?;
+    Ok(match bootid.container_type {
+        ContainerType::OS => format!(
+            "{os_id}_{:<04}.{:<02}.{:<02}_{}_{}.ntfs",
+            bootid.os_version.major,
+            bootid.os_version.minor,
+            bootid.os_version.release,
+            bootid.target_timestamp,
+            bootid.sequence_number
+        ),
+        ContainerType::APP => {
+            if bootid.sequence_number > 0 {
+                format!(
+                    "{game_id}_{}.{:<02}.{:<02}_{}_{}_{}.{:<02}.{:<02}.ntfs",
+                    unsafe { bootid.target_version.version.major },
+                    unsafe { bootid.target_version.version.minor },
+                    unsafe { bootid.target_version.version.release },
+                    bootid.target_timestamp,
+                    bootid.sequence_number,
+                    bootid.source_version.major,
+                    bootid.source_version.minor,
+                    bootid.source_version.release,
+                )
+            } else {
+                format!(
+                    "{game_id}_{}.{:<02}.{:<02}_{}_{}.ntfs",
+                    unsafe { bootid.target_version.version.major },
+                    unsafe { bootid.target_version.version.minor },
+                    unsafe { bootid.target_version.version.release },
+                    bootid.target_timestamp,
+                    bootid.sequence_number,
+                )
+            }
+        }
+        _ => {
+            let option = normalize_id(unsafe { &bootid.target_version.option })?;
+            format!(
+                "{game_id}_{}_{}_{}.exfat",
+                option,
+                bootid.target_timestamp,
+                bootid.sequence_number,
+            )
+        }
+    })
+}
+
+fn name_fields(bootid: &BootId) -> Result<NameFields> {
+    let upstream = output_filename(bootid)?;
+    let (name, extension) = match upstream.rsplit_once('.') {
+        Some((name, "exfat")) => (name.to_string(), "exfat"),
+        Some((name, _)) => (name.to_string(), "ntfs"),
+        None => (upstream.clone(), "ntfs"),
+    };
+    let (id, version) = match bootid.container_type {
+        ContainerType::OS => (normalize_id(&bootid.os_id)?, format_version(&bootid.os_version)),
+        ContainerType::APP => (
+            normalize_id(&bootid.game_id)?,
+            format_version(unsafe { &bootid.target_version.version }),
+        ),
+        _ => (
+            normalize_id(&bootid.game_id)?,
+            normalize_id(unsafe { &bootid.target_version.option })?,
+        ),
+    };
+    Ok(NameFields {
+        name,
+        extension,
+        id,
+        kind: container_type_name(bootid.container_type),
+        version,
+        timestamp: bootid.target_timestamp.to_string(),
+        sequence: bootid.sequence_number,
+    })
+}
+
+/// Reads the BootID of `path` and picks its output; also returns the decrypted image size.
+fn plan_output(
+    planner: &mut OutputPlanner,
+    path: &Path,
+    keys: &FsDecryptKeys,
+    no_extract: bool,
+    extract_tree: bool,
+) -> Result<(OutputTarget, u64)> {
+    let mut reader = BufReader::with_capacity(0x40000, File::open(path)?);
+    let bootid = read_bootid_from_reader(&mut reader, keys)?;
+    let extraction = match bootid.container_type {
+        _ if no_extract => Extraction::None,
+        ContainerType::OS | ContainerType::APP if !extract_tree => Extraction::Vhd,
+        _ => Extraction::Folder,
+    };
+    let target = planner.plan(path, &name_fields(&bootid)?, extraction)?;
+    Ok((target, output_size_from_bootid(&bootid)))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn decrypt_container(
+    path: &Path,
+    output_path: &Path,
+    replace: &[PathBuf],
+    no_extract: bool,
+    extract_tree: bool,
+    keys: &FsDecryptKeys,
+    result: &mut DecryptResult,
+    cancel: &AtomicBool,
+    mut progress: Option<&mut dyn FnMut(DecryptStage, u64, u64)>,
+) -> Result<()> {
+    let file = File::open(path)?;
+    let mut reader = BufReader::with_capacity(0x40000, file);
+
+    let bootid = read_bootid_from_reader(&mut reader, keys)?;
+
+    if bootid.container_type != ContainerType::OS
+        && bootid.container_type != ContainerType::APP
+        && bootid.container_type != ContainerType::OPTION
+    {
+        return Err(anyhow!("Unknown container type {}", bootid.container_type));
+    }
+
+    let os_id = normalize_id(&bootid.os_id)?;
+    let game_id = normalize_id(&bootid.game_id)?;
+    let id = match bootid.container_type {
+        ContainerType::OS => os_id.clone(),
+        _ => game_id.clone(),
+    };
+
+    result.key_id = Some(match bootid.container_type {
+        ContainerType::OS | ContainerType::APP => id.clone(),
+        _ => "OPTION".to_string(),
+    });
+    let keys = match bootid.container_type {
+        ContainerType::OS => keys
+            .game_keys_for(&os_id)
+            .ok_or_else(|| anyhow!("Key not found for {id}"))?,
+        ContainerType::APP => keys
+            .game_keys_for(&game_id)
+            .ok_or_else(|| anyhow!("Key not found for {id}"))?,
+        _ => GameKeys {
+            key: keys.option_key,
+            iv: Some(keys.option_iv),
+        },
+    };
+
+    result.container_type = Some(container_type_name(bootid.container_type).to_string());
+
+    let data_offset = bootid.header_block_count * bootid.block_size;
+    let key = keys.key;
+    let iv = if bootid.use_custom_iv { None } else { keys.iv };
+    result.derived_iv = iv.is_none();
+    let iv = match iv {
+        Some(iv) => iv,
+        None => {
+            reader.seek(SeekFrom::Start(data_offset))?;
+            let mut page: Vec<u8> = Vec::with_capacity(PAGE_SIZE as usize);
+            Read::by_ref(&mut reader).take(4096).read_to_end(&mut page)?;
+
+            if bootid.container_type == ContainerType::OPTION {
+                calculate_file_iv(key, EXFAT_HEADER, &page)?
+            } else {
+                calculate_file_iv(key, NTFS_HEADER, &page)?
+            }
+        }
+    };
+
+    clear_outputs(replace)?;
+    let output_file = File::create(output_path)?;
+    // A cancelled or failed decrypt would leave an image that looks complete but is not.
+    let mut partial = PartialArtifacts::new();
+    partial.track(output_path);
+    let output_size = output_size_from_bootid(&bootid);
+
+    output_file.set_len(output_size)?;
+
+    let mut writer = BufWriter::with_capacity(0x40000, output_file);
+    let cipher = Aes128Dec::new_from_slice(&key).map_err(|e| anyhow!(e))?;
+    let mut page: Vec<u8> = Vec::with_capacity(PAGE_SIZE as usize);
+    let mut page_iv = [0u8; 16];
+    let mut processed: u64 = 0;
+    let mut last_emit = Instant::now();
+    let mut last_reported: u64 = 0;
+    let started = Instant::now();
+
+    reader.seek(SeekFrom::Start(data_offset))?;
+
+    for _ in 0..(output_size / PAGE_SIZE) {
+        if cancel.load(Ordering::Relaxed) {
+            return Err(anyhow!(CANCELLED_MESSAGE));
+        }
+        let file_offset = reader.stream_position()? - data_offset;
+        let reference = Read::by_ref(&mut reader);
+
+        calculate_page_iv(file_offset, &iv, &mut page_iv);
+        page.clear();
+        reference.take(PAGE_SIZE).read_to_end(&mut page)?;
+
+        let page_cipher = Aes128CbcDec::inner_iv_slice_init(cipher.clone(), &page_iv)
+            .map_err(|e| anyhow!(e))?;
+        page_cipher
+            .decrypt_padded_mut::<NoPadding>(&mut page)
+            .map_err(|e| anyhow!(e))?;
+
+        writer.write_all(&page)?;
+        processed = processed.saturating_add(PAGE_SIZE);
+        if let Some(ref mut report) = progress {
+            if last_emit.elapsed() >= Duration::from_millis(120) {
+                report(DecryptStage::Decrypt, processed, output_size);
+                last_reported = processed;
+                last_emit = Instant::now();
+            }
+        }
+    }
+
+    writer.flush()?;
+    drop(writer);
+    partial.keep();
+    if let Some(ref mut report) = progress {
+        if processed != last_reported {
+            report(DecryptStage::Decrypt, processed, output_size);
+        }
+    }
+    let elapsed = started.elapsed();
+    result.decrypted_bytes = processed;
+    result.decrypt_ms = elapsed.as_millis() as u64;
+    result.throughput_bps = (processed as f64 / elapsed.as_secs_f64().max(0.001)) as u64;
+
+    if no_extract {
+        result.output = Some(output_path.to_string_lossy().into_owned());
+        return Ok(());
+    }
+
+    let mut report_extract = |copied: u64, total: u64| {
+        if let Some(ref mut report) = progress {
+            report(DecryptStage::Extract, copied, total);
+        }
+    };
+    let started = Instant::now();
+    match bootid.container_type {
+        ContainerType::OS | ContainerType::APP if extract_tree => {
+            match extract_ntfs_contents(output_path, &mut report_extract) {
+                Ok(dir) => {
+                    let _ = std::fs::remove_file(output_path);
+                    result.output = Some(dir.to_string_lossy().into_owned());
+                    result.extracted = true;
+                }
+                Err(e) => {
+                    result.output = Some(output_path.to_string_lossy().into_owned());
+                    result.warnings.push(format!("Failed to extract NTFS contents: {e:#}"));
+                }
+            }
+        }
+        ContainerType::OS | ContainerType::APP => match extract_internal_vhd(
+            output_path,
+            bootid.sequence_number,
+            &mut report_extract,
+        ) {
+            Ok(vhd_path) => {
+                let _ = std::fs::remove_file(output_path);
+                result.output = Some(vhd_path.to_string_lossy().into_owned());
+                result.extracted = true;
+            }
+            Err(e) => {
+                result.output = Some(output_path.to_string_lossy().into_owned());
+                result.warnings.push(format!("Failed to extract internal VHD: {e:#}"));
+            }
+        },
+        ContainerType::OPTION => match extract_exfat_contents(output_path, &mut report_extract) {
+            Ok(dir) => {
+                let _ = std::fs::remove_file(output_path);
+                result.output = Some(dir.to_string_lossy().into_owned());
+                result.extracted = true;
+            }
+            Err(e) => {
+                result.output = Some(output_path.to_string_lossy().into_owned());
+                result.warnings.push(format!("Failed to extract exfat contents: {e:#}"));
+            }
+        },
+        _ => {
+            result.output = Some(output_path.to_string_lossy().into_owned());
+        }
+    }
+    result.extract_ms = started.elapsed().as_millis() as u64;
+
+    if let (true, Some(output)) = (result.extracted, result.output.clone()) {
+        let started = Instant::now();
+        let mut report_hash = |hashed: u64, total: u64| {
+            if let Some(ref mut report) = progress {
+                report(DecryptStage::Hash, hashed, total);
+            }
+        };
+        match write_manifest(Path::new(&output), path, &mut report_hash) {
+            Ok(manifest) => result.manifest = Some(manifest.to_string_lossy().into_owned()),
+            Err(e) => result.warnings.push(format!("Failed to write manifest: {e:#}")),
+        }
+        result.hash_ms = started.elapsed().as_millis() as u64;
+    }
+
+    Ok(())
+}
+
+/// Worker count used when the caller does not pick one. Decryption is mostly I/O bound, so a
+/// few containers in flight saturate an NVMe drive.
+pub const DEFAULT_DECRYPT_WORKERS: usize = 4;
+
+enum WorkerEvent {
+    Progress {
+        index: usize,
+        stage: DecryptStage,
+        processed: u64,
+        total: u64,
+    },
+    Done {
+        index: usize,
+        result: Box<DecryptResult>,
+    },
+}
+
+fn new_result(path: &Path) -> DecryptResult {
+    DecryptResult {
+        input: path.to_string_lossy().into_owned(),
+        output: None,
+        container_type: None,
+        extracted: false,
+        skipped: false,
+        warnings: Vec::new(),
+        failed: false,
+        error: None,
+        input_bytes: path.metadata().map(|meta| meta.len()).unwrap_or(0),
+        decrypted_bytes: 0,
+        decrypt_ms: 0,
+        throughput_bps: 0,
+        extract_ms: 0,
+        hash_ms: 0,
+        total_ms: 0,
+        key_id: None,
+        derived_iv: false,
+        manifest: None,
+    }
+}
+
+fn decrypt_one(
+    path: &Path,
+    target: &OutputTarget,
+    no_extract: bool,
+    extract_tree: bool,
+    keys: &FsDecryptKeys,
+    cancel: &AtomicBool,
+    progress: Option<&mut dyn FnMut(DecryptStage, u64, u64)>,
+) -> DecryptResult {
+    let mut entry = new_result(path);
+    let (image, replace) = match target {
+        OutputTarget::Write { image, replace } => (image, replace),
+        OutputTarget::Skip { existing } => {
+            entry.output = Some(existing.to_string_lossy().into_owned());
+            entry.skipped = true;
+            entry.warnings.push("Output already exists; skipped".to_string());
+            return entry;
+        }
+    };
+    let started = Instant::now();
+    let decrypt_outcome = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        decrypt_container(path, image, replace, no_extract, extract_tree, keys, &mut entry, cancel, progress)
+    }));
+    match decrypt_outcome {
+        Ok(Ok(())) => {}
+        Ok(Err(err)) => {
+            entry.error = Some(err.to_string());
+            entry.failed = true;
+        }
+        Err(err) => {
+            entry.error = Some(format!("Decrypt panic: {}", panic_message(err)));
+            entry.failed = true;
+        }
+    }
+    entry.total_ms = started.elapsed().as_millis() as u64;
+    entry
+}
+
+/// Decrypts `files` on up to `workers` threads. Progress and results are reported on the
+/// calling thread as they arrive; the summary lists results in input order. With
+/// `extract_tree`, OS and APP images are extracted whole instead of only `internal_N.vhd`.
+/// Every output is named and placed per `output` before the first container starts, and the
+/// run fails up front when a target volume lacks the room. Setting `cancel` stops the
+/// containers in flight, deleting their partial images, and skips the rest.
+#[allow(clippy::too_many_arguments)]
+pub fn decrypt_game_files(
+    files: Vec<PathBuf>,
+    no_extract: bool,
+    extract_tree: bool,
+    key_url: Option<String>,
+    workers: usize,
+    output: &DecryptOutputOptions,
+    cancel: &AtomicBool,
+    mut progress: Option<&mut dyn FnMut(DecryptProgress)>,
+    mut on_result: Option<&mut dyn FnMut(DecryptResult)>,
+) -> Result<DecryptSummary> {
+    let (keys, info) = load_keys(key_url.as_deref())?;
+    let started = Instant::now();
+    let total_files = files.len();
+    let workers = workers.clamp(1, total_files.max(1));
+
+    let mut planner = OutputPlanner::new(output)?;
+    let plans: Vec<Result<(OutputTarget, u64), String>> = files
+        .iter()
+        .map(|path| plan_output(&mut planner, path, &keys, no_extract, extract_tree).map_err(|e| e.to_string()))
+        .collect();
+    let planned: Vec<(PathBuf, u64)> = plans
+        .iter()
+        .filter_map(|plan| match plan {
+            Ok((OutputTarget::Write { image, .. }, size)) => Some((image.clone(), *size)),
+            _ => None,
+        })
+        .collect();
+    check_free_space(&planned, !no_extract, workers)?;
+
+    let file_sizes: Vec<u64> = plans
+        .iter()
+        .zip(&files)
+        .map(|(plan, path)| match plan {
+            Ok((OutputTarget::Write { .. }, size)) => *size,
+            Ok((OutputTarget::Skip { .. }, _)) => 0,
+            Err(_) => path.metadata().map(|meta| meta.len()).unwrap_or(0),
+        })
+        .collect();
+    let total_bytes = file_sizes.iter().sum::<u64>().max(1);
+
+    let mut processed_total: u64 = 0;
+    let mut last_percent: u8 = 0;
+    let mut last_emit = Instant::now();
+
+    let mut emit_progress = |progress: &mut Option<&mut dyn FnMut(DecryptProgress)>,
+                             processed: u64,
+                             current_file: usize,
+                             stage: DecryptStage,
+                             file_processed: u64,
+                             file_total: u64,
+                             force: bool| {
+        if let Some(cb) = progress.as_mut() {
+            let percent = processed
+                .saturating_mul(100)
+                .saturating_div(total_bytes)
+                .min(100) as u8;
+            if force || percent != last_percent || last_emit.elapsed() >= Duration::from_millis(150) {
+                last_percent = percent;
+                last_emit = Instant::now();
+                cb(DecryptProgress {
+                    percent,
+                    processed,
+                    total: total_bytes,
+                    current_file,
+                    total_files,
+                    stage,
+                    file_processed,
+                    file_total,
+                });
+            }
+        }
+    };
+
+    if progress.is_some() {
+        emit_progress(&mut progress, processed_total, 0, DecryptStage::Decrypt, 0, 0, true);
+    }
+
+    let has_progress = progress.is_some();
+    let next_file = AtomicUsize::new(0);
+    let mut results: Vec<Option<DecryptResult>> = vec![None; total_files];
+    let mut last_in_file = vec![0u64; total_files];
+    let (tx, rx) = mpsc::channel::<WorkerEvent>();
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            let tx = tx.clone();
+            let (files, plans, keys, next_file) = (&files, &plans, &keys, &next_file);
+            scope.spawn(move || loop {
+                let index = next_file.fetch_add(1, Ordering::Relaxed);
+                let Some(path) = files.get(index) else {
+                    break;
+                };
+                let mut report = |stage: DecryptStage, processed: u64, total: u64| {
+                    let _ = tx.send(WorkerEvent::Progress {
+                        index,
+                        stage,
+                        processed,
+                        total,
+                    });
+                };
+                let progress_ref: Option<&mut dyn FnMut(DecryptStage, u64, u64)> =
+                    if has_progress { Some(&mut report) } else { None };
+                let result = match &plans[index] {
+                    _ if cancel.load(Ordering::SeqCst) => DecryptResult {
+                        failed: true,
+                        error: Some(CANCELLED_MESSAGE.to_string()),
+                        ..new_result(path)
+                    },
+                    Ok((target, _)) => decrypt_one(path, target, no_extract, extract_tree, keys, cancel, progress_ref),
+                    Err(error) => DecryptResult {
+                        failed: true,
+                        error: Some(error.clone()),
+                        ..new_result(path)
+                    },
+                };
+                let _ = tx.send(WorkerEvent::Done {
+                    index,
+                    result: Box::new(result),
+                });
+            });
+        }
+        drop(tx);
+
+        for event in rx {
+            match event {
+                WorkerEvent::Progress {
+                    index,
+                    stage,
+                    processed,
+                    total,
+                } => {
+                    if stage == DecryptStage::Decrypt {
+                        let delta = processed.saturating_sub(last_in_file[index]);
+                        last_in_file[index] = processed;
+                        processed_total = processed_total.saturating_add(delta).min(total_bytes);
+                    }
+                    emit_progress(&mut progress, processed_total, index + 1, stage, processed, total, false);
+                }
+                WorkerEvent::Done { index, result } => {
+                    // Failed or short containers still count as fully processed.
+                    if let Some(estimated) = file_sizes.get(index).copied() {
+                        if last_in_file[index] < estimated {
+                            processed_total = processed_total
+                                .saturating_add(estimated - last_in_file[index])
+                                .min(total_bytes);
+                            emit_progress(
+                                &mut progress,
+                                processed_total,
+                                index + 1,
+                                DecryptStage::Decrypt,
+                                estimated,
+                                estimated,
+                                true,
+                            );
+                        }
+                    }
+                    if let Some(cb) = on_result.as_mut() {
+                        cb((*result).clone());
+                    }
+                    results[index] = Some(*result);
+                }
+            }
+        }
+    });
+
+    if progress.is_some() {
+        processed_total = total_bytes;
+        emit_progress(&mut progress, processed_total, total_files, DecryptStage::Decrypt, 0, 0, true);
+    }
+
+    let results: Vec<DecryptResult> = results.into_iter().flatten().collect();
+    let elapsed = started.elapsed();
+    let decrypted_bytes = results.iter().map(|result| result.decrypted_bytes).sum::<u64>();
+    Ok(DecryptSummary {
+        results,
+        key_source: info.source,
+        key_game_count: info.game_count,
+        acceleration: crypto_acceleration(),
+        workers,
+        elapsed_ms: elapsed.as_millis() as u64,
+        decrypted_bytes,
+        throughput_bps: (decrypted_bytes as f64 / elapsed.as_secs_f64().max(0.001)) as u64,
+        cancelled: cancel.load(Ordering::SeqCst),
+    })
+}
+
+fn inspect_container(path: &Path, keys: &FsDecryptKeys) -> Result<ContainerInfo> {
+    let file = File::open(path)?;
+    let mut reader = BufReader::with_capacity(0x40000, file);
+    let bootid = read_bootid_from_reader(&mut reader, keys)?;
+
+    let mut info = ContainerInfo {
+        input: path.to_string_lossy().into_owned(),
+        container_type: Some(container_type_name(bootid.container_type).to_string()),
+        timestamp: Some(format_timestamp(&bootid.target_timestamp)),
+        sequence_number: Some(bootid.sequence_number),
+        output_size: Some(output_size_from_bootid(&bootid)),
+        ..ContainerInfo::default()
+    };
+    match bootid.container_type {
+        ContainerType::OS => {
+            let os_id = normalize_id(&bootid.os_id)?;
+            info.key_available = keys.game_keys_for(&os_id).is_some();
+            info.version = Some(format_version(&bootid.os_version));
+            info.id = Some(os_id);
+        }
+        ContainerType::APP => {
+            let game_id = normalize_id(&bootid.game_id)?;
+            info.key_available = keys.game_keys_for(&game_id).is_some();
+            info.version = Some(format_version(unsafe { &bootid.target_version.version }));
+            if bootid.sequence_number > 0 {
+                info.source_version = Some(format_version(&bootid.source_version));
+            }
+            info.id = Some(game_id);
+        }
+        ContainerType::OPTION => {
+            info.key_available = true;
+            info.version = Some(normalize_id(unsafe { &bootid.target_version.option })?);
+            info.id = Some(normalize_id(&bootid.game_id)?);
+        }
+        other => return Err(anyhow!("Unknown container type {other}")),
+    }
+    info.output_name = Some(output_filename(&bootid)?);
+    Ok(info)
+}
+
+/// Reads the BootID of each file without touching the encrypted body, so a batch can be
+/// checked in moments before committing to a full decrypt.
+pub fn inspect_containers(files: &[PathBuf], key_url: Option<String>) -> Result<Vec<ContainerInfo>> {
+    let (keys, _info) = load_keys(key_url.as_deref())?;
+    Ok(files
+        .iter()
+        .map(|path| {
+            inspect_container(path, &keys).unwrap_or_else(|err| ContainerInfo {
+                input: path.to_string_lossy().into_owned(),
+                error: Some(err.to_string()),
+                ..ContainerInfo::default()
+            })
+        })
+        .collect())
+}
+
+/// Four upper-case ASCII letters or digits, as game ids (`SDEZ`) and option names (`A001`) are.
+fn container_id(label: &str, raw: &str) -> Result<[u8; 4]> {
+    let id = raw.trim().to_uppercase();
+    let bytes: [u8; 4] = id
+        .as_bytes()
+        .try_into()
+        .map_err(|_| anyhow!("{label} must be four characters, got {id:?}"))?;
+    if !bytes.iter().all(u8::is_ascii_alphanumeric) {
+        return Err(anyhow!("{label} must be letters and digits, got {id:?}"));
+    }
+    Ok(bytes)
+}
+
+/// Encrypts `image` page by page behind an encrypted BootID header, mirroring
+/// [`decrypt_container`] for option containers.
+fn encrypt_option_image(image: &Path, output: &Path, bootid: &BootId, keys: &FsDecryptKeys) -> Result<()> {
+    let mut header = vec![0u8; (bootid.header_block_count * bootid.block_size) as usize];
+    let mut bootid_bytes = bootid.to_bytes();
+    Aes128CbcEnc::new_from_slices(&keys.bootid_key, &keys.bootid_iv)
+        .map_err(|e| anyhow!(e))?
+        .encrypt_padded_mut::<NoPadding>(&mut bootid_bytes, BOOTID_SIZE)
+        .map_err(|e| anyhow!("Could not encrypt BootID: {e:#?}"))?;
+    header[..BOOTID_SIZE].copy_from_slice(&bootid_bytes);
+
+    let mut reader = BufReader::with_capacity(0x40000, File::open(image)?);
+    let mut writer = BufWriter::with_capacity(0x40000, File::create(output)?);
+    writer.write_all(&header)?;
+
+    let cipher = Aes128Enc::new_from_slice(&keys.option_key).map_err(|e| anyhow!(e))?;
+    let mut page = vec![0u8; PAGE_SIZE as usize];
+    let mut page_iv = [0u8; 16];
+    let image_size = output_size_from_bootid(bootid);
+    for file_offset in (0..image_size).step_by(PAGE_SIZE as usize) {
+        reader.read_exact(&mut page)?;
+        calculate_page_iv(file_offset, &keys.option_iv, &mut page_iv);
+        Aes128CbcEnc::inner_iv_slice_init(cipher.clone(), &page_iv)
+            .map_err(|e| anyhow!(e))?
+            .encrypt_padded_mut::<NoPadding>(&mut page, PAGE_SIZE as usize)
+            .map_err(|e| anyhow!(e))?;
+        writer.write_all(&page)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn build_option_with_keys(
+    folder: &Path,
+    game_id: &str,
+    output_dir: Option<&Path>,
+    keys: &FsDecryptKeys,
+    report: &mut dyn FnMut(u64, u64),
+) -> Result<OptionBuildResult> {
+    if !folder.is_dir() {
+        return Err(anyhow!("Option folder not found: {}", folder.display()));
+    }
+    let option_name = folder
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| anyhow!("Option folder has no name: {}", folder.display()))?;
+    let game_id = container_id("Game id", game_id)?;
+    let option_id = container_id("Option name", option_name)?;
+    let output_dir = match output_dir {
+        Some(dir) => dir.to_path_buf(),
+        None => folder
+            .parent()
+            .ok_or_else(|| anyhow!("Option folder has no parent: {}", folder.display()))?
+            .to_path_buf(),
+    };
+    create_dir_all(&output_dir)?;
+
+    let started = Instant::now();
+    let image = tempfile::NamedTempFile::new_in(&output_dir)?;
+    let image_size = exfat::build_exfat_image(folder, image.path(), report)?;
+
+    let timestamp = Timestamp::from_datetime(&chrono::Local::now().naive_local());
+    let bootid = BootId::option(game_id, option_id, timestamp, PAGE_SIZE, 1, 1 + image_size / PAGE_SIZE);
+    let game_id = normalize_id(&game_id)?;
+    let option_id = normalize_id(&option_id)?;
+    // Named the way ICF option entries name their packages.
+    let output = output_dir.join(format!(
+        "{game_id}_{option_id}_{}_{}.opt",
+        bootid.target_timestamp, bootid.sequence_number
+    ));
+    encrypt_option_image(image.path(), &output, &bootid, keys)?;
+
+    Ok(OptionBuildResult {
+        output: output.to_string_lossy().into_owned(),
+        game_id,
+        option_id,
+        image_size,
+        build_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// Packs an option folder (e.g. `A001`) into an installable `.opt` container for `game_id`:
+/// an exFAT image of its contents, encrypted with the option key behind a fresh BootID. The
+/// container lands in `output_dir`, or next to the folder. `report` receives copied bytes.
+pub fn build_option_container(
+    folder: &Path,
+    game_id: &str,
+    output_dir: Option<&Path>,
+    key_url: Option<String>,
+    report: &mut dyn FnMut(u64, u64),
+) -> Result<OptionBuildResult> {
+    let (keys, _info) = load_keys(key_url.as_deref())?;
+    build_option_with_keys(folder, game_id, output_dir, &keys, report)
+}
+
+pub fn load_key_status(key_url: Option<String>) -> Result<KeyStatus> {
+    let (_keys, info) = load_keys(key_url.as_deref())?;
+    Ok(KeyStatus {
+        key_source: info.source,
+        key_game_count: info.game_count,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn test_keys() -> FsDecryptKeys {
+        FsDecryptKeys {
+            bootid_key: [0x11; 16],
+            bootid_iv: [0x22; 16],
+            option_key: [0x33; 16],
+            option_iv: [0x44; 16],
+            games: Default::default(),
+        }
+    }
+
+    #[test]
+    fn built_option_containers_decrypt_back_to_their_folder() {
+        let dir = TempDir::new().unwrap();
+        let folder = dir.path().join("A001");
+        fs::create_dir_all(folder.join("music").join("music000001")).unwrap();
+        let big: Vec<u8> = (0..20_000u32).map(|i| i as u8).collect();
+        fs::write(folder.join("data.conf"), "VerMajor=1\nVerMinor=45\n").unwrap();
+        fs::write(folder.join("music").join("music000001").join("Music.xml"), &big).unwrap();
+        fs::write(folder.join("empty.txt"), b"").unwrap();
+
+        let keys = test_keys();
+        let built = build_option_with_keys(&folder, "sdez", None, &keys, &mut |_, _| {}).unwrap();
+        assert_eq!((built.game_id.as_str(), built.option_id.as_str()), ("SDEZ", "A001"));
+        let output = PathBuf::from(&built.output);
+
+        let info = inspect_container(&output, &keys).unwrap();
+        assert_eq!(info.container_type.as_deref(), Some("OPTION"));
+        assert_eq!(info.version.as_deref(), Some("A001"));
+        assert_eq!(info.output_size, Some(built.image_size));
+
+        let options = DecryptOutputOptions::default();
+        let mut planner = OutputPlanner::new(&options).unwrap();
+        let (target, _) = plan_output(&mut planner, &output, &keys, false, false).unwrap();
+        let cancelled = decrypt_one(&output, &target, false, false, &keys, &AtomicBool::new(true), None);
+        assert_eq!(cancelled.error.as_deref(), Some(CANCELLED_MESSAGE));
+        if let OutputTarget::Write { image, .. } = &target {
+            assert!(!image.exists());
+        }
+        let result = decrypt_one(&output, &target, false, false, &keys, &AtomicBool::new(false), None);
+        assert!(!result.failed, "{:?}", result.error);
+        assert!(result.extracted, "{:?}", result.warnings);
+        let extracted = PathBuf::from(result.output.unwrap());
+        assert_eq!(fs::read(extracted.join("music").join("music000001").join("Music.xml")).unwrap(), big);
+        assert_eq!(fs::read(extracted.join("data.conf")).unwrap(), b"VerMajor=1\nVerMinor=45\n");
+        assert!(fs::read(extracted.join("empty.txt")).unwrap().is_empty());
+
+        assert!(build_option_with_keys(&folder, "SDEZX", None, &keys, &mut |_, _| {}).is_err());
+    }
+}
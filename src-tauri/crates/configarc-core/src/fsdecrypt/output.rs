@@ -0,0 +1,302 @@
+//! Where a decrypt run writes its images and extracted contents, under what names, and what
+//! happens when an earlier run already left output there.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::platform::free_space_bytes;
+
+/// What to do when the output of a container already exists.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverwritePolicy {
+    /// Leave the existing output and report the container as skipped.
+    Skip,
+    /// Write under the next free name, `name (2)`, `name (3)`, ...
+    Rename,
+    /// Delete the existing output once the container is known to decrypt.
+    #[default]
+    Replace,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DecryptOutputOptions {
+    /// Directory every output goes to; next to each input when `None`.
+    #[serde(default)]
+    pub output_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub overwrite: OverwritePolicy,
+    /// Image name without extension, e.g. `{id}_{version}_{seq}`; the upstream name when
+    /// `None`. See [`validate_name_template`] for the placeholders.
+    #[serde(default)]
+    pub name_template: Option<String>,
+}
+
+/// What a container's name is built from, read from its BootID.
+pub(super) struct NameFields {
+    /// The upstream tool's name for the image, without extension.
+    pub name: String,
+    /// `ntfs` or `exfat`.
+    pub extension: &'static str,
+    pub id: String,
+    pub kind: &'static str,
+    /// Target version, or the option name for OPTION containers.
+    pub version: String,
+    pub timestamp: String,
+    pub sequence: u8,
+}
+
+/// What extraction leaves beside the image once it has been decrypted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Extraction {
+    None,
+    /// A folder named after the image.
+    Folder,
+    /// `internal_N.vhd` copied out as `<image>.vhd`.
+    Vhd,
+}
+
+/// Everything a container with `image` as its decrypted image may leave behind.
+fn outputs(image: &Path, extraction: Extraction) -> Vec<PathBuf> {
+    match extraction {
+        Extraction::None => vec![image.to_path_buf()],
+        Extraction::Folder => vec![image.to_path_buf(), image.with_extension("")],
+        Extraction::Vhd => vec![image.to_path_buf(), image.with_extension("vhd")],
+    }
+}
+
+pub(super) enum OutputTarget {
+    /// Decrypt into `image`, deleting `replace` first.
+    Write { image: PathBuf, replace: Vec<PathBuf> },
+    /// Output already exists at `existing` and the policy is [`OverwritePolicy::Skip`].
+    Skip { existing: PathBuf },
+}
+
+const PLACEHOLDERS: [&str; 7] = ["name", "input", "id", "type", "version", "timestamp", "seq"];
+
+fn render(template: &str, value: impl Fn(&str) -> String) -> Result<String> {
+    let mut rendered = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| anyhow!("Unclosed placeholder in name template {template}"))?;
+        let placeholder = &rest[start + 1..start + end];
+        if !PLACEHOLDERS.contains(&placeholder) {
+            return Err(anyhow!(
+                "Unknown placeholder {{{placeholder}}}; use one of {}",
+                PLACEHOLDERS.map(|p| format!("{{{p}}}")).join(", ")
+            ));
+        }
+        rendered.push_str(&value(placeholder));
+        rest = &rest[start + end + 1..];
+    }
+    rendered.push_str(rest);
+    let rendered = rendered.trim().to_string();
+    if rendered.is_empty() || rendered == "." || rendered == ".." {
+        return Err(anyhow!("Name template {template} renders to an empty name"));
+    }
+    if let Some(bad) = rendered.chars().find(|c| r#"<>:"/\|?*"#.contains(*c) || c.is_control()) {
+        return Err(anyhow!("Name template {template} produces the invalid character {bad:?}"));
+    }
+    Ok(rendered)
+}
+
+/// Checks a naming template without a container at hand. Placeholders are `{name}` (the
+/// upstream name), `{input}` (the container's file name without extension), `{id}`,
+/// `{type}`, `{version}`, `{timestamp}` and `{seq}`.
+pub fn validate_name_template(template: &str) -> Result<()> {
+    render(template, |_| "x".to_string()).map(|_| ())
+}
+
+/// Picks the output of every container of a run in turn. Names taken earlier in the same run
+/// count as existing output but are always renamed around, whatever the policy, so two
+/// containers rendering to one name never overwrite each other.
+pub(super) struct OutputPlanner<'a> {
+    options: &'a DecryptOutputOptions,
+    claimed: HashSet<PathBuf>,
+}
+
+impl<'a> OutputPlanner<'a> {
+    pub fn new(options: &'a DecryptOutputOptions) -> Result<Self> {
+        if let Some(template) = &options.name_template {
+            validate_name_template(template)?;
+        }
+        if let Some(dir) = &options.output_dir {
+            fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        Ok(Self {
+            options,
+            claimed: HashSet::new(),
+        })
+    }
+
+    pub fn plan(&mut self, input: &Path, fields: &NameFields, extraction: Extraction) -> Result<OutputTarget> {
+        let dir = match &self.options.output_dir {
+            Some(dir) => dir.clone(),
+            None => input.parent().map(Path::to_path_buf).unwrap_or_default(),
+        };
+        let stem = match &self.options.name_template {
+            Some(template) => render(template, |placeholder| match placeholder {
+                "name" => fields.name.clone(),
+                "input" => input
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                "id" => fields.id.clone(),
+                "type" => fields.kind.to_string(),
+                "version" => fields.version.clone(),
+                "timestamp" => fields.timestamp.clone(),
+                _ => fields.sequence.to_string(),
+            })?,
+            None => fields.name.clone(),
+        };
+
+        let mut attempt = 1;
+        loop {
+            let name = match attempt {
+                1 => format!("{stem}.{}", fields.extension),
+                n => format!("{stem} ({n}).{}", fields.extension),
+            };
+            let image = dir.join(name);
+            let targets = outputs(&image, extraction);
+            attempt += 1;
+            if targets.iter().any(|target| self.claimed.contains(target)) {
+                continue;
+            }
+            let existing: Vec<PathBuf> = targets.iter().filter(|target| target.exists()).cloned().collect();
+            let replace = match (existing.last().cloned(), self.options.overwrite) {
+                (None, _) => Vec::new(),
+                (Some(_), OverwritePolicy::Rename) => continue,
+                (Some(last), OverwritePolicy::Skip) => return Ok(OutputTarget::Skip { existing: last }),
+                (Some(_), OverwritePolicy::Replace) => existing,
+            };
+            self.claimed.extend(targets);
+            return Ok(OutputTarget::Write { image, replace });
+        }
+    }
+}
+
+/// Deletes what a [`OutputTarget::Write`] is about to replace.
+pub(super) fn clear_outputs(paths: &[PathBuf]) -> Result<()> {
+    for path in paths {
+        let removed = if path.is_dir() { fs::remove_dir_all(path) } else { fs::remove_file(path) };
+        match removed {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                return Err(anyhow!("Failed to replace {}: {e}", path.display()));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Fails when a volume lacks room for the images planned on it. Extracting needs the image
+/// and its contents side by side, so room for `in_flight` more of the largest image is
+/// required on top.
+pub(super) fn check_free_space(planned: &[(PathBuf, u64)], extracting: bool, in_flight: usize) -> Result<()> {
+    let mut by_dir: HashMap<&Path, (u64, u64, usize)> = HashMap::new();
+    for (image, size) in planned {
+        let dir = image.parent().unwrap_or(Path::new("."));
+        let (total, largest, count) = by_dir.entry(dir).or_default();
+        *total = total.saturating_add(*size);
+        *largest = (*largest).max(*size);
+        *count += 1;
+    }
+    for (dir, (total, largest, count)) in by_dir {
+        let required = if extracting {
+            total.saturating_add(largest.saturating_mul(in_flight.min(count) as u64))
+        } else {
+            total
+        };
+        if let Some(free) = free_space_bytes(dir) {
+            if free < required {
+                return Err(anyhow!(
+                    "Not enough free space in {}: {} MB needed, {} MB free",
+                    dir.display(),
+                    required.div_ceil(1024 * 1024),
+                    free / (1024 * 1024)
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn fields() -> NameFields {
+        NameFields {
+            name: "SDEZ_1.40.00_20240101_0".to_string(),
+            extension: "ntfs",
+            id: "SDEZ".to_string(),
+            kind: "APP",
+            version: "1.40.00".to_string(),
+            timestamp: "20240101".to_string(),
+            sequence: 0,
+        }
+    }
+
+    fn image_of(target: OutputTarget) -> PathBuf {
+        match target {
+            OutputTarget::Write { image, .. } => image,
+            OutputTarget::Skip { existing } => panic!("unexpected skip of {}", existing.display()),
+        }
+    }
+
+    #[test]
+    fn names_outputs_and_applies_the_overwrite_policy() {
+        let dir = TempDir::new().unwrap();
+        let input = dir.path().join("in").join("SDEZ.app");
+        let out = dir.path().join("out");
+        assert!(validate_name_template("{id}/{version}").is_err());
+        assert!(validate_name_template("{game}").is_err());
+        assert!(validate_name_template("{id}_{version}_{seq}").is_ok());
+
+        let mut options = DecryptOutputOptions {
+            output_dir: Some(out.clone()),
+            overwrite: OverwritePolicy::Skip,
+            name_template: Some("{id}_{version}".to_string()),
+        };
+        let mut planner = OutputPlanner::new(&options).unwrap();
+        let first = image_of(planner.plan(&input, &fields(), Extraction::Folder).unwrap());
+        assert_eq!(first, out.join("SDEZ_1.40.00.ntfs"));
+        let second = image_of(planner.plan(&input, &fields(), Extraction::Folder).unwrap());
+        assert_eq!(second, out.join("SDEZ_1.40.00 (2).ntfs"));
+
+        fs::create_dir_all(out.join("SDEZ_1.40.00")).unwrap();
+        let mut planner = OutputPlanner::new(&options).unwrap();
+        match planner.plan(&input, &fields(), Extraction::Folder).unwrap() {
+            OutputTarget::Skip { existing } => assert_eq!(existing, out.join("SDEZ_1.40.00")),
+            OutputTarget::Write { .. } => panic!("existing output should be skipped"),
+        }
+
+        options.overwrite = OverwritePolicy::Rename;
+        let mut planner = OutputPlanner::new(&options).unwrap();
+        let renamed = image_of(planner.plan(&input, &fields(), Extraction::Folder).unwrap());
+        assert_eq!(renamed, out.join("SDEZ_1.40.00 (2).ntfs"));
+
+        options.overwrite = OverwritePolicy::Replace;
+        options.name_template = None;
+        fs::create_dir_all(out.join("SDEZ_1.40.00_20240101_0")).unwrap();
+        let mut planner = OutputPlanner::new(&options).unwrap();
+        match planner.plan(&input, &fields(), Extraction::Folder).unwrap() {
+            OutputTarget::Write { image, replace } => {
+                assert_eq!(image, out.join("SDEZ_1.40.00_20240101_0.ntfs"));
+                clear_outputs(&replace).unwrap();
+            }
+            OutputTarget::Skip { .. } => panic!("existing output should be replaced"),
+        }
+        assert!(!out.join("SDEZ_1.40.00_20240101_0").exists());
+    }
+}
@@ -0,0 +1,204 @@
+//! Unity and DirectX shader caches left behind by the tracked games. Stale shader caches are a
+//! common cause of graphical corruption after a GPU driver update; clearing them is safe
+//! because the driver and Unity rebuild them on the next launch.
+
+use super::model::Game;
+use super::store;
+use crate::error::GameError;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+  pub game_id: String,
+  /// `unity_cache` or `shader_cache`.
+  pub kind: String,
+  pub path: String,
+  pub size: u64,
+  pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheReport {
+  pub confirmed: bool,
+  pub total_size: u64,
+  pub entries: Vec<CacheEntry>,
+  pub failed: Vec<CacheEntry>,
+}
+
+/// Shader cache folders under %LOCALAPPDATA% written by the DirectX runtime and GPU drivers.
+const LOCAL_SHADER_CACHES: &[&str] = &[
+  "D3DSCache",
+  "NVIDIA/DXCache",
+  "NVIDIA/GLCache",
+  "AMD/DxCache",
+  "AMD/DxcCache",
+  "AMD/GLCache",
+  "Intel/ShaderCache",
+];
+
+fn path_size(path: &Path) -> u64 {
+  let Ok(meta) = fs::symlink_metadata(path) else {
+    return 0;
+  };
+  if !meta.is_dir() {
+    return meta.len();
+  }
+  fs::read_dir(path)
+    .map(|entries| entries.flatten().map(|entry| path_size(&entry.path())).sum())
+    .unwrap_or(0)
+}
+
+fn exe_stem(game: &Game) -> Option<String> {
+  Path::new(&game.executable_path)
+    .file_stem()
+    .and_then(|stem| stem.to_str())
+    .filter(|stem| !stem.is_empty())
+    .map(|stem| stem.to_lowercase())
+}
+
+/// Company and product name Unity reads from `<exe>_Data/app.info`; they name the title's
+/// folders under LocalLow.
+fn unity_identity(exe: &Path) -> Option<(String, String)> {
+  let stem = exe.file_stem()?.to_str()?;
+  let info = fs::read_to_string(exe.with_file_name(format!("{}_Data", stem)).join("app.info")).ok()?;
+  let mut lines = info.lines().map(str::trim);
+  let company = lines.next().filter(|l| !l.is_empty())?.to_string();
+  let product = lines.next().filter(|l| !l.is_empty())?.to_string();
+  Some((company, product))
+}
+
+/// Entries of a shader cache folder that belong to `stem`: files named after the executable,
+/// and per-application subfolders holding such files (the layout D3DSCache uses).
+fn shader_cache_entries(cache_root: &Path, stem: &str) -> Vec<PathBuf> {
+  let named_after = |path: &Path| {
+    path
+      .file_name()
+      .and_then(|name| name.to_str())
+      .map(|name| name.to_lowercase().starts_with(stem))
+      .unwrap_or(false)
+  };
+  let Ok(entries) = fs::read_dir(cache_root) else {
+    return Vec::new();
+  };
+  let mut found: Vec<PathBuf> = entries
+    .flatten()
+    .map(|entry| entry.path())
+    .filter(|path| {
+      named_after(path)
+        || (path.is_dir()
+          && fs::read_dir(path)
+            .map(|inner| inner.flatten().any(|entry| named_after(&entry.path())))
+            .unwrap_or(false))
+    })
+    .collect();
+  found.sort();
+  found
+}
+
+fn caches_for_game(game: &Game, local_appdata: Option<&Path>, local_low: Option<&Path>) -> Vec<(&'static str, PathBuf)> {
+  let mut found = Vec::new();
+  if let (Some(local_low), Some((company, product))) = (local_low, unity_identity(Path::new(&game.executable_path))) {
+    let cache = local_low.join("Unity").join(format!("{}_{}", company, product));
+    if cache.exists() {
+      found.push(("unity_cache", cache));
+    }
+  }
+  if let (Some(local_appdata), Some(stem)) = (local_appdata, exe_stem(game)) {
+    for rel in LOCAL_SHADER_CACHES {
+      for path in shader_cache_entries(&local_appdata.join(rel), &stem) {
+        found.push(("shader_cache", path));
+      }
+    }
+  }
+  found
+}
+
+/// Reports the caches of one game, or of every tracked game, with their sizes. Without
+/// `confirm` nothing is deleted.
+pub fn clear_game_caches(game_id: Option<&str>, confirm: bool) -> Result<CacheReport, GameError> {
+  let games: Vec<Game> = store::list_games()?
+    .into_iter()
+    .filter(|g| game_id.is_none_or(|id| g.id == id))
+    .collect();
+  if let Some(id) = game_id.filter(|_| games.is_empty()) {
+    return Err(GameError::NotFound(id.to_string()));
+  }
+
+  let local_appdata = env::var_os("LOCALAPPDATA").map(PathBuf::from);
+  let local_low = env::var_os("USERPROFILE").map(|home| PathBuf::from(home).join("AppData").join("LocalLow"));
+
+  let mut report = CacheReport {
+    confirmed: confirm,
+    total_size: 0,
+    entries: Vec::new(),
+    failed: Vec::new(),
+  };
+  let mut seen = Vec::new();
+  for game in &games {
+    for (kind, path) in caches_for_game(game, local_appdata.as_deref(), local_low.as_deref()) {
+      // Titles sharing an executable name share their cache entries; count them once.
+      if seen.contains(&path) {
+        continue;
+      }
+      seen.push(path.clone());
+      let entry = CacheEntry {
+        game_id: game.id.clone(),
+        kind: kind.to_string(),
+        path: path.to_string_lossy().to_string(),
+        size: path_size(&path),
+        error: None,
+      };
+      let result = if !confirm {
+        Ok(())
+      } else if path.is_dir() {
+        fs::remove_dir_all(&path)
+      } else {
+        fs::remove_file(&path)
+      };
+      match result {
+        Ok(()) => {
+          report.total_size += entry.size;
+          report.entries.push(entry);
+        }
+        Err(e) => report.failed.push(CacheEntry {
+          error: Some(e.to_string()),
+          ..entry
+        }),
+      }
+    }
+  }
+  Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::TempDir;
+
+  #[test]
+  fn finds_unity_identity_and_per_title_shader_entries() {
+    let dir = TempDir::new().unwrap();
+    let data = dir.path().join("Sinmai_Data");
+    fs::create_dir_all(&data).unwrap();
+    fs::write(data.join("app.info"), "sega\nSinmai\n").unwrap();
+    assert_eq!(
+      unity_identity(&dir.path().join("Sinmai.exe")),
+      Some(("sega".to_string(), "Sinmai".to_string()))
+    );
+
+    let cache = dir.path().join("D3DSCache");
+    fs::create_dir_all(cache.join("1a2b")).unwrap();
+    fs::create_dir_all(cache.join("3c4d")).unwrap();
+    fs::write(cache.join("1a2b").join("sinmai.idx"), b"idx").unwrap();
+    fs::write(cache.join("3c4d").join("explorer.idx"), b"idx").unwrap();
+    fs::write(cache.join("Sinmai_0001.bin"), b"bin").unwrap();
+    assert_eq!(
+      shader_cache_entries(&cache, "sinmai"),
+      vec![cache.join("1a2b"), cache.join("Sinmai_0001.bin")]
+    );
+    assert_eq!(path_size(&cache.join("1a2b")), 3);
+  }
+}
@@ -0,0 +1,310 @@
+use super::model::Game;
+use super::store;
+use crate::config::paths::{clear_active_game_id, get_active_game_id, segatools_root_for_game_id};
+use crate::error::GameError;
+use crate::trusted::{deployed_files_for_root, trust_state_paths_for_root};
+#[cfg(feature = "vhd")]
+use crate::vhd::runtime_delta_paths_for_game;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CleanupOptions {
+  #[serde(default)]
+  pub remove_launcher_state: bool,
+  #[serde(default)]
+  pub remove_segatools_files: bool,
+  #[serde(default)]
+  pub remove_runtime_deltas: bool,
+  #[serde(default)]
+  pub remove_temp_files: bool,
+  /// Desktop and Start menu shortcuts that start the game through `--launch`.
+  #[serde(default)]
+  pub remove_shortcuts: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CleanupItem {
+  pub kind: String,
+  pub path: String,
+  pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CleanupReport {
+  pub game_id: String,
+  pub confirmed: bool,
+  pub removed: Vec<CleanupItem>,
+  pub failed: Vec<CleanupItem>,
+}
+
+/// Shortcut files read when looking for `--launch`; real ones are a few KiB.
+const MAX_SHORTCUT_BYTES: u64 = 64 * 1024;
+const SHORTCUT_EXTENSIONS: [&str; 4] = ["lnk", "url", "bat", "cmd"];
+
+struct PlannedRemoval {
+  kind: &'static str,
+  path: PathBuf,
+}
+
+fn remove_path(path: &Path) -> std::io::Result<()> {
+  if path.is_dir() {
+    fs::remove_dir_all(path)
+  } else {
+    fs::remove_file(path)
+  }
+}
+
+/// Folders users keep launcher shortcuts in: the desktop and the Start menu.
+fn shortcut_dirs() -> Vec<PathBuf> {
+  let mut dirs = Vec::new();
+  if let Some(home) = std::env::var_os("USERPROFILE") {
+    dirs.push(PathBuf::from(home).join("Desktop"));
+  }
+  if let Some(appdata) = std::env::var_os("APPDATA") {
+    dirs.push(PathBuf::from(appdata).join("Microsoft").join("Windows").join("Start Menu").join("Programs"));
+  }
+  dirs
+}
+
+/// Values passed to `--launch` in `text`, quoted or not, as `CliLaunch` accepts them.
+fn launch_targets(text: &str) -> Vec<String> {
+  text
+    .match_indices("--launch")
+    .filter_map(|(at, flag)| {
+      let rest = &text[at + flag.len()..];
+      let rest = rest.strip_prefix('=').unwrap_or_else(|| rest.trim_start_matches([' ', '\t']));
+      let value = match rest.strip_prefix('"') {
+        Some(quoted) => quoted.split('"').next().unwrap_or_default(),
+        None => rest.split(|c: char| c.is_whitespace() || c.is_control() || c == '"').next().unwrap_or_default(),
+      };
+      (!value.trim().is_empty()).then(|| value.trim().to_string())
+    })
+    .collect()
+}
+
+/// Whether the shortcut at `path` runs `launcher` with `--launch <game id>`. Other programs
+/// take `--launch` too, and a game name may be shared, so both have to match. `.lnk` files keep
+/// their arguments as UTF-16, so the bytes are read both ways.
+fn shortcut_launches(path: &Path, launcher: &str, game: &Game) -> bool {
+  let Ok(bytes) = fs::read(path) else {
+    return false;
+  };
+  let utf16 = |offset: usize| {
+    let units: Vec<u16> = bytes[offset.min(bytes.len())..]
+      .chunks_exact(2)
+      .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+      .collect();
+    String::from_utf16_lossy(&units)
+  };
+  [String::from_utf8_lossy(&bytes).into_owned(), utf16(0), utf16(1)].iter().any(|text| {
+    text.to_lowercase().contains(launcher)
+      && launch_targets(text).iter().any(|target| target.eq_ignore_ascii_case(&game.id))
+  })
+}
+
+/// Shortcuts in `dirs`, and one folder level below, that start `game` through the launcher
+/// executable at `launcher`.
+pub fn find_game_shortcuts(dirs: &[PathBuf], launcher: &Path, game: &Game) -> Vec<PathBuf> {
+  let mut found = Vec::new();
+  let Some(launcher) = launcher.file_name().map(|name| name.to_string_lossy().to_lowercase()) else {
+    return found;
+  };
+  let mut pending: Vec<(PathBuf, usize)> = dirs.iter().map(|dir| (dir.clone(), 0)).collect();
+  while let Some((dir, depth)) = pending.pop() {
+    let Ok(entries) = fs::read_dir(&dir) else {
+      continue;
+    };
+    for entry in entries.flatten() {
+      let path = entry.path();
+      let Ok(meta) = entry.metadata() else {
+        continue;
+      };
+      if meta.is_dir() {
+        if depth < 1 {
+          pending.push((path, depth + 1));
+        }
+        continue;
+      }
+      let is_shortcut = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| SHORTCUT_EXTENSIONS.iter().any(|known| ext.eq_ignore_ascii_case(known)));
+      if is_shortcut && meta.len() <= MAX_SHORTCUT_BYTES && shortcut_launches(&path, &launcher, game) {
+        found.push(path);
+      }
+    }
+  }
+  found.sort();
+  found
+}
+
+fn plan_cleanup(game: &Game, options: &CleanupOptions) -> Vec<PlannedRemoval> {
+  let game_id = game.id.as_str();
+  let root = segatools_root_for_game_id(game_id);
+  let mut plan = Vec::new();
+
+  #[cfg(feature = "vhd")]
+  if options.remove_runtime_deltas {
+    for path in runtime_delta_paths_for_game(game_id) {
+      plan.push(PlannedRemoval { kind: "runtime_delta", path });
+    }
+  }
+
+  if options.remove_temp_files {
+    for name in ["launch_temp.bat", "segatools_launch.ini"] {
+      let path = root.join(name);
+      if path.exists() {
+        plan.push(PlannedRemoval { kind: "temp_file", path });
+      }
+    }
+  }
+
+  if options.remove_segatools_files {
+    for rel in deployed_files_for_root(&root) {
+      let path = root.join(&rel);
+      if path.is_file() {
+        plan.push(PlannedRemoval { kind: "segatools_file", path });
+      }
+    }
+    for path in trust_state_paths_for_root(&root) {
+      if path.exists() {
+        plan.push(PlannedRemoval { kind: "segatools_file", path });
+      }
+    }
+  }
+
+  if options.remove_shortcuts {
+    if let Ok(launcher) = std::env::current_exe() {
+      for path in find_game_shortcuts(&shortcut_dirs(), &launcher, game) {
+        plan.push(PlannedRemoval { kind: "shortcut", path });
+      }
+    }
+  }
+
+  // The per-game Segatools directory only ever holds launcher-generated state,
+  // so it is removed last and as a whole once everything above is gone.
+  if options.remove_launcher_state && root.exists() {
+    plan.push(PlannedRemoval { kind: "launcher_state", path: root });
+  }
+
+  plan
+}
+
+/// Removes launcher-side data for a game without touching the game files themselves.
+/// Without `confirm` nothing is deleted and the report lists what would be removed.
+pub fn cleanup_game(
+  game_id: &str,
+  options: &CleanupOptions,
+  confirm: bool,
+) -> Result<CleanupReport, GameError> {
+  let games = store::list_games()?;
+  let game = games
+    .iter()
+    .find(|g| g.id == game_id)
+    .ok_or_else(|| GameError::NotFound(game_id.to_string()))?;
+
+  let plan = plan_cleanup(game, options);
+  let mut removed = Vec::new();
+  let mut failed = Vec::new();
+
+  for item in plan {
+    let entry = CleanupItem {
+      kind: item.kind.to_string(),
+      path: item.path.to_string_lossy().to_string(),
+      error: None,
+    };
+    if !confirm {
+      removed.push(entry);
+      continue;
+    }
+    match remove_path(&item.path) {
+      Ok(()) => removed.push(entry),
+      Err(e) => failed.push(CleanupItem {
+        error: Some(e.to_string()),
+        ..entry
+      }),
+    }
+  }
+
+  if options.remove_launcher_state {
+    let entry = CleanupItem {
+      kind: "game_entry".to_string(),
+      path: game_id.to_string(),
+      error: None,
+    };
+    if !confirm {
+      removed.push(entry);
+    } else {
+      match store::delete_game(game_id) {
+        Ok(()) => {
+          removed.push(entry);
+          if let Ok(Some(active)) = get_active_game_id() {
+            if active == game_id {
+              let _ = clear_active_game_id();
+            }
+          }
+        }
+        Err(e) => failed.push(CleanupItem {
+          error: Some(e.to_string()),
+          ..entry
+        }),
+      }
+    }
+  }
+
+  Ok(CleanupReport {
+    game_id: game_id.to_string(),
+    confirmed: confirm,
+    removed,
+    failed,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::TempDir;
+
+  fn game() -> Game {
+    serde_json::from_value(serde_json::json!({
+      "id": "sdez-1",
+      "name": "maimai DX",
+      "executable_path": "C:\\Games\\SDEZ\\Sinmai.exe",
+      "working_dir": null,
+      "launch_args": [],
+      "enabled": true,
+      "tags": []
+    }))
+    .unwrap()
+  }
+
+  #[test]
+  fn finds_only_shortcuts_that_launch_the_game() {
+    let tmp = TempDir::new().unwrap();
+    let desktop = tmp.path().join("Desktop");
+    let programs = tmp.path().join("Programs").join("ConfigArc");
+    fs::create_dir_all(&desktop).unwrap();
+    fs::create_dir_all(&programs).unwrap();
+
+    let bat = desktop.join("maimai.bat");
+    fs::write(&bat, "\"C:\\ConfigArc\\configarc-launcher.exe\" --launch=SDEZ-1\r\n").unwrap();
+    // A .lnk keeps its target and arguments as UTF-16; start at an odd offset like a real header would.
+    let lnk_path = programs.join("maimai DX.lnk");
+    let mut lnk = vec![0x4c, 0, 0];
+    let target = "C:\\ConfigArc\\ConfigArc-Launcher.exe --launch \"sdez-1\" --profile main";
+    lnk.extend(target.encode_utf16().flat_map(u16::to_le_bytes));
+    fs::write(&lnk_path, lnk).unwrap();
+    fs::write(desktop.join("by-name.bat"), "configarc-launcher.exe --launch \"maimai DX\"\r\n").unwrap();
+    fs::write(desktop.join("other-tool.bat"), "othertool.exe --launch sdez-1\r\n").unwrap();
+    fs::write(desktop.join("chunithm.url"), "[InternetShortcut]\nURL=configarc-launcher.exe --launch sdhd-1\n").unwrap();
+    fs::write(desktop.join("notes.txt"), "configarc-launcher.exe --launch sdez-1").unwrap();
+
+    let dirs = [desktop, tmp.path().join("Programs"), tmp.path().join("missing")];
+    let found = find_game_shortcuts(&dirs, &tmp.path().join("configarc-launcher.exe"), &game());
+    assert_eq!(found.len(), 2);
+    assert!(found.contains(&bat) && found.contains(&lnk_path));
+    assert_eq!(launch_targets("x --launch \"a b\" --launch c"), ["a b", "c"]);
+  }
+}
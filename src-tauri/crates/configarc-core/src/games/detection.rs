@@ -0,0 +1,243 @@
+use crate::error::GameError;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const BUNDLED_RULES: &str = include_str!("detection_rules.json");
+
+/// Signature of a supported title. Rules are checked in order and the first one whose
+/// executable and data markers are all present in a folder wins.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DetectionRule {
+  pub name: String,
+  /// Candidate executable names, tried in order.
+  pub executables: Vec<String>,
+  /// Files or folders, relative to the executable, that must also exist.
+  #[serde(default)]
+  pub data_markers: Vec<String>,
+  #[serde(default)]
+  pub default_args: Vec<String>,
+  /// Hook DLL injected next to the game, looked up in the game's segatools folder.
+  #[serde(default)]
+  pub hook_dll: Option<String>,
+  /// Process name handed to inject.exe; defaults to the executable without extension.
+  #[serde(default)]
+  pub inject_target: Option<String>,
+  /// Per-version overrides, checked in order; the first range containing the game's version
+  /// applies.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub versions: Vec<VersionDefaults>,
+}
+
+/// Defaults for a range of game versions as read from the ICF ("1.45.00").
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionDefaults {
+  /// Inclusive lower bound; open when missing.
+  #[serde(default)]
+  pub min_version: Option<String>,
+  /// Exclusive upper bound; open when missing.
+  #[serde(default)]
+  pub max_version: Option<String>,
+  /// Replaces the rule's `default_args` for this range.
+  #[serde(default)]
+  pub default_args: Option<Vec<String>>,
+  /// segatools.ini sections this range uses on top of the title's own.
+  #[serde(default)]
+  pub extra_sections: Vec<String>,
+  /// Sections this range no longer reads.
+  #[serde(default)]
+  pub removed_sections: Vec<String>,
+}
+
+fn version_key(version: &str) -> Vec<u32> {
+  version
+    .trim()
+    .trim_start_matches(['v', 'V'])
+    .split(['.', '-', '_'])
+    .map(|part| part.parse().unwrap_or(0))
+    .collect()
+}
+
+/// Compares dotted versions numerically, so "1.9" sorts before "1.10" and "1.40" equals "1.40.00".
+pub fn compare_versions(left: &str, right: &str) -> Ordering {
+  let (mut left, mut right) = (version_key(left), version_key(right));
+  let len = left.len().max(right.len());
+  left.resize(len, 0);
+  right.resize(len, 0);
+  left.cmp(&right)
+}
+
+impl VersionDefaults {
+  pub fn contains(&self, version: &str) -> bool {
+    let above_min = self
+      .min_version
+      .as_deref()
+      .is_none_or(|min| compare_versions(version, min) != Ordering::Less);
+    let below_max = self
+      .max_version
+      .as_deref()
+      .is_none_or(|max| compare_versions(version, max) == Ordering::Less);
+    above_min && below_max
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct DetectedGame {
+  pub rule: DetectionRule,
+  pub executable_path: PathBuf,
+}
+
+impl DetectionRule {
+  pub fn matches_executable(&self, exe_name: &str) -> bool {
+    self.executables.iter().any(|exe| exe.eq_ignore_ascii_case(exe_name))
+  }
+
+  pub fn inject_target_for(&self, exe_name: &str) -> String {
+    self.inject_target.clone().unwrap_or_else(|| {
+      Path::new(exe_name)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+    })
+  }
+
+  /// Overrides for `version`, or `None` when no range matches or the version is unknown.
+  pub fn version_defaults(&self, version: Option<&str>) -> Option<&VersionDefaults> {
+    let version = version?;
+    self.versions.iter().find(|range| range.contains(version))
+  }
+
+  pub fn default_args_for(&self, version: Option<&str>) -> Vec<String> {
+    self
+      .version_defaults(version)
+      .and_then(|range| range.default_args.clone())
+      .unwrap_or_else(|| self.default_args.clone())
+  }
+
+  fn detect(&self, dir: &Path) -> Option<PathBuf> {
+    if !self.data_markers.iter().all(|marker| dir.join(marker).exists()) {
+      return None;
+    }
+    self.executables.iter().map(|exe| dir.join(exe)).find(|path| path.exists())
+  }
+}
+
+/// Canonical key ("chunithm", "sinmai", "ongeki", "cardmaker", "wacca", "diva") for a game
+/// name, game ID or common alias.
+pub fn canonical_game_key(name: &str) -> String {
+  let lower = name.trim().to_lowercase();
+  if lower.starts_with("sdga") || lower.starts_with("sdgb") || lower.starts_with("sdez") {
+    return "sinmai".to_string();
+  }
+  match lower.as_str() {
+    "cardmaker" | "card maker" | "sded" => "cardmaker".to_string(),
+    "wacca" | "mercury" | "sdfe" => "wacca".to_string(),
+    "diva" | "project diva" | "project diva arcade" | "future tone" | "sbzv" => "diva".to_string(),
+    _ => lower,
+  }
+}
+
+pub fn user_rules_path() -> PathBuf {
+  Path::new(".").join("configarc_detection_rules.json")
+}
+
+pub fn bundled_rules() -> Vec<DetectionRule> {
+  serde_json::from_str(BUNDLED_RULES).expect("bundled detection rules are valid JSON")
+}
+
+pub fn read_user_rules(path: &Path) -> Result<Vec<DetectionRule>, GameError> {
+  if !path.exists() {
+    return Ok(vec![]);
+  }
+  let data = fs::read_to_string(path)?;
+  if data.trim().is_empty() {
+    return Ok(vec![]);
+  }
+  let rules: Vec<DetectionRule> = serde_json::from_str(&data)?;
+  Ok(rules)
+}
+
+/// Merges user rules over the bundled ones. A user rule with the same name as a bundled
+/// title replaces it; new titles are checked before the bundled ones.
+pub fn merge_rules(bundled: Vec<DetectionRule>, user: Vec<DetectionRule>) -> Vec<DetectionRule> {
+  let mut merged: Vec<DetectionRule> = Vec::new();
+  for rule in user.into_iter().chain(bundled) {
+    if !merged.iter().any(|r| r.name.eq_ignore_ascii_case(&rule.name)) {
+      merged.push(rule);
+    }
+  }
+  merged
+}
+
+/// Bundled rules extended with `configarc_detection_rules.json`. A user file that cannot be
+/// read is reported so a typo does not silently disable a custom title.
+pub fn load_rules() -> Result<Vec<DetectionRule>, GameError> {
+  let user = read_user_rules(&user_rules_path())?;
+  Ok(merge_rules(bundled_rules(), user))
+}
+
+/// Like [`load_rules`], but falls back to the bundled rules when the user file is broken.
+pub fn load_rules_or_bundled() -> Vec<DetectionRule> {
+  load_rules().unwrap_or_else(|_| bundled_rules())
+}
+
+pub fn detect_in_dir(dir: &Path, rules: &[DetectionRule]) -> Option<DetectedGame> {
+  rules.iter().find_map(|rule| {
+    rule.detect(dir).map(|executable_path| DetectedGame {
+      rule: rule.clone(),
+      executable_path,
+    })
+  })
+}
+
+pub fn rule_for_executable<'a>(rules: &'a [DetectionRule], exe_name: &str) -> Option<&'a DetectionRule> {
+  rules.iter().find(|rule| rule.matches_executable(exe_name))
+}
+
+pub fn supported_executables(rules: &[DetectionRule]) -> Vec<String> {
+  rules.iter().flat_map(|rule| rule.executables.iter().cloned()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn user_rules_override_and_extend_bundled() {
+    let user: Vec<DetectionRule> = serde_json::from_str(
+      r#"[
+        {"name": "Ongeki", "executables": ["mu3.exe"], "defaultArgs": ["-windowed"]},
+        {"name": "Tokyo", "executables": ["Tokyo.exe"], "dataMarkers": ["Tokyo_Data"]}
+      ]"#,
+    )
+    .unwrap();
+    let rules = merge_rules(bundled_rules(), user);
+
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("Tokyo.exe"), "").unwrap();
+    assert!(detect_in_dir(dir.path(), &rules).is_none());
+    fs::create_dir(dir.path().join("Tokyo_Data")).unwrap();
+    assert_eq!(detect_in_dir(dir.path(), &rules).unwrap().rule.name, "Tokyo");
+
+    let ongeki = rule_for_executable(&rules, "MU3.EXE").unwrap();
+    assert_eq!(ongeki.default_args, vec!["-windowed".to_string()]);
+    assert_eq!(rules.iter().filter(|r| r.name == "Ongeki").count(), 1);
+    assert!(rule_for_executable(&rules, "Sinmai.exe").is_some());
+  }
+
+  #[test]
+  fn version_ranges_select_default_args() {
+    let rule: DetectionRule = serde_json::from_str(
+      r#"{"name": "Sinmai", "executables": ["Sinmai.exe"], "defaultArgs": ["-screen-fullscreen", "0"],
+        "versions": [{"maxVersion": "1.40", "defaultArgs": ["-popupwindow"]}, {"minVersion": "1.40"}]}"#,
+    )
+    .unwrap();
+    assert_eq!(rule.default_args_for(Some("1.35.00")), vec!["-popupwindow".to_string()]);
+    assert_eq!(rule.default_args_for(Some("1.40.00")), rule.default_args);
+    assert_eq!(rule.default_args_for(None), rule.default_args);
+    assert_eq!(compare_versions("1.9", "1.10"), Ordering::Less);
+  }
+}
@@ -0,0 +1,180 @@
+use super::journal::{self, JournalEntry};
+use super::model::{Game, HookCommand};
+use super::store::game_root_dir;
+use crate::error::GameError;
+use serde::Serialize;
+use std::io::Read;
+use std::process::{Child, Command, Stdio};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+#[cfg(target_os = "windows")]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+/// Captured output is cut to this many bytes per stream so a chatty tool cannot bloat the journal.
+const MAX_CAPTURED_OUTPUT: usize = 16 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookResult {
+  pub command: String,
+  pub exit_code: Option<i32>,
+  pub timed_out: bool,
+  /// The hook was started in the background and not waited on.
+  pub detached: bool,
+  pub stdout: String,
+  pub stderr: String,
+  pub error: Option<String>,
+}
+
+impl HookResult {
+  pub fn succeeded(&self) -> bool {
+    self.error.is_none() && !self.timed_out && (self.detached || self.exit_code == Some(0))
+  }
+}
+
+fn display_command(hook: &HookCommand) -> String {
+  std::iter::once(hook.command.as_str())
+    .chain(hook.args.iter().map(String::as_str))
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+fn build_command(hook: &HookCommand, game: &Game) -> Command {
+  let mut cmd = Command::new(&hook.command);
+  cmd.args(&hook.args);
+  match hook.working_dir.as_deref().filter(|d| !d.is_empty()) {
+    Some(dir) => {
+      cmd.current_dir(dir);
+    }
+    None => {
+      if let Some(dir) = game_root_dir(game) {
+        cmd.current_dir(dir);
+      }
+    }
+  }
+  cmd.env("CONFIGARC_GAME_ID", &game.id);
+  cmd.env("CONFIGARC_GAME_NAME", &game.name);
+  cmd.env("CONFIGARC_GAME_EXE", &game.executable_path);
+  #[cfg(target_os = "windows")]
+  cmd.creation_flags(CREATE_NO_WINDOW);
+  cmd
+}
+
+fn capture<R: Read + Send + 'static>(stream: Option<R>) -> Option<JoinHandle<String>> {
+  let mut stream = stream?;
+  Some(std::thread::spawn(move || {
+    let mut buf = Vec::new();
+    let _ = stream.read_to_end(&mut buf);
+    buf.truncate(MAX_CAPTURED_OUTPUT);
+    String::from_utf8_lossy(&buf).trim_end().to_string()
+  }))
+}
+
+fn wait_with_timeout(child: &mut Child, timeout: Duration) -> (Option<i32>, bool) {
+  let deadline = Instant::now() + timeout;
+  loop {
+    match child.try_wait() {
+      Ok(Some(status)) => return (status.code(), false),
+      Ok(None) if Instant::now() >= deadline => {
+        let _ = child.kill();
+        let _ = child.wait();
+        return (None, true);
+      }
+      Ok(None) => std::thread::sleep(Duration::from_millis(100)),
+      Err(_) => return (None, false),
+    }
+  }
+}
+
+/// Runs one hook, killing it once its timeout expires. Background hooks are only started.
+pub fn run_hook(hook: &HookCommand, game: &Game) -> HookResult {
+  let mut result = HookResult {
+    command: display_command(hook),
+    exit_code: None,
+    timed_out: false,
+    detached: hook.background,
+    stdout: String::new(),
+    stderr: String::new(),
+    error: None,
+  };
+  let mut cmd = build_command(hook, game);
+  if hook.background {
+    cmd.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null());
+    if let Err(err) = cmd.spawn() {
+      result.error = Some(err.to_string());
+    }
+    return result;
+  }
+  cmd.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+  let mut child = match cmd.spawn() {
+    Ok(child) => child,
+    Err(err) => {
+      result.error = Some(err.to_string());
+      return result;
+    }
+  };
+  let stdout = capture(child.stdout.take());
+  let stderr = capture(child.stderr.take());
+  let (exit_code, timed_out) = wait_with_timeout(&mut child, Duration::from_secs(hook.timeout_secs));
+  result.exit_code = exit_code;
+  result.timed_out = timed_out;
+  result.stdout = stdout.and_then(|h| h.join().ok()).unwrap_or_default();
+  result.stderr = stderr.and_then(|h| h.join().ok()).unwrap_or_default();
+  result
+}
+
+fn record_hooks(game: &Game, stage: &str, results: &[HookResult]) {
+  let failed = results.iter().find(|r| !r.succeeded());
+  let mut entry = JournalEntry::new("hook", format!("Ran {} {} hook(s)", results.len(), stage))
+    .with_details(serde_json::json!({ "stage": stage, "results": results }));
+  if let Some(failed) = failed {
+    entry.success = false;
+    entry.error = Some(hook_failure(failed));
+  }
+  journal::record(&game.id, entry);
+}
+
+fn hook_failure(result: &HookResult) -> String {
+  if let Some(err) = &result.error {
+    format!("{}: {}", result.command, err)
+  } else if result.timed_out {
+    format!("{} timed out", result.command)
+  } else {
+    match result.exit_code {
+      Some(code) => format!("{} exited with code {}", result.command, code),
+      None => format!("{} was terminated", result.command),
+    }
+  }
+}
+
+/// Runs the game's `pre_launch` hooks in order. The first failing hook stops the launch.
+pub fn run_pre_launch_hooks(game: &Game) -> Result<Vec<HookResult>, GameError> {
+  let mut results = Vec::new();
+  for hook in &game.pre_launch {
+    let result = run_hook(hook, game);
+    let failed = !result.succeeded();
+    results.push(result);
+    if failed {
+      break;
+    }
+  }
+  if results.is_empty() {
+    return Ok(results);
+  }
+  record_hooks(game, "pre_launch", &results);
+  match results.iter().find(|r| !r.succeeded()) {
+    Some(failed) => Err(GameError::Launch(format!("Pre-launch hook failed: {}", hook_failure(failed)))),
+    None => Ok(results),
+  }
+}
+
+/// Runs every `post_exit` hook, even after one of them failed, so cleanup steps still happen.
+pub fn run_post_exit_hooks(game: &Game) -> Vec<HookResult> {
+  let results: Vec<HookResult> = game.post_exit.iter().map(|hook| run_hook(hook, game)).collect();
+  if !results.is_empty() {
+    record_hooks(game, "post_exit", &results);
+  }
+  results
+}
@@ -0,0 +1,91 @@
+use crate::config::paths::segatools_root_for_game_id;
+use crate::error::GameError;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+const JOURNAL_FILE_NAME: &str = "journal.jsonl";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+  pub timestamp: String,
+  /// Operation kind, e.g. "deploy", "rollback", "profile_apply", "icf_edit", "mod_install", "launch".
+  pub kind: String,
+  pub summary: String,
+  pub success: bool,
+  #[serde(default)]
+  pub error: Option<String>,
+  #[serde(default)]
+  pub details: Option<Value>,
+}
+
+impl JournalEntry {
+  pub fn new(kind: &str, summary: impl Into<String>) -> Self {
+    Self {
+      timestamp: Utc::now().to_rfc3339(),
+      kind: kind.to_string(),
+      summary: summary.into(),
+      success: true,
+      error: None,
+      details: None,
+    }
+  }
+
+  pub fn with_outcome<T, E: ToString>(mut self, outcome: &Result<T, E>) -> Self {
+    if let Err(err) = outcome {
+      self.success = false;
+      self.error = Some(err.to_string());
+    }
+    self
+  }
+
+  pub fn with_details(mut self, details: Value) -> Self {
+    self.details = Some(details);
+    self
+  }
+}
+
+pub fn journal_path_for_game(game_id: &str) -> PathBuf {
+  segatools_root_for_game_id(game_id).join(JOURNAL_FILE_NAME)
+}
+
+pub fn append_journal_entry(game_id: &str, entry: &JournalEntry) -> Result<(), GameError> {
+  let path = journal_path_for_game(game_id);
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent)?;
+  }
+  let mut line = serde_json::to_string(entry)?;
+  line.push('\n');
+  let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+  file.write_all(line.as_bytes())?;
+  Ok(())
+}
+
+/// Journaling must never fail the operation it describes, so errors are dropped here.
+pub fn record(game_id: &str, entry: JournalEntry) {
+  let _ = append_journal_entry(game_id, &entry);
+}
+
+/// Returns journal entries oldest first. With `limit`, only the most recent entries are kept.
+/// Lines that fail to parse (e.g. a torn write) are skipped.
+pub fn read_journal(game_id: &str, limit: Option<usize>) -> Result<Vec<JournalEntry>, GameError> {
+  let path = journal_path_for_game(game_id);
+  if !path.exists() {
+    return Ok(vec![]);
+  }
+  let data = fs::read_to_string(&path)?;
+  let mut entries: Vec<JournalEntry> = data
+    .lines()
+    .filter(|line| !line.trim().is_empty())
+    .filter_map(|line| serde_json::from_str(line).ok())
+    .collect();
+  if let Some(limit) = limit {
+    if entries.len() > limit {
+      entries.drain(..entries.len() - limit);
+    }
+  }
+  Ok(entries)
+}
@@ -1,14 +1,36 @@
+use super::detection::{load_rules_or_bundled, rule_for_executable};
+use super::hooks::{run_post_exit_hooks, run_pre_launch_hooks};
 use super::model::Game;
+use super::sandbox::{spawn_sandboxed, SandboxedChild};
 use crate::config::paths::segatools_root_for_game_id;
+use crate::config::slots::amdaemon_slot_path;
 use crate::error::GameError;
-use std::path::Path;
-use std::process::{Child, Command};
+use std::path::{Path, PathBuf};
+use std::process::Command;
 use std::fs;
+#[cfg(windows)]
 use std::os::windows::process::CommandExt;
 
 const CREATE_NEW_CONSOLE: u32 = 0x00000010;
+// The launch batch exits with the game's exit code rather than taskkill's.
+const GAME_EXIT_SAVE: &str = "set CONFIGARC_GAME_EXIT=%ERRORLEVEL%\r\n";
+const GAME_EXIT_RESTORE: &str = "exit /b %CONFIGARC_GAME_EXIT%\r\n";
+
+/// Batch lines starting amdaemon with the game's amdaemon config slot, when it has one, and
+/// pointing the game back at `game_ini` afterwards.
+fn amdaemon_start_lines(segatools_root: &Path, game_ini: &Path, start: String) -> String {
+  match amdaemon_slot_path(segatools_root) {
+    Some(slot) => format!(
+      "set \"SEGATOOLS_CONFIG_PATH={}\"\r\n{}set \"SEGATOOLS_CONFIG_PATH={}\"\r\n",
+      slot.to_string_lossy(),
+      start,
+      game_ini.to_string_lossy()
+    ),
+    None => start,
+  }
+}
 
-fn build_launch_command(game: &Game) -> Result<Command, GameError> {
+fn build_launch_command(game: &Game, ini_override: Option<&Path>) -> Result<Command, GameError> {
   if !game.enabled {
     return Err(GameError::Launch("Game is disabled".to_string()));
   }
@@ -21,14 +43,15 @@ fn build_launch_command(game: &Game) -> Result<Command, GameError> {
   };
 
   let segatools_root = segatools_root_for_game_id(&game.id);
-  let segatools_ini = segatools_root.join("segatools.ini");
+  let segatools_ini: PathBuf = match ini_override {
+    Some(path) => path.to_path_buf(),
+    None => segatools_root.join("segatools.ini"),
+  };
   let inject_path = segatools_root.join("inject.exe");
   let inject_x64_path = segatools_root.join("inject_x64.exe");
   let inject_x86_path = segatools_root.join("inject_x86.exe");
   let hook_chusan_x64 = segatools_root.join("chusanhook_x64.dll");
   let hook_chusan_x86 = segatools_root.join("chusanhook_x86.dll");
-  let hook_mai2 = segatools_root.join("mai2hook.dll");
-  let hook_mu3 = segatools_root.join("mu3hook.dll");
   let has_inject = inject_path.exists() || inject_x86_path.exists() || inject_x64_path.exists();
 
   // Check if we should use inject (Segatools style)
@@ -51,10 +74,14 @@ fn build_launch_command(game: &Game) -> Result<Command, GameError> {
       if let (Some(inject_x64), Some(inject_x86)) = (inject_x64, inject_x86) {
         batch_content.push_str("@echo off\r\n");
         batch_content.push_str(&format!("cd /d \"{}\"\r\n", working_dir.to_string_lossy()));
-        batch_content.push_str(&format!(
-          "start \"\" /min \"{}\" -d -k \"{}\" amdaemon.exe -c config_common.json config_server.json config_client.json config_cvt.json config_sp.json config_hook.json\r\n",
-          inject_x64.to_string_lossy(),
-          hook_chusan_x64.to_string_lossy()
+        batch_content.push_str(&amdaemon_start_lines(
+          &segatools_root,
+          &segatools_ini,
+          format!(
+            "start \"\" /min \"{}\" -d -k \"{}\" amdaemon.exe -c config_common.json config_server.json config_client.json config_cvt.json config_sp.json config_hook.json\r\n",
+            inject_x64.to_string_lossy(),
+            hook_chusan_x64.to_string_lossy()
+          ),
         ));
 
         let args_str = game.launch_args.join(" ");
@@ -64,14 +91,19 @@ fn build_launch_command(game: &Game) -> Result<Command, GameError> {
           hook_chusan_x86.to_string_lossy(),
           args_str
         ));
+        batch_content.push_str(GAME_EXIT_SAVE);
         batch_content.push_str("taskkill /f /im amdaemon.exe > nul 2>&1\r\n");
+        batch_content.push_str(GAME_EXIT_RESTORE);
         handled = true;
       }
     } else {
-      let (hook_dll, target_name) = match exe_name.as_str() {
-        "Sinmai.exe" => (Some(&hook_mai2), "sinmai"),
-        "mu3.exe" => (Some(&hook_mu3), "mu3"),
-        _ => (None, "")
+      let rules = load_rules_or_bundled();
+      let (hook_dll, target_name) = match rule_for_executable(&rules, &exe_name) {
+        Some(rule) => (
+          rule.hook_dll.as_ref().map(|dll| segatools_root.join(dll)),
+          rule.inject_target_for(&exe_name),
+        ),
+        None => (None, String::new()),
       };
 
       let inject = if inject_path.exists() {
@@ -82,20 +114,22 @@ fn build_launch_command(game: &Game) -> Result<Command, GameError> {
         None
       };
 
-      if hook_dll.is_some() && inject.is_some() {
+      if let (Some(hook_dll), Some(inject)) = (hook_dll, inject) {
         let amdaemon_path = working_dir.join("amdaemon.exe");
         let has_amdaemon = amdaemon_path.exists();
-        let inject = inject.unwrap();
-        let hook_dll = hook_dll.unwrap();
 
         batch_content.push_str("@echo off\r\n");
         batch_content.push_str(&format!("cd /d \"{}\"\r\n", working_dir.to_string_lossy()));
 
         if has_amdaemon {
-          batch_content.push_str(&format!(
-            "start \"\" /min \"{}\" -d -k \"{}\" amdaemon.exe -f -c config_common.json config_server.json config_client.json\r\n",
-            inject.to_string_lossy(),
-            hook_dll.to_string_lossy()
+          batch_content.push_str(&amdaemon_start_lines(
+            &segatools_root,
+            &segatools_ini,
+            format!(
+              "start \"\" /min \"{}\" -d -k \"{}\" amdaemon.exe -f -c config_common.json config_server.json config_client.json\r\n",
+              inject.to_string_lossy(),
+              hook_dll.to_string_lossy()
+            ),
           ));
         }
 
@@ -107,10 +141,12 @@ fn build_launch_command(game: &Game) -> Result<Command, GameError> {
           target_name,
           args_str
         ));
+        batch_content.push_str(GAME_EXIT_SAVE);
 
         if has_amdaemon {
           batch_content.push_str("taskkill /f /im amdaemon.exe > nul 2>&1\r\n");
         }
+        batch_content.push_str(GAME_EXIT_RESTORE);
         handled = true;
       }
     }
@@ -125,9 +161,10 @@ fn build_launch_command(game: &Game) -> Result<Command, GameError> {
         .map_err(|e| GameError::Launch(format!("Failed to write batch file: {}", e)))?;
 
       let mut cmd = Command::new("cmd");
-      cmd.args(&["/c", batch_path.to_str().unwrap()]);
+      cmd.args(["/c", batch_path.to_str().unwrap()]);
       cmd.current_dir(working_dir);
       cmd.env("SEGATOOLS_CONFIG_PATH", &segatools_ini);
+      #[cfg(windows)]
       cmd.creation_flags(CREATE_NEW_CONSOLE);
       return Ok(cmd);
     }
@@ -142,17 +179,51 @@ fn build_launch_command(game: &Game) -> Result<Command, GameError> {
   }
   cmd.args(&game.launch_args);
   cmd.env("SEGATOOLS_CONFIG_PATH", &segatools_ini);
+  #[cfg(windows)]
   cmd.creation_flags(CREATE_NEW_CONSOLE);
   Ok(cmd)
 }
 
 pub fn launch_game(game: &Game) -> Result<(), GameError> {
-  let mut cmd = build_launch_command(game)?;
-  cmd.spawn().map_err(|e| GameError::Launch(e.to_string()))?;
+  launch_game_with_ini(game, None)
+}
+
+pub fn launch_game_child(game: &Game) -> Result<SandboxedChild, GameError> {
+  launch_game_child_with_ini(game, None)
+}
+
+/// Launches with `SEGATOOLS_CONFIG_PATH` pointed at `ini` instead of the game's segatools.ini.
+pub fn launch_game_with_ini(game: &Game, ini: Option<&Path>) -> Result<(), GameError> {
+  let SandboxedChild { mut child, sandbox } = launch_game_child_with_ini(game, ini)?;
+  if sandbox.is_some() || !game.post_exit.is_empty() {
+    let game = game.clone();
+    // The job has to stay open for as long as the launch process runs.
+    std::thread::spawn(move || {
+      {
+        let _sandbox = sandbox;
+        let _ = child.wait();
+      }
+      run_post_exit_hooks(&game);
+    });
+  }
   Ok(())
 }
 
-pub fn launch_game_child(game: &Game) -> Result<Child, GameError> {
-  let mut cmd = build_launch_command(game)?;
-  cmd.spawn().map_err(|e| GameError::Launch(e.to_string()))
+/// Whether the launch batch for `game` starts amdaemon before the game itself.
+pub fn launches_amdaemon(game: &Game) -> bool {
+  let exe_path = Path::new(&game.executable_path);
+  let working_dir = match game.working_dir.as_deref().filter(|d| !d.is_empty()) {
+    Some(dir) => Path::new(dir),
+    None => exe_path.parent().unwrap_or(Path::new(".")),
+  };
+  working_dir.join("amdaemon.exe").exists()
+}
+
+/// Runs the game's pre-launch hooks, then spawns the launch process inside the game's
+/// sandbox. Keep the returned sandbox alive until the game has exited; dropping it ends the
+/// job. Post-exit hooks are left to the caller, which knows when the game is gone.
+pub fn launch_game_child_with_ini(game: &Game, ini: Option<&Path>) -> Result<SandboxedChild, GameError> {
+  let mut cmd = build_launch_command(game, ini)?;
+  run_pre_launch_hooks(game)?;
+  spawn_sandboxed(&mut cmd, &game.sandbox, CREATE_NEW_CONSOLE)
 }
@@ -1,3 +1,12 @@
+pub mod caches;
+pub mod cleanup;
+pub mod detection;
+pub mod hooks;
+pub mod journal;
 pub mod launcher;
+pub mod mod_conflicts;
 pub mod model;
+pub mod sandbox;
 pub mod store;
+pub mod trash;
+pub mod watchdog;
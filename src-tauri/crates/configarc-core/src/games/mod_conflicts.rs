@@ -0,0 +1,223 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+use zip::read::ZipArchive;
+
+/// Assembly that every game-side Harmony patch targets on Sinmai.
+const GAME_ASSEMBLY_MARKER: &str = ", Assembly-CSharp";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModConflict {
+  /// "assembly_patch" for overlapping Harmony targets, "asset_path" for files shipped by several archives.
+  pub kind: String,
+  pub target: String,
+  pub mods: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModConflictReport {
+  pub scanned: Vec<String>,
+  pub conflicts: Vec<ModConflict>,
+  pub warnings: Vec<String>,
+}
+
+fn read_compressed_len(data: &[u8], pos: usize) -> Option<(usize, usize)> {
+  let b0 = *data.get(pos)? as usize;
+  if b0 & 0x80 == 0 {
+    Some((b0, 1))
+  } else if b0 & 0xC0 == 0x80 {
+    let b1 = *data.get(pos + 1)? as usize;
+    Some((((b0 & 0x3F) << 8) | b1, 2))
+  } else {
+    None
+  }
+}
+
+fn read_ser_string(data: &[u8], pos: usize) -> Option<(String, usize)> {
+  let (len, header) = read_compressed_len(data, pos)?;
+  let start = pos + header;
+  let bytes = data.get(start..start + len)?;
+  let text = std::str::from_utf8(bytes).ok()?;
+  Some((text.to_string(), header + len))
+}
+
+fn is_identifier(value: &str) -> bool {
+  !value.is_empty()
+    && value.len() <= 256
+    && value
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '`' | '<' | '>' | '+'))
+}
+
+/// Extracts `Type::Method` targets from `[HarmonyPatch(typeof(T), "Method")]` attribute blobs.
+///
+/// This is a byte-level scan rather than a metadata parser: a custom attribute blob starts with
+/// the 0x0001 prolog and stores `typeof` arguments as assembly-qualified strings, so any prolog
+/// followed by an Assembly-CSharp type name is treated as a patch. A missing method string means
+/// the attribute only names the type, which is reported as a type-wide target.
+pub fn harmony_targets(data: &[u8]) -> BTreeSet<String> {
+  let mut targets = BTreeSet::new();
+  let mut pos = 0;
+  while pos + 2 < data.len() {
+    if data[pos] != 0x01 || data[pos + 1] != 0x00 {
+      pos += 1;
+      continue;
+    }
+    let Some((type_name, consumed)) = read_ser_string(data, pos + 2) else {
+      pos += 1;
+      continue;
+    };
+    let Some(marker) = type_name.find(GAME_ASSEMBLY_MARKER) else {
+      pos += 1;
+      continue;
+    };
+    let short_type = type_name[..marker].trim().to_string();
+    if !is_identifier(&short_type) {
+      pos += 1;
+      continue;
+    }
+    let method = read_ser_string(data, pos + 2 + consumed)
+      .map(|(value, _)| value)
+      .filter(|value| is_identifier(value) && !value.contains(GAME_ASSEMBLY_MARKER));
+    let target = match method {
+      Some(method) => format!("{}::{}", short_type, method),
+      None => short_type,
+    };
+    targets.insert(target);
+    pos += 2 + consumed;
+  }
+  targets
+}
+
+fn archive_entries(path: &Path) -> Result<BTreeSet<String>, String> {
+  let file = fs::File::open(path).map_err(|e| e.to_string())?;
+  let mut archive = ZipArchive::new(file).map_err(|e| e.to_string())?;
+  let mut entries = BTreeSet::new();
+  for index in 0..archive.len() {
+    let entry = archive.by_index(index).map_err(|e| e.to_string())?;
+    if entry.is_dir() {
+      continue;
+    }
+    entries.insert(entry.name().replace('\\', "/").to_lowercase());
+  }
+  Ok(entries)
+}
+
+fn is_extension(path: &Path, ext: &str) -> bool {
+  path
+    .extension()
+    .and_then(|e| e.to_str())
+    .map(|e| e.eq_ignore_ascii_case(ext))
+    .unwrap_or(false)
+}
+
+fn push_conflicts(
+  kind: &str,
+  owners: BTreeMap<String, BTreeSet<String>>,
+  conflicts: &mut Vec<ModConflict>,
+) {
+  for (target, mods) in owners {
+    if mods.len() > 1 {
+      conflicts.push(ModConflict {
+        kind: kind.to_string(),
+        target,
+        mods: mods.into_iter().collect(),
+      });
+    }
+  }
+}
+
+/// Inspects every DLL and archive in a MelonLoader `Mods` directory and reports targets claimed
+/// by more than one mod. A type-wide patch also conflicts with any method patch on that type.
+pub fn detect_mod_conflicts(mods_dir: &Path) -> ModConflictReport {
+  let mut report = ModConflictReport {
+    scanned: Vec::new(),
+    conflicts: Vec::new(),
+    warnings: Vec::new(),
+  };
+  let Ok(entries) = fs::read_dir(mods_dir) else {
+    return report;
+  };
+
+  let mut patch_owners: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+  let mut type_wide: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+  let mut asset_owners: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+
+  let mut paths: Vec<_> = entries.flatten().map(|e| e.path()).filter(|p| p.is_file()).collect();
+  paths.sort();
+  for path in paths {
+    let name = path
+      .file_name()
+      .map(|n| n.to_string_lossy().to_string())
+      .unwrap_or_default();
+    if is_extension(&path, "dll") {
+      match fs::read(&path) {
+        Ok(data) => {
+          for target in harmony_targets(&data) {
+            if target.contains("::") {
+              patch_owners.entry(target).or_default().insert(name.clone());
+            } else {
+              type_wide.entry(target).or_default().insert(name.clone());
+            }
+          }
+          report.scanned.push(name);
+        }
+        Err(e) => report.warnings.push(format!("{}: {}", name, e)),
+      }
+    } else if is_extension(&path, "zip") {
+      match archive_entries(&path) {
+        Ok(files) => {
+          for file in files {
+            asset_owners.entry(file).or_default().insert(name.clone());
+          }
+          report.scanned.push(name);
+        }
+        Err(e) => report.warnings.push(format!("{}: {}", name, e)),
+      }
+    }
+  }
+
+  // Fold type-wide patches into every method target on the same type.
+  for (type_name, owners) in &type_wide {
+    let prefix = format!("{}::", type_name);
+    let mut matched = false;
+    for (target, mods) in patch_owners.iter_mut() {
+      if target.starts_with(&prefix) {
+        mods.extend(owners.iter().cloned());
+        matched = true;
+      }
+    }
+    if !matched {
+      patch_owners.insert(type_name.clone(), owners.clone());
+    }
+  }
+
+  push_conflicts("assembly_patch", patch_owners, &mut report.conflicts);
+  push_conflicts("asset_path", asset_owners, &mut report.conflicts);
+  report
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn ser_string(value: &str) -> Vec<u8> {
+    let mut out = vec![value.len() as u8];
+    out.extend_from_slice(value.as_bytes());
+    out
+  }
+
+  #[test]
+  fn extracts_type_and_method_from_attribute_blob() {
+    let mut blob = vec![0xAA, 0x01, 0x00];
+    blob.extend(ser_string(
+      "Manager.GameManager, Assembly-CSharp, Version=0.0.0.0, Culture=neutral, PublicKeyToken=null",
+    ));
+    blob.extend(ser_string("Initialize"));
+    blob.extend([0x00, 0x00]);
+    let targets = harmony_targets(&blob);
+    assert!(targets.contains("Manager.GameManager::Initialize"));
+    assert_eq!(targets.len(), 1);
+  }
+}
@@ -1,18 +1,59 @@
+use super::watchdog::WatchdogOptions;
+use crate::display::DisplayPreset;
+use crate::gpu::GpuPreference;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum LaunchMode {
+  #[default]
   Folder,
   Vhd,
 }
 
-impl Default for LaunchMode {
-  fn default() -> Self {
-    LaunchMode::Folder
+/// Opt-in process hardening applied through a Windows job object when the game is launched.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SandboxOptions {
+  /// Kill every process of the launch (game, amdaemon, anything a mod spawned) once the
+  /// game exits or the launcher goes away.
+  #[serde(default)]
+  pub kill_on_close: bool,
+  /// Refuse any child process amdaemon tries to create.
+  #[serde(default)]
+  pub deny_amdaemon_children: bool,
+  /// Per-process working set cap in MiB.
+  #[serde(default)]
+  pub max_working_set_mb: Option<u64>,
+}
+
+impl SandboxOptions {
+  pub fn is_enabled(&self) -> bool {
+    self.kill_on_close || self.deny_amdaemon_children || self.max_working_set_mb.is_some()
   }
 }
 
+fn default_hook_timeout_secs() -> u64 {
+  30
+}
+
+/// An external program run around a launch, e.g. an LED bridge or touch driver.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookCommand {
+  pub command: String,
+  #[serde(default)]
+  pub args: Vec<String>,
+  /// Defaults to the game's folder.
+  #[serde(default)]
+  pub working_dir: Option<String>,
+  /// The hook is killed once it runs longer than this.
+  #[serde(default = "default_hook_timeout_secs")]
+  pub timeout_secs: u64,
+  /// Start the program and carry on without waiting for it, for helpers that must keep
+  /// running alongside the game.
+  #[serde(default)]
+  pub background: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Game {
   pub id: String,
@@ -24,4 +65,26 @@ pub struct Game {
   pub tags: Vec<String>,
   #[serde(default)]
   pub launch_mode: LaunchMode,
+  #[serde(default)]
+  pub sandbox: SandboxOptions,
+  /// Adapter Windows should run the executable on, for hybrid-graphics laptops.
+  #[serde(default)]
+  pub gpu_preference: GpuPreference,
+  /// Saved monitor and resolution setups, applied with `apply_display_preset`.
+  #[serde(default)]
+  pub display_presets: Vec<DisplayPreset>,
+  /// Name of the preset last applied; its resolution is kept when launch args are rebuilt.
+  #[serde(default)]
+  pub display_preset: Option<String>,
+  /// Run in order before the game starts; a failing hook cancels the launch.
+  #[serde(default)]
+  pub pre_launch: Vec<HookCommand>,
+  /// Run after the game has exited.
+  #[serde(default)]
+  pub post_exit: Vec<HookCommand>,
+  #[serde(default)]
+  pub watchdog: WatchdogOptions,
+  /// App version last read from the game's ICF; a change re-applies the version defaults.
+  #[serde(default)]
+  pub detected_version: Option<String>,
 }
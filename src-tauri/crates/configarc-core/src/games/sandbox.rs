@@ -0,0 +1,381 @@
+use super::model::SandboxOptions;
+use crate::error::GameError;
+#[cfg(target_os = "windows")]
+use crate::platform::{Process32FirstW, Process32NextW, ProcessEntry32W};
+use std::process::{Child, Command};
+#[cfg(target_os = "windows")]
+use std::ffi::c_void;
+#[cfg(target_os = "windows")]
+use std::os::windows::io::AsRawHandle;
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+#[cfg(target_os = "windows")]
+const JOB_OBJECT_LIMIT_WORKINGSET: u32 = 0x0000_0001;
+#[cfg(target_os = "windows")]
+const JOB_OBJECT_LIMIT_ACTIVE_PROCESS: u32 = 0x0000_0008;
+#[cfg(target_os = "windows")]
+const JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE: u32 = 0x0000_2000;
+#[cfg(target_os = "windows")]
+const JOB_OBJECT_ASSOCIATE_COMPLETION_PORT_INFORMATION: i32 = 7;
+#[cfg(target_os = "windows")]
+const JOB_OBJECT_EXTENDED_LIMIT_INFORMATION: i32 = 9;
+#[cfg(target_os = "windows")]
+const JOB_OBJECT_MSG_ACTIVE_PROCESS_ZERO: u32 = 4;
+#[cfg(target_os = "windows")]
+const JOB_OBJECT_MSG_NEW_PROCESS: u32 = 6;
+#[cfg(target_os = "windows")]
+const CREATE_SUSPENDED: u32 = 0x0000_0004;
+#[cfg(target_os = "windows")]
+const TH32CS_SNAPPROCESS: u32 = 0x0000_0002;
+#[cfg(target_os = "windows")]
+const TH32CS_SNAPTHREAD: u32 = 0x0000_0004;
+#[cfg(target_os = "windows")]
+const THREAD_SUSPEND_RESUME: u32 = 0x0002;
+#[cfg(target_os = "windows")]
+const PROCESS_TERMINATE: u32 = 0x0001;
+#[cfg(target_os = "windows")]
+const PROCESS_SET_QUOTA: u32 = 0x0100;
+#[cfg(target_os = "windows")]
+const MIN_WORKING_SET: usize = 16 * 1024 * 1024;
+#[cfg(target_os = "windows")]
+const INFINITE: u32 = u32::MAX;
+
+#[cfg(target_os = "windows")]
+#[repr(C)]
+#[derive(Default)]
+struct JobObjectBasicLimitInformation {
+  per_process_user_time_limit: i64,
+  per_job_user_time_limit: i64,
+  limit_flags: u32,
+  minimum_working_set_size: usize,
+  maximum_working_set_size: usize,
+  active_process_limit: u32,
+  affinity: usize,
+  priority_class: u32,
+  scheduling_class: u32,
+}
+
+#[cfg(target_os = "windows")]
+#[repr(C)]
+#[derive(Default)]
+struct IoCounters {
+  read_operation_count: u64,
+  write_operation_count: u64,
+  other_operation_count: u64,
+  read_transfer_count: u64,
+  write_transfer_count: u64,
+  other_transfer_count: u64,
+}
+
+#[cfg(target_os = "windows")]
+#[repr(C)]
+#[derive(Default)]
+struct JobObjectExtendedLimitInformation {
+  basic_limit_information: JobObjectBasicLimitInformation,
+  io_info: IoCounters,
+  process_memory_limit: usize,
+  job_memory_limit: usize,
+  peak_process_memory_used: usize,
+  peak_job_memory_used: usize,
+}
+
+#[cfg(target_os = "windows")]
+#[repr(C)]
+struct JobObjectAssociateCompletionPort {
+  completion_key: *mut c_void,
+  completion_port: *mut c_void,
+}
+
+#[cfg(target_os = "windows")]
+#[repr(C)]
+struct ThreadEntry32 {
+  size: u32,
+  usage: u32,
+  thread_id: u32,
+  owner_process_id: u32,
+  base_priority: i32,
+  delta_priority: i32,
+  flags: u32,
+}
+
+#[cfg(target_os = "windows")]
+#[link(name = "kernel32")]
+extern "system" {
+  fn CreateJobObjectW(attributes: *const c_void, name: *const u16) -> *mut c_void;
+  fn SetInformationJobObject(job: *mut c_void, class: i32, info: *const c_void, length: u32) -> i32;
+  fn AssignProcessToJobObject(job: *mut c_void, process: *mut c_void) -> i32;
+  fn CreateIoCompletionPort(file: *mut c_void, existing: *mut c_void, key: usize, threads: u32) -> *mut c_void;
+  fn GetQueuedCompletionStatus(
+    port: *mut c_void,
+    bytes: *mut u32,
+    key: *mut usize,
+    overlapped: *mut *mut c_void,
+    timeout: u32,
+  ) -> i32;
+  fn CreateToolhelp32Snapshot(flags: u32, pid: u32) -> *mut c_void;
+  fn Thread32First(snapshot: *mut c_void, entry: *mut ThreadEntry32) -> i32;
+  fn Thread32Next(snapshot: *mut c_void, entry: *mut ThreadEntry32) -> i32;
+  fn OpenThread(access: u32, inherit: i32, tid: u32) -> *mut c_void;
+  fn ResumeThread(thread: *mut c_void) -> u32;
+  fn OpenProcess(access: u32, inherit: i32, pid: u32) -> *mut c_void;
+  fn TerminateProcess(process: *mut c_void, exit_code: u32) -> i32;
+  fn CloseHandle(handle: *mut c_void) -> i32;
+}
+
+/// A kernel handle closed on drop: a job, a completion port or a snapshot.
+#[cfg(target_os = "windows")]
+struct OwnedHandle(*mut c_void);
+
+#[cfg(target_os = "windows")]
+unsafe impl Send for OwnedHandle {}
+#[cfg(target_os = "windows")]
+unsafe impl Sync for OwnedHandle {}
+
+#[cfg(target_os = "windows")]
+impl OwnedHandle {
+  /// `None` for the null and `INVALID_HANDLE_VALUE` results of failed calls.
+  fn new(raw: *mut c_void) -> Option<Self> {
+    (!raw.is_null() && raw as isize != -1).then_some(Self(raw))
+  }
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for OwnedHandle {
+  fn drop(&mut self) {
+    unsafe {
+      CloseHandle(self.0);
+    }
+  }
+}
+
+/// Job object holding a launched game and everything it spawns. Dropping it closes the job,
+/// which terminates the remaining processes when `kill_on_close` is set.
+pub struct GameSandbox {
+  #[cfg(target_os = "windows")]
+  _job: OwnedHandle,
+}
+
+/// A launched game together with the sandbox that must outlive it.
+pub struct SandboxedChild {
+  pub child: Child,
+  pub sandbox: Option<GameSandbox>,
+}
+
+#[cfg(target_os = "windows")]
+fn last_error(context: &str) -> GameError {
+  GameError::Launch(format!("{}: {}", context, std::io::Error::last_os_error()))
+}
+
+#[cfg(target_os = "windows")]
+fn create_job(info: &JobObjectExtendedLimitInformation) -> Result<OwnedHandle, GameError> {
+  let job = OwnedHandle::new(unsafe { CreateJobObjectW(std::ptr::null(), std::ptr::null()) })
+    .ok_or_else(|| last_error("Failed to create job object"))?;
+  let ok = unsafe {
+    SetInformationJobObject(
+      job.0,
+      JOB_OBJECT_EXTENDED_LIMIT_INFORMATION,
+      info as *const _ as *const c_void,
+      std::mem::size_of::<JobObjectExtendedLimitInformation>() as u32,
+    )
+  };
+  if ok == 0 {
+    return Err(last_error("Failed to configure job object"));
+  }
+  Ok(job)
+}
+
+/// A completion port the job reports new and exited processes to. Messages queue up from
+/// the moment it is attached, so nothing started before the watcher runs is missed.
+#[cfg(target_os = "windows")]
+fn watch_job(job: &OwnedHandle) -> Result<OwnedHandle, GameError> {
+  let port = OwnedHandle::new(unsafe { CreateIoCompletionPort(-1isize as *mut c_void, std::ptr::null_mut(), 0, 1) })
+    .ok_or_else(|| last_error("Failed to create job completion port"))?;
+  let info = JobObjectAssociateCompletionPort {
+    completion_key: job.0,
+    completion_port: port.0,
+  };
+  let ok = unsafe {
+    SetInformationJobObject(
+      job.0,
+      JOB_OBJECT_ASSOCIATE_COMPLETION_PORT_INFORMATION,
+      &info as *const _ as *const c_void,
+      std::mem::size_of::<JobObjectAssociateCompletionPort>() as u32,
+    )
+  };
+  if ok == 0 {
+    return Err(last_error("Failed to watch job object"));
+  }
+  Ok(port)
+}
+
+/// Parent id and executable name of a running process.
+#[cfg(target_os = "windows")]
+fn process_entry(pid: u32) -> Option<(u32, String)> {
+  let snapshot = OwnedHandle::new(unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) })?;
+  let mut entry = ProcessEntry32W {
+    size: std::mem::size_of::<ProcessEntry32W>() as u32,
+    usage: 0,
+    process_id: 0,
+    default_heap_id: 0,
+    module_id: 0,
+    threads: 0,
+    parent_process_id: 0,
+    priority_class_base: 0,
+    flags: 0,
+    exe_file: [0; 260],
+  };
+  let mut ok = unsafe { Process32FirstW(snapshot.0, &mut entry) };
+  while ok != 0 {
+    if entry.process_id == pid {
+      let len = entry.exe_file.iter().position(|c| *c == 0).unwrap_or(entry.exe_file.len());
+      return Some((entry.parent_process_id, String::from_utf16_lossy(&entry.exe_file[..len])));
+    }
+    ok = unsafe { Process32NextW(snapshot.0, &mut entry) };
+  }
+  None
+}
+
+/// Resumes every thread of a process created with `CREATE_SUSPENDED`; that is its main
+/// thread only.
+#[cfg(target_os = "windows")]
+fn resume_process(pid: u32) -> Result<(), GameError> {
+  let snapshot = OwnedHandle::new(unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPTHREAD, 0) })
+    .ok_or_else(|| last_error("Failed to list game threads"))?;
+  let mut entry = ThreadEntry32 {
+    size: std::mem::size_of::<ThreadEntry32>() as u32,
+    usage: 0,
+    thread_id: 0,
+    owner_process_id: 0,
+    base_priority: 0,
+    delta_priority: 0,
+    flags: 0,
+  };
+  let mut resumed = false;
+  let mut ok = unsafe { Thread32First(snapshot.0, &mut entry) };
+  while ok != 0 {
+    if entry.owner_process_id == pid {
+      if let Some(thread) = OwnedHandle::new(unsafe { OpenThread(THREAD_SUSPEND_RESUME, 0, entry.thread_id) }) {
+        resumed |= unsafe { ResumeThread(thread.0) } != u32::MAX;
+      }
+    }
+    ok = unsafe { Thread32Next(snapshot.0, &mut entry) };
+  }
+  if !resumed {
+    return Err(last_error("Failed to resume game"));
+  }
+  Ok(())
+}
+
+/// Moves amdaemon into a nested job that only admits one process, so it cannot start
+/// anything of its own, and ends any child it started before that. Runs on the job's
+/// process notifications until the last process in the job has exited.
+#[cfg(target_os = "windows")]
+fn confine_amdaemon(port: OwnedHandle) {
+  let mut amdaemons: Vec<u32> = Vec::new();
+  loop {
+    let mut message = 0u32;
+    let mut key = 0usize;
+    let mut data: *mut c_void = std::ptr::null_mut();
+    let ok = unsafe { GetQueuedCompletionStatus(port.0, &mut message, &mut key, &mut data, INFINITE) };
+    if ok == 0 || message == JOB_OBJECT_MSG_ACTIVE_PROCESS_ZERO {
+      return;
+    }
+    if message != JOB_OBJECT_MSG_NEW_PROCESS {
+      continue;
+    }
+    // For process messages the overlapped pointer carries the process id.
+    let pid = data as usize as u32;
+    let Some((parent, name)) = process_entry(pid) else {
+      continue;
+    };
+    if amdaemons.contains(&parent) {
+      if let Some(process) = OwnedHandle::new(unsafe { OpenProcess(PROCESS_TERMINATE, 0, pid) }) {
+        unsafe {
+          TerminateProcess(process.0, 1);
+        }
+      }
+      continue;
+    }
+    if !name.eq_ignore_ascii_case("amdaemon.exe") {
+      continue;
+    }
+    amdaemons.push(pid);
+    let mut info = JobObjectExtendedLimitInformation::default();
+    info.basic_limit_information.limit_flags = JOB_OBJECT_LIMIT_ACTIVE_PROCESS;
+    info.basic_limit_information.active_process_limit = 1;
+    // The nested job lives on with amdaemon once its handle is closed here.
+    let (Ok(nested), Some(process)) = (
+      create_job(&info),
+      OwnedHandle::new(unsafe { OpenProcess(PROCESS_SET_QUOTA | PROCESS_TERMINATE, 0, pid) }),
+    ) else {
+      continue;
+    };
+    unsafe {
+      AssignProcessToJobObject(nested.0, process.0);
+    }
+  }
+}
+
+/// Spawns `cmd` inside a job object configured from `options`. The process starts
+/// suspended and only runs once it is in the job, so it cannot start anything unconfined;
+/// its children inherit the job and cannot break away, which covers the whole
+/// inject/amdaemon/game chain. `creation_flags` are the flags `cmd` was built with. If the
+/// process cannot be confined it is killed rather than left running unprotected.
+#[cfg(target_os = "windows")]
+pub fn spawn_sandboxed(
+  cmd: &mut Command,
+  options: &SandboxOptions,
+  creation_flags: u32,
+) -> Result<SandboxedChild, GameError> {
+  if !options.is_enabled() {
+    let child = cmd.spawn().map_err(|e| GameError::Launch(e.to_string()))?;
+    return Ok(SandboxedChild { child, sandbox: None });
+  }
+  let mut info = JobObjectExtendedLimitInformation::default();
+  if options.kill_on_close {
+    info.basic_limit_information.limit_flags |= JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+  }
+  if let Some(max_mb) = options.max_working_set_mb {
+    let max = (max_mb as usize).saturating_mul(1024 * 1024);
+    info.basic_limit_information.limit_flags |= JOB_OBJECT_LIMIT_WORKINGSET;
+    info.basic_limit_information.minimum_working_set_size = MIN_WORKING_SET.min(max);
+    info.basic_limit_information.maximum_working_set_size = max;
+  }
+  let job = create_job(&info)?;
+  let port = if options.deny_amdaemon_children {
+    Some(watch_job(&job)?)
+  } else {
+    None
+  };
+
+  cmd.creation_flags(creation_flags | CREATE_SUSPENDED);
+  let mut child = cmd.spawn().map_err(|e| GameError::Launch(e.to_string()))?;
+  let confined = if unsafe { AssignProcessToJobObject(job.0, child.as_raw_handle()) } == 0 {
+    Err(last_error("Failed to assign game to job object"))
+  } else {
+    resume_process(child.id())
+  };
+  if let Err(err) = confined {
+    let _ = child.kill();
+    return Err(err);
+  }
+
+  if let Some(port) = port {
+    std::thread::spawn(move || confine_amdaemon(port));
+  }
+  Ok(SandboxedChild {
+    child,
+    sandbox: Some(GameSandbox { _job: job }),
+  })
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn spawn_sandboxed(
+  cmd: &mut Command,
+  _options: &SandboxOptions,
+  _creation_flags: u32,
+) -> Result<SandboxedChild, GameError> {
+  let child = cmd.spawn().map_err(|e| GameError::Launch(e.to_string()))?;
+  Ok(SandboxedChild { child, sandbox: None })
+}
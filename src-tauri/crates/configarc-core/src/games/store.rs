@@ -2,6 +2,7 @@ use super::model::Game;
 use crate::error::GameError;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 fn games_path() -> PathBuf {
   Path::new(".").join("configarc_games.json")
@@ -20,6 +21,11 @@ pub fn list_games() -> Result<Vec<Game>, GameError> {
   Ok(games)
 }
 
+/// Last write time of the games file, for callers that cache `list_games`.
+pub fn games_modified() -> Option<SystemTime> {
+  fs::metadata(games_path()).and_then(|m| m.modified()).ok()
+}
+
 pub fn save_game(game: Game) -> Result<(), GameError> {
   let mut games = list_games()?;
   games.retain(|g| g.id != game.id);
@@ -0,0 +1,177 @@
+//! Soft deletion for files the launcher removes from a game (mods, option folders). Deleted
+//! items move into a trash folder next to them, so the move is a rename even when the OPTION
+//! folder sits on another drive, and can be put back until [`UNDO_WINDOW`] passes. Older
+//! items are purged whenever something new is trashed.
+
+use crate::error::GameError;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const TRASH_DIR_NAME: &str = ".configarc_trash";
+const ENTRY_FILE_NAME: &str = "entry.json";
+const ITEM_DIR_NAME: &str = "item";
+/// How long a deleted item can be restored.
+pub const UNDO_WINDOW: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashEntry {
+  pub id: String,
+  pub game_id: String,
+  /// `mod` or `option`.
+  pub kind: String,
+  pub name: String,
+  pub original_path: PathBuf,
+  pub deleted_at: String,
+  /// When the item is purged and can no longer be restored.
+  pub expires_at: String,
+}
+
+fn trash_dir(root: &Path) -> PathBuf {
+  root.join(TRASH_DIR_NAME)
+}
+
+fn expired(entry: &TrashEntry, now: DateTime<Utc>) -> bool {
+  DateTime::parse_from_rfc3339(&entry.expires_at)
+    .map(|expires| expires.with_timezone(&Utc) <= now)
+    .unwrap_or(true)
+}
+
+fn read_entries(root: &Path) -> Vec<(PathBuf, TrashEntry)> {
+  let Ok(dirs) = fs::read_dir(trash_dir(root)) else {
+    return Vec::new();
+  };
+  let mut entries: Vec<(PathBuf, TrashEntry)> = dirs
+    .flatten()
+    .filter_map(|dir| {
+      let data = fs::read_to_string(dir.path().join(ENTRY_FILE_NAME)).ok()?;
+      let entry = serde_json::from_str(&data).ok()?;
+      Some((dir.path(), entry))
+    })
+    .collect();
+  entries.sort_by(|a, b| a.1.deleted_at.cmp(&b.1.deleted_at));
+  entries
+}
+
+/// Deletes trashed items of the folder `root` whose undo window has passed. Returns how many were removed.
+pub fn purge_expired(root: &Path) -> usize {
+  let now = Utc::now();
+  let mut purged = 0;
+  for (dir, entry) in read_entries(root) {
+    if expired(&entry, now) && fs::remove_dir_all(&dir).is_ok() {
+      purged += 1;
+    }
+  }
+  if fs::read_dir(trash_dir(root)).is_ok_and(|mut rest| rest.next().is_none()) {
+    let _ = fs::remove_dir(trash_dir(root));
+  }
+  purged
+}
+
+/// Moves the file or folder at `path` into the trash of its parent folder.
+pub fn move_to_trash(game_id: &str, path: &Path, kind: &str) -> Result<TrashEntry, GameError> {
+  let (Some(root), Some(name)) = (path.parent(), path.file_name()) else {
+    return Err(GameError::NotFound(path.to_string_lossy().into_owned()));
+  };
+  let name = name.to_string_lossy().into_owned();
+  if !path.exists() {
+    return Err(GameError::NotFound(name));
+  }
+  purge_expired(root);
+
+  let now = Utc::now();
+  let stamp = now.format("%Y%m%d%H%M%S%3f").to_string();
+  let mut id = stamp.clone();
+  let mut suffix = 1;
+  while trash_dir(root).join(&id).exists() {
+    id = format!("{}-{}", stamp, suffix);
+    suffix += 1;
+  }
+  let dir = trash_dir(root).join(&id);
+  fs::create_dir_all(&dir)?;
+  let entry = TrashEntry {
+    id,
+    game_id: game_id.to_string(),
+    kind: kind.to_string(),
+    name,
+    original_path: path.to_path_buf(),
+    deleted_at: now.to_rfc3339(),
+    expires_at: (now + ChronoDuration::from_std(UNDO_WINDOW).unwrap_or_else(|_| ChronoDuration::zero())).to_rfc3339(),
+  };
+  fs::write(dir.join(ENTRY_FILE_NAME), serde_json::to_vec_pretty(&entry)?)?;
+  if let Err(e) = fs::rename(path, dir.join(ITEM_DIR_NAME)) {
+    let _ = fs::remove_dir_all(&dir);
+    return Err(e.into());
+  }
+  Ok(entry)
+}
+
+/// The most recently trashed item from the folder `root` that can still be restored.
+pub fn last_trashed(root: &Path) -> Option<TrashEntry> {
+  let now = Utc::now();
+  read_entries(root)
+    .into_iter()
+    .map(|(_, entry)| entry)
+    .rev()
+    .find(|entry| !expired(entry, now))
+}
+
+/// Puts a trashed item back where it was. Fails when the window has passed or something new
+/// now occupies the original path.
+pub fn restore_from_trash(root: &Path, id: &str) -> Result<TrashEntry, GameError> {
+  let (dir, entry) = read_entries(root)
+    .into_iter()
+    .find(|(_, entry)| entry.id == id)
+    .ok_or_else(|| GameError::NotFound(format!("Deleted item {}", id)))?;
+  if expired(&entry, Utc::now()) {
+    return Err(GameError::NotFound(format!("Undo window for {} has passed", entry.name)));
+  }
+  if entry.original_path.exists() {
+    return Err(GameError::Io(std::io::Error::new(
+      std::io::ErrorKind::AlreadyExists,
+      format!("{} already exists", entry.original_path.display()),
+    )));
+  }
+  if let Some(parent) = entry.original_path.parent() {
+    fs::create_dir_all(parent)?;
+  }
+  fs::rename(dir.join(ITEM_DIR_NAME), &entry.original_path)?;
+  let _ = fs::remove_dir_all(&dir);
+  Ok(entry)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::TempDir;
+
+  #[test]
+  fn trashes_and_restores_within_the_window() {
+    let root = TempDir::new().unwrap();
+    let mods = root.path().join("Mods");
+    fs::create_dir_all(&mods).unwrap();
+    let target = mods.join("Tweaks.dll");
+    fs::write(&target, b"dll").unwrap();
+
+    let entry = move_to_trash("sdez", &target, "mod").unwrap();
+    assert!(!target.exists());
+    assert_eq!(last_trashed(&mods).unwrap().id, entry.id);
+
+    restore_from_trash(&mods, &entry.id).unwrap();
+    assert_eq!(fs::read(&target).unwrap(), b"dll");
+    assert!(last_trashed(&mods).is_none());
+
+    let entry = move_to_trash("sdez", &target, "mod").unwrap();
+    let entry_path = trash_dir(&mods).join(&entry.id).join(ENTRY_FILE_NAME);
+    let stale = TrashEntry {
+      expires_at: (Utc::now() - ChronoDuration::seconds(1)).to_rfc3339(),
+      ..entry.clone()
+    };
+    fs::write(&entry_path, serde_json::to_vec(&stale).unwrap()).unwrap();
+    assert!(restore_from_trash(&mods, &entry.id).is_err());
+    assert_eq!(purge_expired(&mods), 1);
+    assert!(!trash_dir(&mods).exists());
+  }
+}
@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+fn default_restart_delay_secs() -> u64 {
+  5
+}
+
+fn default_stable_after_secs() -> u64 {
+  10 * 60
+}
+
+/// Crash handling for unattended cabinets.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchdogOptions {
+  #[serde(default)]
+  pub enabled: bool,
+  /// Relaunches allowed after consecutive crashes; 0 only reports them.
+  #[serde(default)]
+  pub max_restarts: u32,
+  #[serde(default = "default_restart_delay_secs")]
+  pub restart_delay_secs: u64,
+  /// A run lasting at least this long resets the restart budget, so a cab that crashes once
+  /// a day keeps being brought back.
+  #[serde(default = "default_stable_after_secs")]
+  pub stable_after_secs: u64,
+}
+
+impl Default for WatchdogOptions {
+  fn default() -> Self {
+    Self {
+      enabled: false,
+      max_restarts: 0,
+      restart_delay_secs: default_restart_delay_secs(),
+      stable_after_secs: default_stable_after_secs(),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchdogAction {
+  /// The game exited normally or the watchdog is off.
+  Stop,
+  /// The game crashed and the restart budget is spent.
+  Report,
+  Restart { attempt: u32, delay: Duration },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReport {
+  pub game_id: String,
+  pub game_name: String,
+  /// `None` when the process was killed without an exit code.
+  pub exit_code: Option<i32>,
+  pub uptime_secs: u64,
+  pub detected_at: String,
+  /// Relaunch attempt that follows this crash, if any.
+  pub restart_attempt: Option<u32>,
+  pub log_source: Option<String>,
+  pub log_lines: Vec<String>,
+}
+
+/// A clean shutdown exits with 0; anything else, including an unknown code, is a crash.
+pub fn is_abnormal_exit(code: Option<i32>) -> bool {
+  code != Some(0)
+}
+
+/// Tracks restarts across the runs of one launch.
+#[derive(Debug, Clone)]
+pub struct Watchdog {
+  options: WatchdogOptions,
+  restarts: u32,
+}
+
+impl Watchdog {
+  pub fn new(options: WatchdogOptions) -> Self {
+    Self { options, restarts: 0 }
+  }
+
+  pub fn restarts(&self) -> u32 {
+    self.restarts
+  }
+
+  /// Decides what follows an exit with `code` after the game ran for `uptime`.
+  pub fn on_exit(&mut self, code: Option<i32>, uptime: Duration) -> WatchdogAction {
+    if !self.options.enabled || !is_abnormal_exit(code) {
+      return WatchdogAction::Stop;
+    }
+    if uptime >= Duration::from_secs(self.options.stable_after_secs) {
+      self.restarts = 0;
+    }
+    if self.restarts >= self.options.max_restarts {
+      return WatchdogAction::Report;
+    }
+    self.restarts += 1;
+    WatchdogAction::Restart {
+      attempt: self.restarts,
+      delay: Duration::from_secs(self.options.restart_delay_secs),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn restarts_until_budget_is_spent_and_resets_after_stable_run() {
+    let mut watchdog = Watchdog::new(WatchdogOptions {
+      enabled: true,
+      max_restarts: 2,
+      restart_delay_secs: 1,
+      stable_after_secs: 60,
+    });
+    let quick = Duration::from_secs(5);
+    assert_eq!(watchdog.on_exit(Some(0), quick), WatchdogAction::Stop);
+    assert!(matches!(watchdog.on_exit(Some(-1), quick), WatchdogAction::Restart { attempt: 1, .. }));
+    assert!(matches!(watchdog.on_exit(None, quick), WatchdogAction::Restart { attempt: 2, .. }));
+    assert_eq!(watchdog.on_exit(Some(3), quick), WatchdogAction::Report);
+    assert!(matches!(
+      watchdog.on_exit(Some(3), Duration::from_secs(120)),
+      WatchdogAction::Restart { attempt: 1, .. }
+    ));
+  }
+}
@@ -0,0 +1,198 @@
+use crate::platform::powershell_support;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+#[cfg(target_os = "windows")]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+/// Per-user key Windows' Graphics settings page writes to. It lives under HKCU, so setting a
+/// preference never needs elevation.
+const USER_GPU_PREFERENCES_KEY: &str = r"HKCU\Software\Microsoft\DirectX\UserGpuPreferences";
+
+/// User-mode driver DLLs a Direct3D/OpenGL process loads for the adapter it renders on.
+const NVIDIA_MODULES: &[&str] = &["nvldumd", "nvd3dum", "nvwgf2um", "nvoglv"];
+const AMD_MODULES: &[&str] = &["aticfx", "atidxx", "atiumd", "amdxx", "amdxc", "atioglxx"];
+const INTEL_MODULES: &[&str] = &["igd10iumd", "igd12umd", "igdumdim", "igd9dxva", "ig9icd", "ig75icd", "igxelpicd"];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GpuPreference {
+    /// Leave whatever Windows has stored for the executable alone.
+    #[default]
+    Unmanaged,
+    /// "Let Windows decide".
+    Auto,
+    PowerSaving,
+    HighPerformance,
+}
+
+impl GpuPreference {
+    fn registry_value(self) -> Option<&'static str> {
+        match self {
+            GpuPreference::Unmanaged => None,
+            GpuPreference::Auto => Some("GpuPreference=0;"),
+            GpuPreference::PowerSaving => Some("GpuPreference=1;"),
+            GpuPreference::HighPerformance => Some("GpuPreference=2;"),
+        }
+    }
+
+    fn from_registry_value(value: &str) -> Option<Self> {
+        value.split(';').find_map(|part| match part.trim().strip_prefix("GpuPreference=")? {
+            "0" => Some(GpuPreference::Auto),
+            "1" => Some(GpuPreference::PowerSaving),
+            "2" => Some(GpuPreference::HighPerformance),
+            _ => None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GpuVendor {
+    Nvidia,
+    Amd,
+    Intel,
+}
+
+impl GpuVendor {
+    fn is_discrete_capable(self) -> bool {
+        !matches!(self, GpuVendor::Intel)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GpuUsage {
+    /// Vendor of the adapter the game renders on.
+    pub vendor: GpuVendor,
+    /// Every vendor whose driver the process had loaded.
+    pub loaded_vendors: Vec<GpuVendor>,
+    pub driver_modules: Vec<String>,
+}
+
+#[cfg(target_os = "windows")]
+fn reg() -> Command {
+    let mut cmd = Command::new("reg");
+    cmd.creation_flags(CREATE_NO_WINDOW);
+    cmd
+}
+
+#[cfg(not(target_os = "windows"))]
+fn reg() -> Command {
+    Command::new("reg")
+}
+
+/// Stores `preference` for `exe` the same way the Windows Graphics settings page does.
+/// `Unmanaged` leaves the stored value untouched.
+pub fn set_gpu_preference(exe: &Path, preference: GpuPreference) -> Result<(), String> {
+    let Some(value) = preference.registry_value() else {
+        return Ok(());
+    };
+    let output = reg()
+        .args(["add", USER_GPU_PREFERENCES_KEY, "/v"])
+        .arg(exe)
+        .args(["/t", "REG_SZ", "/d", value, "/f"])
+        .output()
+        .map_err(|e| format!("Failed to run reg.exe: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to set GPU preference: {}", stderr.trim()));
+    }
+    Ok(())
+}
+
+/// Preference Windows currently has stored for `exe`, if any.
+pub fn query_gpu_preference(exe: &Path) -> Option<GpuPreference> {
+    let output = reg()
+        .args(["query", USER_GPU_PREFERENCES_KEY, "/v"])
+        .arg(exe)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout
+        .lines()
+        .find_map(|line| line.split_once("REG_SZ").map(|(_, value)| value.trim().to_string()))
+        .and_then(|value| GpuPreference::from_registry_value(&value))
+}
+
+fn vendor_for_module(module: &str) -> Option<GpuVendor> {
+    let lower = module.to_ascii_lowercase();
+    let has = |prefixes: &[&str]| prefixes.iter().any(|p| lower.starts_with(p));
+    if has(NVIDIA_MODULES) {
+        Some(GpuVendor::Nvidia)
+    } else if has(AMD_MODULES) {
+        Some(GpuVendor::Amd)
+    } else if has(INTEL_MODULES) {
+        Some(GpuVendor::Intel)
+    } else {
+        None
+    }
+}
+
+/// Works out the rendering GPU from the driver modules a process loaded. On hybrid laptops the
+/// integrated driver is often loaded next to the discrete one, so a discrete vendor wins.
+pub fn classify_driver_modules<'a>(modules: impl IntoIterator<Item = &'a str>) -> Option<GpuUsage> {
+    let mut loaded_vendors = Vec::new();
+    let mut driver_modules = Vec::new();
+    for module in modules {
+        let Some(vendor) = vendor_for_module(module) else {
+            continue;
+        };
+        driver_modules.push(module.to_string());
+        if !loaded_vendors.contains(&vendor) {
+            loaded_vendors.push(vendor);
+        }
+    }
+    let vendor = loaded_vendors
+        .iter()
+        .copied()
+        .find(|v| v.is_discrete_capable())
+        .or_else(|| loaded_vendors.first().copied())?;
+    Some(GpuUsage {
+        vendor,
+        loaded_vendors,
+        driver_modules,
+    })
+}
+
+/// GPU the running process `process_name` (without `.exe`) renders on. 32-bit games only
+/// expose their modules to a 32-bit caller, so this can come back empty for them.
+pub fn detect_process_gpu(process_name: &str) -> Option<GpuUsage> {
+    if !powershell_support().runs_cmdlets() {
+        return None;
+    }
+    let script = format!(
+        "Get-Process -Name '{}' -ErrorAction SilentlyContinue | Select-Object -First 1 -ExpandProperty Modules | Select-Object -ExpandProperty ModuleName",
+        process_name.replace('\'', "''")
+    );
+    let mut cmd = Command::new("powershell");
+    cmd.args(["-NoProfile", "-Command", &script]);
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(CREATE_NO_WINDOW);
+    let output = cmd.output().ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    classify_driver_modules(stdout.lines().map(str::trim))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn discrete_driver_wins_over_integrated() {
+        let usage = classify_driver_modules(["ntdll.dll", "igd10iumd64.dll", "nvldumdx.dll", "nvwgf2umx.dll"]).unwrap();
+        assert_eq!(usage.vendor, GpuVendor::Nvidia);
+        assert_eq!(usage.loaded_vendors, vec![GpuVendor::Intel, GpuVendor::Nvidia]);
+        assert_eq!(usage.driver_modules.len(), 3);
+        assert!(classify_driver_modules(["kernel32.dll"]).is_none());
+        assert_eq!(
+            GpuPreference::from_registry_value("SwapEffectUpgradeEnable=1;GpuPreference=2;"),
+            Some(GpuPreference::HighPerformance)
+        );
+    }
+}
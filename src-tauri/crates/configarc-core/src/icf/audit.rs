@@ -0,0 +1,313 @@
+//! Compares an ICF against what is installed next to it, so a stale or hand-edited ICF
+//! shows up as a list of concrete problems rather than hex versions to eyeball.
+
+use chrono::NaiveDateTime;
+use serde::Serialize;
+
+use super::{summarize_icf, IcfData, IcfOptionData, Version};
+
+/// What is actually on disk for the game an ICF belongs to.
+#[derive(Debug, Clone, Default)]
+pub struct InstalledContent {
+    /// Newest app version the installed data reports, if it could be read.
+    pub app_version: Option<Version>,
+    /// Target versions of the installed app patches in chain order. `None` when the install
+    /// does not keep patches apart, as a game folder does.
+    pub patch_versions: Option<Vec<Version>>,
+    /// Option folder names, e.g. `A001`.
+    pub options: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IcfFindingKind {
+    /// Listed in the ICF but missing from the OPTION folder.
+    MissingOption,
+    /// In the OPTION folder but not listed in the ICF.
+    UnlistedOption,
+    /// The ICF's newest app version differs from the installed one.
+    VersionMismatch,
+    /// Installed, but the ICF has no patch entry for it.
+    MissingPatch,
+    /// Listed in the ICF but not installed.
+    UninstalledPatch,
+    /// A patch whose source version does not continue the chain before it.
+    OrphanedPatch,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IcfFinding {
+    pub kind: IcfFindingKind,
+    /// Option id or version the finding is about.
+    pub subject: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IcfAudit {
+    pub app_id: Option<String>,
+    pub icf_version: Option<Version>,
+    pub installed_version: Option<Version>,
+    /// Empty when the ICF matches the installed content.
+    pub findings: Vec<IcfFinding>,
+}
+
+/// Reads `1.45.00`, `1.45` or the `Ver 1.45.0` form of data.conf and DataConfig.xml.
+pub fn parse_version(value: &str) -> Option<Version> {
+    let value = value.trim();
+    let value = value.strip_prefix("Ver").unwrap_or(value).trim();
+    let mut parts = value.split('.').map(|part| part.trim());
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    let build = match parts.next() {
+        Some(part) => part.parse().ok()?,
+        None => 0,
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(Version { major, minor, build })
+}
+
+fn finding(kind: IcfFindingKind, subject: impl Into<String>, message: String) -> IcfFinding {
+    IcfFinding {
+        kind,
+        subject: subject.into(),
+        message,
+    }
+}
+
+pub fn audit_icf(entries: &[IcfData], installed: &InstalledContent) -> IcfAudit {
+    let summary = summarize_icf(entries);
+    let mut findings = Vec::new();
+
+    let listed: Vec<&str> = entries
+        .iter()
+        .filter_map(|entry| match entry {
+            IcfData::Option(option) => Some(option.option_id.as_str()),
+            _ => None,
+        })
+        .collect();
+    for id in &listed {
+        if !installed.options.iter().any(|name| name.eq_ignore_ascii_case(id)) {
+            findings.push(finding(
+                IcfFindingKind::MissingOption,
+                *id,
+                format!("{} is listed in the ICF but not installed", id),
+            ));
+        }
+    }
+    for name in &installed.options {
+        if !listed.iter().any(|id| id.eq_ignore_ascii_case(name)) {
+            findings.push(finding(
+                IcfFindingKind::UnlistedOption,
+                name.as_str(),
+                format!("{} is installed but not listed in the ICF", name),
+            ));
+        }
+    }
+
+    if let (Some(icf), Some(disk)) = (summary.app_version, installed.app_version) {
+        if icf != disk {
+            findings.push(finding(
+                IcfFindingKind::VersionMismatch,
+                icf.to_string(),
+                format!("The ICF declares app version {} but {} is installed", icf, disk),
+            ));
+        }
+    }
+
+    let app_version = entries.iter().find_map(|entry| match entry {
+        IcfData::App(app) => Some(app.version),
+        _ => None,
+    });
+    let mut patches: Vec<_> = entries
+        .iter()
+        .filter_map(|entry| match entry {
+            IcfData::Patch(patch) => Some(patch),
+            _ => None,
+        })
+        .collect();
+    patches.sort_by_key(|patch| patch.sequence_number);
+    let mut chain_at = app_version;
+    for patch in &patches {
+        if let Some(at) = chain_at.filter(|at| *at != patch.source_version) {
+            findings.push(finding(
+                IcfFindingKind::OrphanedPatch,
+                patch.target_version.to_string(),
+                format!(
+                    "Patch {} to {} starts from {} but the chain before it ends at {}",
+                    patch.sequence_number, patch.target_version, patch.source_version, at
+                ),
+            ));
+        }
+        chain_at = Some(patch.target_version);
+    }
+
+    if let Some(installed_patches) = &installed.patch_versions {
+        for patch in &patches {
+            if !installed_patches.contains(&patch.target_version) {
+                findings.push(finding(
+                    IcfFindingKind::UninstalledPatch,
+                    patch.target_version.to_string(),
+                    format!("Patch to {} is listed in the ICF but not installed", patch.target_version),
+                ));
+            }
+        }
+        for version in installed_patches {
+            if !patches.iter().any(|patch| patch.target_version == *version) {
+                findings.push(finding(
+                    IcfFindingKind::MissingPatch,
+                    version.to_string(),
+                    format!("Patch to {} is installed but the ICF has no entry for it", version),
+                ));
+            }
+        }
+    }
+
+    IcfAudit {
+        app_id: summary.app_id,
+        icf_version: summary.app_version,
+        installed_version: installed.app_version,
+        findings,
+    }
+}
+
+/// Option entries for installed option folders the ICF does not list yet, stamped with the
+/// given times and carrying the ICF's app id.
+pub fn propose_option_entries(entries: &[IcfData], installed: &[(String, NaiveDateTime)]) -> Vec<IcfData> {
+    let app_id = summarize_icf(entries).app_id.unwrap_or_default();
+    let mut proposed: Vec<IcfData> = installed
+        .iter()
+        .filter(|(name, _)| {
+            !entries
+                .iter()
+                .any(|entry| matches!(entry, IcfData::Option(option) if option.option_id.eq_ignore_ascii_case(name)))
+        })
+        .map(|(name, datetime)| {
+            IcfData::Option(IcfOptionData {
+                app_id: app_id.clone(),
+                option_id: name.to_ascii_uppercase(),
+                required_system_version: Version { major: 0, minor: 0, build: 0 },
+                datetime: *datetime,
+                is_prerelease: false,
+            })
+        })
+        .collect();
+    proposed.sort_by_key(|entry| match entry {
+        IcfData::Option(option) => option.option_id.clone(),
+        _ => String::new(),
+    });
+    proposed
+}
+
+/// Adds `options` right after the last option entry, or at the end when there is none.
+pub fn insert_option_entries(entries: &mut Vec<IcfData>, options: Vec<IcfData>) {
+    let at = entries
+        .iter()
+        .rposition(|entry| matches!(entry, IcfData::Option(_)))
+        .map(|index| index + 1)
+        .unwrap_or(entries.len());
+    entries.splice(at..at, options);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{IcfInnerData, IcfOptionData, IcfPatchData};
+    use super::*;
+
+    fn version(major: u16, minor: u8) -> Version {
+        Version { major, minor, build: 0 }
+    }
+
+    fn patch(sequence_number: u8, source: Version, target: Version) -> IcfData {
+        let datetime = NaiveDateTime::default();
+        IcfData::Patch(IcfPatchData {
+            id: "SDEZ".to_string(),
+            sequence_number,
+            source_version: source,
+            source_datetime: datetime,
+            source_required_system_version: version(80, 54),
+            target_version: target,
+            target_datetime: datetime,
+            target_required_system_version: version(80, 54),
+            is_prerelease: false,
+        })
+    }
+
+    #[test]
+    fn reports_options_versions_and_patch_chain_drift() {
+        let datetime = NaiveDateTime::default();
+        let entries = vec![
+            IcfData::App(IcfInnerData {
+                id: "SDEZ".to_string(),
+                version: version(1, 40),
+                required_system_version: version(80, 54),
+                datetime,
+                is_prerelease: false,
+            }),
+            patch(1, version(1, 40), version(1, 45)),
+            patch(2, version(1, 41), version(1, 50)),
+            IcfData::Option(IcfOptionData {
+                app_id: "SDEZ".to_string(),
+                option_id: "A001".to_string(),
+                required_system_version: version(0, 0),
+                datetime,
+                is_prerelease: false,
+            }),
+        ];
+        let installed = InstalledContent {
+            app_version: parse_version("Ver 1.45.0"),
+            patch_versions: Some(vec![version(1, 45)]),
+            options: vec!["A002".to_string()],
+        };
+
+        let kinds: Vec<(IcfFindingKind, String)> = audit_icf(&entries, &installed)
+            .findings
+            .into_iter()
+            .map(|f| (f.kind, f.subject))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (IcfFindingKind::MissingOption, "A001".to_string()),
+                (IcfFindingKind::UnlistedOption, "A002".to_string()),
+                (IcfFindingKind::VersionMismatch, "1.50.00".to_string()),
+                (IcfFindingKind::OrphanedPatch, "1.50.00".to_string()),
+                (IcfFindingKind::UninstalledPatch, "1.50.00".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn proposes_unlisted_options_after_the_listed_ones() {
+        let datetime = NaiveDateTime::default();
+        let mut entries = vec![
+            patch(1, version(1, 40), version(1, 45)),
+            IcfData::Option(IcfOptionData {
+                app_id: "SDEZ".to_string(),
+                option_id: "A001".to_string(),
+                required_system_version: version(0, 0),
+                datetime,
+                is_prerelease: false,
+            }),
+            patch(2, version(1, 45), version(1, 50)),
+        ];
+        let installed = vec![
+            ("A003".to_string(), datetime),
+            ("a001".to_string(), datetime),
+            ("A002".to_string(), datetime),
+        ];
+
+        let proposed = propose_option_entries(&entries, &installed);
+        insert_option_entries(&mut entries, proposed);
+        let ids: Vec<&str> = entries
+            .iter()
+            .map(|entry| match entry {
+                IcfData::Option(option) => option.option_id.as_str(),
+                _ => "patch",
+            })
+            .collect();
+        assert_eq!(ids, ["patch", "A001", "A002", "A003", "patch"]);
+    }
+}
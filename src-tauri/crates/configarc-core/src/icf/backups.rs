@@ -0,0 +1,146 @@
+//! Timestamped copies of an ICF taken before every save, kept in `icf_backups` under the
+//! AMFS directory. Only the newest few per ICF are kept.
+
+use std::cmp::Reverse;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{Local, NaiveDateTime};
+use serde::Serialize;
+
+pub const ICF_BACKUP_DIR: &str = "icf_backups";
+/// Backups kept per ICF; older ones are deleted when a new one is taken.
+pub const ICF_BACKUPS_KEPT: usize = 10;
+const STAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IcfBackup {
+    /// `ICF1` or `ICF2`.
+    pub kind: String,
+    pub file_name: String,
+    pub path: String,
+    pub created_at: NaiveDateTime,
+    pub size: u64,
+}
+
+fn backup_dir(amfs: &Path) -> PathBuf {
+    amfs.join(ICF_BACKUP_DIR)
+}
+
+/// Reads `ICF1_20240101-120000.bak`, or `ICF1_20240101-120000-2.bak` for a second backup
+/// taken within the same second.
+fn parse_backup(path: &Path) -> Option<IcfBackup> {
+    let file_name = path.file_name()?.to_str()?.to_string();
+    let stem = file_name.strip_suffix(".bak")?;
+    let (kind, stamp) = stem.split_once('_')?;
+    let created_at = NaiveDateTime::parse_from_str(stamp.get(..15)?, STAMP_FORMAT).ok()?;
+    Some(IcfBackup {
+        kind: kind.to_string(),
+        path: path.to_string_lossy().into_owned(),
+        size: fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+        file_name,
+        created_at,
+    })
+}
+
+/// Order of backups taken within the same second: 1 for the first, then the `-N` suffix.
+fn sequence(backup: &IcfBackup) -> u32 {
+    backup
+        .file_name
+        .strip_suffix(".bak")
+        .and_then(|stem| stem.rsplit_once('_'))
+        .and_then(|(_, stamp)| stamp.get(16..))
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(1)
+}
+
+/// Backups under `amfs`, of one ICF or all of them, newest first.
+pub fn list_icf_backups(amfs: &Path, kind: Option<&str>) -> Vec<IcfBackup> {
+    let kind = kind.map(|k| k.trim().to_uppercase());
+    let mut backups: Vec<IcfBackup> = fs::read_dir(backup_dir(amfs))
+        .map(|entries| entries.flatten().filter_map(|entry| parse_backup(&entry.path())).collect())
+        .unwrap_or_default();
+    backups.retain(|backup| kind.as_ref().is_none_or(|k| &backup.kind == k));
+    backups.sort_by_key(|backup| Reverse((backup.created_at, sequence(backup))));
+    backups
+}
+
+/// Copies the current `kind` ICF under `amfs` into the backup directory and drops all but
+/// the newest `keep` backups of it. Nothing is taken when the ICF does not exist yet.
+pub fn backup_icf(amfs: &Path, kind: &str, keep: usize) -> Result<Option<IcfBackup>> {
+    let kind = kind.trim().to_uppercase();
+    let source = amfs.join(&kind);
+    if !source.is_file() {
+        return Ok(None);
+    }
+    let dir = backup_dir(amfs);
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let stamp = Local::now().format(STAMP_FORMAT).to_string();
+    // Numbered past every backup left from this second, not into a gap rotation left behind,
+    // so the new copy always sorts as the newest.
+    let prefix = format!("{}_{}", kind, stamp);
+    let next = list_icf_backups(amfs, Some(&kind))
+        .iter()
+        .filter(|backup| backup.file_name.starts_with(&prefix))
+        .map(sequence)
+        .max()
+        .map_or(1, |n| n + 1);
+    let target = match next {
+        1 => dir.join(format!("{}.bak", prefix)),
+        n => dir.join(format!("{}-{}.bak", prefix, n)),
+    };
+    fs::copy(&source, &target).with_context(|| format!("Failed to back up {}", source.display()))?;
+
+    for old in list_icf_backups(amfs, Some(&kind)).into_iter().skip(keep.max(1)) {
+        let _ = fs::remove_file(&old.path);
+    }
+    Ok(parse_backup(&target))
+}
+
+/// Puts the backup `file_name` back in place of its ICF. The ICF being replaced is backed up
+/// first, so a restore can itself be undone.
+pub fn restore_icf_backup(amfs: &Path, file_name: &str, keep: usize) -> Result<IcfBackup> {
+    let backup = list_icf_backups(amfs, None)
+        .into_iter()
+        .find(|backup| backup.file_name == file_name.trim())
+        .ok_or_else(|| anyhow!("ICF backup {} not found", file_name.trim()))?;
+    // Read before the safety backup, whose rotation may delete the backup being restored.
+    let content = fs::read(&backup.path).with_context(|| format!("Failed to read {}", backup.path))?;
+    backup_icf(amfs, &backup.kind, keep)?;
+    fs::write(amfs.join(&backup.kind), content).with_context(|| format!("Failed to restore {}", backup.kind))?;
+    Ok(backup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn rotates_and_restores_backups() {
+        let dir = TempDir::new().unwrap();
+        let amfs = dir.path();
+        assert_eq!(backup_icf(amfs, "ICF1", 2).unwrap(), None);
+
+        for content in ["v1", "v2", "v3"] {
+            fs::write(amfs.join("ICF1"), content).unwrap();
+            backup_icf(amfs, "icf1", 2).unwrap().unwrap();
+        }
+        fs::write(amfs.join("ICF2"), "other").unwrap();
+        backup_icf(amfs, "ICF2", 2).unwrap();
+
+        let backups = list_icf_backups(amfs, Some("ICF1"));
+        assert_eq!(backups.len(), 2);
+        assert_eq!(fs::read_to_string(&backups[0].path).unwrap(), "v3");
+        assert_eq!(fs::read_to_string(&backups[1].path).unwrap(), "v2");
+        assert_eq!(list_icf_backups(amfs, None).len(), 3);
+
+        fs::write(amfs.join("ICF1"), "v4").unwrap();
+        let restored = restore_icf_backup(amfs, &backups[1].file_name, 2).unwrap();
+        assert_eq!(restored.kind, "ICF1");
+        assert_eq!(fs::read_to_string(amfs.join("ICF1")).unwrap(), "v2");
+        assert_eq!(fs::read_to_string(&list_icf_backups(amfs, Some("ICF1"))[0].path).unwrap(), "v4");
+        assert!(restore_icf_backup(amfs, "ICF1_missing.bak", 2).is_err());
+    }
+}
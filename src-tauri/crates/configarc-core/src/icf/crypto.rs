@@ -1,11 +1,15 @@
 use aes::cipher::{block_padding::NoPadding, BlockDecryptMut, BlockEncryptMut, KeyIvInit};
 use anyhow::{anyhow, Result};
+#[cfg(feature = "icf-builtin-key")]
 use hex_literal::hex;
 
 type Aes128CbcDec = cbc::Decryptor<aes::Aes128>;
 type Aes128CbcEnc = cbc::Encryptor<aes::Aes128>;
 
+/// The ALLS key and IV, compiled in only with the `icf-builtin-key` feature.
+#[cfg(feature = "icf-builtin-key")]
 pub const ICF_KEY: [u8; 16] = hex!("09ca5efd30c9aaef3804d0a7e3fa7120");
+#[cfg(feature = "icf-builtin-key")]
 pub const ICF_IV: [u8; 16] = hex!("b155c22c2e7f0491fa7f0fdc217aff90");
 
 /// Decrypts an ICF using the provided key and IV.
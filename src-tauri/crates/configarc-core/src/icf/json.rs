@@ -0,0 +1,102 @@
+//! ICFs as pretty JSON, so they can be kept in git and moved between machines. The document
+//! is a small header around the decoded entries; a bare entry array as written by other ICF
+//! tools is accepted on import too.
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::{serialize_icf, IcfData};
+
+const DOCUMENT_FORMAT: &str = "configarc-icf";
+const DOCUMENT_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct IcfDocument {
+    format: String,
+    version: u32,
+    /// `ICF1` or `ICF2`; informational, the import target is chosen by the caller.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    kind: Option<String>,
+    entries: Vec<IcfData>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum IcfJson {
+    Document(IcfDocument),
+    Entries(Vec<IcfData>),
+}
+
+pub fn icf_to_json(kind: &str, entries: &[IcfData]) -> Result<String> {
+    let document = IcfDocument {
+        format: DOCUMENT_FORMAT.to_string(),
+        version: DOCUMENT_VERSION,
+        kind: Some(kind.trim().to_uppercase()).filter(|k| !k.is_empty()),
+        entries: entries.to_vec(),
+    };
+    Ok(serde_json::to_string_pretty(&document)?)
+}
+
+/// Entries of an ICF JSON document, checked to serialize into a valid ICF.
+pub fn icf_from_json(content: &str) -> Result<Vec<IcfData>> {
+    let entries = match serde_json::from_str::<IcfJson>(content).context("Not an ICF JSON document")? {
+        IcfJson::Document(document) => {
+            if document.format != DOCUMENT_FORMAT {
+                return Err(anyhow!("Unknown ICF JSON format: {}", document.format));
+            }
+            if document.version > DOCUMENT_VERSION {
+                return Err(anyhow!(
+                    "ICF JSON version {} is newer than this launcher supports",
+                    document.version
+                ));
+            }
+            document.entries
+        }
+        IcfJson::Entries(entries) => entries,
+    };
+    serialize_icf(&entries)?;
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{IcfInnerData, IcfOptionData, Version};
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    fn entries() -> Vec<IcfData> {
+        let datetime = NaiveDateTime::default();
+        let inner = |id: &str, major| IcfInnerData {
+            id: id.to_string(),
+            version: Version { major, minor: 0, build: 0 },
+            required_system_version: Version { major: 80, minor: 54, build: 1 },
+            datetime,
+            is_prerelease: false,
+        };
+        vec![
+            IcfData::System(inner("ACA", 80)),
+            IcfData::App(inner("SDEZ", 1)),
+            IcfData::Option(IcfOptionData {
+                app_id: "SDEZ".to_string(),
+                option_id: "A001".to_string(),
+                required_system_version: Version { major: 0, minor: 0, build: 0 },
+                datetime,
+                is_prerelease: false,
+            }),
+        ]
+    }
+
+    #[test]
+    fn round_trips_documents_and_bare_entry_arrays() {
+        let json = icf_to_json("icf1", &entries()).unwrap();
+        assert!(json.contains("\"kind\": \"ICF1\""));
+        assert_eq!(icf_from_json(&json).unwrap(), entries());
+
+        let bare = serde_json::to_string(&entries()).unwrap();
+        assert_eq!(icf_from_json(&bare).unwrap(), entries());
+
+        let without_app = serde_json::to_string(&entries()[..1]).unwrap();
+        assert!(icf_from_json(&without_app).is_err());
+        assert!(icf_from_json("{\"format\":\"other\",\"version\":1,\"entries\":[]}").is_err());
+    }
+}
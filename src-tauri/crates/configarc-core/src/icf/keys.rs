@@ -0,0 +1,343 @@
+//! ICF keys, kept in a keyring by platform id (`ALLS`, `ACA`, `ACB`, ...) next to the
+//! launcher. The `ALLS` entry is the key most ICFs are sealed with; some platforms use their
+//! own, and [`decode_icf_with_keyring`] tries those after it. No key ships with the launcher
+//! unless it is built with the `icf-builtin-key` feature; users import theirs from a key file.
+//!
+//! The keyring is sealed like the fsdecrypt key store: with DPAPI for the current user on
+//! Windows, elsewhere with AES-256-GCM under an owner-only key file. It supersedes the
+//! plaintext `icf_keys.json` of older versions, which is not read. Exports are sealed under a
+//! passphrase the same way as profile shares, so keys never leave it in the clear.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config::share::{open_share, seal_share, SHARE_PREFIX};
+use crate::sealed;
+
+use super::{decrypt_icf, parse_icf, IcfData};
+
+const KEYRING_FILE: &str = "icf_keys.db";
+/// Leads every keyring file; the digit is the format version.
+const KEYRING_MAGIC: &[u8] = b"CAIK1";
+const SEAL_ENTROPY: &[u8] = b"ConfigArc ICF keyring";
+/// Platform id of the key ICFs are sealed with unless their platform has its own.
+pub const DEFAULT_KEY_ID: &str = "ALLS";
+
+/// AES key and IV an ICF is sealed with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IcfKey {
+    pub key: [u8; 16],
+    pub iv: [u8; 16],
+}
+
+fn decode_hex_16(label: &str, raw: &str) -> Result<[u8; 16]> {
+    let bytes = hex::decode(raw.trim().trim_start_matches("0x")).map_err(|e| anyhow!("Invalid hex for {label}: {e}"))?;
+    bytes
+        .try_into()
+        .map_err(|bytes: Vec<u8>| anyhow!("Invalid length for {label}: expected 16 bytes, got {}", bytes.len()))
+}
+
+impl IcfKey {
+    pub fn from_hex(key: &str, iv: &str) -> Result<Self> {
+        Ok(Self {
+            key: decode_hex_16("key", key)?,
+            iv: decode_hex_16("iv", iv)?,
+        })
+    }
+
+    /// The ALLS key compiled into this build, if any.
+    #[cfg(feature = "icf-builtin-key")]
+    pub fn builtin() -> Option<Self> {
+        Some(Self {
+            key: super::ICF_KEY,
+            iv: super::ICF_IV,
+        })
+    }
+
+    #[cfg(not(feature = "icf-builtin-key"))]
+    pub fn builtin() -> Option<Self> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredKey {
+    key: String,
+    iv: String,
+}
+
+/// Platform ids with their own ICF key. Serialized the same way as a key file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IcfKeyring {
+    #[serde(flatten)]
+    keys: BTreeMap<String, StoredKey>,
+}
+
+impl IcfKeyring {
+    pub fn platforms(&self) -> Vec<String> {
+        self.keys.keys().cloned().collect()
+    }
+
+    pub fn get(&self, platform_id: &str) -> Option<IcfKey> {
+        let stored = self.keys.get(&platform_id.trim().to_uppercase())?;
+        IcfKey::from_hex(&stored.key, &stored.iv).ok()
+    }
+
+    /// The stored ALLS key, else the built-in one. New ICFs are sealed with it.
+    pub fn default_key(&self) -> Result<IcfKey> {
+        self.get(DEFAULT_KEY_ID).or_else(IcfKey::builtin).ok_or_else(|| {
+            anyhow!("No ICF key for {DEFAULT_KEY_ID} in the keyring; import a key file or add the key first")
+        })
+    }
+
+    /// The ALLS key first, then every other stored one by platform id.
+    fn candidates(&self) -> impl Iterator<Item = IcfKey> + '_ {
+        self.default_key().ok().into_iter().chain(
+            self.keys
+                .iter()
+                .filter(|(id, _)| id.as_str() != DEFAULT_KEY_ID)
+                .filter_map(|(_, stored)| IcfKey::from_hex(&stored.key, &stored.iv).ok()),
+        )
+    }
+}
+
+fn keyring_path() -> PathBuf {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(KEYRING_FILE)
+}
+
+/// A key file's entries with upper-case ids, after checking every key.
+fn parse_key_file(raw: &str, origin: &Path) -> Result<BTreeMap<String, StoredKey>> {
+    let keys: BTreeMap<String, StoredKey> =
+        serde_json::from_str(raw).map_err(|e| anyhow!("Failed to parse {}: {e}", origin.display()))?;
+    keys.into_iter()
+        .map(|(id, stored)| {
+            let id = id.trim().to_uppercase();
+            if id.is_empty() {
+                return Err(anyhow!("{} has a key without a platform id", origin.display()));
+            }
+            IcfKey::from_hex(&stored.key, &stored.iv).map_err(|e| anyhow!("{id}: {e}"))?;
+            Ok((
+                id,
+                StoredKey {
+                    key: stored.key.trim().to_string(),
+                    iv: stored.iv.trim().to_string(),
+                },
+            ))
+        })
+        .collect()
+}
+
+fn write_keyring(path: &Path, keyring: &IcfKeyring) -> Result<()> {
+    let plain = serde_json::to_vec(keyring)?;
+    let body = sealed::seal(&plain, path, SEAL_ENTROPY).map_err(|e| anyhow!(e))?;
+    let tmp = path.with_extension("db.tmp");
+    fs::write(&tmp, [KEYRING_MAGIC, body.as_slice()].concat())?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+fn read_keyring(path: &Path) -> Result<IcfKeyring> {
+    let raw = match fs::read(path) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(IcfKeyring::default()),
+        Err(e) => return Err(anyhow!("Failed to read ICF keyring {}: {e}", path.display())),
+    };
+    let body = raw
+        .strip_prefix(KEYRING_MAGIC)
+        .ok_or_else(|| anyhow!("{} is not an ICF keyring", path.display()))?;
+    let plain = sealed::open(body, path, SEAL_ENTROPY)
+        .map_err(|e| anyhow!("{e}. Remove {} and import the keys again.", path.display()))?;
+    serde_json::from_slice(&plain).map_err(|e| anyhow!("Failed to parse ICF keyring: {e}"))
+}
+
+fn update_keyring(path: &Path, change: impl FnOnce(&mut IcfKeyring) -> Result<()>) -> Result<IcfKeyring> {
+    let mut keyring = read_keyring(path)?;
+    change(&mut keyring)?;
+    write_keyring(path, &keyring)?;
+    Ok(keyring)
+}
+
+fn set_key_in(path: &Path, platform_id: &str, key: &str, iv: &str) -> Result<IcfKeyring> {
+    let id = platform_id.trim().to_uppercase();
+    if id.is_empty() {
+        return Err(anyhow!("Platform id is empty"));
+    }
+    IcfKey::from_hex(key, iv)?;
+    update_keyring(path, |keyring| {
+        keyring.keys.insert(
+            id,
+            StoredKey {
+                key: key.trim().to_string(),
+                iv: iv.trim().to_string(),
+            },
+        );
+        Ok(())
+    })
+}
+
+fn remove_key_in(path: &Path, platform_id: &str) -> Result<IcfKeyring> {
+    let id = platform_id.trim().to_uppercase();
+    update_keyring(path, |keyring| {
+        keyring
+            .keys
+            .remove(&id)
+            .map(|_| ())
+            .ok_or_else(|| anyhow!("No ICF key stored for {id}"))
+    })
+}
+
+fn import_into(path: &Path, source: &Path, passphrase: Option<&str>) -> Result<IcfKeyring> {
+    let mut raw = fs::read_to_string(source).map_err(|e| anyhow!("Failed to read {}: {e}", source.display()))?;
+    if raw.trim_start().starts_with(SHARE_PREFIX) {
+        let passphrase =
+            passphrase.ok_or_else(|| anyhow!("{} is sealed; enter its passphrase", source.display()))?;
+        let plain = open_share(&raw, passphrase).map_err(|e| anyhow!("{}: {e}", source.display()))?;
+        raw = String::from_utf8(plain).map_err(|_| anyhow!("{} is not a key file", source.display()))?;
+    }
+    let imported = parse_key_file(&raw, source)?;
+    if imported.is_empty() {
+        return Err(anyhow!("{} holds no ICF keys", source.display()));
+    }
+    update_keyring(path, |keyring| {
+        keyring.keys.extend(imported);
+        Ok(())
+    })
+}
+
+fn export_from(path: &Path, dest: &Path, passphrase: &str) -> Result<usize> {
+    let keyring = read_keyring(path)?;
+    if keyring.keys.is_empty() {
+        return Err(anyhow!("The ICF keyring is empty"));
+    }
+    if let Some(dir) = dest.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+    let sealed = seal_share(&serde_json::to_vec(&keyring)?, passphrase).map_err(|e| anyhow!(e))?;
+    fs::write(dest, sealed)?;
+    Ok(keyring.keys.len())
+}
+
+pub fn load_icf_keyring() -> Result<IcfKeyring> {
+    read_keyring(&keyring_path())
+}
+
+pub fn set_icf_platform_key(platform_id: &str, key: &str, iv: &str) -> Result<IcfKeyring> {
+    set_key_in(&keyring_path(), platform_id, key, iv)
+}
+
+pub fn remove_icf_platform_key(platform_id: &str) -> Result<IcfKeyring> {
+    remove_key_in(&keyring_path(), platform_id)
+}
+
+/// Merges a key file, a JSON object of `{ "<platform id>": { "key", "iv" } }`, into the
+/// keyring. Files written by [`export_icf_keys`] are opened with `passphrase` first. Every key
+/// is checked before any is stored, and ids already in the keyring are replaced.
+pub fn import_icf_keys(source: &Path, passphrase: Option<&str>) -> Result<IcfKeyring> {
+    import_into(&keyring_path(), source, passphrase)
+}
+
+/// Writes the keyring to `dest` sealed under `passphrase`, e.g. to carry it to another
+/// machine, and returns how many keys it holds.
+pub fn export_icf_keys(dest: &Path, passphrase: &str) -> Result<usize> {
+    export_from(&keyring_path(), dest, passphrase)
+}
+
+/// Decodes an ICF sealed with `key` instead of a keyring one.
+pub fn decode_icf_with_key(data: &mut [u8], key: &IcfKey) -> Result<Vec<IcfData>> {
+    parse_icf(decrypt_icf(data, key.key, key.iv)?)
+}
+
+/// Decodes an ICF with the first key whose output passes the checksum, and returns that key
+/// so the ICF can be sealed the same way again.
+pub fn decode_icf_with_keyring(data: &mut [u8], keyring: &IcfKeyring) -> Result<(IcfKey, Vec<IcfData>)> {
+    let mut first_error = None;
+    for key in keyring.candidates() {
+        match decode_icf_with_key(data, &key) {
+            Ok(entries) => return Ok((key, entries)),
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    Err(first_error.unwrap_or_else(|| anyhow!("No ICF key to try; import a key file first")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{encrypt_icf, serialize_icf, IcfInnerData, Version};
+    use super::*;
+    use chrono::NaiveDateTime;
+    use tempfile::TempDir;
+
+    const KEY: &str = "00112233445566778899aabbccddeeff";
+    const IV: &str = "ffeeddccbbaa99887766554433221100";
+
+    fn entries() -> Vec<IcfData> {
+        let inner = |id: &str| IcfInnerData {
+            id: id.to_string(),
+            version: Version { major: 1, minor: 0, build: 0 },
+            required_system_version: Version { major: 1, minor: 0, build: 0 },
+            datetime: NaiveDateTime::default(),
+            is_prerelease: false,
+        };
+        vec![IcfData::System(inner("ACB")), IcfData::App(inner("SDEZ"))]
+    }
+
+    #[test]
+    fn decodes_with_stored_platform_keys() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(KEYRING_FILE);
+        let custom = IcfKey::from_hex(KEY, IV).unwrap();
+        let mut sealed = encrypt_icf(&serialize_icf(&entries()).unwrap(), custom.key, custom.iv).unwrap();
+
+        assert!(decode_icf_with_keyring(&mut sealed.clone(), &read_keyring(&path).unwrap()).is_err());
+        assert!(set_key_in(&path, "acb", "0011", IV).is_err());
+
+        let keyring = set_key_in(&path, "acb", KEY, IV).unwrap();
+        assert_eq!(keyring.platforms(), ["ACB"]);
+        assert!(!String::from_utf8_lossy(&fs::read(&path).unwrap()).contains(KEY));
+        let (key, decoded) = decode_icf_with_keyring(&mut sealed, &read_keyring(&path).unwrap()).unwrap();
+        assert_eq!((key, decoded), (custom, entries()));
+
+        assert!(remove_key_in(&path, "ACB").unwrap().platforms().is_empty());
+        assert!(remove_key_in(&path, "ACB").is_err());
+    }
+
+    #[test]
+    fn imports_and_exports_key_files() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(KEYRING_FILE);
+        assert!(read_keyring(&path).unwrap().platforms().is_empty());
+        set_key_in(&path, "aca", KEY, IV).unwrap();
+
+        let source = dir.path().join("keys.json");
+        fs::write(&source, format!(r#"{{"alls":{{"key":"{KEY}","iv":"{IV}"}},"acb":{{"key":"zz","iv":"{IV}"}}}}"#))
+            .unwrap();
+        assert!(import_into(&path, &source, None).is_err());
+        fs::write(&source, format!(r#"{{"alls":{{"key":"{KEY}","iv":"{IV}"}}}}"#)).unwrap();
+        let keyring = import_into(&path, &source, None).unwrap();
+        assert_eq!(keyring.platforms(), ["ACA", "ALLS"]);
+        assert_eq!(keyring.default_key().unwrap(), IcfKey::from_hex(KEY, IV).unwrap());
+
+        let exported = dir.path().join("out").join("keys.json");
+        assert!(export_from(&path, &exported, "short").is_err());
+        assert_eq!(export_from(&path, &exported, "correct horse").unwrap(), 2);
+        assert!(!fs::read_to_string(&exported).unwrap().contains(KEY));
+        let other = dir.path().join("other").join(KEYRING_FILE);
+        fs::create_dir_all(other.parent().unwrap()).unwrap();
+        assert!(import_into(&other, &exported, None).is_err());
+        assert!(import_into(&other, &exported, Some("wrong horse")).is_err());
+        let imported = import_into(&other, &exported, Some("correct horse")).unwrap();
+        assert_eq!(imported.platforms(), ["ACA", "ALLS"]);
+    }
+}
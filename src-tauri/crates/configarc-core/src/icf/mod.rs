@@ -1,9 +1,29 @@
+//! ICF files: the AES-encrypted index of app, option and patch versions an ALLS system
+//! boots from. [`decode_icf`] and [`serialize_icf`] round-trip them; [`encrypt_icf`] seals
+//! the result again. [`audit_icf`] checks one against the installed content, and
+//! [`icf_to_json`] and [`icf_from_json`] carry one as JSON. No key is compiled in by
+//! default: the ALLS key and those of platforms sealed under another one come from the
+//! keyring in [`keys`], or per call through [`decode_icf_with_key`]. Saves keep
+//! rotating copies through [`backups`].
+
+mod audit;
+pub mod backups;
 mod crypto;
+mod json;
+pub mod keys;
 mod models;
 mod parser;
 
-pub use crypto::{decrypt_icf, encrypt_icf, ICF_IV, ICF_KEY};
-pub use models::{IcfData, IcfInnerData, IcfOptionData, IcfPatchData, Version};
+pub use audit::{
+    audit_icf, insert_option_entries, parse_version, propose_option_entries, IcfAudit, IcfFinding, IcfFindingKind,
+    InstalledContent,
+};
+pub use crypto::{decrypt_icf, encrypt_icf};
+#[cfg(feature = "icf-builtin-key")]
+pub use crypto::{ICF_IV, ICF_KEY};
+pub use json::{icf_from_json, icf_to_json};
+pub use keys::{decode_icf_with_key, decode_icf_with_keyring, IcfKey, IcfKeyring};
+pub use models::{IcfData, IcfInnerData, IcfOptionData, IcfPatchData, IcfSummary, Version};
 pub use parser::{decode_icf_datetime, decode_icf_version};
 
 use anyhow::{anyhow, Result};
@@ -219,10 +239,28 @@ pub fn parse_icf(data: impl AsRef<[u8]>) -> Result<Vec<IcfData>> {
     Ok(entries)
 }
 
+/// Decodes an ICF sealed with the ALLS key from the keyring.
 pub fn decode_icf(data: &mut [u8]) -> Result<Vec<IcfData>> {
-    let decrypted = decrypt_icf(data, ICF_KEY, ICF_IV)?;
+    decode_icf_with_key(data, &keys::load_icf_keyring()?.default_key()?)
+}
 
-    parse_icf(decrypted)
+pub fn summarize_icf(entries: &[IcfData]) -> IcfSummary {
+    let mut summary = IcfSummary::default();
+    for entry in entries {
+        match entry {
+            IcfData::System(s) => summary.platform_id = Some(s.id.clone()),
+            IcfData::App(a) => {
+                summary.app_id = Some(a.id.clone());
+                summary.app_version = summary.app_version.max(Some(a.version));
+            }
+            IcfData::Patch(p) => {
+                summary.patch_count += 1;
+                summary.app_version = summary.app_version.max(Some(p.target_version));
+            }
+            IcfData::Option(_) => summary.option_count += 1,
+        }
+    }
+    summary
 }
 
 pub fn serialize_datetime(data: &mut Vec<u8>, datetime: NaiveDateTime) {
@@ -342,3 +380,52 @@ pub fn serialize_icf(data: &[IcfData]) -> Result<Vec<u8>> {
 
     Ok(icf)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(major: u16, minor: u8) -> Version {
+        Version { major, minor, build: 0 }
+    }
+
+    #[test]
+    fn summarizes_ids_newest_version_and_counts() {
+        let datetime = NaiveDateTime::default();
+        let inner = |id: &str, version| IcfInnerData {
+            id: id.to_string(),
+            version,
+            required_system_version: version,
+            datetime,
+            is_prerelease: false,
+        };
+        let entries = vec![
+            IcfData::System(inner("ACA", version(80, 54))),
+            IcfData::App(inner("SDEZ", version(1, 40))),
+            IcfData::Patch(IcfPatchData {
+                id: "SDEZ".to_string(),
+                sequence_number: 1,
+                source_version: version(1, 40),
+                source_datetime: datetime,
+                source_required_system_version: version(80, 54),
+                target_version: version(1, 45),
+                target_datetime: datetime,
+                target_required_system_version: version(80, 54),
+                is_prerelease: false,
+            }),
+            IcfData::Option(IcfOptionData {
+                app_id: "SDEZ".to_string(),
+                option_id: "A001".to_string(),
+                required_system_version: version(0, 0),
+                datetime,
+                is_prerelease: false,
+            }),
+        ];
+
+        let summary = summarize_icf(&entries);
+        assert_eq!(summary.app_id.as_deref(), Some("SDEZ"));
+        assert_eq!(summary.platform_id.as_deref(), Some("ACA"));
+        assert_eq!(summary.app_version, Some(version(1, 45)));
+        assert_eq!((summary.patch_count, summary.option_count), (1, 1));
+    }
+}
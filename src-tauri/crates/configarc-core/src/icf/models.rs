@@ -181,6 +181,17 @@ impl IcfData {
     }
 }
 
+/// What a library card shows for one ICF without shipping every entry: the ids from the
+/// System and App entries, the newest app version counting patches, and entry counts.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct IcfSummary {
+    pub app_id: Option<String>,
+    pub platform_id: Option<String>,
+    pub app_version: Option<Version>,
+    pub patch_count: usize,
+    pub option_count: usize,
+}
+
 fn empty_string() -> String {
     String::new()
 }
@@ -1,7 +1,58 @@
+//! Reusable backend of the ConfigArc launcher: segatools configs, the game library, VHD
+//! images, ICF files, encrypted containers and the privileged executor, without any UI.
+//!
+//! The modules below are the supported API and follow semver: [`config`], [`games`],
+//! [`vhd`], [`icf`], [`fsdecrypt`], [`privexec`], [`remote`], [`presets`], [`trusted`],
+//! [`archive`], [`backup`], [`scrub`], [`compression`], [`cancel`], [`gpu`], [`display`],
+//! [`serial`], [`platform`] and [`error`].
+//! Modules hidden from these docs hold launcher state and may change in any release.
+//!
+//! # Features
+//!
+//! All enabled by default:
+//!
+//! - `vhd`: VHD game configs, runtime deltas and mounting through the VirtDisk API.
+//! - `privexec`: the signed-request executor for privileged commands.
+//! - `icf`: ICF decoding and encryption.
+//! - `fsdecrypt`: decryption and extraction of app and option containers, building option
+//!   containers from a folder, and an encrypted local key store.
+//!
+//! Mounting and privileged execution only work on Windows; elsewhere those calls return an
+//! error, so a headless tool can still read configs and images on any platform.
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+#[doc(hidden)]
+pub mod api_tokens;
+pub mod archive;
+pub mod backup;
+pub mod cancel;
+#[doc(hidden)]
+pub mod changelog;
+pub mod compression;
 pub mod config;
+pub mod display;
 pub mod error;
+#[cfg(feature = "fsdecrypt")]
+#[cfg_attr(docsrs, doc(cfg(feature = "fsdecrypt")))]
+pub mod fsdecrypt;
 pub mod games;
+pub mod gpu;
+#[cfg(feature = "icf")]
+#[cfg_attr(docsrs, doc(cfg(feature = "icf")))]
+pub mod icf;
+#[doc(hidden)]
+pub mod notifications;
+pub mod platform;
+pub mod presets;
+#[cfg(feature = "privexec")]
+#[cfg_attr(docsrs, doc(cfg(feature = "privexec")))]
 pub mod privexec;
 pub mod remote;
+pub mod scrub;
+pub mod serial;
+#[cfg(any(feature = "icf", feature = "fsdecrypt", feature = "privexec"))]
+mod sealed;
 pub mod trusted;
+#[cfg(feature = "vhd")]
+#[cfg_attr(docsrs, doc(cfg(feature = "vhd")))]
 pub mod vhd;
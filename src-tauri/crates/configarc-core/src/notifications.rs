@@ -0,0 +1,211 @@
+use crate::error::ConfigError;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+const NOTIFICATIONS_FILE_NAME: &str = "configarc_notifications.json";
+/// Past this, the oldest read notifications are dropped first; unread ones only go once
+/// nothing read is left.
+const MAX_NOTIFICATIONS: usize = 200;
+
+/// Serializes read-modify-write cycles between the UI and background subsystems.
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+static NEXT_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationSeverity {
+    Info,
+    Warning,
+    Error,
+}
+
+fn default_occurrences() -> u32 {
+    1
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Notification {
+    pub id: String,
+    /// Subsystem that posted it, e.g. "updater", "trust", "backup", "remote_config".
+    pub source: String,
+    pub severity: NotificationSeverity,
+    pub title: String,
+    pub message: String,
+    pub created_at: String,
+    #[serde(default)]
+    pub read: bool,
+    /// Posting again with the same key refreshes the unread notification instead of stacking
+    /// a new one, so a check that fails every hour shows up once.
+    #[serde(default)]
+    pub dedupe_key: Option<String>,
+    #[serde(default = "default_occurrences")]
+    pub occurrences: u32,
+    #[serde(default)]
+    pub details: Option<Value>,
+}
+
+impl Notification {
+    pub fn new(
+        source: &str,
+        severity: NotificationSeverity,
+        title: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        let now = Utc::now();
+        Self {
+            id: format!(
+                "{}-{}",
+                now.timestamp_millis(),
+                NEXT_SEQUENCE.fetch_add(1, Ordering::Relaxed)
+            ),
+            source: source.to_string(),
+            severity,
+            title: title.into(),
+            message: message.into(),
+            created_at: now.to_rfc3339(),
+            read: false,
+            dedupe_key: None,
+            occurrences: 1,
+            details: None,
+        }
+    }
+
+    pub fn with_dedupe_key(mut self, key: impl Into<String>) -> Self {
+        self.dedupe_key = Some(key.into());
+        self
+    }
+
+    pub fn with_details(mut self, details: Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+}
+
+fn notifications_path() -> PathBuf {
+    Path::new(".").join(NOTIFICATIONS_FILE_NAME)
+}
+
+fn read_store(path: &Path) -> Result<Vec<Notification>, ConfigError> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let data = fs::read_to_string(path)?;
+    if data.trim().is_empty() {
+        return Ok(vec![]);
+    }
+    Ok(serde_json::from_str(&data)?)
+}
+
+fn write_store(path: &Path, notifications: &[Notification]) -> Result<(), ConfigError> {
+    let json = serde_json::to_string_pretty(notifications)?;
+    fs::write(path, json)?;
+    Ok(())
+}
+
+fn trim_store(notifications: &mut Vec<Notification>) {
+    while notifications.len() > MAX_NOTIFICATIONS {
+        let index = notifications.iter().position(|n| n.read).unwrap_or(0);
+        notifications.remove(index);
+    }
+}
+
+fn post_at(path: &Path, notification: Notification) -> Result<Notification, ConfigError> {
+    let mut notifications = read_store(path)?;
+    let existing = notification.dedupe_key.as_ref().and_then(|key| {
+        notifications
+            .iter()
+            .position(|n| !n.read && n.dedupe_key.as_ref() == Some(key))
+    });
+    let posted = match existing {
+        Some(index) => {
+            let mut merged = notifications.remove(index);
+            merged.severity = notification.severity;
+            merged.title = notification.title;
+            merged.message = notification.message;
+            merged.details = notification.details;
+            merged.created_at = notification.created_at;
+            merged.occurrences += 1;
+            merged
+        }
+        None => notification,
+    };
+    notifications.push(posted.clone());
+    trim_store(&mut notifications);
+    write_store(path, &notifications)?;
+    Ok(posted)
+}
+
+fn mark_read_at(path: &Path, ids: Option<&[String]>) -> Result<usize, ConfigError> {
+    let mut notifications = read_store(path)?;
+    let mut changed = 0;
+    for notification in notifications.iter_mut().filter(|n| !n.read) {
+        if ids.is_none_or(|ids| ids.contains(&notification.id)) {
+            notification.read = true;
+            changed += 1;
+        }
+    }
+    if changed > 0 {
+        write_store(path, &notifications)?;
+    }
+    Ok(changed)
+}
+
+pub fn post_notification(notification: Notification) -> Result<Notification, ConfigError> {
+    let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    post_at(&notifications_path(), notification)
+}
+
+/// Posting must never fail the operation that reports something, so errors are dropped here.
+pub fn notify(notification: Notification) -> Option<Notification> {
+    post_notification(notification).ok()
+}
+
+/// Newest first.
+pub fn list_notifications(unread_only: bool) -> Result<Vec<Notification>, ConfigError> {
+    let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut notifications = read_store(&notifications_path())?;
+    if unread_only {
+        notifications.retain(|n| !n.read);
+    }
+    notifications.reverse();
+    Ok(notifications)
+}
+
+/// Marks the given notifications read, or every one with `None`. Returns how many changed.
+pub fn mark_notifications_read(ids: Option<&[String]>) -> Result<usize, ConfigError> {
+    let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    mark_read_at(&notifications_path(), ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dedupes_unread_posts_and_marks_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(NOTIFICATIONS_FILE_NAME);
+        let drift = || {
+            Notification::new("trust", NotificationSeverity::Warning, "Segatools changed", "amdaemon.dll")
+                .with_dedupe_key("trust:SDEZ")
+        };
+        let first = post_at(&path, drift()).unwrap();
+        let second = post_at(&path, drift()).unwrap();
+        assert_eq!(first.id, second.id);
+        assert_eq!(second.occurrences, 2);
+
+        post_at(&path, Notification::new("remote_config", NotificationSeverity::Info, "Synced", "")).unwrap();
+        assert_eq!(mark_read_at(&path, Some(std::slice::from_ref(&first.id))).unwrap(), 1);
+        assert_eq!(read_store(&path).unwrap().iter().filter(|n| !n.read).count(), 1);
+
+        // Once read, the same key starts a fresh notification.
+        assert_ne!(post_at(&path, drift()).unwrap().id, first.id);
+        assert_eq!(mark_read_at(&path, None).unwrap(), 2);
+    }
+}
@@ -0,0 +1,620 @@
+//! What the host can do without PowerShell, and native fallbacks for when it can't.
+//!
+//! PowerShell can be missing (Wine), blocked outright (AppLocker) or stuck in Constrained
+//! Language Mode, which forbids `Add-Type` and with it the Windows Forms dialogs. Process
+//! checks always go through Toolhelp32, and dialogs fall back to the common dialogs from
+//! comdlg32/shell32. [`capabilities`] reports the rest so the UI can hide what won't work.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+#[cfg(target_os = "windows")]
+use std::ffi::c_void;
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+#[cfg(target_os = "windows")]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+const POWERSHELL_PROBE: &str = "$PSVersionTable.PSVersion.Major; $ExecutionContext.SessionState.LanguageMode";
+/// Large enough for a few hundred selected files in one multi-select dialog.
+#[cfg(target_os = "windows")]
+const OPEN_FILE_BUFFER_LEN: usize = 64 * 1024;
+#[cfg(target_os = "windows")]
+const MAX_PATH: usize = 260;
+#[cfg(target_os = "windows")]
+const TH32CS_SNAPPROCESS: u32 = 0x0000_0002;
+#[cfg(target_os = "windows")]
+const INVALID_HANDLE_VALUE: isize = -1;
+#[cfg(target_os = "windows")]
+const OFN_NOCHANGEDIR: u32 = 0x0000_0008;
+#[cfg(target_os = "windows")]
+const OFN_ALLOWMULTISELECT: u32 = 0x0000_0200;
+#[cfg(target_os = "windows")]
+const OFN_PATHMUSTEXIST: u32 = 0x0000_0800;
+#[cfg(target_os = "windows")]
+const OFN_FILEMUSTEXIST: u32 = 0x0000_1000;
+#[cfg(target_os = "windows")]
+const OFN_EXPLORER: u32 = 0x0008_0000;
+#[cfg(target_os = "windows")]
+const BIF_RETURNONLYFSDIRS: u32 = 0x0000_0001;
+#[cfg(target_os = "windows")]
+const BIF_NEWDIALOGSTYLE: u32 = 0x0000_0040;
+#[cfg(target_os = "windows")]
+const COINIT_APARTMENTTHREADED: u32 = 0x2;
+
+/// Toolhelp process entry, shared with the game sandbox so the two declarations of
+/// `Process32FirstW` agree.
+#[cfg(target_os = "windows")]
+#[repr(C)]
+pub(crate) struct ProcessEntry32W {
+    pub(crate) size: u32,
+    pub(crate) usage: u32,
+    pub(crate) process_id: u32,
+    pub(crate) default_heap_id: usize,
+    pub(crate) module_id: u32,
+    pub(crate) threads: u32,
+    pub(crate) parent_process_id: u32,
+    pub(crate) priority_class_base: i32,
+    pub(crate) flags: u32,
+    pub(crate) exe_file: [u16; MAX_PATH],
+}
+
+#[cfg(target_os = "windows")]
+#[repr(C)]
+struct OpenFileNameW {
+    struct_size: u32,
+    owner: *mut c_void,
+    instance: *mut c_void,
+    filter: *const u16,
+    custom_filter: *mut u16,
+    max_custom_filter: u32,
+    filter_index: u32,
+    file: *mut u16,
+    max_file: u32,
+    file_title: *mut u16,
+    max_file_title: u32,
+    initial_dir: *const u16,
+    title: *const u16,
+    flags: u32,
+    file_offset: u16,
+    file_extension: u16,
+    default_ext: *const u16,
+    cust_data: isize,
+    hook: *mut c_void,
+    template_name: *const u16,
+    reserved_ptr: *mut c_void,
+    reserved: u32,
+    flags_ex: u32,
+}
+
+#[cfg(target_os = "windows")]
+#[repr(C)]
+struct BrowseInfoW {
+    owner: *mut c_void,
+    root: *const c_void,
+    display_name: *mut u16,
+    title: *const u16,
+    flags: u32,
+    callback: *mut c_void,
+    param: isize,
+    image: i32,
+}
+
+#[cfg(target_os = "windows")]
+#[link(name = "kernel32")]
+extern "system" {
+    fn GetModuleHandleW(module_name: *const u16) -> *mut c_void;
+    fn GetProcAddress(module: *mut c_void, proc_name: *const std::ffi::c_char) -> *mut c_void;
+    fn CreateToolhelp32Snapshot(flags: u32, process_id: u32) -> *mut c_void;
+    pub(crate) fn Process32FirstW(snapshot: *mut c_void, entry: *mut ProcessEntry32W) -> i32;
+    pub(crate) fn Process32NextW(snapshot: *mut c_void, entry: *mut ProcessEntry32W) -> i32;
+    fn CloseHandle(handle: *mut c_void) -> i32;
+    fn GetDiskFreeSpaceExW(
+        directory_name: *const u16,
+        free_bytes_available: *mut u64,
+        total_bytes: *mut u64,
+        total_free_bytes: *mut u64,
+    ) -> i32;
+}
+
+#[cfg(target_os = "windows")]
+#[link(name = "comdlg32")]
+extern "system" {
+    fn GetOpenFileNameW(params: *mut OpenFileNameW) -> i32;
+}
+
+#[cfg(target_os = "windows")]
+#[link(name = "shell32")]
+extern "system" {
+    fn SHBrowseForFolderW(info: *const BrowseInfoW) -> *mut c_void;
+    fn SHGetPathFromIDListW(id_list: *const c_void, path: *mut u16) -> i32;
+}
+
+#[cfg(target_os = "windows")]
+#[link(name = "ole32")]
+extern "system" {
+    fn CoInitializeEx(reserved: *mut c_void, co_init: u32) -> i32;
+    fn CoUninitialize();
+    fn CoTaskMemFree(ptr: *mut c_void);
+}
+
+static WINE: OnceLock<bool> = OnceLock::new();
+static POWERSHELL: OnceLock<PowerShellSupport> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerShellSupport {
+    /// Not installed, blocked by policy, or a stub that doesn't run scripts.
+    Unavailable,
+    /// Constrained Language Mode: cmdlets run, but `Add-Type` and COM objects don't.
+    Constrained,
+    Full,
+}
+
+impl PowerShellSupport {
+    /// Whether plain cmdlet scripts (`Get-Process`, `Get-BitLockerVolume`, ...) can run.
+    pub fn runs_cmdlets(self) -> bool {
+        self != PowerShellSupport::Unavailable
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DialogBackend {
+    /// Windows Forms dialogs through PowerShell.
+    #[serde(rename = "powershell")]
+    PowerShell,
+    /// Common dialogs from comdlg32/shell32.
+    Native,
+    None,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Capability {
+    pub supported: bool,
+    /// Why the feature is off, for a tooltip or log line.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl Capability {
+    fn supported() -> Self {
+        Self {
+            supported: true,
+            reason: None,
+        }
+    }
+
+    fn unsupported(reason: impl Into<String>) -> Self {
+        Self {
+            supported: false,
+            reason: Some(reason.into()),
+        }
+    }
+
+    pub(crate) fn from_blocker(blocker: Option<String>) -> Self {
+        blocker.map(Self::unsupported).unwrap_or_else(Self::supported)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlatformCapabilities {
+    pub wine: bool,
+    pub powershell: PowerShellSupport,
+    pub dialogs: DialogBackend,
+    pub process_checks: Capability,
+    pub vhd_mount: Capability,
+    pub bitlocker_unlock: Capability,
+    pub gpu_detection: Capability,
+    pub crypto_acceleration: CryptoAcceleration,
+}
+
+/// CPU instructions behind the AES and CRC32 work of ICF and container decryption. `aes` and
+/// `crc32fast` detect them at runtime and fall back to portable code, which is several times
+/// slower on a large container, so this explains throughput rather than gating anything.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CryptoAcceleration {
+    /// AES-NI on x86, the AES instructions of ARMv8 on aarch64.
+    pub aes: bool,
+    /// PCLMULQDQ with SSE4.1 on x86, the CRC32 instructions of ARMv8 on aarch64.
+    pub crc32: bool,
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+pub fn crypto_acceleration() -> CryptoAcceleration {
+    CryptoAcceleration {
+        aes: std::arch::is_x86_feature_detected!("aes"),
+        crc32: std::arch::is_x86_feature_detected!("pclmulqdq") && std::arch::is_x86_feature_detected!("sse4.1"),
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+pub fn crypto_acceleration() -> CryptoAcceleration {
+    CryptoAcceleration {
+        aes: std::arch::is_aarch64_feature_detected!("aes"),
+        crc32: std::arch::is_aarch64_feature_detected!("crc"),
+    }
+}
+
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")))]
+pub fn crypto_acceleration() -> CryptoAcceleration {
+    CryptoAcceleration { aes: false, crc32: false }
+}
+
+/// A filter row of a file dialog, e.g. `("Container files", "*.app;*.opt")`.
+pub type FileFilter<'a> = (&'a str, &'a str);
+
+/// Whether this process runs under Wine, detected by the `wine_get_version` export Wine's
+/// ntdll carries and Windows' doesn't.
+pub fn is_wine() -> bool {
+    *WINE.get_or_init(detect_wine)
+}
+
+#[cfg(target_os = "windows")]
+fn detect_wine() -> bool {
+    let ntdll = wide("ntdll.dll");
+    unsafe {
+        let module = GetModuleHandleW(ntdll.as_ptr());
+        !module.is_null() && !GetProcAddress(module, c"wine_get_version".as_ptr()).is_null()
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn detect_wine() -> bool {
+    false
+}
+
+/// How far PowerShell works here. Probed once per process, since the answer only changes
+/// with machine policy.
+pub fn powershell_support() -> PowerShellSupport {
+    *POWERSHELL.get_or_init(probe_powershell)
+}
+
+fn probe_powershell() -> PowerShellSupport {
+    let mut cmd = Command::new("powershell");
+    cmd.args(["-NoProfile", "-NonInteractive", "-Command", POWERSHELL_PROBE]);
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(CREATE_NO_WINDOW);
+    match cmd.output() {
+        Ok(output) if output.status.success() => parse_powershell_probe(&String::from_utf8_lossy(&output.stdout)),
+        _ => PowerShellSupport::Unavailable,
+    }
+}
+
+fn parse_powershell_probe(stdout: &str) -> PowerShellSupport {
+    let mut lines = stdout.lines().map(str::trim).filter(|line| !line.is_empty());
+    if lines.next().and_then(|major| major.parse::<u32>().ok()).is_none() {
+        return PowerShellSupport::Unavailable;
+    }
+    match lines.next() {
+        Some(mode) if mode.eq_ignore_ascii_case("FullLanguage") => PowerShellSupport::Full,
+        _ => PowerShellSupport::Constrained,
+    }
+}
+
+/// Backend [`pick_folder`] and [`pick_files`] will use.
+pub fn dialog_backend() -> DialogBackend {
+    if powershell_support() == PowerShellSupport::Full {
+        DialogBackend::PowerShell
+    } else if cfg!(target_os = "windows") {
+        DialogBackend::Native
+    } else {
+        DialogBackend::None
+    }
+}
+
+/// Why VHDs can't be mounted here, or `None` when they can.
+pub fn vhd_mount_blocker() -> Option<String> {
+    if !cfg!(feature = "vhd") {
+        return Some("Built without VHD support".to_string());
+    }
+    if !cfg!(target_os = "windows") {
+        return Some("VHD mounting is only supported on Windows".to_string());
+    }
+    if is_wine() {
+        return Some("Wine does not implement the VirtDisk API needed to attach VHDs".to_string());
+    }
+    #[cfg(feature = "vhd")]
+    if !crate::vhd::is_running_as_admin() && !powershell_support().runs_cmdlets() {
+        return Some(
+            "PowerShell is unavailable, so the elevated mount helper cannot run; start ConfigArc as administrator"
+                .to_string(),
+        );
+    }
+    None
+}
+
+pub fn capabilities() -> PlatformCapabilities {
+    let wine = is_wine();
+    let powershell = powershell_support();
+    let cmdlet_blocker = if wine {
+        Some("Not available under Wine".to_string())
+    } else if !powershell.runs_cmdlets() {
+        Some("PowerShell is unavailable or blocked".to_string())
+    } else {
+        None
+    };
+    PlatformCapabilities {
+        wine,
+        powershell,
+        dialogs: dialog_backend(),
+        process_checks: if cfg!(target_os = "windows") {
+            Capability::supported()
+        } else {
+            Capability::unsupported("Process checks are only supported on Windows")
+        },
+        vhd_mount: Capability::from_blocker(vhd_mount_blocker()),
+        bitlocker_unlock: Capability::from_blocker(cmdlet_blocker.clone()),
+        gpu_detection: Capability::from_blocker(cmdlet_blocker),
+        crypto_acceleration: crypto_acceleration(),
+    }
+}
+
+/// Whether a process named `name` is running. Accepts the image name with or without `.exe`,
+/// matching case-insensitively like `Get-Process -Name`.
+#[cfg(target_os = "windows")]
+pub fn is_process_running(name: &str) -> Result<bool, String> {
+    let snapshot = unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) };
+    if snapshot as isize == INVALID_HANDLE_VALUE {
+        return Err(format!("Failed to enumerate processes: {}", std::io::Error::last_os_error()));
+    }
+    let mut entry = ProcessEntry32W {
+        size: std::mem::size_of::<ProcessEntry32W>() as u32,
+        usage: 0,
+        process_id: 0,
+        default_heap_id: 0,
+        module_id: 0,
+        threads: 0,
+        parent_process_id: 0,
+        priority_class_base: 0,
+        flags: 0,
+        exe_file: [0; MAX_PATH],
+    };
+    let mut found = false;
+    unsafe {
+        let mut more = Process32FirstW(snapshot, &mut entry) != 0;
+        while more {
+            let len = entry.exe_file.iter().position(|&c| c == 0).unwrap_or(MAX_PATH);
+            if image_name_matches(&String::from_utf16_lossy(&entry.exe_file[..len]), name) {
+                found = true;
+                break;
+            }
+            more = Process32NextW(snapshot, &mut entry) != 0;
+        }
+        CloseHandle(snapshot);
+    }
+    Ok(found)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn is_process_running(_name: &str) -> Result<bool, String> {
+    Err("Process checks are only supported on Windows".to_string())
+}
+
+/// Bytes available to the current user on the volume holding `dir`; `None` when unknown.
+#[cfg(target_os = "windows")]
+pub fn free_space_bytes(dir: &Path) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+    let wide: Vec<u16> = dir.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+    let mut free: u64 = 0;
+    let ok = unsafe { GetDiskFreeSpaceExW(wide.as_ptr(), &mut free, std::ptr::null_mut(), std::ptr::null_mut()) };
+    (ok != 0).then_some(free)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn free_space_bytes(_dir: &Path) -> Option<u64> {
+    None
+}
+
+#[cfg(any(target_os = "windows", test))]
+fn image_name_matches(image: &str, name: &str) -> bool {
+    let strip = |value: &str| {
+        let lower = value.trim().to_ascii_lowercase();
+        lower.strip_suffix(".exe").map(str::to_string).unwrap_or(lower)
+    };
+    strip(image) == strip(name)
+}
+
+/// Asks for a folder. `Ok(None)` means the user cancelled.
+pub fn pick_folder() -> Result<Option<PathBuf>, String> {
+    match dialog_backend() {
+        DialogBackend::PowerShell => {
+            let script = "Add-Type -AssemblyName System.Windows.Forms; $f = New-Object System.Windows.Forms.FolderBrowserDialog; if ($f.ShowDialog() -eq 'OK') { Write-Output $f.SelectedPath }";
+            let selected = run_dialog_script(script)?;
+            Ok(selected.into_iter().next())
+        }
+        DialogBackend::Native => native_pick_folder(),
+        DialogBackend::None => Err("No folder dialog is available on this platform".to_string()),
+    }
+}
+
+/// Asks for one or more existing files. An empty list means the user cancelled.
+pub fn pick_files(filters: &[FileFilter]) -> Result<Vec<PathBuf>, String> {
+    match dialog_backend() {
+        DialogBackend::PowerShell => {
+            let filter = filters
+                .iter()
+                .map(|(label, pattern)| format!("{}|{}", label, pattern))
+                .collect::<Vec<_>>()
+                .join("|")
+                .replace('\'', "''");
+            let script = format!(
+                "Add-Type -AssemblyName System.Windows.Forms; $f = New-Object System.Windows.Forms.OpenFileDialog; $f.Multiselect = $true; $f.Filter = '{}'; if ($f.ShowDialog() -eq 'OK') {{ $f.FileNames }}",
+                filter
+            );
+            run_dialog_script(&script)
+        }
+        DialogBackend::Native => native_pick_files(filters),
+        DialogBackend::None => Err("No file dialog is available on this platform".to_string()),
+    }
+}
+
+fn run_dialog_script(script: &str) -> Result<Vec<PathBuf>, String> {
+    let mut cmd = Command::new("powershell");
+    cmd.args(["-NoProfile", "-Command", script]);
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(CREATE_NO_WINDOW);
+    let output = cmd.output().map_err(|e| e.to_string())?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect())
+}
+
+#[cfg(target_os = "windows")]
+fn wide(value: &str) -> Vec<u16> {
+    value.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+/// COM stays initialised for the dialog's lifetime; `SHBrowseForFolderW` needs an STA.
+#[cfg(target_os = "windows")]
+struct ComApartment(bool);
+
+#[cfg(target_os = "windows")]
+impl ComApartment {
+    fn enter() -> Self {
+        let hr = unsafe { CoInitializeEx(std::ptr::null_mut(), COINIT_APARTMENTTHREADED) };
+        ComApartment(hr >= 0)
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for ComApartment {
+    fn drop(&mut self) {
+        if self.0 {
+            unsafe { CoUninitialize() };
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn native_pick_folder() -> Result<Option<PathBuf>, String> {
+    let _com = ComApartment::enter();
+    let mut display_name = [0u16; MAX_PATH];
+    let title = wide("Select a folder");
+    let info = BrowseInfoW {
+        owner: std::ptr::null_mut(),
+        root: std::ptr::null(),
+        display_name: display_name.as_mut_ptr(),
+        title: title.as_ptr(),
+        flags: BIF_RETURNONLYFSDIRS | BIF_NEWDIALOGSTYLE,
+        callback: std::ptr::null_mut(),
+        param: 0,
+        image: 0,
+    };
+    unsafe {
+        let id_list = SHBrowseForFolderW(&info);
+        if id_list.is_null() {
+            return Ok(None);
+        }
+        let mut path = [0u16; MAX_PATH];
+        let ok = SHGetPathFromIDListW(id_list, path.as_mut_ptr()) != 0;
+        CoTaskMemFree(id_list);
+        if !ok {
+            return Err("The selected item is not a file system folder".to_string());
+        }
+        let len = path.iter().position(|&c| c == 0).unwrap_or(MAX_PATH);
+        Ok(Some(PathBuf::from(String::from_utf16_lossy(&path[..len]))))
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn native_pick_folder() -> Result<Option<PathBuf>, String> {
+    Err("No folder dialog is available on this platform".to_string())
+}
+
+#[cfg(target_os = "windows")]
+fn native_pick_files(filters: &[FileFilter]) -> Result<Vec<PathBuf>, String> {
+    let _com = ComApartment::enter();
+    let filter: Vec<u16> = filters
+        .iter()
+        .flat_map(|(label, pattern)| [*label, *pattern])
+        .flat_map(|part| part.encode_utf16().chain(std::iter::once(0)))
+        .chain(std::iter::once(0))
+        .collect();
+    let mut buffer = vec![0u16; OPEN_FILE_BUFFER_LEN];
+    let mut params = OpenFileNameW {
+        struct_size: std::mem::size_of::<OpenFileNameW>() as u32,
+        owner: std::ptr::null_mut(),
+        instance: std::ptr::null_mut(),
+        filter: if filters.is_empty() { std::ptr::null() } else { filter.as_ptr() },
+        custom_filter: std::ptr::null_mut(),
+        max_custom_filter: 0,
+        filter_index: 1,
+        file: buffer.as_mut_ptr(),
+        max_file: buffer.len() as u32,
+        file_title: std::ptr::null_mut(),
+        max_file_title: 0,
+        initial_dir: std::ptr::null(),
+        title: std::ptr::null(),
+        flags: OFN_EXPLORER | OFN_ALLOWMULTISELECT | OFN_FILEMUSTEXIST | OFN_PATHMUSTEXIST | OFN_NOCHANGEDIR,
+        file_offset: 0,
+        file_extension: 0,
+        default_ext: std::ptr::null(),
+        cust_data: 0,
+        hook: std::ptr::null_mut(),
+        template_name: std::ptr::null(),
+        reserved_ptr: std::ptr::null_mut(),
+        reserved: 0,
+        flags_ex: 0,
+    };
+    if unsafe { GetOpenFileNameW(&mut params) } == 0 {
+        // Cancelled, or the buffer was too small; both leave nothing usable.
+        return Ok(Vec::new());
+    }
+    Ok(split_open_file_buffer(&buffer))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn native_pick_files(_filters: &[FileFilter]) -> Result<Vec<PathBuf>, String> {
+    Err("No file dialog is available on this platform".to_string())
+}
+
+/// Decodes a multi-select `GetOpenFileNameW` result: a single full path, or the folder
+/// followed by file names, each NUL-terminated with an extra NUL at the end.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn split_open_file_buffer(buffer: &[u16]) -> Vec<PathBuf> {
+    let parts: Vec<String> = buffer
+        .split(|&c| c == 0)
+        .take_while(|part| !part.is_empty())
+        .map(String::from_utf16_lossy)
+        .collect();
+    match parts.split_first() {
+        None => Vec::new(),
+        Some((single, [])) => vec![PathBuf::from(single)],
+        Some((dir, names)) => names.iter().map(|name| PathBuf::from(dir).join(name)).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_powershell_probe_and_dialog_buffers() {
+        assert_eq!(parse_powershell_probe("5\r\nFullLanguage\r\n"), PowerShellSupport::Full);
+        assert_eq!(parse_powershell_probe("5\nConstrainedLanguage\n"), PowerShellSupport::Constrained);
+        assert_eq!(parse_powershell_probe("wine: powershell stub\n"), PowerShellSupport::Unavailable);
+        assert_eq!(parse_powershell_probe(""), PowerShellSupport::Unavailable);
+
+        assert!(image_name_matches("Sinmai.exe", "sinmai"));
+        assert!(image_name_matches("amdaemon.exe", "AMDaemon.exe"));
+        assert!(!image_name_matches("amdaemon_x64.exe", "amdaemon"));
+
+        let encode = |s: &str| s.encode_utf16().collect::<Vec<u16>>();
+        let single = [encode("C:\\games\\a.app"), vec![0, 0]].concat();
+        assert_eq!(split_open_file_buffer(&single), vec![PathBuf::from("C:\\games\\a.app")]);
+        let multi = [encode("C:\\games"), vec![0], encode("a.app"), vec![0], encode("b.opt"), vec![0, 0]].concat();
+        assert_eq!(
+            split_open_file_buffer(&multi),
+            vec![PathBuf::from("C:\\games").join("a.app"), PathBuf::from("C:\\games").join("b.opt")]
+        );
+        assert!(split_open_file_buffer(&[0, 0]).is_empty());
+    }
+}
@@ -0,0 +1,490 @@
+use crate::config::segatools::{DnsConfig, SegatoolsConfig};
+use crate::trusted::{fetch_signed_document, TrustedError};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+const PRESETS_FEED_PATH: &str = "public/configarc/presets/index.json";
+const PRESETS_CACHE_FILE: &str = "presets_feed.json";
+const NETWORK_PRESETS_FEED_PATH: &str = "public/configarc/presets/network.json";
+const NETWORK_PRESETS_CACHE_FILE: &str = "network_presets_feed.json";
+const BUNDLED_NETWORK_PRESETS: &str = include_str!("network_presets.json");
+
+#[derive(Debug, Error)]
+pub enum PresetError {
+    #[error("{0}")]
+    Trusted(#[from] TrustedError),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Parse error: {0}")]
+    Parse(String),
+    #[error("Preset not found: {0}")]
+    NotFound(String),
+    #[error("Preset rejected: {0}")]
+    Rejected(String),
+}
+
+impl From<serde_json::Error> for PresetError {
+    fn from(err: serde_json::Error) -> Self {
+        PresetError::Parse(err.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PresetKind {
+    /// A full shareable profile for the title.
+    Profile,
+    /// Only the `[dns]` and `[netenv]` sections are taken from the preset.
+    Network,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetEntry {
+    pub id: String,
+    /// Canonical game key ("chunithm", "sinmai", "ongeki").
+    pub game: String,
+    pub kind: PresetKind,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub segatools: SegatoolsConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetFeed {
+    #[serde(default)]
+    pub schema_version: u32,
+    #[serde(default)]
+    pub generated_at: String,
+    #[serde(default)]
+    pub entries: Vec<PresetEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetFeedCache {
+    pub fetched_at: Option<String>,
+    pub feed: Option<PresetFeed>,
+}
+
+pub struct PresetFeedManager {
+    cache_path: PathBuf,
+}
+
+impl PresetFeedManager {
+    pub fn new(root: impl Into<PathBuf>) -> Result<Self, PresetError> {
+        let root = root.into();
+        if !root.exists() {
+            fs::create_dir_all(&root)?;
+        }
+        Ok(Self {
+            cache_path: root.join(PRESETS_CACHE_FILE),
+        })
+    }
+
+    pub fn read_cache(&self) -> PresetFeedCache {
+        fs::read_to_string(&self.cache_path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default()
+    }
+
+    /// Downloads the signed index, verifies it and replaces the local cache.
+    pub fn refresh(&self, mirrors: &[String]) -> Result<PresetFeedCache, PresetError> {
+        let bytes = fetch_signed_document(mirrors, PRESETS_FEED_PATH)?;
+        let feed: PresetFeed = serde_json::from_slice(&bytes)?;
+        let cache = PresetFeedCache {
+            fetched_at: Some(Utc::now().to_rfc3339()),
+            feed: Some(feed),
+        };
+        write_cache(&self.cache_path, &cache)?;
+        Ok(cache)
+    }
+
+    pub fn browse(&self, game: Option<&str>) -> Vec<PresetEntry> {
+        let entries = self.read_cache().feed.map(|f| f.entries).unwrap_or_default();
+        match game {
+            Some(game) => entries
+                .into_iter()
+                .filter(|entry| entry.game.eq_ignore_ascii_case(game))
+                .collect(),
+            None => entries,
+        }
+    }
+
+    pub fn find(&self, id: &str) -> Result<PresetEntry, PresetError> {
+        self.browse(None)
+            .into_iter()
+            .find(|entry| entry.id == id)
+            .ok_or_else(|| PresetError::NotFound(id.to_string()))
+    }
+}
+
+fn write_cache<T: Serialize>(path: &Path, cache: &T) -> Result<(), PresetError> {
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_vec_pretty(cache)?)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+fn is_machine_path(value: &str) -> bool {
+    let trimmed = value.trim();
+    let bytes = trimmed.as_bytes();
+    trimmed.starts_with("\\\\") || (bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':')
+}
+
+/// Prepares a feed entry for local use. Entries carrying a keychip ID are refused outright,
+/// since the author leaked an identifier; machine-specific absolute paths are cleared.
+pub fn redact_preset(entry: &PresetEntry) -> Result<SegatoolsConfig, PresetError> {
+    let mut cfg = entry.segatools.clone();
+    if !cfg.keychip.id.trim().is_empty() {
+        return Err(PresetError::Rejected(format!(
+            "{} contains a keychip ID",
+            entry.name
+        )));
+    }
+    for path in [
+        &mut cfg.vfs.amfs,
+        &mut cfg.vfs.appdata,
+        &mut cfg.vfs.option,
+        &mut cfg.aime.aime_path,
+        &mut cfg.aime.felica_path,
+        &mut cfg.aime.authdata_path,
+    ] {
+        if is_machine_path(path) {
+            path.clear();
+        }
+    }
+    Ok(cfg)
+}
+
+/// Copies the network sections of a preset onto an existing config.
+pub fn apply_network_preset(base: &mut SegatoolsConfig, preset: &SegatoolsConfig) {
+    base.dns = preset.dns.clone();
+    base.netenv = preset.netenv.clone();
+    for section in ["dns", "netenv"] {
+        if !base.present_sections.is_empty() && !base.present_sections.iter().any(|s| s == section) {
+            base.present_sections.push(section.to_string());
+        }
+    }
+    if !base.present_keys.is_empty() {
+        base.present_keys
+            .retain(|k| !k.starts_with("dns.") && !k.starts_with("netenv."));
+        base.present_keys.extend(
+            preset
+                .present_keys
+                .iter()
+                .filter(|k| k.starts_with("dns.") || k.starts_with("netenv."))
+                .cloned(),
+        );
+    }
+}
+
+/// Where a network preset came from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NetworkPresetSource {
+    /// Shipped with the launcher.
+    #[default]
+    Bundled,
+    /// From the signed feed; replaces a bundled preset with the same id.
+    Remote,
+}
+
+/// The `[dns]` section a server preset writes, ports included. Hosts left empty and ports
+/// left at 0 fall back to `default` and the standard ports.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NetworkPresetDns {
+    pub default: String,
+    pub title: String,
+    pub router: String,
+    pub startup: String,
+    pub billing: String,
+    pub aimedb: String,
+    pub replace_host: bool,
+    pub startup_port: u32,
+    pub billing_port: u32,
+    pub aimedb_port: u32,
+}
+
+impl From<&NetworkPresetDns> for DnsConfig {
+    fn from(dns: &NetworkPresetDns) -> Self {
+        Self {
+            default: dns.default.trim().to_string(),
+            title: dns.title.trim().to_string(),
+            router: dns.router.trim().to_string(),
+            startup: dns.startup.trim().to_string(),
+            billing: dns.billing.trim().to_string(),
+            aimedb: dns.aimedb.trim().to_string(),
+            replace_host: dns.replace_host,
+            startup_port: dns.startup_port,
+            billing_port: dns.billing_port,
+            aimedb_port: dns.aimedb_port,
+        }
+    }
+}
+
+/// Billing keys of `[keychip]`; the ones left out keep their current values.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkPresetBilling {
+    #[serde(default)]
+    pub billing_ca: Option<String>,
+    #[serde(default)]
+    pub billing_pub: Option<String>,
+    #[serde(default)]
+    pub billing_type: Option<u32>,
+}
+
+/// A named server to point a title at, e.g. a local ARTEMiS install or a hosted network.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkPreset {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub homepage: Option<String>,
+    /// Canonical game keys the server supports; empty when it serves every title.
+    #[serde(default)]
+    pub games: Vec<String>,
+    pub dns: NetworkPresetDns,
+    #[serde(default)]
+    pub billing: NetworkPresetBilling,
+    #[serde(default, skip_deserializing)]
+    pub source: NetworkPresetSource,
+}
+
+impl NetworkPreset {
+    pub fn supports(&self, game: &str) -> bool {
+        self.games.is_empty() || self.games.iter().any(|g| g.eq_ignore_ascii_case(game))
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkPresetFeed {
+    #[serde(default)]
+    pub schema_version: u32,
+    #[serde(default)]
+    pub generated_at: String,
+    #[serde(default)]
+    pub presets: Vec<NetworkPreset>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkPresetCache {
+    pub fetched_at: Option<String>,
+    pub feed: Option<NetworkPresetFeed>,
+}
+
+fn is_host(value: &str) -> bool {
+    value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | ':' | '[' | ']'))
+}
+
+/// Checks a preset before it is listed. Hosts must be bare names or addresses, and billing
+/// files must stay relative to the game, like the template's `DEVICE\ca.crt`.
+pub fn validate_network_preset(preset: &NetworkPreset) -> Result<(), PresetError> {
+    let reject = |reason: String| Err(PresetError::Rejected(format!("{}: {}", preset.name, reason)));
+    if preset.id.trim().is_empty() || preset.name.trim().is_empty() {
+        return Err(PresetError::Rejected("network preset without an id or name".to_string()));
+    }
+    if preset.dns.default.trim().is_empty() {
+        return reject("no default host".to_string());
+    }
+    let dns = &preset.dns;
+    for host in [&dns.default, &dns.title, &dns.router, &dns.startup, &dns.billing, &dns.aimedb] {
+        if !is_host(host.trim()) {
+            return reject(format!("{} is not a host name", host.trim()));
+        }
+    }
+    for port in [dns.startup_port, dns.billing_port, dns.aimedb_port] {
+        if port > 65535 {
+            return reject(format!("{} is not a port", port));
+        }
+    }
+    for path in [&preset.billing.billing_ca, &preset.billing.billing_pub].into_iter().flatten() {
+        if is_machine_path(path) {
+            return reject(format!("{} is an absolute path", path.trim()));
+        }
+    }
+    Ok(())
+}
+
+/// The bundled server presets plus those of the signed network feed.
+pub struct NetworkPresetCatalog {
+    cache_path: PathBuf,
+}
+
+impl NetworkPresetCatalog {
+    pub fn new(root: impl Into<PathBuf>) -> Result<Self, PresetError> {
+        let root = root.into();
+        if !root.exists() {
+            fs::create_dir_all(&root)?;
+        }
+        Ok(Self {
+            cache_path: root.join(NETWORK_PRESETS_CACHE_FILE),
+        })
+    }
+
+    pub fn read_cache(&self) -> NetworkPresetCache {
+        fs::read_to_string(&self.cache_path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default()
+    }
+
+    /// Downloads the signed network feed, verifies it and replaces the local cache.
+    pub fn refresh(&self, mirrors: &[String]) -> Result<NetworkPresetCache, PresetError> {
+        let bytes = fetch_signed_document(mirrors, NETWORK_PRESETS_FEED_PATH)?;
+        let feed: NetworkPresetFeed = serde_json::from_slice(&bytes)?;
+        let cache = NetworkPresetCache {
+            fetched_at: Some(Utc::now().to_rfc3339()),
+            feed: Some(feed),
+        };
+        write_cache(&self.cache_path, &cache)?;
+        Ok(cache)
+    }
+
+    /// Every valid preset, bundled ones first, optionally only those serving `game`.
+    pub fn list(&self, game: Option<&str>) -> Vec<NetworkPreset> {
+        let bundled: NetworkPresetFeed = serde_json::from_str(BUNDLED_NETWORK_PRESETS).unwrap_or_default();
+        let remote = self.read_cache().feed.map(|f| f.presets).unwrap_or_default();
+        let mut presets: Vec<NetworkPreset> = Vec::new();
+        let tagged = bundled
+            .presets
+            .into_iter()
+            .map(|p| (p, NetworkPresetSource::Bundled))
+            .chain(remote.into_iter().map(|p| (p, NetworkPresetSource::Remote)));
+        for (mut preset, source) in tagged {
+            if validate_network_preset(&preset).is_err() {
+                continue;
+            }
+            preset.source = source;
+            match presets.iter_mut().find(|p| p.id == preset.id) {
+                Some(existing) => *existing = preset,
+                None => presets.push(preset),
+            }
+        }
+        if let Some(game) = game {
+            presets.retain(|preset| preset.supports(game));
+        }
+        presets
+    }
+
+    pub fn find(&self, id: &str) -> Result<NetworkPreset, PresetError> {
+        self.list(None)
+            .into_iter()
+            .find(|preset| preset.id == id)
+            .ok_or_else(|| PresetError::NotFound(id.to_string()))
+    }
+}
+
+const NETWORK_PRESET_DNS_KEYS: [&str; 10] = [
+    "default",
+    "title",
+    "router",
+    "startup",
+    "billing",
+    "aimedb",
+    "replacehost",
+    "startupport",
+    "billingport",
+    "aimedbport",
+];
+
+/// Points `base` at the server of `preset`: `[dns]` is replaced as a whole and the billing
+/// keys of `[keychip]` the preset sets are overwritten. Nothing else changes; narrow the
+/// config to those two sections before saving so the rest of the file is left untouched.
+pub fn apply_server_preset(base: &mut SegatoolsConfig, preset: &NetworkPreset) {
+    base.dns = DnsConfig::from(&preset.dns);
+    let mut keys: Vec<String> = NETWORK_PRESET_DNS_KEYS.iter().map(|key| format!("dns.{}", key)).collect();
+    if let Some(ca) = &preset.billing.billing_ca {
+        base.keychip.billing_ca = ca.trim().to_string();
+        keys.push("keychip.billingca".to_string());
+    }
+    if let Some(key) = &preset.billing.billing_pub {
+        base.keychip.billing_pub = key.trim().to_string();
+        keys.push("keychip.billingpub".to_string());
+    }
+    if let Some(billing_type) = preset.billing.billing_type {
+        base.keychip.billing_type = billing_type;
+        keys.push("keychip.billingtype".to_string());
+    }
+
+    for section in ["dns", "keychip"] {
+        let touched = keys.iter().any(|k| k.starts_with(&format!("{}.", section)));
+        if touched && !base.present_sections.is_empty() && !base.present_sections.iter().any(|s| s == section) {
+            base.present_sections.push(section.to_string());
+        }
+    }
+    base.commented_keys.retain(|k| !keys.iter().any(|key| key.eq_ignore_ascii_case(k)));
+    if !base.present_keys.is_empty() {
+        for key in keys {
+            if !base.present_keys.contains(&key) {
+                base.present_keys.push(key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{load_segatoools_config_from_string, render_segatoools_config, retain_sections};
+    use tempfile::TempDir;
+
+    #[test]
+    fn lists_presets_and_patches_only_network_settings() {
+        let dir = TempDir::new().unwrap();
+        let catalog = NetworkPresetCatalog::new(dir.path()).unwrap();
+        let bundled = catalog.list(None);
+        assert!(bundled.iter().all(|p| p.source == NetworkPresetSource::Bundled));
+        let artemis = catalog.find("artemis-local").unwrap();
+
+        let mut hosted = artemis.clone();
+        hosted.id = "artemis-local".to_string();
+        hosted.name = "Override".to_string();
+        hosted.dns.default = "aime.example.net".to_string();
+        let mut leaky = hosted.clone();
+        leaky.id = "leaky".to_string();
+        leaky.billing.billing_ca = Some("C:\\Users\\me\\ca.crt".to_string());
+        let cache = NetworkPresetCache {
+            fetched_at: None,
+            feed: Some(NetworkPresetFeed {
+                presets: vec![hosted, leaky],
+                ..NetworkPresetFeed::default()
+            }),
+        };
+        write_cache(&catalog.cache_path, &cache).unwrap();
+        let listed = catalog.list(None);
+        assert_eq!(listed.len(), bundled.len());
+        let overridden = catalog.find("artemis-local").unwrap();
+        assert_eq!((overridden.name.as_str(), overridden.source), ("Override", NetworkPresetSource::Remote));
+        assert!(catalog.find("leaky").is_err());
+
+        let ini = "[keychip]\nid=A69E-01A88888888\nbillingType=1\n\n[dns]\ndefault=10.0.0.5\n\n[gpio]\ndipsw1=1\n";
+        let mut cfg = load_segatoools_config_from_string(ini).unwrap();
+        apply_server_preset(&mut cfg, &artemis);
+        retain_sections(&mut cfg, &["dns".to_string(), "keychip".to_string()]);
+        let rendered = render_segatoools_config(&cfg, Some(ini)).unwrap();
+        let reloaded = load_segatoools_config_from_string(&rendered).unwrap();
+        assert_eq!(reloaded.dns.default, artemis.dns.default);
+        assert_eq!(reloaded.keychip.id, "A69E-01A88888888");
+        assert!(rendered.contains("dipsw1=1"));
+    }
+}
@@ -1,3 +1,6 @@
+//! The privileged executor: verifies signed command requests against a signed policy and
+//! runs only the commands it allows. See `README-PrivExec.md` for the threat model.
+
 use base64::{engine::general_purpose::STANDARD as B64, Engine as _};
 use chrono::{DateTime, Duration, Utc};
 use ed25519_dalek::{Signature, Verifier, VerifyingKey};
@@ -5,21 +8,43 @@ use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use sha2::{Digest, Sha256};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::{self, File, OpenOptions};
 use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Stdio};
 use std::sync::{Arc, Mutex, RwLock};
-use std::time::Instant;
+use std::thread;
+use std::time::{Duration as StdDuration, Instant};
 
 const SCHEMA_VERSION: u32 = 1;
+#[cfg(windows)]
 const CREATE_NO_WINDOW: u32 = 0x08000000;
 const POLICY_FILE_NAME: &str = "policy.json";
 const NONCE_STATE_FILE_NAME: &str = "nonces.json";
 const COMMAND_STATE_FILE_NAME: &str = "commands.json";
 const SESSION_STATE_FILE_NAME: &str = "sessions.json";
 const AUDIT_FILE_NAME: &str = "audit.jsonl";
+/// Applies to commands whose policy entry sets no `timeoutSeconds`.
+const DEFAULT_COMMAND_TIMEOUT_SECONDS: u64 = 300;
+/// Reports which modules and rights the host has, as `{elevated, storage, bitlocker, hyperV,
+/// edition}`. Only looks, so it is cheap enough to run before every session.
+const CAPABILITY_PROBE_SCRIPT: &str = "$storage=[bool](Get-Module -ListAvailable -Name Storage);\
+    $bitlocker=[bool](Get-Module -ListAvailable -Name BitLocker);\
+    $hyperV=[bool](Get-Module -ListAvailable -Name Hyper-V);\
+    $elevated=([Security.Principal.WindowsPrincipal][Security.Principal.WindowsIdentity]::GetCurrent()).IsInRole([Security.Principal.WindowsBuiltInRole]::Administrator);\
+    $edition=(Get-CimInstance Win32_OperatingSystem -ErrorAction SilentlyContinue).Caption;\
+    @{elevated=$elevated;storage=$storage;bitlocker=$bitlocker;hyperV=$hyperV;edition=$edition} | ConvertTo-Json -Compress";
+/// Steps a single batch may carry.
+const MAX_BATCH_COMMANDS: usize = 32;
+
+pub mod bootstrap;
+pub mod client;
+pub mod hosts;
+pub mod results;
+
+#[cfg(feature = "vhd")]
+pub mod broker;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PrivExecErrorCode {
@@ -43,6 +68,7 @@ pub enum PrivExecErrorCode {
     PathNotFound,
     PathNotAllowed,
     CommandExecutionFailed,
+    CommandTimeout,
     InternalError,
     PolicyUpdateInvalidSignature,
     PolicyUpdateVersionRejected,
@@ -72,6 +98,7 @@ impl PrivExecErrorCode {
             PrivExecErrorCode::PathNotFound => "PATH_NOT_FOUND",
             PrivExecErrorCode::PathNotAllowed => "PATH_NOT_ALLOWED",
             PrivExecErrorCode::CommandExecutionFailed => "COMMAND_EXECUTION_FAILED",
+            PrivExecErrorCode::CommandTimeout => "COMMAND_TIMEOUT",
             PrivExecErrorCode::InternalError => "INTERNAL_ERROR",
             PrivExecErrorCode::PolicyUpdateInvalidSignature => "POLICY_UPDATE_INVALID_SIGNATURE",
             PrivExecErrorCode::PolicyUpdateVersionRejected => "POLICY_UPDATE_VERSION_REJECTED",
@@ -101,6 +128,7 @@ impl PrivExecErrorCode {
             PrivExecErrorCode::PathNotFound => "Path not found",
             PrivExecErrorCode::PathNotAllowed => "Path not allowed by policy",
             PrivExecErrorCode::CommandExecutionFailed => "Command execution failed",
+            PrivExecErrorCode::CommandTimeout => "Command timed out and was stopped",
             PrivExecErrorCode::InternalError => "Internal execution error",
             PrivExecErrorCode::PolicyUpdateInvalidSignature => {
                 "Policy package signature verification failed"
@@ -161,6 +189,85 @@ pub struct CommandResponse {
     pub result: Option<Value>,
 }
 
+/// One step of a [`BatchRequestPayload`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchCommand {
+    pub command_id: String,
+    pub command: String,
+    #[serde(default)]
+    pub params: Map<String, Value>,
+}
+
+/// Several commands signed as one request and run in order under one nonce, e.g. mount, unlock
+/// and verify. Each step keeps its own `command_id` for idempotency and the audit log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchRequestPayload {
+    #[serde(default = "default_schema_version")]
+    pub schema_version: u32,
+    pub batch_id: String,
+    pub nonce: String,
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub device_id: String,
+    pub commands: Vec<BatchCommand>,
+}
+
+impl BatchRequestPayload {
+    pub fn signing_bytes(&self) -> Result<Vec<u8>, PrivExecErrorCode> {
+        canonical_json_bytes(self)
+    }
+
+    /// Every step as the single request it runs as, sharing the batch's nonce, device and
+    /// validity window.
+    pub fn command_payloads(&self) -> Vec<CommandRequestPayload> {
+        self.commands
+            .iter()
+            .map(|step| CommandRequestPayload {
+                schema_version: self.schema_version,
+                command_id: step.command_id.clone(),
+                nonce: self.nonce.clone(),
+                issued_at: self.issued_at,
+                expires_at: self.expires_at,
+                device_id: self.device_id.clone(),
+                command: step.command.clone(),
+                params: step.params.clone(),
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedBatchRequest {
+    pub payload: BatchRequestPayload,
+    pub signature: SignatureEnvelope,
+}
+
+/// Outcome of a batch. `results` holds a response for every step that ran; a failed step is
+/// the last one, and its code is the batch's. Steps after it never run, and nothing already
+/// done is undone.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchResponse {
+    pub schema_version: u32,
+    pub batch_id: String,
+    pub ok: bool,
+    pub code: String,
+    pub message: String,
+    pub executed_at: DateTime<Utc>,
+    pub results: Vec<CommandResponse>,
+}
+
+/// Whether a request line holds a [`SignedBatchRequest`] rather than a single command.
+pub fn is_batch_request_json(raw_json: &str) -> bool {
+    serde_json::from_str::<Value>(raw_json)
+        .ok()
+        .and_then(|value| value.get("payload")?.get("commands").map(Value::is_array))
+        .unwrap_or(false)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PolicyUpdatePayload {
@@ -194,19 +301,14 @@ pub struct PolicyUpdateResponse {
     pub rolled_back: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum PolicyDefaultAction {
+    #[default]
     Deny,
     Allow,
 }
 
-impl Default for PolicyDefaultAction {
-    fn default() -> Self {
-        Self::Deny
-    }
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PolicySecurity {
@@ -255,6 +357,16 @@ pub struct PolicyCommand {
     pub risk_level: Option<String>,
     #[serde(default)]
     pub params: HashMap<String, ParamRule>,
+    /// Seconds the command's PowerShell may run before it is killed and the request fails
+    /// with `COMMAND_TIMEOUT`; 300 when unset.
+    #[serde(default)]
+    pub timeout_seconds: Option<u64>,
+}
+
+impl PolicyCommand {
+    pub fn timeout(&self) -> StdDuration {
+        StdDuration::from_secs(self.timeout_seconds.unwrap_or(DEFAULT_COMMAND_TIMEOUT_SECONDS).max(1))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -345,6 +457,8 @@ pub struct PrivExecConfig {
     pub device_id: String,
     pub bootstrap_public_keys: HashMap<String, String>,
     pub policy_replace_fail_after_backup: bool,
+    /// File `update_hosts_entries` edits; the system hosts file by default.
+    pub hosts_path: PathBuf,
 }
 
 impl PrivExecConfig {
@@ -354,6 +468,7 @@ impl PrivExecConfig {
             device_id: device_id.into(),
             bootstrap_public_keys: HashMap::new(),
             policy_replace_fail_after_backup: false,
+            hosts_path: hosts::system_hosts_path(),
         }
     }
 }
@@ -365,6 +480,13 @@ pub struct RunnerOutput {
     pub stderr: String,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunnerError {
+    Failed(String),
+    /// The script outlived its timeout and was killed.
+    TimedOut,
+}
+
 pub trait CommandRunner: Send + Sync {
     fn run_powershell(&self, script: &str) -> Result<RunnerOutput, String>;
 
@@ -376,26 +498,43 @@ pub trait CommandRunner: Send + Sync {
         let _ = env;
         self.run_powershell(script)
     }
+
+    /// Runs `script`, killing it once `timeout` has passed. Runners that cannot stop a
+    /// script early run it to completion.
+    fn run_powershell_with_timeout(
+        &self,
+        script: &str,
+        env: &HashMap<String, String>,
+        timeout: StdDuration,
+    ) -> Result<RunnerOutput, RunnerError> {
+        let _ = timeout;
+        self.run_powershell_with_env(script, env)
+            .map_err(RunnerError::Failed)
+    }
 }
 
 #[derive(Debug, Default)]
 pub struct SystemCommandRunner;
 
-impl CommandRunner for SystemCommandRunner {
-    fn run_powershell(&self, script: &str) -> Result<RunnerOutput, String> {
+impl SystemCommandRunner {
+    fn command(script: &str, env: &HashMap<String, String>) -> Command {
         let mut command = Command::new("powershell");
         command.args(["-NoProfile", "-Command", script]);
+        for (key, value) in env {
+            command.env(key, value);
+        }
         #[cfg(windows)]
         {
             use std::os::windows::process::CommandExt;
             command.creation_flags(CREATE_NO_WINDOW);
         }
-        let output = command.output().map_err(|e| e.to_string())?;
-        Ok(RunnerOutput {
-            status_code: output.status.code().unwrap_or(-1),
-            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
-            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
-        })
+        command
+    }
+}
+
+impl CommandRunner for SystemCommandRunner {
+    fn run_powershell(&self, script: &str) -> Result<RunnerOutput, String> {
+        self.run_powershell_with_env(script, &HashMap::new())
     }
 
     fn run_powershell_with_env(
@@ -403,23 +542,63 @@ impl CommandRunner for SystemCommandRunner {
         script: &str,
         env: &HashMap<String, String>,
     ) -> Result<RunnerOutput, String> {
-        let mut command = Command::new("powershell");
-        command.args(["-NoProfile", "-Command", script]);
-        for (key, value) in env {
-            command.env(key, value);
-        }
-        #[cfg(windows)]
-        {
-            use std::os::windows::process::CommandExt;
-            command.creation_flags(CREATE_NO_WINDOW);
-        }
-        let output = command.output().map_err(|e| e.to_string())?;
+        let output = Self::command(script, env)
+            .output()
+            .map_err(|e| e.to_string())?;
         Ok(RunnerOutput {
             status_code: output.status.code().unwrap_or(-1),
             stdout: String::from_utf8_lossy(&output.stdout).to_string(),
             stderr: String::from_utf8_lossy(&output.stderr).to_string(),
         })
     }
+
+    fn run_powershell_with_timeout(
+        &self,
+        script: &str,
+        env: &HashMap<String, String>,
+        timeout: StdDuration,
+    ) -> Result<RunnerOutput, RunnerError> {
+        run_with_timeout(Self::command(script, env), timeout)
+    }
+}
+
+fn read_pipe(mut pipe: impl Read + Send + 'static) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = pipe.read_to_end(&mut buf);
+        String::from_utf8_lossy(&buf).to_string()
+    })
+}
+
+/// Runs `command` to completion, or kills it once `timeout` has passed.
+fn run_with_timeout(mut command: Command, timeout: StdDuration) -> Result<RunnerOutput, RunnerError> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| RunnerError::Failed(e.to_string()))?;
+    // Drained on their own threads so a chatty script cannot stall on a full pipe.
+    let stdout = child.stdout.take().map(read_pipe);
+    let stderr = child.stderr.take().map(read_pipe);
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait().map_err(|e| RunnerError::Failed(e.to_string()))? {
+            Some(status) => break status,
+            None if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(RunnerError::TimedOut);
+            }
+            None => thread::sleep(StdDuration::from_millis(50)),
+        }
+    };
+    let joined = |pipe: Option<thread::JoinHandle<String>>| pipe.and_then(|p| p.join().ok()).unwrap_or_default();
+    Ok(RunnerOutput {
+        status_code: status.code().unwrap_or(-1),
+        stdout: joined(stdout),
+        stderr: joined(stderr),
+    })
 }
 
 pub trait SignatureVerifier: Send + Sync {
@@ -471,6 +650,16 @@ pub struct PrivExecCore {
     runner: Arc<dyn CommandRunner>,
     verifiers: RwLock<HashMap<String, Arc<dyn SignatureVerifier>>>,
     state_lock: Mutex<()>,
+    /// Game VHDs mounted by `mount_vhd` with a `gameId`, kept until `unmount_vhd`.
+    #[cfg(feature = "vhd")]
+    game_mounts: Mutex<HashMap<String, GameMount>>,
+}
+
+/// A game VHD set held by the executor and the process it was mounted for, if given.
+#[cfg(feature = "vhd")]
+struct GameMount {
+    owner_pid: Option<u32>,
+    handle: crate::vhd::VhdMountHandle,
 }
 
 impl PrivExecCore {
@@ -491,6 +680,8 @@ impl PrivExecCore {
             runner,
             verifiers: RwLock::new(verifiers),
             state_lock: Mutex::new(()),
+            #[cfg(feature = "vhd")]
+            game_mounts: Mutex::new(HashMap::new()),
         })
     }
 
@@ -544,6 +735,30 @@ impl PrivExecCore {
         response
     }
 
+    pub fn execute_batch_request_json(&self, raw_json: &str) -> BatchResponse {
+        match serde_json::from_str::<SignedBatchRequest>(raw_json) {
+            Ok(req) => self.execute_batch_request(req),
+            Err(_) => self.batch_error_response("", PrivExecErrorCode::InvalidSchema),
+        }
+    }
+
+    /// Runs the steps of a batch in order under one signature check and one nonce, holding
+    /// the state lock throughout so no other request runs between them. Stops at the first
+    /// failing step.
+    pub fn execute_batch_request(&self, request: SignedBatchRequest) -> BatchResponse {
+        let start = Instant::now();
+        let batch_id = request.payload.batch_id.clone();
+        let _guard = self.state_lock.lock().expect("state lock poisoned");
+        match self.execute_batch_locked(&request) {
+            Ok(response) => response,
+            Err(code) => {
+                let rejected = self.error_response(&batch_id, "batch", code, false);
+                self.write_audit_log(&rejected, "", start.elapsed().as_millis(), "batch");
+                self.batch_error_response(&batch_id, code)
+            }
+        }
+    }
+
     pub fn apply_policy_update_json(&self, raw_json: &str) -> PolicyUpdateResponse {
         match serde_json::from_str::<SignedPolicyUpdateRequest>(raw_json) {
             Ok(req) => self.apply_policy_update(req),
@@ -708,7 +923,9 @@ impl PrivExecCore {
             );
         }
 
-        if let Err(code) = self.verify_request_security(&request, payload_bytes, &policy) {
+        if let Err(code) =
+            self.verify_request_security(&request.signature, &request.payload, payload_bytes, &policy)
+        {
             return (
                 self.error_response(&command_id, &command_name, code, false),
                 false,
@@ -743,10 +960,23 @@ impl PrivExecCore {
             }
         }
 
+        self.run_authorized(&request.payload, &policy)
+    }
+
+    /// Runs a request whose signature, window and nonce have been checked: enforces the
+    /// command's policy entry and session, then executes it. The flag says whether the
+    /// response is kept as the command id's idempotency record.
+    fn run_authorized(
+        &self,
+        payload: &CommandRequestPayload,
+        policy: &PrivExecPolicy,
+    ) -> (CommandResponse, bool) {
+        let command_id = payload.command_id.clone();
+        let command_name = payload.command.clone();
         let command_policy = match policy
             .allowed_commands
             .iter()
-            .find(|c| c.name.eq_ignore_ascii_case(&request.payload.command))
+            .find(|c| c.name.eq_ignore_ascii_case(&payload.command))
         {
             Some(cmd) => cmd,
             None => {
@@ -773,7 +1003,7 @@ impl PrivExecCore {
             );
         }
 
-        if command_policy.requires_session && !request.payload.params.contains_key("sessionId") {
+        if command_policy.requires_session && !payload.params.contains_key("sessionId") {
             return (
                 self.error_response(
                     &command_id,
@@ -785,7 +1015,7 @@ impl PrivExecCore {
             );
         }
 
-        let validated_params = match self.validate_params(command_policy, &request.payload.params) {
+        let validated_params = match self.validate_params(command_policy, &payload.params) {
             Ok(p) => p,
             Err(code) => {
                 return (
@@ -810,7 +1040,7 @@ impl PrivExecCore {
                     )
                 }
             };
-            if let Err(code) = self.touch_session(&session_id, &request.payload.device_id) {
+            if let Err(code) = self.touch_session(&session_id, &payload.device_id) {
                 return (
                     self.error_response(&command_id, &command_name, code, false),
                     true,
@@ -818,7 +1048,15 @@ impl PrivExecCore {
             }
         }
 
-        let result = match self.execute_command(&request.payload, &policy, &validated_params) {
+        let result = self.execute_command(payload, policy, &validated_params);
+        if command_policy.requires_session {
+            // Requests queue behind the state lock, heartbeats included, so a command that ran
+            // longer than the TTL would otherwise leave its own session expired.
+            if let Some(session_id) = validated_params.get("sessionId").and_then(|v| v.as_str()) {
+                let _ = self.touch_session(session_id, &payload.device_id);
+            }
+        }
+        let result = match result {
             Ok(value) => value,
             Err(code) => {
                 return (
@@ -843,6 +1081,99 @@ impl PrivExecCore {
         )
     }
 
+    /// Steps run and their responses, or why the batch as a whole was rejected before any ran.
+    fn execute_batch_locked(
+        &self,
+        request: &SignedBatchRequest,
+    ) -> Result<BatchResponse, PrivExecErrorCode> {
+        let batch = &request.payload;
+        let steps = batch.command_payloads();
+        let unique_ids: HashSet<&str> =
+            steps.iter().map(|step| step.command_id.as_str()).collect();
+        if batch.batch_id.trim().is_empty()
+            || batch.batch_id.len() > 128
+            || steps.is_empty()
+            || steps.len() > MAX_BATCH_COMMANDS
+            || unique_ids.len() != steps.len()
+        {
+            return Err(PrivExecErrorCode::InvalidSchema);
+        }
+        for step in &steps {
+            validate_payload_basic(step)?;
+        }
+
+        let payload_bytes = batch.signing_bytes()?;
+        let policy = self.load_policy()?;
+        if policy.default_action != PolicyDefaultAction::Deny {
+            return Err(PrivExecErrorCode::PolicyInvalid);
+        }
+        self.verify_request_security(&request.signature, &steps[0], &payload_bytes, &policy)?;
+
+        // A resent batch replays the steps it already ran instead of tripping the nonce check.
+        let mut hashes = Vec::with_capacity(steps.len());
+        let mut replays = HashMap::new();
+        for step in &steps {
+            let hash = sha256_hex(&step.signing_bytes()?);
+            if let Ok(Some(existing)) = self.load_command_record(&step.command_id) {
+                if existing.request_hash != hash {
+                    return Err(PrivExecErrorCode::CommandIdConflict);
+                }
+                replays.insert(step.command_id.clone(), existing.response);
+            }
+            hashes.push(hash);
+        }
+        if policy.security.require_nonce && replays.is_empty() {
+            self.reserve_nonce(&batch.nonce, policy.security.nonce_ttl_seconds)?;
+        }
+
+        let mut results = Vec::with_capacity(steps.len());
+        for (step, hash) in steps.iter().zip(&hashes) {
+            let start = Instant::now();
+            let response = match replays.remove(&step.command_id) {
+                Some(mut replayed) => {
+                    replayed.idempotent_replay = true;
+                    replayed
+                }
+                None => {
+                    let (response, should_persist) = self.run_authorized(step, &policy);
+                    if should_persist {
+                        let _ = self.store_command_record(&step.command_id, hash, &response);
+                    }
+                    response
+                }
+            };
+            self.write_audit_log(&response, hash, start.elapsed().as_millis(), &step.command);
+            let ok = response.ok;
+            results.push(response);
+            if !ok {
+                break;
+            }
+        }
+
+        let failed = results.last().filter(|last| !last.ok);
+        Ok(BatchResponse {
+            schema_version: SCHEMA_VERSION,
+            batch_id: batch.batch_id.clone(),
+            ok: failed.is_none(),
+            code: failed.map_or_else(|| PrivExecErrorCode::Ok.as_str().to_string(), |f| f.code.clone()),
+            message: failed.map_or_else(|| PrivExecErrorCode::Ok.message().to_string(), |f| f.message.clone()),
+            executed_at: Utc::now(),
+            results,
+        })
+    }
+
+    fn batch_error_response(&self, batch_id: &str, code: PrivExecErrorCode) -> BatchResponse {
+        BatchResponse {
+            schema_version: SCHEMA_VERSION,
+            batch_id: batch_id.to_string(),
+            ok: false,
+            code: code.as_str().to_string(),
+            message: code.message().to_string(),
+            executed_at: Utc::now(),
+            results: Vec::new(),
+        }
+    }
+
     fn error_response(
         &self,
         command_id: &str,
@@ -862,9 +1193,11 @@ impl PrivExecCore {
         }
     }
 
+    /// Checks `signature` over `payload_bytes` and the device and validity window of `payload`.
     fn verify_request_security(
         &self,
-        request: &SignedCommandRequest,
+        signature: &SignatureEnvelope,
+        payload: &CommandRequestPayload,
         payload_bytes: &[u8],
         policy: &PrivExecPolicy,
     ) -> Result<(), PrivExecErrorCode> {
@@ -875,7 +1208,7 @@ impl PrivExecCore {
                 self.config.bootstrap_public_keys.clone()
             };
             self.verify_with_keys(
-                &request.signature,
+                signature,
                 payload_bytes,
                 &keys,
                 Some(policy.security.signature_algorithm.as_str()),
@@ -884,20 +1217,20 @@ impl PrivExecCore {
         }
 
         if policy.security.require_device_binding
-            && request.payload.device_id != self.config.device_id
+            && payload.device_id != self.config.device_id
         {
             return Err(PrivExecErrorCode::DeviceIdMismatch);
         }
 
         let skew = Duration::seconds(policy.security.max_clock_skew_seconds.max(0));
-        if request.payload.expires_at < request.payload.issued_at {
+        if payload.expires_at < payload.issued_at {
             return Err(PrivExecErrorCode::InvalidSchema);
         }
         let now = Utc::now();
-        if now < request.payload.issued_at - skew {
+        if now < payload.issued_at - skew {
             return Err(PrivExecErrorCode::RequestNotYetValid);
         }
-        if now > request.payload.expires_at + skew {
+        if now > payload.expires_at + skew {
             return Err(PrivExecErrorCode::RequestExpired);
         }
         Ok(())
@@ -975,7 +1308,7 @@ impl PrivExecCore {
     fn touch_session(&self, session_id: &str, device_id: &str) -> Result<(), PrivExecErrorCode> {
         let mut sessions = self.load_sessions();
         let now = Utc::now();
-        sessions.retain(|id, record| id == &session_id || record.expires_at > now);
+        sessions.retain(|id, record| id == session_id || record.expires_at > now);
         let record = sessions
             .get_mut(session_id)
             .ok_or(PrivExecErrorCode::SessionNotFound)?;
@@ -1222,18 +1555,28 @@ impl PrivExecCore {
         if command.eq_ignore_ascii_case("restart_service") {
             return Err(PrivExecErrorCode::CommandDisabled);
         }
+        let timeout = policy
+            .allowed_commands
+            .iter()
+            .find(|c| c.name.eq_ignore_ascii_case(command))
+            .map(PolicyCommand::timeout)
+            .unwrap_or(StdDuration::from_secs(DEFAULT_COMMAND_TIMEOUT_SECONDS));
         match command.to_lowercase().as_str() {
             "begin_session" => self.exec_begin_session(payload, policy),
             "heartbeat" => self.exec_heartbeat(payload, params),
             "end_session" => self.exec_end_session(payload, params),
-            "mount_vhd" => self.exec_mount_vhd(params),
-            "unmount_vhd" => self.exec_unmount_vhd(params),
-            "query_bitlocker_status" => self.exec_query_bitlocker_status(params),
-            "unlock_bitlocker" => self.exec_unlock_bitlocker(params),
-            "lock_bitlocker" => self.exec_lock_bitlocker(params),
-            "query_disk" => self.exec_query_disk(),
-            "query_service_status" => self.exec_query_service_status(params),
+            "mount_vhd" => self.exec_mount_vhd(params, timeout),
+            "unmount_vhd" => self.exec_unmount_vhd(params, timeout),
+            #[cfg(feature = "vhd")]
+            "query_mounts" => self.exec_query_mounts(),
+            "query_bitlocker_status" => self.exec_query_bitlocker_status(params, timeout),
+            "unlock_bitlocker" => self.exec_unlock_bitlocker(params, timeout),
+            "lock_bitlocker" => self.exec_lock_bitlocker(params, timeout),
+            "query_disk" => self.exec_query_disk(timeout),
+            "probe_capabilities" => self.exec_probe_capabilities(timeout),
+            "query_service_status" => self.exec_query_service_status(params, timeout),
             "collect_log" => self.exec_collect_log(params),
+            "update_hosts_entries" => self.exec_update_hosts_entries(params, timeout),
             _ => Err(PrivExecErrorCode::PolicyDeny),
         }
     }
@@ -1327,7 +1670,12 @@ impl PrivExecCore {
         }))
     }
 
-    fn exec_mount_vhd(&self, params: &Map<String, Value>) -> Result<Value, PrivExecErrorCode> {
+    fn exec_mount_vhd(&self, params: &Map<String, Value>, timeout: StdDuration) -> Result<Value, PrivExecErrorCode> {
+        #[cfg(feature = "vhd")]
+        if let Ok(game_id) = get_string(params, "gameId") {
+            let owner_pid = get_i64(params, "ownerPid").ok().and_then(|pid| u32::try_from(pid).ok());
+            return self.mount_game_vhd(&game_id, get_bool(params, "delta").ok(), owner_pid);
+        }
         let path = get_string(params, "path")?;
         let read_only = get_bool(params, "readOnly").unwrap_or(false);
         let mount_point = get_string(params, "mountPoint").unwrap_or("X:\\".to_string());
@@ -1340,31 +1688,143 @@ impl PrivExecCore {
             ps_quote(&mount_point),
             access
         );
-        self.run_powershell_json(&script)
+        let output = self.run_powershell_json(&script, timeout)?;
+        result_value(&results::MountVhdResult::from_powershell(&output))
     }
 
-    fn exec_unmount_vhd(&self, params: &Map<String, Value>) -> Result<Value, PrivExecErrorCode> {
+    fn exec_unmount_vhd(&self, params: &Map<String, Value>, timeout: StdDuration) -> Result<Value, PrivExecErrorCode> {
+        #[cfg(feature = "vhd")]
+        if let Ok(game_id) = get_string(params, "gameId") {
+            return self.unmount_game_vhd(&game_id);
+        }
         let path = get_string(params, "path")?;
         let script = format!(
             "$imagePath={};Dismount-DiskImage -ImagePath $imagePath -Confirm:$false -ErrorAction Stop;@{{ok=$true;imagePath=$imagePath}} | ConvertTo-Json -Compress",
             ps_quote(&path),
         );
-        self.run_powershell_json(&script)
+        self.run_powershell_json(&script, timeout)
+    }
+
+    /// Mounts a game's VHD set from its own vhd.json, so callers name a game instead of
+    /// passing image paths. `delta` overrides the config's runtime delta setting.
+    #[cfg(feature = "vhd")]
+    fn mount_game_vhd(
+        &self,
+        game_id: &str,
+        delta: Option<bool>,
+        owner_pid: Option<u32>,
+    ) -> Result<Value, PrivExecErrorCode> {
+        use crate::vhd::{load_vhd_config, mount_vhd_with_elevation, resolve_vhd_config};
+
+        let mut mounts = self
+            .game_mounts
+            .lock()
+            .map_err(|_| PrivExecErrorCode::InternalError)?;
+        if let Some(existing) = mounts.get(game_id) {
+            return result_value(&results::MountVhdResult::for_game(
+                game_id,
+                existing.handle.mount_points().clone(),
+                true,
+            ));
+        }
+        let cfg = load_vhd_config(game_id).map_err(|_| PrivExecErrorCode::PathNotFound)?;
+        let mut resolved =
+            resolve_vhd_config(game_id, &cfg).map_err(|_| PrivExecErrorCode::PathNotFound)?;
+        if let Some(delta) = delta {
+            resolved.delta_enabled = delta;
+        }
+        let handle = mount_vhd_with_elevation(&resolved).map_err(|err| {
+            eprintln!("[privexec] mounting {} failed: {}", game_id, err);
+            PrivExecErrorCode::CommandExecutionFailed
+        })?;
+        let result = results::MountVhdResult::for_game(game_id, handle.mount_points().clone(), false);
+        mounts.insert(game_id.to_string(), GameMount { owner_pid, handle });
+        result_value(&result)
+    }
+
+    #[cfg(feature = "vhd")]
+    fn unmount_game_vhd(&self, game_id: &str) -> Result<Value, PrivExecErrorCode> {
+        let handle = self
+            .game_mounts
+            .lock()
+            .map_err(|_| PrivExecErrorCode::InternalError)?
+            .remove(game_id)
+            .ok_or(PrivExecErrorCode::PathNotFound)?
+            .handle;
+        crate::vhd::unmount_vhd_handle(&handle).map_err(|err| {
+            eprintln!("[privexec] unmounting {} failed: {}", game_id, err);
+            PrivExecErrorCode::CommandExecutionFailed
+        })?;
+        Ok(serde_json::json!({ "ok": true, "gameId": game_id }))
+    }
+
+    #[cfg(feature = "vhd")]
+    fn exec_query_mounts(&self) -> Result<Value, PrivExecErrorCode> {
+        Ok(serde_json::json!({ "gameIds": self.game_mount_ids() }))
+    }
+
+    /// Games whose VHD set this executor currently holds.
+    #[cfg(feature = "vhd")]
+    pub fn game_mount_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = match self.game_mounts.lock() {
+            Ok(mounts) => mounts.keys().cloned().collect(),
+            Err(_) => Vec::new(),
+        };
+        ids.sort();
+        ids
+    }
+
+    /// Unmounts every game VHD this executor still holds, e.g. when its client went away.
+    #[cfg(feature = "vhd")]
+    pub fn release_game_mounts(&self) {
+        self.release_game_mounts_where(|_| true);
+    }
+
+    /// Unmounts the game VHDs whose owning process has exited without unmounting them.
+    #[cfg(feature = "vhd")]
+    pub fn release_orphaned_game_mounts(&self) {
+        self.release_game_mounts_where(|mount| {
+            mount.owner_pid.is_some_and(|pid| !crate::vhd::process_alive(pid))
+        });
+    }
+
+    #[cfg(feature = "vhd")]
+    fn release_game_mounts_where(&self, release: impl Fn(&GameMount) -> bool) {
+        let released: Vec<(String, GameMount)> = match self.game_mounts.lock() {
+            Ok(mut mounts) => {
+                let ids: Vec<String> = mounts
+                    .iter()
+                    .filter(|(_, mount)| release(mount))
+                    .map(|(id, _)| id.clone())
+                    .collect();
+                ids.into_iter()
+                    .filter_map(|id| mounts.remove(&id).map(|mount| (id, mount)))
+                    .collect()
+            }
+            Err(_) => return,
+        };
+        for (game_id, mount) in released {
+            if let Err(err) = crate::vhd::unmount_vhd_handle(&mount.handle) {
+                eprintln!("[privexec] unmounting {} failed: {}", game_id, err);
+            }
+        }
     }
 
     fn exec_query_bitlocker_status(
         &self,
         params: &Map<String, Value>,
+        timeout: StdDuration,
     ) -> Result<Value, PrivExecErrorCode> {
         let mount_point = get_string(params, "mountPoint")?;
         let script = format!(
             "$mountPoint={};Get-BitLockerVolume -MountPoint $mountPoint -ErrorAction Stop | Select-Object MountPoint,VolumeStatus,ProtectionStatus,LockStatus,EncryptionPercentage,AutoUnlockEnabled | ConvertTo-Json -Compress",
             ps_quote(&mount_point)
         );
-        self.run_powershell_json(&script)
+        let output = self.run_powershell_json(&script, timeout)?;
+        result_value(&results::BitLockerStatus::from_powershell(&output, &mount_point))
     }
 
-    fn exec_unlock_bitlocker(&self, params: &Map<String, Value>) -> Result<Value, PrivExecErrorCode> {
+    fn exec_unlock_bitlocker(&self, params: &Map<String, Value>, timeout: StdDuration) -> Result<Value, PrivExecErrorCode> {
         let mount_point = get_string(params, "mountPoint")?;
         let recovery_password = params
             .get("recoveryPassword")
@@ -1420,10 +1880,11 @@ impl PrivExecCore {
             skip_flag,
             unlock_cmd
         );
-        self.run_powershell_json_with_env(&script, &env)
+        let output = self.run_powershell_json_with_env(&script, &env, timeout)?;
+        result_value(&results::BitLockerStatus::from_powershell(&output, &mount_point))
     }
 
-    fn exec_lock_bitlocker(&self, params: &Map<String, Value>) -> Result<Value, PrivExecErrorCode> {
+    fn exec_lock_bitlocker(&self, params: &Map<String, Value>, timeout: StdDuration) -> Result<Value, PrivExecErrorCode> {
         let mount_point = get_string(params, "mountPoint")?;
         let force_dismount = get_bool(params, "forceDismount").unwrap_or(true);
         let force_flag = if force_dismount { "$true" } else { "$false" };
@@ -1434,24 +1895,31 @@ impl PrivExecCore {
             force_flag,
             force_flag
         );
-        self.run_powershell_json(&script)
+        self.run_powershell_json(&script, timeout)
     }
 
-    fn exec_query_disk(&self) -> Result<Value, PrivExecErrorCode> {
+    fn exec_query_disk(&self, timeout: StdDuration) -> Result<Value, PrivExecErrorCode> {
         let script = "Get-Disk | Select-Object Number,FriendlyName,OperationalStatus,PartitionStyle,Size | ConvertTo-Json -Compress";
-        self.run_powershell_json(script)
+        let output = self.run_powershell_json(script, timeout)?;
+        result_value(&results::DiskList::from_powershell(&output))
+    }
+
+    fn exec_probe_capabilities(&self, timeout: StdDuration) -> Result<Value, PrivExecErrorCode> {
+        let output = self.run_powershell_json(CAPABILITY_PROBE_SCRIPT, timeout)?;
+        result_value(&results::HostCapabilities::from_powershell(&output))
     }
 
     fn exec_query_service_status(
         &self,
         params: &Map<String, Value>,
+        timeout: StdDuration,
     ) -> Result<Value, PrivExecErrorCode> {
         let service_name = get_string(params, "serviceName")?;
         let script = format!(
             "Get-Service -Name {} -ErrorAction Stop | Select-Object Name,Status,StartType | ConvertTo-Json -Compress",
             ps_quote(&service_name)
         );
-        self.run_powershell_json(&script)
+        self.run_powershell_json(&script, timeout)
     }
 
     fn exec_collect_log(&self, params: &Map<String, Value>) -> Result<Value, PrivExecErrorCode> {
@@ -1485,30 +1953,51 @@ impl PrivExecCore {
         Ok(Value::Object(out))
     }
 
-    fn run_powershell_json(&self, script: &str) -> Result<Value, PrivExecErrorCode> {
-        let output = self
-            .runner
-            .run_powershell(script)
-            .map_err(|_| PrivExecErrorCode::CommandExecutionFailed)?;
-        if output.status_code != 0 {
-            return Err(PrivExecErrorCode::CommandExecutionFailed);
-        }
-        let stdout = output.stdout.trim();
-        if stdout.is_empty() {
-            return Ok(Value::Object(Map::new()));
+    /// `entries` is a JSON array of [`hosts::HostsEntry`]; with `gameId` only that game's
+    /// entries are replaced.
+    fn exec_update_hosts_entries(
+        &self,
+        params: &Map<String, Value>,
+        timeout: StdDuration,
+    ) -> Result<Value, PrivExecErrorCode> {
+        let entries: Vec<hosts::HostsEntry> = serde_json::from_str(&get_string(params, "entries")?)
+            .map_err(|_| PrivExecErrorCode::InvalidParameter)?;
+        let game_id = get_string(params, "gameId").ok().filter(|id| !id.trim().is_empty());
+        if entries.iter().any(|entry| hosts::validate_hosts_entry(entry).is_err()) {
+            return Err(PrivExecErrorCode::InvalidParameter);
         }
-        serde_json::from_str::<Value>(stdout).or_else(|_| Ok(Value::String(stdout.to_string())))
+        let previous = hosts::update_hosts_file(&self.config.hosts_path, game_id.as_deref(), &entries)
+            .map_err(|_| PrivExecErrorCode::CommandExecutionFailed)?;
+        // Cached lookups would keep resolving the old addresses until they expire.
+        let _ = self
+            .runner
+            .run_powershell_with_timeout("Clear-DnsClientCache", &HashMap::new(), timeout);
+        let current = fs::read_to_string(&self.config.hosts_path)
+            .map(|content| hosts::managed_entries(&content))
+            .unwrap_or_default();
+        let mut out = Map::new();
+        out.insert("previous".to_string(), serde_json::to_value(previous).unwrap_or_default());
+        out.insert("entries".to_string(), serde_json::to_value(current).unwrap_or_default());
+        Ok(Value::Object(out))
+    }
+
+    fn run_powershell_json(&self, script: &str, timeout: StdDuration) -> Result<Value, PrivExecErrorCode> {
+        self.run_powershell_json_with_env(script, &HashMap::new(), timeout)
     }
 
     fn run_powershell_json_with_env(
         &self,
         script: &str,
         env: &HashMap<String, String>,
+        timeout: StdDuration,
     ) -> Result<Value, PrivExecErrorCode> {
         let output = self
             .runner
-            .run_powershell_with_env(script, env)
-            .map_err(|_| PrivExecErrorCode::CommandExecutionFailed)?;
+            .run_powershell_with_timeout(script, env, timeout)
+            .map_err(|err| match err {
+                RunnerError::TimedOut => PrivExecErrorCode::CommandTimeout,
+                RunnerError::Failed(_) => PrivExecErrorCode::CommandExecutionFailed,
+            })?;
         if output.status_code != 0 {
             return Err(PrivExecErrorCode::CommandExecutionFailed);
         }
@@ -1520,6 +2009,10 @@ impl PrivExecCore {
     }
 }
 
+fn result_value<T: Serialize>(result: &T) -> Result<Value, PrivExecErrorCode> {
+    serde_json::to_value(result).map_err(|_| PrivExecErrorCode::InternalError)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct StoredCommandRecord {
     request_hash: String,
@@ -1789,4 +2282,21 @@ mod tests {
         let child = PathBuf::from(r"C:\iris\vhd\test.vhd");
         assert!(is_under_root(&child, &root));
     }
+
+    #[test]
+    fn runner_kills_commands_past_their_timeout() {
+        let slow = if cfg!(windows) {
+            let mut command = Command::new("ping");
+            command.args(["-n", "30", "127.0.0.1"]);
+            command
+        } else {
+            let mut command = Command::new("sleep");
+            command.arg("30");
+            command
+        };
+        let started = Instant::now();
+        let result = run_with_timeout(slow, StdDuration::from_millis(200));
+        assert_eq!(result.unwrap_err(), RunnerError::TimedOut);
+        assert!(started.elapsed() < StdDuration::from_secs(10));
+    }
 }
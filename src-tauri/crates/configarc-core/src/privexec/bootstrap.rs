@@ -0,0 +1,324 @@
+//! First-time setup of a standalone executor without hand-written JSON: a policy built from
+//! a template with this host's game and VHD roots, a signing key sealed on disk, and the
+//! signed version 1 policy update that installs both through the bootstrap key path.
+
+use super::client::load_or_create_sealed_key;
+use super::{
+    ParamRule, PolicyCommand, PolicyDefaultAction, PolicySecurity, PolicyUpdatePayload, PrivExecConfig, PrivExecCore,
+    PrivExecErrorCode, PrivExecPolicy, SignatureEnvelope, SignedPolicyUpdateRequest, POLICY_FILE_NAME, SCHEMA_VERSION,
+};
+use base64::{engine::general_purpose::STANDARD as B64, Engine as _};
+use chrono::Utc;
+use ed25519_dalek::{Signer, SigningKey};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+pub const BOOTSTRAP_POLICY_NAME: &str = "configarc-local";
+pub const DEFAULT_KEY_ID: &str = "operator";
+
+/// Directories the template may point path parameters at.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BootstrapRoots {
+    /// Game folders; `collect_log` may read logs below them.
+    #[serde(default)]
+    pub game_roots: Vec<String>,
+    /// Folders holding game VHDs; `mount_vhd` and `unmount_vhd` only take images below them.
+    #[serde(default)]
+    pub vhd_roots: Vec<String>,
+    /// Folders games mount their volumes below instead of drive letters; the BitLocker
+    /// commands take the volume folders in them as well as the launcher's drive letters.
+    #[serde(default)]
+    pub mount_roots: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BootstrapReport {
+    pub key_id: String,
+    /// Base64 ed25519 public key, as listed in the policy's `publicKeys`.
+    pub public_key: String,
+    pub key_path: String,
+    pub policy_path: String,
+    pub roots: BootstrapRoots,
+    /// Roots left out of the policy because they are not absolute or do not exist; the
+    /// executor rejects every path under a root it cannot resolve.
+    pub skipped_roots: Vec<String>,
+    pub update: SignedPolicyUpdateRequest,
+}
+
+/// Existing absolute directories among `roots`, without duplicates, and the rest.
+fn usable_roots(roots: &[String]) -> (Vec<String>, Vec<String>) {
+    let mut usable: Vec<String> = Vec::new();
+    let mut skipped = Vec::new();
+    for root in roots.iter().map(|r| r.trim()).filter(|r| !r.is_empty()) {
+        let path = Path::new(root);
+        if !path.is_absolute() || !path.is_dir() {
+            skipped.push(root.to_string());
+        } else if !usable.iter().any(|u| u.eq_ignore_ascii_case(root)) {
+            usable.push(root.to_string());
+        }
+    }
+    (usable, skipped)
+}
+
+/// `mountPoint` values the BitLocker commands accept: every mount point the VHD mounter may
+/// hand out, in the form the launcher passes them.
+#[cfg(feature = "vhd")]
+fn mount_point_values(roots: &BootstrapRoots) -> Vec<String> {
+    let mount_roots: Vec<std::path::PathBuf> = roots.mount_roots.iter().map(Into::into).collect();
+    crate::vhd::mount_point_candidates(&mount_roots)
+        .iter()
+        .map(crate::vhd::MountPoint::volume_name)
+        .collect()
+}
+
+/// Without VHD support the launcher mounts nothing, so the BitLocker commands stay off.
+#[cfg(not(feature = "vhd"))]
+fn mount_point_values(_roots: &BootstrapRoots) -> Vec<String> {
+    Vec::new()
+}
+
+fn string_rule(required: bool, allow_values: &[&str]) -> ParamRule {
+    ParamRule::String {
+        required,
+        default: None,
+        allow_values: allow_values.iter().map(|v| v.to_string()).collect(),
+        fixed_value: None,
+    }
+}
+
+fn bool_rule(default: bool) -> ParamRule {
+    ParamRule::Bool {
+        required: false,
+        default: Some(default),
+        fixed_value: None,
+    }
+}
+
+fn path_rule(roots: &[String], extensions: &[&str]) -> ParamRule {
+    ParamRule::Path {
+        required: true,
+        default: None,
+        allow_roots: roots.to_vec(),
+        allow_extensions: extensions.iter().map(|e| e.to_string()).collect(),
+        fixed_value: None,
+    }
+}
+
+fn command(name: &str, enabled: bool, requires_session: bool, params: Vec<(&str, ParamRule)>) -> PolicyCommand {
+    PolicyCommand {
+        name: name.to_string(),
+        enabled,
+        requires_session,
+        risk_level: None,
+        params: params.into_iter().map(|(key, rule)| (key.to_string(), rule)).collect(),
+        timeout_seconds: None,
+    }
+}
+
+/// The policy a fresh install starts from, trusting only `public_key` under `key_id`.
+/// Commands that take a path stay disabled while there is no root for them, since a path
+/// rule without roots would accept any file. `restart_service` and `query_service_status`
+/// are left out; they need a hand-picked service list.
+pub fn policy_template(key_id: &str, public_key: &str, roots: &BootstrapRoots) -> PrivExecPolicy {
+    let session = || ("sessionId", string_rule(true, &[]));
+    let mount_points = mount_point_values(roots);
+    let has_mount_points = !mount_points.is_empty();
+    let mount_point = || {
+        let values: Vec<&str> = mount_points.iter().map(String::as_str).collect();
+        ("mountPoint", string_rule(true, &values))
+    };
+    let has_vhd_roots = !roots.vhd_roots.is_empty();
+    let vhd_path = || ("path", path_rule(&roots.vhd_roots, &[".vhd", ".vhdx"]));
+    PrivExecPolicy {
+        schema_version: SCHEMA_VERSION,
+        policy_name: BOOTSTRAP_POLICY_NAME.to_string(),
+        version: 1,
+        default_action: PolicyDefaultAction::Deny,
+        security: PolicySecurity {
+            public_keys: HashMap::from([(key_id.to_string(), public_key.to_string())]),
+            ..PolicySecurity::default()
+        },
+        allowed_commands: vec![
+            command("begin_session", true, false, vec![]),
+            command("heartbeat", true, false, vec![session()]),
+            command("end_session", true, false, vec![session()]),
+            command(
+                "mount_vhd",
+                has_vhd_roots,
+                true,
+                vec![session(), vhd_path(), ("readOnly", bool_rule(false))],
+            ),
+            command("unmount_vhd", has_vhd_roots, true, vec![session(), vhd_path()]),
+            command("query_bitlocker_status", has_mount_points, false, vec![mount_point()]),
+            command(
+                "unlock_bitlocker",
+                has_mount_points,
+                true,
+                vec![
+                    session(),
+                    mount_point(),
+                    ("recoveryPassword", string_rule(false, &[])),
+                    ("password", string_rule(false, &[])),
+                    ("skipIfUnlocked", bool_rule(true)),
+                ],
+            ),
+            command(
+                "lock_bitlocker",
+                has_mount_points,
+                true,
+                vec![session(), mount_point(), ("forceDismount", bool_rule(true))],
+            ),
+            command("query_disk", true, false, vec![]),
+            command("probe_capabilities", true, false, vec![]),
+            command(
+                "collect_log",
+                !roots.game_roots.is_empty(),
+                false,
+                vec![
+                    ("path", path_rule(&roots.game_roots, &[".log", ".txt"])),
+                    (
+                        "maxBytes",
+                        ParamRule::Int {
+                            required: false,
+                            default: Some(1_048_576),
+                            min: Some(1),
+                            max: Some(5_242_880),
+                            fixed_value: None,
+                        },
+                    ),
+                ],
+            ),
+        ],
+    }
+}
+
+/// `policy` as the signed version 1 update the executor accepts from a bootstrap key.
+pub fn sign_initial_policy(
+    mut policy: PrivExecPolicy,
+    key_id: &str,
+    key: &SigningKey,
+) -> Result<SignedPolicyUpdateRequest, PrivExecErrorCode> {
+    policy.version = 1;
+    let payload = PolicyUpdatePayload {
+        schema_version: SCHEMA_VERSION,
+        version: 1,
+        issued_at: Utc::now(),
+        policy,
+    };
+    let signature = key.sign(&payload.signing_bytes()?);
+    Ok(SignedPolicyUpdateRequest {
+        payload,
+        signature: SignatureEnvelope {
+            algorithm: "ed25519".to_string(),
+            key_id: key_id.to_string(),
+            signature: B64.encode(signature.to_bytes()),
+        },
+    })
+}
+
+/// Installs the first policy of the executor at `config.root_dir`, signed with the key
+/// sealed at `key_path` (created if missing). Refuses to touch an executor that already has
+/// a policy; those change through signed updates from a key they trust.
+pub fn bootstrap_policy(
+    mut config: PrivExecConfig,
+    key_path: &Path,
+    key_id: &str,
+    roots: &BootstrapRoots,
+) -> Result<BootstrapReport, String> {
+    let key_id = key_id.trim();
+    if key_id.is_empty() {
+        return Err("Key id must not be empty".to_string());
+    }
+    let policy_path = config.root_dir.join(POLICY_FILE_NAME);
+    if policy_path.exists() {
+        return Err(format!(
+            "{} already exists; change it with a signed policy update",
+            policy_path.display()
+        ));
+    }
+
+    let (game_roots, mut skipped_roots) = usable_roots(&roots.game_roots);
+    let (vhd_roots, skipped_vhd) = usable_roots(&roots.vhd_roots);
+    let (mount_roots, skipped_mount) = usable_roots(&roots.mount_roots);
+    skipped_roots.extend(skipped_vhd);
+    skipped_roots.extend(skipped_mount);
+    let roots = BootstrapRoots {
+        game_roots,
+        vhd_roots,
+        mount_roots,
+    };
+
+    let key = load_or_create_sealed_key(key_path)?;
+    let public_key = B64.encode(key.verifying_key().as_bytes());
+    let update = sign_initial_policy(policy_template(key_id, &public_key, &roots), key_id, &key)
+        .map_err(|code| code.message().to_string())?;
+
+    config.bootstrap_public_keys = HashMap::from([(key_id.to_string(), public_key.clone())]);
+    let core = PrivExecCore::new(config).map_err(|e| e.to_string())?;
+    let response = core.apply_policy_update(update.clone());
+    if !response.ok {
+        return Err(format!("{}: {}", response.code, response.message));
+    }
+
+    Ok(BootstrapReport {
+        key_id: key_id.to_string(),
+        public_key,
+        key_path: key_path.to_string_lossy().to_string(),
+        policy_path: core.policy_path().to_string_lossy().to_string(),
+        roots,
+        skipped_roots,
+        update,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn installs_first_policy_once() {
+        let tmp = TempDir::new().unwrap();
+        let vhd_root = tmp.path().join("vhd");
+        let mount_root = tmp.path().join("mnt");
+        fs::create_dir_all(&vhd_root).unwrap();
+        fs::create_dir_all(&mount_root).unwrap();
+        let roots = BootstrapRoots {
+            game_roots: vec!["relative\\game".to_string()],
+            vhd_roots: vec![vhd_root.to_string_lossy().to_string(), String::new()],
+            mount_roots: vec![mount_root.to_string_lossy().to_string()],
+        };
+        let config = PrivExecConfig::new(tmp.path().join("root"), "device-1");
+        let key_path = tmp.path().join("operator.key");
+
+        let report = bootstrap_policy(config.clone(), &key_path, DEFAULT_KEY_ID, &roots).unwrap();
+        assert_eq!(report.skipped_roots, vec!["relative\\game"]);
+        assert_eq!(report.update.payload.version, 1);
+
+        let installed: PrivExecPolicy = serde_json::from_slice(&fs::read(&report.policy_path).unwrap()).unwrap();
+        assert_eq!(installed.security.public_keys[DEFAULT_KEY_ID], report.public_key);
+        let enabled = |name: &str| installed.allowed_commands.iter().any(|c| c.name == name && c.enabled);
+        assert!(enabled("mount_vhd"));
+        assert!(!enabled("collect_log"));
+        assert!(!installed.allowed_commands.iter().any(|c| c.name == "restart_service"));
+        #[cfg(feature = "vhd")]
+        {
+            let unlock = installed.allowed_commands.iter().find(|c| c.name == "unlock_bitlocker").unwrap();
+            let ParamRule::String { allow_values, .. } = &unlock.params["mountPoint"] else {
+                panic!("mountPoint is not a string rule");
+            };
+            let app_folder = crate::vhd::MountPoint::Folder(mount_root.join("app")).volume_name();
+            for value in ["D:", "X:", app_folder.as_str()] {
+                assert!(allow_values.iter().any(|v| v == value), "{value} is not allowed");
+            }
+            assert!(!allow_values.iter().any(|v| v == "C:"));
+        }
+
+        let err = bootstrap_policy(config, &key_path, DEFAULT_KEY_ID, &roots).unwrap_err();
+        assert!(err.contains("already exists"), "{}", err);
+    }
+}
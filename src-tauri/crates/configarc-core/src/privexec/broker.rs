@@ -0,0 +1,616 @@
+//! Resident elevated executor. The launcher starts it once with a single UAC prompt and
+//! then mounts game VHDs through signed `begin_session`/`mount_vhd`/`unmount_vhd` requests
+//! sent over a loopback socket, instead of spawning a new elevated helper per launch.
+//!
+//! A broker either follows the launcher that started it or, with `--resident`, outlives it
+//! so later launcher runs reuse it until the machine restarts.
+
+use super::client::RequestSigner;
+use super::hosts::HostsEntry;
+use super::results::MountVhdResult;
+use super::{
+    is_batch_request_json, ps_quote, write_json_atomic, CommandResponse, ParamRule, PolicyCommand, PolicyDefaultAction,
+    PolicySecurity, PrivExecConfig, PrivExecCore, PrivExecPolicy, SignedCommandRequest, SCHEMA_VERSION,
+};
+use crate::vhd::{process_alive, run_powershell, set_active_mount_points, MountPoints};
+use base64::{engine::general_purpose::STANDARD as B64, Engine as _};
+use chrono::Utc;
+use ed25519_dalek::SigningKey;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+const ENDPOINT_FILE_NAME: &str = "broker.json";
+const SIGNING_KEY_FILE_NAME: &str = "launcher.key";
+const LAUNCHER_KEY_ID: &str = "launcher";
+const REQUEST_TTL_SECONDS: i64 = 60;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Mounting waits for volumes to arrive and may retry with a repaired patch chain.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(120);
+/// Leaves the user time to answer the UAC prompt.
+const START_TIMEOUT: Duration = Duration::from_secs(60);
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Where a running broker listens, written to `broker.json` in its root directory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrokerEndpoint {
+    pub pid: u32,
+    pub port: u16,
+    pub started_at: String,
+    #[serde(default)]
+    pub resident: bool,
+}
+
+pub fn endpoint_path(root_dir: &Path) -> PathBuf {
+    root_dir.join(ENDPOINT_FILE_NAME)
+}
+
+pub fn read_endpoint(root_dir: &Path) -> Option<BrokerEndpoint> {
+    let data = fs::read_to_string(endpoint_path(root_dir)).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+/// Command-line options of the broker process, parsed from `--privexec-broker` invocations.
+#[derive(Debug, Clone)]
+pub struct BrokerOptions {
+    pub root_dir: PathBuf,
+    pub device_id: String,
+    /// Base64 ed25519 key the launcher signs its requests with.
+    pub public_key: String,
+    /// The broker unmounts everything and exits once this process is gone, unless resident.
+    pub parent_pid: Option<u32>,
+    /// Keep running after the parent exits, serving later launcher runs.
+    pub resident: bool,
+}
+
+impl BrokerOptions {
+    /// Returns `None` unless `--privexec-broker` is present with a root, device and key.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Option<Self> {
+        let mut enabled = false;
+        let mut resident = false;
+        let mut values: HashMap<String, String> = HashMap::new();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--privexec-broker" => enabled = true,
+                "--resident" => resident = true,
+                "--root" | "--device-id" | "--public-key" | "--parent-pid" => {
+                    if let Some(value) = args.next() {
+                        values.insert(arg, value);
+                    }
+                }
+                _ => {}
+            }
+        }
+        if !enabled {
+            return None;
+        }
+        Some(Self {
+            root_dir: PathBuf::from(values.remove("--root")?),
+            device_id: values.remove("--device-id")?,
+            public_key: values.remove("--public-key")?,
+            parent_pid: values.get("--parent-pid").and_then(|v| v.parse().ok()),
+            resident,
+        })
+    }
+
+    fn to_args(&self) -> Vec<String> {
+        let mut args = vec![
+            "--privexec-broker".to_string(),
+            "--root".to_string(),
+            self.root_dir.to_string_lossy().to_string(),
+            "--device-id".to_string(),
+            self.device_id.clone(),
+            "--public-key".to_string(),
+            self.public_key.clone(),
+        ];
+        if let Some(pid) = self.parent_pid {
+            args.push("--parent-pid".to_string());
+            args.push(pid.to_string());
+        }
+        if self.resident {
+            args.push("--resident".to_string());
+        }
+        args
+    }
+}
+
+fn string_param(required: bool) -> ParamRule {
+    ParamRule::String {
+        required,
+        default: None,
+        allow_values: Vec::new(),
+        fixed_value: None,
+    }
+}
+
+fn broker_command(name: &str, requires_session: bool, params: &[(&str, ParamRule)]) -> PolicyCommand {
+    PolicyCommand {
+        name: name.to_string(),
+        enabled: true,
+        requires_session,
+        risk_level: None,
+        params: params
+            .iter()
+            .map(|(key, rule)| (key.to_string(), rule.clone()))
+            .collect(),
+        timeout_seconds: None,
+    }
+}
+
+/// The broker only mounts and unmounts game VHDs and edits the launcher's hosts overrides, and
+/// only for holders of the launcher key.
+pub fn broker_policy(public_key: &str) -> PrivExecPolicy {
+    let session = ("sessionId", string_param(true));
+    let game = ("gameId", string_param(true));
+    let delta = (
+        "delta",
+        ParamRule::Bool {
+            required: false,
+            default: None,
+            fixed_value: None,
+        },
+    );
+    let owner = (
+        "ownerPid",
+        ParamRule::Int {
+            required: false,
+            default: None,
+            min: Some(1),
+            max: Some(i64::from(u32::MAX)),
+            fixed_value: None,
+        },
+    );
+    PrivExecPolicy {
+        schema_version: SCHEMA_VERSION,
+        policy_name: "configarc-broker".to_string(),
+        version: 1,
+        default_action: PolicyDefaultAction::Deny,
+        security: PolicySecurity {
+            public_keys: HashMap::from([(LAUNCHER_KEY_ID.to_string(), public_key.to_string())]),
+            ..PolicySecurity::default()
+        },
+        allowed_commands: vec![
+            broker_command("begin_session", false, &[]),
+            broker_command("heartbeat", false, std::slice::from_ref(&session)),
+            broker_command("end_session", false, std::slice::from_ref(&session)),
+            broker_command("mount_vhd", true, &[session.clone(), game.clone(), delta, owner]),
+            broker_command("unmount_vhd", true, &[session.clone(), game]),
+            broker_command("query_mounts", false, &[]),
+            broker_command("probe_capabilities", false, &[]),
+            broker_command(
+                "update_hosts_entries",
+                true,
+                &[session, ("entries", string_param(true)), ("gameId", string_param(false))],
+            ),
+        ],
+    }
+}
+
+/// Runs the broker until its parent exits, or for good when resident. Meant for an
+/// elevated process.
+pub fn run_broker(options: BrokerOptions) -> Result<(), String> {
+    let mut config = PrivExecConfig::new(&options.root_dir, &options.device_id);
+    config
+        .bootstrap_public_keys
+        .insert(LAUNCHER_KEY_ID.to_string(), options.public_key.clone());
+    let core = Arc::new(PrivExecCore::new(config).map_err(|e| e.to_string())?);
+    write_json_atomic(&core.policy_path(), &broker_policy(&options.public_key))
+        .map_err(|_| "Failed to write the broker policy".to_string())?;
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).map_err(|e| e.to_string())?;
+    let endpoint = BrokerEndpoint {
+        pid: std::process::id(),
+        port: listener.local_addr().map_err(|e| e.to_string())?.port(),
+        started_at: Utc::now().to_rfc3339(),
+        resident: options.resident,
+    };
+    let endpoint_json = serde_json::to_string_pretty(&endpoint).map_err(|e| e.to_string())?;
+    fs::write(endpoint_path(&options.root_dir), endpoint_json).map_err(|e| e.to_string())?;
+
+    let watched_core = core.clone();
+    let root_dir = options.root_dir.clone();
+    let exit_with = options.parent_pid.filter(|_| !options.resident);
+    thread::spawn(move || loop {
+        thread::sleep(WATCH_INTERVAL);
+        // A launcher that crashed mid-session must not leave its game mounted.
+        watched_core.release_orphaned_game_mounts();
+        if exit_with.is_some_and(|pid| !process_alive(pid)) {
+            watched_core.release_game_mounts();
+            let _ = fs::remove_file(endpoint_path(&root_dir));
+            std::process::exit(0);
+        }
+    });
+
+    serve(core, listener);
+    Ok(())
+}
+
+fn serve(core: Arc<PrivExecCore>, listener: TcpListener) {
+    for stream in listener.incoming().flatten() {
+        let core = core.clone();
+        thread::spawn(move || handle_connection(&core, stream));
+    }
+}
+
+/// One signed request or batch per line in, one response per line out.
+fn handle_connection(core: &PrivExecCore, stream: TcpStream) {
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            return;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let json = if is_batch_request_json(&line) {
+            serde_json::to_string(&core.execute_batch_request_json(&line))
+        } else {
+            serde_json::to_string(&core.execute_request_json(&line))
+        };
+        let Ok(json) = json else {
+            return;
+        };
+        if writeln!(writer, "{}", json).is_err() {
+            return;
+        }
+    }
+}
+
+/// Loads the launcher's signing key, creating it on first use.
+pub fn load_or_create_signing_key(root_dir: &Path) -> Result<SigningKey, String> {
+    let path = root_dir.join(SIGNING_KEY_FILE_NAME);
+    if let Ok(encoded) = fs::read_to_string(&path) {
+        let bytes: [u8; 32] = B64
+            .decode(encoded.trim())
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| format!("Invalid broker signing key: {}", path.display()))?;
+        return Ok(SigningKey::from_bytes(&bytes));
+    }
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).map_err(|e| e.to_string())?;
+    fs::create_dir_all(root_dir).map_err(|e| e.to_string())?;
+    fs::write(&path, B64.encode(bytes)).map_err(|e| e.to_string())?;
+    Ok(SigningKey::from_bytes(&bytes))
+}
+
+/// Signs requests for a running broker.
+#[derive(Clone)]
+pub struct BrokerClient {
+    endpoint: BrokerEndpoint,
+    device_id: String,
+    key: SigningKey,
+}
+
+impl BrokerClient {
+    /// Connects to the broker recorded under `root_dir`, if its process is still running.
+    pub fn connect(root_dir: &Path, device_id: &str) -> Result<Self, String> {
+        let endpoint = read_endpoint(root_dir).ok_or("The privileged broker is not running")?;
+        if !process_alive(endpoint.pid) {
+            return Err("The privileged broker has exited".to_string());
+        }
+        Ok(Self {
+            endpoint,
+            device_id: device_id.to_string(),
+            key: load_or_create_signing_key(root_dir)?,
+        })
+    }
+
+    /// Starts a broker in an elevated child (one UAC prompt) and waits until it is listening.
+    /// A resident broker keeps running after this process exits.
+    pub fn start_elevated(exe: &Path, root_dir: &Path, device_id: &str, resident: bool) -> Result<Self, String> {
+        if !cfg!(target_os = "windows") {
+            return Err("The privileged broker is only available on Windows".to_string());
+        }
+        let key = load_or_create_signing_key(root_dir)?;
+        let options = BrokerOptions {
+            root_dir: root_dir.to_path_buf(),
+            device_id: device_id.to_string(),
+            public_key: B64.encode(key.verifying_key().as_bytes()),
+            parent_pid: Some(std::process::id()),
+            resident,
+        };
+        let _ = fs::remove_file(endpoint_path(root_dir));
+        // Start-Process joins the list with spaces, so every argument is quoted for the child.
+        let arg_list = options
+            .to_args()
+            .iter()
+            .map(|a| ps_quote(&format!("\"{}\"", a)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let working_dir = std::env::current_dir().map_err(|e| e.to_string())?;
+        run_powershell(&format!(
+            "Start-Process -Verb RunAs -WindowStyle Hidden -FilePath {} -WorkingDirectory {} -ArgumentList @({}) | Out-Null",
+            ps_quote(&exe.to_string_lossy()),
+            ps_quote(&working_dir.to_string_lossy()),
+            arg_list
+        ))?;
+
+        let start = Instant::now();
+        while start.elapsed() < START_TIMEOUT {
+            if let Some(endpoint) = read_endpoint(root_dir).filter(|e| process_alive(e.pid)) {
+                return Ok(Self {
+                    endpoint,
+                    device_id: device_id.to_string(),
+                    key,
+                });
+            }
+            thread::sleep(Duration::from_millis(200));
+        }
+        Err("Timed out waiting for the privileged broker to start".to_string())
+    }
+
+    pub fn endpoint(&self) -> &BrokerEndpoint {
+        &self.endpoint
+    }
+
+    fn sign(&self, command: &str, params: Map<String, Value>) -> Result<SignedCommandRequest, String> {
+        RequestSigner::new(LAUNCHER_KEY_ID, self.key.clone(), &self.device_id)
+            .with_ttl_seconds(REQUEST_TTL_SECONDS)
+            .request(command, params)
+    }
+
+    /// Sends one signed command and returns its result, or the broker's error message.
+    pub fn call(&self, command: &str, params: Map<String, Value>) -> Result<Value, String> {
+        let request = serde_json::to_string(&self.sign(command, params)?).map_err(|e| e.to_string())?;
+        let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, self.endpoint.port));
+        let mut stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT).map_err(|e| e.to_string())?;
+        stream
+            .set_read_timeout(Some(RESPONSE_TIMEOUT))
+            .map_err(|e| e.to_string())?;
+        writeln!(stream, "{}", request).map_err(|e| e.to_string())?;
+        let mut line = String::new();
+        BufReader::new(stream)
+            .read_line(&mut line)
+            .map_err(|e| e.to_string())?;
+        let response: CommandResponse =
+            serde_json::from_str(&line).map_err(|_| "Invalid response from the privileged broker".to_string())?;
+        if !response.ok {
+            return Err(format!("{} ({})", response.message, response.code));
+        }
+        Ok(response.result.unwrap_or(Value::Null))
+    }
+
+    fn begin_session(&self) -> Result<(String, i64), String> {
+        let session = self.call("begin_session", Map::new())?;
+        let session_id = session
+            .get("sessionId")
+            .and_then(Value::as_str)
+            .ok_or("The privileged broker returned no session")?
+            .to_string();
+        let ttl_seconds = session.get("ttlSeconds").and_then(Value::as_i64).unwrap_or(120);
+        Ok((session_id, ttl_seconds))
+    }
+
+    fn session_call(&self, command: &str, session_id: &str, mut params: Map<String, Value>) -> Result<Value, String> {
+        params.insert("sessionId".to_string(), Value::String(session_id.to_string()));
+        self.call(command, params)
+    }
+
+    /// Runs `f` inside a fresh session and ends the session afterwards.
+    fn with_session<T>(&self, f: impl FnOnce(&str) -> Result<T, String>) -> Result<T, String> {
+        let (session_id, _) = self.begin_session()?;
+        let result = f(&session_id);
+        let _ = self.session_call("end_session", &session_id, Map::new());
+        result
+    }
+
+    /// Opens a session for a privileged sequence that may outlast the session TTL, such as
+    /// mounting a game's VHDs and unlocking them before launch. See [`BrokerSession`].
+    pub fn begin_keepalive_session(&self) -> Result<BrokerSession, String> {
+        let (session_id, ttl_seconds) = self.begin_session()?;
+        let id = Arc::new(Mutex::new(session_id));
+        let (stop, stopped) = mpsc::channel::<()>();
+        // A third of the TTL leaves room for two missed beats before the session lapses.
+        let interval = Duration::from_secs((ttl_seconds / 3).max(1) as u64);
+        let client = self.clone();
+        let current = id.clone();
+        let keepalive = thread::spawn(move || loop {
+            match stopped.recv_timeout(interval) {
+                Err(RecvTimeoutError::Timeout) => {}
+                _ => return,
+            }
+            let session_id = current.lock().unwrap().clone();
+            if let Err(err) = client.session_call("heartbeat", &session_id, Map::new()) {
+                // The broker restarted or the session lapsed; carry on in a new one.
+                eprintln!("[privexec] session heartbeat failed, reopening: {}", err);
+                if let Ok((reopened, _)) = client.begin_session() {
+                    *current.lock().unwrap() = reopened;
+                }
+            }
+        });
+        Ok(BrokerSession {
+            client: self.clone(),
+            id,
+            stop: Some(stop),
+            keepalive: Some(keepalive),
+        })
+    }
+
+    fn mount_in_session(&self, session_id: &str, game_id: &str, delta: bool) -> Result<MountPoints, String> {
+        let mut params = Map::new();
+        params.insert("gameId".to_string(), Value::String(game_id.to_string()));
+        params.insert("delta".to_string(), Value::Bool(delta));
+        params.insert("ownerPid".to_string(), Value::from(std::process::id()));
+        let result = self.session_call("mount_vhd", session_id, params)?;
+        let points: MountPoints = serde_json::from_value::<MountVhdResult>(result)
+            .ok()
+            .and_then(|result| result.mount_points)
+            .ok_or("The privileged broker returned no mount points")?;
+        set_active_mount_points(Some(points.clone()));
+        Ok(points)
+    }
+
+    fn unmount_in_session(&self, session_id: &str, game_id: &str) -> Result<(), String> {
+        set_active_mount_points(None);
+        let mut params = Map::new();
+        params.insert("gameId".to_string(), Value::String(game_id.to_string()));
+        self.session_call("unmount_vhd", session_id, params).map(|_| ())
+    }
+
+    /// Mounts a game's VHD set in the broker. `delta` overrides the game's runtime delta setting.
+    pub fn mount_game_vhd(&self, game_id: &str, delta: bool) -> Result<MountPoints, String> {
+        self.with_session(|session_id| self.mount_in_session(session_id, game_id, delta))
+    }
+
+    pub fn unmount_game_vhd(&self, game_id: &str) -> Result<(), String> {
+        self.with_session(|session_id| self.unmount_in_session(session_id, game_id))
+    }
+
+    /// Makes `entries` the hosts overrides of `game_id`, or all of the launcher's overrides
+    /// when `None`, and returns every override in the hosts file afterwards.
+    pub fn update_hosts_entries(&self, game_id: Option<&str>, entries: &[HostsEntry]) -> Result<Vec<HostsEntry>, String> {
+        let mut params = Map::new();
+        params.insert(
+            "entries".to_string(),
+            Value::String(serde_json::to_string(entries).map_err(|e| e.to_string())?),
+        );
+        if let Some(game_id) = game_id {
+            params.insert("gameId".to_string(), Value::String(game_id.to_string()));
+        }
+        let result = self.with_session(|session_id| self.session_call("update_hosts_entries", session_id, params))?;
+        Ok(result
+            .get("entries")
+            .cloned()
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default())
+    }
+
+    /// Games whose VHD set the broker currently holds.
+    pub fn mounted_games(&self) -> Result<Vec<String>, String> {
+        let result = self.call("query_mounts", Map::new())?;
+        Ok(result
+            .get("gameIds")
+            .cloned()
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default())
+    }
+}
+
+/// A broker session held open for a privileged sequence. A background thread heartbeats it
+/// until the sequence is finished, so no step fails because the steps before it outlasted the
+/// TTL. The session ends when finished or dropped.
+pub struct BrokerSession {
+    client: BrokerClient,
+    id: Arc<Mutex<String>>,
+    stop: Option<mpsc::Sender<()>>,
+    keepalive: Option<JoinHandle<()>>,
+}
+
+impl BrokerSession {
+    pub fn session_id(&self) -> String {
+        self.id.lock().unwrap().clone()
+    }
+
+    pub fn client(&self) -> &BrokerClient {
+        &self.client
+    }
+
+    /// [`BrokerClient::mount_game_vhd`] inside this session.
+    pub fn mount_game_vhd(&self, game_id: &str, delta: bool) -> Result<MountPoints, String> {
+        self.client.mount_in_session(&self.session_id(), game_id, delta)
+    }
+
+    pub fn unmount_game_vhd(&self, game_id: &str) -> Result<(), String> {
+        self.client.unmount_in_session(&self.session_id(), game_id)
+    }
+
+    /// Stops the heartbeats and ends the session.
+    pub fn finish(mut self) {
+        self.stop_keepalive();
+    }
+
+    fn stop_keepalive(&mut self) {
+        let Some(stop) = self.stop.take() else {
+            return;
+        };
+        drop(stop);
+        if let Some(keepalive) = self.keepalive.take() {
+            let _ = keepalive.join();
+        }
+        let _ = self.client.session_call("end_session", &self.session_id(), Map::new());
+    }
+}
+
+impl Drop for BrokerSession {
+    fn drop(&mut self) {
+        self.stop_keepalive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn serves_signed_session_requests_over_loopback() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().to_path_buf();
+        let key = load_or_create_signing_key(&root).unwrap();
+        let public_key = B64.encode(key.verifying_key().as_bytes());
+        assert_eq!(load_or_create_signing_key(&root).unwrap().to_bytes(), key.to_bytes());
+
+        let args = BrokerOptions {
+            root_dir: root.clone(),
+            device_id: "device-1".to_string(),
+            public_key: public_key.clone(),
+            parent_pid: None,
+            resident: true,
+        }
+        .to_args();
+        let options = BrokerOptions::from_args(args).unwrap();
+        assert_eq!(options.public_key, public_key);
+        assert!(options.resident);
+
+        let core = Arc::new(PrivExecCore::new(PrivExecConfig::new(&root, "device-1")).unwrap());
+        write_json_atomic(&core.policy_path(), &broker_policy(&public_key)).unwrap();
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let endpoint = BrokerEndpoint {
+            pid: std::process::id(),
+            port: listener.local_addr().unwrap().port(),
+            started_at: Utc::now().to_rfc3339(),
+            resident: true,
+        };
+        fs::write(endpoint_path(&root), serde_json::to_string(&endpoint).unwrap()).unwrap();
+        thread::spawn(move || serve(core, listener));
+
+        let client = BrokerClient::connect(&root, "device-1").unwrap();
+        let session_id = client.with_session(|id| Ok(id.to_string())).unwrap();
+        assert!(!session_id.is_empty());
+        assert!(client.mounted_games().unwrap().is_empty());
+
+        let mut params = Map::new();
+        params.insert("gameId".to_string(), Value::String("game".to_string()));
+        let err = client.call("mount_vhd", params).unwrap_err();
+        assert!(err.contains("SESSION_REQUIRED"), "{}", err);
+
+        let session = client.begin_keepalive_session().unwrap();
+        let session_id = session.session_id();
+        let err = session.unmount_game_vhd("game").unwrap_err();
+        assert!(err.contains("PATH_NOT_FOUND"), "{}", err);
+        session.finish();
+        let err = client.session_call("heartbeat", &session_id, Map::new()).unwrap_err();
+        assert!(err.contains("SESSION_NOT_FOUND"), "{}", err);
+
+        let stranger = BrokerClient {
+            endpoint: client.endpoint().clone(),
+            device_id: "device-1".to_string(),
+            key: SigningKey::from_bytes(&[9u8; 32]),
+        };
+        let err = stranger.call("begin_session", Map::new()).unwrap_err();
+        assert!(err.contains("INVALID_SIGNATURE"), "{}", err);
+    }
+}
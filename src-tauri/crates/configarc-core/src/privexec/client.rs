@@ -0,0 +1,679 @@
+//! The caller's side of the executor: builds request payloads with a fresh command id, nonce
+//! and expiry, signs them over their canonical JSON with an ed25519 key kept sealed on disk,
+//! opens and refreshes sessions for the commands that need one and wraps every command in a
+//! typed call, so integrators never hand-roll signed JSON.
+//!
+//! Requests travel over a [`RequestTransport`]: [`LoopbackTransport`] speaks the broker's
+//! line protocol, and a [`PrivExecCore`] in the same process serves them directly.
+
+use super::{
+    hex_char, BatchCommand, BatchRequestPayload, BatchResponse, CommandRequestPayload, CommandResponse, PrivExecCore,
+    PrivExecErrorCode, SignatureEnvelope, SignedBatchRequest, SignedCommandRequest, SCHEMA_VERSION,
+};
+use super::hosts::HostsEntry;
+use super::results::{BitLockerStatus, DiskList, HostCapabilities, MountVhdResult};
+use crate::sealed;
+use base64::{engine::general_purpose::STANDARD as B64, Engine as _};
+use chrono::{DateTime, Duration, Utc};
+use ed25519_dalek::{Signer, SigningKey};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpStream};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Lifetime of a request, well inside the executor's default nonce TTL.
+pub const DEFAULT_REQUEST_TTL_SECONDS: i64 = 60;
+/// Leads a sealed client key file; the digit is the format version.
+const KEY_FILE_MAGIC: &[u8] = b"CAPK1";
+const KEY_ENTROPY: &[u8] = b"ConfigArc privexec client key";
+/// A session closer than this to expiry gets a heartbeat before it is used again.
+const SESSION_REFRESH_SECONDS: i64 = 15;
+
+fn random_hex() -> Result<String, String> {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).map_err(|e| e.to_string())?;
+    Ok(bytes
+        .iter()
+        .flat_map(|b| [hex_char(b >> 4), hex_char(b & 0x0f)])
+        .collect())
+}
+
+/// Loads the ed25519 key sealed at `path` (DPAPI on Windows), creating it on first use.
+pub fn load_or_create_sealed_key(path: &Path) -> Result<SigningKey, String> {
+    match fs::read(path) {
+        Ok(raw) => {
+            let body = raw
+                .strip_prefix(KEY_FILE_MAGIC)
+                .ok_or_else(|| format!("{} is not a privexec key file", path.display()))?;
+            let seed: [u8; 32] = sealed::open(body, path, KEY_ENTROPY)?
+                .try_into()
+                .map_err(|_| format!("{} does not hold an ed25519 key", path.display()))?;
+            Ok(SigningKey::from_bytes(&seed))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let mut seed = [0u8; 32];
+            getrandom::getrandom(&mut seed).map_err(|e| e.to_string())?;
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            let body = sealed::seal(&seed, path, KEY_ENTROPY)?;
+            fs::write(path, [KEY_FILE_MAGIC, body.as_slice()].concat()).map_err(|e| e.to_string())?;
+            Ok(SigningKey::from_bytes(&seed))
+        }
+        Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+    }
+}
+
+/// Builds and signs requests as one key of a policy's `publicKeys`.
+pub struct RequestSigner {
+    key_id: String,
+    key: SigningKey,
+    device_id: String,
+    ttl_seconds: i64,
+}
+
+impl RequestSigner {
+    pub fn new(key_id: impl Into<String>, key: SigningKey, device_id: impl Into<String>) -> Self {
+        Self {
+            key_id: key_id.into(),
+            key,
+            device_id: device_id.into(),
+            ttl_seconds: DEFAULT_REQUEST_TTL_SECONDS,
+        }
+    }
+
+    pub fn with_ttl_seconds(mut self, ttl_seconds: i64) -> Self {
+        self.ttl_seconds = ttl_seconds.max(1);
+        self
+    }
+
+    /// Base64 public key to list under this signer's key id in a policy.
+    pub fn public_key(&self) -> String {
+        B64.encode(self.key.verifying_key().as_bytes())
+    }
+
+    /// A payload for `command` that is valid from now for the signer's TTL.
+    pub fn payload(&self, command: &str, params: Map<String, Value>) -> Result<CommandRequestPayload, String> {
+        let issued_at = Utc::now();
+        Ok(CommandRequestPayload {
+            schema_version: SCHEMA_VERSION,
+            command_id: random_hex()?,
+            nonce: random_hex()?,
+            issued_at,
+            expires_at: issued_at + Duration::seconds(self.ttl_seconds),
+            device_id: self.device_id.clone(),
+            command: command.to_string(),
+            params,
+        })
+    }
+
+    fn envelope(&self, bytes: Result<Vec<u8>, PrivExecErrorCode>) -> Result<SignatureEnvelope, String> {
+        let bytes = bytes.map_err(|code| code.message().to_string())?;
+        Ok(SignatureEnvelope {
+            algorithm: "ed25519".to_string(),
+            key_id: self.key_id.clone(),
+            signature: B64.encode(self.key.sign(&bytes).to_bytes()),
+        })
+    }
+
+    pub fn sign(&self, payload: CommandRequestPayload) -> Result<SignedCommandRequest, String> {
+        let signature = self.envelope(payload.signing_bytes())?;
+        Ok(SignedCommandRequest { payload, signature })
+    }
+
+    pub fn request(&self, command: &str, params: Map<String, Value>) -> Result<SignedCommandRequest, String> {
+        self.sign(self.payload(command, params)?)
+    }
+
+    /// A batch of `steps`, each with its own command id, valid from now for the signer's TTL.
+    pub fn batch_payload(&self, steps: Vec<(String, Map<String, Value>)>) -> Result<BatchRequestPayload, String> {
+        let issued_at = Utc::now();
+        let commands = steps
+            .into_iter()
+            .map(|(command, params)| {
+                Ok(BatchCommand {
+                    command_id: random_hex()?,
+                    command,
+                    params,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(BatchRequestPayload {
+            schema_version: SCHEMA_VERSION,
+            batch_id: random_hex()?,
+            nonce: random_hex()?,
+            issued_at,
+            expires_at: issued_at + Duration::seconds(self.ttl_seconds),
+            device_id: self.device_id.clone(),
+            commands,
+        })
+    }
+
+    pub fn sign_batch(&self, payload: BatchRequestPayload) -> Result<SignedBatchRequest, String> {
+        let signature = self.envelope(payload.signing_bytes())?;
+        Ok(SignedBatchRequest { payload, signature })
+    }
+}
+
+/// Carries a signed request to an executor and brings back its response.
+pub trait RequestTransport: Send + Sync {
+    fn send(&self, request: &SignedCommandRequest) -> Result<CommandResponse, String>;
+
+    fn send_batch(&self, request: &SignedBatchRequest) -> Result<BatchResponse, String>;
+}
+
+impl RequestTransport for PrivExecCore {
+    fn send(&self, request: &SignedCommandRequest) -> Result<CommandResponse, String> {
+        Ok(self.execute_request(request.clone()))
+    }
+
+    fn send_batch(&self, request: &SignedBatchRequest) -> Result<BatchResponse, String> {
+        Ok(self.execute_batch_request(request.clone()))
+    }
+}
+
+/// The broker's wire format: one request per line over a loopback socket, one response line
+/// back.
+#[derive(Debug, Clone)]
+pub struct LoopbackTransport {
+    pub port: u16,
+    pub connect_timeout: std::time::Duration,
+    /// Mounting waits for volumes to arrive, so responses may take a while.
+    pub response_timeout: std::time::Duration,
+}
+
+impl LoopbackTransport {
+    pub fn new(port: u16) -> Self {
+        Self {
+            port,
+            connect_timeout: std::time::Duration::from_secs(5),
+            response_timeout: std::time::Duration::from_secs(120),
+        }
+    }
+}
+
+impl LoopbackTransport {
+    fn round_trip<R: DeserializeOwned>(&self, request: &impl Serialize) -> Result<R, String> {
+        let line = serde_json::to_string(request).map_err(|e| e.to_string())?;
+        let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, self.port));
+        let mut stream = TcpStream::connect_timeout(&addr, self.connect_timeout).map_err(|e| e.to_string())?;
+        stream
+            .set_read_timeout(Some(self.response_timeout))
+            .map_err(|e| e.to_string())?;
+        writeln!(stream, "{}", line).map_err(|e| e.to_string())?;
+        let mut response = String::new();
+        BufReader::new(stream)
+            .read_line(&mut response)
+            .map_err(|e| e.to_string())?;
+        serde_json::from_str(&response).map_err(|_| "Invalid response from the privileged executor".to_string())
+    }
+}
+
+impl RequestTransport for LoopbackTransport {
+    fn send(&self, request: &SignedCommandRequest) -> Result<CommandResponse, String> {
+        self.round_trip(request)
+    }
+
+    fn send_batch(&self, request: &SignedBatchRequest) -> Result<BatchResponse, String> {
+        self.round_trip(request)
+    }
+}
+
+/// A failed call. `code` is the executor's error code, e.g. `POLICY_DENY`; `None` when the
+/// request never got an answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientError {
+    pub code: Option<String>,
+    pub message: String,
+}
+
+impl ClientError {
+    pub fn is(&self, code: PrivExecErrorCode) -> bool {
+        self.code.as_deref() == Some(code.as_str())
+    }
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.code {
+            Some(code) => write!(f, "{} ({})", self.message, code),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<String> for ClientError {
+    fn from(message: String) -> Self {
+        Self { code: None, message }
+    }
+}
+
+/// A batch that stopped at a failed step. `completed` holds the results of the steps before
+/// it, which stay done.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchError {
+    pub completed: Vec<Value>,
+    pub error: ClientError,
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Step {} of the batch failed: {}", self.completed.len() + 1, self.error)
+    }
+}
+
+impl std::error::Error for BatchError {}
+
+/// Key to unlock a BitLocker volume with.
+#[derive(Debug, Clone)]
+pub enum BitLockerSecret {
+    RecoveryPassword(String),
+    Password(String),
+}
+
+struct Session {
+    id: String,
+    expires_at: DateTime<Utc>,
+}
+
+impl Session {
+    fn from_result(result: &Value) -> Option<Self> {
+        let id = result.get("sessionId")?.as_str()?.to_string();
+        let expires_at = result
+            .get("expiresAt")
+            .cloned()
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_else(Utc::now);
+        Some(Self { id, expires_at })
+    }
+}
+
+fn params<const N: usize>(pairs: [(&str, Value); N]) -> Map<String, Value> {
+    pairs.into_iter().map(|(key, value)| (key.to_string(), value)).collect()
+}
+
+/// Signed calls to one executor. A session is opened the first time a command asks for one,
+/// kept alive with heartbeats and reused by every later call that needs it; it is ended when
+/// the client is dropped.
+pub struct PrivExecClient<T: RequestTransport> {
+    signer: RequestSigner,
+    transport: T,
+    session: Mutex<Option<Session>>,
+    /// Commands the executor answered with `SESSION_REQUIRED`; they get a session up front.
+    session_commands: Mutex<HashSet<String>>,
+}
+
+impl<T: RequestTransport> PrivExecClient<T> {
+    pub fn new(signer: RequestSigner, transport: T) -> Self {
+        Self {
+            signer,
+            transport,
+            session: Mutex::new(None),
+            session_commands: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub fn signer(&self) -> &RequestSigner {
+        &self.signer
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Sends `command` exactly once, as given, and returns its result.
+    pub fn call_raw(&self, command: &str, params: Map<String, Value>) -> Result<Value, ClientError> {
+        let response = self.transport.send(&self.signer.request(command, params)?)?;
+        if !response.ok {
+            return Err(ClientError {
+                code: Some(response.code),
+                message: response.message,
+            });
+        }
+        Ok(response.result.unwrap_or(Value::Null))
+    }
+
+    /// [`call`](Self::call) for the commands with a typed result.
+    fn call_typed<R: DeserializeOwned>(&self, command: &str, params: Map<String, Value>) -> Result<R, ClientError> {
+        let result = self.call(command, params)?;
+        serde_json::from_value(result)
+            .map_err(|e| ClientError::from(format!("Unexpected {} result from the privileged executor: {}", command, e)))
+    }
+
+    /// Sends `command`, inside a session when the executor asks for one.
+    pub fn call(&self, command: &str, params: Map<String, Value>) -> Result<Value, ClientError> {
+        let needs_session = self.session_commands.lock().unwrap().contains(command);
+        if !needs_session {
+            match self.call_raw(command, params.clone()) {
+                Err(e) if e.is(PrivExecErrorCode::SessionRequired) => {
+                    self.session_commands.lock().unwrap().insert(command.to_string());
+                }
+                other => return other,
+            }
+        }
+        let mut retried = false;
+        loop {
+            let mut with_session = params.clone();
+            with_session.insert("sessionId".to_string(), Value::String(self.session_id()?));
+            match self.call_raw(command, with_session) {
+                // The executor restarted or the session lapsed between calls; open a new one.
+                Err(e)
+                    if !retried
+                        && (e.is(PrivExecErrorCode::SessionExpired) || e.is(PrivExecErrorCode::SessionNotFound)) =>
+                {
+                    *self.session.lock().unwrap() = None;
+                    retried = true;
+                }
+                other => return other,
+            }
+        }
+    }
+
+    /// Sends `steps` exactly once, as given, as one signed batch.
+    pub fn call_batch_raw(&self, steps: Vec<(String, Map<String, Value>)>) -> Result<BatchResponse, ClientError> {
+        let request = self.signer.sign_batch(self.signer.batch_payload(steps)?)?;
+        Ok(self.transport.send_batch(&request)?)
+    }
+
+    /// Runs `steps` in order as one signed batch, stopping at the first that fails. Steps the
+    /// executor wants a session for get one the same way [`Self::call`] does; when a step
+    /// stops the batch for lack of a valid session, the rest is resent from that step on.
+    pub fn call_batch(&self, steps: Vec<(&str, Map<String, Value>)>) -> Result<Vec<Value>, BatchError> {
+        let mut completed = Vec::with_capacity(steps.len());
+        let mut pending = steps;
+        let mut retried = false;
+        while !pending.is_empty() {
+            let session_commands = self.session_commands.lock().unwrap().clone();
+            let mut signed = Vec::with_capacity(pending.len());
+            for (command, params) in &pending {
+                let mut params = params.clone();
+                if session_commands.contains(*command) {
+                    match self.session_id() {
+                        Ok(id) => params.insert("sessionId".to_string(), Value::String(id)),
+                        Err(error) => return Err(BatchError { completed, error }),
+                    };
+                }
+                signed.push((command.to_string(), params));
+            }
+            let response = match self.call_batch_raw(signed) {
+                Ok(response) => response,
+                Err(error) => return Err(BatchError { completed, error }),
+            };
+
+            let ran = response.results.len();
+            for result in response.results.into_iter().take_while(|result| result.ok) {
+                completed.push(result.result.unwrap_or(Value::Null));
+            }
+            if response.ok {
+                return Ok(completed);
+            }
+            let error = ClientError {
+                code: Some(response.code),
+                message: response.message,
+            };
+            let failed_at = ran.saturating_sub(1);
+            let command = pending.get(failed_at).map(|(command, _)| *command).unwrap_or_default();
+            let session_error = ran > 0
+                && !retried
+                && ((error.is(PrivExecErrorCode::SessionRequired) && !session_commands.contains(command))
+                    || error.is(PrivExecErrorCode::SessionExpired)
+                    || error.is(PrivExecErrorCode::SessionNotFound));
+            if !session_error {
+                return Err(BatchError { completed, error });
+            }
+            if error.is(PrivExecErrorCode::SessionRequired) {
+                self.session_commands.lock().unwrap().insert(command.to_string());
+            } else {
+                *self.session.lock().unwrap() = None;
+            }
+            retried = true;
+            pending.drain(..failed_at);
+        }
+        Ok(completed)
+    }
+
+    fn session_id(&self) -> Result<String, ClientError> {
+        let mut session = self.session.lock().unwrap();
+        if let Some(current) = session.as_mut() {
+            if current.expires_at - Utc::now() > Duration::seconds(SESSION_REFRESH_SECONDS) {
+                return Ok(current.id.clone());
+            }
+            let heartbeat = self.call_raw("heartbeat", params([("sessionId", Value::String(current.id.clone()))]));
+            if let Some(refreshed) = heartbeat.ok().as_ref().and_then(Session::from_result) {
+                *current = refreshed;
+                return Ok(current.id.clone());
+            }
+        }
+        let started = self.call_raw("begin_session", Map::new())?;
+        let opened = Session::from_result(&started).ok_or_else(|| "The privileged executor returned no session".to_string())?;
+        let id = opened.id.clone();
+        *session = Some(opened);
+        Ok(id)
+    }
+
+    /// Ends the open session, if any. Later calls open a new one when needed.
+    pub fn end_session(&self) -> Result<(), ClientError> {
+        let Some(session) = self.session.lock().unwrap().take() else {
+            return Ok(());
+        };
+        self.call_raw("end_session", params([("sessionId", Value::String(session.id))]))
+            .map(|_| ())
+    }
+
+    pub fn mount_vhd(&self, path: &str, read_only: bool, mount_point: Option<&str>) -> Result<MountVhdResult, ClientError> {
+        let mut args = params([("path", Value::from(path)), ("readOnly", Value::Bool(read_only))]);
+        if let Some(mount_point) = mount_point {
+            args.insert("mountPoint".to_string(), Value::from(mount_point));
+        }
+        self.call_typed("mount_vhd", args)
+    }
+
+    pub fn unmount_vhd(&self, path: &str) -> Result<Value, ClientError> {
+        self.call("unmount_vhd", params([("path", Value::from(path))]))
+    }
+
+    /// Mounts a game's whole VHD set; the executor releases it if this process exits first.
+    /// `delta` overrides the game's runtime delta setting.
+    pub fn mount_game_vhd(&self, game_id: &str, delta: Option<bool>) -> Result<MountVhdResult, ClientError> {
+        let mut args = params([
+            ("gameId", Value::from(game_id)),
+            ("ownerPid", Value::from(std::process::id())),
+        ]);
+        if let Some(delta) = delta {
+            args.insert("delta".to_string(), Value::Bool(delta));
+        }
+        self.call_typed("mount_vhd", args)
+    }
+
+    pub fn unmount_game_vhd(&self, game_id: &str) -> Result<Value, ClientError> {
+        self.call("unmount_vhd", params([("gameId", Value::from(game_id))]))
+    }
+
+    /// Games whose VHD set the executor currently holds.
+    pub fn query_mounts(&self) -> Result<Vec<String>, ClientError> {
+        let result = self.call("query_mounts", Map::new())?;
+        Ok(result
+            .get("gameIds")
+            .cloned()
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default())
+    }
+
+    pub fn query_disk(&self) -> Result<DiskList, ClientError> {
+        self.call_typed("query_disk", Map::new())
+    }
+
+    /// Which PowerShell modules and rights the executor's host has, e.g. no BitLocker on Home.
+    pub fn probe_capabilities(&self) -> Result<HostCapabilities, ClientError> {
+        self.call_typed("probe_capabilities", Map::new())
+    }
+
+    pub fn query_bitlocker_status(&self, mount_point: &str) -> Result<BitLockerStatus, ClientError> {
+        self.call_typed("query_bitlocker_status", params([("mountPoint", Value::from(mount_point))]))
+    }
+
+    pub fn unlock_bitlocker(
+        &self,
+        mount_point: &str,
+        secret: BitLockerSecret,
+        skip_if_unlocked: bool,
+    ) -> Result<BitLockerStatus, ClientError> {
+        let (key, value) = match secret {
+            BitLockerSecret::RecoveryPassword(value) => ("recoveryPassword", value),
+            BitLockerSecret::Password(value) => ("password", value),
+        };
+        self.call_typed(
+            "unlock_bitlocker",
+            params([
+                ("mountPoint", Value::from(mount_point)),
+                (key, Value::String(value)),
+                ("skipIfUnlocked", Value::Bool(skip_if_unlocked)),
+            ]),
+        )
+    }
+
+    pub fn lock_bitlocker(&self, mount_point: &str, force_dismount: bool) -> Result<Value, ClientError> {
+        self.call(
+            "lock_bitlocker",
+            params([
+                ("mountPoint", Value::from(mount_point)),
+                ("forceDismount", Value::Bool(force_dismount)),
+            ]),
+        )
+    }
+
+    pub fn query_service_status(&self, service_name: &str) -> Result<Value, ClientError> {
+        self.call("query_service_status", params([("serviceName", Value::from(service_name))]))
+    }
+
+    /// Makes `entries` the hosts overrides of `game_id`, or all of the launcher's overrides
+    /// when `None`. The result holds the `previous` and current `entries`.
+    pub fn update_hosts_entries(&self, game_id: Option<&str>, entries: &[HostsEntry]) -> Result<Value, ClientError> {
+        let entries = serde_json::to_string(entries).map_err(|e| e.to_string())?;
+        let mut args = params([("entries", Value::String(entries))]);
+        if let Some(game_id) = game_id {
+            args.insert("gameId".to_string(), Value::from(game_id));
+        }
+        self.call("update_hosts_entries", args)
+    }
+
+    /// Reads the tail of a log file, up to `max_bytes` (1 MiB when `None`).
+    pub fn collect_log(&self, path: &str, max_bytes: Option<u64>) -> Result<Value, ClientError> {
+        let mut args = params([("path", Value::from(path))]);
+        if let Some(max_bytes) = max_bytes {
+            args.insert("maxBytes".to_string(), Value::from(max_bytes));
+        }
+        self.call("collect_log", args)
+    }
+}
+
+impl<T: RequestTransport> Drop for PrivExecClient<T> {
+    fn drop(&mut self) {
+        let _ = self.end_session();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        CommandRunner, ParamRule, PolicyCommand, PolicyDefaultAction, PolicySecurity, PrivExecConfig, PrivExecPolicy,
+        RunnerOutput,
+    };
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    struct ServiceRunner;
+
+    impl CommandRunner for ServiceRunner {
+        fn run_powershell(&self, _script: &str) -> Result<RunnerOutput, String> {
+            Ok(RunnerOutput {
+                status_code: 0,
+                stdout: r#"{"Name":"TermService","Status":"Running"}"#.to_string(),
+                stderr: String::new(),
+            })
+        }
+    }
+
+    fn command(name: &str, requires_session: bool, params: &[&str]) -> PolicyCommand {
+        PolicyCommand {
+            name: name.to_string(),
+            enabled: true,
+            requires_session,
+            risk_level: None,
+            params: params
+                .iter()
+                .map(|key| {
+                    let rule = ParamRule::String {
+                        required: true,
+                        default: None,
+                        allow_values: Vec::new(),
+                        fixed_value: None,
+                    };
+                    (key.to_string(), rule)
+                })
+                .collect(),
+            timeout_seconds: None,
+        }
+    }
+
+    #[test]
+    fn signs_requests_and_opens_sessions_on_demand() {
+        let tmp = TempDir::new().unwrap();
+        let key_path = tmp.path().join("client.key");
+        let key = load_or_create_sealed_key(&key_path).unwrap();
+        assert_eq!(load_or_create_sealed_key(&key_path).unwrap().to_bytes(), key.to_bytes());
+        assert!(!fs::read(&key_path).unwrap().windows(32).any(|w| w == key.to_bytes()));
+
+        let signer = RequestSigner::new("client", key, "device-1");
+        let core = PrivExecCore::with_runner(PrivExecConfig::new(tmp.path().join("root"), "device-1"), Arc::new(ServiceRunner))
+            .unwrap();
+        let policy = PrivExecPolicy {
+            schema_version: SCHEMA_VERSION,
+            policy_name: "client-test".to_string(),
+            version: 1,
+            default_action: PolicyDefaultAction::Deny,
+            security: PolicySecurity {
+                public_keys: HashMap::from([("client".to_string(), signer.public_key())]),
+                ..PolicySecurity::default()
+            },
+            allowed_commands: vec![
+                command("begin_session", false, &[]),
+                command("heartbeat", false, &["sessionId"]),
+                command("end_session", false, &["sessionId"]),
+                command("query_service_status", true, &["sessionId", "serviceName"]),
+            ],
+        };
+        fs::write(core.policy_path(), serde_json::to_vec(&policy).unwrap()).unwrap();
+
+        let client = PrivExecClient::new(signer, core);
+        let status = client.query_service_status("TermService").unwrap();
+        assert_eq!(status["Status"], "Running");
+        assert!(client.session_commands.lock().unwrap().contains("query_service_status"));
+        let session = client.session.lock().unwrap().as_ref().map(|s| s.id.clone()).unwrap();
+        client.query_service_status("TermService").unwrap();
+        assert_eq!(client.session.lock().unwrap().as_ref().map(|s| s.id.clone()), Some(session));
+
+        client.end_session().unwrap();
+        assert!(client.session.lock().unwrap().is_none());
+        let err = client.query_disk().unwrap_err();
+        assert!(err.is(PrivExecErrorCode::PolicyDeny), "{}", err);
+
+        let service = params([("serviceName", Value::from("TermService"))]);
+        let err = client
+            .call_batch(vec![("query_service_status", service.clone()), ("query_disk", Map::new()), ("query_service_status", service)])
+            .unwrap_err();
+        assert_eq!(err.completed.len(), 1);
+        assert_eq!(err.completed[0]["Status"], "Running");
+        assert!(err.error.is(PrivExecErrorCode::PolicyDeny), "{}", err);
+    }
+}
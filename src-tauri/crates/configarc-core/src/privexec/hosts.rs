@@ -0,0 +1,197 @@
+//! Host overrides the launcher keeps in the system hosts file, for servers `replaceHost` cannot
+//! redirect. They live in one marked block, so lines the user wrote are never touched and the
+//! block can be rewritten or dropped as a whole. Each line records the game it was added for.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+
+const BLOCK_BEGIN: &str = "# BEGIN ConfigArc Launcher";
+const BLOCK_END: &str = "# END ConfigArc Launcher";
+const GAME_TAG: &str = "# game:";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HostsEntry {
+    pub address: String,
+    pub host: String,
+    /// Game the override was added for; cleared with that game's config.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub game_id: Option<String>,
+}
+
+/// `%SystemRoot%\System32\drivers\etc\hosts`.
+pub fn system_hosts_path() -> PathBuf {
+    let root = std::env::var_os("SystemRoot").unwrap_or_else(|| "C:\\Windows".into());
+    PathBuf::from(root).join("System32").join("drivers").join("etc").join("hosts")
+}
+
+pub fn validate_hosts_entry(entry: &HostsEntry) -> Result<(), String> {
+    if entry.address.trim().parse::<IpAddr>().is_err() {
+        return Err(format!("{} is not an IP address", entry.address));
+    }
+    let host = entry.host.trim();
+    let valid_label = |label: &str| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    };
+    if host.is_empty() || host.len() > 253 || !host.split('.').all(valid_label) {
+        return Err(format!("{} is not a host name", entry.host));
+    }
+    if let Some(game_id) = &entry.game_id {
+        if game_id.is_empty() || !game_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(format!("{} is not a game id", game_id));
+        }
+    }
+    Ok(())
+}
+
+fn is_block_line(line: &str, marker: &str) -> bool {
+    line.trim().eq_ignore_ascii_case(marker)
+}
+
+/// The launcher's entries in hosts file `content`.
+pub fn managed_entries(content: &str) -> Vec<HostsEntry> {
+    let mut inside = false;
+    let mut entries = Vec::new();
+    for line in content.lines() {
+        if is_block_line(line, BLOCK_BEGIN) {
+            inside = true;
+        } else if is_block_line(line, BLOCK_END) {
+            inside = false;
+        } else if inside {
+            let (mapping, game_id) = match line.split_once(GAME_TAG) {
+                Some((mapping, game)) => (mapping, Some(game.trim().to_string())),
+                None => (line.split('#').next().unwrap_or_default(), None),
+            };
+            let mut fields = mapping.split_whitespace();
+            if let (Some(address), Some(host)) = (fields.next(), fields.next()) {
+                entries.push(HostsEntry {
+                    address: address.to_string(),
+                    host: host.to_string(),
+                    game_id: game_id.filter(|id| !id.is_empty()),
+                });
+            }
+        }
+    }
+    entries
+}
+
+/// `content` with the launcher's block holding exactly `entries`, or without a block when
+/// there are none. Lines outside the block and the file's line endings are kept.
+pub fn render_hosts(content: &str, entries: &[HostsEntry]) -> String {
+    let newline = if content.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut lines = Vec::new();
+    let mut inside = false;
+    for line in content.lines() {
+        if is_block_line(line, BLOCK_BEGIN) {
+            inside = true;
+        } else if is_block_line(line, BLOCK_END) {
+            inside = false;
+        } else if !inside {
+            lines.push(line.to_string());
+        }
+    }
+    while lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+    if !entries.is_empty() {
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.push(BLOCK_BEGIN.to_string());
+        for entry in entries {
+            let mut line = format!("{} {}", entry.address.trim(), entry.host.trim());
+            if let Some(game_id) = &entry.game_id {
+                line.push_str(&format!(" {}{}", GAME_TAG, game_id));
+            }
+            lines.push(line);
+        }
+        lines.push(BLOCK_END.to_string());
+    }
+    let mut rendered = lines.join(newline);
+    rendered.push_str(newline);
+    rendered
+}
+
+/// Replaces the launcher's entries in the hosts file at `path`: all of them, or only those of
+/// `game_id` when given, whose entries `entries` then become. Returns the entries the block
+/// held before. If the write fails part way, the original file is put back.
+pub fn update_hosts_file(path: &Path, game_id: Option<&str>, entries: &[HostsEntry]) -> Result<Vec<HostsEntry>, String> {
+    let original = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    let previous = managed_entries(&original);
+    let mut next: Vec<HostsEntry> = match game_id {
+        Some(game_id) => previous
+            .iter()
+            .filter(|entry| entry.game_id.as_deref() != Some(game_id))
+            .cloned()
+            .collect(),
+        None => Vec::new(),
+    };
+    for entry in entries {
+        let mut entry = entry.clone();
+        if game_id.is_some() {
+            entry.game_id = game_id.map(str::to_string);
+        }
+        validate_hosts_entry(&entry)?;
+        next.retain(|existing| !existing.host.eq_ignore_ascii_case(&entry.host));
+        next.push(entry);
+    }
+
+    if next == previous {
+        return Ok(previous);
+    }
+    if let Err(e) = fs::write(path, render_hosts(&original, &next)) {
+        let _ = fs::write(path, &original);
+        return Err(format!("Failed to write {}: {}", path.display(), e));
+    }
+    Ok(previous)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(address: &str, host: &str) -> HostsEntry {
+        HostsEntry {
+            address: address.to_string(),
+            host: host.to_string(),
+            game_id: None,
+        }
+    }
+
+    #[test]
+    fn rewrites_only_the_managed_block() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("hosts");
+        let user = "# Copyright\r\n127.0.0.1 localhost\r\n";
+        fs::write(&path, user).unwrap();
+
+        update_hosts_file(&path, Some("SDEZ"), &[entry("192.168.1.2", "naominet.jp")]).unwrap();
+        update_hosts_file(&path, Some("SDHD"), &[entry("10.0.0.5", "aime.naominet.jp")]).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with(user));
+        assert!(!content.contains("\r\n\r\n\r\n") && content.ends_with("\r\n"));
+        let managed = managed_entries(&content);
+        assert_eq!(managed.len(), 2);
+        assert_eq!(managed[0].game_id.as_deref(), Some("SDEZ"));
+
+        let previous = update_hosts_file(&path, Some("SDEZ"), &[]).unwrap();
+        assert_eq!(previous, managed);
+        assert_eq!(managed_entries(&fs::read_to_string(&path).unwrap()), managed[1..]);
+        assert!(update_hosts_file(&path, None, &[entry("999.0.0.1", "naominet.jp")]).is_err());
+        assert!(update_hosts_file(&path, None, &[entry("127.0.0.1", "bad host")]).is_err());
+
+        update_hosts_file(&path, None, &[]).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), user);
+    }
+}
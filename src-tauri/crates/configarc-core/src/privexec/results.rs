@@ -0,0 +1,338 @@
+//! Typed results of the commands that report on images, volumes, disks and the host.
+//! Windows PowerShell 5.1 and PowerShell 7 serialize the same objects differently (enums as
+//! numbers or names, a single item as an object instead of an array), so parsing here accepts
+//! either and clients get the same fields. Each result carries `schema_version` so a field
+//! change is detectable.
+
+use crate::platform::Capability;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Bumped when a field of a result below changes meaning or goes away.
+pub const RESULT_SCHEMA_VERSION: u32 = 1;
+
+const LOCK_STATUS: &[(i64, &str)] = &[(0, "Unlocked"), (1, "Locked")];
+const PROTECTION_STATUS: &[(i64, &str)] = &[(0, "Off"), (1, "On"), (2, "Unknown")];
+const VOLUME_STATUS: &[(i64, &str)] = &[
+    (0, "FullyDecrypted"),
+    (1, "FullyEncrypted"),
+    (2, "EncryptionInProgress"),
+    (3, "DecryptionInProgress"),
+    (4, "EncryptionPaused"),
+    (5, "DecryptionPaused"),
+];
+const PARTITION_STYLE: &[(i64, &str)] = &[(0, "Unknown"), (1, "MBR"), (2, "GPT")];
+const OPERATIONAL_STATUS: &[(i64, &str)] = &[
+    (0, "Unknown"),
+    (2, "OK"),
+    (3, "Degraded"),
+    (0xD010, "Online"),
+    (0xD011, "Not Ready"),
+    (0xD012, "No Media"),
+    (0xD013, "Offline"),
+    (0xD014, "Failed"),
+];
+
+/// Result of `mount_vhd`, for a single image or a game's VHD set.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MountVhdResult {
+    pub schema_version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_path: Option<String>,
+    #[serde(default)]
+    pub attached: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub game_id: Option<String>,
+    #[cfg(feature = "vhd")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mount_points: Option<crate::vhd::MountPoints>,
+    /// The executor already held the game's set, so nothing was mounted.
+    #[serde(default)]
+    pub already_mounted: bool,
+}
+
+impl MountVhdResult {
+    /// From `Mount-DiskImage -PassThru | Select-Object ImagePath,Attached`.
+    pub fn from_powershell(value: &Value) -> Self {
+        let image = first_object(value);
+        Self {
+            schema_version: RESULT_SCHEMA_VERSION,
+            image_path: image.and_then(|v| text(v, "ImagePath")),
+            attached: image.and_then(|v| flag(v, "Attached")).unwrap_or(false),
+            ..Self::default()
+        }
+    }
+
+    #[cfg(feature = "vhd")]
+    pub fn for_game(game_id: &str, mount_points: crate::vhd::MountPoints, already_mounted: bool) -> Self {
+        Self {
+            schema_version: RESULT_SCHEMA_VERSION,
+            attached: true,
+            game_id: Some(game_id.to_string()),
+            mount_points: Some(mount_points),
+            already_mounted,
+            ..Self::default()
+        }
+    }
+}
+
+/// Result of `query_bitlocker_status` and `unlock_bitlocker`. Statuses are the names
+/// `Get-BitLockerVolume` prints, such as `Locked` or `FullyEncrypted`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BitLockerStatus {
+    pub schema_version: u32,
+    pub mount_point: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume_status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protection_status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lock_status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption_percentage: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_unlock_enabled: Option<bool>,
+    /// Only set by `unlock_bitlocker`: the volume was unlocked before it ran.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub already_unlocked: Option<bool>,
+}
+
+impl BitLockerStatus {
+    /// From a `Get-BitLockerVolume` selection or the unlock script's summary. `mount_point`
+    /// fills in when the output has none.
+    pub fn from_powershell(value: &Value, mount_point: &str) -> Self {
+        let Some(volume) = first_object(value) else {
+            return Self {
+                schema_version: RESULT_SCHEMA_VERSION,
+                mount_point: mount_point.to_string(),
+                ..Self::default()
+            };
+        };
+        Self {
+            schema_version: RESULT_SCHEMA_VERSION,
+            mount_point: text(volume, "MountPoint").unwrap_or_else(|| mount_point.to_string()),
+            volume_status: status(volume, "VolumeStatus", VOLUME_STATUS),
+            protection_status: status(volume, "ProtectionStatus", PROTECTION_STATUS),
+            lock_status: status(volume, "LockStatus", LOCK_STATUS),
+            encryption_percentage: field(volume, "EncryptionPercentage").and_then(as_f64),
+            auto_unlock_enabled: flag(volume, "AutoUnlockEnabled"),
+            already_unlocked: flag(volume, "alreadyUnlocked"),
+        }
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.lock_status.as_deref().is_some_and(|s| s.eq_ignore_ascii_case("Locked"))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiskInfo {
+    pub number: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub friendly_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operational_status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partition_style: Option<String>,
+    /// Bytes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+}
+
+/// Result of `query_disk`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiskList {
+    pub schema_version: u32,
+    pub disks: Vec<DiskInfo>,
+}
+
+impl DiskList {
+    /// From `Get-Disk | Select-Object Number,FriendlyName,...`; disks without a number are
+    /// dropped.
+    pub fn from_powershell(value: &Value) -> Self {
+        let disks = objects(value)
+            .filter_map(|disk| {
+                Some(DiskInfo {
+                    number: field(disk, "Number").and_then(as_u64).and_then(|n| u32::try_from(n).ok())?,
+                    friendly_name: text(disk, "FriendlyName"),
+                    operational_status: status(disk, "OperationalStatus", OPERATIONAL_STATUS),
+                    partition_style: status(disk, "PartitionStyle", PARTITION_STYLE),
+                    size: field(disk, "Size").and_then(as_u64),
+                })
+            })
+            .collect();
+        Self {
+            schema_version: RESULT_SCHEMA_VERSION,
+            disks,
+        }
+    }
+}
+
+/// Result of `probe_capabilities`: what this host lets the executor do, so a client can hide
+/// BitLocker on Home editions instead of failing on the first unlock.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HostCapabilities {
+    pub schema_version: u32,
+    /// The executor runs as administrator, which mounting and unlocking need.
+    pub elevated: Capability,
+    /// The Storage module's `Mount-DiskImage` and `Get-Disk`.
+    pub storage: Capability,
+    /// The BitLocker module, which Home editions lack.
+    pub bitlocker: Capability,
+    /// The Hyper-V module's `Mount-VHD`. Optional: images mount through Storage without it.
+    pub hyper_v: Capability,
+    /// Such as "Microsoft Windows 11 Home".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub os_edition: Option<String>,
+}
+
+impl HostCapabilities {
+    /// From the probe script's `{elevated, storage, bitlocker, hyperV, edition}`; anything it
+    /// did not report counts as missing.
+    pub fn from_powershell(value: &Value) -> Self {
+        let probe = first_object(value);
+        let check = |name: &str, reason: &str| {
+            let present = probe.and_then(|v| flag(v, name)).unwrap_or(false);
+            Capability::from_blocker((!present).then(|| reason.to_string()))
+        };
+        Self {
+            schema_version: RESULT_SCHEMA_VERSION,
+            elevated: check("elevated", "The privileged executor is not running as administrator"),
+            storage: check("storage", "The Storage PowerShell module is not installed, so VHDs cannot be mounted"),
+            bitlocker: check("bitlocker", "BitLocker cmdlets are not available on this edition of Windows"),
+            hyper_v: check("hyperV", "The Hyper-V PowerShell module is not installed"),
+            os_edition: probe.and_then(|v| text(v, "edition")),
+        }
+    }
+}
+
+/// The items of `value`, which PowerShell writes as a bare object when there is only one.
+fn objects(value: &Value) -> impl Iterator<Item = &Value> {
+    let items: &[Value] = match value {
+        Value::Array(items) => items,
+        Value::Object(_) => std::slice::from_ref(value),
+        _ => &[],
+    };
+    items.iter().filter(|item| item.is_object())
+}
+
+fn first_object(value: &Value) -> Option<&Value> {
+    objects(value).next()
+}
+
+/// Property `name` of `object`, matched without regard to case.
+fn field<'a>(object: &'a Value, name: &str) -> Option<&'a Value> {
+    object
+        .as_object()?
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value)
+        .filter(|value| !value.is_null())
+}
+
+fn text(object: &Value, name: &str) -> Option<String> {
+    match field(object, name)? {
+        Value::String(s) => Some(s.trim().to_string()).filter(|s| !s.is_empty()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn flag(object: &Value, name: &str) -> Option<bool> {
+    match field(object, name)? {
+        Value::Bool(b) => Some(*b),
+        Value::Number(n) => n.as_i64().map(|n| n != 0),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn as_u64(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(n) => n.as_u64().or_else(|| n.as_f64().filter(|f| *f >= 0.0).map(|f| f as u64)),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn as_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().trim_end_matches('%').parse().ok(),
+        _ => None,
+    }
+}
+
+/// An enum property by name: kept when PowerShell already wrote the name, looked up in
+/// `names` when it wrote the number. Multi-valued properties, like a disk's operational
+/// status, are joined with `, `.
+fn status(object: &Value, name: &str, names: &[(i64, &str)]) -> Option<String> {
+    let name_of = |value: &Value| match value {
+        Value::String(s) => Some(s.trim().to_string()).filter(|s| !s.is_empty()),
+        Value::Number(n) => {
+            let n = n.as_i64()?;
+            Some(
+                names
+                    .iter()
+                    .find(|(code, _)| *code == n)
+                    .map(|(_, label)| label.to_string())
+                    .unwrap_or_else(|| n.to_string()),
+            )
+        }
+        _ => None,
+    };
+    match field(object, name)? {
+        Value::Array(values) => {
+            let labels: Vec<String> = values.iter().filter_map(name_of).collect();
+            Some(labels.join(", ")).filter(|s| !s.is_empty())
+        }
+        value => name_of(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reads_both_powershell_shapes() {
+        let v5 = BitLockerStatus::from_powershell(
+            &json!({"MountPoint": "X:", "VolumeStatus": 1, "ProtectionStatus": 1, "LockStatus": 1, "EncryptionPercentage": 100}),
+            "X:",
+        );
+        let v7 = BitLockerStatus::from_powershell(
+            &json!({"mountPoint": "X:", "volumeStatus": "FullyEncrypted", "protectionStatus": "On", "lockStatus": "Locked", "encryptionPercentage": "100"}),
+            "X:",
+        );
+        assert_eq!(v5, v7);
+        assert!(v5.is_locked());
+        assert_eq!(v5.schema_version, RESULT_SCHEMA_VERSION);
+
+        let single = DiskList::from_powershell(&json!({"Number": 1, "FriendlyName": "Msft Virtual Disk", "PartitionStyle": 2, "OperationalStatus": [53264], "Size": 2147483648u64}));
+        assert_eq!(single.disks.len(), 1);
+        assert_eq!(single.disks[0].partition_style.as_deref(), Some("GPT"));
+        assert_eq!(single.disks[0].operational_status.as_deref(), Some("Online"));
+        assert_eq!(single.disks[0].size, Some(2147483648));
+        let listed = DiskList::from_powershell(&json!([{"Number": 0, "PartitionStyle": "MBR", "OperationalStatus": "Online"}, {"FriendlyName": "no number"}]));
+        assert_eq!(listed.disks.len(), 1);
+        assert_eq!(listed.disks[0].partition_style.as_deref(), Some("MBR"));
+
+        let mounted = MountVhdResult::from_powershell(&json!({"ImagePath": "C:\\game.vhd", "Attached": true}));
+        assert!(mounted.attached);
+        assert_eq!(mounted.image_path.as_deref(), Some("C:\\game.vhd"));
+        assert!(!MountVhdResult::from_powershell(&json!("unexpected")).attached);
+
+        let home = HostCapabilities::from_powershell(&json!({"elevated": true, "storage": true, "bitlocker": false, "edition": "Microsoft Windows 11 Home"}));
+        assert!(home.elevated.supported && home.storage.supported);
+        assert!(!home.bitlocker.supported && home.bitlocker.reason.is_some());
+        assert!(!home.hyper_v.supported);
+        assert!(!HostCapabilities::from_powershell(&json!("")).storage.supported);
+    }
+}
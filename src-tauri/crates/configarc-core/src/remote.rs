@@ -1,9 +1,14 @@
+//! Fleet-wide defaults fetched from a remote JSON document, cached locally and merged under
+//! local overrides. The document may also carry maintainer-signed [`RemoteDirectives`].
+
 use crate::config::{profiles::ConfigProfile, segatools::SegatoolsConfig};
-use crate::games::model::Game;
-use chrono::Utc;
+use crate::games::{detection::compare_versions, model::Game};
+use crate::trusted::verify_maintainer_signature;
+use chrono::{DateTime, Utc};
 use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -70,6 +75,84 @@ pub struct RemoteApplyPlan {
     pub segatools_by_game: HashMap<String, SegatoolsConfig>,
 }
 
+/// Operator-provided defaults read from the `defaults` object of the effective config.
+/// The local override is merged over the remote cache, so local values win.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteDefaults {
+    #[serde(default)]
+    pub fsdecrypt_key_url: Option<String>,
+    #[serde(default)]
+    pub segatools_mirrors: Vec<String>,
+}
+
+/// `directives` of the remote document: the JSON of [`RemoteDirectives`] as a string, and a
+/// minisign signature over exactly that string.
+#[derive(Debug, Deserialize)]
+struct SignedDirectives {
+    payload: String,
+    minisig: String,
+}
+
+/// Kill switch the maintainer can publish to stop a bad rollout. Only honored when signed with
+/// the trusted manifest key, so neither the remote endpoint nor a local override can forge one.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteDirectives {
+    /// Subsystems to refuse: `launch`, `deploy`, `presets`, `download_order` or `fsdecrypt`.
+    #[serde(default)]
+    pub disabled_features: Vec<String>,
+    /// Launches are refused on older launcher versions until the user updates.
+    #[serde(default)]
+    pub min_version: Option<String>,
+    /// Shown to the user with every refusal.
+    #[serde(default)]
+    pub message: Option<String>,
+    /// RFC 3339; the directives are dropped once it has passed.
+    #[serde(default)]
+    pub expires_at: Option<String>,
+}
+
+impl RemoteDirectives {
+    pub fn is_disabled(&self, feature: &str) -> bool {
+        self.disabled_features
+            .iter()
+            .any(|disabled| disabled.trim().eq_ignore_ascii_case(feature))
+    }
+
+    /// Whether `version` is older than [`Self::min_version`].
+    pub fn requires_update(&self, version: &str) -> bool {
+        self.min_version
+            .as_deref()
+            .map(str::trim)
+            .filter(|min| !min.is_empty())
+            .is_some_and(|min| compare_versions(version, min) == Ordering::Less)
+    }
+
+    fn expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at
+            .as_deref()
+            .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+            .is_some_and(|at| at.with_timezone(&Utc) <= now)
+    }
+}
+
+/// Reads the directives of a remote document. A document without any yields the empty set;
+/// one with a bad signature is an error so it can be reported rather than silently ignored.
+pub fn parse_directives(config: &Value) -> Result<RemoteDirectives, RemoteError> {
+    let Some(value) = config.get("directives").filter(|value| !value.is_null()) else {
+        return Ok(RemoteDirectives::default());
+    };
+    let signed: SignedDirectives = serde_json::from_value(value.clone())?;
+    verify_maintainer_signature(signed.payload.as_bytes(), &signed.minisig)
+        .map_err(|e| RemoteError::Parse(format!("Directives are not signed by the maintainer: {}", e)))?;
+    let directives: RemoteDirectives = serde_json::from_str(&signed.payload)?;
+    if directives.expired(Utc::now()) {
+        return Ok(RemoteDirectives::default());
+    }
+    Ok(directives)
+}
+
 pub struct RemoteConfigManager {
     root: PathBuf,
     remote_cache_path: PathBuf,
@@ -134,6 +217,32 @@ impl RemoteConfigManager {
         merge_json(&remote, &local)
     }
 
+    pub fn defaults(&self) -> RemoteDefaults {
+        let config = self.effective_config();
+        let mut defaults = config
+            .get("defaults")
+            .cloned()
+            .and_then(|value| serde_json::from_value::<RemoteDefaults>(value).ok())
+            .unwrap_or_default();
+        defaults.fsdecrypt_key_url = defaults
+            .fsdecrypt_key_url
+            .map(|url| url.trim().to_string())
+            .filter(|url| !url.is_empty());
+        defaults.segatools_mirrors = defaults
+            .segatools_mirrors
+            .into_iter()
+            .map(|url| url.trim().to_string())
+            .filter(|url| !url.is_empty())
+            .collect();
+        defaults
+    }
+
+    /// Directives of the cached remote document. The local override is not consulted, so a
+    /// directive can only be lifted by the maintainer publishing a new document.
+    pub fn directives(&self) -> Result<RemoteDirectives, RemoteError> {
+        parse_directives(&self.read_remote_cache().config)
+    }
+
     pub fn apply_plan(&self) -> Result<RemoteApplyPlan, RemoteError> {
         let config = self.effective_config();
         let plan: RemoteApplyPlan = serde_json::from_value(config)?;
@@ -278,3 +387,40 @@ fn write_json_value(path: &Path, value: &Value) -> Result<(), RemoteError> {
     fs::write(path, json)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn directives_need_a_valid_signature() {
+        assert_eq!(parse_directives(&json!({})).unwrap(), RemoteDirectives::default());
+        let forged = json!({
+            "directives": {
+                "payload": "{\"disabledFeatures\":[\"deploy\"]}",
+                "minisig": "untrusted comment: forged\nRWQCzONvpPTxVgAAAA==\n"
+            }
+        });
+        assert!(parse_directives(&forged).is_err());
+    }
+
+    #[test]
+    fn directives_compare_versions_and_features() {
+        let directives = RemoteDirectives {
+            disabled_features: vec!["Deploy".to_string()],
+            min_version: Some("1.10.0".to_string()),
+            ..RemoteDirectives::default()
+        };
+        assert!(directives.is_disabled("deploy"));
+        assert!(!directives.is_disabled("launch"));
+        assert!(directives.requires_update("1.9.3"));
+        assert!(!directives.requires_update("1.10.0"));
+
+        let expired = RemoteDirectives {
+            expires_at: Some("2020-01-01T00:00:00Z".to_string()),
+            ..directives
+        };
+        assert!(expired.expired(Utc::now()));
+    }
+}
@@ -0,0 +1,223 @@
+use crate::backup::{appdata_backup_root_for, referenced_chunks};
+use crate::config::paths::segatools_root_for_game_id;
+use crate::trusted::{
+    backup_file_path, backup_root_for, read_backup_metadata, sha256_file, write_backup_metadata, TrustedError,
+};
+use chrono::Utc;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScrubFinding {
+    /// Restore point type, e.g. "deployment_snapshot" or "appdata_backup".
+    pub kind: String,
+    pub game_id: String,
+    pub location: String,
+    pub checked: usize,
+    pub corrupted: Vec<String>,
+    pub missing: Vec<String>,
+    /// True when the restore point predates checksum recording and could not be verified.
+    pub unverifiable: bool,
+}
+
+impl ScrubFinding {
+    pub fn is_healthy(&self) -> bool {
+        self.corrupted.is_empty() && self.missing.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ScrubReport {
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    pub cancelled: bool,
+    pub findings: Vec<ScrubFinding>,
+}
+
+impl ScrubReport {
+    pub fn problems(&self) -> Vec<&ScrubFinding> {
+        self.findings.iter().filter(|f| !f.is_healthy()).collect()
+    }
+}
+
+/// Re-hashes every file listed in `expected` (relative path -> SHA-256), locating each one
+/// through `resolve`. Returns `(checked, corrupted, missing)`.
+pub fn verify_checksums(
+    resolve: impl Fn(&str) -> PathBuf,
+    expected: &BTreeMap<String, String>,
+    cancel: &AtomicBool,
+) -> (usize, Vec<String>, Vec<String>) {
+    let mut checked = 0;
+    let mut corrupted = Vec::new();
+    let mut missing = Vec::new();
+    for (rel, sha) in expected {
+        if cancel.load(Ordering::SeqCst) {
+            break;
+        }
+        let path = resolve(rel);
+        if !path.exists() {
+            missing.push(rel.clone());
+            continue;
+        }
+        checked += 1;
+        match sha256_file(&path) {
+            Ok(actual) if actual.eq_ignore_ascii_case(sha) => {}
+            _ => corrupted.push(rel.clone()),
+        }
+    }
+    (checked, corrupted, missing)
+}
+
+/// Verifies the segatools deployment snapshot of one game and records the outcome in its
+/// metadata so rollback can refuse a damaged snapshot.
+pub fn scrub_deployment_snapshot(root: &Path, game_id: &str, cancel: &AtomicBool) -> Result<Option<ScrubFinding>, TrustedError> {
+    let backup_root = backup_root_for(root);
+    let Some(mut meta) = read_backup_metadata(&backup_root)? else {
+        return Ok(None);
+    };
+    let mut finding = ScrubFinding {
+        kind: "deployment_snapshot".to_string(),
+        game_id: game_id.to_string(),
+        location: backup_root.to_string_lossy().to_string(),
+        checked: 0,
+        corrupted: Vec::new(),
+        missing: Vec::new(),
+        unverifiable: meta.file_sha256.is_empty() && !meta.backed_up_files.is_empty(),
+    };
+    if finding.unverifiable {
+        // Older snapshots only record file names, so presence is all that can be checked.
+        finding.missing = meta
+            .backed_up_files
+            .iter()
+            .filter(|file| !backup_file_path(&backup_root, file).exists())
+            .cloned()
+            .collect();
+    } else {
+        let (checked, corrupted, missing) =
+            verify_checksums(|rel| backup_file_path(&backup_root, rel), &meta.file_sha256, cancel);
+        if cancel.load(Ordering::SeqCst) {
+            return Ok(None);
+        }
+        finding.checked = checked;
+        finding.corrupted = corrupted;
+        finding.missing = missing;
+    }
+
+    meta.corrupted_files = finding.corrupted.iter().chain(finding.missing.iter()).cloned().collect();
+    meta.verified_at = Some(Utc::now().to_rfc3339());
+    write_backup_metadata(&backup_root, &meta)?;
+    Ok(Some(finding))
+}
+
+/// Re-hashes every chunk the retained appdata backups of one game depend on. Findings list
+/// chunk hashes, since one chunk can be shared by many files and snapshots.
+pub fn scrub_appdata_backups(root: &Path, game_id: &str, cancel: &AtomicBool) -> Result<Option<ScrubFinding>, String> {
+    let repo = appdata_backup_root_for(root);
+    let chunks = referenced_chunks(&repo)?;
+    if chunks.is_empty() {
+        return Ok(None);
+    }
+    let expected: BTreeMap<String, String> = chunks.keys().map(|hash| (hash.clone(), hash.clone())).collect();
+    let (checked, corrupted, missing) = verify_checksums(|hash| chunks[hash].clone(), &expected, cancel);
+    if cancel.load(Ordering::SeqCst) {
+        return Ok(None);
+    }
+    Ok(Some(ScrubFinding {
+        kind: "appdata_backup".to_string(),
+        game_id: game_id.to_string(),
+        location: repo.to_string_lossy().to_string(),
+        checked,
+        corrupted,
+        missing,
+        unverifiable: false,
+    }))
+}
+
+/// Scrubs every restore point of the given games. Stops early, marking the report
+/// cancelled, once `cancel` is set.
+pub fn scrub_games(game_ids: &[String], cancel: &AtomicBool) -> ScrubReport {
+    let mut report = ScrubReport {
+        started_at: Utc::now().to_rfc3339(),
+        ..ScrubReport::default()
+    };
+    for game_id in game_ids {
+        if cancel.load(Ordering::SeqCst) {
+            report.cancelled = true;
+            break;
+        }
+        let root = segatools_root_for_game_id(game_id);
+        match scrub_deployment_snapshot(&root, game_id, cancel) {
+            Ok(Some(finding)) => report.findings.push(finding),
+            Ok(None) => {}
+            Err(err) => report.findings.push(ScrubFinding {
+                kind: "deployment_snapshot".to_string(),
+                game_id: game_id.clone(),
+                location: backup_root_for(&root).to_string_lossy().to_string(),
+                checked: 0,
+                corrupted: vec![err.to_string()],
+                missing: Vec::new(),
+                unverifiable: false,
+            }),
+        }
+        match scrub_appdata_backups(&root, game_id, cancel) {
+            Ok(Some(finding)) => report.findings.push(finding),
+            Ok(None) => {}
+            Err(err) => report.findings.push(ScrubFinding {
+                kind: "appdata_backup".to_string(),
+                game_id: game_id.clone(),
+                location: appdata_backup_root_for(&root).to_string_lossy().to_string(),
+                checked: 0,
+                corrupted: vec![err],
+                missing: Vec::new(),
+                unverifiable: false,
+            }),
+        }
+    }
+    if cancel.load(Ordering::SeqCst) {
+        report.cancelled = true;
+    }
+    report.finished_at = Some(Utc::now().to_rfc3339());
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trusted::BackupMetadata;
+    use std::fs;
+
+    #[test]
+    fn marks_altered_snapshot_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let backup_root = backup_root_for(dir.path());
+        let file = backup_file_path(&backup_root, "inject.exe");
+        fs::create_dir_all(file.parent().unwrap()).unwrap();
+        fs::write(&file, b"original").unwrap();
+        let mut file_sha256 = BTreeMap::new();
+        file_sha256.insert("inject.exe".to_string(), sha256_file(&file).unwrap());
+        let meta = BackupMetadata {
+            created_at: String::new(),
+            artifact_name: "segatools.zip".to_string(),
+            artifact_sha256: String::new(),
+            build_id: None,
+            backed_up_files: vec!["inject.exe".to_string()],
+            new_files: vec![],
+            file_sha256,
+            corrupted_files: vec![],
+            verified_at: None,
+        };
+        write_backup_metadata(&backup_root, &meta).unwrap();
+        fs::write(&file, b"bitrot").unwrap();
+
+        let finding = scrub_deployment_snapshot(dir.path(), "game", &AtomicBool::new(false))
+            .unwrap()
+            .unwrap();
+        assert_eq!(finding.corrupted, vec!["inject.exe".to_string()]);
+        let stored = read_backup_metadata(&backup_root).unwrap().unwrap();
+        assert_eq!(stored.corrupted_files, vec!["inject.exe".to_string()]);
+    }
+}
@@ -0,0 +1,217 @@
+//! Secrets at rest that only the current user on this machine can read back. On Windows
+//! the bytes are sealed with DPAPI, so a copied file is useless elsewhere. Other platforms
+//! use AES-256-GCM under a random key kept in an owner-only `.sealkey` file next to the store.
+
+use std::path::Path;
+
+#[cfg(target_os = "windows")]
+mod imp {
+    use std::{ffi::c_void, path::Path, ptr};
+
+    const CRYPTPROTECT_UI_FORBIDDEN: u32 = 0x1;
+
+    #[repr(C)]
+    struct DataBlob {
+        size: u32,
+        data: *mut u8,
+    }
+
+    #[link(name = "crypt32")]
+    extern "system" {
+        fn CryptProtectData(
+            data_in: *const DataBlob,
+            description: *const u16,
+            entropy: *const DataBlob,
+            reserved: *mut c_void,
+            prompt: *mut c_void,
+            flags: u32,
+            data_out: *mut DataBlob,
+        ) -> i32;
+        fn CryptUnprotectData(
+            data_in: *const DataBlob,
+            description: *mut *mut u16,
+            entropy: *const DataBlob,
+            reserved: *mut c_void,
+            prompt: *mut c_void,
+            flags: u32,
+            data_out: *mut DataBlob,
+        ) -> i32;
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn LocalFree(mem: *mut c_void) -> *mut c_void;
+    }
+
+    fn blob(data: &[u8]) -> DataBlob {
+        DataBlob {
+            size: data.len() as u32,
+            data: data.as_ptr() as *mut u8,
+        }
+    }
+
+    fn take(out: DataBlob) -> Vec<u8> {
+        let bytes = unsafe { std::slice::from_raw_parts(out.data, out.size as usize) }.to_vec();
+        unsafe { LocalFree(out.data as *mut c_void) };
+        bytes
+    }
+
+    pub(super) fn seal(plain: &[u8], store: &Path, entropy: &[u8]) -> Result<Vec<u8>, String> {
+        let mut out = DataBlob { size: 0, data: ptr::null_mut() };
+        let ok = unsafe {
+            CryptProtectData(
+                &blob(plain),
+                ptr::null(),
+                &blob(entropy),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                CRYPTPROTECT_UI_FORBIDDEN,
+                &mut out,
+            )
+        };
+        if ok == 0 {
+            return Err(format!(
+                "DPAPI could not protect {}: {}",
+                store.display(),
+                std::io::Error::last_os_error()
+            ));
+        }
+        Ok(take(out))
+    }
+
+    pub(super) fn open(sealed: &[u8], store: &Path, entropy: &[u8]) -> Result<Vec<u8>, String> {
+        let mut out = DataBlob { size: 0, data: ptr::null_mut() };
+        let ok = unsafe {
+            CryptUnprotectData(
+                &blob(sealed),
+                ptr::null_mut(),
+                &blob(entropy),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                CRYPTPROTECT_UI_FORBIDDEN,
+                &mut out,
+            )
+        };
+        if ok == 0 {
+            return Err(format!(
+                "{} was sealed by another user or machine: {}",
+                store.display(),
+                std::io::Error::last_os_error()
+            ));
+        }
+        Ok(take(out))
+    }
+
+    /// DPAPI keeps its keys with the user profile; there is no file next to the store.
+    #[cfg(feature = "fsdecrypt")]
+    pub(super) fn remove_key_file(_store: &Path) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+mod imp {
+    use aes_gcm::aead::{Aead, KeyInit};
+    use aes_gcm::{Aes256Gcm, Nonce};
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+    };
+
+    const NONCE_LEN: usize = 12;
+
+    /// Key file of `store`: the full file name plus `.sealkey`, so a store named `*.key` never
+    /// shares a path with its key.
+    pub(super) fn key_file_path(store: &Path) -> PathBuf {
+        let mut name = store.as_os_str().to_owned();
+        name.push(".sealkey");
+        name.into()
+    }
+
+    // The entropy only matters to DPAPI; here every store has its own key file.
+    fn cipher(store: &Path, create: bool) -> Result<Aes256Gcm, String> {
+        let key_path = key_file_path(store);
+        let key = match fs::read(&key_path) {
+            Ok(key) => key,
+            Err(e) if create && e.kind() == std::io::ErrorKind::NotFound => {
+                let mut key = vec![0u8; 32];
+                getrandom::getrandom(&mut key).map_err(|e| e.to_string())?;
+                fs::write(&key_path, &key).map_err(|e| e.to_string())?;
+                #[cfg(unix)]
+                {
+                    use std::os::unix::fs::PermissionsExt;
+                    fs::set_permissions(&key_path, fs::Permissions::from_mode(0o600)).map_err(|e| e.to_string())?;
+                }
+                key
+            }
+            Err(e) => return Err(format!("Failed to read key file {}: {e}", key_path.display())),
+        };
+        Aes256Gcm::new_from_slice(&key).map_err(|e| e.to_string())
+    }
+
+    pub(super) fn seal(plain: &[u8], store: &Path, _entropy: &[u8]) -> Result<Vec<u8>, String> {
+        let mut nonce = [0u8; NONCE_LEN];
+        getrandom::getrandom(&mut nonce).map_err(|e| e.to_string())?;
+        let sealed = cipher(store, true)?
+            .encrypt(Nonce::from_slice(&nonce), plain)
+            .map_err(|e| e.to_string())?;
+        Ok([nonce.as_slice(), sealed.as_slice()].concat())
+    }
+
+    pub(super) fn open(sealed: &[u8], store: &Path, _entropy: &[u8]) -> Result<Vec<u8>, String> {
+        if sealed.len() < NONCE_LEN {
+            return Err(format!("{} is truncated", store.display()));
+        }
+        let (nonce, sealed) = sealed.split_at(NONCE_LEN);
+        cipher(store, false)?
+            .decrypt(Nonce::from_slice(nonce), sealed)
+            .map_err(|_| format!("{} could not be decrypted with its key file", store.display()))
+    }
+
+    #[cfg(feature = "fsdecrypt")]
+    pub(super) fn remove_key_file(store: &Path) -> std::io::Result<()> {
+        match fs::remove_file(key_file_path(store)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Seals `plain` for the file `store`. `entropy` ties the result to one purpose, so a blob
+/// sealed for one store does not open as another.
+pub(crate) fn seal(plain: &[u8], store: &Path, entropy: &[u8]) -> Result<Vec<u8>, String> {
+    imp::seal(plain, store, entropy)
+}
+
+pub(crate) fn open(sealed: &[u8], store: &Path, entropy: &[u8]) -> Result<Vec<u8>, String> {
+    imp::open(sealed, store, entropy)
+}
+
+/// Deletes whatever key `seal` kept for `store` outside of it, once the store itself is gone.
+#[cfg(feature = "fsdecrypt")]
+pub(crate) fn remove_key_file(store: &Path) -> std::io::Result<()> {
+    imp::remove_key_file(store)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn reopens_stores_named_like_key_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = dir.path().join("client.key");
+        let sealed = seal(b"secret", &store, b"test").unwrap();
+        fs::write(&store, &sealed).unwrap();
+        assert_eq!(open(&fs::read(&store).unwrap(), &store, b"test").unwrap(), b"secret");
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn keeps_the_key_next_to_the_full_store_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = dir.path().join("client.key");
+        assert_eq!(imp::key_file_path(&store), dir.path().join("client.key.sealkey"));
+    }
+}
@@ -0,0 +1,160 @@
+use crate::platform::powershell_support;
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+#[cfg(target_os = "windows")]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+/// Serial ports the kernel has created right now, one value per port, readable without
+/// elevation. Unplugged devices drop out of it, unlike the PnP device list.
+const SERIALCOMM_KEY: &str = r"HKLM\HARDWARE\DEVICEMAP\SERIALCOMM";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerialPort {
+    /// `COM3`.
+    pub port_name: String,
+    /// What segatools' `portNo` takes for this port.
+    pub number: u32,
+    /// Device name from Device Manager without the `(COMn)` suffix, such as
+    /// "USB-SERIAL CH340"; the port name when PowerShell could not be asked.
+    pub friendly_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manufacturer: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<String>,
+}
+
+/// Port number of `COMn`, ignoring case.
+pub fn com_port_number(name: &str) -> Option<u32> {
+    let name = name.trim();
+    let prefix = name.get(..3)?;
+    if !prefix.eq_ignore_ascii_case("COM") {
+        return None;
+    }
+    name[3..].parse().ok().filter(|n| *n > 0)
+}
+
+/// Port names in `reg query` output for [`SERIALCOMM_KEY`]. Lines look like
+/// `    \Device\Serial0    REG_SZ    COM1`.
+pub fn parse_serialcomm(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once("REG_SZ").map(|(_, value)| value.trim()))
+        .filter(|value| com_port_number(value).is_some())
+        .map(|value| value.to_ascii_uppercase())
+        .collect()
+}
+
+/// Ports in tab-separated `Name`, `Manufacturer`, `PNPDeviceID` lines of PnP devices whose
+/// name ends in `(COMn)`.
+pub fn parse_pnp_ports(output: &str) -> Vec<SerialPort> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t').map(str::trim);
+            let name = fields.next()?;
+            let manufacturer = fields.next().filter(|m| !m.is_empty()).map(str::to_string);
+            let device_id = fields.next().filter(|id| !id.is_empty()).map(str::to_string);
+            let open = name.rfind("(COM")?;
+            let port_name = name[open + 1..].strip_suffix(')')?.to_ascii_uppercase();
+            let number = com_port_number(&port_name)?;
+            Some(SerialPort {
+                port_name,
+                number,
+                friendly_name: name[..open].trim().to_string(),
+                manufacturer,
+                device_id,
+            })
+        })
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+fn reg() -> Command {
+    let mut cmd = Command::new("reg");
+    cmd.creation_flags(CREATE_NO_WINDOW);
+    cmd
+}
+
+#[cfg(not(target_os = "windows"))]
+fn reg() -> Command {
+    Command::new("reg")
+}
+
+fn query_serialcomm() -> Vec<String> {
+    match reg().args(["query", SERIALCOMM_KEY]).output() {
+        Ok(output) if output.status.success() => parse_serialcomm(&String::from_utf8_lossy(&output.stdout)),
+        _ => Vec::new(),
+    }
+}
+
+fn query_pnp_ports() -> Vec<SerialPort> {
+    if !powershell_support().runs_cmdlets() {
+        return Vec::new();
+    }
+    let script = "Get-CimInstance Win32_PnPEntity -Filter \"Name LIKE '%(COM%'\" | ForEach-Object { \"$($_.Name)`t$($_.Manufacturer)`t$($_.PNPDeviceID)\" }";
+    let mut cmd = Command::new("powershell");
+    cmd.args(["-NoProfile", "-Command", script]);
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(CREATE_NO_WINDOW);
+    match cmd.output() {
+        Ok(output) => parse_pnp_ports(&String::from_utf8_lossy(&output.stdout)),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Joins the ports that exist with the PnP names known for them, sorted by number. A port
+/// only PnP knows about is listed too, since com0com and some card readers skip the registry.
+pub fn merge_serial_ports(present: &[String], pnp: Vec<SerialPort>) -> Vec<SerialPort> {
+    let mut ports = pnp;
+    for name in present {
+        if ports.iter().any(|port| port.port_name.eq_ignore_ascii_case(name)) {
+            continue;
+        }
+        if let Some(number) = com_port_number(name) {
+            ports.push(SerialPort {
+                port_name: name.to_ascii_uppercase(),
+                number,
+                friendly_name: name.to_ascii_uppercase(),
+                manufacturer: None,
+                device_id: None,
+            });
+        }
+    }
+    ports.sort_by_key(|port| port.number);
+    ports.dedup_by_key(|port| port.number);
+    ports
+}
+
+/// COM ports on this machine with their device names. Empty off Windows.
+pub fn enumerate_serial_ports() -> Vec<SerialPort> {
+    if !cfg!(target_os = "windows") {
+        return Vec::new();
+    }
+    merge_serial_ports(&query_serialcomm(), query_pnp_ports())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_registry_and_pnp_ports() {
+        let registry = "\r\nHKEY_LOCAL_MACHINE\\HARDWARE\\DEVICEMAP\\SERIALCOMM\r\n    \\Device\\Serial0    REG_SZ    COM1\r\n    \\Device\\VCP0    REG_SZ    COM12\r\n";
+        let present = parse_serialcomm(registry);
+        assert_eq!(present, vec!["COM1", "COM12"]);
+
+        let pnp = parse_pnp_ports("USB-SERIAL CH340 (COM3)\twch.cn\tUSB\\VID_1A86&PID_7523\\5&1\nCommunications Port (COM12)\t(Standard port types)\t\nHID Keyboard\t\t\n");
+        let ports = merge_serial_ports(&present, pnp);
+        let names: Vec<_> = ports.iter().map(|p| p.port_name.as_str()).collect();
+        assert_eq!(names, vec!["COM1", "COM3", "COM12"]);
+        assert_eq!(ports[1].friendly_name, "USB-SERIAL CH340");
+        assert_eq!(ports[1].manufacturer.as_deref(), Some("wch.cn"));
+        assert_eq!(ports[2].friendly_name, "Communications Port");
+        assert_eq!(ports[2].device_id, None);
+        assert_eq!(ports[0].friendly_name, "COM1");
+        assert_eq!(com_port_number("com0"), None);
+    }
+}
@@ -1,7 +1,8 @@
 use crate::config::paths::{get_active_game_id, segatools_root_for_active};
-use crate::games::{model::Game, store};
+use crate::config::slots::slot_file_names;
+use crate::games::{detection::canonical_game_key, model::Game, store};
 use chrono::Utc;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use minisign_verify::{PublicKey, Signature};
 use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
@@ -27,6 +28,7 @@ const TRUST_CACHE_TTL_SECS: u64 = 300;
 const TRUST_TIMEOUT_SECS: u64 = 60;
 const TRUST_CONNECT_TIMEOUT_SECS: u64 = 10;
 const TRUST_CACHE_FILE_NAME: &str = ".trust_cache.json";
+const INSTALL_RECORD_NAME: &str = ".imported_install.json";
 
 #[derive(Debug, Error)]
 pub enum TrustedError {
@@ -196,6 +198,31 @@ pub struct BackupMetadata {
     pub build_id: Option<String>,
     pub backed_up_files: Vec<String>,
     pub new_files: Vec<String>,
+    /// SHA-256 of each backed up file, keyed like `backed_up_files`. Empty for older backups.
+    #[serde(default)]
+    pub file_sha256: BTreeMap<String, String>,
+    /// Files the integrity scrubber found missing or altered; rollback refuses such backups.
+    #[serde(default)]
+    pub corrupted_files: Vec<String>,
+    #[serde(default)]
+    pub verified_at: Option<String>,
+}
+
+/// Segatools files adopted from a game folder that already had them, recorded so the install
+/// counts as deployed without a download.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportedInstall {
+    pub source_dir: String,
+    pub imported_at: String,
+    pub files: Vec<ImportedFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportedFile {
+    pub path: String,
+    pub sha256: String,
+    /// PE link time of binaries; none for segatools.ini.
+    pub build_time: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -397,21 +424,26 @@ fn client() -> Result<Client, TrustedError> {
         .map_err(|e| TrustedError::Network(e.to_string()))
 }
 
-fn trusted_url(path: &str) -> String {
-    let base = TRUSTED_BASE.trim_end_matches('/');
-    let trimmed = path.trim_start_matches('/');
-    format!("{}/{}", base, trimmed)
+fn trusted_bases(mirrors: &[String]) -> Vec<String> {
+    let mut bases: Vec<String> = mirrors
+        .iter()
+        .map(|m| m.trim().trim_end_matches('/').to_string())
+        .filter(|m| !m.is_empty())
+        .collect();
+    if !bases.iter().any(|b| b == TRUSTED_BASE) {
+        bases.push(TRUSTED_BASE.to_string());
+    }
+    bases
 }
 
-fn manifest_url() -> String {
-    trusted_url(&format!("{}/{}/{}", TRUSTED_PREFIX, "latest", MANIFEST_NAME))
+fn trusted_url(base: &str, path: &str) -> String {
+    let base = base.trim_end_matches('/');
+    let trimmed = path.trim_start_matches('/');
+    format!("{}/{}", base, trimmed)
 }
 
-fn manifest_sig_url() -> String {
-    trusted_url(&format!(
-        "{}/{}/{}.minisig",
-        TRUSTED_PREFIX, "latest", MANIFEST_NAME
-    ))
+fn manifest_path() -> String {
+    format!("{}/{}/{}", TRUSTED_PREFIX, "latest", MANIFEST_NAME)
 }
 
 fn download_bytes(url: &str) -> Result<Vec<u8>, TrustedError> {
@@ -436,10 +468,33 @@ fn verify_manifest_signature(manifest_bytes: &[u8], sig_bytes: &[u8]) -> Result<
     Ok(())
 }
 
-fn fetch_manifest() -> Result<TrustedManifest, TrustedError> {
-    let manifest_bytes = download_bytes(&manifest_url())?;
-    let sig_bytes = download_bytes(&manifest_sig_url())?;
-    verify_manifest_signature(&manifest_bytes, &sig_bytes)?;
+/// Checks a document the maintainer signed with the same key as the trusted manifests.
+pub fn verify_maintainer_signature(data: &[u8], minisig: &str) -> Result<(), TrustedError> {
+    verify_manifest_signature(data, minisig.as_bytes())
+}
+
+fn fetch_signed_from(base: &str, path: &str) -> Result<Vec<u8>, TrustedError> {
+    let bytes = download_bytes(&trusted_url(base, path))?;
+    let sig_bytes = download_bytes(&trusted_url(base, &format!("{}.minisig", path)))?;
+    verify_manifest_signature(&bytes, &sig_bytes)?;
+    Ok(bytes)
+}
+
+/// Downloads `path` and its `.minisig` from the first mirror that serves a correctly signed
+/// copy. Every mirror is checked against the same minisign key as the trusted manifest.
+pub fn fetch_signed_document(mirrors: &[String], path: &str) -> Result<Vec<u8>, TrustedError> {
+    let mut last_err = None;
+    for base in trusted_bases(mirrors) {
+        match fetch_signed_from(&base, path) {
+            Ok(bytes) => return Ok(bytes),
+            Err(err) => last_err = Some(err),
+        }
+    }
+    Err(last_err.unwrap_or_else(|| TrustedError::Network("No trusted mirror available".to_string())))
+}
+
+fn fetch_manifest(mirrors: &[String]) -> Result<TrustedManifest, TrustedError> {
+    let manifest_bytes = fetch_signed_document(mirrors, &manifest_path())?;
     let manifest: TrustedManifest = serde_json::from_slice(&manifest_bytes)?;
     Ok(manifest)
 }
@@ -456,19 +511,14 @@ fn active_game_ctx() -> Result<ActiveGameContext, TrustedError> {
     Ok(ActiveGameContext { game, root })
 }
 
-fn canonical_game_name(name: &str) -> String {
-    let lower = name.trim().to_lowercase();
-    if lower.starts_with("sdga") || lower.starts_with("sdgb") || lower.starts_with("sdez") {
-        return "sinmai".to_string();
-    }
-    lower
-}
-
 fn artifact_candidates(game: &Game) -> Vec<&'static str> {
-    match canonical_game_name(&game.name).as_str() {
+    match canonical_game_key(&game.name).as_str() {
         "chunithm" => vec!["chusan.zip", "chuni.zip"],
         "sinmai" => vec!["mai2.zip"],
         "ongeki" => vec!["mu3.zip"],
+        "cardmaker" => vec!["cm.zip"],
+        "wacca" => vec!["mercury.zip"],
+        "diva" => vec!["diva.zip"],
         _ => vec![],
     }
 }
@@ -493,6 +543,10 @@ fn select_artifact<'a>(
     )))
 }
 
+pub(crate) fn sha256_file(path: &Path) -> Result<String, TrustedError> {
+    sha256_reader(fs::File::open(path)?)
+}
+
 fn sha256_reader<R: Read>(mut reader: R) -> Result<String, TrustedError> {
     let mut hasher = Sha256::new();
     let mut buf = [0u8; 8192];
@@ -506,8 +560,8 @@ fn sha256_reader<R: Read>(mut reader: R) -> Result<String, TrustedError> {
     Ok(format!("{:x}", hasher.finalize()))
 }
 
-fn download_artifact(artifact: &TrustedArtifact) -> Result<DownloadedArtifact, TrustedError> {
-    let url = trusted_url(&artifact.r2_key);
+fn download_artifact_from(base: &str, artifact: &TrustedArtifact) -> Result<DownloadedArtifact, TrustedError> {
+    let url = trusted_url(base, &artifact.r2_key);
     let mut resp = client()?.get(url).send()?;
     if !resp.status().is_success() {
         return Err(TrustedError::Network(format!(
@@ -532,6 +586,17 @@ fn download_artifact(artifact: &TrustedArtifact) -> Result<DownloadedArtifact, T
     Ok(DownloadedArtifact { path: tmp })
 }
 
+fn download_artifact(mirrors: &[String], artifact: &TrustedArtifact) -> Result<DownloadedArtifact, TrustedError> {
+    let mut last_err = None;
+    for base in trusted_bases(mirrors) {
+        match download_artifact_from(&base, artifact) {
+            Ok(downloaded) => return Ok(downloaded),
+            Err(err) => last_err = Some(err),
+        }
+    }
+    Err(last_err.unwrap_or_else(|| TrustedError::Network("No trusted mirror available".to_string())))
+}
+
 fn clean_entry_path(entry: &str) -> Option<String> {
     let normalized = entry.replace('\\', "/");
     if normalized.trim().is_empty() || normalized.ends_with('/') {
@@ -611,7 +676,7 @@ fn check_files(
             let sha = fs::File::open(&target)
                 .and_then(|mut f| {
                     let res = sha256_reader(&mut f);
-                    res.map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))
+                    res.map_err(|e| std::io::Error::other(e.to_string()))
                 })
                 .ok();
             let matches = sha.as_ref().map(|s| s == &file.sha256).unwrap_or(false);
@@ -620,7 +685,7 @@ fn check_files(
                 let lower = file.path.to_lowercase();
                 if lower.ends_with(".dll") || lower.ends_with(".exe") {
                      if let Some(ts) = get_pe_timestamp(&target) {
-                         if max_mismatch_ts.map_or(true, |current| ts > current) {
+                         if max_mismatch_ts.is_none_or(|current| ts > current) {
                              max_mismatch_ts = Some(ts);
                          }
                      }
@@ -673,17 +738,17 @@ fn check_files(
     }
 }
 
-pub fn verify_segatoools_for_active() -> Result<SegatoolsTrustStatus, TrustedError> {
+pub fn verify_segatoools_for_active(mirrors: &[String]) -> Result<SegatoolsTrustStatus, TrustedError> {
     let ctx = active_game_ctx()?;
 
     if let Some(cached) = cached_status_for(&ctx.root) {
         return Ok(cached);
     }
 
-    let manifest = fetch_manifest()?;
+    let manifest = fetch_manifest(mirrors)?;
     let artifact = select_artifact(&manifest, &ctx.game)?;
     let downloaded = if artifact.files.is_empty() {
-        Some(download_artifact(artifact)?)
+        Some(download_artifact(mirrors, artifact)?)
     } else {
         None
     };
@@ -730,6 +795,7 @@ fn backup_existing(
 
     let mut backed_up = Vec::new();
     let mut new_files = Vec::new();
+    let mut file_sha256 = BTreeMap::new();
 
     for entry in entries {
         let target = root.join(entry);
@@ -737,6 +803,7 @@ fn backup_existing(
             let backup_target = files_dir.join(entry);
             ensure_parent(&backup_target)?;
             fs::copy(&target, &backup_target)?;
+            file_sha256.insert(entry.clone(), sha256_file(&backup_target)?);
             backed_up.push(entry.clone());
         } else {
             new_files.push(entry.clone());
@@ -750,13 +817,36 @@ fn backup_existing(
         build_id: Some(manifest.build_id.clone()),
         backed_up_files: backed_up,
         new_files,
+        file_sha256,
+        corrupted_files: Vec::new(),
+        verified_at: None,
     };
+    write_backup_metadata(&backup_root, &metadata)?;
 
+    Ok((backup_root, metadata))
+}
+
+/// Deployment snapshot directory for a game's segatools root.
+pub fn backup_root_for(root: &Path) -> PathBuf {
+    root.join(BACKUP_DIR)
+}
+
+pub fn backup_file_path(backup_root: &Path, file: &str) -> PathBuf {
+    backup_root.join(BACKUP_FILES_DIR).join(file)
+}
+
+pub fn read_backup_metadata(backup_root: &Path) -> Result<Option<BackupMetadata>, TrustedError> {
     let meta_path = backup_root.join(BACKUP_META_NAME);
-    let meta_json = serde_json::to_string_pretty(&metadata)?;
-    fs::write(meta_path, meta_json)?;
+    if !meta_path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::from_slice(&fs::read(&meta_path)?)?))
+}
 
-    Ok((backup_root, metadata))
+pub fn write_backup_metadata(backup_root: &Path, metadata: &BackupMetadata) -> Result<(), TrustedError> {
+    let meta_json = serde_json::to_string_pretty(metadata)?;
+    fs::write(backup_root.join(BACKUP_META_NAME), meta_json)?;
+    Ok(())
 }
 
 fn extract_artifact(root: &Path, path: &Path) -> Result<(), TrustedError> {
@@ -774,11 +864,11 @@ fn extract_artifact(root: &Path, path: &Path) -> Result<(), TrustedError> {
     Ok(())
 }
 
-pub fn deploy_segatoools_for_active(force: bool) -> Result<DeployResult, TrustedError> {
+pub fn deploy_segatoools_for_active(force: bool, mirrors: &[String]) -> Result<DeployResult, TrustedError> {
     let ctx = active_game_ctx()?;
-    let manifest = fetch_manifest()?;
+    let manifest = fetch_manifest(mirrors)?;
     let artifact = select_artifact(&manifest, &ctx.game)?;
-    let downloaded = download_artifact(artifact)?;
+    let downloaded = download_artifact(mirrors, artifact)?;
     let entries = collect_zip_entries(downloaded.path.path())?;
     let existing: Vec<String> = entries
         .iter()
@@ -799,7 +889,10 @@ pub fn deploy_segatoools_for_active(force: bool) -> Result<DeployResult, Trusted
     }
 
     if !existing.is_empty() {
-        let _ = backup_existing(&ctx.root, &entries, artifact, &manifest)?;
+        // Config slots are not part of the artifact but roll back together with segatools.ini.
+        let mut backup_entries = entries.clone();
+        backup_entries.extend(slot_file_names(&ctx.root));
+        let _ = backup_existing(&ctx.root, &backup_entries, artifact, &manifest)?;
     }
 
     extract_artifact(&ctx.root, downloaded.path.path())?;
@@ -821,20 +914,155 @@ pub fn deploy_segatoools_for_active(force: bool) -> Result<DeployResult, Trusted
     })
 }
 
-pub fn rollback_segatoools_for_active() -> Result<RollbackResult, TrustedError> {
+/// Relative paths of segatools files known to have been deployed under `root`,
+/// taken from the last verification cache, the deploy backup metadata and any imported install.
+pub fn deployed_files_for_root(root: &Path) -> Vec<String> {
+    let mut files: Vec<String> = Vec::new();
+    if let Ok(data) = fs::read(trust_cache_path(root)) {
+        if let Ok(entry) = serde_json::from_slice::<PersistentTrustEntry>(&data) {
+            files.extend(entry.status.checked_files.into_iter().map(|f| f.path));
+        }
+    }
+    let meta_path = root.join(BACKUP_DIR).join(BACKUP_META_NAME);
+    if let Ok(data) = fs::read(meta_path) {
+        if let Ok(meta) = serde_json::from_slice::<BackupMetadata>(&data) {
+            files.extend(meta.backed_up_files);
+            files.extend(meta.new_files);
+        }
+    }
+    if let Some(record) = read_imported_install(root) {
+        files.extend(record.files.into_iter().map(|f| f.path));
+    }
+    let mut files: Vec<String> = files.iter().filter_map(|f| clean_entry_path(f)).collect();
+    files.sort();
+    files.dedup();
+    files
+}
+
+/// Best-effort description of the segatools build deployed under `root`, without network access.
+/// Prefers the trusted build id from the last verification and falls back to the newest
+/// PE link timestamp of the DLLs in the folder.
+pub fn local_segatools_version(root: &Path) -> Option<String> {
+    if let Ok(data) = fs::read(trust_cache_path(root)) {
+        if let Ok(entry) = serde_json::from_slice::<PersistentTrustEntry>(&data) {
+            if let Some(build_id) = entry.status.build_id.filter(|id| !id.is_empty()) {
+                return Some(format!("trusted build {}", build_id));
+            }
+        }
+    }
+    let newest = fs::read_dir(root)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .map(|ext| ext.eq_ignore_ascii_case("dll"))
+                .unwrap_or(false)
+        })
+        .filter_map(|path| get_pe_timestamp(&path))
+        .max()?;
+    Some(format!("built {}", format_timestamp(newest)))
+}
+
+fn is_hook_dll(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    lower.ends_with(".dll") && lower.contains("hook")
+}
+
+fn is_inject_exe(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    lower.starts_with("inject") && lower.ends_with(".exe")
+}
+
+/// Segatools files in a game folder set up by hand: `segatools.ini`, its config slots and the
+/// inject executables and hook DLLs beside it. Empty unless both the ini and a hook DLL exist.
+pub fn find_existing_install(dir: &Path) -> Vec<String> {
+    if !dir.join("segatools.ini").is_file() {
+        return Vec::new();
+    }
+    let mut binaries: Vec<String> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| entry.path().is_file())
+                .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+                .filter(|name| is_hook_dll(name) || is_inject_exe(name))
+                .collect()
+        })
+        .unwrap_or_default();
+    if !binaries.iter().any(|name| is_hook_dll(name)) {
+        return Vec::new();
+    }
+    binaries.sort_by_key(|name| name.to_lowercase());
+    let mut files = vec!["segatools.ini".to_string()];
+    files.extend(slot_file_names(dir));
+    files.append(&mut binaries);
+    files
+}
+
+/// Copies an existing install found in `source` into the segatools root `root` and records
+/// the hash and build time of each file. Returns `None` when there is nothing to import or
+/// `root` already has its own segatools.ini, which is never overwritten.
+pub fn import_existing_install(source: &Path, root: &Path) -> Result<Option<ImportedInstall>, TrustedError> {
+    let entries = find_existing_install(source);
+    if entries.is_empty() || root.join("segatools.ini").exists() {
+        return Ok(None);
+    }
+    fs::create_dir_all(root)?;
+    clear_cached_status(root);
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let target = root.join(&entry);
+        fs::copy(source.join(&entry), &target)?;
+        let build_time = if is_binary_path(&entry) {
+            get_pe_timestamp(&target).map(format_timestamp)
+        } else {
+            None
+        };
+        files.push(ImportedFile {
+            sha256: sha256_file(&target)?,
+            path: entry,
+            build_time,
+        });
+    }
+
+    let record = ImportedInstall {
+        source_dir: source.to_string_lossy().to_string(),
+        imported_at: Utc::now().to_rfc3339(),
+        files,
+    };
+    fs::write(root.join(INSTALL_RECORD_NAME), serde_json::to_string_pretty(&record)?)?;
+    Ok(Some(record))
+}
+
+pub fn read_imported_install(root: &Path) -> Option<ImportedInstall> {
+    let data = fs::read(root.join(INSTALL_RECORD_NAME)).ok()?;
+    serde_json::from_slice(&data).ok()
+}
+
+/// Backup directory, verification cache and import record kept next to deployed segatools files.
+pub fn trust_state_paths_for_root(root: &Path) -> Vec<PathBuf> {
+    vec![root.join(BACKUP_DIR), trust_cache_path(root), root.join(INSTALL_RECORD_NAME)]
+}
+
+pub fn rollback_segatoools_for_active(mirrors: &[String]) -> Result<RollbackResult, TrustedError> {
     let ctx = active_game_ctx()?;
-    let backup_root = ctx.root.join(BACKUP_DIR);
-    let meta_path = backup_root.join(BACKUP_META_NAME);
-    if !meta_path.exists() {
-        return Err(TrustedError::NotFound(
-            "No segatools backup available to roll back".to_string(),
-        ));
+    let backup_root = backup_root_for(&ctx.root);
+    let meta = read_backup_metadata(&backup_root)?.ok_or_else(|| {
+        TrustedError::NotFound("No segatools backup available to roll back".to_string())
+    })?;
+    if !meta.corrupted_files.is_empty() {
+        return Err(TrustedError::Verification(format!(
+            "Segatools backup is corrupted: {}",
+            meta.corrupted_files.join(", ")
+        )));
     }
-    let meta: BackupMetadata = serde_json::from_slice(&fs::read(&meta_path)?)?;
 
     clear_cached_status(&ctx.root);
     for file in &meta.backed_up_files {
-        let backup_path = backup_root.join(BACKUP_FILES_DIR).join(file);
+        let backup_path = backup_file_path(&backup_root, file);
         let target = ctx.root.join(file);
         ensure_parent(&target)?;
         fs::copy(&backup_path, &target)?;
@@ -847,7 +1075,7 @@ pub fn rollback_segatoools_for_active() -> Result<RollbackResult, TrustedError>
         }
     }
 
-    let verification = verify_segatoools_for_active().ok();
+    let verification = verify_segatoools_for_active(mirrors).ok();
 
     Ok(RollbackResult {
         restored: true,
@@ -855,3 +1083,39 @@ pub fn rollback_segatoools_for_active() -> Result<RollbackResult, TrustedError>
         verification,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn imports_hand_deployed_installs_once() {
+        let game = TempDir::new().unwrap();
+        let root = TempDir::new().unwrap();
+        let root = root.path().join("1700000000000");
+        fs::write(game.path().join("segatools.ini"), "[vfs]\namfs=..\\amfs\n").unwrap();
+        fs::write(game.path().join("inject_x64.exe"), b"inject").unwrap();
+        fs::write(game.path().join("amdaemon.exe"), b"game").unwrap();
+        assert!(find_existing_install(game.path()).is_empty());
+
+        fs::write(game.path().join("mai2hook.dll"), b"hook").unwrap();
+        assert_eq!(
+            find_existing_install(game.path()),
+            vec!["segatools.ini", "inject_x64.exe", "mai2hook.dll"]
+        );
+
+        let record = import_existing_install(game.path(), &root).unwrap().unwrap();
+        assert_eq!(record.files.len(), 3);
+        assert_eq!(fs::read(root.join("mai2hook.dll")).unwrap(), b"hook");
+        assert!(!root.join("amdaemon.exe").exists());
+        assert_eq!(
+            deployed_files_for_root(&root),
+            vec!["inject_x64.exe", "mai2hook.dll", "segatools.ini"]
+        );
+
+        fs::write(root.join("segatools.ini"), "[vfs]\n").unwrap();
+        assert!(import_existing_install(game.path(), &root).unwrap().is_none());
+        assert_eq!(fs::read_to_string(root.join("segatools.ini")).unwrap(), "[vfs]\n");
+    }
+}
@@ -1,22 +1,81 @@
+//! VHD-packaged games: their image config, runtime differencing disks and snapshots, and
+//! mounting through the VirtDisk API. Mounting is Windows-only and fails elsewhere.
+
 use crate::config::paths::segatools_root_for_game_id;
 use crate::error::ConfigError;
+use crate::platform::{free_space_bytes, powershell_support, vhd_mount_blocker, PowerShellSupport};
 use serde::{Deserialize, Deserializer, Serialize};
+use sha2::{Digest, Sha256};
+#[cfg(target_os = "windows")]
 use std::ffi::c_void;
 use std::ffi::OsStr;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::io::Read;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+#[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
+#[cfg(target_os = "windows")]
 use std::os::windows::ffi::OsStrExt;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use std::thread::sleep;
 
+#[cfg(target_os = "windows")]
 const CREATE_NO_WINDOW: u32 = 0x08000000;
+const DEFAULT_DELTA_MIN_FREE_MB: u64 = 2048;
+const DEFAULT_LETTER_WAIT_SECS: u32 = 15;
+const LETTER_POLL_INTERVAL: Duration = Duration::from_millis(500);
+const DELTA_SNAPSHOT_DIR_NAME: &str = "delta_snapshots";
+const DELTA_SNAPSHOT_INDEX_NAME: &str = "snapshots.json";
+const WARM_UP_CHUNK_SIZE: usize = 4 * 1024 * 1024;
+const WARM_UP_PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+/// Floppy and system drive letters are never handed out.
+const RESERVED_DRIVE_LETTERS: &[char] = &['A', 'B', 'C'];
+/// Written into vhd.json; files without it predate the unified schema and are rewritten on load.
+const VHD_CONFIG_VERSION: u32 = 2;
+const MOUNT_STATE_FILE_NAME: &str = "configarc_vhd_mount_state.json";
+/// How long recovery waits for a leftover elevated helper to unmount before detaching itself.
+const STALE_HELPER_TIMEOUT: Duration = Duration::from_secs(10);
+/// Covers the UAC prompt the elevated helper waits behind before it opens its pipe.
+const HELPER_CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+const HELPER_MOUNT_TIMEOUT: Duration = Duration::from_secs(60);
+const HELPER_UNMOUNT_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub mod chain;
+mod helper_ipc;
+pub mod migration;
+
+/// Mount points of the app/appdata/option images currently attached by this process.
+static ACTIVE_MOUNT_POINTS: Mutex<Option<MountPoints>> = Mutex::new(None);
+/// Held while mounting or recovering, so a launch never races the startup recovery pass.
+static MOUNT_LOCK: Mutex<()> = Mutex::new(());
 
 fn default_true() -> bool {
     true
 }
 
+fn default_delta_min_free_mb() -> u64 {
+    DEFAULT_DELTA_MIN_FREE_MB
+}
+
+fn is_default_delta_min_free_mb(value: &u64) -> bool {
+    *value == DEFAULT_DELTA_MIN_FREE_MB
+}
+
+fn default_letter_wait_secs() -> u32 {
+    DEFAULT_LETTER_WAIT_SECS
+}
+
+fn is_default_letter_wait_secs(value: &u32) -> bool {
+    *value == DEFAULT_LETTER_WAIT_SECS
+}
+
+fn is_zero(value: &u32) -> bool {
+    *value == 0
+}
+
 fn normalize_patch_paths(paths: Vec<String>) -> Vec<String> {
     paths
         .into_iter()
@@ -25,17 +84,32 @@ fn normalize_patch_paths(paths: Vec<String>) -> Vec<String> {
         .collect()
 }
 
+/// Every layout vhd.json has had: the app/appdata/option one, its older single-patch form,
+/// and the base/patch naming of the first VHD support.
 #[derive(Debug, Deserialize)]
 struct RawVhdConfig {
-    pub app_base_path: String,
     #[serde(default)]
+    pub version: u32,
+    #[serde(alias = "base_path")]
+    pub app_base_path: String,
+    #[serde(default, alias = "patch_paths")]
     pub app_patch_paths: Vec<String>,
-    #[serde(default)]
+    #[serde(default, alias = "patch_path")]
     pub app_patch_path: Option<String>,
     pub appdata_path: String,
     pub option_path: String,
     #[serde(default = "default_true")]
     pub delta_enabled: bool,
+    #[serde(default)]
+    pub delta_dir: Option<String>,
+    #[serde(default = "default_delta_min_free_mb")]
+    pub delta_min_free_mb: u64,
+    #[serde(default)]
+    pub delta_snapshots: u32,
+    #[serde(default)]
+    pub mount_root: Option<String>,
+    #[serde(default = "default_letter_wait_secs")]
+    pub letter_wait_secs: u32,
 }
 
 #[derive(Debug, Clone, Serialize, PartialEq, Eq)]
@@ -47,6 +121,24 @@ pub struct VhdConfig {
     pub option_path: String,
     #[serde(default = "default_true")]
     pub delta_enabled: bool,
+    /// Alternate directory for the runtime delta, e.g. a RAM disk. Falls back to the
+    /// parent VHD directory when missing or short on space.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delta_dir: Option<String>,
+    #[serde(default = "default_delta_min_free_mb", skip_serializing_if = "is_default_delta_min_free_mb")]
+    pub delta_min_free_mb: u64,
+    /// Finished sessions whose runtime delta is kept as a snapshot instead of being deleted.
+    /// 0 discards the delta at unmount.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub delta_snapshots: u32,
+    /// Empty NTFS folder whose `app`, `appdata` and `option` subfolders receive the volumes
+    /// instead of drive letters.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mount_root: Option<String>,
+    /// How long a launch waits for X:/Y:/Z: to be released by a virtual disk that is still
+    /// detaching before it mounts on other letters.
+    #[serde(default = "default_letter_wait_secs", skip_serializing_if = "is_default_letter_wait_secs")]
+    pub letter_wait_secs: u32,
 }
 
 impl<'de> Deserialize<'de> for VhdConfig {
@@ -54,20 +146,36 @@ impl<'de> Deserialize<'de> for VhdConfig {
     where
         D: Deserializer<'de>,
     {
-        let raw = RawVhdConfig::deserialize(deserializer)?;
+        RawVhdConfig::deserialize(deserializer).map(VhdConfig::from)
+    }
+}
+
+impl From<RawVhdConfig> for VhdConfig {
+    fn from(raw: RawVhdConfig) -> Self {
         let mut app_patch_paths = normalize_patch_paths(raw.app_patch_paths);
         if app_patch_paths.is_empty() {
             if let Some(legacy_path) = raw.app_patch_path {
                 app_patch_paths = normalize_patch_paths(vec![legacy_path]);
             }
         }
-        Ok(Self {
+        Self {
             app_base_path: raw.app_base_path,
             app_patch_paths,
             appdata_path: raw.appdata_path,
             option_path: raw.option_path,
             delta_enabled: raw.delta_enabled,
-        })
+            delta_dir: raw
+                .delta_dir
+                .map(|dir| dir.trim().to_string())
+                .filter(|dir| !dir.is_empty()),
+            delta_min_free_mb: raw.delta_min_free_mb,
+            delta_snapshots: raw.delta_snapshots,
+            mount_root: raw
+                .mount_root
+                .map(|dir| dir.trim().to_string())
+                .filter(|dir| !dir.is_empty()),
+            letter_wait_secs: raw.letter_wait_secs,
+        }
     }
 }
 
@@ -78,6 +186,13 @@ pub struct ResolvedVhdConfig {
     pub appdata_path: PathBuf,
     pub option_path: PathBuf,
     pub delta_enabled: bool,
+    pub delta_dir: Option<PathBuf>,
+    pub delta_min_free_mb: u64,
+    pub mount_root: Option<PathBuf>,
+    pub letter_wait: Duration,
+    pub delta_snapshots: DeltaSnapshotPolicy,
+    /// Snapshot the next session starts from instead of a fresh delta.
+    pub delta_restore: Option<PathBuf>,
 }
 
 impl ResolvedVhdConfig {
@@ -87,31 +202,256 @@ impl ResolvedVhdConfig {
             .map(PathBuf::as_path)
             .unwrap_or_else(|| self.app_base_path.as_path())
     }
+
+    /// Picks where the runtime delta goes. The alternate directory is only used when it
+    /// exists and has at least `delta_min_free_mb` free; otherwise the delta stays next
+    /// to its parent and the reason is returned alongside.
+    pub fn runtime_delta_placement(&self) -> (PathBuf, Option<String>) {
+        let parent = self.app_parent_path();
+        let default_path = runtime_path_for_parent(parent);
+        let Some(dir) = &self.delta_dir else {
+            return (default_path, None);
+        };
+        if !dir.is_dir() {
+            return (
+                default_path,
+                Some(format!("Delta directory not found: {}", dir.to_string_lossy())),
+            );
+        }
+        let required = self.delta_min_free_mb.saturating_mul(1024 * 1024);
+        match free_space_bytes(dir) {
+            Some(free) if free < required => {
+                return (
+                    default_path,
+                    Some(format!(
+                        "Not enough free space in {} ({} MB free, {} MB required)",
+                        dir.to_string_lossy(),
+                        free / (1024 * 1024),
+                        self.delta_min_free_mb
+                    )),
+                );
+            }
+            _ => {}
+        }
+        (dir.join(shared_runtime_name(parent)), None)
+    }
+
+    /// The policy to apply at unmount, when finished deltas are kept at all.
+    fn kept_delta_snapshots(&self) -> Option<DeltaSnapshotPolicy> {
+        (self.delta_enabled && self.delta_snapshots.keep > 0).then(|| self.delta_snapshots.clone())
+    }
+
+    /// Every image a mount reads from: base, patches, appdata and option.
+    pub fn image_paths(&self) -> Vec<PathBuf> {
+        std::iter::once(self.app_base_path.clone())
+            .chain(self.app_patch_paths.iter().cloned())
+            .chain([self.appdata_path.clone(), self.option_path.clone()])
+            .collect()
+    }
 }
 
-#[derive(Debug, Clone)]
+/// On-disk format of a virtual disk, taken from its extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VirtualDiskFormat {
+    Vhd,
+    Vhdx,
+}
+
+impl VirtualDiskFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path
+            .extension()
+            .and_then(OsStr::to_str)
+            .map(|ext| ext.to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("vhd") => Some(Self::Vhd),
+            Some("vhdx") => Some(Self::Vhdx),
+            _ => None,
+        }
+    }
+
+    /// `VHD` or `VHDX`, as Windows names the storage type.
+    pub fn storage_type(self) -> &'static str {
+        match self {
+            Self::Vhd => "VHD",
+            Self::Vhdx => "VHDX",
+        }
+    }
+}
+
+pub fn is_virtual_disk_path(path: &Path) -> bool {
+    VirtualDiskFormat::from_path(path).is_some()
+}
+
+fn disk_format_for(label: &str, path: &Path) -> Result<VirtualDiskFormat, String> {
+    VirtualDiskFormat::from_path(path).ok_or_else(|| {
+        format!(
+            "{} must be a .vhd or .vhdx file: {}",
+            label,
+            path.to_string_lossy()
+        )
+    })
+}
+
+/// Where one image is attached.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MountPoint {
+    Drive(char),
+    /// Empty NTFS folder the volume is attached to.
+    Folder(PathBuf),
+}
+
+impl MountPoint {
+    /// Root of the mounted volume, e.g. `X:\`.
+    pub fn root(&self) -> PathBuf {
+        match self {
+            MountPoint::Drive(letter) => PathBuf::from(format!("{}:\\", letter)),
+            MountPoint::Folder(dir) => dir.clone(),
+        }
+    }
+
+    /// `Add-PartitionAccessPath` and the BitLocker cmdlets want a trailing separator on folders.
+    pub fn access_path(&self) -> String {
+        match self {
+            MountPoint::Drive(letter) => format!("{}:\\", letter),
+            MountPoint::Folder(dir) => format!("{}\\", dir.to_string_lossy().trim_end_matches('\\')),
+        }
+    }
+
+    /// `X:` for drives, the access path for folders.
+    pub fn volume_name(&self) -> String {
+        match self {
+            MountPoint::Drive(letter) => format!("{}:", letter),
+            MountPoint::Folder(_) => self.access_path(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MountRole {
+    App,
+    AppData,
+    Option,
+}
+
+impl MountRole {
+    pub const ALL: [MountRole; 3] = [MountRole::App, MountRole::AppData, MountRole::Option];
+
+    /// Letter used whenever it is free. BitLocker secrets stay keyed by it, so
+    /// `CONFIGARC_BITLOCKER_X_PASSWORD` keeps meaning the app volume wherever it lands.
+    pub fn preferred_letter(self) -> char {
+        match self {
+            MountRole::App => 'X',
+            MountRole::AppData => 'Y',
+            MountRole::Option => 'Z',
+        }
+    }
+
+    fn folder_name(self) -> &'static str {
+        match self {
+            MountRole::App => "app",
+            MountRole::AppData => "appdata",
+            MountRole::Option => "option",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MountPoints {
+    pub app: MountPoint,
+    pub appdata: MountPoint,
+    pub option: MountPoint,
+}
+
+impl Default for MountPoints {
+    /// The classic X:/Y:/Z: layout.
+    fn default() -> Self {
+        Self {
+            app: MountPoint::Drive(MountRole::App.preferred_letter()),
+            appdata: MountPoint::Drive(MountRole::AppData.preferred_letter()),
+            option: MountPoint::Drive(MountRole::Option.preferred_letter()),
+        }
+    }
+}
+
+impl MountPoints {
+    pub fn get(&self, role: MountRole) -> &MountPoint {
+        match role {
+            MountRole::App => &self.app,
+            MountRole::AppData => &self.appdata,
+            MountRole::Option => &self.option,
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (MountRole, &MountPoint)> {
+        MountRole::ALL.into_iter().map(move |role| (role, self.get(role)))
+    }
+
+    /// True when `path` lies on one of these volumes.
+    pub fn contains(&self, path: &str) -> bool {
+        let lower = path.trim().to_ascii_lowercase();
+        self.iter().any(|(_, point)| {
+            let root = point.access_path().to_ascii_lowercase();
+            lower.starts_with(&root) || lower == root.trim_end_matches('\\')
+        })
+    }
+
+    /// One-line summary for logs and dry runs.
+    pub fn describe(&self) -> String {
+        format!(
+            "app at {}, appdata at {}, option at {}",
+            self.app.access_path(),
+            self.appdata.access_path(),
+            self.option.access_path()
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MountedVhd {
     pub app_mount_path: PathBuf,
     pub app_runtime_path: Option<PathBuf>,
     pub appdata_mount_path: PathBuf,
     pub option_mount_path: PathBuf,
     pub repair_root: Option<PathBuf>,
+    pub mount_points: MountPoints,
+    pub delta_snapshots: Option<DeltaSnapshotPolicy>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ElevatedVhdMount {
     pub script_path: PathBuf,
-    pub result_path: PathBuf,
-    pub signal_path: PathBuf,
-    pub done_path: PathBuf,
+    /// Named pipe the helper serves its unmount request on. Empty in state files written
+    /// before the helper used pipes.
+    #[serde(default)]
+    pub pipe_name: String,
+    pub mount_points: MountPoints,
+    /// Images the helper attached, so they can still be detached if the helper is gone.
+    pub app_mount_path: PathBuf,
+    pub appdata_mount_path: PathBuf,
+    pub option_mount_path: PathBuf,
+    pub app_runtime_path: Option<PathBuf>,
+    pub delta_snapshots: Option<DeltaSnapshotPolicy>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
 pub enum VhdMountHandle {
     Direct(MountedVhd),
     Elevated(ElevatedVhdMount),
 }
 
+impl VhdMountHandle {
+    pub fn mount_points(&self) -> &MountPoints {
+        match self {
+            VhdMountHandle::Direct(mounted) => &mounted.mount_points,
+            VhdMountHandle::Elevated(mounted) => &mounted.mount_points,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct HelperResult {
     ok: bool,
@@ -127,10 +467,22 @@ struct VhdHelperParams {
     pub app_data: PathBuf,
     pub option: PathBuf,
     pub delta: bool,
+    pub delta_path: Option<PathBuf>,
     pub repair_root: Option<PathBuf>,
-    pub result_path: PathBuf,
-    pub signal_path: PathBuf,
-    pub done_path: PathBuf,
+    /// The runtime delta was restored from a snapshot and must not be recreated.
+    pub reuse_runtime: bool,
+    /// Leave the runtime delta on disk at teardown so it can be stored as a snapshot.
+    pub keep_runtime: bool,
+    pub app_mount_point: String,
+    pub appdata_mount_point: String,
+    pub option_mount_point: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case", tag = "command")]
+enum HelperRequest {
+    Mount(Box<VhdHelperParams>),
+    Unmount,
 }
 
 #[derive(Debug, Clone)]
@@ -164,11 +516,21 @@ struct OpenVirtualDiskParametersVersion1 {
     rw_depth: u32,
 }
 
+#[cfg(target_os = "windows")]
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct OpenVirtualDiskParametersVersion2 {
+    get_info_only: i32,
+    read_only: i32,
+    resiliency_guid: Guid,
+}
+
 #[cfg(target_os = "windows")]
 #[derive(Clone, Copy)]
 #[repr(C)]
 union OpenVirtualDiskParametersUnion {
     version1: OpenVirtualDiskParametersVersion1,
+    version2: OpenVirtualDiskParametersVersion2,
 }
 
 #[cfg(target_os = "windows")]
@@ -194,6 +556,90 @@ struct SetVirtualDiskInfo {
     union_data: SetVirtualDiskInfoUnion,
 }
 
+#[cfg(target_os = "windows")]
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct CreateVirtualDiskParametersVersion2 {
+    unique_id: Guid,
+    maximum_size: u64,
+    block_size_in_bytes: u32,
+    sector_size_in_bytes: u32,
+    physical_sector_size_in_bytes: u32,
+    parent_path: *const u16,
+    source_path: *const u16,
+    open_flags: u32,
+    parent_virtual_storage_type: VirtualStorageType,
+    source_virtual_storage_type: VirtualStorageType,
+    resiliency_guid: Guid,
+}
+
+#[cfg(target_os = "windows")]
+#[derive(Clone, Copy)]
+#[repr(C)]
+union CreateVirtualDiskParametersUnion {
+    version2: CreateVirtualDiskParametersVersion2,
+}
+
+#[cfg(target_os = "windows")]
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct CreateVirtualDiskParameters {
+    version: u32,
+    union_data: CreateVirtualDiskParametersUnion,
+}
+
+#[cfg(target_os = "windows")]
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct AttachVirtualDiskParameters {
+    version: u32,
+    reserved: u32,
+}
+
+#[cfg(target_os = "windows")]
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct DiskExtent {
+    disk_number: u32,
+    starting_offset: i64,
+    extent_length: i64,
+}
+
+#[cfg(target_os = "windows")]
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct VolumeDiskExtents {
+    number_of_disk_extents: u32,
+    extents: [DiskExtent; 1],
+}
+
+/// Owns a handle from `OpenVirtualDisk`/`CreateVirtualDisk` and closes it on drop.
+#[cfg(target_os = "windows")]
+struct VirtualDiskHandle(*mut c_void);
+
+#[cfg(target_os = "windows")]
+impl Drop for VirtualDiskHandle {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = CloseHandle(self.0);
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+const NULL_GUID: Guid = Guid {
+    data1: 0,
+    data2: 0,
+    data3: 0,
+    data4: [0; 8],
+};
+#[cfg(target_os = "windows")]
+const VIRTUAL_STORAGE_TYPE_VENDOR_MICROSOFT: Guid = Guid {
+    data1: 0xec98_4aec,
+    data2: 0xa0f9,
+    data3: 0x47e9,
+    data4: [0x90, 0x1f, 0x71, 0x41, 0x5a, 0x66, 0x34, 0x5b],
+};
 #[cfg(target_os = "windows")]
 const VIRTUAL_STORAGE_TYPE_DEVICE_VHD: u32 = 2;
 #[cfg(target_os = "windows")]
@@ -206,6 +652,38 @@ const OPEN_VIRTUAL_DISK_VERSION_1: u32 = 1;
 const OPEN_VIRTUAL_DISK_FLAG_NO_PARENTS: u32 = 0x0000_0001;
 #[cfg(target_os = "windows")]
 const SET_VIRTUAL_DISK_INFO_PARENT_PATH: u32 = 1;
+#[cfg(target_os = "windows")]
+const VIRTUAL_DISK_ACCESS_NONE: u32 = 0;
+#[cfg(target_os = "windows")]
+const OPEN_VIRTUAL_DISK_VERSION_2: u32 = 2;
+#[cfg(target_os = "windows")]
+const OPEN_VIRTUAL_DISK_FLAG_NONE: u32 = 0;
+#[cfg(target_os = "windows")]
+const CREATE_VIRTUAL_DISK_VERSION_2: u32 = 2;
+#[cfg(target_os = "windows")]
+const CREATE_VIRTUAL_DISK_FLAG_NONE: u32 = 0;
+#[cfg(target_os = "windows")]
+const ATTACH_VIRTUAL_DISK_VERSION_1: u32 = 1;
+#[cfg(target_os = "windows")]
+const ATTACH_VIRTUAL_DISK_FLAG_NO_DRIVE_LETTER: u32 = 0x0000_0002;
+/// Keeps the disk attached after our handle closes; it stays until `DetachVirtualDisk`.
+#[cfg(target_os = "windows")]
+const ATTACH_VIRTUAL_DISK_FLAG_PERMANENT_LIFETIME: u32 = 0x0000_0004;
+#[cfg(target_os = "windows")]
+const DETACH_VIRTUAL_DISK_FLAG_NONE: u32 = 0;
+#[cfg(target_os = "windows")]
+const IOCTL_VOLUME_GET_VOLUME_DISK_EXTENTS: u32 = 0x0056_0000;
+#[cfg(target_os = "windows")]
+const FILE_SHARE_READ: u32 = 0x0000_0001;
+#[cfg(target_os = "windows")]
+const FILE_SHARE_WRITE: u32 = 0x0000_0002;
+#[cfg(target_os = "windows")]
+const OPEN_EXISTING: u32 = 3;
+#[cfg(target_os = "windows")]
+const INVALID_HANDLE_VALUE: isize = -1;
+/// How long an attached disk may take before its volume shows up in the volume list.
+#[cfg(target_os = "windows")]
+const VOLUME_ARRIVAL_TIMEOUT: Duration = Duration::from_secs(10);
 
 #[cfg(target_os = "windows")]
 #[link(name = "VirtDisk")]
@@ -223,12 +701,78 @@ extern "system" {
         virtual_disk_handle: *mut c_void,
         virtual_disk_info: *const SetVirtualDiskInfo,
     ) -> u32;
+
+    fn CreateVirtualDisk(
+        virtual_storage_type: *const VirtualStorageType,
+        path: *const u16,
+        virtual_disk_access_mask: u32,
+        security_descriptor: *const c_void,
+        flags: u32,
+        provider_specific_flags: u32,
+        parameters: *const CreateVirtualDiskParameters,
+        overlapped: *mut c_void,
+        handle: *mut *mut c_void,
+    ) -> u32;
+
+    fn AttachVirtualDisk(
+        virtual_disk_handle: *mut c_void,
+        security_descriptor: *const c_void,
+        flags: u32,
+        provider_specific_flags: u32,
+        parameters: *const AttachVirtualDiskParameters,
+        overlapped: *mut c_void,
+    ) -> u32;
+
+    fn DetachVirtualDisk(virtual_disk_handle: *mut c_void, flags: u32, provider_specific_flags: u32) -> u32;
+
+    fn GetVirtualDiskPhysicalPath(
+        virtual_disk_handle: *mut c_void,
+        disk_path_size_in_bytes: *mut u32,
+        disk_path: *mut u16,
+    ) -> u32;
 }
 
 #[cfg(target_os = "windows")]
 #[link(name = "kernel32")]
 extern "system" {
     fn CloseHandle(handle: *mut c_void) -> i32;
+
+    fn GetLogicalDrives() -> u32;
+
+    fn CreateFileW(
+        file_name: *const u16,
+        desired_access: u32,
+        share_mode: u32,
+        security_attributes: *mut c_void,
+        creation_disposition: u32,
+        flags_and_attributes: u32,
+        template_file: *mut c_void,
+    ) -> *mut c_void;
+
+    fn DeviceIoControl(
+        device: *mut c_void,
+        io_control_code: u32,
+        in_buffer: *mut c_void,
+        in_buffer_size: u32,
+        out_buffer: *mut c_void,
+        out_buffer_size: u32,
+        bytes_returned: *mut u32,
+        overlapped: *mut c_void,
+    ) -> i32;
+
+    fn FindFirstVolumeW(volume_name: *mut u16, buffer_length: u32) -> *mut c_void;
+
+    fn FindNextVolumeW(find_volume: *mut c_void, volume_name: *mut u16, buffer_length: u32) -> i32;
+
+    fn FindVolumeClose(find_volume: *mut c_void) -> i32;
+
+    fn SetVolumeMountPointW(volume_mount_point: *const u16, volume_name: *const u16) -> i32;
+
+    fn DeleteVolumeMountPointW(volume_mount_point: *const u16) -> i32;
+
+    fn OpenProcess(desired_access: u32, inherit_handle: i32, process_id: u32) -> *mut c_void;
+
+    fn GetExitCodeProcess(process: *mut c_void, exit_code: *mut u32) -> i32;
 }
 
 const VHD_HELPER_SCRIPT: &str = include_str!("../scripts/vhd-helper.ps1");
@@ -237,26 +781,53 @@ pub fn vhd_config_path_for_game_id(game_id: &str) -> PathBuf {
     segatools_root_for_game_id(game_id).join("vhd.json")
 }
 
-pub fn load_vhd_config(game_id: &str) -> Result<VhdConfig, ConfigError> {
-    let path = vhd_config_path_for_game_id(game_id);
-    if !path.exists() {
-        return Err(ConfigError::NotFound("vhd.json not found".to_string()));
-    }
-    let data = fs::read_to_string(&path)?;
-    let cfg: VhdConfig = serde_json::from_str(&data)?;
-    Ok(cfg)
+#[derive(Serialize)]
+struct VersionedVhdConfig<'a> {
+    version: u32,
+    #[serde(flatten)]
+    config: &'a VhdConfig,
 }
 
-pub fn save_vhd_config(game_id: &str, cfg: &VhdConfig) -> Result<(), ConfigError> {
-    let path = vhd_config_path_for_game_id(game_id);
+fn write_vhd_config_at(path: &Path, cfg: &VhdConfig) -> Result<(), ConfigError> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)?;
     }
-    let json = serde_json::to_string_pretty(cfg)?;
+    let json = serde_json::to_string_pretty(&VersionedVhdConfig {
+        version: VHD_CONFIG_VERSION,
+        config: cfg,
+    })?;
     fs::write(path, json)?;
     Ok(())
 }
 
+/// Reads vhd.json in any earlier layout. An older file is rewritten in the current one, with
+/// the original kept once as `vhd.json.bak`; a failed rewrite still returns the config.
+fn load_vhd_config_at(path: &Path) -> Result<VhdConfig, ConfigError> {
+    if !path.exists() {
+        return Err(ConfigError::NotFound("vhd.json not found".to_string()));
+    }
+    let data = fs::read_to_string(path)?;
+    let raw: RawVhdConfig = serde_json::from_str(&data)?;
+    let outdated = raw.version < VHD_CONFIG_VERSION;
+    let cfg = VhdConfig::from(raw);
+    if outdated {
+        let backup = path.with_extension("json.bak");
+        if !backup.exists() {
+            let _ = fs::write(&backup, &data);
+        }
+        let _ = write_vhd_config_at(path, &cfg);
+    }
+    Ok(cfg)
+}
+
+pub fn load_vhd_config(game_id: &str) -> Result<VhdConfig, ConfigError> {
+    load_vhd_config_at(&vhd_config_path_for_game_id(game_id))
+}
+
+pub fn save_vhd_config(game_id: &str, cfg: &VhdConfig) -> Result<(), ConfigError> {
+    write_vhd_config_at(&vhd_config_path_for_game_id(game_id), cfg)
+}
+
 pub fn resolve_vhd_config(game_id: &str, cfg: &VhdConfig) -> Result<ResolvedVhdConfig, String> {
     let base_dir = vhd_config_path_for_game_id(game_id)
         .parent()
@@ -269,6 +840,18 @@ pub fn resolve_vhd_config(game_id: &str, cfg: &VhdConfig) -> Result<ResolvedVhdC
         .collect::<Vec<_>>();
     let appdata_path = resolve_with_base(&base_dir, cfg.appdata_path.trim());
     let option_path = resolve_with_base(&base_dir, cfg.option_path.trim());
+    let delta_dir = cfg
+        .delta_dir
+        .as_deref()
+        .map(str::trim)
+        .filter(|dir| !dir.is_empty())
+        .map(|dir| resolve_with_base(&base_dir, dir));
+    let mount_root = cfg
+        .mount_root
+        .as_deref()
+        .map(str::trim)
+        .filter(|dir| !dir.is_empty())
+        .map(|dir| resolve_with_base(&base_dir, dir));
 
     if !app_base_path.exists() {
         return Err(format!("App base VHD not found: {}", app_base_path.to_string_lossy()));
@@ -289,12 +872,46 @@ pub fn resolve_vhd_config(game_id: &str, cfg: &VhdConfig) -> Result<ResolvedVhdC
         return Err(format!("Option VHD not found: {}", option_path.to_string_lossy()));
     }
 
+    // A differencing disk has to use its parent's format, so a patch chain cannot mix the two.
+    let base_format = disk_format_for("App base VHD", &app_base_path)?;
+    for (index, app_patch_path) in app_patch_paths.iter().enumerate() {
+        let format = disk_format_for("App patch VHD", app_patch_path)?;
+        if format != base_format {
+            return Err(format!(
+                "App patch VHD at position {} is {} but the base is {}: {}",
+                index + 1,
+                format.storage_type(),
+                base_format.storage_type(),
+                app_patch_path.to_string_lossy()
+            ));
+        }
+    }
+    disk_format_for("AppData VHD", &appdata_path)?;
+    disk_format_for("Option VHD", &option_path)?;
+
+    let delta_snapshots = DeltaSnapshotPolicy {
+        dir: base_dir.join(DELTA_SNAPSHOT_DIR_NAME),
+        keep: cfg.delta_snapshots,
+        parent_path: app_patch_paths.last().unwrap_or(&app_base_path).clone(),
+    };
+    let delta_restore = if cfg.delta_enabled {
+        pending_restore_file(&delta_snapshots)?
+    } else {
+        None
+    };
+
     Ok(ResolvedVhdConfig {
         app_base_path,
         app_patch_paths,
         appdata_path,
         option_path,
         delta_enabled: cfg.delta_enabled,
+        delta_dir,
+        delta_min_free_mb: cfg.delta_min_free_mb,
+        mount_root,
+        letter_wait: Duration::from_secs(u64::from(cfg.letter_wait_secs)),
+        delta_snapshots,
+        delta_restore,
     })
 }
 
@@ -317,68 +934,627 @@ fn runtime_path_for_parent(parent_path: &Path) -> PathBuf {
     parent.join(format!("{}-runtime.{}", stem, ext))
 }
 
-fn ensure_drive_free(drive_letter: char) -> Result<(), String> {
-    let root = format!("{}:\\", drive_letter.to_ascii_uppercase());
-    if Path::new(&root).exists() {
+/// File name of the runtime delta of `parent_path` in a delta directory. Several games may
+/// share that directory with app images of the same name, so the name carries a hash of the
+/// full parent path.
+fn shared_runtime_name(parent_path: &Path) -> String {
+    let digest = Sha256::digest(parent_path.to_string_lossy().to_lowercase().as_bytes());
+    let tag: String = digest.iter().take(4).map(|b| format!("{:02x}", b)).collect();
+    let stem = parent_path
+        .file_stem()
+        .and_then(OsStr::to_str)
+        .unwrap_or("runtime");
+    let ext = parent_path.extension().and_then(OsStr::to_str).unwrap_or("vhd");
+    format!("{}-{}-runtime.{}", stem, tag, ext)
+}
+
+/// Lists runtime delta disks left next to the configured app VHDs of a game,
+/// or in its alternate delta directory.
+pub fn runtime_delta_paths_for_game(game_id: &str) -> Vec<PathBuf> {
+    let cfg = match load_vhd_config(game_id) {
+        Ok(cfg) => cfg,
+        Err(_) => return Vec::new(),
+    };
+    let base_dir = match vhd_config_path_for_game_id(game_id).parent() {
+        Some(dir) => dir.to_path_buf(),
+        None => return Vec::new(),
+    };
+    let mut parents = vec![resolve_with_base(&base_dir, cfg.app_base_path.trim())];
+    for patch in normalize_patch_paths(cfg.app_patch_paths.clone()) {
+        parents.push(resolve_with_base(&base_dir, &patch));
+    }
+    let delta_dir = cfg
+        .delta_dir
+        .as_deref()
+        .map(|dir| resolve_with_base(&base_dir, dir));
+    let mut deltas = Vec::new();
+    for parent in parents {
+        let mut candidates = vec![runtime_path_for_parent(&parent)];
+        if let Some(dir) = &delta_dir {
+            candidates.push(dir.join(shared_runtime_name(&parent)));
+        }
+        for candidate in candidates {
+            if candidate.exists() && !deltas.contains(&candidate) {
+                deltas.push(candidate);
+            }
+        }
+    }
+    deltas
+}
+
+/// Where finished runtime deltas are kept for a game.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeltaSnapshotPolicy {
+    pub dir: PathBuf,
+    /// Automatic snapshots to keep; 0 discards the delta at unmount.
+    pub keep: u32,
+    /// App image the delta sits on, as configured rather than any auto-repair copy of it.
+    pub parent_path: PathBuf,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeltaSnapshot {
+    pub name: String,
+    pub file: PathBuf,
+    /// A snapshot only restores on top of the image it was taken against.
+    pub parent_path: PathBuf,
+    pub created_at: String,
+    pub size: u64,
+    /// Renamed snapshots are left out of the keep-last-N rotation.
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeltaSnapshots {
+    /// Oldest first.
+    #[serde(default)]
+    pub snapshots: Vec<DeltaSnapshot>,
+    #[serde(default)]
+    pub pending_restore: Option<String>,
+}
+
+pub fn delta_snapshot_dir_for_game_id(game_id: &str) -> PathBuf {
+    vhd_config_path_for_game_id(game_id).with_file_name(DELTA_SNAPSHOT_DIR_NAME)
+}
+
+fn read_snapshot_index(dir: &Path) -> Result<DeltaSnapshots, String> {
+    let path = dir.join(DELTA_SNAPSHOT_INDEX_NAME);
+    if !path.exists() {
+        return Ok(DeltaSnapshots::default());
+    }
+    let data = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&data).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+fn write_snapshot_index(dir: &Path, index: &DeltaSnapshots) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let path = dir.join(DELTA_SNAPSHOT_INDEX_NAME);
+    let json = serde_json::to_string_pretty(index).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn validate_snapshot_name(name: &str) -> Result<&str, String> {
+    let name = name.trim();
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ' '))
+        && !name.starts_with('.');
+    if valid {
+        Ok(name)
+    } else {
+        Err(format!("Invalid snapshot name: {}", name))
+    }
+}
+
+/// Renames, or copies and deletes when the delta lives on another volume such as a RAM disk.
+fn move_file(from: &Path, to: &Path) -> Result<(), String> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    fs::copy(from, to).map_err(|e| format!("Failed to copy {} to {}: {}", from.display(), to.display(), e))?;
+    fs::remove_file(from).map_err(|e| format!("Failed to remove {}: {}", from.display(), e))
+}
+
+fn snapshot_file_name(name: &str, like: &Path) -> String {
+    let ext = like.extension().and_then(OsStr::to_str).unwrap_or("vhd");
+    format!("{}.{}", name, ext)
+}
+
+/// Moves a detached runtime delta into the snapshot directory, then drops the oldest
+/// unpinned snapshots beyond `policy.keep`.
+fn store_delta_snapshot(policy: &DeltaSnapshotPolicy, runtime_path: &Path) -> Result<DeltaSnapshot, String> {
+    let mut index = read_snapshot_index(&policy.dir)?;
+    fs::create_dir_all(&policy.dir).map_err(|e| format!("Failed to create {}: {}", policy.dir.display(), e))?;
+    let now = chrono::Local::now();
+    let stem = format!("session-{}", now.format("%Y%m%d-%H%M%S"));
+    let mut name = stem.clone();
+    let mut suffix = 2;
+    while index.snapshots.iter().any(|s| s.name == name) {
+        name = format!("{}-{}", stem, suffix);
+        suffix += 1;
+    }
+    let file = policy.dir.join(snapshot_file_name(&name, runtime_path));
+    move_file(runtime_path, &file)?;
+    let snapshot = DeltaSnapshot {
+        name,
+        size: fs::metadata(&file).map(|m| m.len()).unwrap_or(0),
+        file,
+        parent_path: policy.parent_path.clone(),
+        created_at: now.to_rfc3339(),
+        pinned: false,
+    };
+    index.snapshots.push(snapshot.clone());
+
+    let mut unpinned = index.snapshots.iter().filter(|s| !s.pinned).count();
+    while unpinned > policy.keep as usize {
+        let Some(oldest) = index.snapshots.iter().position(|s| !s.pinned) else {
+            break;
+        };
+        let removed = index.snapshots.remove(oldest);
+        let _ = fs::remove_file(&removed.file);
+        if index.pending_restore.as_deref() == Some(removed.name.as_str()) {
+            index.pending_restore = None;
+        }
+        unpinned -= 1;
+    }
+    write_snapshot_index(&policy.dir, &index)?;
+    Ok(snapshot)
+}
+
+/// File of the snapshot picked for the next session, checked against the current patch chain.
+fn pending_restore_file(policy: &DeltaSnapshotPolicy) -> Result<Option<PathBuf>, String> {
+    // A damaged index must not keep the game from launching with a fresh delta.
+    let index = read_snapshot_index(&policy.dir).unwrap_or_default();
+    let Some(name) = index.pending_restore.as_deref() else {
+        return Ok(None);
+    };
+    let snapshot = index
+        .snapshots
+        .iter()
+        .find(|s| s.name == name)
+        .ok_or_else(|| format!("Delta snapshot selected for restore no longer exists: {}", name))?;
+    if snapshot.parent_path != policy.parent_path {
         return Err(format!(
-            "Drive {}: is already in use. Please eject or change the assigned drive.",
-            drive_letter.to_ascii_uppercase()
+            "Delta snapshot {} was taken on top of {}, but the app image is now {}. Clear the pending restore or pick another snapshot.",
+            snapshot.name,
+            snapshot.parent_path.to_string_lossy(),
+            policy.parent_path.to_string_lossy()
         ));
     }
+    if !snapshot.file.exists() {
+        return Err(format!("Delta snapshot file not found: {}", snapshot.file.to_string_lossy()));
+    }
+    Ok(Some(snapshot.file.clone()))
+}
+
+/// Puts a copy of `snapshot` where the runtime delta goes and points it at `parent_path`,
+/// which may be an auto-repaired copy of the chain it was taken on.
+fn start_delta_from_snapshot(snapshot: &Path, delta_path: &Path, parent_path: &Path) -> Result<(), String> {
+    if VirtualDiskFormat::from_path(snapshot) != VirtualDiskFormat::from_path(delta_path) {
+        return Err(format!(
+            "Delta snapshot {} does not match the runtime delta format",
+            snapshot.to_string_lossy()
+        ));
+    }
+    fs::copy(snapshot, delta_path).map_err(|e| {
+        format!(
+            "Failed to restore delta snapshot {}: {}",
+            snapshot.to_string_lossy(),
+            e
+        )
+    })?;
+    if let Err(err) = set_vhd_parent_path(delta_path, parent_path) {
+        let _ = fs::remove_file(delta_path);
+        return Err(err);
+    }
     Ok(())
 }
 
-fn ensure_mount_points_free() -> Result<(), String> {
-    ensure_drive_free('X')?;
-    ensure_drive_free('Y')?;
-    ensure_drive_free('Z')?;
+fn clear_pending_restore(dir: &Path) -> Result<(), String> {
+    let mut index = read_snapshot_index(dir)?;
+    if index.pending_restore.take().is_some() {
+        write_snapshot_index(dir, &index)?;
+    }
     Ok(())
 }
 
-fn run_powershell(command: &str) -> Result<(), String> {
-    let output = Command::new("powershell")
-        .args(&["-NoProfile", "-Command", command])
-        .creation_flags(CREATE_NO_WINDOW)
-        .output()
-        .map_err(|e| e.to_string())?;
-    if output.status.success() {
-        return Ok(());
+pub fn list_delta_snapshots(game_id: &str) -> Result<DeltaSnapshots, String> {
+    read_snapshot_index(&delta_snapshot_dir_for_game_id(game_id))
+}
+
+/// Picks the snapshot the next launch starts from; `None` goes back to a fresh delta.
+pub fn restore_delta_snapshot(game_id: &str, name: Option<&str>) -> Result<DeltaSnapshots, String> {
+    let dir = delta_snapshot_dir_for_game_id(game_id);
+    let mut index = read_snapshot_index(&dir)?;
+    if let Some(name) = name {
+        if !index.snapshots.iter().any(|s| s.name == name) {
+            return Err(format!("Delta snapshot not found: {}", name));
+        }
     }
-    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
-    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
-    let msg = if !stderr.is_empty() { stderr } else { stdout };
-    Err(if msg.is_empty() {
-        "PowerShell command failed".to_string()
-    } else {
-        msg
+    index.pending_restore = name.map(str::to_string);
+    write_snapshot_index(&dir, &index)?;
+    Ok(index)
+}
+
+pub fn delete_delta_snapshot(game_id: &str, name: &str) -> Result<DeltaSnapshots, String> {
+    let dir = delta_snapshot_dir_for_game_id(game_id);
+    let mut index = read_snapshot_index(&dir)?;
+    let position = index
+        .snapshots
+        .iter()
+        .position(|s| s.name == name)
+        .ok_or_else(|| format!("Delta snapshot not found: {}", name))?;
+    let removed = index.snapshots.remove(position);
+    if removed.file.exists() {
+        fs::remove_file(&removed.file).map_err(|e| format!("Failed to delete {}: {}", removed.file.display(), e))?;
+    }
+    if index.pending_restore.as_deref() == Some(name) {
+        index.pending_restore = None;
+    }
+    write_snapshot_index(&dir, &index)?;
+    Ok(index)
+}
+
+/// Gives a snapshot a name of its own, which also pins it outside the rotation.
+pub fn rename_delta_snapshot(game_id: &str, name: &str, new_name: &str) -> Result<DeltaSnapshots, String> {
+    let new_name = validate_snapshot_name(new_name)?;
+    let dir = delta_snapshot_dir_for_game_id(game_id);
+    let mut index = read_snapshot_index(&dir)?;
+    if name != new_name && index.snapshots.iter().any(|s| s.name == new_name) {
+        return Err(format!("A delta snapshot named {} already exists", new_name));
+    }
+    let snapshot = index
+        .snapshots
+        .iter_mut()
+        .find(|s| s.name == name)
+        .ok_or_else(|| format!("Delta snapshot not found: {}", name))?;
+    let file = dir.join(snapshot_file_name(new_name, &snapshot.file));
+    if file != snapshot.file {
+        move_file(&snapshot.file, &file)?;
+    }
+    snapshot.name = new_name.to_string();
+    snapshot.file = file;
+    snapshot.pinned = true;
+    if index.pending_restore.as_deref() == Some(name) {
+        index.pending_restore = Some(new_name.to_string());
+    }
+    write_snapshot_index(&dir, &index)?;
+    Ok(index)
+}
+
+#[cfg(target_os = "windows")]
+fn logical_drive_mask() -> u32 {
+    unsafe { GetLogicalDrives() }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn logical_drive_mask() -> u32 {
+    0
+}
+
+/// Letters already assigned to a volume, including mapped network drives that are
+/// currently disconnected.
+pub fn used_drive_letters() -> Vec<char> {
+    let mask = logical_drive_mask();
+    ('A'..='Z')
+        .enumerate()
+        .filter(|(index, letter)| mask & (1 << index) != 0 || Path::new(&format!("{}:\\", letter)).exists())
+        .map(|(_, letter)| letter)
+        .collect()
+}
+
+/// Letters game volumes may be mounted on, lowest first.
+fn mount_drive_letters() -> impl DoubleEndedIterator<Item = char> {
+    ('A'..='Z').filter(|l| !RESERVED_DRIVE_LETTERS.contains(l))
+}
+
+/// Every mount point a game volume may end up on: the drive letters the launcher picks from,
+/// and the role folders below each of `mount_roots`. Policies allow-list these.
+pub fn mount_point_candidates(mount_roots: &[PathBuf]) -> Vec<MountPoint> {
+    let folders = mount_roots
+        .iter()
+        .flat_map(|root| MountRole::ALL.map(|role| MountPoint::Folder(root.join(role.folder_name()))));
+    mount_drive_letters().map(MountPoint::Drive).chain(folders).collect()
+}
+
+/// Keeps X:/Y:/Z: for their usual role when free; a taken one is replaced by the highest
+/// free letter that no other role prefers.
+fn pick_drive_letters(used: &[char]) -> Result<MountPoints, String> {
+    let mut taken: Vec<char> = used.iter().map(|l| l.to_ascii_uppercase()).collect();
+    let preferred: Vec<char> = MountRole::ALL.iter().map(|r| r.preferred_letter()).collect();
+    let mut pick = |role: MountRole| -> Result<MountPoint, String> {
+        let wanted = role.preferred_letter();
+        let letter = if !taken.contains(&wanted) {
+            wanted
+        } else {
+            mount_drive_letters()
+                .rev()
+                .find(|l| !taken.contains(l) && !preferred.contains(l))
+                .ok_or_else(|| "No free drive letter left to mount the game VHDs".to_string())?
+        };
+        taken.push(letter);
+        Ok(MountPoint::Drive(letter))
+    };
+    Ok(MountPoints {
+        app: pick(MountRole::App)?,
+        appdata: pick(MountRole::AppData)?,
+        option: pick(MountRole::Option)?,
     })
 }
 
-fn run_diskpart(script: &str) -> Result<(), String> {
-    let script_path = std::env::temp_dir().join("configarc_vhd_diskpart.txt");
-    fs::write(&script_path, script.as_bytes()).map_err(|e| e.to_string())?;
-    let output = Command::new("diskpart.exe")
-        .args(&["/s", script_path.to_string_lossy().as_ref()])
-        .creation_flags(CREATE_NO_WINDOW)
+fn folder_mount_point(root: &Path, role: MountRole, create: bool) -> Result<MountPoint, String> {
+    let dir = root.join(role.folder_name());
+    if create {
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create mount folder {}: {}", dir.display(), e))?;
+    }
+    let occupied = fs::read_dir(&dir).map(|mut entries| entries.next().is_some()).unwrap_or(false);
+    if occupied {
+        return Err(format!(
+            "Mount folder {} is not empty. It may still have a volume attached.",
+            dir.display()
+        ));
+    }
+    Ok(MountPoint::Folder(dir))
+}
+
+fn mount_points_for(mount_root: Option<&Path>, create: bool) -> Result<MountPoints, String> {
+    match mount_root {
+        Some(root) => Ok(MountPoints {
+            app: folder_mount_point(root, MountRole::App, create)?,
+            appdata: folder_mount_point(root, MountRole::AppData, create)?,
+            option: folder_mount_point(root, MountRole::Option, create)?,
+        }),
+        None => {
+            let used = used_drive_letters();
+            pick_drive_letters(&used).map_err(|err| {
+                let holders = identify_letter_holders(&busy_preferred_letters(&used));
+                if holders.is_empty() {
+                    err
+                } else {
+                    let held = holders.iter().map(LetterHolder::describe).collect::<Vec<_>>();
+                    format!("{}. {}", err, held.join("; "))
+                }
+            })
+        }
+    }
+}
+
+/// The process keeping a drive letter's volume attached.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct HolderProcess {
+    pub pid: u32,
+    pub name: String,
+}
+
+/// What currently occupies one of the preferred drive letters.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LetterHolder {
+    pub letter: char,
+    /// Virtual disk attached at the letter.
+    #[serde(default)]
+    pub image: Option<String>,
+    /// Share a mapped network drive points to.
+    #[serde(default)]
+    pub network: Option<String>,
+    #[serde(default)]
+    pub process: Option<HolderProcess>,
+}
+
+impl LetterHolder {
+    /// Virtual disks and helpers go away once their detach finishes; physical and network
+    /// drives do not, so they are never waited for.
+    pub fn releases_soon(&self) -> bool {
+        self.image.is_some() || self.process.is_some()
+    }
+
+    /// One-line summary for logs and errors.
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(image) = &self.image {
+            parts.push(format!("image {}", image));
+        }
+        if let Some(network) = &self.network {
+            parts.push(format!("network share {}", network));
+        }
+        if let Some(process) = &self.process {
+            parts.push(format!("{} (pid {})", process.name, process.pid));
+        }
+        if parts.is_empty() {
+            format!("{}: is in use", self.letter)
+        } else {
+            format!("{}: is held by {}", self.letter, parts.join(", "))
+        }
+    }
+}
+
+/// Reported while a launch waits for its preferred letters.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LetterWait {
+    pub waited_ms: u64,
+    pub timeout_ms: u64,
+    pub holders: Vec<LetterHolder>,
+}
+
+/// Preferred letters of the mount roles that `used` already contains.
+fn busy_preferred_letters(used: &[char]) -> Vec<char> {
+    MountRole::ALL
+        .iter()
+        .map(|role| role.preferred_letter())
+        .filter(|letter| used.iter().any(|l| l.eq_ignore_ascii_case(letter)))
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+fn query_letter_holders(letters: &[char]) -> Vec<LetterHolder> {
+    let list = letters
+        .iter()
+        .map(|l| ps_quote(&l.to_string()))
+        .collect::<Vec<_>>()
+        .join(",");
+    let script = format!(
+        "$helper = Get-CimInstance Win32_Process -ErrorAction SilentlyContinue | Where-Object {{ $_.CommandLine -like '*configarc_vhd_helper*' }} | Select-Object -First 1;\
+        $out = foreach ($l in @({})) {{\
+          $image = $null; $proc = $null;\
+          $p = Get-Partition -DriveLetter $l -ErrorAction SilentlyContinue | Select-Object -First 1;\
+          if ($p) {{ $img = Get-DiskImage -DevicePath ('\\\\.\\PHYSICALDRIVE' + $p.DiskNumber) -ErrorAction SilentlyContinue; if ($img) {{ $image = $img.ImagePath }} }};\
+          if ($image -and $helper) {{ $proc = @{{ pid = [int]$helper.ProcessId; name = $helper.Name }} }};\
+          $ld = Get-CimInstance Win32_LogicalDisk -Filter (\"DeviceID='\" + $l + \":'\") -ErrorAction SilentlyContinue;\
+          @{{ letter = $l; image = $image; network = $ld.ProviderName; process = $proc }}\
+        }};\
+        ConvertTo-Json -InputObject @($out) -Compress -Depth 3",
+        list
+    );
+    powershell_output(&script)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_else(|| letters.iter().map(|&letter| unknown_holder(letter)).collect())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn query_letter_holders(letters: &[char]) -> Vec<LetterHolder> {
+    letters.iter().map(|&letter| unknown_holder(letter)).collect()
+}
+
+fn unknown_holder(letter: char) -> LetterHolder {
+    LetterHolder {
+        letter,
+        image: None,
+        network: None,
+        process: None,
+    }
+}
+
+/// Identifies what holds each of `letters`. A letter in the recorded mount of another,
+/// still running launcher is attributed to that launcher.
+pub fn identify_letter_holders(letters: &[char]) -> Vec<LetterHolder> {
+    if letters.is_empty() {
+        return Vec::new();
+    }
+    let mut holders = query_letter_holders(letters);
+    let owner = read_mount_state(&mount_state_path())
+        .filter(|state| state.pid != std::process::id() && process_alive(state.pid));
+    if let Some(state) = owner {
+        for holder in holders.iter_mut().filter(|h| h.process.is_none()) {
+            let point = MountPoint::Drive(holder.letter);
+            if state.handle.mount_points().iter().any(|(_, p)| *p == point) {
+                holder.process = Some(HolderProcess {
+                    pid: state.pid,
+                    name: "ConfigArc launcher".to_string(),
+                });
+            }
+        }
+    }
+    holders
+}
+
+/// Waits up to the game's letter grace period for X:/Y:/Z: to come free when a virtual disk
+/// or mount helper still holds them, as happens right after the previous session unmounted.
+/// Letters held by anything else are not waited for. Returns the holders of preferred
+/// letters that are still taken; their roles fall back to other letters.
+pub fn wait_for_mount_letters(cfg: &ResolvedVhdConfig, mut on_wait: impl FnMut(&LetterWait)) -> Vec<LetterHolder> {
+    if cfg.mount_root.is_some() {
+        return Vec::new();
+    }
+    let started = Instant::now();
+    let mut busy = busy_preferred_letters(&used_drive_letters());
+    let mut holders = identify_letter_holders(&busy);
+    loop {
+        if busy.is_empty() || started.elapsed() >= cfg.letter_wait || !holders.iter().any(LetterHolder::releases_soon)
+        {
+            return holders;
+        }
+        on_wait(&LetterWait {
+            waited_ms: started.elapsed().as_millis() as u64,
+            timeout_ms: cfg.letter_wait.as_millis() as u64,
+            holders: holders.clone(),
+        });
+        sleep(LETTER_POLL_INTERVAL);
+        let now_busy = busy_preferred_letters(&used_drive_letters());
+        if now_busy != busy {
+            // Only freed letters drop out; holders are not re-queried for every poll.
+            holders.retain(|holder| now_busy.contains(&holder.letter));
+            let added: Vec<char> = now_busy.iter().copied().filter(|l| !busy.contains(l)).collect();
+            holders.extend(identify_letter_holders(&added));
+            busy = now_busy;
+        }
+    }
+}
+
+/// Mount points a launch would use right now, without creating anything.
+pub fn plan_mount_points(cfg: &ResolvedVhdConfig) -> Result<MountPoints, String> {
+    mount_points_for(cfg.mount_root.as_deref(), false)
+}
+
+fn allocate_mount_points(cfg: &ResolvedVhdConfig) -> Result<MountPoints, String> {
+    mount_points_for(cfg.mount_root.as_deref(), true)
+}
+
+/// Mount points of the VHDs this process has attached, if any.
+pub fn active_mount_points() -> Option<MountPoints> {
+    ACTIVE_MOUNT_POINTS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
+
+pub(crate) fn set_active_mount_points(points: Option<MountPoints>) {
+    *ACTIVE_MOUNT_POINTS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = points;
+}
+
+pub(crate) fn run_powershell(command: &str) -> Result<(), String> {
+    powershell_output(command).map(|_| ())
+}
+
+/// Runs `command` and returns its trimmed stdout.
+fn powershell_output(command: &str) -> Result<String, String> {
+    let mut cmd = Command::new("powershell");
+    cmd.args(["-NoProfile", "-Command", command]);
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(CREATE_NO_WINDOW);
+    let output = cmd
         .output()
         .map_err(|e| e.to_string())?;
-    let _ = fs::remove_file(&script_path);
     if output.status.success() {
-        return Ok(());
+        return Ok(String::from_utf8_lossy(&output.stdout).trim().to_string());
     }
     let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
     let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
     let msg = if !stderr.is_empty() { stderr } else { stdout };
     Err(if msg.is_empty() {
-        "DiskPart command failed".to_string()
+        "PowerShell command failed".to_string()
     } else {
         msg
     })
 }
 
-fn close_explorer_for_x_drive() {
-    let cmd = "Start-Sleep -Milliseconds 300; $shell = New-Object -ComObject Shell.Application; $shell.Windows() | Where-Object { $_.LocationURL -like 'file:///X:*' -or $_.LocationURL -like 'file:///X:/*' -or $_.LocationURL -like 'file:///Y:*' -or $_.LocationURL -like 'file:///Y:/*' -or $_.LocationURL -like 'file:///Z:*' -or $_.LocationURL -like 'file:///Z:/*' } | ForEach-Object { $_.Quit() }";
-    let _ = run_powershell(cmd);
+fn close_explorer_for_mounts(points: &MountPoints) {
+    // Shell.Application is a COM object, which Constrained Language Mode refuses to create.
+    if powershell_support() != PowerShellSupport::Full {
+        return;
+    }
+    let filter = points
+        .iter()
+        .map(|(_, point)| {
+            let url = point.access_path().trim_end_matches('\\').replace('\\', "/");
+            format!("$_.LocationURL -like {}", ps_quote(&format!("file:///{}*", url)))
+        })
+        .collect::<Vec<_>>()
+        .join(" -or ");
+    let cmd = format!(
+        "Start-Sleep -Milliseconds 300; $shell = New-Object -ComObject Shell.Application; $shell.Windows() | Where-Object {{ {} }} | ForEach-Object {{ $_.Quit() }}",
+        filter
+    );
+    let _ = run_powershell(&cmd);
 }
 
 #[cfg(target_os = "windows")]
@@ -387,7 +1563,8 @@ extern "system" {
     fn IsUserAnAdmin() -> i32;
 }
 
-fn is_running_as_admin() -> bool {
+/// Whether this process holds an elevated administrator token.
+pub fn is_running_as_admin() -> bool {
     #[cfg(target_os = "windows")]
     unsafe {
         return IsUserAnAdmin() != 0;
@@ -433,32 +1610,27 @@ fn to_wide_path(path: &Path) -> Vec<u16> {
 
 #[cfg(target_os = "windows")]
 fn device_id_for_path(path: &Path) -> Result<u32, String> {
-    match path
-        .extension()
-        .and_then(OsStr::to_str)
-        .map(|ext| ext.to_ascii_lowercase())
-        .as_deref()
-    {
-        Some("vhd") => Ok(VIRTUAL_STORAGE_TYPE_DEVICE_VHD),
-        Some("vhdx") => Ok(VIRTUAL_STORAGE_TYPE_DEVICE_VHDX),
-        _ => Err(format!(
-            "Unsupported virtual disk type for auto-repair: {}",
+    match VirtualDiskFormat::from_path(path) {
+        Some(VirtualDiskFormat::Vhd) => Ok(VIRTUAL_STORAGE_TYPE_DEVICE_VHD),
+        Some(VirtualDiskFormat::Vhdx) => Ok(VIRTUAL_STORAGE_TYPE_DEVICE_VHDX),
+        None => Err(format!(
+            "Unsupported virtual disk type: {}",
             path.to_string_lossy()
         )),
     }
 }
 
+#[cfg(target_os = "windows")]
+fn virtual_storage_type(path: &Path) -> Result<VirtualStorageType, String> {
+    Ok(VirtualStorageType {
+        device_id: device_id_for_path(path)?,
+        vendor_id: VIRTUAL_STORAGE_TYPE_VENDOR_MICROSOFT,
+    })
+}
+
 #[cfg(target_os = "windows")]
 fn set_vhd_parent_path(child_path: &Path, parent_path: &Path) -> Result<(), String> {
-    let storage_type = VirtualStorageType {
-        device_id: device_id_for_path(child_path)?,
-        vendor_id: Guid {
-            data1: 0xec98_4aec,
-            data2: 0xa0f9,
-            data3: 0x47e9,
-            data4: [0x90, 0x1f, 0x71, 0x41, 0x5a, 0x66, 0x34, 0x5b],
-        },
-    };
+    let storage_type = virtual_storage_type(child_path)?;
     let child_wide = to_wide_path(child_path);
     let parent_wide = to_wide_path(parent_path);
     let open_params = OpenVirtualDiskParameters {
@@ -545,77 +1717,332 @@ fn prepare_repaired_patch_chain(cfg: &ResolvedVhdConfig) -> Result<PreparedPatch
     })
 }
 
-fn mount_image_to_drive(image_path: &Path, drive_letter: char) -> Result<(), String> {
-    let drive = drive_letter.to_ascii_uppercase();
-    let access_path = format!("{}:\\", drive);
-    let mount_cmd = format!(
-        "Mount-DiskImage -ImagePath \"{}\" -StorageType VHD -NoDriveLetter -Passthru -Access ReadWrite -Confirm:$false -ErrorAction Stop | Get-Disk | Get-Partition | Where-Object {{ ($_ | Get-Volume) -ne $Null }} | Add-PartitionAccessPath -AccessPath \"{}\" -ErrorAction Stop | Out-Null",
-        image_path.to_string_lossy(),
-        access_path
-    );
-    run_powershell(&mount_cmd)
+/// Opens an image for attaching or detaching. Version 2 with no access mask is what VHDX
+/// requires, and VHD accepts it too.
+#[cfg(target_os = "windows")]
+fn open_virtual_disk(path: &Path) -> Result<VirtualDiskHandle, String> {
+    let storage_type = virtual_storage_type(path)?;
+    let path_wide = to_wide_path(path);
+    let params = OpenVirtualDiskParameters {
+        version: OPEN_VIRTUAL_DISK_VERSION_2,
+        union_data: OpenVirtualDiskParametersUnion {
+            version2: OpenVirtualDiskParametersVersion2 {
+                get_info_only: 0,
+                read_only: 0,
+                resiliency_guid: NULL_GUID,
+            },
+        },
+    };
+    let mut handle: *mut c_void = std::ptr::null_mut();
+    let status = unsafe {
+        OpenVirtualDisk(
+            &storage_type,
+            path_wide.as_ptr(),
+            VIRTUAL_DISK_ACCESS_NONE,
+            OPEN_VIRTUAL_DISK_FLAG_NONE,
+            &params,
+            &mut handle,
+        )
+    };
+    if status != 0 {
+        return Err(format!(
+            "Failed to open disk image ({}): {}",
+            path.to_string_lossy(),
+            win32_error(status)
+        ));
+    }
+    Ok(VirtualDiskHandle(handle))
 }
 
-fn dismount_image(image_path: &Path) {
-    let dismount = format!(
-        "Dismount-DiskImage -ImagePath \"{}\" -Confirm:$false -ErrorAction SilentlyContinue",
-        image_path.to_string_lossy()
-    );
-    let _ = run_powershell(&dismount);
+/// Creates `child_path` as a differencing disk on top of `parent_path`, in the child's format.
+#[cfg(target_os = "windows")]
+fn create_differencing_disk(child_path: &Path, parent_path: &Path) -> Result<(), String> {
+    let storage_type = virtual_storage_type(child_path)?;
+    let unknown_type = VirtualStorageType {
+        device_id: 0,
+        vendor_id: NULL_GUID,
+    };
+    let child_wide = to_wide_path(child_path);
+    let parent_wide = to_wide_path(parent_path);
+    // Size, block and sector sizes of zero are inherited from the parent.
+    let params = CreateVirtualDiskParameters {
+        version: CREATE_VIRTUAL_DISK_VERSION_2,
+        union_data: CreateVirtualDiskParametersUnion {
+            version2: CreateVirtualDiskParametersVersion2 {
+                unique_id: NULL_GUID,
+                maximum_size: 0,
+                block_size_in_bytes: 0,
+                sector_size_in_bytes: 0,
+                physical_sector_size_in_bytes: 0,
+                parent_path: parent_wide.as_ptr(),
+                source_path: std::ptr::null(),
+                open_flags: OPEN_VIRTUAL_DISK_FLAG_NONE,
+                parent_virtual_storage_type: unknown_type,
+                source_virtual_storage_type: unknown_type,
+                resiliency_guid: NULL_GUID,
+            },
+        },
+    };
+    let mut handle: *mut c_void = std::ptr::null_mut();
+    let status = unsafe {
+        CreateVirtualDisk(
+            &storage_type,
+            child_wide.as_ptr(),
+            VIRTUAL_DISK_ACCESS_NONE,
+            std::ptr::null(),
+            CREATE_VIRTUAL_DISK_FLAG_NONE,
+            0,
+            &params,
+            std::ptr::null_mut(),
+            &mut handle,
+        )
+    };
+    if status != 0 {
+        return Err(format!(
+            "Failed to create runtime VHD (child: {}, parent: {}): {}",
+            child_path.to_string_lossy(),
+            parent_path.to_string_lossy(),
+            win32_error(status)
+        ));
+    }
+    drop(VirtualDiskHandle(handle));
+    Ok(())
 }
 
-fn cleanup_runtime(runtime_path: &Option<PathBuf>) {
-    if let Some(path) = runtime_path {
-        dismount_image(path);
-        if path.exists() {
-            let _ = fs::remove_file(path);
+#[cfg(not(target_os = "windows"))]
+fn create_differencing_disk(_child_path: &Path, _parent_path: &Path) -> Result<(), String> {
+    Err("Runtime VHDs are only supported on Windows".to_string())
+}
+
+/// Number of the `\\.\PhysicalDriveN` an attached image shows up as.
+#[cfg(target_os = "windows")]
+fn attached_disk_number(handle: &VirtualDiskHandle, image_path: &Path) -> Result<u32, String> {
+    let mut buf = [0u16; 260];
+    let mut size = (buf.len() * 2) as u32;
+    let status = unsafe { GetVirtualDiskPhysicalPath(handle.0, &mut size, buf.as_mut_ptr()) };
+    if status != 0 {
+        return Err(format!(
+            "Failed to resolve attached disk for {}: {}",
+            image_path.to_string_lossy(),
+            win32_error(status)
+        ));
+    }
+    let len = buf.iter().position(|c| *c == 0).unwrap_or(buf.len());
+    let physical = String::from_utf16_lossy(&buf[..len]);
+    physical
+        .trim_start_matches(|c: char| !c.is_ascii_digit())
+        .parse::<u32>()
+        .map_err(|_| format!("Unexpected physical path for {}: {}", image_path.to_string_lossy(), physical))
+}
+
+#[cfg(target_os = "windows")]
+fn volume_disk_number(volume: &[u16]) -> Option<u32> {
+    // The volume list ends names with a backslash, which `CreateFileW` would read as the root folder.
+    let device: Vec<u16> = volume
+        .strip_suffix(&[b'\\' as u16])
+        .unwrap_or(volume)
+        .iter()
+        .copied()
+        .chain(std::iter::once(0))
+        .collect();
+    let handle = unsafe {
+        CreateFileW(
+            device.as_ptr(),
+            0,
+            FILE_SHARE_READ | FILE_SHARE_WRITE,
+            std::ptr::null_mut(),
+            OPEN_EXISTING,
+            0,
+            std::ptr::null_mut(),
+        )
+    };
+    if handle as isize == INVALID_HANDLE_VALUE {
+        return None;
+    }
+    let mut extents = VolumeDiskExtents {
+        number_of_disk_extents: 0,
+        extents: [DiskExtent {
+            disk_number: 0,
+            starting_offset: 0,
+            extent_length: 0,
+        }],
+    };
+    let mut returned = 0u32;
+    let ok = unsafe {
+        DeviceIoControl(
+            handle,
+            IOCTL_VOLUME_GET_VOLUME_DISK_EXTENTS,
+            std::ptr::null_mut(),
+            0,
+            &mut extents as *mut VolumeDiskExtents as *mut c_void,
+            std::mem::size_of::<VolumeDiskExtents>() as u32,
+            &mut returned,
+            std::ptr::null_mut(),
+        )
+    };
+    unsafe {
+        let _ = CloseHandle(handle);
+    }
+    (ok != 0 && extents.number_of_disk_extents > 0).then(|| extents.extents[0].disk_number)
+}
+
+/// Volume GUID path (`\\?\Volume{...}\`, NUL-terminated) of the first volume on `disk_number`.
+#[cfg(target_os = "windows")]
+fn volume_on_disk(disk_number: u32) -> Option<Vec<u16>> {
+    let mut name = [0u16; 64];
+    let find = unsafe { FindFirstVolumeW(name.as_mut_ptr(), name.len() as u32) };
+    if find as isize == INVALID_HANDLE_VALUE {
+        return None;
+    }
+    let mut found = None;
+    loop {
+        let len = name.iter().position(|c| *c == 0).unwrap_or(name.len());
+        if volume_disk_number(&name[..len]) == Some(disk_number) {
+            found = Some(name[..len].iter().copied().chain(std::iter::once(0)).collect());
+            break;
+        }
+        if unsafe { FindNextVolumeW(find, name.as_mut_ptr(), name.len() as u32) } == 0 {
+            break;
         }
     }
+    unsafe {
+        let _ = FindVolumeClose(find);
+    }
+    found
 }
 
-fn wait_for_helper_result(path: &Path, timeout: Duration) -> Result<HelperResult, String> {
-    let start = Instant::now();
-    let mut last_err: Option<String> = None;
-    while start.elapsed() < timeout {
-        if path.exists() {
-            let data = fs::read_to_string(path).map_err(|e| e.to_string())?;
-            let trimmed = data.trim();
-            if trimmed.is_empty() {
-                sleep(Duration::from_millis(200));
-                continue;
+#[cfg(target_os = "windows")]
+fn mount_point_wide(mount_point: &MountPoint) -> Vec<u16> {
+    OsStr::new(&mount_point.access_path())
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect()
+}
+
+/// Attaches the image without a drive letter and binds its volume to `mount_point`.
+#[cfg(target_os = "windows")]
+fn mount_image_to(image_path: &Path, mount_point: &MountPoint) -> Result<(), String> {
+    let handle = open_virtual_disk(image_path)?;
+    let params = AttachVirtualDiskParameters {
+        version: ATTACH_VIRTUAL_DISK_VERSION_1,
+        reserved: 0,
+    };
+    let status = unsafe {
+        AttachVirtualDisk(
+            handle.0,
+            std::ptr::null(),
+            ATTACH_VIRTUAL_DISK_FLAG_NO_DRIVE_LETTER | ATTACH_VIRTUAL_DISK_FLAG_PERMANENT_LIFETIME,
+            0,
+            &params,
+            std::ptr::null_mut(),
+        )
+    };
+    if status != 0 {
+        return Err(format!(
+            "Failed to attach disk image ({}): {}",
+            image_path.to_string_lossy(),
+            win32_error(status)
+        ));
+    }
+
+    let bound = attached_disk_number(&handle, image_path).and_then(|disk_number| {
+        let start = Instant::now();
+        let volume = loop {
+            if let Some(volume) = volume_on_disk(disk_number) {
+                break volume;
             }
-            let trimmed = trimmed.strip_prefix('\u{feff}').unwrap_or(trimmed);
-            match serde_json::from_str::<HelperResult>(trimmed) {
-                Ok(result) => return Ok(result),
-                Err(err) => {
-                    last_err = Some(err.to_string());
-                }
+            if start.elapsed() >= VOLUME_ARRIVAL_TIMEOUT {
+                return Err(format!(
+                    "No volume appeared for {} on disk {}",
+                    image_path.to_string_lossy(),
+                    disk_number
+                ));
             }
+            sleep(Duration::from_millis(200));
+        };
+        let target = mount_point_wide(mount_point);
+        if unsafe { SetVolumeMountPointW(target.as_ptr(), volume.as_ptr()) } == 0 {
+            return Err(format!(
+                "Failed to mount {} at {}: {}",
+                image_path.to_string_lossy(),
+                mount_point.access_path(),
+                std::io::Error::last_os_error()
+            ));
+        }
+        Ok(())
+    });
+    if bound.is_err() {
+        unsafe {
+            let _ = DetachVirtualDisk(handle.0, DETACH_VIRTUAL_DISK_FLAG_NONE, 0);
         }
-        sleep(Duration::from_millis(200));
     }
-    if let Some(err) = last_err {
-        Err(format!("Failed to parse elevated helper result: {err}"))
-    } else {
-        Err("Timed out waiting for elevated mount helper".to_string())
+    bound
+}
+
+#[cfg(not(target_os = "windows"))]
+fn mount_image_to(_image_path: &Path, _mount_point: &MountPoint) -> Result<(), String> {
+    Err("Mounting disk images is only supported on Windows".to_string())
+}
+
+/// Detaching frees a drive letter by itself, but a folder mount point stays behind as a
+/// dangling reparse point unless it is removed first.
+#[cfg(target_os = "windows")]
+fn remove_mount_point(mount_point: &MountPoint) {
+    let target = mount_point_wide(mount_point);
+    unsafe {
+        let _ = DeleteVolumeMountPointW(target.as_ptr());
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn remove_mount_point(_mount_point: &MountPoint) {}
+
+/// Best effort, like the cleanup paths that call it: an image that is not attached is fine.
+#[cfg(target_os = "windows")]
+fn dismount_image(image_path: &Path) {
+    if let Ok(handle) = open_virtual_disk(image_path) {
+        unsafe {
+            let _ = DetachVirtualDisk(handle.0, DETACH_VIRTUAL_DISK_FLAG_NONE, 0);
+        }
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn dismount_image(_image_path: &Path) {}
+
+fn unmount_image(image_path: &Path, mount_point: &MountPoint) {
+    remove_mount_point(mount_point);
+    dismount_image(image_path);
+}
+
+fn cleanup_runtime(runtime_path: &Option<PathBuf>) {
+    if let Some(path) = runtime_path {
+        dismount_image(path);
+        if path.exists() {
+            let _ = fs::remove_file(path);
+        }
     }
 }
 
 fn mount_vhd_via_helper(cfg: &ResolvedVhdConfig, repair_root: Option<PathBuf>) -> Result<ElevatedVhdMount, String> {
+    let mount_points = allocate_mount_points(cfg)?;
     let tag = temp_tag();
     let temp = std::env::temp_dir();
     let script_path = temp.join(format!("configarc_vhd_helper_{tag}.ps1"));
-    let params_path = temp.join(format!("configarc_vhd_params_{tag}.json"));
-    let result_path = temp.join(format!("configarc_vhd_result_{tag}.json"));
-    let signal_path = temp.join(format!("configarc_vhd_signal_{tag}.flag"));
-    let done_path = temp.join(format!("configarc_vhd_done_{tag}.flag"));
+    let pipe_name = helper_pipe_name()?;
 
     fs::write(&script_path, VHD_HELPER_SCRIPT.as_bytes()).map_err(|e| e.to_string())?;
-    // Cleanup old files
-    let _ = fs::remove_file(&result_path);
-    let _ = fs::remove_file(&signal_path);
-    let _ = fs::remove_file(&done_path);
-    let _ = fs::remove_file(&params_path);
+
+    let delta_path = cfg.delta_enabled.then(|| cfg.runtime_delta_placement().0);
+    let reuse_runtime = match (&delta_path, &cfg.delta_restore) {
+        (Some(delta_path), Some(snapshot)) => {
+            dismount_image(delta_path);
+            start_delta_from_snapshot(snapshot, delta_path, cfg.app_parent_path())?;
+            true
+        }
+        _ => false,
+    };
+    let delta_snapshots = cfg.kept_delta_snapshots();
 
     let params = VhdHelperParams {
         app_base: cfg.app_base_path.clone(),
@@ -623,23 +2050,23 @@ fn mount_vhd_via_helper(cfg: &ResolvedVhdConfig, repair_root: Option<PathBuf>) -
         app_data: cfg.appdata_path.clone(),
         option: cfg.option_path.clone(),
         delta: cfg.delta_enabled,
+        delta_path,
         repair_root,
-        result_path: result_path.clone(),
-        signal_path: signal_path.clone(),
-        done_path: done_path.clone(),
+        reuse_runtime,
+        keep_runtime: delta_snapshots.is_some(),
+        app_mount_point: mount_points.app.access_path(),
+        appdata_mount_point: mount_points.appdata.access_path(),
+        option_mount_point: mount_points.option.access_path(),
     };
 
-    let params_json = serde_json::to_string_pretty(&params).map_err(|e| e.to_string())?;
-    fs::write(&params_path, params_json).map_err(|e| e.to_string())?;
-
-    let args = vec![
+    let args = [
         "-NoProfile".to_string(),
         "-ExecutionPolicy".to_string(),
         "Bypass".to_string(),
         "-File".to_string(),
         script_path.to_string_lossy().to_string(),
-        "-ConfigPath".to_string(),
-        params_path.to_string_lossy().to_string(),
+        "-PipeName".to_string(),
+        pipe_name.clone(),
     ];
 
     let arg_list = args
@@ -654,44 +2081,54 @@ fn mount_vhd_via_helper(cfg: &ResolvedVhdConfig, repair_root: Option<PathBuf>) -
     );
     run_powershell(&cmd)?;
 
-    let result = wait_for_helper_result(&result_path, Duration::from_secs(30))?;
+    let result: HelperResult = helper_ipc::exchange(
+        &pipe_name,
+        &HelperRequest::Mount(Box::new(params)),
+        "mount the images",
+        HELPER_CONNECT_TIMEOUT,
+        HELPER_MOUNT_TIMEOUT,
+    )
+    .map_err(|e| e.to_string())?;
     if !result.ok {
         let message = result.error.unwrap_or_else(|| "Elevated mount helper failed".to_string());
         return Err(message);
     }
 
-    let _ = result.app_mount_path;
-    let _ = result.app_runtime_path;
+    let app_runtime_path = result.app_runtime_path.map(PathBuf::from);
+    let app_mount_path = result
+        .app_mount_path
+        .map(PathBuf::from)
+        .or_else(|| app_runtime_path.clone())
+        .unwrap_or_else(|| cfg.app_parent_path().to_path_buf());
 
     Ok(ElevatedVhdMount {
         script_path,
-        result_path,
-        signal_path,
-        done_path,
+        pipe_name,
+        mount_points,
+        app_mount_path,
+        appdata_mount_path: cfg.appdata_path.clone(),
+        option_mount_path: cfg.option_path.clone(),
+        app_runtime_path,
+        delta_snapshots,
     })
 }
 
 fn mount_vhd_once(cfg: &ResolvedVhdConfig, repair_root: Option<PathBuf>) -> Result<MountedVhd, String> {
-    ensure_mount_points_free()?;
+    let mount_points = allocate_mount_points(cfg)?;
 
     let app_parent_path = cfg.app_parent_path();
     let mut app_mount_path = app_parent_path.to_path_buf();
     let mut app_runtime_path = None;
     if cfg.delta_enabled {
-        let delta_path = runtime_path_for_parent(app_parent_path);
-        let dismount = format!(
-            "Dismount-DiskImage -ImagePath \"{}\" -Confirm:$false -ErrorAction SilentlyContinue",
-            delta_path.to_string_lossy()
-        );
-        let _ = run_powershell(&dismount);
+        let (delta_path, _) = cfg.runtime_delta_placement();
+        dismount_image(&delta_path);
         if delta_path.exists() {
             let _ = fs::remove_file(&delta_path);
         }
-        let script = format!("create vdisk file=\"{}\" parent=\"{}\"\n",
-            delta_path.to_string_lossy(),
-            app_parent_path.to_string_lossy()
-        );
-        run_diskpart(&script)?;
+        match &cfg.delta_restore {
+            Some(snapshot) => start_delta_from_snapshot(snapshot, &delta_path, app_parent_path)?,
+            None => create_differencing_disk(&delta_path, app_parent_path)?,
+        }
         if !delta_path.exists() {
             return Err("Failed to create runtime VHD".to_string());
         }
@@ -699,26 +2136,26 @@ fn mount_vhd_once(cfg: &ResolvedVhdConfig, repair_root: Option<PathBuf>) -> Resu
         app_runtime_path = Some(delta_path);
     }
 
-    if let Err(err) = mount_image_to_drive(&app_mount_path, 'X') {
+    if let Err(err) = mount_image_to(&app_mount_path, &mount_points.app) {
         cleanup_runtime(&app_runtime_path);
         cleanup_repair_root(&repair_root);
         return Err(err);
     }
-    if let Err(err) = mount_image_to_drive(&cfg.appdata_path, 'Y') {
-        dismount_image(&app_mount_path);
+    if let Err(err) = mount_image_to(&cfg.appdata_path, &mount_points.appdata) {
+        unmount_image(&app_mount_path, &mount_points.app);
         cleanup_runtime(&app_runtime_path);
         cleanup_repair_root(&repair_root);
         return Err(err);
     }
-    if let Err(err) = mount_image_to_drive(&cfg.option_path, 'Z') {
-        dismount_image(&cfg.appdata_path);
-        dismount_image(&app_mount_path);
+    if let Err(err) = mount_image_to(&cfg.option_path, &mount_points.option) {
+        unmount_image(&cfg.appdata_path, &mount_points.appdata);
+        unmount_image(&app_mount_path, &mount_points.app);
         cleanup_runtime(&app_runtime_path);
         cleanup_repair_root(&repair_root);
         return Err(err);
     }
 
-    close_explorer_for_x_drive();
+    close_explorer_for_mounts(&mount_points);
 
     Ok(MountedVhd {
         app_mount_path,
@@ -726,6 +2163,8 @@ fn mount_vhd_once(cfg: &ResolvedVhdConfig, repair_root: Option<PathBuf>) -> Resu
         appdata_mount_path: cfg.appdata_path.clone(),
         option_mount_path: cfg.option_path.clone(),
         repair_root,
+        mount_points,
+        delta_snapshots: cfg.kept_delta_snapshots(),
     })
 }
 
@@ -734,15 +2173,44 @@ pub fn mount_vhd(cfg: &ResolvedVhdConfig) -> Result<MountedVhd, String> {
 }
 
 pub fn unmount_vhd(mounted: &MountedVhd) -> Result<(), String> {
-    dismount_image(&mounted.option_mount_path);
-    dismount_image(&mounted.appdata_mount_path);
-    dismount_image(&mounted.app_mount_path);
-    cleanup_runtime(&mounted.app_runtime_path);
+    unmount_image(&mounted.option_mount_path, &mounted.mount_points.option);
+    unmount_image(&mounted.appdata_mount_path, &mounted.mount_points.appdata);
+    unmount_image(&mounted.app_mount_path, &mounted.mount_points.app);
+    let kept = keep_runtime_delta(&mounted.app_runtime_path, &mounted.delta_snapshots);
     cleanup_repair_root(&mounted.repair_root);
-    Ok(())
+    kept
+}
+
+/// Stores the session's delta as a snapshot when the game keeps them, otherwise deletes it.
+fn keep_runtime_delta(runtime_path: &Option<PathBuf>, policy: &Option<DeltaSnapshotPolicy>) -> Result<(), String> {
+    match (runtime_path, policy) {
+        (Some(path), Some(policy)) if path.exists() => {
+            dismount_image(path);
+            store_delta_snapshot(policy, path).map(|_| ())
+        }
+        _ => {
+            cleanup_runtime(runtime_path);
+            Ok(())
+        }
+    }
 }
 
 pub fn mount_vhd_with_elevation(cfg: &ResolvedVhdConfig) -> Result<VhdMountHandle, String> {
+    if let Some(blocker) = vhd_mount_blocker() {
+        return Err(blocker);
+    }
+    let _guard = MOUNT_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let handle = mount_vhd_with_repair(cfg)?;
+    if cfg.delta_restore.is_some() {
+        // Restores are one-shot; the session after this one starts from its own snapshot.
+        let _ = clear_pending_restore(&cfg.delta_snapshots.dir);
+    }
+    set_active_mount_points(Some(handle.mount_points().clone()));
+    write_mount_state(&mount_state_path(), &handle);
+    Ok(handle)
+}
+
+fn mount_vhd_with_repair(cfg: &ResolvedVhdConfig) -> Result<VhdMountHandle, String> {
     let try_mount = |cfg: &ResolvedVhdConfig, repair_root: Option<PathBuf>| -> Result<VhdMountHandle, String> {
         if is_running_as_admin() {
             mount_vhd_once(cfg, repair_root).map(VhdMountHandle::Direct)
@@ -772,38 +2240,292 @@ pub fn mount_vhd_with_elevation(cfg: &ResolvedVhdConfig) -> Result<VhdMountHandl
 }
 
 pub fn unmount_vhd_handle(handle: &VhdMountHandle) -> Result<(), String> {
-    match handle {
+    set_active_mount_points(None);
+    let result = match handle {
         VhdMountHandle::Direct(mounted) => unmount_vhd(mounted),
         VhdMountHandle::Elevated(mounted) => {
-            fs::write(&mounted.signal_path, b"1").map_err(|e| e.to_string())?;
-            let start = Instant::now();
-            let timeout = Duration::from_secs(30);
-            let mut done = false;
-            while start.elapsed() < timeout {
-                if mounted.done_path.exists() {
-                    done = true;
-                    break;
+            signal_elevated_unmount(mounted, HELPER_CONNECT_TIMEOUT)?;
+            remove_helper_files(mounted);
+            if mounted.delta_snapshots.is_some() {
+                keep_runtime_delta(&mounted.app_runtime_path, &mounted.delta_snapshots)?;
+            }
+            Ok(())
+        }
+    };
+    if result.is_ok() {
+        let _ = fs::remove_file(mount_state_path());
+    }
+    result
+}
+
+fn remove_helper_files(mounted: &ElevatedVhdMount) {
+    let _ = fs::remove_file(&mounted.script_path);
+}
+
+/// Random so another process cannot predict the pipe and claim it before the helper does.
+fn helper_pipe_name() -> Result<String, String> {
+    use std::fmt::Write as _;
+
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).map_err(|e| e.to_string())?;
+    let mut name = String::from("configarc-vhd-");
+    for b in bytes {
+        let _ = write!(name, "{b:02x}");
+    }
+    Ok(name)
+}
+
+/// Asks the helper to detach its images and waits until it has. `connect_timeout` bounds
+/// how long a helper that may be gone is waited for.
+fn signal_elevated_unmount(mounted: &ElevatedVhdMount, connect_timeout: Duration) -> Result<(), String> {
+    if mounted.pipe_name.is_empty() {
+        return Err("Mount was made by an older helper that cannot be reached".to_string());
+    }
+    let result: HelperResult = helper_ipc::exchange(
+        &mounted.pipe_name,
+        &HelperRequest::Unmount,
+        "unmount the images",
+        connect_timeout,
+        HELPER_UNMOUNT_TIMEOUT,
+    )
+    .map_err(|e| e.to_string())?;
+    if result.ok {
+        Ok(())
+    } else {
+        Err(result.error.unwrap_or_else(|| "Elevated unmount failed".to_string()))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MountState {
+    pid: u32,
+    mounted_at: String,
+    handle: VhdMountHandle,
+}
+
+/// What the startup recovery pass cleaned up after a session that did not unmount.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StaleMountRecovery {
+    /// When the stale mount was made, if one had been recorded.
+    pub mounted_at: Option<String>,
+    pub mount_points: Option<MountPoints>,
+    /// Runtime deltas kept as snapshots because their game keeps them.
+    pub snapshotted_deltas: Vec<PathBuf>,
+    pub removed_deltas: Vec<PathBuf>,
+    pub errors: Vec<String>,
+}
+
+impl StaleMountRecovery {
+    pub fn is_empty(&self) -> bool {
+        self.mount_points.is_none()
+            && self.snapshotted_deltas.is_empty()
+            && self.removed_deltas.is_empty()
+            && self.errors.is_empty()
+    }
+}
+
+fn mount_state_path() -> PathBuf {
+    Path::new(".").join(MOUNT_STATE_FILE_NAME)
+}
+
+/// Best effort: failing to record the mount must not fail a mount that already worked.
+fn write_mount_state(path: &Path, handle: &VhdMountHandle) {
+    let state = MountState {
+        pid: std::process::id(),
+        mounted_at: chrono::Utc::now().to_rfc3339(),
+        handle: handle.clone(),
+    };
+    if let Ok(json) = serde_json::to_string_pretty(&state) {
+        let _ = fs::write(path, json);
+    }
+}
+
+fn read_mount_state(path: &Path) -> Option<MountState> {
+    let data = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn process_alive(pid: u32) -> bool {
+    const PROCESS_QUERY_LIMITED_INFORMATION: u32 = 0x1000;
+    const STILL_ACTIVE: u32 = 259;
+    unsafe {
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if process.is_null() {
+            return false;
+        }
+        let mut code = 0u32;
+        let ok = GetExitCodeProcess(process, &mut code);
+        CloseHandle(process);
+        ok != 0 && code == STILL_ACTIVE
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn process_alive(pid: u32) -> bool {
+    Path::new("/proc").join(pid.to_string()).exists()
+}
+
+fn settle_runtime_delta(path: &Path, policy: Option<&DeltaSnapshotPolicy>, report: &mut StaleMountRecovery) {
+    dismount_image(path);
+    let result = match policy {
+        Some(policy) => store_delta_snapshot(policy, path).map(|_| true),
+        None => fs::remove_file(path).map(|_| false).map_err(|e| e.to_string()),
+    };
+    match result {
+        Ok(true) => report.snapshotted_deltas.push(path.to_path_buf()),
+        Ok(false) => report.removed_deltas.push(path.to_path_buf()),
+        Err(err) => report.errors.push(format!("{}: {}", path.display(), err)),
+    }
+}
+
+fn reconcile_at(state_path: &Path, game_ids: &[String]) -> StaleMountRecovery {
+    let mut report = StaleMountRecovery::default();
+    if let Some(state) = read_mount_state(state_path) {
+        if state.pid != std::process::id() && process_alive(state.pid) {
+            // Another launcher instance still owns that mount, and so every runtime delta.
+            return report;
+        }
+        report.mounted_at = Some(state.mounted_at.clone());
+        report.mount_points = Some(state.handle.mount_points().clone());
+        let (runtime, policy) = match &state.handle {
+            VhdMountHandle::Direct(mounted) => {
+                unmount_image(&mounted.option_mount_path, &mounted.mount_points.option);
+                unmount_image(&mounted.appdata_mount_path, &mounted.mount_points.appdata);
+                unmount_image(&mounted.app_mount_path, &mounted.mount_points.app);
+                cleanup_repair_root(&mounted.repair_root);
+                (mounted.app_runtime_path.clone(), mounted.delta_snapshots.clone())
+            }
+            VhdMountHandle::Elevated(mounted) => {
+                // A helper that outlived the launcher still unmounts on its signal; one that
+                // is gone left the images attached, which only works from an elevated launcher.
+                if signal_elevated_unmount(mounted, STALE_HELPER_TIMEOUT).is_err() {
+                    unmount_image(&mounted.option_mount_path, &mounted.mount_points.option);
+                    unmount_image(&mounted.appdata_mount_path, &mounted.mount_points.appdata);
+                    unmount_image(&mounted.app_mount_path, &mounted.mount_points.app);
                 }
-                sleep(Duration::from_millis(200));
+                remove_helper_files(mounted);
+                (mounted.app_runtime_path.clone(), mounted.delta_snapshots.clone())
+            }
+        };
+        if let Some(path) = runtime.filter(|path| path.exists()) {
+            settle_runtime_delta(&path, policy.as_ref(), &mut report);
+        }
+        let _ = fs::remove_file(state_path);
+    }
+
+    // Deltas the state file did not know about, e.g. from a crash before it was written.
+    for game_id in game_ids {
+        let policy = load_vhd_config(game_id)
+            .ok()
+            .and_then(|cfg| resolve_vhd_config(game_id, &cfg).ok())
+            .and_then(|resolved| resolved.kept_delta_snapshots());
+        for path in runtime_delta_paths_for_game(game_id) {
+            if path.exists() {
+                settle_runtime_delta(&path, policy.as_ref(), &mut report);
+            }
+        }
+    }
+    report
+}
+
+/// Cleans up after a session that never unmounted, e.g. because the launcher crashed: the
+/// recorded mount is detached and leftover runtime deltas of `game_ids` are kept as
+/// snapshots or deleted, as their game's snapshot setting says. Meant to run once at
+/// startup; mounts wait until it has finished.
+pub fn reconcile_stale_mounts(game_ids: &[String]) -> StaleMountRecovery {
+    let _guard = MOUNT_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    reconcile_at(&mount_state_path(), game_ids)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WarmUpProgress {
+    pub file: PathBuf,
+    pub file_index: usize,
+    pub file_count: usize,
+    /// Bytes read across all files so far.
+    pub processed: u64,
+    pub total: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WarmUpReport {
+    pub files: Vec<PathBuf>,
+    pub bytes_read: u64,
+    pub elapsed_ms: u64,
+    pub cancelled: bool,
+}
+
+/// Reads `paths` front to back so their blocks sit in the OS file cache before a launch
+/// needs them. The data is discarded; a cold spinning disk is the only thing being paid for
+/// ahead of time. Stops between chunks once `cancel` is set.
+pub fn warm_up_images(
+    paths: &[PathBuf],
+    cancel: &AtomicBool,
+    mut on_progress: impl FnMut(WarmUpProgress),
+) -> Result<WarmUpReport, String> {
+    let mut sizes = Vec::with_capacity(paths.len());
+    for path in paths {
+        let meta = fs::metadata(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        sizes.push(meta.len());
+    }
+    let total: u64 = sizes.iter().sum();
+    let started = Instant::now();
+    let mut last_report: Option<Instant> = None;
+    let mut processed = 0u64;
+    let mut buf = vec![0u8; WARM_UP_CHUNK_SIZE];
+    let mut cancelled = false;
+    'files: for (index, path) in paths.iter().enumerate() {
+        let mut file = fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        loop {
+            if cancel.load(Ordering::SeqCst) {
+                cancelled = true;
+                break 'files;
+            }
+            let read = file
+                .read(&mut buf)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            if read == 0 {
+                break;
             }
-            if done {
-                let _ = fs::remove_file(&mounted.signal_path);
-                let _ = fs::remove_file(&mounted.result_path);
-                let _ = fs::remove_file(&mounted.done_path);
-                let _ = fs::remove_file(&mounted.script_path);
-                Ok(())
-            } else {
-                Err("Timed out waiting for elevated unmount".to_string())
+            processed += read as u64;
+            let due = last_report.is_none_or(|last| last.elapsed() >= WARM_UP_PROGRESS_INTERVAL);
+            if due || processed == total {
+                last_report = Some(Instant::now());
+                on_progress(WarmUpProgress {
+                    file: path.clone(),
+                    file_index: index,
+                    file_count: paths.len(),
+                    processed,
+                    total,
+                });
             }
         }
     }
+    Ok(WarmUpReport {
+        files: paths.to_vec(),
+        bytes_read: processed,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+        cancelled,
+    })
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ResolvedVhdConfig, VhdConfig};
+    use super::{
+        busy_preferred_letters, load_vhd_config_at, pending_restore_file, pick_drive_letters, read_snapshot_index, reconcile_at,
+        resolve_with_base, runtime_path_for_parent, store_delta_snapshot, warm_up_images, write_mount_state,
+        unknown_holder, write_snapshot_index, DeltaSnapshotPolicy, MountPoint, MountPoints, MountedVhd, ResolvedVhdConfig,
+        VhdConfig, VhdMountHandle, VirtualDiskFormat,
+    };
+    use std::sync::atomic::{AtomicBool, Ordering};
     use std::path::Path;
     use std::path::PathBuf;
+    use std::time::Duration;
 
     #[test]
     fn deserializes_legacy_single_patch_config() {
@@ -821,6 +2543,23 @@ mod tests {
         assert_eq!(cfg.app_patch_paths, vec!["patch-1.vhd"]);
     }
 
+    #[test]
+    fn migrates_base_patch_layout_to_current_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vhd.json");
+        let legacy = r#"{"base_path":"base.vhd","patch_path":"patch-1.vhd","appdata_path":"appdata.vhd","option_path":"option.vhd"}"#;
+        std::fs::write(&path, legacy).unwrap();
+
+        let cfg = load_vhd_config_at(&path).unwrap();
+        assert_eq!(cfg.app_base_path, "base.vhd");
+        assert_eq!(cfg.app_patch_paths, vec!["patch-1.vhd"]);
+        assert_eq!(std::fs::read_to_string(path.with_extension("json.bak")).unwrap(), legacy);
+        let migrated: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(migrated["version"], 2);
+        assert_eq!(migrated["app_patch_paths"][0], "patch-1.vhd");
+        assert_eq!(load_vhd_config_at(&path).unwrap(), cfg);
+    }
+
     #[test]
     fn deserializes_multi_patch_config_and_drops_blank_entries() {
         let cfg: VhdConfig = serde_json::from_str(
@@ -837,6 +2576,18 @@ mod tests {
         assert!(cfg.delta_enabled);
     }
 
+    #[test]
+    fn waits_only_for_preferred_letters_in_use() {
+        assert_eq!(busy_preferred_letters(&['C', 'D', 'y', 'Z']), vec!['Y', 'Z']);
+        assert!(busy_preferred_letters(&['C', 'D']).is_empty());
+
+        let mut holder = unknown_holder('X');
+        assert!(!holder.releases_soon());
+        holder.image = Some("C:\\games\\SDEZ-runtime.vhd".to_string());
+        assert!(holder.releases_soon());
+        assert_eq!(holder.describe(), "X: is held by image C:\\games\\SDEZ-runtime.vhd");
+    }
+
     #[test]
     fn uses_latest_patch_as_runtime_parent_or_base_when_empty() {
         let with_patches = ResolvedVhdConfig {
@@ -845,6 +2596,12 @@ mod tests {
             appdata_path: PathBuf::from("appdata.vhd"),
             option_path: PathBuf::from("option.vhd"),
             delta_enabled: true,
+            delta_dir: None,
+            delta_min_free_mb: 2048,
+            mount_root: None,
+            letter_wait: Duration::ZERO,
+            delta_snapshots: DeltaSnapshotPolicy::default(),
+            delta_restore: None,
         };
         assert_eq!(with_patches.app_parent_path(), Path::new("patch-2.vhd"));
 
@@ -854,7 +2611,161 @@ mod tests {
             appdata_path: PathBuf::from("appdata.vhd"),
             option_path: PathBuf::from("option.vhd"),
             delta_enabled: true,
+            delta_dir: None,
+            delta_min_free_mb: 2048,
+            mount_root: None,
+            letter_wait: Duration::ZERO,
+            delta_snapshots: DeltaSnapshotPolicy::default(),
+            delta_restore: None,
         };
         assert_eq!(without_patches.app_parent_path(), Path::new("base.vhd"));
     }
+
+    #[test]
+    fn falls_back_to_parent_dir_when_delta_dir_is_missing() {
+        let cfg = ResolvedVhdConfig {
+            app_base_path: PathBuf::from("games/base.vhd"),
+            app_patch_paths: vec![],
+            appdata_path: PathBuf::from("appdata.vhd"),
+            option_path: PathBuf::from("option.vhd"),
+            delta_enabled: true,
+            delta_dir: Some(PathBuf::from("definitely-missing-ramdisk")),
+            delta_min_free_mb: 2048,
+            mount_root: None,
+            letter_wait: Duration::ZERO,
+            delta_snapshots: DeltaSnapshotPolicy::default(),
+            delta_restore: None,
+        };
+        let (path, warning) = cfg.runtime_delta_placement();
+        assert_eq!(path, Path::new("games").join("base-runtime.vhd"));
+        assert!(warning.is_some());
+    }
+
+    #[test]
+    fn shared_delta_dir_keeps_games_apart() {
+        let delta_dir = tempfile::tempdir().unwrap();
+        let placement = |base: &str| {
+            ResolvedVhdConfig {
+                app_base_path: PathBuf::from(base),
+                app_patch_paths: vec![],
+                appdata_path: PathBuf::from("appdata.vhd"),
+                option_path: PathBuf::from("option.vhd"),
+                delta_enabled: true,
+                delta_dir: Some(delta_dir.path().to_path_buf()),
+                delta_min_free_mb: 0,
+                mount_root: None,
+                letter_wait: Duration::ZERO,
+                delta_snapshots: DeltaSnapshotPolicy::default(),
+                delta_restore: None,
+            }
+            .runtime_delta_placement()
+        };
+        let (chunithm, warning) = placement("chunithm/app.vhdx");
+        let (maimai, _) = placement("maimai/app.vhdx");
+        assert!(warning.is_none());
+        assert_ne!(chunithm, maimai);
+        assert_eq!(chunithm.parent(), Some(delta_dir.path()));
+        assert_eq!(VirtualDiskFormat::from_path(&chunithm), Some(VirtualDiskFormat::Vhdx));
+        assert_eq!(placement("chunithm/app.vhdx").0, chunithm);
+    }
+
+    #[test]
+    fn runtime_delta_keeps_vhdx_format() {
+        let parent = resolve_with_base(Path::new("games"), "SDHD_2.30.00_20250101000000_0.VHDX");
+        assert_eq!(VirtualDiskFormat::from_path(&parent), Some(VirtualDiskFormat::Vhdx));
+        let runtime = runtime_path_for_parent(&parent);
+        assert_eq!(runtime, Path::new("games").join("SDHD_2.30.00_20250101000000_0-runtime.VHDX"));
+        assert_eq!(VirtualDiskFormat::from_path(&runtime).map(|f| f.storage_type()), Some("VHDX"));
+        assert_eq!(VirtualDiskFormat::from_path(Path::new("option.iso")), None);
+    }
+
+    #[test]
+    fn keeps_preferred_letters_and_replaces_taken_ones() {
+        assert_eq!(pick_drive_letters(&['C', 'D']).unwrap(), MountPoints::default());
+        let points = pick_drive_letters(&['C', 'X', 'Z']).unwrap();
+        assert_eq!(points.app, MountPoint::Drive('W'));
+        assert_eq!(points.appdata, MountPoint::Drive('Y'));
+        assert_eq!(points.option, MountPoint::Drive('V'));
+        assert!(points.contains("w:\\amfs"));
+        assert!(!points.contains("X:\\amfs"));
+        assert!(pick_drive_letters(&('D'..='Z').collect::<Vec<_>>()).is_err());
+    }
+
+    #[test]
+    fn warm_up_reads_every_image_and_stops_when_cancelled() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = dir.path().join("app.vhd");
+        let appdata = dir.path().join("appdata.vhd");
+        std::fs::write(&app, vec![1u8; super::WARM_UP_CHUNK_SIZE + 3]).unwrap();
+        std::fs::write(&appdata, vec![2u8; 10]).unwrap();
+        let paths = vec![app, appdata];
+
+        let cancel = AtomicBool::new(false);
+        let mut last = None;
+        let report = warm_up_images(&paths, &cancel, |p| last = Some((p.processed, p.total))).unwrap();
+        let total = super::WARM_UP_CHUNK_SIZE as u64 + 13;
+        assert_eq!(report.bytes_read, total);
+        assert!(!report.cancelled);
+        assert_eq!(last, Some((total, total)));
+
+        cancel.store(true, Ordering::SeqCst);
+        let report = warm_up_images(&paths, &cancel, |_| {}).unwrap();
+        assert!(report.cancelled);
+        assert_eq!(report.bytes_read, 0);
+    }
+
+    #[test]
+    fn keeps_last_sessions_and_checks_restore_parent() {
+        let dir = tempfile::tempdir().unwrap();
+        let policy = DeltaSnapshotPolicy {
+            dir: dir.path().join("delta_snapshots"),
+            keep: 2,
+            parent_path: PathBuf::from("games/base.vhd"),
+        };
+        let runtime = dir.path().join("base-runtime.vhd");
+        let mut names = Vec::new();
+        for session in 0..3u8 {
+            std::fs::write(&runtime, [session]).unwrap();
+            names.push(store_delta_snapshot(&policy, &runtime).unwrap().name);
+            assert!(!runtime.exists());
+        }
+        let index = read_snapshot_index(&policy.dir).unwrap();
+        assert_eq!(index.snapshots.len(), 2);
+        assert_eq!(index.snapshots[0].name, names[1]);
+        assert_eq!(std::fs::read(&index.snapshots[1].file).unwrap(), vec![2]);
+
+        let mut index = index;
+        index.pending_restore = Some(names[2].clone());
+        write_snapshot_index(&policy.dir, &index).unwrap();
+        assert_eq!(pending_restore_file(&policy).unwrap(), Some(index.snapshots[1].file.clone()));
+        let patched = DeltaSnapshotPolicy {
+            parent_path: PathBuf::from("games/patch-1.vhd"),
+            ..policy.clone()
+        };
+        assert!(pending_restore_file(&patched).is_err());
+    }
+
+    #[test]
+    fn recovers_a_recorded_mount_and_its_runtime_delta() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = dir.path().join("mount_state.json");
+        let runtime = dir.path().join("base-runtime.vhd");
+        std::fs::write(&runtime, b"delta").unwrap();
+        let handle = VhdMountHandle::Direct(MountedVhd {
+            app_mount_path: runtime.clone(),
+            app_runtime_path: Some(runtime.clone()),
+            appdata_mount_path: dir.path().join("appdata.vhd"),
+            option_mount_path: dir.path().join("option.vhd"),
+            repair_root: None,
+            mount_points: MountPoints::default(),
+            delta_snapshots: None,
+        });
+        write_mount_state(&state, &handle);
+
+        let report = reconcile_at(&state, &[]);
+        assert_eq!(report.mount_points, Some(MountPoints::default()));
+        assert_eq!(report.removed_deltas, vec![runtime.clone()]);
+        assert!(!runtime.exists() && !state.exists());
+        assert!(reconcile_at(&state, &[]).is_empty());
+    }
 }
@@ -0,0 +1,445 @@
+//! Reads just enough of VHD and VHDX headers to tell base images from differencing ones and
+//! to find the image each differencing disk was created on, so a folder of renamed images
+//! still sorts into base, patches and standalone disks. Nothing is mounted.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use super::VirtualDiskFormat;
+
+const VHD_FOOTER_COOKIE: &[u8] = b"conectix";
+const VHD_DYNAMIC_COOKIE: &[u8] = b"cxsparse";
+const VHD_DISK_TYPE_DIFFERENCING: u32 = 4;
+/// Relative and absolute Windows paths, UTF-16LE.
+const VHD_LOCATOR_CODES: [&[u8]; 2] = [b"W2ru", b"W2ku"];
+const VHDX_SIGNATURE: &[u8] = b"vhdxfile";
+const VHDX_HEADER_OFFSETS: [u64; 2] = [64 * 1024, 128 * 1024];
+const VHDX_REGION_TABLE_OFFSET: u64 = 192 * 1024;
+const VHDX_METADATA_REGION: &str = "8B7CA206-4790-4B9A-B8FE-575F050F886E";
+const VHDX_FILE_PARAMETERS: &str = "CAA16737-FA36-4D43-B3B6-33F0AA44E76B";
+const VHDX_PARENT_LOCATOR: &str = "A8D35F2D-B30B-454D-ABF7-D3D84834AB0C";
+const VHDX_HAS_PARENT: u32 = 0x2;
+
+/// What an image says about itself and its parent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiskIdentity {
+    pub format: VirtualDiskFormat,
+    /// Id a differencing child records for this image: the VHD unique id or the VHDX data
+    /// write GUID.
+    pub id: String,
+    pub parent: Option<ParentLink>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParentLink {
+    /// [`DiskIdentity::id`] the parent had when this image was created on it.
+    pub id: String,
+    /// Parent paths recorded in the image, relative ones first.
+    pub paths: Vec<String>,
+}
+
+impl ParentLink {
+    /// File name of the parent as recorded, for messages.
+    pub fn file_name(&self) -> Option<String> {
+        self.paths.iter().find_map(|path| {
+            path.rsplit(['\\', '/'])
+                .next()
+                .filter(|name| !name.is_empty())
+                .map(str::to_string)
+        })
+    }
+}
+
+/// App base image and its patches in the order they stack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppChain {
+    pub base: PathBuf,
+    pub patches: Vec<PathBuf>,
+}
+
+fn read_at(file: &mut File, offset: u64, len: usize) -> Result<Vec<u8>, String> {
+    let mut buf = vec![0; len];
+    file.seek(SeekFrom::Start(offset)).map_err(|e| e.to_string())?;
+    file.read_exact(&mut buf).map_err(|e| e.to_string())?;
+    Ok(buf)
+}
+
+fn field(bytes: &[u8], at: usize, len: usize) -> Result<&[u8], String> {
+    bytes
+        .get(at..at.saturating_add(len))
+        .ok_or_else(|| "header is truncated".to_string())
+}
+
+fn be_u32(bytes: &[u8], at: usize) -> Result<u32, String> {
+    Ok(u32::from_be_bytes(field(bytes, at, 4)?.try_into().unwrap()))
+}
+
+fn be_u64(bytes: &[u8], at: usize) -> Result<u64, String> {
+    Ok(u64::from_be_bytes(field(bytes, at, 8)?.try_into().unwrap()))
+}
+
+fn le_u16(bytes: &[u8], at: usize) -> Result<u16, String> {
+    Ok(u16::from_le_bytes(field(bytes, at, 2)?.try_into().unwrap()))
+}
+
+fn le_u32(bytes: &[u8], at: usize) -> Result<u32, String> {
+    Ok(u32::from_le_bytes(field(bytes, at, 4)?.try_into().unwrap()))
+}
+
+fn le_u64(bytes: &[u8], at: usize) -> Result<u64, String> {
+    Ok(u64::from_le_bytes(field(bytes, at, 8)?.try_into().unwrap()))
+}
+
+fn hex_id(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+/// A GUID in the mixed-endian layout Windows stores it in.
+fn guid_string(bytes: &[u8]) -> String {
+    let d1 = u32::from_le_bytes(bytes[0..4].try_into().unwrap());
+    let d2 = u16::from_le_bytes(bytes[4..6].try_into().unwrap());
+    let d3 = u16::from_le_bytes(bytes[6..8].try_into().unwrap());
+    format!("{:08X}-{:04X}-{:04X}-{}-{}", d1, d2, d3, hex_id(&bytes[8..10]), hex_id(&bytes[10..16]))
+}
+
+fn utf16_string(bytes: &[u8], big_endian: bool) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| {
+            let pair = [pair[0], pair[1]];
+            if big_endian {
+                u16::from_be_bytes(pair)
+            } else {
+                u16::from_le_bytes(pair)
+            }
+        })
+        .take_while(|unit| *unit != 0)
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+fn read_vhd(file: &mut File, len: u64) -> Result<DiskIdentity, String> {
+    if len < 512 {
+        return Err("file is too small for a VHD footer".to_string());
+    }
+    // Dynamic and differencing disks keep a copy of the footer at the start.
+    let footer = [len - 512, 0]
+        .into_iter()
+        .map(|offset| read_at(file, offset, 512))
+        .find(|footer| footer.as_ref().is_ok_and(|f| f.starts_with(VHD_FOOTER_COOKIE)))
+        .ok_or_else(|| "no VHD footer found".to_string())??;
+    let id = hex_id(field(&footer, 68, 16)?);
+    if be_u32(&footer, 60)? != VHD_DISK_TYPE_DIFFERENCING {
+        return Ok(DiskIdentity {
+            format: VirtualDiskFormat::Vhd,
+            id,
+            parent: None,
+        });
+    }
+
+    let header = read_at(file, be_u64(&footer, 16)?, 1024)?;
+    if !header.starts_with(VHD_DYNAMIC_COOKIE) {
+        return Err("differencing disk without a dynamic disk header".to_string());
+    }
+    let mut located = Vec::new();
+    for index in 0..8 {
+        let entry = field(&header, 576 + index * 24, 24)?;
+        let code = &entry[0..4];
+        let data_len = be_u32(entry, 8)? as usize;
+        if data_len == 0 || !VHD_LOCATOR_CODES.contains(&code) {
+            continue;
+        }
+        let data = read_at(file, be_u64(entry, 16)?, data_len)?;
+        located.push((code != VHD_LOCATOR_CODES[0], utf16_string(&data, false)));
+    }
+    located.sort_by_key(|(absolute, _)| *absolute);
+    let mut paths: Vec<String> = located.into_iter().map(|(_, path)| path).collect();
+    let name = utf16_string(field(&header, 64, 512)?, true);
+    if !name.is_empty() {
+        paths.push(name);
+    }
+    Ok(DiskIdentity {
+        format: VirtualDiskFormat::Vhd,
+        id,
+        parent: Some(ParentLink {
+            id: hex_id(field(&header, 40, 16)?),
+            paths,
+        }),
+    })
+}
+
+/// Data of the metadata item `guid` in a VHDX metadata region.
+fn metadata_item<'a>(metadata: &'a [u8], guid: &str) -> Result<Option<&'a [u8]>, String> {
+    for index in 0..le_u16(metadata, 10)? as usize {
+        let entry = field(metadata, 32 + index * 32, 32)?;
+        if guid_string(&entry[0..16]) == guid {
+            return field(metadata, le_u32(entry, 16)? as usize, le_u32(entry, 20)? as usize).map(Some);
+        }
+    }
+    Ok(None)
+}
+
+fn read_vhdx(file: &mut File) -> Result<DiskIdentity, String> {
+    let mut active: Option<(u64, Vec<u8>)> = None;
+    for offset in VHDX_HEADER_OFFSETS {
+        let header = read_at(file, offset, 4096)?;
+        if !header.starts_with(b"head") {
+            continue;
+        }
+        let sequence = le_u64(&header, 8)?;
+        if active.as_ref().is_none_or(|(newest, _)| sequence > *newest) {
+            active = Some((sequence, header));
+        }
+    }
+    let (_, header) = active.ok_or_else(|| "no valid VHDX header".to_string())?;
+    let id = guid_string(field(&header, 32, 16)?);
+
+    let regions = read_at(file, VHDX_REGION_TABLE_OFFSET, 64 * 1024)?;
+    if !regions.starts_with(b"regi") {
+        return Err("no VHDX region table".to_string());
+    }
+    let region_count = le_u32(&regions, 8)? as usize;
+    let mut metadata_region = None;
+    for index in 0..region_count.min(2047) {
+        let entry = field(&regions, 16 + index * 32, 32)?;
+        if guid_string(&entry[0..16]) == VHDX_METADATA_REGION {
+            metadata_region = Some((le_u64(entry, 16)?, le_u32(entry, 24)? as usize));
+        }
+    }
+    let (offset, len) = metadata_region.ok_or_else(|| "no VHDX metadata region".to_string())?;
+    let metadata = read_at(file, offset, len)?;
+    if !metadata.starts_with(b"metadata") {
+        return Err("no VHDX metadata table".to_string());
+    }
+    let parameters = metadata_item(&metadata, VHDX_FILE_PARAMETERS)?.ok_or_else(|| "no VHDX file parameters".to_string())?;
+    if le_u32(parameters, 4)? & VHDX_HAS_PARENT == 0 {
+        return Ok(DiskIdentity {
+            format: VirtualDiskFormat::Vhdx,
+            id,
+            parent: None,
+        });
+    }
+
+    let locator = metadata_item(&metadata, VHDX_PARENT_LOCATOR)?.ok_or_else(|| "differencing disk without a parent locator".to_string())?;
+    let mut linkage = None;
+    let mut paths = BTreeMap::new();
+    for index in 0..le_u16(locator, 18)? as usize {
+        let entry = field(locator, 20 + index * 12, 12)?;
+        let key = field(locator, le_u32(entry, 0)? as usize, le_u16(entry, 8)? as usize)?;
+        let value = field(locator, le_u32(entry, 4)? as usize, le_u16(entry, 10)? as usize)?;
+        let value = utf16_string(value, false);
+        match utf16_string(key, false).as_str() {
+            "parent_linkage" => linkage = Some(value.trim_matches(['{', '}']).to_ascii_uppercase()),
+            "relative_path" => {
+                paths.insert(0, value);
+            }
+            "absolute_win32_path" => {
+                paths.insert(1, value);
+            }
+            "volume_path" => {
+                paths.insert(2, value);
+            }
+            _ => {}
+        }
+    }
+    Ok(DiskIdentity {
+        format: VirtualDiskFormat::Vhdx,
+        id,
+        parent: Some(ParentLink {
+            id: linkage.ok_or_else(|| "parent locator without parent_linkage".to_string())?,
+            paths: paths.into_values().collect(),
+        }),
+    })
+}
+
+/// Identity of the image at `path`, by its content rather than its extension.
+pub fn read_disk_identity(path: &Path) -> Result<DiskIdentity, String> {
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    let len = file.metadata().map_err(|e| e.to_string())?.len();
+    if len >= 8 && read_at(&mut file, 0, 8)? == VHDX_SIGNATURE {
+        read_vhdx(&mut file)
+    } else {
+        read_vhd(&mut file, len)
+    }
+}
+
+fn display_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string_lossy().into_owned())
+}
+
+/// Splits `images` into the patch chain among them and the standalone images left over.
+/// There is no chain when none of them is a differencing disk. A differencing disk whose
+/// parent is missing or was changed after it was created, a chain that branches, and more
+/// than one chain are errors naming the images involved.
+pub fn classify_images(images: &[PathBuf]) -> Result<(Option<AppChain>, Vec<PathBuf>), String> {
+    let identities = images
+        .iter()
+        .map(|path| {
+            read_disk_identity(path)
+                .map_err(|e| format!("{} is not a readable VHD or VHDX: {}", display_name(path), e))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if identities.iter().all(|identity| identity.parent.is_none()) {
+        return Ok((None, images.to_vec()));
+    }
+
+    let mut parent_of: BTreeMap<usize, usize> = BTreeMap::new();
+    for (index, identity) in identities.iter().enumerate() {
+        let Some(link) = &identity.parent else {
+            continue;
+        };
+        let child = display_name(&images[index]);
+        let parent = identities
+            .iter()
+            .position(|candidate| candidate.id == link.id && candidate.format == identity.format);
+        let Some(parent) = parent else {
+            let recorded = link.file_name().unwrap_or_else(|| "an unknown image".to_string());
+            let renamed_or_changed = images
+                .iter()
+                .any(|path| display_name(path).eq_ignore_ascii_case(&recorded));
+            return Err(if renamed_or_changed {
+                format!(
+                    "{} was created on {}, but {} has changed since, so the patch no longer applies",
+                    child, recorded, recorded
+                )
+            } else {
+                format!("{} is a patch on top of {}, which is not in the folder", child, recorded)
+            });
+        };
+        if let Some((&sibling, _)) = parent_of.iter().find(|(_, &p)| p == parent) {
+            return Err(format!(
+                "{} and {} are both patches on top of {}; keep only one of them",
+                display_name(&images[sibling]),
+                child,
+                display_name(&images[parent])
+            ));
+        }
+        parent_of.insert(index, parent);
+    }
+
+    let root_of = |mut index: usize| {
+        for _ in 0..images.len() {
+            match parent_of.get(&index) {
+                Some(&parent) => index = parent,
+                None => return Some(index),
+            }
+        }
+        None
+    };
+    let mut roots = Vec::new();
+    for &child in parent_of.keys() {
+        let root = root_of(child).ok_or_else(|| format!("{} is part of a looping patch chain", display_name(&images[child])))?;
+        if !roots.contains(&root) {
+            roots.push(root);
+        }
+    }
+    if roots.len() > 1 {
+        let names: Vec<String> = roots.iter().map(|&root| display_name(&images[root])).collect();
+        return Err(format!("The folder holds patch chains for more than one base image: {}", names.join(", ")));
+    }
+
+    let base = roots[0];
+    let mut chain = vec![base];
+    while let Some((&child, _)) = parent_of.iter().find(|(_, &p)| Some(&p) == chain.last()) {
+        chain.push(child);
+    }
+    let standalone = (0..images.len())
+        .filter(|index| !chain.contains(index))
+        .map(|index| images[index].clone())
+        .collect();
+    Ok((
+        Some(AppChain {
+            base: images[base].clone(),
+            patches: chain[1..].iter().map(|&index| images[index].clone()).collect(),
+        }),
+        standalone,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn footer(disk_type: u32, id: u8, data_offset: u64) -> Vec<u8> {
+        let mut footer = vec![0; 512];
+        footer[0..8].copy_from_slice(VHD_FOOTER_COOKIE);
+        footer[16..24].copy_from_slice(&data_offset.to_be_bytes());
+        footer[60..64].copy_from_slice(&disk_type.to_be_bytes());
+        footer[68..84].copy_from_slice(&[id; 16]);
+        footer
+    }
+
+    fn base(path: &Path, id: u8) {
+        fs::write(path, footer(2, id, u64::MAX)).unwrap();
+    }
+
+    fn patch(path: &Path, id: u8, parent_id: u8, parent_name: &str) {
+        let foot = footer(VHD_DISK_TYPE_DIFFERENCING, id, 512);
+        let mut header = vec![0; 1024];
+        header[0..8].copy_from_slice(VHD_DYNAMIC_COOKIE);
+        header[40..56].copy_from_slice(&[parent_id; 16]);
+        let name: Vec<u8> = parent_name.encode_utf16().flat_map(u16::to_be_bytes).collect();
+        header[64..64 + name.len()].copy_from_slice(&name);
+        let relative: Vec<u8> = format!(".\\{}", parent_name).encode_utf16().flat_map(u16::to_le_bytes).collect();
+        header[576..580].copy_from_slice(b"W2ru");
+        header[584..588].copy_from_slice(&(relative.len() as u32).to_be_bytes());
+        header[592..600].copy_from_slice(&(1536u64).to_be_bytes());
+        let mut locator = relative;
+        locator.resize(512, 0);
+        fs::write(path, [foot.clone(), header, locator, foot].concat()).unwrap();
+    }
+
+    #[test]
+    fn orders_renamed_images_by_their_parent_links() {
+        let dir = TempDir::new().unwrap();
+        let path = |name: &str| dir.path().join(name);
+        base(&path("zzz.vhd"), 1);
+        patch(&path("aaa.vhd"), 2, 1, "SDEZ_1.40.00.vhd");
+        patch(&path("mmm.vhd"), 3, 2, "SDEZ_1.45.00.vhd");
+        base(&path("data.vhd"), 9);
+
+        let link = read_disk_identity(&path("aaa.vhd")).unwrap().parent.unwrap();
+        assert_eq!(link.paths, [".\\SDEZ_1.40.00.vhd", "SDEZ_1.40.00.vhd"]);
+        assert_eq!(link.file_name().as_deref(), Some("SDEZ_1.40.00.vhd"));
+
+        let images = vec![path("mmm.vhd"), path("data.vhd"), path("aaa.vhd"), path("zzz.vhd")];
+        let (chain, standalone) = classify_images(&images).unwrap();
+        assert_eq!(
+            chain,
+            Some(AppChain {
+                base: path("zzz.vhd"),
+                patches: vec![path("aaa.vhd"), path("mmm.vhd")],
+            })
+        );
+        assert_eq!(standalone, [path("data.vhd")]);
+    }
+
+    #[test]
+    fn rejects_broken_and_branching_chains() {
+        let dir = TempDir::new().unwrap();
+        let path = |name: &str| dir.path().join(name);
+        base(&path("base.vhd"), 1);
+        patch(&path("p1.vhd"), 2, 7, "base.vhd");
+        let err = classify_images(&[path("base.vhd"), path("p1.vhd")]).unwrap_err();
+        assert!(err.contains("has changed since"), "{}", err);
+
+        patch(&path("p1.vhd"), 2, 7, "gone.vhd");
+        let err = classify_images(&[path("base.vhd"), path("p1.vhd")]).unwrap_err();
+        assert!(err.contains("not in the folder"), "{}", err);
+
+        patch(&path("p1.vhd"), 2, 1, "base.vhd");
+        patch(&path("p2.vhd"), 3, 1, "base.vhd");
+        let err = classify_images(&[path("base.vhd"), path("p1.vhd"), path("p2.vhd")]).unwrap_err();
+        assert!(err.contains("both patches on top of base.vhd"), "{}", err);
+
+        fs::write(path("junk.vhd"), b"not a disk").unwrap();
+        assert!(classify_images(&[path("junk.vhd")]).is_err());
+        assert_eq!(classify_images(&[path("base.vhd")]).unwrap(), (None, vec![path("base.vhd")]));
+    }
+}
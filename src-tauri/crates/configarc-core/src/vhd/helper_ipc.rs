@@ -0,0 +1,130 @@
+//! Named-pipe channel to the elevated VHD helper. Each connection carries one JSON request
+//! line from the launcher and one JSON reply line from the helper.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub(super) enum HelperIpcError {
+    /// The pipe did not appear, or stayed busy, until the deadline.
+    #[error("Could not connect to the elevated mount helper: {0}")]
+    Connect(String),
+    #[error("Timed out waiting for the elevated mount helper to {0}")]
+    Timeout(&'static str),
+    #[error("The elevated mount helper closed the connection without replying")]
+    Closed,
+    #[error("Unexpected reply from the elevated mount helper: {0}")]
+    Protocol(String),
+    #[error("Elevated mount helper pipe error: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// Connects to `\\.\pipe\<name>`, retrying while the helper is still starting (it may be
+/// waiting on the UAC prompt) for up to `connect_timeout`, and exchanges one request.
+#[cfg(target_os = "windows")]
+pub(super) fn exchange<Req: Serialize, Resp: DeserializeOwned>(
+    name: &str,
+    request: &Req,
+    stage: &'static str,
+    connect_timeout: Duration,
+    reply_timeout: Duration,
+) -> Result<Resp, HelperIpcError> {
+    let path = format!(r"\\.\pipe\{name}");
+    let start = std::time::Instant::now();
+    let pipe = loop {
+        match std::fs::OpenOptions::new().read(true).write(true).open(&path) {
+            Ok(pipe) => break pipe,
+            Err(_) if start.elapsed() < connect_timeout => thread::sleep(Duration::from_millis(200)),
+            Err(err) => return Err(HelperIpcError::Connect(err.to_string())),
+        }
+    };
+    exchange_over(pipe, request, stage, reply_timeout)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub(super) fn exchange<Req: Serialize, Resp: DeserializeOwned>(
+    _name: &str,
+    _request: &Req,
+    _stage: &'static str,
+    _connect_timeout: Duration,
+    _reply_timeout: Duration,
+) -> Result<Resp, HelperIpcError> {
+    Err(HelperIpcError::Connect("named pipes are only available on Windows".to_string()))
+}
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn exchange_over<S, Req, Resp>(
+    mut stream: S,
+    request: &Req,
+    stage: &'static str,
+    reply_timeout: Duration,
+) -> Result<Resp, HelperIpcError>
+where
+    S: Read + Write + Send + 'static,
+    Req: Serialize,
+    Resp: DeserializeOwned,
+{
+    let mut line = serde_json::to_vec(request).map_err(|e| HelperIpcError::Protocol(e.to_string()))?;
+    line.push(b'\n');
+    stream.write_all(&line)?;
+    stream.flush()?;
+
+    // A synchronous pipe read cannot time out, so the reply is read on its own thread. The
+    // write above is complete by then: pending reads and writes on one pipe handle serialize.
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut reply = String::new();
+        let read = BufReader::new(stream).read_line(&mut reply);
+        let _ = tx.send(read.map(|_| reply));
+    });
+    let reply = match rx.recv_timeout(reply_timeout) {
+        Ok(reply) => reply?,
+        Err(RecvTimeoutError::Timeout) => return Err(HelperIpcError::Timeout(stage)),
+        Err(RecvTimeoutError::Disconnected) => return Err(HelperIpcError::Closed),
+    };
+    let reply = reply.trim();
+    let reply = reply.strip_prefix('\u{feff}').unwrap_or(reply);
+    if reply.is_empty() {
+        return Err(HelperIpcError::Closed);
+    }
+    serde_json::from_str(reply).map_err(|e| HelperIpcError::Protocol(e.to_string()))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+    use std::os::unix::net::UnixStream;
+
+    #[test]
+    fn exchanges_one_line_and_reports_silent_helpers() {
+        let (client, server) = UnixStream::pair().unwrap();
+        let helper = thread::spawn(move || {
+            let mut reader = BufReader::new(server.try_clone().unwrap());
+            let mut request = String::new();
+            reader.read_line(&mut request).unwrap();
+            let request: Value = serde_json::from_str(&request).unwrap();
+            assert_eq!(request["command"], "unmount");
+            let mut server = server;
+            server.write_all(b"{\"ok\":true}\n").unwrap();
+        });
+        let reply: Value =
+            exchange_over(client, &json!({ "command": "unmount" }), "unmount", Duration::from_secs(5)).unwrap();
+        assert_eq!(reply["ok"], true);
+        helper.join().unwrap();
+
+        let (client, _server) = UnixStream::pair().unwrap();
+        let err = exchange_over::<_, _, Value>(client, &json!({}), "mount", Duration::from_millis(100)).unwrap_err();
+        assert!(matches!(err, HelperIpcError::Timeout("mount")));
+
+        let (client, server) = UnixStream::pair().unwrap();
+        drop(server);
+        let err = exchange_over::<_, _, Value>(client, &json!({}), "mount", Duration::from_secs(5)).unwrap_err();
+        assert!(matches!(err, HelperIpcError::Closed | HelperIpcError::Io(_)));
+    }
+}
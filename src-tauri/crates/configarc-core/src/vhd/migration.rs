@@ -0,0 +1,457 @@
+//! Turning a folder-mode install into the three images a VHD game mounts: the game folder
+//! goes onto the app volume, amfs and appdata onto the appdata volume and the option
+//! folders onto the option volume. Images are created and formatted with diskpart, so
+//! [`migrate_to_vhd`] needs an elevated launcher on Windows; planning works anywhere.
+
+use super::{
+    default_delta_min_free_mb, default_letter_wait_secs, free_space_bytes, is_running_as_admin, MountRole, VhdConfig,
+    VirtualDiskFormat,
+};
+use crate::cancel::CANCELLED_MESSAGE;
+use crate::games::trash::TRASH_DIR_NAME;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+#[cfg(target_os = "windows")]
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+/// Folder beside the new images that the volumes are attached to while they are filled.
+const STAGING_DIR_NAME: &str = ".configarc_migration";
+const MB: u64 = 1024 * 1024;
+/// Room left on every volume for saves, logs and updates applied later.
+const SLACK_MB: u64 = 1024;
+
+/// Where the content of a folder-mode install lives today.
+#[derive(Debug, Clone)]
+pub struct MigrationSources {
+    pub game_root: PathBuf,
+    pub amfs: Option<PathBuf>,
+    pub appdata: Option<PathBuf>,
+    pub option: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationCopy {
+    pub source: PathBuf,
+    /// Folder on the volume the source's content lands in; empty for the volume root.
+    pub dest: String,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationVolume {
+    /// `app`, `appdata` or `option`.
+    pub role: &'static str,
+    pub image: PathBuf,
+    /// Maximum size of the expandable image.
+    pub size_mb: u64,
+    pub content_bytes: u64,
+    pub copies: Vec<MigrationCopy>,
+    /// Paths inside the copied folders that another volume takes or that stay behind.
+    #[serde(skip)]
+    pub exclude: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationPlan {
+    pub target_dir: PathBuf,
+    pub volumes: Vec<MigrationVolume>,
+    /// Space the filled images take; they only grow towards `size_mb` as data is written.
+    pub required_bytes: u64,
+    /// Free space on the target drive, when it can be read.
+    pub free_bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationProgress {
+    pub role: &'static str,
+    /// `creating`, `copying` or `detaching`.
+    pub stage: &'static str,
+    pub copied_bytes: u64,
+    pub total_bytes: u64,
+    /// File being copied, relative to its source folder.
+    pub current: Option<String>,
+}
+
+impl MigrationPlan {
+    pub fn image(&self, role: MountRole) -> Option<&Path> {
+        self.volumes
+            .iter()
+            .find(|volume| volume.role == role.folder_name())
+            .map(|volume| volume.image.as_path())
+    }
+
+    /// A config that mounts the planned images with the usual defaults.
+    pub fn vhd_config(&self) -> VhdConfig {
+        let image = |role| {
+            self.image(role)
+                .map(|path| path.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        };
+        VhdConfig {
+            app_base_path: image(MountRole::App),
+            app_patch_paths: Vec::new(),
+            appdata_path: image(MountRole::AppData),
+            option_path: image(MountRole::Option),
+            delta_enabled: true,
+            delta_dir: None,
+            delta_min_free_mb: default_delta_min_free_mb(),
+            delta_snapshots: 0,
+            mount_root: None,
+            letter_wait_secs: default_letter_wait_secs(),
+        }
+    }
+
+    /// The copied folders that can be removed afterwards, leaving out folders inside
+    /// another one that is removed with it.
+    pub fn original_paths(&self) -> Vec<PathBuf> {
+        let sources: Vec<&PathBuf> = self
+            .volumes
+            .iter()
+            .flat_map(|volume| volume.copies.iter().map(|copy| &copy.source))
+            .collect();
+        sources
+            .iter()
+            .filter(|source| !sources.iter().any(|other| other != *source && source.starts_with(other)))
+            .map(|source| (*source).clone())
+            .collect()
+    }
+}
+
+/// Image size for `content_bytes` of data: a quarter on top plus [`SLACK_MB`], rounded up
+/// to whole gigabytes.
+pub fn image_size_mb(content_bytes: u64) -> u64 {
+    let content_mb = content_bytes.div_ceil(MB);
+    let wanted = content_mb + content_mb / 4 + SLACK_MB;
+    wanted.div_ceil(1024) * 1024
+}
+
+fn tree_size(path: &Path, exclude: &[PathBuf]) -> u64 {
+    if exclude.iter().any(|skip| skip == path) {
+        return 0;
+    }
+    let Ok(meta) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !meta.is_dir() {
+        return meta.len();
+    }
+    fs::read_dir(path)
+        .map(|entries| entries.flatten().map(|entry| tree_size(&entry.path(), exclude)).sum())
+        .unwrap_or(0)
+}
+
+/// Lays out the images for `sources` in `target_dir` without touching anything. Folders
+/// that do not exist are skipped; their volume is still created, empty.
+pub fn plan_migration(
+    sources: &MigrationSources,
+    target_dir: &Path,
+    format: VirtualDiskFormat,
+) -> Result<MigrationPlan, String> {
+    if !sources.game_root.is_dir() {
+        return Err(format!("Game folder not found: {}", sources.game_root.to_string_lossy()));
+    }
+    let existing = |path: &Option<PathBuf>| path.clone().filter(|p| p.is_dir());
+    let (amfs, appdata, option) = (
+        existing(&sources.amfs),
+        existing(&sources.appdata),
+        existing(&sources.option),
+    );
+    let extension = match format {
+        VirtualDiskFormat::Vhd => "vhd",
+        VirtualDiskFormat::Vhdx => "vhdx",
+    };
+
+    let mut stay_behind = vec![
+        target_dir.to_path_buf(),
+        sources.game_root.join(TRASH_DIR_NAME),
+    ];
+    stay_behind.extend(option.iter().map(|dir| dir.join(TRASH_DIR_NAME)));
+    let mut app_exclude: Vec<PathBuf> = amfs.iter().chain(appdata.iter()).chain(option.iter()).cloned().collect();
+    app_exclude.extend(stay_behind.iter().cloned());
+
+    let copy = |source: &PathBuf, dest: &str, exclude: &[PathBuf]| MigrationCopy {
+        source: source.clone(),
+        dest: dest.to_string(),
+        bytes: tree_size(source, exclude),
+    };
+    let layout = [
+        (MountRole::App, vec![copy(&sources.game_root, "", &app_exclude)], app_exclude.clone()),
+        (
+            MountRole::AppData,
+            amfs.iter()
+                .map(|dir| copy(dir, "amfs", &stay_behind))
+                .chain(appdata.iter().map(|dir| copy(dir, "appdata", &stay_behind)))
+                .collect(),
+            stay_behind.clone(),
+        ),
+        (
+            MountRole::Option,
+            option.iter().map(|dir| copy(dir, "", &stay_behind)).collect(),
+            stay_behind.clone(),
+        ),
+    ];
+
+    let mut volumes = Vec::new();
+    for (role, copies, exclude) in layout {
+        let image = target_dir.join(format!("{}.{}", role.folder_name(), extension));
+        if image.exists() {
+            return Err(format!("{} already exists", image.to_string_lossy()));
+        }
+        let content_bytes = copies.iter().map(|copy| copy.bytes).sum();
+        volumes.push(MigrationVolume {
+            role: role.folder_name(),
+            image,
+            size_mb: image_size_mb(content_bytes),
+            content_bytes,
+            copies,
+            exclude,
+        });
+    }
+    let required_bytes = volumes.iter().map(|volume| volume.content_bytes).sum::<u64>() + 3 * SLACK_MB * MB;
+    let probe = target_dir
+        .ancestors()
+        .find(|dir| dir.is_dir())
+        .unwrap_or(target_dir);
+    Ok(MigrationPlan {
+        target_dir: target_dir.to_path_buf(),
+        volumes,
+        required_bytes,
+        free_bytes: free_space_bytes(probe),
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn run_diskpart(script: &str) -> Result<String, String> {
+    let mut file = tempfile::Builder::new()
+        .prefix("configarc-diskpart-")
+        .suffix(".txt")
+        .tempfile()
+        .map_err(|e| e.to_string())?;
+    std::io::Write::write_all(&mut file, script.as_bytes()).map_err(|e| e.to_string())?;
+    let output = Command::new("diskpart")
+        .arg("/s")
+        .arg(file.path())
+        .creation_flags(super::CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| format!("Failed to run diskpart: {}", e))?;
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if output.status.success() {
+        Ok(stdout)
+    } else {
+        Err(format!("diskpart failed: {}", stdout.lines().last().unwrap_or("unknown error")))
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn run_diskpart(_script: &str) -> Result<String, String> {
+    Err("Creating VHDs is only supported on Windows".to_string())
+}
+
+/// Creates an expandable image, formats it as NTFS and attaches it to `mount_dir`.
+fn create_attached_image(image: &Path, size_mb: u64, label: &str, mount_dir: &Path) -> Result<(), String> {
+    run_diskpart(&format!(
+        "create vdisk file=\"{image}\" maximum={size_mb} type=expandable\r\n\
+         select vdisk file=\"{image}\"\r\n\
+         attach vdisk\r\n\
+         create partition primary\r\n\
+         format fs=ntfs label=\"{label}\" quick\r\n\
+         assign mount=\"{mount}\"\r\n",
+        image = image.to_string_lossy(),
+        mount = mount_dir.to_string_lossy(),
+    ))
+    .map(|_| ())
+}
+
+fn detach_image(image: &Path) -> Result<(), String> {
+    run_diskpart(&format!(
+        "select vdisk file=\"{}\"\r\ndetach vdisk\r\n",
+        image.to_string_lossy()
+    ))
+    .map(|_| ())
+}
+
+/// Copies the content of `src` into `dest`, skipping `exclude`. `on_file` gets the size and
+/// relative path of every copied file. Stops between files once `cancel` is set.
+fn copy_tree(
+    src: &Path,
+    dest: &Path,
+    exclude: &[PathBuf],
+    cancel: &AtomicBool,
+    on_file: &mut dyn FnMut(u64, &Path),
+) -> Result<(), String> {
+    let mut pending = vec![src.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let relative = dir.strip_prefix(src).map_err(|e| e.to_string())?;
+        let target_dir = dest.join(relative);
+        fs::create_dir_all(&target_dir)
+            .map_err(|e| format!("Failed to create {}: {}", target_dir.to_string_lossy(), e))?;
+        let entries = fs::read_dir(&dir).map_err(|e| format!("Failed to read {}: {}", dir.to_string_lossy(), e))?;
+        for entry in entries {
+            let entry = entry.map_err(|e| e.to_string())?;
+            let path = entry.path();
+            if exclude.iter().any(|skip| skip == &path) {
+                continue;
+            }
+            if cancel.load(Ordering::SeqCst) {
+                return Err(CANCELLED_MESSAGE.to_string());
+            }
+            let file_type = entry.file_type().map_err(|e| e.to_string())?;
+            if file_type.is_dir() {
+                pending.push(path);
+            } else if file_type.is_file() {
+                let bytes = fs::copy(&path, target_dir.join(entry.file_name()))
+                    .map_err(|e| format!("Failed to copy {}: {}", path.to_string_lossy(), e))?;
+                on_file(bytes, path.strip_prefix(src).unwrap_or(&path));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn fill_volume(
+    volume: &MigrationVolume,
+    mount_dir: &Path,
+    cancel: &AtomicBool,
+    on_progress: &mut dyn FnMut(&MigrationProgress),
+) -> Result<(), String> {
+    let mut copied = 0u64;
+    for copy in &volume.copies {
+        let dest = if copy.dest.is_empty() {
+            mount_dir.to_path_buf()
+        } else {
+            mount_dir.join(&copy.dest)
+        };
+        copy_tree(&copy.source, &dest, &volume.exclude, cancel, &mut |bytes, current| {
+            copied += bytes;
+            on_progress(&MigrationProgress {
+                role: volume.role,
+                stage: "copying",
+                copied_bytes: copied,
+                total_bytes: volume.content_bytes,
+                current: Some(current.to_string_lossy().into_owned()),
+            });
+        })?;
+    }
+    Ok(())
+}
+
+/// Creates every image of `plan` and copies the install into it. Sources are only read;
+/// on failure or once `cancel` is set the images created so far are deleted again.
+pub fn migrate_to_vhd(
+    plan: &MigrationPlan,
+    cancel: &AtomicBool,
+    on_progress: &mut dyn FnMut(&MigrationProgress),
+) -> Result<(), String> {
+    if !is_running_as_admin() {
+        return Err("Creating VHDs needs the launcher to run as administrator".to_string());
+    }
+    if let Some(free) = plan.free_bytes.filter(|free| *free < plan.required_bytes) {
+        return Err(format!(
+            "Not enough free space in {} ({} MB free, {} MB required)",
+            plan.target_dir.to_string_lossy(),
+            free / MB,
+            plan.required_bytes / MB
+        ));
+    }
+    fs::create_dir_all(&plan.target_dir).map_err(|e| e.to_string())?;
+    let staging = plan.target_dir.join(STAGING_DIR_NAME);
+
+    let mut created: Vec<&Path> = Vec::new();
+    let result = (|| -> Result<(), String> {
+        for volume in &plan.volumes {
+            if cancel.load(Ordering::SeqCst) {
+                return Err(CANCELLED_MESSAGE.to_string());
+            }
+            let mount_dir = staging.join(volume.role);
+            fs::create_dir_all(&mount_dir).map_err(|e| e.to_string())?;
+            let progress = |stage, copied_bytes| MigrationProgress {
+                role: volume.role,
+                stage,
+                copied_bytes,
+                total_bytes: volume.content_bytes,
+                current: None,
+            };
+            on_progress(&progress("creating", 0));
+            created.push(&volume.image);
+            create_attached_image(&volume.image, volume.size_mb, &volume.role.to_ascii_uppercase(), &mount_dir)?;
+            let filled = fill_volume(volume, &mount_dir, cancel, on_progress);
+            on_progress(&progress("detaching", volume.content_bytes));
+            let detached = detach_image(&volume.image);
+            filled?;
+            detached?;
+        }
+        Ok(())
+    })();
+
+    let _ = fs::remove_dir_all(&staging);
+    if result.is_err() {
+        for image in created {
+            let _ = detach_image(image);
+            let _ = fs::remove_file(image);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn plans_one_volume_per_role_without_copying_anything_twice() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join("Package");
+        let appdata = root.join("appdata");
+        let option = dir.path().join("option");
+        fs::create_dir_all(appdata.join("SDEZ")).unwrap();
+        fs::create_dir_all(option.join("A001")).unwrap();
+        fs::create_dir_all(root.join(TRASH_DIR_NAME)).unwrap();
+        fs::write(root.join("Sinmai.exe"), vec![0u8; 100]).unwrap();
+        fs::write(root.join(TRASH_DIR_NAME).join("old.dll"), vec![0u8; 50]).unwrap();
+        fs::write(appdata.join("SDEZ").join("save.bin"), vec![0u8; 40]).unwrap();
+        fs::write(option.join("A001").join("data.bin"), vec![0u8; 10]).unwrap();
+
+        let sources = MigrationSources {
+            game_root: root.clone(),
+            amfs: Some(dir.path().join("amfs")),
+            appdata: Some(appdata.clone()),
+            option: Some(option.clone()),
+        };
+        let target = dir.path().join("images");
+        let plan = plan_migration(&sources, &target, VirtualDiskFormat::Vhdx).unwrap();
+
+        let sizes: Vec<(&str, u64, usize)> = plan
+            .volumes
+            .iter()
+            .map(|v| (v.role, v.content_bytes, v.copies.len()))
+            .collect();
+        assert_eq!(sizes, [("app", 100, 1), ("appdata", 40, 1), ("option", 10, 1)]);
+        assert_eq!(plan.volumes[1].copies[0].dest, "appdata");
+        assert_eq!(plan.vhd_config().app_base_path, target.join("app.vhdx").to_string_lossy());
+        assert_eq!(plan.original_paths(), [root, option]);
+
+        let staged = dir.path().join("staged");
+        let app = &plan.volumes[0];
+        let cancelled = copy_tree(&app.copies[0].source, &staged, &app.exclude, &AtomicBool::new(true), &mut |_, _| {});
+        assert_eq!(cancelled.unwrap_err(), CANCELLED_MESSAGE);
+        copy_tree(&app.copies[0].source, &staged, &app.exclude, &AtomicBool::new(false), &mut |_, _| {}).unwrap();
+        assert!(staged.join("Sinmai.exe").is_file());
+        assert!(!staged.join("appdata").exists());
+        assert!(!staged.join(TRASH_DIR_NAME).exists());
+    }
+
+    #[test]
+    fn images_get_a_quarter_and_a_gigabyte_on_top() {
+        assert_eq!(image_size_mb(0), 1024);
+        assert_eq!(image_size_mb(10 * 1024 * MB), 14 * 1024);
+    }
+}
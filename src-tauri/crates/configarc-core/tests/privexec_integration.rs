@@ -1,22 +1,25 @@
 use base64::{engine::general_purpose::STANDARD as B64, Engine as _};
 use chrono::{Duration, Utc};
 use configarc_core::privexec::{
-    AuditLogEntry, CommandRequestPayload, CommandRunner, ParamRule, PolicyCommand,
-    PolicyDefaultAction, PolicySecurity, PolicyUpdatePayload, PrivExecConfig, PrivExecCore,
-    PrivExecPolicy, RunnerOutput, SignatureEnvelope, SignedCommandRequest,
-    SignedPolicyUpdateRequest,
+    AuditLogEntry, BatchCommand, BatchRequestPayload, CommandRequestPayload, CommandRunner,
+    ParamRule, PolicyCommand, PolicyDefaultAction, PolicySecurity, PolicyUpdatePayload,
+    PrivExecConfig, PrivExecCore, PrivExecPolicy, RunnerError, RunnerOutput, SignatureEnvelope,
+    SignedBatchRequest, SignedCommandRequest, SignedPolicyUpdateRequest,
 };
 use ed25519_dalek::{Signer, SigningKey};
 use serde_json::{Map, Value};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use tempfile::TempDir;
 
 #[derive(Default)]
 struct MockRunner {
     scripts: Mutex<Vec<String>>,
+    timeouts: Mutex<Vec<std::time::Duration>>,
+    /// Scripts containing this never finish and hit their timeout.
+    hang_on: Mutex<Option<String>>,
 }
 
 impl MockRunner {
@@ -40,6 +43,9 @@ impl CommandRunner for MockRunner {
             r#"{"Name":"TermService","Status":"Running"}"#.to_string()
         } else if script.contains("Get-BitLockerVolume") {
             r#"{"MountPoint":"X:","LockStatus":"Unlocked","ProtectionStatus":"On"}"#.to_string()
+        } else if script.contains("Get-Module -ListAvailable") {
+            r#"{"elevated":true,"storage":true,"bitlocker":false,"hyperV":false,"edition":"Microsoft Windows 11 Home"}"#
+                .to_string()
         } else if script.contains("Get-Disk") {
             r#"[{"Number":1,"FriendlyName":"MockDisk"}]"#.to_string()
         } else {
@@ -51,6 +57,27 @@ impl CommandRunner for MockRunner {
             stderr: String::new(),
         })
     }
+
+    fn run_powershell_with_timeout(
+        &self,
+        script: &str,
+        env: &HashMap<String, String>,
+        timeout: std::time::Duration,
+    ) -> Result<RunnerOutput, RunnerError> {
+        self.timeouts.lock().unwrap().push(timeout);
+        let hangs = self
+            .hang_on
+            .lock()
+            .unwrap()
+            .as_deref()
+            .is_some_and(|needle| script.contains(needle));
+        if hangs {
+            self.scripts.lock().unwrap().push(script.to_string());
+            return Err(RunnerError::TimedOut);
+        }
+        self.run_powershell_with_env(script, env)
+            .map_err(RunnerError::Failed)
+    }
 }
 
 struct TestContext {
@@ -77,6 +104,7 @@ fn setup(fail_policy_swap: bool) -> TestContext {
         .bootstrap_public_keys
         .insert("k1".to_string(), pubkey.clone());
     config.policy_replace_fail_after_backup = fail_policy_swap;
+    config.hosts_path = tmp.path().join("hosts");
 
     let runner = Arc::new(MockRunner::default());
     let core = PrivExecCore::with_runner(config, runner.clone()).unwrap();
@@ -100,8 +128,8 @@ fn setup(fail_policy_swap: bool) -> TestContext {
 fn build_policy(
     version: u64,
     pubkey: &str,
-    vhd_root: &PathBuf,
-    log_root: &PathBuf,
+    vhd_root: &Path,
+    log_root: &Path,
 ) -> PrivExecPolicy {
     let mut keys = HashMap::new();
     keys.insert("k1".to_string(), pubkey.to_string());
@@ -295,6 +323,19 @@ fn build_policy(
         },
     );
 
+    let mut hosts_params = HashMap::new();
+    for (name, required) in [("entries", true), ("gameId", false)] {
+        hosts_params.insert(
+            name.to_string(),
+            ParamRule::String {
+                required,
+                default: None,
+                allow_values: vec![],
+                fixed_value: None,
+            },
+        );
+    }
+
     PrivExecPolicy {
         schema_version: 1,
         policy_name: "test-policy".to_string(),
@@ -317,6 +358,7 @@ fn build_policy(
                 requires_session: false,
                 risk_level: Some("low".to_string()),
                 params: HashMap::new(),
+                timeout_seconds: None,
             },
             PolicyCommand {
                 name: "heartbeat".to_string(),
@@ -324,6 +366,7 @@ fn build_policy(
                 requires_session: false,
                 risk_level: Some("low".to_string()),
                 params: session_params.clone(),
+                timeout_seconds: None,
             },
             PolicyCommand {
                 name: "end_session".to_string(),
@@ -331,6 +374,7 @@ fn build_policy(
                 requires_session: false,
                 risk_level: Some("low".to_string()),
                 params: session_params,
+                timeout_seconds: None,
             },
             PolicyCommand {
                 name: "mount_vhd".to_string(),
@@ -338,6 +382,7 @@ fn build_policy(
                 requires_session: true,
                 risk_level: Some("medium".to_string()),
                 params: mount_params,
+                timeout_seconds: None,
             },
             PolicyCommand {
                 name: "unmount_vhd".to_string(),
@@ -345,6 +390,7 @@ fn build_policy(
                 requires_session: true,
                 risk_level: Some("medium".to_string()),
                 params: unmount_params,
+                timeout_seconds: None,
             },
             PolicyCommand {
                 name: "query_disk".to_string(),
@@ -352,6 +398,15 @@ fn build_policy(
                 requires_session: false,
                 risk_level: Some("low".to_string()),
                 params: HashMap::new(),
+                timeout_seconds: None,
+            },
+            PolicyCommand {
+                name: "probe_capabilities".to_string(),
+                enabled: true,
+                requires_session: false,
+                risk_level: Some("low".to_string()),
+                params: HashMap::new(),
+                timeout_seconds: Some(30),
             },
             PolicyCommand {
                 name: "query_bitlocker_status".to_string(),
@@ -359,6 +414,7 @@ fn build_policy(
                 requires_session: false,
                 risk_level: Some("low".to_string()),
                 params: bitlocker_query_params,
+                timeout_seconds: None,
             },
             PolicyCommand {
                 name: "unlock_bitlocker".to_string(),
@@ -366,6 +422,7 @@ fn build_policy(
                 requires_session: true,
                 risk_level: Some("high".to_string()),
                 params: bitlocker_unlock_params,
+                timeout_seconds: None,
             },
             PolicyCommand {
                 name: "lock_bitlocker".to_string(),
@@ -373,6 +430,7 @@ fn build_policy(
                 requires_session: true,
                 risk_level: Some("high".to_string()),
                 params: bitlocker_lock_params,
+                timeout_seconds: None,
             },
             PolicyCommand {
                 name: "query_service_status".to_string(),
@@ -380,6 +438,15 @@ fn build_policy(
                 requires_session: false,
                 risk_level: Some("low".to_string()),
                 params: service_params,
+                timeout_seconds: Some(15),
+            },
+            PolicyCommand {
+                name: "update_hosts_entries".to_string(),
+                enabled: true,
+                requires_session: false,
+                risk_level: Some("high".to_string()),
+                params: hosts_params,
+                timeout_seconds: None,
             },
             PolicyCommand {
                 name: "restart_service".to_string(),
@@ -387,6 +454,7 @@ fn build_policy(
                 requires_session: false,
                 risk_level: Some("high".to_string()),
                 params: HashMap::new(),
+                timeout_seconds: None,
             },
             PolicyCommand {
                 name: "collect_log".to_string(),
@@ -394,6 +462,7 @@ fn build_policy(
                 requires_session: false,
                 risk_level: Some("low".to_string()),
                 params: collect_params,
+                timeout_seconds: None,
             },
         ],
     }
@@ -500,6 +569,9 @@ fn nonce_replay_is_rejected() {
     assert!(first.ok);
     assert!(!second.ok);
     assert_eq!(second.code, "NONCE_REPLAY");
+    let disks = &first.result.as_ref().unwrap()["disks"];
+    assert_eq!(disks[0]["number"], 1);
+    assert_eq!(disks[0]["friendlyName"], "MockDisk");
 }
 
 #[test]
@@ -712,6 +784,10 @@ fn unlock_and_lock_bitlocker_with_session() {
         .execute_request(sign_request(unlock_payload, &ctx.signing_key));
     assert!(unlock_response.ok);
     assert!(ctx.runner.script_contains("Unlock-BitLocker"));
+    let status = unlock_response.result.as_ref().unwrap();
+    assert_eq!(status["schemaVersion"], 1);
+    assert_eq!(status["mountPoint"], "X:");
+    assert_eq!(status["lockStatus"], "Unlocked");
     assert!(!ctx.runner.script_contains(recovery_password));
 
     let mut lock_payload = base_payload("cmd-7j", "nonce-7j", "lock_bitlocker", "device-1");
@@ -731,6 +807,21 @@ fn unlock_and_lock_bitlocker_with_session() {
     assert!(ctx.runner.script_contains("Lock-BitLocker"));
 }
 
+#[test]
+fn probe_capabilities_reports_missing_bitlocker() {
+    let ctx = setup(false);
+    let response = ctx.core.execute_request(sign_request(
+        base_payload("cmd-caps", "nonce-caps", "probe_capabilities", "device-1"),
+        &ctx.signing_key,
+    ));
+    assert!(response.ok, "{}", response.code);
+    let caps = response.result.as_ref().unwrap();
+    assert_eq!(caps["storage"]["supported"], true);
+    assert_eq!(caps["bitlocker"]["supported"], false);
+    assert!(caps["bitlocker"]["reason"].is_string());
+    assert_eq!(caps["osEdition"], "Microsoft Windows 11 Home");
+}
+
 #[test]
 fn command_id_is_idempotent() {
     let ctx = setup(false);
@@ -782,3 +873,148 @@ fn policy_hot_update_failure_rolls_back() {
         serde_json::from_slice(&fs::read(ctx.core.policy_path()).unwrap()).unwrap();
     assert_eq!(after.version, 1);
 }
+
+#[test]
+fn hung_command_times_out() {
+    let ctx = setup(false);
+    *ctx.runner.hang_on.lock().unwrap() = Some("Get-Service".to_string());
+
+    let mut payload = base_payload("cmd-timeout", "nonce-timeout", "query_service_status", "device-1");
+    payload.params.insert(
+        "serviceName".to_string(),
+        Value::String("TermService".to_string()),
+    );
+    let response = ctx
+        .core
+        .execute_request(sign_request(payload, &ctx.signing_key));
+
+    assert!(!response.ok);
+    assert_eq!(response.code, "COMMAND_TIMEOUT");
+    assert!(ctx.runner.script_contains("Get-Service"));
+    assert_eq!(
+        ctx.runner.timeouts.lock().unwrap().last(),
+        Some(&std::time::Duration::from_secs(15))
+    );
+}
+
+fn sign_batch(payload: BatchRequestPayload, signing_key: &SigningKey) -> SignedBatchRequest {
+    let signature = signing_key.sign(&payload.signing_bytes().unwrap());
+    SignedBatchRequest {
+        payload,
+        signature: SignatureEnvelope {
+            algorithm: "ed25519".to_string(),
+            key_id: "k1".to_string(),
+            signature: B64.encode(signature.to_bytes()),
+        },
+    }
+}
+
+fn batch_payload(batch_id: &str, nonce: &str, commands: &[(&str, &str)]) -> BatchRequestPayload {
+    BatchRequestPayload {
+        schema_version: 1,
+        batch_id: batch_id.to_string(),
+        nonce: nonce.to_string(),
+        issued_at: Utc::now() - Duration::seconds(5),
+        expires_at: Utc::now() + Duration::seconds(60),
+        device_id: "device-1".to_string(),
+        commands: commands
+            .iter()
+            .map(|(command_id, command)| BatchCommand {
+                command_id: command_id.to_string(),
+                command: command.to_string(),
+                params: Map::new(),
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn batch_stops_at_first_failure_and_replays_completed_steps() {
+    let ctx = setup(false);
+    let mut payload = batch_payload(
+        "batch-1",
+        "nonce-batch-1",
+        &[
+            ("b1-disk", "query_disk"),
+            ("b1-service", "query_service_status"),
+            ("b1-restart", "restart_service"),
+            ("b1-never", "query_disk"),
+        ],
+    );
+    payload.commands[1].params.insert(
+        "serviceName".to_string(),
+        Value::String("TermService".to_string()),
+    );
+    let request = sign_batch(payload, &ctx.signing_key);
+    let raw = serde_json::to_string(&request).unwrap();
+    assert!(configarc_core::privexec::is_batch_request_json(&raw));
+
+    let first = ctx.core.execute_batch_request_json(&raw);
+    assert!(!first.ok);
+    assert_eq!(first.code, "COMMAND_DISABLED");
+    let codes: Vec<&str> = first.results.iter().map(|r| r.code.as_str()).collect();
+    assert_eq!(codes, ["OK", "OK", "COMMAND_DISABLED"]);
+    assert_eq!(ctx.runner.script_count(), 2);
+
+    let replay = ctx.core.execute_batch_request(request);
+    assert_eq!(replay.results.len(), 3);
+    assert!(replay.results.iter().all(|r| r.idempotent_replay));
+    assert_eq!(ctx.runner.script_count(), 2);
+
+    let reused_nonce = sign_batch(
+        batch_payload("batch-2", "nonce-batch-1", &[("b2-disk", "query_disk")]),
+        &ctx.signing_key,
+    );
+    let rejected = ctx.core.execute_batch_request(reused_nonce);
+    assert_eq!(rejected.code, "NONCE_REPLAY");
+    assert!(rejected.results.is_empty());
+
+    let mut tampered = sign_batch(
+        batch_payload("batch-3", "nonce-batch-3", &[("b3-disk", "query_disk")]),
+        &ctx.signing_key,
+    );
+    tampered.payload.commands[0].command = "collect_log".to_string();
+    assert_eq!(
+        ctx.core.execute_batch_request(tampered).code,
+        "INVALID_SIGNATURE"
+    );
+    assert_eq!(ctx.runner.script_count(), 2);
+}
+
+#[test]
+fn hosts_entries_are_written_to_the_managed_block() {
+    let ctx = setup(false);
+    let hosts_path = ctx.vhd_root.parent().unwrap().join("hosts");
+    fs::write(&hosts_path, "127.0.0.1 localhost\n").unwrap();
+
+    let mut payload = base_payload("cmd-hosts", "nonce-hosts", "update_hosts_entries", "device-1");
+    payload.params.insert(
+        "entries".to_string(),
+        Value::String(r#"[{"address":"192.168.1.2","host":"naominet.jp"}]"#.to_string()),
+    );
+    payload
+        .params
+        .insert("gameId".to_string(), Value::String("SDEZ".to_string()));
+    let response = ctx
+        .core
+        .execute_request(sign_request(payload, &ctx.signing_key));
+    assert!(response.ok, "{}", response.code);
+    let entries = &response.result.as_ref().unwrap()["entries"];
+    assert_eq!(entries[0]["host"], "naominet.jp");
+    assert_eq!(entries[0]["gameId"], "SDEZ");
+    let content = fs::read_to_string(&hosts_path).unwrap();
+    assert!(content.starts_with("127.0.0.1 localhost\n"));
+    assert!(content.contains("192.168.1.2 naominet.jp # game:SDEZ"));
+    assert!(ctx.runner.script_contains("Clear-DnsClientCache"));
+
+    let mut bad = base_payload("cmd-hosts-bad", "nonce-hosts-bad", "update_hosts_entries", "device-1");
+    bad.params.insert(
+        "entries".to_string(),
+        Value::String(r#"[{"address":"192.168.1.2","host":"naominet.jp evil.example"}]"#.to_string()),
+    );
+    let response = ctx
+        .core
+        .execute_request(sign_request(bad, &ctx.signing_key));
+    assert_eq!(response.code, "INVALID_PARAMETER");
+    assert_eq!(fs::read_to_string(&hosts_path).unwrap(), content);
+}
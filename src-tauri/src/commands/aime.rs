@@ -0,0 +1,332 @@
+use super::config::{gen_profile_id, load_active_seg_config};
+use super::games::resolve_with_base;
+use crate::config::aime::{generate_access_code, validate_access_code};
+use crate::error::{ApiError, ApiResult, ErrorCode};
+use serde::{Deserialize, Serialize};
+use tauri::{command, AppHandle, Manager};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const AIME_STORE_FILE_NAME: &str = "configarc_aime.json";
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AimeEntry {
+    pub id: String,
+    pub name: String,
+    pub number: String,
+}
+
+fn aime_store_path(app: &AppHandle) -> ApiResult<PathBuf> {
+    let root = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| ApiError::from(e.to_string()))?;
+    fs::create_dir_all(&root).map_err(|e| ApiError::from(e.to_string()))?;
+    Ok(root.join(AIME_STORE_FILE_NAME))
+}
+
+fn read_aime_file(path: &Path) -> ApiResult<Vec<AimeEntry>> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let data = fs::read_to_string(path).map_err(|e| ApiError::from(e.to_string()))?;
+    if data.trim().is_empty() {
+        return Ok(vec![]);
+    }
+    serde_json::from_str(&data).map_err(|e| ApiError::from(e.to_string()))
+}
+
+pub(super) fn load_aimes(app: &AppHandle) -> ApiResult<Vec<AimeEntry>> {
+    let path = aime_store_path(app)?;
+    if !path.exists() {
+        // First run after the store moved out of the working directory.
+        migrate_legacy_aimes(app, &[])?;
+    }
+    read_aime_file(&path)
+}
+
+fn save_aimes(app: &AppHandle, entries: &[AimeEntry]) -> ApiResult<()> {
+    let path = aime_store_path(app)?;
+    let json = serde_json::to_string_pretty(entries).map_err(|e| ApiError::from(e.to_string()))?;
+    fs::write(path, json).map_err(|e| ApiError::from(e.to_string()))
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AimeMigrationSource {
+    pub path: String,
+    pub imported: usize,
+    pub duplicates: usize,
+    pub invalid: usize,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AimeMigrationReport {
+    pub store_path: String,
+    pub scanned: Vec<String>,
+    pub sources: Vec<AimeMigrationSource>,
+    pub imported: Vec<AimeEntry>,
+    pub total: usize,
+}
+
+/// Folders older releases kept `configarc_aime.json` in: the working directory, the install
+/// folder and its sibling install folders from previous versions.
+fn legacy_aime_locations() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    if let Ok(cwd) = std::env::current_dir() {
+        dirs.push(cwd);
+    }
+    if let Some(exe_dir) = std::env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf)) {
+        if let Some(parent) = exe_dir.parent() {
+            if let Ok(entries) = fs::read_dir(parent) {
+                for entry in entries.flatten() {
+                    let name = entry.file_name().to_string_lossy().to_lowercase();
+                    if entry.path().is_dir() && name.contains("configarc") {
+                        dirs.push(entry.path());
+                    }
+                }
+            }
+        }
+        dirs.push(exe_dir);
+    }
+    for var in ["LOCALAPPDATA", "ProgramFiles", "ProgramFiles(x86)"] {
+        if let Ok(base) = std::env::var(var) {
+            let base = PathBuf::from(base);
+            dirs.push(base.join("ConfigArcLauncher"));
+            dirs.push(base.join("Programs").join("ConfigArcLauncher"));
+        }
+    }
+    let mut files: Vec<PathBuf> = Vec::new();
+    for dir in dirs {
+        let file = dir.join(AIME_STORE_FILE_NAME);
+        if !files.iter().any(|f| f == &file) {
+            files.push(file);
+        }
+    }
+    files
+}
+
+/// Adds `incoming` cards not already in `entries`, matching by card number.
+/// Returns `(imported, duplicates, invalid)`.
+fn merge_aime_entries(entries: &mut Vec<AimeEntry>, incoming: Vec<AimeEntry>) -> (Vec<AimeEntry>, usize, usize) {
+    let mut imported = Vec::new();
+    let mut duplicates = 0;
+    let mut invalid = 0;
+    for entry in incoming {
+        let Ok(number) = normalize_aime_number(&entry.number) else {
+            invalid += 1;
+            continue;
+        };
+        if entries.iter().any(|e| e.number == number) {
+            duplicates += 1;
+            continue;
+        }
+        let mut id = entry.id.trim().to_string();
+        if id.is_empty() || entries.iter().any(|e| e.id == id) {
+            id = format!("{}-{}", gen_profile_id("aime"), entries.len());
+        }
+        let name = match entry.name.trim() {
+            "" => number.clone(),
+            name => name.to_string(),
+        };
+        let merged = AimeEntry { id, name, number };
+        entries.push(merged.clone());
+        imported.push(merged);
+    }
+    (imported, duplicates, invalid)
+}
+
+fn migrate_legacy_aimes(app: &AppHandle, extra_paths: &[PathBuf]) -> ApiResult<AimeMigrationReport> {
+    let store = aime_store_path(app)?;
+    let mut entries = read_aime_file(&store)?;
+    let mut report = AimeMigrationReport {
+        store_path: store.to_string_lossy().to_string(),
+        scanned: Vec::new(),
+        sources: Vec::new(),
+        imported: Vec::new(),
+        total: 0,
+    };
+    let mut candidates = legacy_aime_locations();
+    candidates.extend(extra_paths.iter().map(|p| if p.is_dir() { p.join(AIME_STORE_FILE_NAME) } else { p.clone() }));
+    for path in candidates {
+        if path == store || report.scanned.iter().any(|s| Path::new(s) == path) {
+            continue;
+        }
+        report.scanned.push(path.to_string_lossy().to_string());
+        if !path.is_file() {
+            continue;
+        }
+        let mut source = AimeMigrationSource {
+            path: path.to_string_lossy().to_string(),
+            imported: 0,
+            duplicates: 0,
+            invalid: 0,
+            error: None,
+        };
+        match read_aime_file(&path) {
+            Ok(found) => {
+                let (imported, duplicates, invalid) = merge_aime_entries(&mut entries, found);
+                source.imported = imported.len();
+                source.duplicates = duplicates;
+                source.invalid = invalid;
+                report.imported.extend(imported);
+            }
+            Err(err) => source.error = Some(err.message),
+        }
+        report.sources.push(source);
+    }
+    if !report.imported.is_empty() || !store.exists() {
+        save_aimes(app, &entries)?;
+    }
+    report.total = entries.len();
+    Ok(report)
+}
+
+fn normalize_aime_number(raw: &str) -> ApiResult<String> {
+    validate_access_code(raw).map_err(|e| ApiError::new(ErrorCode::InvalidInput, e))
+}
+
+#[command]
+pub fn list_aimes_cmd(app: AppHandle) -> ApiResult<Vec<AimeEntry>> {
+    load_aimes(&app)
+}
+
+/// Imports card lists left behind by older installs, skipping cards already in the store.
+#[command]
+pub fn migrate_legacy_aimes_cmd(app: AppHandle, paths: Option<Vec<String>>) -> ApiResult<AimeMigrationReport> {
+    let extra: Vec<PathBuf> = paths.unwrap_or_default().into_iter().map(PathBuf::from).collect();
+    migrate_legacy_aimes(&app, &extra)
+}
+
+/// A new access code with a valid prefix and check digit that no saved card uses yet.
+#[command]
+pub fn generate_aime_cmd(app: AppHandle) -> ApiResult<String> {
+    let entries = load_aimes(&app)?;
+    loop {
+        let number = generate_access_code().map_err(ApiError::from)?;
+        if !entries.iter().any(|e| e.number == number) {
+            return Ok(number);
+        }
+    }
+}
+
+#[command]
+pub fn save_aime_cmd(app: AppHandle, name: String, number: String) -> ApiResult<AimeEntry> {
+    let trimmed_name = name.trim().to_string();
+    if trimmed_name.is_empty() {
+        return Err(("Name is required".to_string()).into());
+    }
+    let cleaned_number = normalize_aime_number(&number)?;
+    let mut entries = load_aimes(&app)?;
+    let ts = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+    let entry = AimeEntry {
+        id: format!("aime-{}", ts),
+        name: trimmed_name,
+        number: cleaned_number,
+    };
+    entries.push(entry.clone());
+    save_aimes(&app, &entries)?;
+    Ok(entry)
+}
+
+#[command]
+pub fn update_aime_cmd(app: AppHandle, id: String, name: String, number: String) -> ApiResult<AimeEntry> {
+    let trimmed_name = name.trim().to_string();
+    if trimmed_name.is_empty() {
+        return Err(("Name is required".to_string()).into());
+    }
+    let cleaned_number = normalize_aime_number(&number)?;
+    let mut entries = load_aimes(&app)?;
+    
+    if let Some(entry) = entries.iter_mut().find(|e| e.id == id) {
+        entry.name = trimmed_name;
+        entry.number = cleaned_number;
+        let result = entry.clone();
+        save_aimes(&app, &entries)?;
+        Ok(result)
+    } else {
+        Err("Aime not found".to_string().into())
+    }
+}
+
+#[command]
+pub fn delete_aime_cmd(app: AppHandle, id: String) -> ApiResult<()> {
+    let mut entries = load_aimes(&app)?;
+    let before = entries.len();
+    entries.retain(|e| e.id != id);
+    if entries.len() == before {
+        return Err(("Aime not found".to_string()).into());
+    }
+    save_aimes(&app, &entries)
+}
+
+#[command]
+pub fn apply_aime_to_active_cmd(app: AppHandle, id: String) -> ApiResult<()> {
+    let entries = load_aimes(&app)?;
+    let entry = entries
+        .into_iter()
+        .find(|e| e.id == id)
+        .ok_or_else(|| "Aime not found".to_string())?;
+    let (cfg, base) = load_active_seg_config()?;
+    let raw_path = cfg.aime.aime_path.trim();
+    if raw_path.is_empty() {
+        return Err(("aimePath is empty in segatools.ini".to_string()).into());
+    }
+    let target = resolve_with_base(&base, raw_path);
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| ApiError::from(e.to_string()))?;
+    }
+    fs::write(target, entry.number).map_err(|e| ApiError::from(e.to_string()))
+}
+
+#[command]
+pub fn get_active_aime_cmd() -> ApiResult<Option<String>> {
+    let (cfg, base) = match load_active_seg_config() {
+        Ok(res) => res,
+        Err(err) => return Err(err),
+    };
+    let raw_path = cfg.aime.aime_path.trim();
+    if raw_path.is_empty() {
+        return Ok(None);
+    }
+    let target = resolve_with_base(&base, raw_path);
+    if !target.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(target).map_err(|e| ApiError::from(e.to_string()))?;
+    let trimmed = content.trim();
+    if trimmed.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(trimmed.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_legacy_aimes_by_card_number() {
+        let card = |id: &str, name: &str, number: &str| AimeEntry {
+            id: id.to_string(),
+            name: name.to_string(),
+            number: number.to_string(),
+        };
+        let mut entries = vec![card("aime-1", "Main", "12345678901234567894")];
+        let (imported, duplicates, invalid) = merge_aime_entries(
+            &mut entries,
+            vec![
+                card("aime-9", "Same card", "1234 5678 9012 3456 7894"),
+                card("aime-1", "Second", "99999999999999999999"),
+                card("aime-3", "Broken", "1234"),
+                card("aime-4", "Typo", "12345678901234567895"),
+            ],
+        );
+        assert_eq!((imported.len(), duplicates, invalid), (1, 1, 2));
+        assert_eq!(entries.len(), 2);
+        assert_ne!(entries[1].id, "aime-1");
+        assert_eq!(entries[1].name, "Second");
+    }
+}
@@ -0,0 +1,173 @@
+use super::games::resolve_with_base;
+use super::state::AppState;
+use super::system::notify_app;
+use crate::config::{load_segatoools_config, paths::{segatools_root_for_game_id, segatoools_path_for_game_id}};
+use crate::games::store;
+use crate::error::{ApiError, ApiResult};
+use crate::notifications::{Notification, NotificationSeverity};
+use crate::scrub::{scrub_games, ScrubFinding, ScrubReport};
+use crate::vhd::active_mount_points;
+use crate::backup::{
+    appdata_backup_root_for, create_backup, list_backups, prune_backups, restore_backup, BackupSummary, PruneReport,
+    RestoreReport,
+};
+use tauri::{command, AppHandle, Emitter, Manager, State};
+use std::path::PathBuf;
+use std::time::Duration;
+
+const INTEGRITY_SCRUB_INITIAL_DELAY: Duration = Duration::from_secs(10 * 60);
+const INTEGRITY_SCRUB_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+const INTEGRITY_SCRUB_BUSY_RETRY: Duration = Duration::from_secs(5 * 60);
+
+/// Nothing queued and no game VHD attached.
+fn launcher_is_idle(app: &AppHandle) -> bool {
+    !app.state::<AppState>().launch_queue().status.running && active_mount_points().is_none()
+}
+
+fn run_integrity_scrub(app: &AppHandle, operation_id: Option<String>) -> ApiResult<ScrubReport> {
+    let operation = app
+        .state::<AppState>()
+        .begin_cancellable("integrity_scrub", operation_id, true)?;
+    let game_ids: Vec<String> = store::list_games()
+        .map(|games| games.into_iter().map(|g| g.id).collect())
+        .unwrap_or_default();
+    let report = scrub_games(&game_ids, operation.token());
+    let problems: Vec<ScrubFinding> = report.problems().into_iter().cloned().collect();
+    if !problems.is_empty() {
+        let _ = app.emit("integrity-warning", &problems);
+        notify_app(
+            app,
+            Notification::new(
+                "backup",
+                NotificationSeverity::Warning,
+                "Restore points failed verification",
+                format!("{} restore point(s) have corrupted or missing files", problems.len()),
+            )
+            .with_dedupe_key("integrity-scrub")
+            .with_details(serde_json::json!({ "findings": problems })),
+        );
+    }
+    app.state::<AppState>().store_integrity_report(&report);
+    Ok(report)
+}
+
+/// Periodically re-verifies stored restore points while the launcher is idle. Started once
+/// from `main`; a launch cancels any scrub in progress and the next pass retries later.
+pub fn start_integrity_scrubber(app: AppHandle) {
+    std::thread::spawn(move || {
+        std::thread::sleep(INTEGRITY_SCRUB_INITIAL_DELAY);
+        loop {
+            if !launcher_is_idle(&app) {
+                std::thread::sleep(INTEGRITY_SCRUB_BUSY_RETRY);
+                continue;
+            }
+            // A scrub started by hand is already running when this fails; retry later too.
+            let delay = match run_integrity_scrub(&app, None) {
+                Ok(report) if !report.cancelled => INTEGRITY_SCRUB_INTERVAL,
+                _ => INTEGRITY_SCRUB_BUSY_RETRY,
+            };
+            std::thread::sleep(delay);
+        }
+    });
+}
+
+#[command]
+pub async fn run_integrity_scrub_cmd(app: AppHandle, operation_id: Option<String>) -> ApiResult<ScrubReport> {
+    tauri::async_runtime::spawn_blocking(move || run_integrity_scrub(&app, operation_id))
+        .await
+        .map_err(|e| ApiError::from(e.to_string()))?
+}
+
+#[command]
+pub fn get_integrity_report_cmd(state: State<'_, AppState>) -> ApiResult<Option<ScrubReport>> {
+    Ok(state.integrity_report())
+}
+
+/// The game's APPDATA folder as segatools.ini points at it, resolved against the game root.
+fn appdata_dir_for_game(game_id: &str) -> ApiResult<PathBuf> {
+    let games = store::list_games().map_err(|e| ApiError::from(e.to_string()))?;
+    let game = games
+        .into_iter()
+        .find(|g| g.id == game_id)
+        .ok_or_else(|| ApiError::from(format!("Game not found: {}", game_id)))?;
+    let game_root = store::game_root_dir(&game).ok_or_else(|| ApiError::from("Game path missing".to_string()))?;
+    let seg_path = segatoools_path_for_game_id(game_id).map_err(|e| ApiError::from(e.to_string()))?;
+    if !seg_path.exists() {
+        return Err(("segatools.ini not found. Please configure the game.".to_string()).into());
+    }
+    let cfg = load_segatoools_config(&seg_path).map_err(|e| ApiError::from(e.to_string()))?;
+    if cfg.vfs.appdata.trim().is_empty() {
+        return Err(ApiError::from("APPDATA path is not configured".to_string()));
+    }
+    Ok(resolve_with_base(&game_root, cfg.vfs.appdata.trim()))
+}
+
+fn appdata_backup_repo(game_id: &str) -> PathBuf {
+    appdata_backup_root_for(&segatools_root_for_game_id(game_id))
+}
+
+/// Backs up a game's APPDATA folder in the background and returns the operation id.
+/// Progress is emitted as `appdata-backup-progress` and the report as
+/// `appdata-backup-finished`; a cancelled backup resumes where it stopped the next time this
+/// is called.
+#[command]
+pub fn start_appdata_backup_cmd(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    game_id: String,
+    operation_id: Option<String>,
+) -> ApiResult<String> {
+    let source = appdata_dir_for_game(&game_id)?;
+    let repo = appdata_backup_repo(&game_id);
+    let operation = state.begin_cancellable("appdata_backup", operation_id, true)?;
+    let id = operation.id().to_string();
+    std::thread::spawn(move || {
+        let result = create_backup(&repo, &source, operation.token(), |progress| {
+            let _ = app.emit("appdata-backup-progress", &progress);
+        });
+        drop(operation);
+        match result {
+            Ok(report) => {
+                let _ = app.emit("appdata-backup-finished", &report);
+            }
+            Err(err) => notify_app(
+                &app,
+                Notification::new("backup", NotificationSeverity::Error, "Appdata backup failed", err)
+                    .with_dedupe_key(format!("appdata-backup:{}", game_id)),
+            ),
+        }
+    });
+    Ok(id)
+}
+
+#[command]
+pub fn cancel_appdata_backup_cmd(state: State<'_, AppState>) -> ApiResult<()> {
+    state.cancel_operations("appdata_backup");
+    Ok(())
+}
+
+#[command]
+pub fn list_appdata_backups_cmd(game_id: String) -> ApiResult<Vec<BackupSummary>> {
+    list_backups(&appdata_backup_repo(&game_id)).map_err(ApiError::from)
+}
+
+#[command]
+pub fn restore_appdata_backup_cmd(
+    state: State<'_, AppState>,
+    game_id: String,
+    backup_id: String,
+) -> ApiResult<RestoreReport> {
+    if state.is_running("appdata_backup") {
+        return Err(ApiError::from("Wait for the running appdata backup to finish first"));
+    }
+    let dest = appdata_dir_for_game(&game_id)?;
+    restore_backup(&appdata_backup_repo(&game_id), &backup_id, &dest).map_err(ApiError::from)
+}
+
+#[command]
+pub fn prune_appdata_backups_cmd(game_id: String, keep_last: usize) -> ApiResult<PruneReport> {
+    if keep_last == 0 {
+        return Err(ApiError::from("Keep at least one appdata backup"));
+    }
+    prune_backups(&appdata_backup_repo(&game_id), keep_last).map_err(ApiError::from)
+}
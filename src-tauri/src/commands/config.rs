@@ -0,0 +1,1084 @@
+use super::aime::load_aimes;
+use super::games::{journal_for_active, resolve_with_base, version_defaults_for_game};
+use super::launch::apply_profile_files;
+#[cfg(not(feature = "privexec"))]
+use super::features::clear_hosts_overrides_for_game;
+#[cfg(feature = "privexec")]
+use super::privexec::clear_hosts_overrides_for_game;
+use super::remote::{ensure_feature_allowed, remote_defaults};
+use super::settings::ensure_network_allowed;
+use super::state::AppState;
+use super::system::notify_app;
+use crate::config::{
+    amdaemon::{typed_kind, validate_json_config, AmdaemonConfig, JsonConfigIssue},
+    connectivity::{probe_dns_endpoints, EndpointProbe},
+    default_segatoools_config,
+    diff::{diff_segatoools_configs, ConfigChange},
+    json_configs::{
+        list_json_configs_for_active, load_json_config_for_active, save_json_config_for_active, validate_json_schema,
+        JsonConfigFile, JsonSchemaError,
+    },
+    keybindings::{capture_key, key_conflicts, key_name, key_names, KeyConflict, KeyName},
+    load_segatoools_config, load_segatoools_config_from_string,
+    paths::{
+        active_game_dir, ensure_default_segatoools_exists, get_active_game_id, segatools_root_for_game_id,
+        segatoools_path_for_active, segatoools_path_for_game_id,
+    },
+    profiles::{
+        delete_profile, delete_profile_asset, list_profiles, load_profile, load_profile_notes, profile_assets,
+        save_profile, save_profile_asset, save_profile_notes, ConfigProfile, ProfileAsset,
+    },
+    provenance::{load_provenance, reset_key_to_template, save_provenance, KeyOrigin, KeyProvenance},
+    qr::{apply_qr_backup, decode_qr_backup, encode_qr_backup, QrBackup},
+    render_segatoools_config, retain_sections, save_segatoools_config as persist_segatoools_config,
+    segatools::SegatoolsConfig,
+    share::{open_share, seal_share},
+    slots::{self, create_slot_in, delete_slot_in, list_slots_for_game, segatoools_path_for_slot, ConfigSlot},
+    templates,
+    transaction::FileTransaction,
+};
+use crate::games::{detection::canonical_game_key, journal::{self, JournalEntry}, model::Game, store};
+use crate::error::{ApiError, ApiResult, ErrorCode, ShareError};
+use crate::trusted::{
+    deploy_segatoools_for_active, rollback_segatoools_for_active, verify_segatoools_for_active, DeployResult,
+    RollbackResult, SegatoolsTrustStatus,
+};
+use crate::notifications::{Notification, NotificationSeverity};
+use crate::serial::{enumerate_serial_ports, SerialPort};
+use serde::{Deserialize, Serialize};
+use tauri::{command, AppHandle, State};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub(super) fn redact_keychip_id(content: &str) -> String {
+    let mut result = String::with_capacity(content.len());
+    let mut in_keychip = false;
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') && trimmed.ends_with(']') {
+            in_keychip = trimmed[1..trimmed.len() - 1].eq_ignore_ascii_case("keychip");
+            result.push_str(line);
+            result.push('\n');
+            continue;
+        }
+
+        if in_keychip {
+            let mut body = trimmed;
+            if body.starts_with(';') || body.starts_with('#') {
+                body = body[1..].trim_start();
+            }
+            if let Some(idx) = body.find('=') {
+                let key = body[..idx].trim();
+                if key.eq_ignore_ascii_case("id") {
+                    result.push_str("id=\n");
+                    continue;
+                }
+            }
+        }
+
+        result.push_str(line);
+        result.push('\n');
+    }
+
+    result
+}
+
+#[derive(Deserialize)]
+struct ImportProfilePayload {
+    name: Option<String>,
+    description: Option<String>,
+    segatools: SegatoolsConfig,
+}
+
+pub(super) fn gen_profile_id(prefix: &str) -> String {
+    let ts = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+    format!("{}-{}", prefix, ts)
+}
+
+pub(super) fn ensure_segatoools_present_sections(cfg: &mut SegatoolsConfig, game_name: Option<&str>) {
+    if !cfg.present_sections.is_empty() {
+        return;
+    }
+    let key = canonical_game_key(game_name.unwrap_or(""));
+    let mut sections: Vec<String> = allowed_sections_for_game(&key)
+        .into_iter()
+        .map(|s| s.to_string())
+        .collect();
+    sections.sort();
+    cfg.present_sections = sections;
+}
+
+fn blacklist_sections_for_game(name: &str) -> HashSet<&'static str> {
+    let blacklist: HashSet<&'static str> = ["ds", "eeprom", "gpio", "jvs", "sram"].into_iter().collect();
+
+    match name {
+        // Extendable per-game blacklist
+        _ => {}
+    }
+
+    blacklist
+}
+
+fn template_for_game_key(key: &str) -> Option<&'static str> {
+    match key {
+        "chunithm" => Some(templates::CHUSAN_TEMPLATE),
+        "sinmai" => Some(templates::MAI2_TEMPLATE),
+        "ongeki" => Some(templates::MU3_TEMPLATE),
+        "cardmaker" => Some(templates::CM_TEMPLATE),
+        "wacca" => Some(templates::MERCURY_TEMPLATE),
+        "diva" => Some(templates::DIVA_TEMPLATE),
+        _ => None,
+    }
+}
+
+fn allowed_sections_for_game(name: &str) -> HashSet<&'static str> {
+    let key = canonical_game_key(name);
+    let all_sections: &[&str] = &[
+        "aimeio", "aime", "vfd", "amvideo", "clock", "dns", "ds", "eeprom", "gpio", "gfx", "hwmon",
+        "jvs", "io4", "keychip", "netenv", "pcbid", "sram", "vfs", "epay", "openssl", "system",
+        "led15070", "unity", "mai2io", "chuniio", "mu3io", "button", "touch", "led15093", "led",
+        "io3", "slider", "ir", "cmio", "printer", "mercuryio", "divaio",
+    ];
+    let common: &[&str] = &[
+        "aimeio", "aime", "vfd", "amvideo", "clock", "dns", "ds", "eeprom", "gpio", "hwmon",
+        "jvs", "keychip", "netenv", "pcbid", "sram", "vfs", "epay", "openssl", "system",
+    ];
+    let mut allowed: HashSet<&'static str> = match key.as_str() {
+        "chunithm" => common.iter().copied()
+            .chain(["gfx", "led15093", "led", "chuniio", "io3", "ir", "slider"].iter().copied())
+            .collect(),
+        "sinmai" => common.iter().copied()
+            .chain(["led15070", "unity", "mai2io", "io4", "button", "touch", "gfx"].iter().copied())
+            .collect(),
+        "ongeki" => common.iter().copied()
+            .chain(["gfx", "unity", "led15093", "led", "mu3io", "io4"].iter().copied())
+            .collect(),
+        "cardmaker" => common.iter().copied()
+            .chain(["gfx", "unity", "led15093", "printer", "cmio", "io4"].iter().copied())
+            .collect(),
+        "wacca" => common.iter().copied()
+            .chain(["mercuryio", "io4"].iter().copied())
+            .collect(),
+        "diva" => common.iter().copied()
+            .chain(["divaio"].iter().copied())
+            .collect(),
+        _ => all_sections.iter().copied().collect(),
+    };
+
+    if let Some(range) = version_defaults_for_game(name) {
+        for section in &range.extra_sections {
+            if let Some(known) = all_sections.iter().find(|s| s.eq_ignore_ascii_case(section)) {
+                allowed.insert(known);
+            }
+        }
+        allowed.retain(|s| !range.removed_sections.iter().any(|removed| removed.eq_ignore_ascii_case(s)));
+    }
+
+    for section in blacklist_sections_for_game(name) {
+        allowed.remove(section);
+    }
+
+    allowed
+}
+
+pub(super) fn load_active_seg_config() -> ApiResult<(SegatoolsConfig, PathBuf)> {
+    let base = active_game_dir().map_err(|e| ApiError::from(e.to_string()))?;
+    let seg_path = segatoools_path_for_active().map_err(|e| ApiError::from(e.to_string()))?;
+    if !seg_path.exists() {
+        return Err(("segatools.ini not found. Please deploy first.".to_string()).into());
+    }
+    let cfg = load_segatoools_config(&seg_path).map_err(|e| ApiError::from(e.to_string()))?;
+    Ok((cfg, base))
+}
+
+pub(super) fn sanitize_segatoools_for_game(mut cfg: SegatoolsConfig, game_name: Option<&str>) -> SegatoolsConfig {
+    let name = game_name.unwrap_or("");
+    let key = canonical_game_key(name);
+    let allowed_sections = allowed_sections_for_game(&key);
+    let blacklist = blacklist_sections_for_game(name);
+
+    let allowed_lower: HashSet<String> = allowed_sections.into_iter().map(|s| s.to_lowercase()).collect();
+    let blacklist_lower: HashSet<String> = blacklist.into_iter().map(|s| s.to_lowercase()).collect();
+
+    let mut present: Vec<String> = cfg
+        .present_sections
+        .into_iter()
+        .filter(|s| allowed_lower.contains(&s.to_lowercase()))
+        .collect();
+
+    if present.is_empty() {
+        let template = template_for_game_key(&key);
+
+        if let Some(tmpl) = template {
+            if let Ok(default_cfg) = load_segatoools_config_from_string(tmpl) {
+                return default_cfg;
+            }
+        }
+        present = allowed_lower.iter().cloned().collect();
+    }
+
+    let filter_keys = |keys: &mut Vec<String>| {
+        keys.retain(|k| {
+            k.split('.')
+                .next()
+                .map(|sec| !blacklist_lower.contains(&sec.to_lowercase()))
+                .unwrap_or(true)
+        });
+    };
+
+    filter_keys(&mut cfg.present_keys);
+    filter_keys(&mut cfg.commented_keys);
+    cfg.present_sections = present;
+
+    cfg
+}
+
+#[derive(Serialize)]
+pub struct PathInfo {
+    pub configured: String,
+    pub resolved: String,
+    pub exists: bool,
+}
+
+#[derive(Serialize)]
+pub struct DataPaths {
+    pub game_root: String,
+    pub amfs: Option<PathInfo>,
+    pub appdata: Option<PathInfo>,
+    pub option: Option<PathInfo>,
+}
+
+fn build_path_info(base: &Path, raw: &str) -> Option<PathInfo> {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    let resolved = resolve_with_base(base, trimmed);
+    Some(PathInfo {
+        configured: trimmed.to_string(),
+        resolved: resolved.to_string_lossy().into_owned(),
+        exists: resolved.exists(),
+    })
+}
+
+/// segatools.ini of the active game for the default slot, or the named slot's file beside it.
+pub(super) fn active_slot_path(slot: Option<&str>) -> ApiResult<PathBuf> {
+    match slot.filter(|&s| !slots::is_default(Some(s))) {
+        None => segatoools_path_for_active().map_err(|e| ApiError::from(e.to_string())),
+        Some(slot) => {
+            let game_id = get_active_game_id()
+                .map_err(ApiError::from)?
+                .ok_or_else(|| ApiError::new(ErrorCode::NotFound, "No active game selected"))?;
+            segatoools_path_for_slot(&game_id, Some(slot)).map_err(ApiError::from)
+        }
+    }
+}
+
+pub(super) fn slot_missing(path: &Path) -> ApiError {
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    ApiError::from(format!("{} not found. Please deploy first.", name))
+}
+
+#[command]
+pub fn get_segatoools_config(state: State<'_, AppState>, slot: Option<String>) -> ApiResult<SegatoolsConfig> {
+    ensure_default_segatoools_exists().map_err(|e| ApiError::from(e.to_string()))?;
+    let path = active_slot_path(slot.as_deref())?;
+    if !path.exists() {
+        return Err(slot_missing(&path));
+    }
+    let game_name = state.active_game().ok().map(|g| g.name);
+    let cfg = load_segatoools_config(&path).map_err(|e| ApiError::from(e.to_string()))?;
+    Ok(sanitize_segatoools_for_game(cfg, game_name.as_deref()))
+}
+
+#[command]
+pub fn get_game_dir_segatoools_config(state: State<'_, AppState>) -> ApiResult<SegatoolsConfig> {
+    let game = state.active_game()?;
+    let root = store::game_root_dir(&game).ok_or_else(|| "Game path missing".to_string())?;
+    let path = root.join("segatools.ini");
+    if !path.exists() {
+        return Err(("segatools.ini not found in game directory.".to_string()).into());
+    }
+    let cfg = load_segatoools_config(&path).map_err(|e| ApiError::from(e.to_string()))?;
+    Ok(sanitize_segatoools_for_game(cfg, Some(game.name.as_str())))
+}
+
+/// Resolves and connects to the startup, title, billing and Aime DB servers `[dns]` of the slot
+/// points at, for diagnosing a game that hangs at startup.
+#[command]
+pub async fn test_network_cmd(slot: Option<String>, timeout_ms: Option<u64>) -> ApiResult<Vec<EndpointProbe>> {
+    let path = active_slot_path(slot.as_deref())?;
+    if !path.exists() {
+        return Err(slot_missing(&path));
+    }
+    let cfg = load_segatoools_config(&path).map_err(|e| ApiError::from(e.to_string()))?;
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(3000).clamp(100, 30_000));
+    tauri::async_runtime::spawn_blocking(move || probe_dns_endpoints(&cfg.dns, timeout))
+        .await
+        .map_err(|e| ApiError::from(e.to_string()))
+}
+
+/// COM ports with their device names, for the `portNo` fields of `[aime]`, `[vfd]` and the
+/// LED board sections.
+#[command]
+pub async fn enumerate_serial_ports_cmd() -> ApiResult<Vec<SerialPort>> {
+    tauri::async_runtime::spawn_blocking(enumerate_serial_ports)
+        .await
+        .map_err(|e| ApiError::from(e.to_string()))
+}
+
+/// Virtual-key codes with the names the binding editor shows for them.
+#[command]
+pub fn list_key_names_cmd() -> Vec<KeyName> {
+    key_names()
+}
+
+/// Waits for the next keypress (up to `timeout_ms`, 10 seconds by default) and returns it,
+/// or `None` if nothing was pressed in time.
+#[command]
+pub async fn capture_key_cmd(timeout_ms: Option<u64>) -> ApiResult<Option<KeyName>> {
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(10_000).clamp(500, 60_000));
+    let code = tauri::async_runtime::spawn_blocking(move || capture_key(timeout))
+        .await
+        .map_err(|e| ApiError::from(e.to_string()))?
+        .map_err(ApiError::from)?;
+    Ok(code.map(|code| KeyName {
+        code,
+        name: key_name(code).map(str::to_string).unwrap_or_else(|| format!("0x{:02X}", code)),
+    }))
+}
+
+/// Keys bound to more than one setting, in `config` when given (unsaved edits) or else in
+/// the slot's file. Disabled sections and sections the file lacks are not counted.
+#[command]
+pub fn check_key_bindings_cmd(config: Option<SegatoolsConfig>, slot: Option<String>) -> ApiResult<Vec<KeyConflict>> {
+    let cfg = match config {
+        Some(cfg) => cfg,
+        None => {
+            let path = active_slot_path(slot.as_deref())?;
+            if !path.exists() {
+                return Err(slot_missing(&path));
+            }
+            load_segatoools_config(&path).map_err(|e| ApiError::from(e.to_string()))?
+        }
+    };
+    Ok(key_conflicts(&cfg))
+}
+
+#[command]
+pub fn save_segatoools_config(
+    state: State<'_, AppState>,
+    config: SegatoolsConfig,
+    slot: Option<String>,
+) -> ApiResult<()> {
+    let path = active_slot_path(slot.as_deref())?;
+    if !path.exists() {
+        return Err(slot_missing(&path));
+    }
+    let game = state.active_game().ok();
+    let sanitized = sanitize_segatoools_for_game(config, game.as_ref().map(|g| g.name.as_str()));
+    let _guard = state.lock_config();
+    let before = live_segatoools_or_default(&path);
+    persist_segatoools_config(&path, &sanitized).map_err(|e| ApiError::from(e.to_string()))?;
+    // Provenance tracks the default slot only.
+    if let Some(game) = game.filter(|_| slots::is_default(slot.as_deref())) {
+        record_segatoools_provenance(&game, &before, &live_segatoools_or_default(&path), &KeyOrigin::user());
+    }
+    Ok(())
+}
+
+#[command]
+pub fn export_segatoools_config_cmd(state: State<'_, AppState>, slot: Option<String>) -> ApiResult<String> {
+    ensure_default_segatoools_exists().map_err(|e| ApiError::from(e.to_string()))?;
+    let path = active_slot_path(slot.as_deref())?;
+    if !path.exists() {
+        return Err(slot_missing(&path));
+    }
+    let content = fs::read_to_string(&path).map_err(|e| ApiError::from(e.to_string()))?;
+    let game_name = state.active_game().ok().map(|g| g.name);
+    let mut cfg = load_segatoools_config_from_string(&content).map_err(|e| ApiError::from(e.to_string()))?;
+    cfg.keychip.id.clear();
+    let sanitized = sanitize_segatoools_for_game(cfg, game_name.as_deref());
+    let rendered = render_segatoools_config(&sanitized, Some(&content)).map_err(|e| ApiError::from(e.to_string()))?;
+    Ok(redact_keychip_id(&rendered))
+}
+
+#[command]
+pub fn import_segatoools_config_cmd(state: State<'_, AppState>, content: String) -> ApiResult<SegatoolsConfig> {
+    let game_name = state.active_game().ok().map(|g| g.name);
+    let cfg = load_segatoools_config_from_string(&content).map_err(|e| ApiError::from(e.to_string()))?;
+    Ok(sanitize_segatoools_for_game(cfg, game_name.as_deref()))
+}
+
+/// Serializes a profile, or the live segatools.ini when `profile_id` is `None`, with the
+/// keychip id and every section the game does not use stripped.
+fn profile_bundle(game: &Game, profile_id: Option<String>) -> ApiResult<String> {
+    let game_name = game.name.clone();
+    let allowed = allowed_sections_for_game(&game.name);
+
+    let (name, description, mut cfg) = if let Some(id) = profile_id {
+        let profile = load_profile(&id, None).map_err(|e| ApiError::from(e.to_string()))?;
+        (profile.name, profile.description, profile.segatools)
+    } else {
+        let path = segatoools_path_for_active().map_err(|e| ApiError::from(e.to_string()))?;
+        let cfg = load_segatoools_config(&path).map_err(|e| ApiError::from(e.to_string()))?;
+        ("Shared Profile".to_string(), None, cfg)
+    };
+
+    cfg = sanitize_segatoools_for_game(cfg, Some(game_name.as_str()));
+    cfg.keychip.id.clear();
+
+    let mut payload = serde_json::to_value(serde_json::json!({
+        "name": name,
+        "description": description,
+        "segatools": cfg,
+    })).map_err(|e| ApiError::from(e.to_string()))?;
+
+    if let Some(seg) = payload.get_mut("segatools").and_then(|v| v.as_object_mut()) {
+        let keys: Vec<String> = seg.keys().cloned().collect();
+        for k in keys {
+            if k == "presentSections" || k == "presentKeys" || k == "commentedKeys" {
+                continue;
+            }
+            if !allowed.contains(k.as_str()) {
+                seg.remove(&k);
+            }
+        }
+
+        // Filter present sections/keys to only allowed
+        if let Some(present) = seg.get_mut("presentSections").and_then(|v| v.as_array_mut()) {
+            present.retain(|s| s.as_str().map(|v| allowed.contains(v)).unwrap_or(true));
+        }
+        if let Some(present) = seg.get_mut("presentKeys").and_then(|v| v.as_array_mut()) {
+            present.retain(|s| {
+                s.as_str().map(|v| {
+                    let sec = v.split('.').next().unwrap_or("");
+                    allowed.contains(sec)
+                }).unwrap_or(true)
+            });
+        }
+        if let Some(comments) = seg.get_mut("commentedKeys").and_then(|v| v.as_array_mut()) {
+            comments.retain(|s| {
+                s.as_str().map(|v| {
+                    let sec = v.split('.').next().unwrap_or("");
+                    allowed.contains(sec)
+                }).unwrap_or(true)
+            });
+        }
+    }
+
+    serde_json::to_string_pretty(&payload).map_err(|e| ApiError::from(e.to_string()))
+}
+
+#[command]
+pub fn export_profile_cmd(state: State<'_, AppState>, profile_id: Option<String>) -> ApiResult<String> {
+    ensure_default_segatoools_exists().map_err(|e| ApiError::from(e.to_string()))?;
+    let game = state.active_game()?;
+    profile_bundle(&game, profile_id)
+}
+
+/// Same bundle as `export_profile_cmd`, encrypted under `passphrase` into a link-safe blob so it
+/// can be posted publicly without exposing server addresses.
+#[command]
+pub fn export_profile_share_cmd(
+    state: State<'_, AppState>,
+    profile_id: Option<String>,
+    passphrase: String,
+) -> ApiResult<String> {
+    ensure_default_segatoools_exists().map_err(|e| ApiError::from(e.to_string()))?;
+    let game = state.active_game()?;
+    let bundle = profile_bundle(&game, profile_id)?;
+    Ok(seal_share(bundle.as_bytes(), &passphrase)?)
+}
+
+fn import_profile_bundle(game_name: Option<&str>, content: &str) -> ApiResult<ConfigProfile> {
+    let mut payload: ImportProfilePayload = serde_json::from_str(content).map_err(|e| ApiError::from(e.to_string()))?;
+    payload.segatools.keychip.id.clear();
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut profile = ConfigProfile {
+        id: gen_profile_id("import"),
+        name: payload.name.unwrap_or_else(|| "Imported Profile".to_string()),
+        description: payload.description,
+        base_profile_id: None,
+        segatools: payload.segatools,
+        segatools_slots: BTreeMap::new(),
+        json_configs: BTreeMap::new(),
+        created_at: now.clone(),
+        updated_at: now,
+    };
+    profile.segatools = sanitize_segatoools_for_game(profile.segatools, game_name);
+    save_profile(&profile).map_err(|e| ApiError::from(e.to_string()))?;
+    Ok(profile)
+}
+
+#[command]
+pub fn import_profile_cmd(state: State<'_, AppState>, content: String) -> ApiResult<ConfigProfile> {
+    let game_name = state.active_game().ok().map(|g| g.name);
+    import_profile_bundle(game_name.as_deref(), &content)
+}
+
+/// Decrypts a blob or link from `export_profile_share_cmd` and imports it like a plain bundle.
+#[command]
+pub fn import_profile_share_cmd(
+    state: State<'_, AppState>,
+    share: String,
+    passphrase: String,
+) -> ApiResult<ConfigProfile> {
+    let bundle = open_share(&share, &passphrase)?;
+    let content = String::from_utf8(bundle).map_err(|_| ApiError::from(ShareError::Malformed))?;
+    let game_name = state.active_game().ok().map(|g| g.name);
+    import_profile_bundle(game_name.as_deref(), &content)
+}
+
+/// The active slot's `[dns]` and `[gfx]` and the saved card names as one QR code payload,
+/// to copy basic settings to another machine with a phone.
+#[command]
+pub fn export_qr_backup_cmd(app: AppHandle, slot: Option<String>) -> ApiResult<String> {
+    let path = active_slot_path(slot.as_deref())?;
+    if !path.exists() {
+        return Err(slot_missing(&path));
+    }
+    let cfg = load_segatoools_config(&path).map_err(|e| ApiError::from(e.to_string()))?;
+    let cards = load_aimes(&app)?;
+    let backup = QrBackup::new(&cfg, cards.iter().map(|c| (c.name.as_str(), c.number.as_str())));
+    Ok(encode_qr_backup(&backup)?)
+}
+
+/// What a scanned QR backup holds, to preview before importing it.
+#[command]
+pub fn parse_qr_backup_cmd(payload: String) -> ApiResult<QrBackup> {
+    Ok(decode_qr_backup(&payload)?)
+}
+
+/// Writes the `[dns]` and `[gfx]` of a scanned QR backup into the active slot. The card
+/// names are only shown by `parse_qr_backup_cmd`; without numbers there is nothing to add.
+#[command]
+pub fn import_qr_backup_cmd(
+    state: State<'_, AppState>,
+    payload: String,
+    slot: Option<String>,
+) -> ApiResult<SegatoolsConfig> {
+    let backup = decode_qr_backup(&payload)?;
+    let game = state.active_game()?;
+    let path = active_slot_path(slot.as_deref())?;
+    if !path.exists() {
+        return Err(slot_missing(&path));
+    }
+
+    let _guard = state.lock_config();
+    let before = live_segatoools_or_default(&path);
+    let mut cfg = load_segatoools_config(&path).map_err(|e| ApiError::from(e.to_string()))?;
+    apply_qr_backup(&mut cfg, &backup);
+    retain_sections(&mut cfg, &["dns".to_string(), "gfx".to_string()]);
+    let result = persist_segatoools_config(&path, &cfg);
+    journal::record(
+        &game.id,
+        JournalEntry::new("qr_backup_import", "Imported network and display settings from a QR backup")
+            .with_outcome(&result)
+            .with_details(serde_json::json!({ "slot": slot, "cards": backup.cards.len() })),
+    );
+    result.map_err(|e| ApiError::from(e.to_string()))?;
+    let after = live_segatoools_or_default(&path);
+    if slots::is_default(slot.as_deref()) {
+        record_segatoools_provenance(&game, &before, &after, &KeyOrigin::user());
+    }
+    Ok(after)
+}
+
+#[command]
+pub fn list_profiles_cmd(game_id: Option<String>) -> ApiResult<Vec<ConfigProfile>> {
+    list_profiles(game_id.as_deref()).map_err(|e| ApiError::from(e.to_string()))
+}
+
+#[command]
+pub fn load_profile_cmd(state: State<'_, AppState>, id: String) -> ApiResult<ConfigProfile> {
+    let game_name = state.active_game().ok().map(|g| g.name);
+    let mut profile = load_profile(&id, None).map_err(|e| ApiError::from(e.to_string()))?;
+    profile.segatools = sanitize_segatoools_for_game(profile.segatools, game_name.as_deref());
+    Ok(profile)
+}
+
+#[command]
+pub fn save_profile_cmd(state: State<'_, AppState>, profile: ConfigProfile) -> ApiResult<()> {
+    let game_name = state.active_game().ok().map(|g| g.name);
+    let mut profile = profile;
+    profile.segatools = sanitize_segatoools_for_game(profile.segatools, game_name.as_deref());
+    save_profile(&profile).map_err(|e| ApiError::from(e.to_string()))
+}
+
+#[command]
+pub fn delete_profile_cmd(id: String) -> ApiResult<()> {
+    delete_profile(&id).map_err(|e| ApiError::from(e.to_string()))
+}
+
+#[command]
+pub fn list_profile_assets_cmd(profile_id: String, game_id: Option<String>) -> ApiResult<Vec<ProfileAsset>> {
+    profile_assets(&profile_id, game_id.as_deref()).map_err(ApiError::from)
+}
+
+/// Copies a file into the profile so applying it restores the file at `target`, a path relative
+/// to the game directory. Without `source` the game's current file at `target` is captured.
+#[command]
+pub fn add_profile_asset_cmd(profile_id: String, target: String, source: Option<String>) -> ApiResult<ProfileAsset> {
+    let source = match source {
+        Some(source) => PathBuf::from(source),
+        None => resolve_with_base(&active_game_dir().map_err(ApiError::from)?, &target),
+    };
+    let contents = fs::read(&source).map_err(|e| ApiError::from(format!("{}: {}", source.display(), e)))?;
+    save_profile_asset(&profile_id, None, &target, &contents).map_err(ApiError::from)
+}
+
+#[command]
+pub fn delete_profile_asset_cmd(profile_id: String, target: String) -> ApiResult<()> {
+    delete_profile_asset(&profile_id, None, &target).map_err(ApiError::from)
+}
+
+#[command]
+pub fn load_profile_notes_cmd(profile_id: String) -> ApiResult<String> {
+    load_profile_notes(&profile_id, None).map_err(ApiError::from)
+}
+
+#[command]
+pub fn save_profile_notes_cmd(profile_id: String, notes: String) -> ApiResult<()> {
+    save_profile_notes(&profile_id, None, &notes).map_err(ApiError::from)
+}
+
+#[command]
+pub fn default_segatoools_config_cmd() -> ApiResult<SegatoolsConfig> {
+    // Try to load game-specific default if an active game is selected
+    let active = if let Ok(Some(id)) = get_active_game_id() {
+        if let Ok(games) = store::list_games() {
+            games.iter().find(|g| g.id == id).cloned()
+        } else {
+            None
+        }
+    } else {
+        None
+    };
+
+    template_segatoools_for_game(active.as_ref().map(|g| g.name.as_str()))
+}
+
+/// The game's bundled segatools template, or the generic defaults for unknown titles.
+pub(super) fn template_segatoools_for_game(game_name: Option<&str>) -> ApiResult<SegatoolsConfig> {
+    let Some(name) = game_name else {
+        return Ok(sanitize_segatoools_for_game(default_segatoools_config(), None));
+    };
+    let key = canonical_game_key(name);
+    if let Some(ini_content) = template_for_game_key(&key) {
+        let cfg = load_segatoools_config_from_string(ini_content).map_err(|e| ApiError::from(e.to_string()))?;
+        return Ok(sanitize_segatoools_for_game(cfg, Some(key.as_str())));
+    }
+    Ok(sanitize_segatoools_for_game(default_segatoools_config(), Some(key.as_str())))
+}
+
+/// Attributes the keys a write changed to `origin`. Provenance is advisory, so a failure here
+/// never fails the write itself.
+pub(super) fn record_segatoools_provenance(game: &Game, before: &SegatoolsConfig, after: &SegatoolsConfig, origin: &KeyOrigin) {
+    let Ok(template) = template_segatoools_for_game(Some(game.name.as_str())) else {
+        return;
+    };
+    let Ok(mut provenance) = load_provenance(&game.id) else {
+        return;
+    };
+    provenance.record(before, after, &template, origin);
+    if let Err(err) = save_provenance(&game.id, &provenance) {
+        eprintln!("[config] failed to save key provenance for {}: {}", game.id, err);
+    }
+}
+
+/// Live segatools.ini of a game as it is on disk, or the defaults when it is missing.
+pub(super) fn live_segatoools_or_default(path: &Path) -> SegatoolsConfig {
+    if path.exists() {
+        if let Ok(cfg) = load_segatoools_config(path) {
+            return cfg;
+        }
+    }
+    default_segatoools_config()
+}
+
+/// Source of every key in the active game's segatools.ini and whether it differs from the
+/// template, for the editor's "modified" markers.
+#[command]
+pub fn get_segatoools_provenance_cmd(state: State<'_, AppState>) -> ApiResult<Vec<KeyProvenance>> {
+    ensure_default_segatoools_exists().map_err(|e| ApiError::from(e.to_string()))?;
+    let game = state.active_game()?;
+    let path = segatoools_path_for_active().map_err(|e| ApiError::from(e.to_string()))?;
+    let cfg = load_segatoools_config(&path).map_err(|e| ApiError::from(e.to_string()))?;
+    let cfg = sanitize_segatoools_for_game(cfg, Some(game.name.as_str()));
+    let template = template_segatoools_for_game(Some(game.name.as_str()))?;
+    let provenance = load_provenance(&game.id)?;
+    Ok(provenance.report(&cfg, &template))
+}
+
+/// Puts one key of the active game's segatools.ini back to its template value and returns
+/// the saved config.
+#[command]
+pub fn reset_segatoools_key_cmd(state: State<'_, AppState>, section: String, key: String) -> ApiResult<SegatoolsConfig> {
+    ensure_default_segatoools_exists().map_err(|e| ApiError::from(e.to_string()))?;
+    let game = state.active_game()?;
+    let path = segatoools_path_for_active().map_err(|e| ApiError::from(e.to_string()))?;
+    let _guard = state.lock_config();
+    let current = load_segatoools_config(&path).map_err(|e| ApiError::from(e.to_string()))?;
+    let template = template_segatoools_for_game(Some(game.name.as_str()))?;
+    let reset = reset_key_to_template(&current, &template, &section, &key)?;
+    let sanitized = sanitize_segatoools_for_game(reset, Some(game.name.as_str()));
+    persist_segatoools_config(&path, &sanitized).map_err(|e| ApiError::from(e.to_string()))?;
+    if let Ok(mut provenance) = load_provenance(&game.id) {
+        provenance.forget(&section, &key);
+        let _ = save_provenance(&game.id, &provenance);
+    }
+    Ok(sanitized)
+}
+
+#[command]
+pub fn segatoools_path_cmd() -> ApiResult<String> {
+    Ok(segatoools_path_for_active()
+        .map_err(|e| ApiError::from(e.to_string()))?
+        .to_str()
+        .unwrap_or("./segatools.ini")
+        .to_string())
+}
+
+#[command]
+pub fn open_segatoools_folder_cmd() -> ApiResult<()> {
+    let ini_path = segatoools_path_for_active().map_err(|e| ApiError::from(e.to_string()))?;
+    let dir = ini_path
+        .parent()
+        .ok_or_else(|| "Config folder not found".to_string())?;
+    if !dir.exists() {
+        return Err(("Config folder not found".to_string()).into());
+    }
+    Command::new("explorer")
+        .arg(dir)
+        .spawn()
+        .map_err(|e| ApiError::from(e.to_string()))?;
+    Ok(())
+}
+
+#[command]
+pub fn get_data_paths_cmd() -> ApiResult<DataPaths> {
+    let (cfg, base) = load_active_seg_config()?;
+    Ok(DataPaths {
+        game_root: base.to_string_lossy().into_owned(),
+        amfs: build_path_info(&base, &cfg.vfs.amfs),
+        appdata: build_path_info(&base, &cfg.vfs.appdata),
+        option: build_path_info(&base, &cfg.vfs.option),
+    })
+}
+
+#[command]
+pub fn apply_profile_to_game_cmd(state: State<'_, AppState>, game_id: String, profile_id: String) -> ApiResult<()> {
+    let games = store::list_games().map_err(|e| ApiError::from(e.to_string()))?;
+    let game = games
+        .into_iter()
+        .find(|g| g.id == game_id)
+        .ok_or_else(|| "Game not found".to_string())?;
+    let seg_path = segatoools_path_for_game_id(&game_id).map_err(|e| ApiError::from(e.to_string()))?;
+    if !seg_path.exists() {
+        return Err(("segatools.ini not found. Please deploy first.".to_string()).into());
+    }
+    let profile = load_profile(&profile_id, Some(&game_id)).map_err(|e| ApiError::from(e.to_string()))?;
+    let _guard = state.lock_config();
+    let result = apply_profile_files(&game, &profile, &game.name).map(|_| ());
+    journal::record(
+        &game_id,
+        JournalEntry::new("profile_apply", format!("Applied profile \"{}\"", profile.name))
+            .with_outcome(&result)
+            .with_details(serde_json::json!({ "profileId": profile_id })),
+    );
+    result.map_err(ApiError::from)
+}
+
+/// Applies only the listed sections of a profile (e.g. `["dns", "keychip"]`) and leaves every
+/// other section of the game's segatools.ini, or of config `slot`, untouched. Returns the
+/// sections actually written.
+#[command]
+pub fn apply_profile_sections_cmd(
+    state: State<'_, AppState>,
+    game_id: String,
+    profile_id: String,
+    sections: Vec<String>,
+    slot: Option<String>,
+) -> ApiResult<Vec<String>> {
+    let games = store::list_games().map_err(|e| ApiError::from(e.to_string()))?;
+    let game = games
+        .into_iter()
+        .find(|g| g.id == game_id)
+        .ok_or_else(|| "Game not found".to_string())?;
+    let seg_path = segatoools_path_for_slot(&game_id, slot.as_deref()).map_err(ApiError::from)?;
+    if !seg_path.exists() {
+        return Err(slot_missing(&seg_path));
+    }
+    let profile = load_profile(&profile_id, Some(&game_id)).map_err(|e| ApiError::from(e.to_string()))?;
+    let slot_cfg = profile_slot_config(&profile, slot.as_deref())?;
+    let mut partial = sanitize_segatoools_for_game(slot_cfg, Some(game.name.as_str()));
+    let applied = retain_sections(&mut partial, &sections);
+    if applied.is_empty() {
+        return Err(ApiError::from("Profile does not contain any of the requested sections".to_string()));
+    }
+    let _guard = state.lock_config();
+    let before = live_segatoools_or_default(&seg_path);
+    let mut txn = FileTransaction::new();
+    let result = txn.stage_segatoools(&seg_path, &partial).and_then(|_| txn.commit());
+    if result.is_ok() && slots::is_default(slot.as_deref()) {
+        record_segatoools_provenance(
+            &game,
+            &before,
+            &live_segatoools_or_default(&seg_path),
+            &KeyOrigin::profile(&profile.id, &profile.name),
+        );
+    }
+    journal::record(
+        &game_id,
+        JournalEntry::new(
+            "profile_apply",
+            format!("Applied [{}] from profile \"{}\"", applied.join("], ["), profile.name),
+        )
+        .with_outcome(&result)
+        .with_details(serde_json::json!({ "profileId": profile_id, "sections": applied, "slot": slot })),
+    );
+    result.map_err(ApiError::from)?;
+    Ok(applied)
+}
+
+/// Diffs two configs, or previews applying `profile_id` to the live segatools.ini of
+/// `game_id` (the active game when omitted), or to its config `slot`.
+#[command]
+pub fn diff_segatoools_configs_cmd(
+    state: State<'_, AppState>,
+    old: Option<SegatoolsConfig>,
+    new: Option<SegatoolsConfig>,
+    profile_id: Option<String>,
+    game_id: Option<String>,
+    slot: Option<String>,
+) -> ApiResult<Vec<ConfigChange>> {
+    if let (Some(old), Some(new)) = (old.as_ref(), new.as_ref()) {
+        return Ok(diff_segatoools_configs(old, new));
+    }
+    let profile_id = profile_id
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| ApiError::from("Provide two configs or a profile id".to_string()))?;
+    let game = match game_id {
+        Some(id) => store::list_games()
+            .map_err(|e| ApiError::from(e.to_string()))?
+            .into_iter()
+            .find(|g| g.id == id)
+            .ok_or_else(|| ApiError::from("Game not found".to_string()))?,
+        None => state.active_game()?,
+    };
+    let seg_path = segatoools_path_for_slot(&game.id, slot.as_deref()).map_err(ApiError::from)?;
+    let live = match old {
+        Some(old) => old,
+        None if seg_path.exists() => load_segatoools_config(&seg_path).map_err(|e| ApiError::from(e.to_string()))?,
+        None => default_segatoools_config(),
+    };
+    let profile = load_profile(&profile_id, Some(&game.id)).map_err(|e| ApiError::from(e.to_string()))?;
+    let slot_cfg = profile_slot_config(&profile, slot.as_deref())?;
+    let incoming = sanitize_segatoools_for_game(slot_cfg, Some(game.name.as_str()));
+    Ok(diff_segatoools_configs(&live, &incoming))
+}
+
+/// The profile's config for `slot`; its main config for the default slot.
+fn profile_slot_config(profile: &ConfigProfile, slot: Option<&str>) -> ApiResult<SegatoolsConfig> {
+    if slots::is_default(slot) {
+        return Ok(profile.segatools.clone());
+    }
+    let slot = slot.unwrap_or_default().trim().to_ascii_lowercase();
+    profile.segatools_slots.get(&slot).cloned().ok_or_else(|| {
+        ApiError::new(
+            ErrorCode::NotFound,
+            format!("Profile \"{}\" has no config for slot {}", profile.name, slot),
+        )
+    })
+}
+
+/// Config slots of `game_id`, or of the active game when omitted. The default slot is always
+/// listed first.
+#[command]
+pub fn list_config_slots_cmd(state: State<'_, AppState>, game_id: Option<String>) -> ApiResult<Vec<ConfigSlot>> {
+    let game_id = match game_id {
+        Some(id) => id,
+        None => state.active_game()?.id,
+    };
+    Ok(list_slots_for_game(&game_id))
+}
+
+/// Adds a named config slot for the active game as a copy of slot `from` (segatools.ini when
+/// omitted).
+#[command]
+pub fn add_config_slot_cmd(state: State<'_, AppState>, name: String, from: Option<String>) -> ApiResult<ConfigSlot> {
+    let game = state.active_game()?;
+    let _guard = state.lock_config();
+    let result = create_slot_in(&segatools_root_for_game_id(&game.id), &name, from.as_deref()).map_err(ApiError::from);
+    journal::record(
+        &game.id,
+        JournalEntry::new("config_slot", format!("Added config slot {}", name))
+            .with_outcome(&result.as_ref().map_err(|e| e.message.clone()))
+            .with_details(serde_json::json!({ "slot": name, "from": from })),
+    );
+    result
+}
+
+#[command]
+pub fn delete_config_slot_cmd(state: State<'_, AppState>, name: String) -> ApiResult<()> {
+    let game = state.active_game()?;
+    let _guard = state.lock_config();
+    let result = delete_slot_in(&segatools_root_for_game_id(&game.id), &name).map_err(ApiError::from);
+    journal::record(
+        &game.id,
+        JournalEntry::new("config_slot", format!("Deleted config slot {}", name))
+            .with_outcome(&result.as_ref().map_err(|e| e.message.clone()))
+            .with_details(serde_json::json!({ "slot": name })),
+    );
+    result
+}
+
+#[command]
+pub fn list_json_configs_cmd() -> ApiResult<Vec<JsonConfigFile>> {
+    list_json_configs_for_active().map_err(|e| ApiError::from(e.to_string()))
+}
+
+#[command]
+pub fn load_json_config_cmd(name: String) -> ApiResult<Value> {
+    load_json_config_for_active(&name).map_err(|e| ApiError::from(e.to_string()))
+}
+
+/// Rejects edits that break the file's bundled schema or that [`validate_json_config`] finds
+/// problems in. The errors travel as JSON in the error details so forms can mark the fields.
+fn check_json_config(name: &str, content: &Value) -> ApiResult<()> {
+    let schema_errors = validate_json_schema(name, content);
+    if let Some(first) = schema_errors.first() {
+        let at = if first.pointer.is_empty() { "/" } else { first.pointer.as_str() };
+        return Err(ApiError::with_details(
+            ErrorCode::InvalidInput,
+            format!("{} does not match its schema. {}: {}", name.trim(), at, first.message),
+            serde_json::to_string(&schema_errors).unwrap_or_default(),
+        ));
+    }
+    let issues = validate_json_config(name, content);
+    let Some(first) = issues.first() else {
+        return Ok(());
+    };
+    let at = if first.path.is_empty() { String::new() } else { format!("{}: ", first.path) };
+    Err(ApiError::with_details(
+        ErrorCode::InvalidInput,
+        format!("{} has {} invalid setting(s). {}{}", name.trim(), issues.len(), at, first.message),
+        serde_json::to_string(&issues).unwrap_or_default(),
+    ))
+}
+
+#[command]
+pub fn save_json_config_cmd(name: String, content: Value) -> ApiResult<()> {
+    check_json_config(&name, &content)?;
+    save_json_config_for_active(&name, &content).map_err(|e| ApiError::from(e.to_string()))
+}
+
+/// Schema violations of an edited config, by JSON pointer; empty for files without a bundled
+/// schema.
+#[command]
+pub fn validate_json_schema_cmd(name: String, content: Value) -> ApiResult<Vec<JsonSchemaError>> {
+    Ok(validate_json_schema(&name, &content))
+}
+
+/// Problems in an edited config without saving it; empty for files without a typed model.
+#[command]
+pub fn validate_json_config_cmd(name: String, content: Value) -> ApiResult<Vec<JsonConfigIssue>> {
+    Ok(validate_json_config(&name, &content))
+}
+
+/// config_common.json, config_server.json or config_client.json of the active game as a
+/// typed model for the settings forms.
+#[command]
+pub fn load_amdaemon_config_cmd(name: String) -> ApiResult<AmdaemonConfig> {
+    let value = load_json_config_for_active(&name).map_err(|e| ApiError::from(e.to_string()))?;
+    AmdaemonConfig::parse(&name, &value)
+        .ok_or_else(|| ApiError::new(ErrorCode::InvalidInput, format!("{} has no typed model", name.trim())))?
+        .map_err(|e| ApiError::new(ErrorCode::InvalidInput, e.to_string()))
+}
+
+#[command]
+pub fn save_amdaemon_config_cmd(name: String, config: AmdaemonConfig) -> ApiResult<()> {
+    if typed_kind(&name) != Some(config.kind()) {
+        return Err(ApiError::new(
+            ErrorCode::InvalidInput,
+            format!("A {} config cannot be saved as {}", config.kind(), name.trim()),
+        ));
+    }
+    let content = config.to_value().map_err(|e| ApiError::from(e.to_string()))?;
+    check_json_config(&name, &content)?;
+    save_json_config_for_active(&name, &content).map_err(|e| ApiError::from(e.to_string()))
+}
+
+#[command]
+pub async fn segatools_trust_status_cmd(app: AppHandle) -> ApiResult<SegatoolsTrustStatus> {
+    ensure_network_allowed(&app)?;
+    let mirrors = remote_defaults(&app).segatools_mirrors;
+    let status = tauri::async_runtime::spawn_blocking(move || -> ApiResult<SegatoolsTrustStatus> {
+        verify_segatoools_for_active(&mirrors).map_err(ApiError::from)
+    })
+    .await
+    .map_err(|e| ApiError::from(e.to_string()))??;
+    // Missing binaries mean "not deployed yet"; only files that changed since deploy are drift.
+    if !status.trusted && !status.missing_files && !status.checked_files.is_empty() {
+        let game_id = get_active_game_id().ok().flatten().unwrap_or_default();
+        notify_app(
+            &app,
+            Notification::new(
+                "trust",
+                NotificationSeverity::Warning,
+                "Segatools files no longer match the trusted build",
+                status.reason.clone().unwrap_or_default(),
+            )
+            .with_dedupe_key(format!("segatools-trust:{}", game_id))
+            .with_details(serde_json::json!({ "gameId": game_id, "buildId": status.build_id })),
+        );
+    }
+    Ok(status)
+}
+
+#[command]
+pub fn deploy_segatoools_cmd(app: AppHandle, state: State<'_, AppState>, force: bool) -> ApiResult<DeployResult> {
+    ensure_network_allowed(&app)?;
+    ensure_feature_allowed(&app, "deploy")?;
+    let game = state.active_game()?;
+    let _operation = state.begin_operation(&game.id, "deploy")?;
+    let mirrors = remote_defaults(&app).segatools_mirrors;
+    let result = deploy_segatoools_for_active(force, &mirrors);
+    // A deploy that only asks for confirmation has not changed anything yet.
+    if !matches!(&result, Ok(r) if r.needs_confirmation) {
+        let build_id = result
+            .as_ref()
+            .ok()
+            .and_then(|r| r.verification.as_ref())
+            .and_then(|v| v.build_id.clone());
+        journal_for_active(
+            JournalEntry::new("deploy", "Deployed trusted segatools")
+                .with_outcome(&result)
+                .with_details(serde_json::json!({ "force": force, "buildId": build_id })),
+        );
+    }
+    result.map_err(ApiError::from)
+}
+
+#[command]
+pub fn rollback_segatoools_cmd(app: AppHandle) -> ApiResult<RollbackResult> {
+    ensure_network_allowed(&app)?;
+    ensure_feature_allowed(&app, "deploy")?;
+    let mirrors = remote_defaults(&app).segatools_mirrors;
+    let result = rollback_segatoools_for_active(&mirrors);
+    journal_for_active(JournalEntry::new("rollback", "Restored segatools backup").with_outcome(&result));
+    let result = result.map_err(ApiError::from)?;
+    // Overrides added for the game's servers go with the setup they were added for. The
+    // rollback itself has succeeded, so a failure here is only journaled.
+    if let Ok(Some(game_id)) = get_active_game_id() {
+        let _ = clear_hosts_overrides_for_game(&app, &game_id);
+    }
+    Ok(result)
+}
@@ -0,0 +1,352 @@
+use super::config::template_segatoools_for_game;
+use super::remote::{ensure_feature_allowed, remote_defaults};
+use super::settings::ensure_network_allowed;
+use super::state::{AppState, OperationGuard};
+use super::vhd::{build_vhd_game, vhd_config_for_app_chain};
+use crate::config::{paths::segatoools_path_for_game_id, save_segatoools_config as persist_segatoools_config};
+use crate::games::{journal::{self, JournalEntry}, store};
+use crate::error::{ApiError, ApiResult, ErrorCode};
+use crate::platform;
+use crate::vhd::{is_virtual_disk_path, save_vhd_config};
+use crate::fsdecrypt;
+use serde::Serialize;
+use tauri::{command, AppHandle, Emitter, Manager, Window};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+fn emit_decrypt_progress(window: &Window, progress: fsdecrypt::DecryptProgress) {
+    let _ = window.emit("decrypt-progress", progress);
+}
+
+fn emit_decrypt_result(window: &Window, result: fsdecrypt::DecryptResult) {
+    let _ = window.emit("decrypt-result", result);
+}
+
+/// The key URL to use: none while the local key store is usable, else the caller's, else the
+/// remote default. Fetching keys needs network.
+fn resolve_key_url(app: &AppHandle, key_url: Option<String>) -> ApiResult<Option<String>> {
+    if fsdecrypt::key_store_ready() {
+        return Ok(None);
+    }
+    let key_url = key_url
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty())
+        .or_else(|| remote_defaults(app).fsdecrypt_key_url);
+    if key_url.is_some() {
+        ensure_network_allowed(app)?;
+    }
+    Ok(key_url)
+}
+
+/// Claims every tracked game whose folder holds one of `paths` for `operation`.
+fn claim_games_under(app: &AppHandle, paths: &[PathBuf], operation: &'static str) -> ApiResult<Vec<OperationGuard>> {
+    let state = app.state::<AppState>();
+    let mut operations = Vec::new();
+    for game in store::list_games().unwrap_or_default() {
+        if store::game_root_dir(&game).is_some_and(|root| paths.iter().any(|p| p.starts_with(&root))) {
+            operations.push(state.begin_operation(&game.id, operation)?);
+        }
+    }
+    Ok(operations)
+}
+
+#[derive(Serialize)]
+pub struct DecryptGameFilesResult {
+    #[serde(flatten)]
+    pub summary: fsdecrypt::DecryptSummary,
+    /// Game created from the decrypted APP VHDs when an import was requested.
+    pub imported_game_id: Option<String>,
+}
+
+/// Adds the APP VHDs a decrypt run extracted as a VHD game with the title's segatools template,
+/// or returns the existing game that already boots from the same base VHD. `None` when the run
+/// produced no APP VHD.
+fn import_decrypted_game(summary: &fsdecrypt::DecryptSummary) -> ApiResult<Option<String>> {
+    let app_vhds: Vec<PathBuf> = summary
+        .results
+        .iter()
+        .filter(|r| !r.failed && r.extracted && r.container_type.as_deref() == Some("APP"))
+        .filter_map(|r| r.output.as_deref().map(PathBuf::from))
+        .filter(|path| is_virtual_disk_path(path))
+        .collect();
+    let Some(vhd) = vhd_config_for_app_chain(app_vhds) else {
+        return Ok(None);
+    };
+    let games = store::list_games().map_err(ApiError::from)?;
+    if let Some(existing) = games.iter().find(|g| g.executable_path == vhd.app_base_path) {
+        return Ok(Some(existing.id.clone()));
+    }
+
+    let dir = Path::new(&vhd.app_base_path)
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    let game = build_vhd_game(&dir, &vhd);
+    store::save_game(game.clone()).map_err(ApiError::from)?;
+    save_vhd_config(&game.id, &vhd).map_err(ApiError::from)?;
+    let seg_path = segatoools_path_for_game_id(&game.id).map_err(ApiError::from)?;
+    let template = template_segatoools_for_game(Some(game.name.as_str()))?;
+    persist_segatoools_config(&seg_path, &template).map_err(ApiError::from)?;
+    journal::record(
+        &game.id,
+        JournalEntry::new("decrypt_import", format!("Imported {} from decrypted files", game.name))
+            .with_details(serde_json::json!({ "vhd": vhd })),
+    );
+    Ok(Some(game.id))
+}
+
+/// Journals the option containers a decrypt run extracted into a game's folder as option
+/// installs of that game.
+fn journal_option_installs(summary: &fsdecrypt::DecryptSummary) {
+    let options: Vec<&Path> = summary
+        .results
+        .iter()
+        .filter(|r| !r.failed && !r.skipped && r.extracted && r.container_type.as_deref() == Some("OPTION"))
+        .filter_map(|r| r.output.as_deref().map(Path::new))
+        .collect();
+    if options.is_empty() {
+        return;
+    }
+    for game in store::list_games().unwrap_or_default() {
+        let Some(root) = store::game_root_dir(&game) else {
+            continue;
+        };
+        let installed: Vec<&Path> = options.iter().copied().filter(|p| p.starts_with(&root)).collect();
+        if installed.is_empty() {
+            continue;
+        }
+        journal::record(
+            &game.id,
+            JournalEntry::new("option_install", format!("Installed {} option(s) from decrypted files", installed.len()))
+                .with_details(serde_json::json!({ "options": installed })),
+        );
+    }
+}
+
+#[command]
+pub async fn pick_decrypt_files_cmd() -> ApiResult<Vec<String>> {
+    tauri::async_runtime::spawn_blocking(|| {
+        let files: Vec<String> = platform::pick_files(&[
+            ("Container files (*.app;*.opt;*.pack)", "*.app;*.opt;*.pack"),
+            ("All files (*.*)", "*.*"),
+        ])?
+        .into_iter()
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect();
+
+        if files.is_empty() {
+            return Err(("No files selected".to_string()).into());
+        }
+
+        Ok(files)
+    })
+    .await
+    .map_err(|e| ApiError::from(e.to_string()))?
+}
+
+#[command]
+pub async fn load_fsdecrypt_keys_cmd(app: AppHandle, key_url: Option<String>) -> ApiResult<fsdecrypt::KeyStatus> {
+    let key_url = resolve_key_url(&app, key_url)?;
+    tauri::async_runtime::spawn_blocking(move || fsdecrypt::load_key_status(key_url))
+        .await
+        .map_err(|e| ApiError::from(e.to_string()))?
+        .map_err(|e| ApiError::from(e.to_string()))
+}
+
+#[command]
+pub fn get_fsdecrypt_key_store_cmd() -> ApiResult<fsdecrypt::KeyStoreStatus> {
+    fsdecrypt::key_store_status().map_err(|e| ApiError::from(e.to_string()))
+}
+
+/// Imports a key file from a URL or local path into the local key store.
+#[command]
+pub async fn import_fsdecrypt_keys_cmd(app: AppHandle, source: String) -> ApiResult<fsdecrypt::KeyStoreStatus> {
+    let source = source.trim().to_string();
+    if source.is_empty() {
+        return Err(ApiError::new(ErrorCode::InvalidInput, "Key source is empty"));
+    }
+    if source.starts_with("http://") || source.starts_with("https://") {
+        ensure_network_allowed(&app)?;
+    }
+    tauri::async_runtime::spawn_blocking(move || fsdecrypt::import_keys(&source))
+        .await
+        .map_err(|e| ApiError::from(e.to_string()))?
+        .map_err(|e| ApiError::from(e.to_string()))
+}
+
+#[command]
+pub fn set_fsdecrypt_game_key_cmd(
+    game_id: String,
+    key: String,
+    iv: Option<String>,
+) -> ApiResult<fsdecrypt::KeyStoreStatus> {
+    let iv = iv.filter(|iv| !iv.trim().is_empty());
+    fsdecrypt::set_game_key(&game_id, &key, iv.as_deref()).map_err(|e| ApiError::from(e.to_string()))
+}
+
+#[command]
+pub fn delete_fsdecrypt_game_key_cmd(game_id: String) -> ApiResult<fsdecrypt::KeyStoreStatus> {
+    fsdecrypt::remove_game_key(&game_id).map_err(|e| ApiError::from(e.to_string()))
+}
+
+#[command]
+pub fn clear_fsdecrypt_key_store_cmd() -> ApiResult<fsdecrypt::KeyStoreStatus> {
+    fsdecrypt::clear_key_store().map_err(|e| ApiError::from(e.to_string()))
+}
+
+#[command]
+#[allow(clippy::too_many_arguments)]
+pub async fn decrypt_game_files_cmd(
+    window: Window,
+    files: Vec<String>,
+    no_extract: bool,
+    extract_tree: Option<bool>,
+    key_url: Option<String>,
+    workers: Option<usize>,
+    import_game: Option<bool>,
+    output_dir: Option<String>,
+    overwrite: Option<fsdecrypt::OverwritePolicy>,
+    name_template: Option<String>,
+    operation_id: Option<String>,
+) -> ApiResult<DecryptGameFilesResult> {
+    ensure_feature_allowed(window.app_handle(), "fsdecrypt")?;
+    if files.is_empty() {
+        return Err(("No files provided".to_string()).into());
+    }
+    let paths: Vec<PathBuf> = files.into_iter().map(PathBuf::from).collect();
+    let output = fsdecrypt::DecryptOutputOptions {
+        output_dir: output_dir.filter(|dir| !dir.trim().is_empty()).map(PathBuf::from),
+        overwrite: overwrite.unwrap_or_default(),
+        name_template: name_template.map(|t| t.trim().to_string()).filter(|t| !t.is_empty()),
+    };
+    if let Some(template) = &output.name_template {
+        fsdecrypt::validate_name_template(template)
+            .map_err(|e| ApiError::new(ErrorCode::InvalidInput, e.to_string()))?;
+    }
+    let key_url = resolve_key_url(window.app_handle(), key_url)?;
+    // Outputs land next to the inputs or in the output directory; claim any game whose folder
+    // that is.
+    let targets = match &output.output_dir {
+        Some(dir) => vec![dir.clone()],
+        None => paths.clone(),
+    };
+    let operations = claim_games_under(window.app_handle(), &targets, "decrypt")?;
+    let cancellable = window
+        .app_handle()
+        .state::<AppState>()
+        .begin_cancellable("decrypt", operation_id, false)?;
+    let window = window.clone();
+    tauri::async_runtime::spawn_blocking(move || -> ApiResult<DecryptGameFilesResult> {
+        let _operations = operations;
+        let mut report_progress = |progress: fsdecrypt::DecryptProgress| {
+            emit_decrypt_progress(&window, progress);
+        };
+        let mut report_result = |result: fsdecrypt::DecryptResult| {
+            emit_decrypt_result(&window, result);
+        };
+        let summary = fsdecrypt::decrypt_game_files(
+            paths,
+            no_extract,
+            extract_tree.unwrap_or(false),
+            key_url,
+            workers.unwrap_or(fsdecrypt::DEFAULT_DECRYPT_WORKERS),
+            &output,
+            cancellable.token(),
+            Some(&mut report_progress),
+            Some(&mut report_result),
+        )
+        .map_err(|e| ApiError::from(e.to_string()))?;
+        journal_option_installs(&summary);
+        let imported_game_id = if import_game.unwrap_or(false) && !summary.cancelled {
+            import_decrypted_game(&summary)?
+        } else {
+            None
+        };
+        Ok(DecryptGameFilesResult {
+            summary,
+            imported_game_id,
+        })
+    })
+    .await
+    .map_err(|e| ApiError::from(e.to_string()))?
+}
+
+/// Reads only the BootID header of each container to report what it holds.
+#[command]
+pub async fn inspect_container_cmd(
+    app: AppHandle,
+    files: Vec<String>,
+    key_url: Option<String>,
+) -> ApiResult<Vec<fsdecrypt::ContainerInfo>> {
+    if files.is_empty() {
+        return Err(("No files provided".to_string()).into());
+    }
+    let paths: Vec<PathBuf> = files.into_iter().map(PathBuf::from).collect();
+    let key_url = resolve_key_url(&app, key_url)?;
+    tauri::async_runtime::spawn_blocking(move || fsdecrypt::inspect_containers(&paths, key_url))
+        .await
+        .map_err(|e| ApiError::from(e.to_string()))?
+        .map_err(|e| ApiError::from(e.to_string()))
+}
+
+/// Packs an option folder into an encrypted `.opt` container for `game_id`, written to
+/// `output_dir` or next to the folder.
+#[command]
+pub async fn build_option_container_cmd(
+    app: AppHandle,
+    folder: String,
+    game_id: String,
+    output_dir: Option<String>,
+    key_url: Option<String>,
+) -> ApiResult<fsdecrypt::OptionBuildResult> {
+    let folder = PathBuf::from(folder);
+    let output_dir = output_dir.filter(|dir| !dir.trim().is_empty()).map(PathBuf::from);
+    let key_url = resolve_key_url(&app, key_url)?;
+    let target = output_dir.clone().or_else(|| folder.parent().map(Path::to_path_buf)).unwrap_or_default();
+    let operations = claim_games_under(&app, &[target], "option build")?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let _operations = operations;
+        fsdecrypt::build_option_container(&folder, &game_id, output_dir.as_deref(), key_url, &mut |_, _| {})
+    })
+    .await
+    .map_err(|e| ApiError::from(e.to_string()))?
+    .map_err(|e| ApiError::from(e.to_string()))
+}
+
+/// Payload of the `decrypt-verify-progress` event.
+#[derive(Serialize, Clone)]
+struct VerifyProgress {
+    output: String,
+    processed: u64,
+    total: u64,
+}
+
+/// Re-hashes a decrypted output against the manifest written beside it when it was extracted
+/// and reports the files that drifted.
+#[command]
+pub async fn verify_decrypted_output_cmd(window: Window, output: String) -> ApiResult<fsdecrypt::OutputVerification> {
+    let path = PathBuf::from(output.trim());
+    if !fsdecrypt::manifest_path_for(&path).exists() {
+        return Err(ApiError::new(ErrorCode::NotFound, format!("No manifest found for {}", path.display())));
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut last_emit: Option<Instant> = None;
+        let mut report = |processed: u64, total: u64| {
+            if last_emit.is_none_or(|last| last.elapsed() >= Duration::from_millis(250)) || processed == total {
+                last_emit = Some(Instant::now());
+                let _ = window.emit(
+                    "decrypt-verify-progress",
+                    VerifyProgress {
+                        output: output.clone(),
+                        processed,
+                        total,
+                    },
+                );
+            }
+        };
+        fsdecrypt::verify_output(&path, Some(&mut report))
+    })
+    .await
+    .map_err(|e| ApiError::from(e.to_string()))?
+    .map_err(|e| ApiError::from(e.to_string()))
+}
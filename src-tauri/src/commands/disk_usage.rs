@@ -0,0 +1,316 @@
+use super::games::{dir_mtime, resolve_with_base};
+use super::state::AppState;
+use crate::config::{load_segatoools_config, paths::{segatools_root_for_game_id, segatoools_path_for_game_id}};
+use crate::games::{journal, model::{Game, LaunchMode}, store, trash::TRASH_DIR_NAME};
+use crate::error::{ApiError, ApiResult, ErrorCode};
+use crate::vhd::{list_delta_snapshots, load_vhd_config, runtime_delta_paths_for_game, vhd_config_path_for_game_id};
+use crate::backup::appdata_backup_root_for;
+use serde::Serialize;
+use tauri::{command, AppHandle, Manager};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How long a report is served from cache while the folders it measured look unchanged.
+/// Top-level mtimes miss edits deep inside a tree, so reports also age out.
+const DISK_USAGE_CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DiskUsageKind {
+    /// The game folder, minus the AppData, Option and log paths counted on their own.
+    Binaries,
+    VhdBase,
+    VhdPatch,
+    /// Runtime deltas left next to the app images and kept delta snapshots.
+    VhdRuntime,
+    Appdata,
+    Options,
+    /// AppData backups and items waiting in the delete trash.
+    Backups,
+    /// The game's timeline journal and `*.log` files in its folder.
+    Logs,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiskUsageEntry {
+    pub kind: DiskUsageKind,
+    pub size: u64,
+    pub paths: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiskUsageReport {
+    pub game_id: String,
+    pub total_size: u64,
+    /// One entry per kind, largest first.
+    pub entries: Vec<DiskUsageEntry>,
+    pub computed_at: String,
+    /// Served from the cache rather than measured by this call.
+    pub cached: bool,
+}
+
+#[derive(Clone)]
+pub(super) struct CachedDiskUsage {
+    measured_at: Instant,
+    mtimes: Vec<(PathBuf, Option<SystemTime>)>,
+    report: DiskUsageReport,
+}
+
+/// Paths of one kind, and paths inside them that another kind already counts.
+struct UsageTarget {
+    kind: DiskUsageKind,
+    paths: Vec<PathBuf>,
+    exclude: Vec<PathBuf>,
+}
+
+fn usage_size(path: &Path, exclude: &[PathBuf]) -> u64 {
+    if exclude.iter().any(|skip| skip == path) {
+        return 0;
+    }
+    let Ok(meta) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !meta.is_dir() {
+        return meta.len();
+    }
+    fs::read_dir(path)
+        .map(|entries| entries.flatten().map(|entry| usage_size(&entry.path(), exclude)).sum())
+        .unwrap_or(0)
+}
+
+fn resolve_vhd_entry(base_dir: &Path, raw: &str) -> Option<PathBuf> {
+    let raw = raw.trim();
+    (!raw.is_empty()).then(|| resolve_with_base(base_dir, raw))
+}
+
+fn log_files_in(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.is_file()
+                        && path
+                            .extension()
+                            .and_then(|ext| ext.to_str())
+                            .is_some_and(|ext| ext.eq_ignore_ascii_case("log"))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn usage_targets(game: &Game) -> ApiResult<Vec<UsageTarget>> {
+    let segatools_root = segatools_root_for_game_id(&game.id);
+    let mut targets = Vec::new();
+    let mut logs = vec![journal::journal_path_for_game(&game.id)];
+    let mut backups = vec![appdata_backup_root_for(&segatools_root)];
+
+    if matches!(game.launch_mode, LaunchMode::Vhd) {
+        let cfg = load_vhd_config(&game.id).map_err(|e| ApiError::from(e.to_string()))?;
+        let base_dir = vhd_config_path_for_game_id(&game.id)
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        let mut runtime = runtime_delta_paths_for_game(&game.id);
+        if let Ok(snapshots) = list_delta_snapshots(&game.id) {
+            runtime.extend(snapshots.snapshots.into_iter().map(|snapshot| snapshot.file));
+        }
+        let role = |kind, raw: &str| UsageTarget {
+            kind,
+            paths: resolve_vhd_entry(&base_dir, raw).into_iter().collect(),
+            exclude: Vec::new(),
+        };
+        targets.push(role(DiskUsageKind::VhdBase, &cfg.app_base_path));
+        targets.push(UsageTarget {
+            kind: DiskUsageKind::VhdPatch,
+            paths: cfg
+                .app_patch_paths
+                .iter()
+                .filter_map(|raw| resolve_vhd_entry(&base_dir, raw))
+                .collect(),
+            exclude: Vec::new(),
+        });
+        targets.push(UsageTarget {
+            kind: DiskUsageKind::VhdRuntime,
+            paths: runtime,
+            exclude: Vec::new(),
+        });
+        targets.push(role(DiskUsageKind::Appdata, &cfg.appdata_path));
+        targets.push(role(DiskUsageKind::Options, &cfg.option_path));
+    } else {
+        let root = store::game_root_dir(game).ok_or_else(|| ApiError::from("Game path missing".to_string()))?;
+        let seg_path = segatoools_path_for_game_id(&game.id).map_err(ApiError::from)?;
+        let vfs_path = |raw: &str| {
+            let raw = raw.trim();
+            (!raw.is_empty()).then(|| resolve_with_base(&root, raw))
+        };
+        let (appdata, option) = match load_segatoools_config(&seg_path) {
+            Ok(cfg) => (vfs_path(&cfg.vfs.appdata), vfs_path(&cfg.vfs.option)),
+            Err(_) => (None, None),
+        };
+        logs.extend(log_files_in(&root));
+        backups.push(root.join(TRASH_DIR_NAME));
+        backups.push(root.join("Mods").join(TRASH_DIR_NAME));
+        if let Some(option) = &option {
+            backups.push(option.join(TRASH_DIR_NAME));
+        }
+
+        let mut counted_elsewhere: Vec<PathBuf> = appdata.iter().chain(option.iter()).cloned().collect();
+        counted_elsewhere.extend(logs.iter().cloned());
+        counted_elsewhere.extend(backups.iter().cloned());
+        targets.push(UsageTarget {
+            kind: DiskUsageKind::Binaries,
+            paths: vec![root],
+            exclude: counted_elsewhere,
+        });
+        targets.push(UsageTarget {
+            kind: DiskUsageKind::Appdata,
+            paths: appdata.into_iter().collect(),
+            exclude: Vec::new(),
+        });
+        targets.push(UsageTarget {
+            kind: DiskUsageKind::Options,
+            paths: option.into_iter().collect(),
+            exclude: Vec::new(),
+        });
+    }
+
+    targets.push(UsageTarget {
+        kind: DiskUsageKind::Backups,
+        paths: backups,
+        exclude: Vec::new(),
+    });
+    targets.push(UsageTarget {
+        kind: DiskUsageKind::Logs,
+        paths: logs,
+        exclude: Vec::new(),
+    });
+    for target in &mut targets {
+        target.paths.retain(|path| path.exists());
+        target.paths.dedup();
+    }
+    Ok(targets)
+}
+
+/// Measures every path on its own thread; a game's images and folders often sit on different
+/// drives, so they are walked side by side rather than one after another.
+fn measure_targets(game_id: &str, targets: &[UsageTarget]) -> DiskUsageReport {
+    let sizes: Vec<(DiskUsageKind, u64)> = std::thread::scope(|scope| {
+        let handles: Vec<_> = targets
+            .iter()
+            .flat_map(|target| {
+                target
+                    .paths
+                    .iter()
+                    .map(move |path| scope.spawn(move || (target.kind, usage_size(path, &target.exclude))))
+            })
+            .collect();
+        handles.into_iter().filter_map(|handle| handle.join().ok()).collect()
+    });
+
+    let mut entries: Vec<DiskUsageEntry> = targets
+        .iter()
+        .map(|target| DiskUsageEntry {
+            kind: target.kind,
+            size: sizes
+                .iter()
+                .filter(|(kind, _)| *kind == target.kind)
+                .map(|(_, size)| size)
+                .sum(),
+            paths: target.paths.iter().map(|path| path.to_string_lossy().into_owned()).collect(),
+        })
+        .collect();
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.size));
+    DiskUsageReport {
+        game_id: game_id.to_string(),
+        total_size: entries.iter().map(|entry| entry.size).sum(),
+        entries,
+        computed_at: chrono::Utc::now().to_rfc3339(),
+        cached: false,
+    }
+}
+
+fn target_mtimes(targets: &[UsageTarget]) -> Vec<(PathBuf, Option<SystemTime>)> {
+    targets
+        .iter()
+        .flat_map(|target| target.paths.iter())
+        .map(|path| (path.clone(), dir_mtime(path)))
+        .collect()
+}
+
+/// Space a game takes up, broken down by what it is, so users can see what to prune.
+/// `refresh` skips the cache.
+#[command]
+pub async fn get_game_disk_usage_cmd(app: AppHandle, game_id: String, refresh: Option<bool>) -> ApiResult<DiskUsageReport> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let game = store::list_games()
+            .map_err(ApiError::from)?
+            .into_iter()
+            .find(|g| g.id == game_id)
+            .ok_or_else(|| ApiError::new(ErrorCode::NotFound, format!("Game {} not found", game_id)))?;
+        let targets = usage_targets(&game)?;
+        let mtimes = target_mtimes(&targets);
+        let state = app.state::<AppState>();
+
+        if !refresh.unwrap_or(false) {
+            let cached = state.disk_usage_cache().get(&game.id).cloned();
+            if let Some(cached) = cached {
+                if cached.mtimes == mtimes && cached.measured_at.elapsed() < DISK_USAGE_CACHE_TTL {
+                    return Ok(DiskUsageReport {
+                        cached: true,
+                        ..cached.report
+                    });
+                }
+            }
+        }
+
+        let report = measure_targets(&game.id, &targets);
+        state.disk_usage_cache().insert(
+            game.id.clone(),
+            CachedDiskUsage {
+                measured_at: Instant::now(),
+                mtimes,
+                report: report.clone(),
+            },
+        );
+        Ok(report)
+    })
+    .await
+    .map_err(|e| ApiError::from(e.to_string()))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn measures_targets_without_counting_excluded_paths_twice() {
+        let root = TempDir::new().unwrap();
+        let appdata = root.path().join("appdata");
+        fs::create_dir_all(appdata.join("SDEZ")).unwrap();
+        fs::write(root.path().join("Sinmai.exe"), vec![0u8; 100]).unwrap();
+        fs::write(appdata.join("SDEZ").join("save.bin"), vec![0u8; 40]).unwrap();
+
+        let targets = vec![
+            UsageTarget {
+                kind: DiskUsageKind::Binaries,
+                paths: vec![root.path().to_path_buf()],
+                exclude: vec![appdata.clone()],
+            },
+            UsageTarget {
+                kind: DiskUsageKind::Appdata,
+                paths: vec![appdata],
+                exclude: Vec::new(),
+            },
+        ];
+        let report = measure_targets("sdez", &targets);
+        assert_eq!(report.total_size, 140);
+        assert_eq!(report.entries[0].kind, DiskUsageKind::Binaries);
+        assert_eq!(report.entries[0].size, 100);
+        assert_eq!(report.entries[1].size, 40);
+    }
+}
@@ -0,0 +1,709 @@
+use super::remote::ensure_feature_allowed;
+use super::settings::ensure_network_allowed;
+use super::state::AppState;
+use crate::error::{ApiError, ApiResult};
+use crate::cancel::PartialArtifacts;
+use serde::{Deserialize, Serialize};
+use base64::engine::general_purpose;
+use base64::Engine as _;
+use flate2::Compression;
+use flate2::write::{DeflateEncoder, ZlibEncoder};
+use flate2::read::ZlibDecoder;
+use reqwest::blocking::Client;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE, USER_AGENT};
+use reqwest::Proxy;
+use tauri::{command, AppHandle, Emitter, Manager, State};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use std::io::{Read, Write};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadOrderRequest {
+    pub url: String,
+    pub game_id: String,
+    pub ver: String,
+    pub serial: String,
+    pub headers: Vec<String>,
+    pub proxy: Option<String>,
+    pub timeout_secs: Option<u64>,
+    #[serde(alias = "encode_request")]
+    pub encode_request: Option<bool>,
+}
+
+#[derive(Serialize)]
+pub struct DownloadOrderResponse {
+    pub raw: String,
+    pub decoded: String,
+    pub decode_error: Option<String>,
+    pub status_code: u16,
+    pub status_text: String,
+    pub content_length: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct DownloadOrderDownloadItem {
+    pub url: String,
+    pub filename: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct DownloadOrderDownloadResult {
+    pub url: String,
+    pub filename: String,
+    pub path: String,
+}
+
+#[derive(Serialize, Clone)]
+pub struct DownloadOrderProgress {
+    pub percent: f64,
+    pub current_file: usize,
+    pub total_files: usize,
+    pub filename: String,
+    pub downloaded: u64,
+    pub total: Option<u64>,
+}
+
+fn sanitize_filename(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    for ch in name.chars() {
+        let is_invalid = matches!(ch, '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*')
+            || ch.is_control();
+        if is_invalid {
+            result.push('_');
+        } else {
+            result.push(ch);
+        }
+    }
+    let trimmed = result.trim().trim_end_matches('.');
+    if trimmed.is_empty() {
+        "download".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+fn unique_filename(base: &str, used: &mut HashSet<String>, dir: &Path) -> String {
+    if !used.contains(base) && !dir.join(base).exists() {
+        used.insert(base.to_string());
+        return base.to_string();
+    }
+    let path = Path::new(base);
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(base);
+    let ext = path.extension().and_then(|s| s.to_str());
+    let mut index = 1;
+    loop {
+        let candidate = if let Some(ext) = ext {
+            format!("{}-{}.{}", stem, index, ext)
+        } else {
+            format!("{}-{}", stem, index)
+        };
+        if !used.contains(&candidate) && !dir.join(&candidate).exists() {
+            used.insert(candidate.clone());
+            return candidate;
+        }
+        index += 1;
+    }
+}
+
+fn decode_http_chunked(body: &[u8]) -> Result<Vec<u8>, String> {
+    let mut cursor = 0usize;
+    let mut decoded = Vec::with_capacity(body.len());
+
+    loop {
+        let line_end_rel = body[cursor..]
+            .windows(2)
+            .position(|window| window == b"\r\n")
+            .ok_or_else(|| "Invalid chunked response: missing chunk size terminator".to_string())?;
+        let line_end = cursor + line_end_rel;
+        let size_line = std::str::from_utf8(&body[cursor..line_end])
+            .map_err(|e| format!("Invalid chunked response: {}", e))?;
+        let size_token = size_line.split(';').next().unwrap_or("").trim();
+        let chunk_size = usize::from_str_radix(size_token, 16)
+            .map_err(|e| format!("Invalid chunk size '{}': {}", size_token, e))?;
+        cursor = line_end + 2;
+
+        if chunk_size == 0 {
+            if cursor + 2 <= body.len() && &body[cursor..cursor + 2] == b"\r\n" {
+                return Ok(decoded);
+            }
+            while cursor < body.len() {
+                let trailer_end_rel = body[cursor..]
+                    .windows(2)
+                    .position(|window| window == b"\r\n")
+                    .ok_or_else(|| "Invalid chunked response: unterminated trailer".to_string())?;
+                let trailer_end = cursor + trailer_end_rel;
+                cursor = trailer_end + 2;
+                if trailer_end_rel == 0 {
+                    return Ok(decoded);
+                }
+            }
+            return Ok(decoded);
+        }
+
+        let chunk_end = cursor
+            .checked_add(chunk_size)
+            .ok_or_else(|| "Invalid chunked response: chunk size overflow".to_string())?;
+        if chunk_end > body.len() {
+            return Err("Invalid chunked response: chunk exceeds body length".to_string());
+        }
+        decoded.extend_from_slice(&body[cursor..chunk_end]);
+        cursor = chunk_end;
+
+        if cursor + 2 > body.len() || &body[cursor..cursor + 2] != b"\r\n" {
+            return Err("Invalid chunked response: missing chunk terminator".to_string());
+        }
+        cursor += 2;
+    }
+}
+
+#[command]
+pub async fn download_order_fetch_text_cmd(
+    app: AppHandle,
+    url: String,
+    user_agent: Option<String>,
+    proxy: Option<String>,
+) -> ApiResult<String> {
+    ensure_network_allowed(&app)?;
+    ensure_feature_allowed(&app, "download_order")?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let debug_logs = cfg!(debug_assertions)
+            || std::env::var_os("CONFIGARC_DEBUG_DOWNLOAD_ORDER").is_some();
+        let trimmed = url.trim();
+        if trimmed.is_empty() {
+            return Err(("URL is required".to_string()).into());
+        }
+        let user_agent = user_agent
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string);
+        let proxy = proxy
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string);
+        if debug_logs {
+            let ua_log = user_agent.as_deref().unwrap_or("<none>");
+            let proxy_log = proxy.as_deref().unwrap_or("<none>");
+            eprintln!(
+                "[download_order] fetch_instruction url={} ua={} proxy={}",
+                trimmed, ua_log, proxy_log
+            );
+        }
+
+        let mut builder = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .connect_timeout(Duration::from_secs(10))
+            .http1_only()
+            .no_proxy();
+
+        if let Some(p) = proxy {
+            builder = builder.proxy(Proxy::all(p).map_err(|e| ApiError::from(e.to_string()))?);
+        }
+
+        let client = builder
+            .danger_accept_invalid_certs(true)
+            .build()
+            .map_err(|e| ApiError::from(e.to_string()))?;
+        
+        let mut request = client.get(trimmed);
+        if let Some(agent) = user_agent {
+            request = request.header(USER_AGENT, HeaderValue::from_str(&agent).map_err(|e| ApiError::from(e.to_string()))?);
+        }
+        let mut resp = request
+            .send()
+            .map_err(|e| ApiError::from(e.to_string()))?
+            .error_for_status()
+            .map_err(|e| ApiError::from(e.to_string()))?;
+        let mut buffer = Vec::new();
+        resp.read_to_end(&mut buffer).map_err(|e| ApiError::from(e.to_string()))?;
+        Ok(String::from_utf8_lossy(&buffer).to_string())
+    })
+    .await
+    .map_err(|e| ApiError::from(e.to_string()))?
+}
+
+#[command]
+pub fn download_order_cancel_cmd(state: State<'_, AppState>) -> ApiResult<()> {
+    state.cancel_operations("download");
+    Ok(())
+}
+
+/// Downloads `items` into the download folder. Cancelling deletes the file being written;
+/// files already finished are kept.
+#[command]
+pub async fn download_order_download_files_cmd(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    items: Vec<DownloadOrderDownloadItem>,
+    user_agent: Option<String>,
+    proxy: Option<String>,
+    operation_id: Option<String>,
+) -> ApiResult<Vec<DownloadOrderDownloadResult>> {
+    ensure_network_allowed(&app)?;
+    ensure_feature_allowed(&app, "download_order")?;
+    let operation = state.begin_cancellable("download", operation_id, false)?;
+    tauri::async_runtime::spawn_blocking(move || -> ApiResult<Vec<DownloadOrderDownloadResult>> {
+        if items.is_empty() {
+            return Err(("No files selected".to_string()).into());
+        }
+        let download_dir = app
+            .path()
+            .download_dir()
+            .map_err(|e| ApiError::from(e.to_string()))?;
+        if !download_dir.exists() {
+            fs::create_dir_all(&download_dir).map_err(|e| ApiError::from(e.to_string()))?;
+        }
+
+        let user_agent = user_agent
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string);
+        let proxy = proxy
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string);
+
+        let mut builder = Client::builder()
+            .timeout(Duration::from_secs(120))
+            .connect_timeout(Duration::from_secs(10))
+            .http1_only()
+            .no_proxy();
+
+        if let Some(p) = proxy {
+            builder = builder.proxy(Proxy::all(p).map_err(|e| ApiError::from(e.to_string()))?);
+        }
+
+        let client = builder
+            .build()
+            .map_err(|e| ApiError::from(e.to_string()))?;
+        let mut used_names = HashSet::new();
+        let mut results = Vec::with_capacity(items.len());
+        let total_files = items.len();
+        let is_cancelled = || operation.token().is_cancelled();
+
+        for (index, item) in items.into_iter().enumerate() {
+            if is_cancelled() {
+                return Err(("Download cancelled".to_string()).into());
+            }
+            let url = item.url.trim().to_string();
+            if url.is_empty() {
+                return Err(("URL is required".to_string()).into());
+            }
+            let mut name = item
+                .filename
+                .as_deref()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(sanitize_filename)
+                .unwrap_or_else(|| {
+                    reqwest::Url::parse(&url)
+                        .ok()
+                        .and_then(|parsed| {
+                            parsed
+                                .path_segments()
+                                .and_then(|segments| segments.last().map(str::to_string))
+                        })
+                        .map(|name| sanitize_filename(&name))
+                        .unwrap_or_else(|| format!("download-{}", index + 1))
+                });
+            name = unique_filename(&name, &mut used_names, &download_dir);
+            let path = download_dir.join(&name);
+
+            let mut request = client.get(&url);
+            if let Some(ref agent) = user_agent {
+                request = request.header(USER_AGENT, HeaderValue::from_str(agent).map_err(|e| ApiError::from(e.to_string()))?);
+            }
+
+            let mut resp = request
+                .send()
+                .map_err(|e| ApiError::from(e.to_string()))?
+                .error_for_status()
+                .map_err(|e| ApiError::from(e.to_string()))?;
+            let total = resp.content_length();
+            // Declared before the file so the file is closed by the time the guard deletes it.
+            let mut partial = PartialArtifacts::new();
+            let mut file = fs::File::create(&path).map_err(|e| ApiError::from(e.to_string()))?;
+            partial.track(&path);
+            let mut downloaded: u64 = 0;
+            let mut buffer = [0u8; 64 * 1024];
+            let mut last_emit = Instant::now();
+            let emit_progress = |done: bool,
+                                 downloaded: u64,
+                                 total: Option<u64>,
+                                 name: &str,
+                                 current_file: usize| {
+                let file_progress = match total {
+                    Some(total) if total > 0 => (downloaded as f64) / (total as f64),
+                    _ => {
+                        if done { 1.0 } else { 0.0 }
+                    }
+                };
+                let overall = ((current_file - 1) as f64 + file_progress) / (total_files as f64);
+                let percent = (overall * 100.0).clamp(0.0, 100.0);
+                let payload = DownloadOrderProgress {
+                    percent,
+                    current_file,
+                    total_files,
+                    filename: name.to_string(),
+                    downloaded,
+                    total,
+                };
+                let _ = app.emit("download-order-progress", payload);
+            };
+
+            let current_file = index + 1;
+            emit_progress(false, downloaded, total, &name, current_file);
+
+            loop {
+                let read = resp.read(&mut buffer).map_err(|e| ApiError::from(e.to_string()))?;
+                if read == 0 {
+                    break;
+                }
+                file.write_all(&buffer[..read]).map_err(|e| ApiError::from(e.to_string()))?;
+                downloaded = downloaded.saturating_add(read as u64);
+                if is_cancelled() {
+                    return Err(("Download cancelled".to_string()).into());
+                }
+                if last_emit.elapsed() >= Duration::from_millis(120) {
+                    emit_progress(false, downloaded, total, &name, current_file);
+                    last_emit = Instant::now();
+                }
+            }
+            drop(file);
+            partial.keep();
+            emit_progress(true, downloaded, total, &name, current_file);
+
+            results.push(DownloadOrderDownloadResult {
+                url,
+                filename: name,
+                path: path.to_string_lossy().into_owned(),
+            });
+        }
+
+        Ok(results)
+    })
+    .await
+    .map_err(|e| ApiError::from(e.to_string()))?
+}
+
+#[command]
+pub async fn download_order_cmd(app: AppHandle, payload: DownloadOrderRequest) -> ApiResult<DownloadOrderResponse> {
+    ensure_network_allowed(&app)?;
+    ensure_feature_allowed(&app, "download_order")?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let debug_logs = cfg!(debug_assertions)
+            || std::env::var_os("CONFIGARC_DEBUG_DOWNLOAD_ORDER").is_some();
+        let url = payload.url.trim().to_string();
+        if url.is_empty() {
+            return Err(("URL is required".to_string()).into());
+        }
+        let game_id = payload.game_id.trim().to_string();
+        if game_id.is_empty() {
+            return Err(("gameId is required".to_string()).into());
+        }
+        let ver = payload.ver.trim().to_string();
+        if ver.is_empty() {
+            return Err(("ver is required".to_string()).into());
+        }
+        let serial = payload.serial.trim().to_string();
+        if serial.is_empty() {
+            return Err(("serial is required".to_string()).into());
+        }
+
+        let encode_request = payload.encode_request.unwrap_or(true);
+        let timeout_secs = payload.timeout_secs.unwrap_or(15);
+        let proxy = payload
+            .proxy
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string);
+        let header_lines = payload.headers;
+
+        if debug_logs {
+            eprintln!(
+                "[download_order] request url={} game_id={} ver={} serial={} encode_request={} timeout_secs={} proxy={}",
+                url,
+                game_id,
+                ver,
+                serial,
+                encode_request,
+                timeout_secs,
+                proxy.as_deref().unwrap_or("<none>")
+            );
+        }
+
+        let query = format!("game_id={}&ver={}&serial={}", game_id, ver, serial);
+        let compression_level = Compression::new(6);
+        let encode_zlib = |input: &str| -> ApiResult<String> {
+            let mut encoder = ZlibEncoder::new(Vec::new(), compression_level);
+            encoder.write_all(input.as_bytes()).map_err(|e| ApiError::from(e.to_string()))?;
+            let compressed = encoder.finish().map_err(|e| ApiError::from(e.to_string()))?;
+            Ok(general_purpose::STANDARD.encode(compressed))
+        };
+        let encode_deflate = |input: &str| -> ApiResult<String> {
+            let mut encoder = DeflateEncoder::new(Vec::new(), compression_level);
+            encoder.write_all(input.as_bytes()).map_err(|e| ApiError::from(e.to_string()))?;
+            let compressed = encoder.finish().map_err(|e| ApiError::from(e.to_string()))?;
+            Ok(general_purpose::STANDARD.encode(compressed))
+        };
+        let (primary_body, primary_label) = if encode_request {
+            (encode_zlib(&query)?, "zlib")
+        } else {
+            (query.clone(), "plain")
+        };
+
+        let timeout = Duration::from_secs(timeout_secs);
+        let mut builder = Client::builder()
+            .timeout(timeout)
+            .connect_timeout(Duration::from_secs(10))
+            .no_proxy();
+        if let Some(proxy) = proxy.as_deref() {
+            builder = builder.proxy(Proxy::all(proxy).map_err(|e| ApiError::from(e.to_string()))?);
+        }
+        let client = builder.build().map_err(|e| ApiError::from(e.to_string()))?;
+
+        let mut headers = HeaderMap::new();
+        let mut has_content_type = false;
+        let mut has_user_agent = false;
+        for raw in header_lines {
+            let line = raw.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (name, value) = line
+                .split_once(':')
+                .ok_or_else(|| format!("Invalid header: {}", line))?;
+            let name = name.trim();
+            let value = value.trim();
+            if name.is_empty() || value.is_empty() {
+                return Err((format!("Invalid header: {}", line)).into());
+            }
+            let header_name = HeaderName::from_bytes(name.as_bytes()).map_err(|e| ApiError::from(e.to_string()))?;
+            let header_value = HeaderValue::from_str(value).map_err(|e| ApiError::from(e.to_string()))?;
+            if header_name == CONTENT_TYPE {
+                has_content_type = true;
+            }
+            if header_name == USER_AGENT {
+                has_user_agent = true;
+            }
+            headers.insert(header_name, header_value);
+        }
+        if !has_content_type {
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/x-www-form-urlencoded"));
+        }
+        if !has_user_agent {
+            headers.insert(USER_AGENT, HeaderValue::from_static("ALL.Net"));
+        }
+        // DownloadOrder requires Pragma: DFI; force it to avoid empty responses.
+        headers.insert(HeaderName::from_static("pragma"), HeaderValue::from_static("DFI"));
+
+        if debug_logs {
+            let header_dump = headers
+                .iter()
+                .map(|(name, value)| {
+                    let value = value.to_str().unwrap_or("<binary>");
+                    format!("{}: {}", name.as_str(), value)
+                })
+                .collect::<Vec<_>>()
+                .join("; ");
+            eprintln!("[download_order] headers {}", header_dump);
+        }
+
+        let send_request = |body: &str, label: &str| -> ApiResult<(u16, String, Option<u64>, String)> {
+            if debug_logs {
+                let body_head = body.chars().take(80).collect::<String>();
+                eprintln!(
+                    "[download_order] sending {} body_len={} body_head={}",
+                    label,
+                    body.len(),
+                    body_head
+                );
+            }
+
+            if proxy.is_none() {
+                // Use raw TCP to ensure header casing (Pragma: DFI) which reqwest/hyper lowercases
+                use std::net::TcpStream;
+                use std::io::{Read, Write};
+                
+                let parsed_url = reqwest::Url::parse(&url).map_err(|e| ApiError::from(e.to_string()))?;
+                let host = parsed_url.host_str().ok_or("Invalid host")?;
+                let port = parsed_url.port_or_known_default().unwrap_or(80);
+                let path = parsed_url.path();
+                
+                let addr = format!("{}:{}", host, port);
+                let mut stream = TcpStream::connect(&addr).map_err(|e| format!("Connection failed: {}", e))?;
+                stream.set_read_timeout(Some(timeout)).ok();
+                stream.set_write_timeout(Some(timeout)).ok();
+                
+                let request = format!(
+                    "POST {} HTTP/1.1\r\n\
+                     Host: {}\r\n\
+                     User-Agent: ALL.Net\r\n\
+                     Pragma: DFI\r\n\
+                     Content-Type: application/x-www-form-urlencoded\r\n\
+                     Content-Length: {}\r\n\
+                     Connection: close\r\n\
+                     \r\n\
+                     {}",
+                    path, host, body.len(), body
+                );
+                
+                stream.write_all(request.as_bytes()).map_err(|e| ApiError::from(e.to_string()))?;
+                
+                let mut response_bytes = Vec::new();
+                stream.read_to_end(&mut response_bytes).map_err(|e| ApiError::from(e.to_string()))?;
+                
+                let header_end = response_bytes
+                    .windows(4)
+                    .position(|window| window == b"\r\n\r\n")
+                    .ok_or_else(|| ApiError::from("Invalid HTTP response: missing header separator".to_string()))?;
+                let header_part = String::from_utf8_lossy(&response_bytes[..header_end]).to_string();
+                let body_bytes = &response_bytes[header_end + 4..];
+                
+                let status_line = header_part.lines().next().unwrap_or("");
+                let mut status_parts = status_line.split_whitespace();
+                let _http_ver = status_parts.next();
+                let status_code_str = status_parts.next().unwrap_or("0");
+                let status_code: u16 = status_code_str.parse().unwrap_or(0);
+                let status_text = status_parts.collect::<Vec<_>>().join(" ");
+                
+                let content_length = header_part.lines()
+                    .find(|l| l.to_lowercase().starts_with("content-length:"))
+                    .and_then(|l| l.split(':').nth(1))
+                    .and_then(|v| v.trim().parse::<u64>().ok());
+                let is_chunked = header_part.lines().any(|line| {
+                    let lower = line.to_ascii_lowercase();
+                    lower.starts_with("transfer-encoding:") && lower.contains("chunked")
+                });
+                let decoded_body = if is_chunked {
+                    decode_http_chunked(body_bytes)
+                        .map_err(ApiError::from)?
+                } else {
+                    body_bytes.to_vec()
+                };
+                let body_part = String::from_utf8_lossy(&decoded_body).to_string();
+
+                if debug_logs {
+                     eprintln!(
+                        "[download_order] raw response status={} len={} chunked={}",
+                        status_code,
+                        body_part.len(),
+                        is_chunked
+                    );
+                }
+
+                Ok((status_code, status_text, content_length, body_part))
+            } else {
+                let response = client
+                    .post(&url)
+                    .headers(headers.clone())
+                    .body(body.to_string())
+                    .send()
+                    .map_err(|e| ApiError::from(e.to_string()))?;
+                let status = response.status();
+                let status_code = status.as_u16();
+                let status_text = status.canonical_reason().unwrap_or("").to_string();
+                let content_length = response.content_length();
+                if debug_logs {
+                    let header_dump = response
+                        .headers()
+                        .iter()
+                        .map(|(name, value)| {
+                            let value = value.to_str().unwrap_or("<binary>");
+                            format!("{}: {}", name.as_str(), value)
+                        })
+                        .collect::<Vec<_>>()
+                        .join("; ");
+                    eprintln!(
+                        "[download_order] response url={} version={:?} status={} {} content_length={:?} headers={}",
+                        response.url(),
+                        response.version(),
+                        status_code,
+                        status_text,
+                        content_length,
+                        header_dump
+                    );
+                }
+                let text = response.text().map_err(|e| ApiError::from(e.to_string()))?;
+                Ok((status_code, status_text, content_length, text))
+            }
+        };
+
+        let (mut status_code, mut status_text, mut content_length, mut text) =
+            send_request(&primary_body, primary_label)?;
+
+        if encode_request && text.trim().is_empty() {
+            if debug_logs {
+                eprintln!("[download_order] empty response, retrying with raw deflate");
+            }
+            let fallback_body = encode_deflate(&query)?;
+            let (fallback_status, fallback_status_text, fallback_length, fallback_raw) =
+                send_request(&fallback_body, "deflate_raw")?;
+            status_code = fallback_status;
+            status_text = fallback_status_text;
+            content_length = fallback_length;
+            text = fallback_raw;
+        }
+        let trimmed = text.trim();
+        let mut decoded_text = String::new();
+        let mut decode_error = None;
+        if !trimmed.is_empty() {
+            match general_purpose::STANDARD.decode(trimmed) {
+                Ok(decoded) => {
+                    let mut decoder = ZlibDecoder::new(decoded.as_slice());
+                    let mut output = Vec::new();
+                    if let Err(err) = decoder.read_to_end(&mut output) {
+                        decode_error = Some(err.to_string());
+                    } else {
+                        decoded_text = String::from_utf8_lossy(&output).to_string();
+                    }
+                }
+                Err(err) => {
+                    decode_error = Some(err.to_string());
+                }
+            }
+        }
+        if debug_logs {
+            let raw_head = text.chars().take(120).collect::<String>();
+            eprintln!(
+                "[download_order] response status={} {} content_length={:?} raw_len={} raw_head={}",
+                status_code,
+                status_text,
+                content_length,
+                text.len(),
+                raw_head
+            );
+            if let Some(ref err) = decode_error {
+                eprintln!("[download_order] decode_error={}", err);
+            }
+            if !decoded_text.is_empty() {
+                let decoded_head = decoded_text.chars().take(120).collect::<String>();
+                eprintln!(
+                    "[download_order] decoded_len={} decoded_head={}",
+                    decoded_text.len(),
+                    decoded_head
+                );
+            }
+        }
+        Ok(DownloadOrderResponse {
+            raw: text,
+            decoded: decoded_text,
+            decode_error,
+            status_code,
+            status_text,
+            content_length,
+        })
+    })
+    .await
+    .map_err(|e| ApiError::from(e.to_string()))?
+}
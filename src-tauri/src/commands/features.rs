@@ -0,0 +1,254 @@
+//! Optional subsystems of this build. Commands of a feature that was compiled out stay
+//! registered as stand-ins failing with FEATURE_DISABLED, so the frontend can call them
+//! unconditionally and hide what [`get_enabled_features_cmd`] does not list.
+#[cfg(not(all(feature = "icf", feature = "fsdecrypt", feature = "privexec")))]
+use crate::error::{ApiError, ErrorCode};
+use crate::error::ApiResult;
+use tauri::command;
+
+/// Cargo features of the launcher that are compiled into this build.
+pub(super) fn enabled_features() -> Vec<&'static str> {
+    let mut features = Vec::new();
+    if cfg!(feature = "icf") {
+        features.push("icf");
+    }
+    if cfg!(feature = "fsdecrypt") {
+        features.push("fsdecrypt");
+    }
+    if cfg!(feature = "privexec") {
+        features.push("privexec");
+    }
+    features
+}
+
+#[cfg(not(all(feature = "icf", feature = "fsdecrypt", feature = "privexec")))]
+fn feature_disabled(feature: &str) -> ApiError {
+    ApiError::new(
+        ErrorCode::FeatureDisabled,
+        format!("This build of the launcher was compiled without {} support.", feature),
+    )
+}
+
+#[command]
+pub fn get_enabled_features_cmd() -> ApiResult<Vec<&'static str>> {
+    Ok(enabled_features())
+}
+
+#[cfg(not(feature = "icf"))]
+mod without_icf {
+    use super::*;
+
+    #[command]
+    pub fn load_icf_cmd() -> ApiResult<()> {
+        Err(feature_disabled("icf"))
+    }
+
+    #[command]
+    pub fn icf_summary_cmd() -> ApiResult<()> {
+        Err(feature_disabled("icf"))
+    }
+
+    #[command]
+    pub fn save_icf_cmd() -> ApiResult<()> {
+        Err(feature_disabled("icf"))
+    }
+
+    #[command]
+    pub fn icf_audit_cmd() -> ApiResult<()> {
+        Err(feature_disabled("icf"))
+    }
+
+    #[command]
+    pub fn propose_icf_options_cmd() -> ApiResult<()> {
+        Err(feature_disabled("icf"))
+    }
+
+    #[command]
+    pub fn apply_icf_options_cmd() -> ApiResult<()> {
+        Err(feature_disabled("icf"))
+    }
+
+    #[command]
+    pub fn export_icf_json_cmd() -> ApiResult<()> {
+        Err(feature_disabled("icf"))
+    }
+
+    #[command]
+    pub fn import_icf_json_cmd() -> ApiResult<()> {
+        Err(feature_disabled("icf"))
+    }
+
+    #[command]
+    pub fn list_icf_backups_cmd() -> ApiResult<()> {
+        Err(feature_disabled("icf"))
+    }
+
+    #[command]
+    pub fn restore_icf_backup_cmd() -> ApiResult<()> {
+        Err(feature_disabled("icf"))
+    }
+
+    #[command]
+    pub fn list_icf_keys_cmd() -> ApiResult<()> {
+        Err(feature_disabled("icf"))
+    }
+
+    #[command]
+    pub fn set_icf_key_cmd() -> ApiResult<()> {
+        Err(feature_disabled("icf"))
+    }
+
+    #[command]
+    pub fn delete_icf_key_cmd() -> ApiResult<()> {
+        Err(feature_disabled("icf"))
+    }
+
+    #[command]
+    pub fn import_icf_keys_cmd() -> ApiResult<()> {
+        Err(feature_disabled("icf"))
+    }
+
+    #[command]
+    pub fn export_icf_keys_cmd() -> ApiResult<()> {
+        Err(feature_disabled("icf"))
+    }
+}
+
+#[cfg(not(feature = "icf"))]
+pub use without_icf::*;
+
+#[cfg(not(feature = "fsdecrypt"))]
+mod without_fsdecrypt {
+    use super::*;
+
+    #[command]
+    pub fn pick_decrypt_files_cmd() -> ApiResult<()> {
+        Err(feature_disabled("fsdecrypt"))
+    }
+
+    #[command]
+    pub fn load_fsdecrypt_keys_cmd() -> ApiResult<()> {
+        Err(feature_disabled("fsdecrypt"))
+    }
+
+    #[command]
+    pub fn get_fsdecrypt_key_store_cmd() -> ApiResult<()> {
+        Err(feature_disabled("fsdecrypt"))
+    }
+
+    #[command]
+    pub fn import_fsdecrypt_keys_cmd() -> ApiResult<()> {
+        Err(feature_disabled("fsdecrypt"))
+    }
+
+    #[command]
+    pub fn set_fsdecrypt_game_key_cmd() -> ApiResult<()> {
+        Err(feature_disabled("fsdecrypt"))
+    }
+
+    #[command]
+    pub fn delete_fsdecrypt_game_key_cmd() -> ApiResult<()> {
+        Err(feature_disabled("fsdecrypt"))
+    }
+
+    #[command]
+    pub fn clear_fsdecrypt_key_store_cmd() -> ApiResult<()> {
+        Err(feature_disabled("fsdecrypt"))
+    }
+
+    #[command]
+    pub fn decrypt_game_files_cmd() -> ApiResult<()> {
+        Err(feature_disabled("fsdecrypt"))
+    }
+
+    #[command]
+    pub fn inspect_container_cmd() -> ApiResult<()> {
+        Err(feature_disabled("fsdecrypt"))
+    }
+
+    #[command]
+    pub fn build_option_container_cmd() -> ApiResult<()> {
+        Err(feature_disabled("fsdecrypt"))
+    }
+
+    #[command]
+    pub fn verify_decrypted_output_cmd() -> ApiResult<()> {
+        Err(feature_disabled("fsdecrypt"))
+    }
+}
+
+#[cfg(not(feature = "fsdecrypt"))]
+pub use without_fsdecrypt::*;
+
+#[cfg(not(feature = "privexec"))]
+mod without_privexec {
+    use super::*;
+    use tauri::AppHandle;
+
+    /// Nothing to supervise without the broker.
+    pub fn start_broker_supervisor(_app: AppHandle) {}
+
+    #[command]
+    pub fn broker_status_cmd() -> ApiResult<()> {
+        Err(feature_disabled("privexec"))
+    }
+
+    #[command]
+    pub fn set_resident_broker_cmd() -> ApiResult<()> {
+        Err(feature_disabled("privexec"))
+    }
+
+    #[command]
+    pub fn privexec_get_paths_cmd() -> ApiResult<()> {
+        Err(feature_disabled("privexec"))
+    }
+
+    #[command]
+    pub fn privexec_execute_cmd() -> ApiResult<()> {
+        Err(feature_disabled("privexec"))
+    }
+
+    #[command]
+    pub fn privexec_execute_batch_cmd() -> ApiResult<()> {
+        Err(feature_disabled("privexec"))
+    }
+
+    #[command]
+    pub fn privexec_apply_policy_update_cmd() -> ApiResult<()> {
+        Err(feature_disabled("privexec"))
+    }
+
+    #[command]
+    pub fn privexec_bootstrap_roots_cmd() -> ApiResult<()> {
+        Err(feature_disabled("privexec"))
+    }
+
+    #[command]
+    pub fn privexec_bootstrap_policy_cmd() -> ApiResult<()> {
+        Err(feature_disabled("privexec"))
+    }
+
+    #[command]
+    pub fn list_hosts_overrides_cmd() -> ApiResult<()> {
+        Err(feature_disabled("privexec"))
+    }
+
+    #[command]
+    pub fn add_hosts_override_cmd() -> ApiResult<()> {
+        Err(feature_disabled("privexec"))
+    }
+
+    #[command]
+    pub fn delete_hosts_override_cmd() -> ApiResult<()> {
+        Err(feature_disabled("privexec"))
+    }
+
+    /// Without the broker the launcher never adds hosts overrides.
+    pub fn clear_hosts_overrides_for_game(_app: &AppHandle, _game_id: &str) -> ApiResult<bool> {
+        Ok(false)
+    }
+}
+
+#[cfg(not(feature = "privexec"))]
+pub use without_privexec::*;
+
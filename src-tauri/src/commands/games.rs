@@ -0,0 +1,852 @@
+use super::config::{
+    active_slot_path, gen_profile_id, live_segatoools_or_default, record_segatoools_provenance,
+    sanitize_segatoools_for_game, slot_missing,
+};
+use super::launch::apply_profile_files;
+use super::state::AppState;
+use super::vhd::{build_vhd_game, detect_vfs_paths_on_drive, detect_vhd_files_in_dir, VfsResolved};
+use crate::config::{
+    diff::{diff_segatoools_configs, ConfigChange},
+    load_segatoools_config,
+    paths::{
+        active_game_dir, get_active_game_id, segatools_root_for_game_id, segatoools_path_for_active, set_active_game_id,
+    },
+    profiles::{list_profiles, load_profile, save_profile, save_profile_for_game, ConfigProfile},
+    provenance::KeyOrigin,
+    retain_sections, save_segatoools_config as persist_segatoools_config,
+    segatools::{SegatoolsConfig, VfsConfig},    slots,
+};
+use crate::games::{
+    caches::{clear_game_caches, CacheReport},
+    cleanup::{cleanup_game, CleanupOptions, CleanupReport},
+    detection::{self, canonical_game_key, DetectionRule, VersionDefaults},
+    journal::{self, JournalEntry},
+    model::{Game, LaunchMode, SandboxOptions},
+    store,
+    watchdog::WatchdogOptions,
+};
+use crate::display::{apply_display_preset, list_monitors, validate_display_preset, DisplayPreset, MonitorInfo};
+use crate::gpu::{GpuPreference, GpuUsage};
+use crate::error::{ApiError, ApiResult, ErrorCode};
+use crate::trusted::{import_existing_install, ImportedInstall};
+use crate::platform;
+use crate::vhd::{active_mount_points, VhdConfig};
+use crate::cancel::CANCELLED_MESSAGE;
+use serde::Serialize;
+use tauri::{command, State};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub(super) fn rule_for_game_key(key: &str) -> Option<DetectionRule> {
+    detection::load_rules_or_bundled()
+        .into_iter()
+        .find(|rule| canonical_game_key(&rule.name) == key)
+}
+
+/// Version overrides for the stored game called `name`, preferring the active game when
+/// several installs of the same title exist.
+pub(super) fn version_defaults_for_game(name: &str) -> Option<VersionDefaults> {
+    let key = canonical_game_key(name);
+    let active = get_active_game_id().ok().flatten();
+    let games = store::list_games().ok()?;
+    let version = games
+        .iter()
+        .filter(|g| g.detected_version.is_some() && canonical_game_key(&g.name) == key)
+        .min_by_key(|g| active.as_deref() != Some(g.id.as_str()))?
+        .detected_version
+        .clone();
+    rule_for_game_key(&key)?.version_defaults(version.as_deref()).cloned()
+}
+
+/// Records `version` on `game`. When it differs from the last detected version, launch args
+/// still equal to the old version's defaults are replaced with the new version's, so
+/// hand-edited args survive an update. Returns whether `game` changed.
+pub(super) fn apply_detected_version(game: &mut Game, rule_name: &str, version: &str) -> bool {
+    if game.detected_version.as_deref() == Some(version) {
+        return false;
+    }
+    if let Some(rule) = rule_for_game_key(&canonical_game_key(rule_name)) {
+        if game.launch_args == rule.default_args_for(game.detected_version.as_deref()) {
+            game.launch_args = rule.default_args_for(Some(version));
+        }
+    }
+    let previous = game.detected_version.replace(version.to_string());
+    journal::record(
+        &game.id,
+        JournalEntry::new("version_change", format!("Detected {} version {}", game.name, version))
+            .with_details(serde_json::json!({ "previous": previous, "current": version })),
+    );
+    true
+}
+
+pub(super) struct DetectedGameInfo {
+    pub(super) name: String,
+    pub(super) executable_path: String,
+    pub(super) working_dir: String,
+    pub(super) launch_args: Vec<String>,
+}
+
+pub(super) fn detect_game_in_dir(dir: &Path) -> Option<DetectedGameInfo> {
+    let rules = detection::load_rules_or_bundled();
+    let detected = detection::detect_in_dir(dir, &rules)?;
+    Some(DetectedGameInfo {
+        name: detected.rule.name,
+        executable_path: detected.executable_path.to_string_lossy().to_string(),
+        working_dir: dir.to_string_lossy().to_string(),
+        launch_args: detected.rule.default_args,
+    })
+}
+
+fn detect_game_with_fallback(dir: &Path) -> Option<DetectedGameInfo> {
+    if let Some(detected) = detect_game_in_dir(dir) {
+        return Some(detected);
+    }
+
+    let package_bin = dir.join("package").join("bin");
+    if let Some(detected) = detect_game_in_dir(&package_bin) {
+        return Some(detected);
+    }
+
+    let mut subdirs = Vec::new();
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                subdirs.push(path);
+            }
+        }
+    }
+    subdirs.sort_by_key(|p| p.to_string_lossy().to_lowercase());
+
+    for subdir in subdirs {
+        if let Some(detected) = detect_game_in_dir(&subdir) {
+            return Some(detected);
+        }
+    }
+
+    None
+}
+
+fn build_folder_game(detected: DetectedGameInfo) -> Game {
+    Game {
+        id: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis().to_string(),
+        name: detected.name,
+        executable_path: detected.executable_path,
+        working_dir: Some(detected.working_dir),
+        launch_args: detected.launch_args,
+        enabled: true,
+        tags: vec![],
+        launch_mode: LaunchMode::Folder,
+        sandbox: SandboxOptions::default(),
+        gpu_preference: GpuPreference::default(),
+        display_presets: vec![],
+        display_preset: None,
+        pre_launch: vec![],
+        post_exit: vec![],
+        watchdog: WatchdogOptions::default(),
+        detected_version: None,
+    }
+}
+
+fn scan_game_folder_logic(path: &str) -> ApiResult<Game> {
+    let dir = Path::new(path);
+    if !dir.exists() || !dir.is_dir() {
+        return Err(("Invalid directory".to_string()).into());
+    }
+
+    let detected = detect_game_in_dir(dir).ok_or_else(|| {
+        let rules = detection::load_rules_or_bundled();
+        format!(
+            "No supported game executable found ({})",
+            detection::supported_executables(&rules).join(", ")
+        )
+    })?;
+
+    Ok(build_folder_game(detected))
+}
+
+pub(super) fn journal_for_active(entry: JournalEntry) {
+    if let Ok(Some(game_id)) = get_active_game_id() {
+        journal::record(&game_id, entry);
+    }
+}
+
+pub(super) fn resolve_with_base(base: &Path, target: &str) -> PathBuf {
+    let raw = PathBuf::from(target);
+    if raw.is_absolute() {
+        raw
+    } else {
+        base.join(target)
+    }
+}
+
+/// Folder chosen in the picker dialog; cancelling it is reported as an error.
+pub(super) fn pick_folder_or_cancel() -> ApiResult<String> {
+    platform::pick_folder()?
+        .map(|path| path.to_string_lossy().into_owned())
+        .ok_or_else(|| ApiError::from("No folder selected".to_string()))
+}
+
+#[command]
+pub async fn pick_game_folder_cmd() -> ApiResult<Game> {
+    tauri::async_runtime::spawn_blocking(|| {
+        let path = pick_folder_or_cancel()?;
+
+        scan_game_folder_logic(&path)
+    })
+    .await
+    .map_err(|e| ApiError::from(e.to_string()))?
+}
+
+#[command]
+pub async fn pick_game_auto_cmd() -> ApiResult<AutoDetectResult> {
+    tauri::async_runtime::spawn_blocking(|| {
+        let path = pick_folder_or_cancel()?;
+
+        let dir = Path::new(&path);
+        if !dir.exists() || !dir.is_dir() {
+            return Err(("Invalid directory".to_string()).into());
+        }
+
+        auto_detect_game_in_dir(dir)
+    })
+    .await
+    .map_err(|e| ApiError::from(e.to_string()))?
+}
+
+#[derive(Serialize)]
+pub struct AutoDetectResult {
+    pub game: Game,
+    pub vhd: Option<VhdConfig>,
+}
+
+fn auto_detect_game_in_dir(dir: &Path) -> ApiResult<AutoDetectResult> {
+    if let Some(detected) = detect_game_with_fallback(dir) {
+        return Ok(AutoDetectResult {
+            game: build_folder_game(detected),
+            vhd: None,
+        });
+    }
+
+    let vhd = detect_vhd_files_in_dir(dir)?;
+    let game = build_vhd_game(dir, &vhd);
+
+    Ok(AutoDetectResult {
+        game,
+        vhd: Some(vhd),
+    })
+}
+
+#[command]
+pub fn list_games_cmd() -> ApiResult<Vec<Game>> {
+    store::list_games().map_err(|e| ApiError::from(e.to_string()))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LastGpuUsage {
+    pub timestamp: String,
+    #[serde(flatten)]
+    pub usage: GpuUsage,
+}
+
+/// GPU the game rendered on during its most recent launch where that could be determined.
+#[command]
+pub fn get_last_gpu_usage_cmd(game_id: String) -> ApiResult<Option<LastGpuUsage>> {
+    let entries = journal::read_journal(&game_id, None).map_err(ApiError::from)?;
+    Ok(entries.into_iter().rev().filter(|e| e.kind == "gpu_usage").find_map(|entry| {
+        let usage = serde_json::from_value(entry.details?).ok()?;
+        Some(LastGpuUsage {
+            timestamp: entry.timestamp,
+            usage,
+        })
+    }))
+}
+
+/// Displays attached to the desktop, for `[gfx] monitor` and display presets.
+#[command]
+pub async fn list_monitors_cmd() -> ApiResult<Vec<MonitorInfo>> {
+    tauri::async_runtime::spawn_blocking(list_monitors)
+        .await
+        .map_err(|e| ApiError::from(e.to_string()))
+}
+
+/// The display preset `game` last had applied, if it still exists.
+pub(super) fn active_display_preset(game: &Game) -> Option<&DisplayPreset> {
+    let name = game.display_preset.as_deref()?;
+    game.display_presets.iter().find(|preset| preset.name == name)
+}
+
+/// Applies the active game's display preset `name` in one step: `[gfx] monitor` and
+/// `windowed` in its segatools.ini (or `slot`), and the resolution in its launch args.
+#[command]
+pub fn apply_display_preset_cmd(state: State<'_, AppState>, name: String, slot: Option<String>) -> ApiResult<Game> {
+    let mut game = state.active_game()?;
+    let preset = game
+        .display_presets
+        .iter()
+        .find(|preset| preset.name == name)
+        .cloned()
+        .ok_or_else(|| ApiError::new(ErrorCode::NotFound, format!("{} has no display preset \"{}\"", game.name, name)))?;
+    let path = active_slot_path(slot.as_deref())?;
+    if !path.exists() {
+        return Err(slot_missing(&path));
+    }
+
+    let _guard = state.lock_config();
+    let before = live_segatoools_or_default(&path);
+    let mut cfg = load_segatoools_config(&path).map_err(|e| ApiError::from(e.to_string()))?;
+    apply_display_preset(&mut cfg, &mut game.launch_args, &preset);
+    retain_sections(&mut cfg, &["gfx".to_string()]);
+    game.display_preset = Some(preset.name.clone());
+    let result = persist_segatoools_config(&path, &cfg)
+        .map_err(|e| e.to_string())
+        .and_then(|_| store::save_game(game.clone()).map_err(|e| e.to_string()));
+    journal::record(
+        &game.id,
+        JournalEntry::new("display_preset_apply", format!("Applied display preset \"{}\"", preset.name))
+            .with_outcome(&result)
+            .with_details(serde_json::json!({ "preset": preset, "slot": slot })),
+    );
+    result.map_err(ApiError::from)?;
+    if slots::is_default(slot.as_deref()) {
+        record_segatoools_provenance(&game, &before, &live_segatoools_or_default(&path), &KeyOrigin::user());
+    }
+    Ok(game)
+}
+
+/// Effective detection rules: the bundled titles plus `configarc_detection_rules.json`.
+#[command]
+pub fn list_detection_rules_cmd() -> ApiResult<Vec<DetectionRule>> {
+    detection::load_rules().map_err(ApiError::from)
+}
+
+/// Adopts segatools that were already set up in a newly added game folder: the files move into
+/// the launcher's segatools root and the folder's segatools.ini becomes the first profile, so
+/// the game is not reported as undeployed.
+fn import_existing_segatools(game: &Game) -> ApiResult<Option<ImportedInstall>> {
+    let Some(game_dir) = store::game_root_dir(game) else {
+        return Ok(None);
+    };
+    let root = segatools_root_for_game_id(&game.id);
+    let Some(record) = import_existing_install(&game_dir, &root)? else {
+        return Ok(None);
+    };
+
+    let segatools = load_segatoools_config(&root.join("segatools.ini")).map_err(ApiError::from)?;
+    let now = chrono::Utc::now().to_rfc3339();
+    let profile = ConfigProfile {
+        id: gen_profile_id("existing"),
+        name: "Existing install".to_string(),
+        description: Some(format!("Imported from {}", record.source_dir)),
+        base_profile_id: None,
+        segatools: sanitize_segatoools_for_game(segatools, Some(&game.name)),
+        segatools_slots: BTreeMap::new(),
+        json_configs: BTreeMap::new(),
+        created_at: now.clone(),
+        updated_at: now,
+    };
+    save_profile_for_game(&profile, &game.id).map_err(ApiError::from)?;
+    Ok(Some(record))
+}
+
+#[command]
+pub fn save_game_cmd(game: Game) -> ApiResult<()> {
+    for preset in &game.display_presets {
+        validate_display_preset(preset).map_err(|e| ApiError::new(ErrorCode::InvalidInput, e))?;
+    }
+    let is_new = !store::list_games()
+        .map_err(|e| ApiError::from(e.to_string()))?
+        .iter()
+        .any(|g| g.id == game.id);
+    store::save_game(game.clone()).map_err(|e| ApiError::from(e.to_string()))?;
+
+    if is_new && matches!(game.launch_mode, LaunchMode::Folder) {
+        let imported = import_existing_segatools(&game);
+        if let Ok(Some(record)) = &imported {
+            let files: Vec<&str> = record.files.iter().map(|f| f.path.as_str()).collect();
+            journal::record(
+                &game.id,
+                JournalEntry::new("segatools_import", "Imported existing segatools install")
+                    .with_details(serde_json::json!({ "source": record.source_dir, "files": files })),
+            );
+        }
+        imported?;
+    }
+    Ok(())
+}
+
+#[command]
+pub fn delete_game_cmd(id: String) -> ApiResult<()> {
+    store::delete_game(&id).map_err(|e| ApiError::from(e.to_string()))
+}
+
+#[command]
+pub fn cleanup_game_cmd(game_id: String, options: CleanupOptions, confirm: bool) -> ApiResult<CleanupReport> {
+    cleanup_game(&game_id, &options, confirm).map_err(ApiError::from)
+}
+
+/// Lists the Unity and shader caches of one game, or all games, and deletes them when `confirm`.
+#[command]
+pub fn cleanup_game_caches_cmd(game_id: Option<String>, confirm: bool) -> ApiResult<CacheReport> {
+    clear_game_caches(game_id.as_deref(), confirm).map_err(ApiError::from)
+}
+
+#[command]
+pub fn get_game_timeline_cmd(game_id: String, limit: Option<usize>) -> ApiResult<Vec<JournalEntry>> {
+    journal::read_journal(&game_id, limit).map_err(ApiError::from)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VfsScanResult {
+    pub amfs: Option<String>,
+    pub appdata: Option<String>,
+    pub option: Option<String>,
+}
+
+const VFS_SCAN_DEFAULT_DEPTH: usize = 1;
+const VFS_SCAN_MAX_DEPTH: usize = 4;
+
+#[derive(Clone)]
+pub(super) struct CachedVfsScan {
+    depth: usize,
+    mtimes: Vec<(PathBuf, Option<SystemTime>)>,
+    result: VfsScanResult,
+}
+
+struct VfsDirInfo {
+    path: PathBuf,
+    mtime: Option<SystemTime>,
+    has_icf: bool,
+    has_appdata: bool,
+    has_option: bool,
+    subdirs: Vec<PathBuf>,
+}
+
+pub(super) fn dir_mtime(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+// One read_dir per directory covers all three folder kinds.
+fn classify_vfs_dir(path: &Path, cancel: &AtomicBool) -> Option<VfsDirInfo> {
+    if cancel.load(Ordering::SeqCst) {
+        return None;
+    }
+    let mut info = VfsDirInfo {
+        path: path.to_path_buf(),
+        mtime: dir_mtime(path),
+        has_icf: false,
+        has_appdata: false,
+        has_option: false,
+        subdirs: Vec::new(),
+    };
+    let entries = fs::read_dir(path).ok()?;
+    for entry in entries.flatten() {
+        let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+        if name.starts_with("ICF") {
+            info.has_icf = true;
+        }
+        if !entry.path().is_dir() {
+            continue;
+        }
+        if name.len() == 4 && name.starts_with('S') && name.chars().skip(1).all(|c| c.is_ascii_uppercase()) {
+            info.has_appdata = true;
+        }
+        // User requested X***, standard is A***. Support both.
+        if name.len() == 4 && (name.starts_with('X') || name.starts_with('A')) {
+            info.has_option = true;
+        }
+        info.subdirs.push(entry.path());
+    }
+    Some(info)
+}
+
+fn classify_vfs_level(dirs: &[PathBuf], cancel: &AtomicBool) -> Vec<VfsDirInfo> {
+    if dirs.is_empty() {
+        return Vec::new();
+    }
+    let workers = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+        .min(dirs.len());
+    let chunk_size = dirs.len().div_ceil(workers);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = dirs
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().filter_map(|dir| classify_vfs_dir(dir, cancel)).collect::<Vec<_>>()))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap_or_default())
+            .collect()
+    })
+}
+
+fn scan_vfs_folders(game_dir: &Path, max_depth: usize, cancel: &AtomicBool) -> ApiResult<CachedVfsScan> {
+    let mut result = VfsScanResult {
+        amfs: None,
+        appdata: None,
+        option: None,
+    };
+    let mut mtimes = vec![(game_dir.to_path_buf(), dir_mtime(game_dir))];
+    let mut level: Vec<PathBuf> = fs::read_dir(game_dir)
+        .map_err(|e| ApiError::from(e.to_string()))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+
+    for depth in 1..=max_depth {
+        if level.is_empty() {
+            break;
+        }
+        let mut infos = classify_vfs_level(&level, cancel);
+        if cancel.load(Ordering::SeqCst) {
+            return Err(CANCELLED_MESSAGE.to_string().into());
+        }
+        // Shallowest match wins; within a level the order is alphabetical so results are stable.
+        infos.sort_by(|a, b| a.path.cmp(&b.path));
+        let mut next = Vec::new();
+        for info in infos {
+            let rel = info
+                .path
+                .strip_prefix(game_dir)
+                .unwrap_or(&info.path)
+                .to_string_lossy()
+                .to_string();
+            if result.amfs.is_none() && info.has_icf {
+                result.amfs = Some(rel.clone());
+            }
+            if result.appdata.is_none() && info.has_appdata {
+                result.appdata = Some(rel.clone());
+            }
+            if result.option.is_none() && info.has_option {
+                result.option = Some(rel);
+            }
+            mtimes.push((info.path, info.mtime));
+            if depth < max_depth {
+                next.extend(info.subdirs);
+            }
+        }
+        if result.amfs.is_some() && result.appdata.is_some() && result.option.is_some() {
+            break;
+        }
+        level = next;
+    }
+
+    Ok(CachedVfsScan {
+        depth: max_depth,
+        mtimes,
+        result,
+    })
+}
+
+fn cached_vfs_scan(state: &AppState, game_dir: &Path, depth: usize) -> Option<VfsScanResult> {
+    let cache = state.vfs_scan_cache();
+    let entry = cache.get(game_dir)?;
+    if entry.depth != depth {
+        return None;
+    }
+    let fresh = entry
+        .mtimes
+        .iter()
+        .all(|(path, mtime)| dir_mtime(path) == *mtime);
+    fresh.then(|| entry.result.clone())
+}
+
+#[command]
+pub async fn scan_game_vfs_folders_cmd(
+    state: State<'_, AppState>,
+    max_depth: Option<usize>,
+    operation_id: Option<String>,
+) -> ApiResult<VfsScanResult> {
+    let game = state.active_game()?;
+    if matches!(game.launch_mode, LaunchMode::Vhd) {
+        let points = active_mount_points().unwrap_or_default();
+        let vfs = detect_vfs_paths_on_drive(&points).unwrap_or_else(|_| VfsResolved {
+            amfs: points.appdata.root().join("amfs").to_string_lossy().to_string(),
+            appdata: points.appdata.root().join("appdata").to_string_lossy().to_string(),
+            option: points.option.root().to_string_lossy().to_string(),
+        });
+        return Ok(VfsScanResult {
+            amfs: Some(vfs.amfs),
+            appdata: Some(vfs.appdata),
+            option: Some(vfs.option),
+        });
+    }
+
+    let game_dir = active_game_dir().map_err(|e| ApiError::from(e.to_string()))?;
+    let depth = max_depth
+        .unwrap_or(VFS_SCAN_DEFAULT_DEPTH)
+        .clamp(1, VFS_SCAN_MAX_DEPTH);
+    if let Some(result) = cached_vfs_scan(&state, &game_dir, depth) {
+        return Ok(result);
+    }
+
+    let operation = state.begin_cancellable("vfs_scan", operation_id, false)?;
+    let scan_dir = game_dir.clone();
+    let scan = tauri::async_runtime::spawn_blocking(move || scan_vfs_folders(&scan_dir, depth, operation.token()))
+        .await
+        .map_err(|e| ApiError::from(e.to_string()))??;
+    state.vfs_scan_cache().insert(game_dir, scan.clone());
+    Ok(scan.result)
+}
+
+#[command]
+pub fn cancel_vfs_scan_cmd(state: State<'_, AppState>) -> ApiResult<()> {
+    state.cancel_operations("vfs_scan");
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VfsDriftReason {
+    /// The key is empty.
+    Unset,
+    /// The folder does not exist.
+    Missing,
+    /// The folder exists but holds none of the data it is meant for.
+    Empty,
+}
+
+/// A `[vfs]` path that no longer points at its data.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VfsDrift {
+    pub field: String,
+    pub configured: String,
+    pub reason: VfsDriftReason,
+    /// What the scanner found instead; without one there is nothing to fix automatically.
+    pub candidate: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VfsDriftReport {
+    pub drifts: Vec<VfsDrift>,
+    /// What applying the fix changes in segatools.ini.
+    pub changes: Vec<ConfigChange>,
+}
+
+/// Compares the `[vfs]` paths against the scanner's picks. A path still holding its kind of
+/// data is left alone even if the scanner prefers another folder.
+fn vfs_drifts(base: &Path, vfs: &VfsConfig, scan: &VfsScanResult) -> Vec<VfsDrift> {
+    let fields = [
+        ("amfs", &vfs.amfs, &scan.amfs),
+        ("appdata", &vfs.appdata, &scan.appdata),
+        ("option", &vfs.option, &scan.option),
+    ];
+    let mut drifts = Vec::new();
+    for (field, configured, candidate) in fields {
+        let configured = configured.trim();
+        let reason = if configured.is_empty() {
+            VfsDriftReason::Unset
+        } else {
+            let resolved = resolve_with_base(base, configured);
+            if !resolved.is_dir() {
+                VfsDriftReason::Missing
+            } else {
+                // An unreadable folder counts as fine rather than stale.
+                let holds_data = classify_vfs_dir(&resolved, &AtomicBool::new(false)).is_none_or(|info| match field {
+                    "amfs" => info.has_icf,
+                    "appdata" => info.has_appdata,
+                    _ => info.has_option,
+                });
+                if holds_data {
+                    continue;
+                }
+                VfsDriftReason::Empty
+            }
+        };
+        let candidate = candidate
+            .as_ref()
+            .filter(|c| resolve_with_base(base, c) != resolve_with_base(base, configured))
+            .cloned();
+        drifts.push(VfsDrift {
+            field: field.to_string(),
+            configured: configured.to_string(),
+            reason,
+            candidate,
+        });
+    }
+    drifts
+}
+
+fn apply_vfs_drifts(cfg: &mut SegatoolsConfig, drifts: &[VfsDrift]) {
+    for drift in drifts {
+        let Some(candidate) = &drift.candidate else {
+            continue;
+        };
+        match drift.field.as_str() {
+            "amfs" => cfg.vfs.amfs = candidate.clone(),
+            "appdata" => cfg.vfs.appdata = candidate.clone(),
+            _ => cfg.vfs.option = candidate.clone(),
+        }
+    }
+}
+
+async fn active_vfs_drift(
+    state: State<'_, AppState>,
+    max_depth: Option<usize>,
+) -> ApiResult<(PathBuf, SegatoolsConfig, Vec<VfsDrift>)> {
+    let path = active_slot_path(None)?;
+    if !path.exists() {
+        return Err(slot_missing(&path));
+    }
+    let base = active_game_dir().map_err(|e| ApiError::from(e.to_string()))?;
+    let scan = scan_game_vfs_folders_cmd(state, max_depth, None).await?;
+    let cfg = load_segatoools_config(&path).map_err(|e| ApiError::from(e.to_string()))?;
+    let drifts = vfs_drifts(&base, &cfg.vfs, &scan);
+    Ok((path, cfg, drifts))
+}
+
+/// `[vfs]` paths of the active game that went stale after a re-deploy or a move, with the
+/// folders the scanner would use instead.
+#[command]
+pub async fn detect_vfs_drift_cmd(state: State<'_, AppState>, max_depth: Option<usize>) -> ApiResult<VfsDriftReport> {
+    let (_, cfg, drifts) = active_vfs_drift(state, max_depth).await?;
+    let mut fixed = cfg.clone();
+    apply_vfs_drifts(&mut fixed, &drifts);
+    Ok(VfsDriftReport {
+        changes: diff_segatoools_configs(&cfg, &fixed),
+        drifts,
+    })
+}
+
+/// Points every drifted `[vfs]` path that has a candidate at it. Drift is detected again
+/// here, so the fix matches the folders as they are now.
+#[command]
+pub async fn apply_vfs_drift_fix_cmd(
+    state: State<'_, AppState>,
+    max_depth: Option<usize>,
+) -> ApiResult<VfsDriftReport> {
+    let game = state.active_game()?;
+    let (path, _, drifts) = active_vfs_drift(state, max_depth).await?;
+    if drifts.iter().all(|d| d.candidate.is_none()) {
+        return Ok(VfsDriftReport { drifts, changes: Vec::new() });
+    }
+
+    let _guard = state.lock_config();
+    let before = live_segatoools_or_default(&path);
+    let mut cfg = load_segatoools_config(&path).map_err(|e| ApiError::from(e.to_string()))?;
+    apply_vfs_drifts(&mut cfg, &drifts);
+    retain_sections(&mut cfg, &["vfs".to_string()]);
+    let result = persist_segatoools_config(&path, &cfg);
+    let after = live_segatoools_or_default(&path);
+    let changes = diff_segatoools_configs(&before, &after);
+    journal::record(
+        &game.id,
+        JournalEntry::new("vfs_drift_fix", format!("Repointed {} stale [vfs] path(s)", changes.len()))
+            .with_outcome(&result)
+            .with_details(serde_json::json!({ "changes": changes })),
+    );
+    result.map_err(ApiError::from)?;
+    record_segatoools_provenance(&game, &before, &after, &KeyOrigin::user());
+    Ok(VfsDriftReport { drifts, changes })
+}
+
+#[command]
+pub fn get_active_game_cmd() -> ApiResult<Option<String>> {
+    get_active_game_id().map_err(|e| ApiError::from(e.to_string()))
+}
+
+#[command]
+pub fn set_active_game_cmd(id: String, profile_id: Option<String>) -> ApiResult<()> {
+    set_active_game_id(&id).map_err(|e| ApiError::from(e.to_string()))?;
+
+    let game_opt = store::list_games()
+        .ok()
+        .and_then(|games| games.into_iter().find(|g| g.id == id));
+    let game_name = game_opt.as_ref().map(|g| g.name.clone());
+
+    // Auto-backup logic: Check if "Original INI" profile exists, if not, create it from current file
+    if let Ok(path) = segatoools_path_for_active() {
+        if path.exists() {
+            let profiles = list_profiles(None).unwrap_or_default();
+            let has_original = profiles.iter().any(|p| p.name == "Original INI");
+            
+            if !has_original {
+                if let Ok(current_cfg) = load_segatoools_config(&path) {
+                    let sanitized = sanitize_segatoools_for_game(current_cfg, game_name.as_deref());
+                    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis();
+                    let backup_profile = ConfigProfile {
+                        id: format!("original-{}", timestamp),
+                        name: "Original INI".to_string(),
+                        description: Some("Automatically created from initial configuration".to_string()),
+                        base_profile_id: None,
+                        segatools: sanitized,
+                        segatools_slots: BTreeMap::new(),
+                        json_configs: BTreeMap::new(),
+                        created_at: timestamp.to_string(),
+                        updated_at: timestamp.to_string(),
+                    };
+                    let _ = save_profile(&backup_profile);
+                }
+            }
+        }
+    }
+
+    // If a profile is supplied when activating a game, apply it immediately (so switching config does not require launch)
+    if let Some(pid) = profile_id.filter(|s| !s.is_empty()) {
+        let game = game_opt.ok_or_else(|| "Game not found".to_string())?;
+        let seg_path = segatoools_path_for_active().map_err(|e| ApiError::from(e.to_string()))?;
+        if !seg_path.exists() {
+            return Err(("segatools.ini not found. Please deploy first.".to_string()).into());
+        }
+        let profile = load_profile(&pid, Some(&id)).map_err(|e| ApiError::from(e.to_string()))?;
+        let result = apply_profile_files(&game, &profile, &game.name);
+        journal::record(
+            &id,
+            JournalEntry::new("profile_apply", format!("Applied profile \"{}\"", profile.name))
+                .with_outcome(&result)
+                .with_details(serde_json::json!({ "profileId": pid })),
+        );
+        result.map_err(ApiError::from)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn reports_stale_vfs_paths_with_candidates() {
+        let tmp = TempDir::new().unwrap();
+        let base = tmp.path();
+        fs::create_dir_all(base.join("old_option")).unwrap();
+        fs::create_dir_all(base.join("data").join("option").join("A001")).unwrap();
+        fs::create_dir_all(base.join("appdata").join("SDEZ")).unwrap();
+        let vfs = VfsConfig {
+            enable: true,
+            amfs: "gone".to_string(),
+            appdata: "appdata".to_string(),
+            option: "old_option".to_string(),
+        };
+        let scan = VfsScanResult {
+            amfs: None,
+            appdata: Some("appdata".to_string()),
+            option: Some("data\\option".to_string()),
+        };
+
+        let drifts = vfs_drifts(base, &vfs, &scan);
+        assert_eq!(drifts.len(), 2);
+        assert_eq!((drifts[0].field.as_str(), drifts[0].reason), ("amfs", VfsDriftReason::Missing));
+        assert!(drifts[0].candidate.is_none());
+        assert_eq!(drifts[1].reason, VfsDriftReason::Empty);
+
+        let mut cfg = SegatoolsConfig {
+            vfs,
+            ..SegatoolsConfig::default()
+        };
+        apply_vfs_drifts(&mut cfg, &drifts);
+        assert_eq!((cfg.vfs.amfs.as_str(), cfg.vfs.option.as_str()), ("gone", "data\\option"));
+    }
+}
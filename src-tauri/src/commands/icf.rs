@@ -0,0 +1,611 @@
+use super::config::load_active_seg_config;
+#[cfg(feature = "icf")]
+use super::games::journal_for_active;
+use super::games::resolve_with_base;
+use super::state::AppState;
+use super::trash::trash_game_item;
+#[cfg(feature = "icf")]
+use super::vhd::app_chain_versions;
+#[cfg(feature = "icf")]
+use crate::games::{journal::JournalEntry, model::{Game, LaunchMode}, store};
+#[cfg(feature = "icf")]
+use crate::icf::{
+    audit_icf,
+    backups::{backup_icf, list_icf_backups, restore_icf_backup, IcfBackup, ICF_BACKUPS_KEPT},
+    decode_icf_with_key, decode_icf_with_keyring, encrypt_icf, icf_from_json, icf_to_json, insert_option_entries,
+    keys::{export_icf_keys, import_icf_keys, remove_icf_platform_key, set_icf_platform_key},
+    parse_version, propose_option_entries, serialize_icf, summarize_icf, IcfAudit, IcfData, IcfKey, IcfSummary,
+    InstalledContent,
+};
+use crate::error::{ApiError, ApiResult};
+#[cfg(feature = "icf")]
+use crate::error::ErrorCode;
+#[cfg(feature = "icf")]
+use crate::vhd::load_vhd_config;
+use crate::compression::{folder_compression, set_folder_compression, FolderCompression};
+use serde::Serialize;
+use tauri::{command, AppHandle, State};
+use std::fs;
+use std::path::{Path, PathBuf};
+#[cfg(feature = "icf")]
+use std::time::SystemTime;
+
+/// Newest app version the ICF under `amfs` declares, counting installed patches.
+#[cfg(feature = "icf")]
+pub(super) fn read_icf_app_version(state: &AppState, amfs: &Path) -> Option<String> {
+    let mut buf = fs::read(amfs.join("ICF1")).ok()?;
+    let (_, entries) = decode_sealed_icf(state, &mut buf, None).ok()?;
+    summarize_icf(&entries).app_version.map(|version| version.to_string())
+}
+
+/// Without ICF support no version is read and launches keep the stored one.
+#[cfg(not(feature = "icf"))]
+pub(super) fn read_icf_app_version(_state: &AppState, _amfs: &Path) -> Option<String> {
+    None
+}
+
+#[derive(Serialize)]
+pub struct OptionEntry {
+    pub name: String,
+    pub path: String,
+    pub is_dir: bool,
+    pub size: u64,
+    pub version: Option<String>,
+    pub compression: FolderCompression,
+}
+
+#[cfg(feature = "icf")]
+fn amfs_path() -> ApiResult<PathBuf> {
+    let (cfg, base) = load_active_seg_config()?;
+    let trimmed = cfg.vfs.amfs.trim();
+    if trimmed.is_empty() {
+        return Err(("AMFS path is empty in segatools.ini".to_string()).into());
+    }
+    Ok(resolve_with_base(&base, trimmed))
+}
+
+pub(super) fn option_dir() -> ApiResult<PathBuf> {
+    let (cfg, base) = load_active_seg_config()?;
+    let trimmed = cfg.vfs.option.trim();
+    if trimmed.is_empty() {
+        return Err(("OPTION path is empty in segatools.ini".to_string()).into());
+    }
+    Ok(resolve_with_base(&base, trimmed))
+}
+
+#[cfg(feature = "icf")]
+fn icf_path(kind: &str) -> ApiResult<PathBuf> {
+    let icf_name = kind.trim().to_uppercase();
+    if icf_name.is_empty() {
+        return Err(("ICF name missing".to_string()).into());
+    }
+    let mut path = amfs_path()?;
+    path.push(icf_name);
+    Ok(path)
+}
+
+/// Decodes with `key` when given; otherwise with the keyring's ALLS key or, failing that, the
+/// first platform key in the keyring that fits. Returns the key so a save can seal the same way.
+#[cfg(feature = "icf")]
+fn decode_sealed_icf(state: &AppState, buf: &mut [u8], key: Option<&IcfKey>) -> ApiResult<(IcfKey, Vec<IcfData>)> {
+    if let Some(key) = key {
+        return decode_icf_with_key(buf, key)
+            .map(|entries| (*key, entries))
+            .map_err(|e| ApiError::from(e.to_string()));
+    }
+    let keyring = state.icf_keyring()?;
+    decode_icf_with_keyring(buf, &keyring).map_err(|e| ApiError::from(e.to_string()))
+}
+
+/// Per-call key and IV, as hex; both or neither.
+#[cfg(feature = "icf")]
+fn key_override(key: Option<String>, iv: Option<String>) -> ApiResult<Option<IcfKey>> {
+    match (key, iv) {
+        (None, None) => Ok(None),
+        (Some(key), Some(iv)) => IcfKey::from_hex(&key, &iv)
+            .map(Some)
+            .map_err(|e| ApiError::new(ErrorCode::InvalidInput, e.to_string())),
+        _ => Err(ApiError::new(ErrorCode::InvalidInput, "ICF key and IV must be given together")),
+    }
+}
+
+fn is_option_folder(name: &str) -> bool {
+    let chars: Vec<char> = name.chars().collect();
+    if chars.len() != 4 {
+        return false;
+    }
+    chars[0].is_ascii_uppercase()
+        && chars[1].is_ascii_digit()
+        && chars[2].is_ascii_digit()
+        && chars[3].is_ascii_digit()
+}
+
+fn find_case_insensitive(dir: &Path, candidates: &[&str]) -> Option<PathBuf> {
+    let lower_candidates: Vec<String> = candidates.iter().map(|s| s.to_lowercase()).collect();
+    let entries = fs::read_dir(dir).ok()?;
+    for entry in entries.flatten() {
+        let fname = entry.file_name();
+        let name = fname.to_string_lossy().to_lowercase();
+        if lower_candidates.contains(&name) {
+            return Some(entry.path());
+        }
+    }
+    None
+}
+
+fn parse_data_conf_version(path: &Path) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    let mut major: Option<u32> = None;
+    let mut minor: Option<u32> = None;
+    let mut release: Option<u32> = None;
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('#') || line.is_empty() {
+            continue;
+        }
+        if let Some(idx) = line.find('=') {
+            let key = line[..idx].trim();
+            let val = line[idx + 1..].trim();
+            match key {
+                "VerMajor" => major = val.parse::<u32>().ok(),
+                "VerMinor" => minor = val.parse::<u32>().ok(),
+                "VerRelease" => release = val.parse::<u32>().ok(),
+                _ => {}
+            }
+        }
+    }
+    match (major, minor, release) {
+        (Some(a), Some(b), Some(c)) => Some(format!("Ver {a}.{b}.{c}")),
+        _ => None,
+    }
+}
+
+fn extract_tag_value(content: &str, tag: &str) -> Option<String> {
+    let open = format!("<{tag}>");
+    let close = format!("</{tag}>");
+    let start = content.to_lowercase().find(&open.to_lowercase())?;
+    let end = content.to_lowercase().find(&close.to_lowercase())?;
+    if end <= start {
+        return None;
+    }
+    let inner_start = start + open.len();
+    Some(content[inner_start..end].trim().to_string())
+}
+
+fn parse_dataconfig_xml_version(path: &Path) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    let major = extract_tag_value(&content, "major")?.parse::<u32>().ok()?;
+    let minor = extract_tag_value(&content, "minor")?.parse::<u32>().ok()?;
+    let release = extract_tag_value(&content, "release")?.parse::<u32>().ok()?;
+    Some(format!("Ver {major}.{minor}.{release}"))
+}
+
+fn detect_option_version(dir: &Path) -> Option<String> {
+    if let Some(conf) = find_case_insensitive(dir, &["data.conf"]) {
+        if let Some(ver) = parse_data_conf_version(&conf) {
+            return Some(ver);
+        }
+    }
+    if let Some(xml) = find_case_insensitive(dir, &["dataconfig.xml", "DataConfig.xml"]) {
+        if let Some(ver) = parse_dataconfig_xml_version(&xml) {
+            return Some(ver);
+        }
+    }
+    None
+}
+
+/// Decoded entries of one ICF of the active game. `key` and `iv` override the key lookup for
+/// ICFs sealed under a key the keyring does not hold.
+#[cfg(feature = "icf")]
+#[command]
+pub fn load_icf_cmd(
+    state: State<'_, AppState>,
+    kind: String,
+    key: Option<String>,
+    iv: Option<String>,
+) -> ApiResult<Vec<IcfData>> {
+    let key = key_override(key, iv)?;
+    let path = icf_path(&kind)?;
+    let kind_upper = kind.trim().to_uppercase();
+    if !path.exists() {
+        if kind_upper == "ICF2" {
+            return Ok(vec![]);
+        }
+        return Err((format!("{} not found", kind_upper)).into());
+    }
+    let mut buf = fs::read(path).map_err(|e| ApiError::from(e.to_string()))?;
+    decode_sealed_icf(&state, &mut buf, key.as_ref()).map(|(_, entries)| entries)
+}
+
+#[cfg(feature = "icf")]
+/// Ids, newest app version and entry counts of one ICF, for library cards that should not
+/// pull every entry across the bridge. A missing ICF2 summarizes as empty, like [`load_icf_cmd`].
+#[command]
+pub fn icf_summary_cmd(state: State<'_, AppState>, kind: String) -> ApiResult<IcfSummary> {
+    let path = icf_path(&kind)?;
+    let kind_upper = kind.trim().to_uppercase();
+    if !path.exists() {
+        if kind_upper == "ICF2" {
+            return Ok(IcfSummary::default());
+        }
+        return Err((format!("{} not found", kind_upper)).into());
+    }
+    let mut buf = fs::read(path).map_err(|e| ApiError::from(e.to_string()))?;
+    let (_, entries) = decode_sealed_icf(&state, &mut buf, None)?;
+    Ok(summarize_icf(&entries))
+}
+
+/// Seals and writes one ICF of the active game, under `key`/`iv` when given and otherwise
+/// under the key the current file was sealed with.
+#[cfg(feature = "icf")]
+#[command]
+pub fn save_icf_cmd(
+    state: State<'_, AppState>,
+    kind: String,
+    entries: Vec<IcfData>,
+    key: Option<String>,
+    iv: Option<String>,
+) -> ApiResult<()> {
+    let key = key_override(key, iv)?;
+    let result = write_icf(&state, &kind, &entries, key.as_ref());
+    journal_for_active(
+        JournalEntry::new("icf_edit", format!("Saved {} ({} entries)", kind.trim().to_uppercase(), entries.len()))
+            .with_outcome(&result.as_ref().map_err(|e| e.message.clone())),
+    );
+    result
+}
+
+/// One ICF of the active game as a pretty JSON document, for keeping in git or moving to
+/// another machine. [`import_icf_json_cmd`] reads it back.
+#[cfg(feature = "icf")]
+#[command]
+pub fn export_icf_json_cmd(state: State<'_, AppState>, kind: String) -> ApiResult<String> {
+    let entries = read_icf(&state, &kind)?
+        .ok_or_else(|| ApiError::new(ErrorCode::NotFound, format!("{} not found", kind.trim().to_uppercase())))?;
+    icf_to_json(&kind, &entries).map_err(|e| ApiError::from(e.to_string()))
+}
+
+/// Rebuilds and encrypts one ICF of the active game from an exported JSON document. The
+/// previous file is backed up, as with [`save_icf_cmd`].
+#[cfg(feature = "icf")]
+#[command]
+pub fn import_icf_json_cmd(state: State<'_, AppState>, kind: String, content: String) -> ApiResult<Vec<IcfData>> {
+    let entries = icf_from_json(&content).map_err(|e| ApiError::new(ErrorCode::InvalidInput, format!("{:#}", e)))?;
+    let result = write_icf(&state, &kind, &entries, None);
+    journal_for_active(
+        JournalEntry::new(
+            "icf_edit",
+            format!("Imported {} from JSON ({} entries)", kind.trim().to_uppercase(), entries.len()),
+        )
+        .with_outcome(&result.as_ref().map_err(|e| e.message.clone())),
+    );
+    result.map(|_| entries)
+}
+
+/// What the active game has installed, for [`icf_audit_cmd`]. VHD games report their app
+/// and patch versions through the image names; game folders through a data.conf or
+/// DataConfig.xml in the game folder, without patches.
+#[cfg(feature = "icf")]
+fn installed_content(game: &Game) -> ApiResult<InstalledContent> {
+    let dir = option_dir()?;
+    let mut options: Vec<String> = fs::read_dir(&dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| entry.path().is_dir())
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .filter(|name| is_option_folder(name))
+                .collect()
+        })
+        .unwrap_or_default();
+    options.sort();
+
+    let (app_version, patch_versions) = if matches!(game.launch_mode, LaunchMode::Vhd) {
+        let cfg = load_vhd_config(&game.id).map_err(ApiError::from)?;
+        let (base, patches) = app_chain_versions(&cfg);
+        let patches: Vec<_> = patches.iter().filter_map(|v| parse_version(v)).collect();
+        let newest = patches.last().copied().or_else(|| base.as_deref().and_then(parse_version));
+        (newest, Some(patches))
+    } else {
+        let version = store::game_root_dir(game)
+            .and_then(|root| detect_option_version(&root))
+            .and_then(|v| parse_version(&v));
+        (version, None)
+    };
+    Ok(InstalledContent {
+        app_version,
+        patch_versions,
+        options,
+    })
+}
+
+/// Checks the active game's ICF1 against its installed app version, patch chain and option
+/// folders, and lists every disagreement.
+#[cfg(feature = "icf")]
+#[command]
+pub fn icf_audit_cmd(state: State<'_, AppState>) -> ApiResult<IcfAudit> {
+    let game = state.active_game()?;
+    let path = icf_path("ICF1")?;
+    let mut buf = fs::read(&path).map_err(|e| ApiError::new(ErrorCode::NotFound, format!("ICF1 not found: {}", e)))?;
+    let (_, entries) = decode_sealed_icf(&state, &mut buf, None)?;
+    Ok(audit_icf(&entries, &installed_content(&game)?))
+}
+
+/// When an option folder was put in place: the newest modification time of the folder and
+/// the files directly in it, in local time like the ICF's own timestamps.
+#[cfg(feature = "icf")]
+fn option_timestamp(dir: &Path) -> chrono::NaiveDateTime {
+    use chrono::Timelike;
+
+    let newest = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| entry.metadata().and_then(|m| m.modified()).ok())
+                .max()
+        })
+        .ok()
+        .flatten();
+    let folder = fs::metadata(dir).and_then(|m| m.modified()).ok();
+    let modified = newest.max(folder).unwrap_or_else(SystemTime::now);
+    chrono::DateTime::<chrono::Local>::from(modified)
+        .naive_local()
+        .with_nanosecond(0)
+        .unwrap_or_default()
+}
+
+#[cfg(feature = "icf")]
+fn read_icf(state: &AppState, kind: &str) -> ApiResult<Option<Vec<IcfData>>> {
+    let path = icf_path(kind)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let mut buf = fs::read(path).map_err(|e| ApiError::from(e.to_string()))?;
+    decode_sealed_icf(state, &mut buf, None).map(|(_, entries)| Some(entries))
+}
+
+/// Option entries ICF1 is missing for the option folders of the active game, built from the
+/// folders' names and modification times. Nothing is written.
+#[cfg(feature = "icf")]
+#[command]
+pub fn propose_icf_options_cmd(state: State<'_, AppState>) -> ApiResult<Vec<IcfData>> {
+    propose_options(&state)
+}
+
+#[cfg(feature = "icf")]
+fn propose_options(state: &AppState) -> ApiResult<Vec<IcfData>> {
+    let entries = read_icf(state, "ICF1")?.ok_or_else(|| ApiError::new(ErrorCode::NotFound, "ICF1 not found"))?;
+    let dir = option_dir()?;
+    let installed: Vec<(String, chrono::NaiveDateTime)> = list_option_files_cmd()?
+        .into_iter()
+        .map(|option| {
+            let stamp = option_timestamp(&dir.join(&option.name));
+            (option.name, stamp)
+        })
+        .collect();
+    Ok(propose_option_entries(&entries, &installed))
+}
+
+/// Adds entries for the option folders `options` to ICF1, and to ICF2 when the game has
+/// one. Options an ICF already lists are left as they are. Returns the updated ICF1.
+#[cfg(feature = "icf")]
+#[command]
+pub fn apply_icf_options_cmd(state: State<'_, AppState>, options: Vec<String>) -> ApiResult<Vec<IcfData>> {
+    let proposed: Vec<IcfData> = propose_options(&state)?
+        .into_iter()
+        .filter(|entry| {
+            matches!(entry, IcfData::Option(option) if options.iter().any(|name| name.eq_ignore_ascii_case(&option.option_id)))
+        })
+        .collect();
+    let result = (|| -> ApiResult<Vec<IcfData>> {
+        let mut icf1 =
+            read_icf(&state, "ICF1")?.ok_or_else(|| ApiError::new(ErrorCode::NotFound, "ICF1 not found"))?;
+        let mut icf2 = read_icf(&state, "ICF2")?;
+        insert_option_entries(&mut icf1, proposed.clone());
+        write_icf(&state, "ICF1", &icf1, None)?;
+        if let Some(icf2) = icf2.as_mut() {
+            let missing: Vec<IcfData> = proposed.iter().filter(|entry| !icf2.contains(entry)).cloned().collect();
+            insert_option_entries(icf2, missing);
+            write_icf(&state, "ICF2", icf2, None)?;
+        }
+        Ok(icf1)
+    })();
+    let names: Vec<String> = proposed
+        .iter()
+        .filter_map(|entry| match entry {
+            IcfData::Option(option) => Some(option.option_id.clone()),
+            _ => None,
+        })
+        .collect();
+    journal_for_active(
+        JournalEntry::new("icf_edit", format!("Added {} option entries to the ICF", names.len()))
+            .with_details(serde_json::json!({ "options": names }))
+            .with_outcome(&result.as_ref().map_err(|e| e.message.clone())),
+    );
+    result
+}
+
+/// Without `key`, an existing ICF is resealed under the key it decodes with and a new one
+/// under the keyring's ALLS key.
+#[cfg(feature = "icf")]
+fn write_icf(state: &AppState, kind: &str, entries: &[IcfData], key: Option<&IcfKey>) -> ApiResult<()> {
+    let path = icf_path(kind)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| ApiError::from(e.to_string()))?;
+    }
+    let key = match key {
+        Some(key) => *key,
+        None => match fs::read(&path).ok().and_then(|mut buf| decode_sealed_icf(state, &mut buf, None).ok()) {
+            Some((key, _)) => key,
+            None => state
+                .icf_keyring()?
+                .default_key()
+                .map_err(|e| ApiError::new(ErrorCode::InvalidInput, e.to_string()))?,
+        },
+    };
+    let serialized = serialize_icf(entries).map_err(|e| ApiError::from(e.to_string()))?;
+    let encrypted = encrypt_icf(&serialized, key.key, key.iv).map_err(|e| ApiError::from(e.to_string()))?;
+    backup_icf(&amfs_path()?, kind, ICF_BACKUPS_KEPT).map_err(|e| ApiError::from(format!("{:#}", e)))?;
+    fs::write(path, encrypted).map_err(|e| ApiError::from(e.to_string()))
+}
+
+/// Backups of the active game's ICFs taken before each save, newest first; all ICFs when
+/// `kind` is omitted.
+#[cfg(feature = "icf")]
+#[command]
+pub fn list_icf_backups_cmd(kind: Option<String>) -> ApiResult<Vec<IcfBackup>> {
+    Ok(list_icf_backups(&amfs_path()?, kind.as_deref()))
+}
+
+/// Puts one backup back in place of its ICF. The replaced ICF is backed up first, so the
+/// restore shows up in [`list_icf_backups_cmd`] and can be undone the same way.
+#[cfg(feature = "icf")]
+#[command]
+pub fn restore_icf_backup_cmd(file_name: String) -> ApiResult<IcfBackup> {
+    let result = amfs_path().and_then(|amfs| {
+        restore_icf_backup(&amfs, &file_name, ICF_BACKUPS_KEPT)
+            .map_err(|e| ApiError::new(ErrorCode::NotFound, format!("{:#}", e)))
+    });
+    journal_for_active(
+        JournalEntry::new("icf_edit", format!("Restored ICF backup {}", file_name.trim()))
+            .with_outcome(&result.as_ref().map_err(|e| e.message.clone())),
+    );
+    result
+}
+
+/// Platform ids with a stored ICF key. Key material is never sent back.
+#[cfg(feature = "icf")]
+#[command]
+pub fn list_icf_keys_cmd(state: State<'_, AppState>) -> ApiResult<Vec<String>> {
+    state.icf_keyring().map(|keyring| keyring.platforms())
+}
+
+/// Stores the ICF key and IV (hex) of one platform; ICFs sealed with it then load and save
+/// without per-call overrides.
+#[cfg(feature = "icf")]
+#[command]
+pub fn set_icf_key_cmd(
+    state: State<'_, AppState>,
+    platform_id: String,
+    key: String,
+    iv: String,
+) -> ApiResult<Vec<String>> {
+    let keyring = set_icf_platform_key(&platform_id, &key, &iv)
+        .map_err(|e| ApiError::new(ErrorCode::InvalidInput, e.to_string()))?;
+    state.store_icf_keyring(&keyring);
+    Ok(keyring.platforms())
+}
+
+#[cfg(feature = "icf")]
+#[command]
+pub fn delete_icf_key_cmd(state: State<'_, AppState>, platform_id: String) -> ApiResult<Vec<String>> {
+    let keyring =
+        remove_icf_platform_key(&platform_id).map_err(|e| ApiError::new(ErrorCode::NotFound, e.to_string()))?;
+    state.store_icf_keyring(&keyring);
+    Ok(keyring.platforms())
+}
+
+/// Merges a key file into the keyring, e.g. one exported on another machine. Distribution
+/// builds carry no ICF key, so this is how the ALLS key gets in. `passphrase` opens files
+/// written by [`export_icf_keys_cmd`].
+#[cfg(feature = "icf")]
+#[command]
+pub fn import_icf_keys_cmd(
+    state: State<'_, AppState>,
+    path: String,
+    passphrase: Option<String>,
+) -> ApiResult<Vec<String>> {
+    let path = path.trim();
+    if path.is_empty() {
+        return Err(ApiError::new(ErrorCode::InvalidInput, "Key file path is empty"));
+    }
+    let keyring = import_icf_keys(Path::new(path), passphrase.as_deref())
+        .map_err(|e| ApiError::new(ErrorCode::InvalidInput, e.to_string()))?;
+    state.store_icf_keyring(&keyring);
+    Ok(keyring.platforms())
+}
+
+/// Writes the keyring to `path` sealed under `passphrase` and returns how many keys it holds.
+#[cfg(feature = "icf")]
+#[command]
+pub fn export_icf_keys_cmd(path: String, passphrase: String) -> ApiResult<usize> {
+    let path = path.trim();
+    if path.is_empty() {
+        return Err(ApiError::new(ErrorCode::InvalidInput, "Key file path is empty"));
+    }
+    export_icf_keys(Path::new(path), &passphrase).map_err(|e| ApiError::new(ErrorCode::InvalidInput, e.to_string()))
+}
+
+#[command]
+pub fn list_option_files_cmd() -> ApiResult<Vec<OptionEntry>> {
+    let dir = option_dir()?;
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(&dir).map_err(|e| ApiError::from(e.to_string()))? {
+        let entry = entry.map_err(|e| ApiError::from(e.to_string()))?;
+        let meta = entry.metadata().map_err(|e| ApiError::from(e.to_string()))?;
+        if !meta.is_dir() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if !is_option_folder(&name) {
+            continue;
+        }
+        entries.push(option_entry(name, &entry.path(), meta.len()));
+    }
+    entries.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    Ok(entries)
+}
+
+/// Moves one option folder of the active game to the trash; [`undo_last_delete_cmd`] puts
+/// it back.
+#[command]
+pub fn delete_option_cmd(app: AppHandle, state: State<'_, AppState>, name: String) -> ApiResult<Vec<OptionEntry>> {
+    if !is_option_folder(&name) {
+        return Err(ApiError::from(format!("Not an option folder: {}", name)));
+    }
+    let game = state.active_game()?;
+    let _operation = state.begin_operation(&game.id, "option update")?;
+    let path = option_dir()?.join(&name);
+    if !path.is_dir() {
+        return Err(ApiError::from(format!("Option not found: {}", name)));
+    }
+    trash_game_item(&app, &game, &path, "option")?;
+    list_option_files_cmd()
+}
+
+fn option_entry(name: String, path: &Path, size: u64) -> OptionEntry {
+    OptionEntry {
+        name,
+        path: path.to_string_lossy().into_owned(),
+        is_dir: true,
+        size,
+        version: detect_option_version(path),
+        compression: folder_compression(path),
+    }
+}
+
+/// Turns NTFS compression on or off for one option folder of the active game.
+#[command]
+pub async fn set_option_compression_cmd(
+    state: State<'_, AppState>,
+    name: String,
+    enabled: bool,
+) -> ApiResult<OptionEntry> {
+    if !is_option_folder(&name) {
+        return Err(ApiError::from(format!("Not an option folder: {}", name)));
+    }
+    let game = state.active_game()?;
+    let operation = state.begin_operation(&game.id, "option update")?;
+    let path = option_dir()?.join(&name);
+    tauri::async_runtime::spawn_blocking(move || -> ApiResult<OptionEntry> {
+        let _operation = operation;
+        set_folder_compression(&path, enabled)?;
+        let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        Ok(option_entry(name, &path, size))
+    })
+    .await
+    .map_err(|e| ApiError::from(e.to_string()))?
+}
@@ -0,0 +1,1251 @@
+use super::config::{
+    live_segatoools_or_default, record_segatoools_provenance, redact_keychip_id, sanitize_segatoools_for_game,
+};
+use super::games::{active_display_preset, apply_detected_version, resolve_with_base, rule_for_game_key};
+use super::icf::read_icf_app_version;
+use super::mods::emit_mod_conflicts;
+use super::remote::ensure_launch_allowed;
+use super::settings::ensure_not_read_only;
+use super::state::AppState;
+use super::vhd::{
+    apply_unpacked_zip_overlay, detect_game_on_mount, detect_vfs_paths_on_drive, ensure_vfs_keys_present,
+    find_unpacked_zip_for_chain, lock_mounted_vhd_bitlocker_volumes_best_effort, locked_bitlocker_mounts,
+    mount_launch_vhd, run_powershell_capture_with_env, unlock_mounted_vhd_bitlocker_volumes, LaunchSession,
+};
+use crate::config::{
+    credits::{apply_credit_mode, CreditMode},
+    json_configs::json_config_path_for_game,
+    load_segatoools_config,
+    paths::{game_dir, segatools_root_for_game_id, segatoools_path_for_game_id},
+    profiles::{list_profiles, load_profile, profile_assets, ConfigProfile},
+    provenance::KeyOrigin,
+    segatools::SegatoolsConfig,
+    slots::segatoools_path_for_slot,
+    transaction::FileTransaction,
+};
+use crate::games::{
+    detection::canonical_game_key,
+    hooks::run_post_exit_hooks,
+    journal::{self, JournalEntry},
+    launcher::{launch_game_child_with_ini, launches_amdaemon},
+    model::{Game, LaunchMode},
+    sandbox::SandboxedChild,
+    store,
+    watchdog::{CrashReport, Watchdog, WatchdogAction},
+};
+use crate::display::set_screen_args;
+use crate::gpu::{detect_process_gpu, set_gpu_preference};
+use crate::error::{ApiError, ApiResult, ConfigError, ErrorCode, GameError, Remediation};
+use crate::trusted::local_segatools_version;
+use crate::platform;
+use crate::vhd::{
+    load_vhd_config, plan_mount_points, resolve_vhd_config, wait_for_mount_letters, LetterWait, MountPoints, VhdConfig,
+};
+use serde::{Deserialize, Serialize};
+use tauri::{command, AppHandle, Emitter, Manager, State, Window};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant, SystemTime};
+use std::os::windows::process::CommandExt;
+
+fn is_process_running(name: &str) -> ApiResult<bool> {
+    Ok(platform::is_process_running(name)?)
+}
+
+fn wait_for_process_start(name: &str, timeout: Duration) -> ApiResult<bool> {
+    let start = Instant::now();
+    while start.elapsed() < timeout {
+        if is_process_running(name)? {
+            return Ok(true);
+        }
+        std::thread::sleep(Duration::from_millis(500));
+    }
+    Ok(false)
+}
+
+fn wait_for_process_exit(name: &str) -> ApiResult<()> {
+    loop {
+        if !is_process_running(name)? {
+            return Ok(());
+        }
+        std::thread::sleep(Duration::from_secs(1));
+    }
+}
+
+#[derive(Serialize, Clone)]
+struct LaunchProgress {
+    game_id: String,
+    stage: String,
+}
+
+/// Payload of `vhd-letter-wait`, emitted while a launch waits for X:/Y:/Z: to be released.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct LetterWaitEvent<'a> {
+    game_id: &'a str,
+    #[serde(flatten)]
+    wait: &'a LetterWait,
+}
+
+fn emit_launch_progress(window: &Window, game_id: &str, stage: &str) {
+    let _ = window.emit(
+        "launch-progress",
+        LaunchProgress {
+            game_id: game_id.to_string(),
+            stage: stage.to_string(),
+        },
+    );
+}
+
+/// Launch lifecycle reported on the `launch-event` channel, from mounting through exit.
+/// `mounting` and `unmounted` only occur for VHD games.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(tag = "stage", rename_all = "kebab-case")]
+enum LaunchStage {
+    Mounting,
+    Configuring,
+    StartingAmdaemon,
+    StartingGame,
+    Running,
+    /// `code` is missing when the game was killed or its exit code could not be read.
+    Exited { code: Option<i32> },
+    Unmounted,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct LaunchEvent {
+    game_id: String,
+    #[serde(flatten)]
+    stage: LaunchStage,
+}
+
+fn emit_launch_event(window: &Window, game_id: &str, stage: LaunchStage) {
+    let _ = window.emit(
+        "launch-event",
+        LaunchEvent {
+            game_id: game_id.to_string(),
+            stage,
+        },
+    );
+}
+
+#[command]
+pub async fn launch_game_cmd(
+    window: Window,
+    id: String,
+    profile_id: Option<String>,
+    credit_mode: Option<CreditMode>,
+) -> ApiResult<()> {
+    tauri::async_runtime::spawn_blocking(move || run_launch(&id, profile_id, credit_mode, &window, None))
+        .await
+        .map_err(|e| ApiError::from(e.to_string()))?
+}
+
+/// Called once the launched game has exited and any VHDs are unmounted again.
+type LaunchExitHook = Box<dyn FnOnce() + Send + 'static>;
+
+/// `--launch <game-id-or-name> [--profile <name>]`, used by Steam/LaunchBox shortcuts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CliLaunch {
+    pub game: String,
+    pub profile: Option<String>,
+}
+
+impl CliLaunch {
+    /// Accepts both `--launch value` and `--launch=value`; returns `None` without `--launch`.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Option<Self> {
+        let mut game = None;
+        let mut profile = None;
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+            let target = match flag.as_str() {
+                "--launch" => &mut game,
+                "--profile" => &mut profile,
+                _ => continue,
+            };
+            *target = inline.or_else(|| args.next()).filter(|v| !v.trim().is_empty());
+        }
+        Some(Self { game: game?, profile })
+    }
+}
+
+fn resolve_cli_game(query: &str) -> ApiResult<Game> {
+    let games = store::list_games().map_err(|e| ApiError::from(e.to_string()))?;
+    if let Some(game) = games.iter().find(|g| g.id == query) {
+        return Ok(game.clone());
+    }
+    let mut matches = games.into_iter().filter(|g| g.name.eq_ignore_ascii_case(query.trim()));
+    match (matches.next(), matches.next()) {
+        (Some(game), None) => Ok(game),
+        (Some(_), Some(_)) => Err(ApiError::new(
+            ErrorCode::InvalidInput,
+            format!("More than one game is named \"{}\"; use its id instead", query),
+        )),
+        (None, _) => Err(ApiError::new(ErrorCode::NotFound, format!("Game not found: {}", query))),
+    }
+}
+
+fn resolve_cli_profile(game_id: &str, query: &str) -> ApiResult<String> {
+    let profiles = list_profiles(Some(game_id)).map_err(|e| ApiError::from(e.to_string()))?;
+    profiles
+        .iter()
+        .find(|p| p.id == query)
+        .or_else(|| profiles.iter().find(|p| p.name.eq_ignore_ascii_case(query.trim())))
+        .map(|p| p.id.clone())
+        .ok_or_else(|| ApiError::new(ErrorCode::NotFound, format!("Profile not found: {}", query)))
+}
+
+/// Runs the full launch pipeline for a `--launch` start with the window minimized, and quits
+/// once the game has exited and its VHDs are unmounted. On failure the window is restored so
+/// the failure report can be read.
+pub fn start_cli_launch(app: &AppHandle, launch: CliLaunch) {
+    let Some(main) = app.get_webview_window("main") else {
+        return;
+    };
+    let _ = main.minimize();
+    let window = main.as_ref().window();
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let on_exit: LaunchExitHook = Box::new(move || app.exit(0));
+        let result = resolve_cli_game(&launch.game).and_then(|game| {
+            let profile_id = match launch.profile.as_deref() {
+                Some(profile) => Some(resolve_cli_profile(&game.id, profile)?),
+                None => None,
+            };
+            run_launch(&game.id, profile_id, None, &window, Some(on_exit))
+        });
+        if let Err(err) = result {
+            eprintln!("Failed to launch {}: {}", launch.game, err.message);
+            let _ = main.unminimize();
+            let _ = main.set_focus();
+        }
+    });
+}
+
+fn run_launch(
+    id: &str,
+    profile_id: Option<String>,
+    credit_mode: Option<CreditMode>,
+    window: &Window,
+    on_exit: Option<LaunchExitHook>,
+) -> ApiResult<()> {
+    let state = window.app_handle().state::<AppState>();
+    // Scrubbing hashes whole backups; never compete with a game for disk bandwidth.
+    state.cancel_operations("integrity_scrub");
+    // Whatever the warm-up has read by now is already cached; the rest would only slow the boot.
+    state.cancel_operations("vhd_warmup");
+    let games = store::list_games().map_err(|e| ApiError::from(e.to_string()))?;
+    let game = games
+        .into_iter()
+        .find(|g| g.id == id)
+        .ok_or_else(|| "Game not found".to_string())?;
+    ensure_launch_allowed(window.app_handle())?;
+    // Applying a profile rewrites segatools.ini and a credit mode writes segatools_launch.ini
+    // next to it, which read-only mode exists to prevent.
+    if profile_id.as_deref().is_some_and(|pid| !pid.is_empty()) || credit_mode.is_some() {
+        ensure_not_read_only(&state)?;
+    }
+    let _operation = state.begin_operation(&game.id, "launch")?;
+    let details = serde_json::json!({ "profileId": profile_id, "creditMode": credit_mode });
+    emit_mod_conflicts(window, &game);
+    let result = if matches!(game.launch_mode, LaunchMode::Vhd) {
+        launch_vhd_game(&game, profile_id, credit_mode, window, on_exit)
+    } else {
+        launch_folder_game(&game, profile_id, credit_mode, window, on_exit)
+    };
+    if let Err(err) = &result {
+        record_failure_report(&game, err);
+    }
+    journal::record(
+        &game.id,
+        JournalEntry::new("launch", format!("Launched {}", game.name))
+            .with_outcome(&result.as_ref().map_err(|e| e.message.clone()))
+            .with_details(details),
+    );
+    result
+}
+
+fn launch_error(game: &Game, err: GameError) -> ApiError {
+    ApiError::from(err).with_remediation(Remediation::new("edit_game").with_target(game.id.clone()))
+}
+
+// The spawned child is often a wrapper (cmd or inject), so exit is tracked by the game's process name.
+// The launch batch exits with the game's code, so the wrapper is still waited on for it.
+fn wait_for_game_exit(window: &Window, game_id: &str, process_name: &str, child: &mut std::process::Child) -> Option<i32> {
+    emit_launch_event(window, game_id, LaunchStage::StartingGame);
+    let started = if process_name.is_empty() {
+        false
+    } else {
+        wait_for_process_start(process_name, Duration::from_secs(15)).unwrap_or(false)
+    };
+    emit_launch_event(window, game_id, LaunchStage::Running);
+    if started {
+        record_gpu_usage(game_id, process_name);
+        let _ = wait_for_process_exit(process_name);
+    }
+    child.wait().ok().and_then(|status| status.code())
+}
+
+// Games create their graphics device a few seconds after the process appears.
+const GPU_PROBE_DELAY: Duration = Duration::from_secs(20);
+
+/// Journals the GPU the game renders on once its graphics driver had time to load.
+fn record_gpu_usage(game_id: &str, process_name: &str) {
+    let game_id = game_id.to_string();
+    let process_name = process_name.to_string();
+    std::thread::spawn(move || {
+        std::thread::sleep(GPU_PROBE_DELAY);
+        if let Some(usage) = detect_process_gpu(&process_name) {
+            journal::record(
+                &game_id,
+                JournalEntry::new("gpu_usage", format!("Rendered on {:?}", usage.vendor))
+                    .with_details(serde_json::json!(usage)),
+            );
+        }
+    });
+}
+
+const CRASH_LOG_TAIL_LINES: usize = 50;
+
+fn report_crash(window: &Window, game: &Game, exit_code: Option<i32>, uptime: Duration, restart_attempt: Option<u32>) {
+    let mut log_dirs = vec![segatools_root_for_game_id(&game.id)];
+    if let Some(dir) = store::game_root_dir(game) {
+        log_dirs.push(dir);
+    }
+    let log_file = newest_log_file(&log_dirs);
+    let report = CrashReport {
+        game_id: game.id.clone(),
+        game_name: game.name.clone(),
+        exit_code,
+        uptime_secs: uptime.as_secs(),
+        detected_at: chrono::Utc::now().to_rfc3339(),
+        restart_attempt,
+        log_lines: log_file
+            .as_ref()
+            .map(|path| tail_lines(path, CRASH_LOG_TAIL_LINES))
+            .unwrap_or_default(),
+        log_source: log_file.map(|path| path.to_string_lossy().to_string()),
+    };
+    let mut entry = JournalEntry::new("crash", format!("{} exited abnormally", game.name))
+        .with_details(serde_json::json!(report));
+    entry.success = false;
+    entry.error = Some(match exit_code {
+        Some(code) => format!("Exit code {}", code),
+        None => "Terminated without an exit code".to_string(),
+    });
+    journal::record(&game.id, entry);
+    let _ = window.emit("game-crash", &report);
+}
+
+/// Waits for `game` to exit. With the owner's watchdog enabled, abnormal exits are reported
+/// and the game is relaunched while the restart budget lasts. Post-exit hooks run once the
+/// game is gone for good.
+fn supervise_launch(window: &Window, owner: &Game, game: &Game, overlay: Option<&Path>, mut launched: SandboxedChild) {
+    let process_name = Path::new(&game.executable_path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_string();
+    let mut watchdog = Watchdog::new(owner.watchdog.clone());
+    loop {
+        let started = Instant::now();
+        let code = wait_for_game_exit(window, &owner.id, &process_name, &mut launched.child);
+        // Closing the sandbox job reaps amdaemon and anything else the launch left behind;
+        // processes still holding files on a VHD would also block its unmount.
+        drop(launched);
+        emit_launch_event(window, &owner.id, LaunchStage::Exited { code });
+        let uptime = started.elapsed();
+        let action = watchdog.on_exit(code, uptime);
+        let delay = match action {
+            WatchdogAction::Stop => break,
+            WatchdogAction::Report => {
+                report_crash(window, game, code, uptime, None);
+                break;
+            }
+            WatchdogAction::Restart { attempt, delay } => {
+                report_crash(window, game, code, uptime, Some(attempt));
+                delay
+            }
+        };
+        std::thread::sleep(delay);
+        launched = match spawn_launch(game, owner, overlay, window) {
+            Ok(launched) => launched,
+            Err(err) => {
+                eprintln!("[watchdog] relaunching {} failed: {}", owner.name, err.message);
+                break;
+            }
+        };
+    }
+    run_post_exit_hooks(game);
+}
+
+/// Spawns the launch chain for `game`, reporting amdaemon start-up when the batch runs it.
+fn spawn_launch(
+    game: &Game,
+    owner: &Game,
+    overlay: Option<&Path>,
+    window: &Window,
+) -> ApiResult<SandboxedChild> {
+    if let Err(err) = set_gpu_preference(Path::new(&game.executable_path), owner.gpu_preference) {
+        eprintln!("[launch] {}", err);
+    }
+    let with_amdaemon = launches_amdaemon(game);
+    if with_amdaemon {
+        emit_launch_event(window, &owner.id, LaunchStage::StartingAmdaemon);
+    }
+    let launched = launch_game_child_with_ini(game, overlay).map_err(|e| launch_error(owner, e))?;
+    if with_amdaemon {
+        let _ = wait_for_process_start("amdaemon", Duration::from_secs(10));
+    }
+    Ok(launched)
+}
+
+fn launch_folder_game(
+    game: &Game,
+    profile_id: Option<String>,
+    credit_mode: Option<CreditMode>,
+    window: &Window,
+    on_exit: Option<LaunchExitHook>,
+) -> ApiResult<()> {
+    let game_name = game.name.clone();
+    let game_root = store::game_root_dir(game).ok_or_else(|| "Game path missing".to_string())?;
+
+    emit_launch_event(window, &game.id, LaunchStage::Configuring);
+    let config_to_validate = if let Some(pid) = profile_id.filter(|s| !s.is_empty()) {
+        let profile = load_profile(&pid, Some(&game.id)).map_err(|e| ApiError::from(e.to_string()))?;
+        apply_profile_files(game, &profile, &game_name).map_err(ApiError::from)?
+    } else {
+        let seg_path = segatoools_path_for_game_id(&game.id).map_err(|e| ApiError::from(e.to_string()))?;
+        if seg_path.exists() {
+            let cfg = load_segatoools_config(&seg_path).map_err(|e| ApiError::from(e.to_string()))?;
+            sanitize_segatoools_for_game(cfg, Some(game_name.as_str()))
+        } else {
+            return Err(("segatools.ini not found. Please configure the game.".to_string()).into());
+        }
+    };
+
+    let mut missing = Vec::new();
+    if config_to_validate.keychip.id.is_empty() { missing.push("Keychip ID"); }
+    if config_to_validate.vfs.amfs.is_empty() { missing.push("AMFS Path"); }
+    if config_to_validate.vfs.appdata.is_empty() { missing.push("APPDATA Path"); }
+    if config_to_validate.vfs.option.is_empty() { missing.push("OPTION Path"); }
+
+    if !missing.is_empty() {
+        return Err((format!("Missing required fields: {}. Please configure them in settings.", missing.join(", "))).into());
+    }
+
+    let mut game = game.clone();
+    let amfs = resolve_with_base(&game_root, config_to_validate.vfs.amfs.trim());
+    if let Some(version) = read_icf_app_version(&window.app_handle().state::<AppState>(), &amfs) {
+        if apply_detected_version(&mut game, &game_name, &version) {
+            store::save_game(game.clone()).map_err(|e| ApiError::from(e.to_string()))?;
+        }
+    }
+    let game = &game;
+
+    let overlay = match credit_mode {
+        Some(mode) => {
+            let seg_path = segatoools_path_for_game_id(&game.id).map_err(|e| ApiError::from(e.to_string()))?;
+            let mut txn = FileTransaction::new();
+            let overlay = stage_credit_overlay(&mut txn, &seg_path, config_to_validate, &game_name, mode)?;
+            txn.commit().map_err(ApiError::from)?;
+            Some(overlay)
+        }
+        None => None,
+    };
+    let launched = spawn_launch(game, game, overlay.as_deref(), window)?;
+    let window = window.clone();
+    let game = game.clone();
+    std::thread::spawn(move || {
+        supervise_launch(&window, &game, &game, overlay.as_deref(), launched);
+        if let Some(on_exit) = on_exit {
+            on_exit();
+        }
+    });
+    Ok(())
+}
+
+fn load_launch_config(game: &Game, profile_id: Option<String>, game_name: &str) -> ApiResult<(SegatoolsConfig, PathBuf)> {
+    let seg_path = segatoools_path_for_game_id(&game.id).map_err(|e| ApiError::from(e.to_string()))?;
+    let cfg = if let Some(pid) = profile_id.filter(|s| !s.is_empty()) {
+        let profile = load_profile(&pid, Some(&game.id)).map_err(|e| ApiError::from(e.to_string()))?;
+        apply_profile_files(game, &profile, game_name).map_err(ApiError::from)?
+    } else {
+        if !seg_path.exists() {
+            return Err(("segatools.ini not found. Please configure the game.".to_string()).into());
+        }
+        let cfg = load_segatoools_config(&seg_path).map_err(|e| ApiError::from(e.to_string()))?;
+        sanitize_segatoools_for_game(cfg, Some(game_name))
+    };
+    Ok((cfg, seg_path))
+}
+
+/// Writes a profile's segatools.ini and amdaemon config JSONs for `game` as one transaction,
+/// so a failed write never leaves the ini and the JSONs out of step.
+pub(super) fn apply_profile_files(game: &Game, profile: &ConfigProfile, game_name: &str) -> Result<SegatoolsConfig, ConfigError> {
+    let seg_path = segatoools_path_for_game_id(&game.id)?;
+    let sanitized = sanitize_segatoools_for_game(profile.segatools.clone(), Some(game_name));
+    let before = live_segatoools_or_default(&seg_path);
+    let mut txn = FileTransaction::new();
+    txn.stage_segatoools(&seg_path, &sanitized)?;
+    for (slot, cfg) in &profile.segatools_slots {
+        let slot_path = segatoools_path_for_slot(&game.id, Some(slot))?;
+        txn.stage_segatoools(&slot_path, &sanitize_segatoools_for_game(cfg.clone(), Some(game_name)))?;
+    }
+    for (name, value) in &profile.json_configs {
+        txn.stage_json(&json_config_path_for_game(&game.id, name)?, value)?;
+    }
+    let assets = profile_assets(&profile.id, Some(&game.id))?;
+    if !assets.is_empty() {
+        let dir = game_dir(&game.id)?;
+        for asset in assets {
+            txn.stage(dir.join(&asset.target), fs::read(&asset.source)?);
+        }
+    }
+    txn.commit()?;
+    record_segatoools_provenance(
+        game,
+        &before,
+        &live_segatoools_or_default(&seg_path),
+        &KeyOrigin::profile(&profile.id, &profile.name),
+    );
+    Ok(sanitized)
+}
+
+/// Stages a launch-only copy of segatools.ini with the credit mode applied, so the
+/// choice does not leak into the saved config or profiles. The copy starts from whatever
+/// `txn` already stages for segatools.ini.
+fn stage_credit_overlay(
+    txn: &mut FileTransaction,
+    seg_path: &Path,
+    mut cfg: SegatoolsConfig,
+    game_name: &str,
+    mode: CreditMode,
+) -> ApiResult<PathBuf> {
+    apply_credit_mode(&mut cfg, &canonical_game_key(game_name), mode);
+    let overlay_path = seg_path.with_file_name("segatools_launch.ini");
+    let base = match txn.staged(seg_path) {
+        Some(staged) => staged.to_vec(),
+        None if seg_path.exists() => fs::read(seg_path).map_err(|e| ApiError::from(e.to_string()))?,
+        None => Vec::new(),
+    };
+    txn.stage(&overlay_path, base);
+    txn.stage_segatoools(&overlay_path, &cfg).map_err(ApiError::from)?;
+    Ok(overlay_path)
+}
+
+fn launch_vhd_game(
+    game: &Game,
+    profile_id: Option<String>,
+    credit_mode: Option<CreditMode>,
+    window: &Window,
+    on_exit: Option<LaunchExitHook>,
+) -> ApiResult<()> {
+    if !game.enabled {
+        emit_launch_progress(window, &game.id, "error");
+        return Err(ApiError::from("Game is disabled".to_string())
+            .with_remediation(Remediation::new("edit_game").with_target(game.id.clone())));
+    }
+    let edit_vhd = || Remediation::new("edit_vhd_config").with_target(game.id.clone());
+    let vhd_cfg = load_vhd_config(&game.id).map_err(|e| ApiError::from(e).with_remediation(edit_vhd()))?;
+    let mut resolved =
+        resolve_vhd_config(&game.id, &vhd_cfg).map_err(|e| ApiError::from(e).with_remediation(edit_vhd()))?;
+    let unpacked_zip = find_unpacked_zip_for_chain(&resolved.app_base_path, &resolved.app_patch_paths);
+    if unpacked_zip.is_some() && !resolved.delta_enabled {
+        // Overlay extraction must target a disposable runtime so source VHDs stay untouched.
+        resolved.delta_enabled = true;
+    }
+    if resolved.delta_enabled {
+        if let (_, Some(reason)) = resolved.runtime_delta_placement() {
+            eprintln!("[launch] runtime delta kept next to parent VHD: {}", reason);
+        }
+    }
+    emit_launch_progress(window, &game.id, "mounting");
+    emit_launch_event(window, &game.id, LaunchStage::Mounting);
+    // Opened before waiting for letters so the broker is ready, and kept alive until the
+    // volumes are mounted and unlocked.
+    let session = LaunchSession::open(window.app_handle());
+    let held = wait_for_mount_letters(&resolved, |wait| {
+        let _ = window.emit(
+            "vhd-letter-wait",
+            LetterWaitEvent {
+                game_id: &game.id,
+                wait,
+            },
+        );
+    });
+    for holder in &held {
+        eprintln!("[launch] {}, mounting on another letter", holder.describe());
+    }
+    let mounted = match mount_launch_vhd(window.app_handle(), &game.id, &resolved, &session) {
+        Ok(mounted) => mounted,
+        Err(err) => {
+            session.finish();
+            emit_launch_progress(window, &game.id, "error");
+            return Err((err).into());
+        }
+    };
+
+    let points = mounted.mount_points().clone();
+    let result = (|| -> ApiResult<()> {
+        let locked_drives = locked_bitlocker_mounts(&points)?;
+        if !locked_drives.is_empty() {
+            emit_launch_progress(window, &game.id, "unlocking");
+            unlock_mounted_vhd_bitlocker_volumes(&locked_drives)?;
+        }
+        session.finish();
+
+        if let Some(zip_path) = unpacked_zip.as_ref() {
+            apply_unpacked_zip_overlay(&points.app.root(), zip_path)?;
+        }
+
+        emit_launch_progress(window, &game.id, "detecting");
+        let detected = detect_game_on_mount(&points)?;
+        let (mut cfg, seg_path) = load_launch_config(game, profile_id, &detected.name)?;
+
+        emit_launch_progress(window, &game.id, "configuring");
+        emit_launch_event(window, &game.id, LaunchStage::Configuring);
+        // Paths come from wherever the volumes were attached, so segatools.ini follows the allocation.
+        let vfs = detect_vfs_paths_on_drive(&points)?;
+        let version = read_icf_app_version(&window.app_handle().state::<AppState>(), Path::new(&vfs.amfs));
+        if let Some(version) = version.as_deref() {
+            let mut stored = game.clone();
+            if apply_detected_version(&mut stored, &detected.name, version) {
+                // VHD games launch with the detected title's defaults, so only the version is kept.
+                stored.launch_args = game.launch_args.clone();
+                let _ = store::save_game(stored);
+            }
+        }
+        cfg.vfs.enable = true;
+        cfg.vfs.amfs = vfs.amfs;
+        cfg.vfs.appdata = vfs.appdata;
+        cfg.vfs.option = vfs.option;
+        ensure_vfs_keys_present(&mut cfg);
+        let mut txn = FileTransaction::new();
+        txn.stage_segatoools(&seg_path, &cfg).map_err(ApiError::from)?;
+
+        if cfg.keychip.id.is_empty() {
+            txn.commit().map_err(ApiError::from)?;
+            return Err(("Missing required fields: Keychip ID. Please configure it in settings.".to_string()).into());
+        }
+
+        let overlay = match credit_mode {
+            Some(mode) => Some(stage_credit_overlay(&mut txn, &seg_path, cfg, &detected.name, mode)?),
+            None => None,
+        };
+        txn.commit().map_err(ApiError::from)?;
+
+        emit_launch_progress(window, &game.id, "launching");
+        let mut launch_args = match rule_for_game_key(&canonical_game_key(&detected.name)) {
+            Some(rule) => rule.default_args_for(version.as_deref()),
+            None => detected.launch_args,
+        };
+        if let Some(preset) = active_display_preset(game) {
+            set_screen_args(&mut launch_args, preset.width, preset.height);
+        }
+        let launch_game = Game {
+            id: game.id.clone(),
+            name: detected.name,
+            executable_path: detected.executable_path,
+            working_dir: Some(detected.working_dir),
+            launch_args,
+            enabled: game.enabled,
+            tags: game.tags.clone(),
+            launch_mode: LaunchMode::Folder,
+            sandbox: game.sandbox.clone(),
+            gpu_preference: game.gpu_preference,
+            display_presets: game.display_presets.clone(),
+            display_preset: game.display_preset.clone(),
+            pre_launch: game.pre_launch.clone(),
+            post_exit: game.post_exit.clone(),
+            watchdog: game.watchdog.clone(),
+            detected_version: version.clone(),
+        };
+
+        let launched = spawn_launch(&launch_game, game, overlay.as_deref(), window)?;
+        let mounted_for_thread = mounted.clone();
+        let window = window.clone();
+        let owner = game.clone();
+        std::thread::spawn(move || {
+            // Restarts reuse the mounted VHD and post-exit hooks can still reach its files.
+            supervise_launch(&window, &owner, &launch_game, overlay.as_deref(), launched);
+            lock_mounted_vhd_bitlocker_volumes_best_effort(mounted_for_thread.mount_points());
+            let _ = mounted_for_thread.unmount();
+            emit_launch_event(&window, &owner.id, LaunchStage::Unmounted);
+            if let Some(on_exit) = on_exit {
+                on_exit();
+            }
+        });
+        Ok(())
+    })();
+
+    if result.is_err() {
+        lock_mounted_vhd_bitlocker_volumes_best_effort(&points);
+        let _ = mounted.unmount();
+        emit_launch_event(window, &game.id, LaunchStage::Unmounted);
+        emit_launch_progress(window, &game.id, "error");
+    } else {
+        emit_launch_progress(window, &game.id, "started");
+    }
+    result
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchPlanStep {
+    /// Mirrors the `launch-progress` stages, plus "resolving" and "validating".
+    pub stage: String,
+    pub description: String,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PredictedVfsPath {
+    pub key: String,
+    pub path: String,
+    /// "recorded" when taken from the last saved segatools.ini, "default" otherwise.
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchPlan {
+    pub game_id: String,
+    pub ready: bool,
+    pub steps: Vec<LaunchPlanStep>,
+    pub predicted_vfs: Vec<PredictedVfsPath>,
+}
+
+impl LaunchPlan {
+    fn step(&mut self, stage: &str, description: impl Into<String>, outcome: Result<(), String>) -> bool {
+        let ok = outcome.is_ok();
+        if !ok {
+            self.ready = false;
+        }
+        self.steps.push(LaunchPlanStep {
+            stage: stage.to_string(),
+            description: description.into(),
+            ok,
+            error: outcome.err(),
+        });
+        ok
+    }
+}
+
+/// VFS paths the launch is expected to write, preferring the mapping recorded by the last
+/// successful mount when it still lies on the mount points the launch would get.
+fn predict_vfs_paths(recorded: Option<&SegatoolsConfig>, points: &MountPoints) -> Vec<PredictedVfsPath> {
+    let defaults = [
+        ("amfs", points.app.root().join("amfs")),
+        ("appdata", points.appdata.root()),
+        ("option", points.option.root()),
+    ];
+    defaults
+        .iter()
+        .map(|(key, default)| {
+            let recorded = recorded
+                .map(|cfg| match *key {
+                    "amfs" => cfg.vfs.amfs.clone(),
+                    "appdata" => cfg.vfs.appdata.clone(),
+                    _ => cfg.vfs.option.clone(),
+                })
+                .filter(|path| points.contains(path));
+            PredictedVfsPath {
+                key: key.to_string(),
+                source: if recorded.is_some() { "recorded" } else { "default" }.to_string(),
+                path: recorded.unwrap_or_else(|| default.to_string_lossy().to_string()),
+            }
+        })
+        .collect()
+}
+
+/// Walks the same steps as `launch_vhd_game` without attaching images, writing configs or
+/// spawning processes, and reports what a real launch would do.
+fn simulate_vhd_launch(game: &Game, profile_id: Option<String>, credit_mode: Option<CreditMode>) -> LaunchPlan {
+    let mut plan = LaunchPlan {
+        game_id: game.id.clone(),
+        ready: true,
+        steps: Vec::new(),
+        predicted_vfs: Vec::new(),
+    };
+    let enabled = if game.enabled { Ok(()) } else { Err("Game is disabled".to_string()) };
+    plan.step("validating", format!("Launch {}", game.name), enabled);
+
+    let resolved = load_vhd_config(&game.id)
+        .map_err(|e| e.to_string())
+        .and_then(|cfg| resolve_vhd_config(&game.id, &cfg));
+    let mut resolved = match resolved {
+        Ok(resolved) => resolved,
+        Err(err) => {
+            plan.step("resolving", "Resolve VHD chain from vhd.json", Err(err));
+            return plan;
+        }
+    };
+    let chain = std::iter::once(&resolved.app_base_path)
+        .chain(resolved.app_patch_paths.iter())
+        .map(|p| p.to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+        .join(" -> ");
+    plan.step("resolving", format!("Resolve VHD chain: {}", chain), Ok(()));
+
+    let unpacked_zip = find_unpacked_zip_for_chain(&resolved.app_base_path, &resolved.app_patch_paths);
+    if let Some(zip) = unpacked_zip.as_ref() {
+        resolved.delta_enabled = true;
+        plan.step(
+            "mounting",
+            format!("Extract unpacked overlay {} onto the runtime delta", zip.display()),
+            Ok(()),
+        );
+    }
+    if resolved.delta_enabled {
+        let (delta_path, fallback) = resolved.runtime_delta_placement();
+        let mut description = match &resolved.delta_restore {
+            Some(snapshot) => format!(
+                "Restore runtime delta at {} from snapshot {}",
+                delta_path.display(),
+                snapshot.display()
+            ),
+            None => format!("Create runtime delta at {}", delta_path.display()),
+        };
+        if let Some(reason) = fallback {
+            description.push_str(&format!(" ({})", reason));
+        }
+        plan.step("mounting", description, Ok(()));
+    }
+
+    let allocation = plan_mount_points(&resolved);
+    let points = allocation.clone().unwrap_or_default();
+    plan.step(
+        "mounting",
+        format!("Allocate mount points: {}", points.describe()),
+        allocation.map(|_| ()),
+    );
+    plan.step(
+        "mounting",
+        format!(
+            "Attach app at {}, appdata {} at {}, option {} at {}",
+            points.app.access_path(),
+            resolved.appdata_path.display(),
+            points.appdata.access_path(),
+            resolved.option_path.display(),
+            points.option.access_path()
+        ),
+        Ok(()),
+    );
+
+    let seg_path = segatoools_path_for_game_id(&game.id).map_err(|e| e.to_string());
+    let recorded = seg_path
+        .as_ref()
+        .ok()
+        .filter(|path| path.exists())
+        .and_then(|path| load_segatoools_config(path).ok());
+    plan.predicted_vfs = predict_vfs_paths(recorded.as_ref(), &points);
+    plan.step(
+        "detecting",
+        format!("Detect game executable and VFS folders on {}", points.app.access_path()),
+        Ok(()),
+    );
+
+    // Config validation uses the game name stored in the library; the real launch re-sanitizes
+    // with the detected name after mounting.
+    let cfg = match profile_id.filter(|s| !s.is_empty()) {
+        Some(pid) => load_profile(&pid, Some(&game.id))
+            .map(|profile| profile.segatools)
+            .map_err(|e| e.to_string()),
+        None => match (&seg_path, recorded) {
+            (_, Some(cfg)) => Ok(cfg),
+            (Err(err), None) => Err(err.clone()),
+            (Ok(_), None) => Err("segatools.ini not found. Please configure the game.".to_string()),
+        },
+    }
+    .map(|cfg| sanitize_segatoools_for_game(cfg, Some(&game.name)));
+    let validated = cfg.and_then(|cfg| {
+        if cfg.keychip.id.is_empty() {
+            Err("Missing required fields: Keychip ID. Please configure it in settings.".to_string())
+        } else {
+            Ok(())
+        }
+    });
+    plan.step("configuring", "Write VFS paths into segatools.ini", validated);
+    if let Some(mode) = credit_mode {
+        plan.step(
+            "configuring",
+            format!("Write segatools_launch.ini with {} credit mode", format!("{:?}", mode).to_lowercase()),
+            Ok(()),
+        );
+    }
+    plan.step("launching", "Start the detected executable and unmount after exit", Ok(()));
+    plan
+}
+
+#[command]
+pub async fn dry_run_launch_cmd(
+    id: String,
+    profile_id: Option<String>,
+    credit_mode: Option<CreditMode>,
+) -> ApiResult<LaunchPlan> {
+    tauri::async_runtime::spawn_blocking(move || -> ApiResult<LaunchPlan> {
+        let games = store::list_games().map_err(|e| ApiError::from(e.to_string()))?;
+        let game = games
+            .into_iter()
+            .find(|g| g.id == id)
+            .ok_or_else(|| "Game not found".to_string())?;
+        if !matches!(game.launch_mode, LaunchMode::Vhd) {
+            return Err(ApiError::from("Dry run is only available for VHD games".to_string()));
+        }
+        Ok(simulate_vhd_launch(&game, profile_id, credit_mode))
+    })
+    .await
+    .map_err(|e| ApiError::from(e.to_string()))?
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchQueueItem {
+    pub game_id: String,
+    #[serde(default)]
+    pub profile_id: Option<String>,
+    #[serde(default)]
+    pub credit_mode: Option<CreditMode>,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchQueueStatus {
+    pub running: bool,
+    pub current: Option<LaunchQueueItem>,
+    pub pending: Vec<LaunchQueueItem>,
+    pub completed: usize,
+    pub failed: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LaunchQueueProgress {
+    /// "starting", "running", "exited", "failed", "stopped" or "finished".
+    stage: String,
+    game_id: Option<String>,
+    error: Option<String>,
+    status: LaunchQueueStatus,
+}
+
+#[derive(Default)]
+pub(super) struct LaunchQueueState {
+    pub(super) status: LaunchQueueStatus,
+    stop_requested: bool,
+}
+
+fn emit_launch_queue_progress(window: &Window, stage: &str, game_id: Option<&str>, error: Option<String>) {
+    let status = window.state::<AppState>().launch_queue().status.clone();
+    let _ = window.emit(
+        "launch-queue-progress",
+        LaunchQueueProgress {
+            stage: stage.to_string(),
+            game_id: game_id.map(str::to_string),
+            error,
+            status,
+        },
+    );
+}
+
+// Runs queued games one at a time. The next title is only launched after the previous one
+// has exited and its VHDs are unmounted, so drive letters are free again.
+fn run_launch_queue(window: Window) {
+    let state = window.state::<AppState>();
+    loop {
+        let item = {
+            let mut queue = state.launch_queue();
+            if queue.stop_requested || queue.status.pending.is_empty() {
+                let stopped = queue.stop_requested;
+                queue.status.running = false;
+                queue.status.current = None;
+                queue.stop_requested = false;
+                drop(queue);
+                emit_launch_queue_progress(&window, if stopped { "stopped" } else { "finished" }, None, None);
+                return;
+            }
+            let item = queue.status.pending.remove(0);
+            queue.status.current = Some(item.clone());
+            item
+        };
+
+        emit_launch_queue_progress(&window, "starting", Some(&item.game_id), None);
+        let (exit_tx, exit_rx) = std::sync::mpsc::channel::<()>();
+        let on_exit: LaunchExitHook = Box::new(move || {
+            let _ = exit_tx.send(());
+        });
+        match run_launch(&item.game_id, item.profile_id.clone(), item.credit_mode, &window, Some(on_exit)) {
+            Ok(()) => {
+                emit_launch_queue_progress(&window, "running", Some(&item.game_id), None);
+                let _ = exit_rx.recv();
+                state.launch_queue().status.completed += 1;
+                emit_launch_queue_progress(&window, "exited", Some(&item.game_id), None);
+            }
+            Err(err) => {
+                state.launch_queue().status.failed += 1;
+                emit_launch_queue_progress(&window, "failed", Some(&item.game_id), Some(err.message));
+            }
+        }
+    }
+}
+
+#[command]
+pub fn get_launch_queue_cmd(state: State<'_, AppState>) -> ApiResult<LaunchQueueStatus> {
+    Ok(state.launch_queue().status.clone())
+}
+
+#[command]
+pub fn enqueue_launch_cmd(state: State<'_, AppState>, items: Vec<LaunchQueueItem>) -> ApiResult<LaunchQueueStatus> {
+    let games = store::list_games().map_err(|e| ApiError::from(e.to_string()))?;
+    for item in &items {
+        if !games.iter().any(|g| g.id == item.game_id) {
+            return Err((format!("Game not found: {}", item.game_id)).into());
+        }
+    }
+    let mut queue = state.launch_queue();
+    queue.status.pending.extend(items);
+    Ok(queue.status.clone())
+}
+
+#[command]
+pub fn clear_launch_queue_cmd(state: State<'_, AppState>) -> ApiResult<LaunchQueueStatus> {
+    let mut queue = state.launch_queue();
+    queue.status.pending.clear();
+    if !queue.status.running {
+        queue.status.completed = 0;
+        queue.status.failed = 0;
+    }
+    Ok(queue.status.clone())
+}
+
+#[command]
+pub fn start_launch_queue_cmd(state: State<'_, AppState>, window: Window) -> ApiResult<LaunchQueueStatus> {
+    let status = {
+        let mut queue = state.launch_queue();
+        if queue.status.running {
+            return Ok(queue.status.clone());
+        }
+        if queue.status.pending.is_empty() {
+            return Err(("Launch queue is empty".to_string()).into());
+        }
+        queue.status.running = true;
+        queue.status.completed = 0;
+        queue.status.failed = 0;
+        queue.stop_requested = false;
+        queue.status.clone()
+    };
+    std::thread::spawn(move || run_launch_queue(window));
+    Ok(status)
+}
+
+/// Stops after the current game exits; the running title is not terminated.
+#[command]
+pub fn stop_launch_queue_cmd(state: State<'_, AppState>) -> ApiResult<LaunchQueueStatus> {
+    let mut queue = state.launch_queue();
+    if queue.status.running {
+        queue.stop_requested = true;
+    }
+    Ok(queue.status.clone())
+}
+
+const LAST_FAILURE_REPORT_FILE: &str = "configarc_last_failure.json";
+const FAILURE_LOG_TAIL_LINES: usize = 200;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailureReport {
+    pub game_id: String,
+    pub game_name: String,
+    pub launch_mode: LaunchMode,
+    pub captured_at: String,
+    pub error_code: String,
+    pub error_message: String,
+    pub os_build: Option<String>,
+    pub gpus: Vec<String>,
+    pub segatools_version: Option<String>,
+    pub config_hash: Option<String>,
+    pub vhd_config: Option<VhdConfig>,
+    pub log_source: Option<String>,
+    pub log_lines: Vec<String>,
+}
+
+fn last_failure_report_path() -> PathBuf {
+    Path::new(".").join(LAST_FAILURE_REPORT_FILE)
+}
+
+fn query_os_build() -> Option<String> {
+    if !platform::powershell_support().runs_cmdlets() {
+        return query_os_build_from_registry();
+    }
+    let script = "$v=Get-ItemProperty 'HKLM:\\SOFTWARE\\Microsoft\\Windows NT\\CurrentVersion'; \"$($v.ProductName) $($v.DisplayVersion) (build $($v.CurrentBuild).$($v.UBR))\"";
+    run_powershell_capture_with_env(script, None)
+        .ok()
+        .filter(|s| !s.is_empty())
+}
+
+/// Same fields as the PowerShell query, read through reg.exe for hosts without PowerShell.
+fn query_os_build_from_registry() -> Option<String> {
+    let output = Command::new("reg")
+        .args(["query", r"HKLM\SOFTWARE\Microsoft\Windows NT\CurrentVersion"])
+        .creation_flags(0x08000000)
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let value = |name: &str| {
+        stdout.lines().find_map(|line| {
+            let mut parts = line.split_whitespace();
+            if parts.next()? != name {
+                return None;
+            }
+            let kind = parts.next()?;
+            let raw = parts.collect::<Vec<_>>().join(" ");
+            if kind == "REG_DWORD" {
+                u64::from_str_radix(raw.trim_start_matches("0x"), 16).ok().map(|n| n.to_string())
+            } else {
+                Some(raw)
+            }
+        })
+    };
+    let product = value("ProductName")?;
+    let display = value("DisplayVersion").unwrap_or_default();
+    let build = value("CurrentBuild").unwrap_or_default();
+    let ubr = value("UBR").unwrap_or_default();
+    Some(format!("{} {} (build {}.{})", product, display, build, ubr))
+}
+
+fn query_gpu_names() -> Vec<String> {
+    let script = "Get-CimInstance Win32_VideoController | ForEach-Object { \"$($_.Name) (driver $($_.DriverVersion))\" }";
+    run_powershell_capture_with_env(script, None)
+        .map(|out| {
+            out.lines()
+                .map(|line| line.trim().to_string())
+                .filter(|line| !line.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn newest_log_file(dirs: &[PathBuf]) -> Option<PathBuf> {
+    let mut newest: Option<(SystemTime, PathBuf)> = None;
+    for dir in dirs {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_log = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .map(|ext| ext.eq_ignore_ascii_case("log"))
+                .unwrap_or(false);
+            if !is_log {
+                continue;
+            }
+            let modified = match entry.metadata().and_then(|m| m.modified()) {
+                Ok(modified) => modified,
+                Err(_) => continue,
+            };
+            if newest.as_ref().map(|(time, _)| modified > *time).unwrap_or(true) {
+                newest = Some((modified, path));
+            }
+        }
+    }
+    newest.map(|(_, path)| path)
+}
+
+fn tail_lines(path: &Path, max_lines: usize) -> Vec<String> {
+    let content = match fs::read(path) {
+        Ok(bytes) => redact_keychip_id(&String::from_utf8_lossy(&bytes)),
+        Err(_) => return Vec::new(),
+    };
+    let lines: Vec<&str> = content.lines().collect();
+    let start = lines.len().saturating_sub(max_lines);
+    lines[start..].iter().map(|line| line.trim_end().to_string()).collect()
+}
+
+fn capture_failure_report(game: &Game, err: &ApiError) -> FailureReport {
+    let seg_root = segatools_root_for_game_id(&game.id);
+    let config_hash = segatoools_path_for_game_id(&game.id)
+        .ok()
+        .and_then(|path| fs::read(path).ok())
+        .map(|bytes| hex::encode(Sha256::digest(&bytes)));
+    let vhd_config = if matches!(game.launch_mode, LaunchMode::Vhd) {
+        load_vhd_config(&game.id).ok()
+    } else {
+        None
+    };
+
+    let mut log_dirs = vec![seg_root.clone()];
+    if let Some(dir) = store::game_root_dir(game) {
+        log_dirs.push(dir);
+    }
+    let log_file = newest_log_file(&log_dirs);
+    let log_lines = log_file
+        .as_ref()
+        .map(|path| tail_lines(path, FAILURE_LOG_TAIL_LINES))
+        .unwrap_or_default();
+
+    FailureReport {
+        game_id: game.id.clone(),
+        game_name: game.name.clone(),
+        launch_mode: game.launch_mode.clone(),
+        captured_at: chrono::Utc::now().to_rfc3339(),
+        error_code: err.code.clone(),
+        error_message: err.message.clone(),
+        os_build: query_os_build(),
+        gpus: query_gpu_names(),
+        segatools_version: local_segatools_version(&seg_root),
+        config_hash,
+        vhd_config,
+        log_source: log_file.map(|path| path.to_string_lossy().to_string()),
+        log_lines,
+    }
+}
+
+fn record_failure_report(game: &Game, err: &ApiError) {
+    let report = capture_failure_report(game, err);
+    if let Ok(json) = serde_json::to_string_pretty(&report) {
+        let _ = fs::write(last_failure_report_path(), json);
+    }
+}
+
+#[command]
+pub fn get_last_failure_report_cmd() -> ApiResult<Option<FailureReport>> {
+    let path = last_failure_report_path();
+    if !path.exists() {
+        return Ok(None);
+    }
+    let data = fs::read_to_string(&path).map_err(|e| ApiError::from(e.to_string()))?;
+    let report = serde_json::from_str::<FailureReport>(&data).map_err(|e| ApiError::from(e.to_string()))?;
+    Ok(Some(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_launch_arguments() {
+        let args = |list: &[&str]| list.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            CliLaunch::from_args(args(&["--launch", "maimai DX", "--profile=Online"])),
+            Some(CliLaunch {
+                game: "maimai DX".to_string(),
+                profile: Some("Online".to_string()),
+            })
+        );
+        assert_eq!(CliLaunch::from_args(args(&["--profile", "Online"])), None);
+        assert_eq!(CliLaunch::from_args(args(&["--launch"])), None);
+    }
+}
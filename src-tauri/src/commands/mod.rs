@@ -0,0 +1,41 @@
+mod aime;
+mod backup;
+mod config;
+#[cfg(feature = "fsdecrypt")]
+mod decrypt;
+mod disk_usage;
+mod download;
+mod features;
+mod games;
+mod icf;
+mod launch;
+mod mods;
+#[cfg(feature = "privexec")]
+mod privexec;
+mod remote;
+mod settings;
+mod state;
+mod system;
+mod trash;
+mod vhd;
+
+pub use aime::*;
+pub use backup::*;
+pub use config::*;
+#[cfg(feature = "fsdecrypt")]
+pub use decrypt::*;
+pub use disk_usage::*;
+pub use download::*;
+pub use features::*;
+pub use games::*;
+pub use icf::*;
+pub use launch::*;
+pub use mods::*;
+#[cfg(feature = "privexec")]
+pub use privexec::*;
+pub use remote::*;
+pub use settings::*;
+pub use state::*;
+pub use system::*;
+pub use trash::*;
+pub use vhd::*;
@@ -0,0 +1,324 @@
+use super::config::{
+    active_slot_path, live_segatoools_or_default, record_segatoools_provenance, slot_missing,
+};
+use super::state::AppState;
+use super::trash::trash_game_item;
+use crate::config::{
+    io_dlls::{
+        activate_io_dll, add_io_dll, io_dll_intact, is_active_io_dll, list_io_dlls, load_io_dlls, remove_io_dll, IoDll,
+        IoDllEntry, IoDllKind,
+    },
+    load_segatoools_config,
+    paths::{segatools_root_for_game_id, segatoools_path_for_active},
+    provenance::KeyOrigin,
+    retain_sections,
+    segatools::SegatoolsConfig,
+    slots,
+    transaction::FileTransaction,
+};
+use crate::games::{
+    journal::{self, JournalEntry},
+    mod_conflicts::{detect_mod_conflicts, ModConflictReport},
+    model::{Game, LaunchMode},
+    store,
+};
+use crate::error::{ApiError, ApiResult, ErrorCode};
+use serde::Serialize;
+use tauri::{command, AppHandle, Emitter, State, Window};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize)]
+pub struct ModEntry {
+    pub name: String,
+    pub path: String,
+    pub size: u64,
+}
+
+#[derive(Serialize)]
+pub struct ModsStatus {
+    pub supported: bool,
+    pub game: Option<String>,
+    pub melonloader_installed: bool,
+    pub mods_dir: Option<String>,
+    pub mods: Vec<ModEntry>,
+    pub message: Option<String>,
+}
+
+fn detect_melonloader(base: &Path) -> bool {
+    base.join("MelonLoader").is_dir()
+        || base.join("version.dll").exists()
+        || base.join("winhttp.dll").exists()
+        || base.join("mods").join("version.dll").exists()
+}
+
+fn list_mods(dir: &Path) -> ApiResult<Vec<ModEntry>> {
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+    let mut mods = Vec::new();
+    for entry in fs::read_dir(dir).map_err(|e| ApiError::from(e.to_string()))? {
+        let entry = entry.map_err(|e| ApiError::from(e.to_string()))?;
+        let meta = entry.metadata().map_err(|e| ApiError::from(e.to_string()))?;
+        if meta.is_file() {
+            mods.push(ModEntry {
+                name: entry.file_name().to_string_lossy().into_owned(),
+                path: entry.path().to_string_lossy().into_owned(),
+                size: meta.len(),
+            });
+        }
+    }
+    mods.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    Ok(mods)
+}
+
+fn unique_copy_destination(dir: &Path, src: &Path) -> ApiResult<PathBuf> {
+    let name = src.file_name().ok_or_else(|| "Invalid file name".to_string())?;
+    let mut dest = dir.join(name);
+    if !dest.exists() {
+        return Ok(dest);
+    }
+
+    let stem = src
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| "Invalid file name".to_string())?;
+    let ext = src.extension().and_then(|s| s.to_str()).unwrap_or("");
+    let mut index = 1;
+    loop {
+        let candidate = if ext.is_empty() {
+            format!("{}_{}", stem, index)
+        } else {
+            format!("{}_{}.{}", stem, index, ext)
+        };
+        let candidate_path = dir.join(candidate);
+        if !candidate_path.exists() {
+            dest = candidate_path;
+            break;
+        }
+        index += 1;
+    }
+
+    Ok(dest)
+}
+
+#[command]
+pub fn get_mods_status_cmd(state: State<'_, AppState>) -> ApiResult<ModsStatus> {
+    let game = state.active_game()?;
+    let root = state.active_game_root_dir()?;
+    let supported = game.name.eq_ignore_ascii_case("sinmai");
+    let mods_dir = root.join("Mods");
+    let melonloader_installed = detect_melonloader(&root);
+
+    let mods = if supported {
+        list_mods(&mods_dir)?
+    } else {
+        vec![]
+    };
+
+    Ok(ModsStatus {
+        supported,
+        game: Some(game.name),
+        melonloader_installed,
+        mods_dir: if supported {
+            Some(mods_dir.to_string_lossy().into_owned())
+        } else {
+            None
+        },
+        mods,
+        message: if supported {
+            None
+        } else {
+            Some("Mods are only supported for Sinmai right now".to_string())
+        },
+    })
+}
+
+#[command]
+pub fn store_io_dll_cmd(path: String) -> ApiResult<String> {
+    let trimmed = path.trim();
+    if trimmed.is_empty() {
+        return Err(("Path is empty".to_string()).into());
+    }
+    let src = PathBuf::from(trimmed);
+    if !src.exists() || !src.is_file() {
+        return Err((format!("File not found: {}", trimmed)).into());
+    }
+    let seg_path = segatoools_path_for_active().map_err(|e| ApiError::from(e.to_string()))?;
+    if !seg_path.exists() {
+        return Err(("segatools.ini not found. Please deploy first.".to_string()).into());
+    }
+    let base = seg_path.parent().ok_or_else(|| "Invalid segatools.ini path".to_string())?;
+    let io_dir = base.join("IO");
+    fs::create_dir_all(&io_dir).map_err(|e| ApiError::from(e.to_string()))?;
+    let dest = unique_copy_destination(&io_dir, &src)?;
+    fs::copy(&src, &dest).map_err(|e| ApiError::from(e.to_string()))?;
+    let relative = dest.strip_prefix(base).unwrap_or(&dest);
+    Ok(relative.to_string_lossy().into_owned())
+}
+
+fn active_slot_config(slot: Option<&str>) -> ApiResult<(PathBuf, SegatoolsConfig)> {
+    let path = active_slot_path(slot)?;
+    if !path.exists() {
+        return Err(slot_missing(&path));
+    }
+    let cfg = load_segatoools_config(&path).map_err(|e| ApiError::from(e.to_string()))?;
+    Ok((path, cfg))
+}
+
+/// The active game's IO DLL library, marking the DLLs the slot's config loads.
+#[command]
+pub fn list_io_dlls_cmd(state: State<'_, AppState>, slot: Option<String>) -> ApiResult<Vec<IoDllEntry>> {
+    let game = state.active_game()?;
+    let (_, cfg) = active_slot_config(slot.as_deref())?;
+    Ok(list_io_dlls(&segatools_root_for_game_id(&game.id), &cfg)?)
+}
+
+#[command]
+pub fn add_io_dll_cmd(
+    state: State<'_, AppState>,
+    path: String,
+    kind: IoDllKind,
+    label: Option<String>,
+) -> ApiResult<IoDll> {
+    let game = state.active_game()?;
+    let root = segatools_root_for_game_id(&game.id);
+    let dll = add_io_dll(&root, Path::new(path.trim()), kind, label.as_deref().unwrap_or_default())?;
+    journal::record(
+        &game.id,
+        JournalEntry::new("io_dll_add", format!("Added {} \"{}\" to the IO library", kind.section(), dll.label))
+            .with_details(serde_json::json!({ "id": dll.id, "sha256": dll.sha256 })),
+    );
+    Ok(dll)
+}
+
+/// Switches the slot's `[aimeio]`/`[mai2io]`/`[chuniio]`/`[mu3io]` path to a DLL of the
+/// library. The config is replaced in one rename, so a failed write leaves the old DLL set.
+#[command]
+pub fn activate_io_dll_cmd(state: State<'_, AppState>, id: String, slot: Option<String>) -> ApiResult<SegatoolsConfig> {
+    let game = state.active_game()?;
+    let root = segatools_root_for_game_id(&game.id);
+    let dll = load_io_dlls(&root)?
+        .into_iter()
+        .find(|d| d.id == id)
+        .ok_or_else(|| ApiError::new(ErrorCode::NotFound, format!("IO DLL {} not found", id)))?;
+
+    let _guard = state.lock_config();
+    if !io_dll_intact(&root, &dll) {
+        return Err(ApiError::new(
+            ErrorCode::InvalidInput,
+            format!("{} changed or went missing since it was added; add it again", dll.file),
+        ));
+    }
+    let (path, mut cfg) = active_slot_config(slot.as_deref())?;
+    let before = live_segatoools_or_default(&path);
+    activate_io_dll(&dll, &mut cfg);
+    retain_sections(&mut cfg, &[dll.kind.section().to_string()]);
+    let mut txn = FileTransaction::new();
+    let result = txn.stage_segatoools(&path, &cfg).and_then(|_| txn.commit());
+    journal::record(
+        &game.id,
+        JournalEntry::new("io_dll_activate", format!("Switched {} to \"{}\"", dll.kind.section(), dll.label))
+            .with_outcome(&result)
+            .with_details(serde_json::json!({ "id": dll.id, "file": dll.file, "slot": slot })),
+    );
+    result.map_err(ApiError::from)?;
+    let after = live_segatoools_or_default(&path);
+    if slots::is_default(slot.as_deref()) {
+        record_segatoools_provenance(&game, &before, &after, &KeyOrigin::user());
+    }
+    Ok(after)
+}
+
+/// Removes a DLL from the library and deletes its copy. The DLL in use cannot be removed.
+#[command]
+pub fn delete_io_dll_cmd(state: State<'_, AppState>, id: String, slot: Option<String>) -> ApiResult<()> {
+    let game = state.active_game()?;
+    let root = segatools_root_for_game_id(&game.id);
+    let (_, cfg) = active_slot_config(slot.as_deref())?;
+    if let Some(dll) = load_io_dlls(&root)?.iter().find(|d| d.id == id && is_active_io_dll(d, &cfg)) {
+        return Err(ApiError::new(
+            ErrorCode::InvalidInput,
+            format!("{} is in use; activate another DLL first", dll.label),
+        ));
+    }
+    let result = remove_io_dll(&root, &id);
+    journal::record(
+        &game.id,
+        JournalEntry::new("io_dll_delete", format!("Removed IO DLL {}", id)).with_outcome(&result),
+    );
+    result?;
+    Ok(())
+}
+
+#[command]
+pub fn add_mods_cmd(state: State<'_, AppState>, paths: Vec<String>) -> ApiResult<Vec<ModEntry>> {
+    let game = state.active_game()?;
+    if !game.name.eq_ignore_ascii_case("sinmai") {
+        return Err(("Mods are only supported for Sinmai".to_string()).into());
+    }
+    let _operation = state.begin_operation(&game.id, "mod install")?;
+    let mods_dir = state.active_game_root_dir()?.join("Mods");
+    fs::create_dir_all(&mods_dir).map_err(|e| ApiError::from(e.to_string()))?;
+
+    for src in paths {
+        let src_path = PathBuf::from(&src);
+        if !src_path.exists() || !src_path.is_file() {
+            return Err((format!("Mod file not found: {}", src)).into());
+        }
+        let Some(name) = src_path.file_name() else {
+            return Err(("Invalid mod file name".to_string()).into());
+        };
+        let dest = mods_dir.join(name);
+        fs::copy(&src_path, &dest).map_err(|e| ApiError::from(e.to_string()))?;
+        journal::record(
+            &game.id,
+            JournalEntry::new("mod_install", format!("Installed mod {}", name.to_string_lossy())),
+        );
+    }
+
+    list_mods(&mods_dir)
+}
+
+#[command]
+pub fn check_mod_conflicts_cmd(state: State<'_, AppState>) -> ApiResult<ModConflictReport> {
+    let game = state.active_game()?;
+    if !game.name.eq_ignore_ascii_case("sinmai") {
+        return Err(("Mods are only supported for Sinmai".to_string()).into());
+    }
+    let mods_dir = state.active_game_root_dir()?.join("Mods");
+    Ok(detect_mod_conflicts(&mods_dir))
+}
+
+// Conflicts do not block the launch; the UI surfaces them next to the launch progress.
+pub(super) fn emit_mod_conflicts(window: &Window, game: &Game) {
+    if !game.name.eq_ignore_ascii_case("sinmai") || matches!(game.launch_mode, LaunchMode::Vhd) {
+        return;
+    }
+    let Some(root) = store::game_root_dir(game) else {
+        return;
+    };
+    let report = detect_mod_conflicts(&root.join("Mods"));
+    if !report.conflicts.is_empty() {
+        let _ = window.emit("mod-conflicts", &report);
+    }
+}
+
+#[command]
+pub fn delete_mod_cmd(app: AppHandle, state: State<'_, AppState>, name: String) -> ApiResult<Vec<ModEntry>> {
+    let game = state.active_game()?;
+    if !game.name.eq_ignore_ascii_case("sinmai") {
+        return Err(("Mods are only supported for Sinmai".to_string()).into());
+    }
+    let mods_dir = state.active_game_root_dir()?.join("Mods");
+    let sanitized = PathBuf::from(&name);
+    let Some(fname) = sanitized.file_name() else {
+        return Err(("Invalid mod name".to_string()).into());
+    };
+    let target = mods_dir.join(fname);
+    if !target.is_file() {
+        return Err(("Mod not found".to_string()).into());
+    }
+    trash_game_item(&app, &game, &target, "mod")?;
+    list_mods(&mods_dir)
+}
@@ -0,0 +1,413 @@
+use super::settings::{read_app_settings, write_app_settings};
+use super::state::AppState;
+use crate::config::paths::get_active_game_id;
+use crate::games::{journal::{self, JournalEntry}, model::LaunchMode, store};
+use crate::error::{ApiError, ApiResult, ErrorCode};
+use crate::privexec::{
+    bootstrap::{bootstrap_policy, BootstrapReport, BootstrapRoots, DEFAULT_KEY_ID},
+    broker::BrokerClient,
+    hosts::{managed_entries, system_hosts_path, validate_hosts_entry, HostsEntry},
+    BatchResponse as PrivExecBatchResponse, CommandResponse as PrivExecCommandResponse,
+    PolicyUpdateResponse as PrivExecPolicyUpdateResponse, PrivExecConfig, PrivExecCore,
+};
+use crate::vhd::{is_running_as_admin, load_vhd_config};
+use serde::Serialize;
+use tauri::{command, AppHandle, Manager, State};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrivExecPaths {
+    pub root_dir: String,
+    pub policy_path: String,
+    pub audit_log_path: String,
+}
+
+fn resolve_privexec_root_dir(app: &AppHandle, root_dir: Option<&str>) -> ApiResult<PathBuf> {
+    if let Some(root) = root_dir.map(str::trim).filter(|v| !v.is_empty()) {
+        let path = PathBuf::from(root);
+        if !path.is_absolute() {
+            return Err(ApiError::from("privexec rootDir must be an absolute path"));
+        }
+        return Ok(path);
+    }
+
+    let app_root = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| ApiError::from(e.to_string()))?;
+    Ok(app_root.join("privexec"))
+}
+
+fn resolve_privexec_device_id(device_id: Option<&str>) -> String {
+    if let Some(value) = device_id.map(str::trim).filter(|v| !v.is_empty()) {
+        return value.to_string();
+    }
+    if let Ok(value) = std::env::var("CONFIGARC_DEVICE_ID") {
+        if !value.trim().is_empty() {
+            return value.trim().to_string();
+        }
+    }
+    std::env::var("COMPUTERNAME")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .map(|v| v.trim().to_string())
+        .unwrap_or_else(|| "UNKNOWN_DEVICE".to_string())
+}
+
+fn resolve_bootstrap_keys(
+    bootstrap_public_keys: Option<HashMap<String, String>>,
+) -> ApiResult<HashMap<String, String>> {
+    if let Some(keys) = bootstrap_public_keys {
+        return Ok(keys);
+    }
+
+    let from_env = match std::env::var("CONFIGARC_PRIVEXEC_BOOTSTRAP_KEYS") {
+        Ok(raw) if !raw.trim().is_empty() => {
+            serde_json::from_str::<HashMap<String, String>>(&raw)
+                .map_err(|e| ApiError::from(format!("invalid CONFIGARC_PRIVEXEC_BOOTSTRAP_KEYS: {}", e)))?
+        }
+        _ => HashMap::new(),
+    };
+    Ok(from_env)
+}
+
+fn build_privexec_core(
+    app: &AppHandle,
+    root_dir: Option<&str>,
+    device_id: Option<&str>,
+    bootstrap_public_keys: Option<HashMap<String, String>>,
+) -> ApiResult<PrivExecCore> {
+    let mut config = PrivExecConfig::new(
+        resolve_privexec_root_dir(app, root_dir)?,
+        resolve_privexec_device_id(device_id),
+    );
+    config.bootstrap_public_keys = resolve_bootstrap_keys(bootstrap_public_keys)?;
+    PrivExecCore::new(config).map_err(|e| ApiError::from(e.to_string()))
+}
+
+fn privexec_broker_root(app: &AppHandle) -> ApiResult<PathBuf> {
+    Ok(resolve_privexec_root_dir(app, None)?.join("broker"))
+}
+
+fn resident_broker_enabled(app: &AppHandle) -> bool {
+    read_app_settings(app).map(|s| s.resident_broker).unwrap_or(false)
+}
+
+fn connect_privexec_broker(app: &AppHandle) -> Result<BrokerClient, String> {
+    let root = privexec_broker_root(app).map_err(|e| e.message)?;
+    BrokerClient::connect(&root, &resolve_privexec_device_id(None))
+}
+
+fn start_privexec_broker(app: &AppHandle) -> Result<BrokerClient, String> {
+    let root = privexec_broker_root(app).map_err(|e| e.message)?;
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    let result = BrokerClient::start_elevated(&exe, &root, &resolve_privexec_device_id(None), resident_broker_enabled(app));
+    let state = app.state::<AppState>();
+    let mut supervisor = state.broker_supervisor();
+    match &result {
+        Ok(_) => {
+            supervisor.failures = 0;
+            supervisor.last_error = None;
+        }
+        Err(err) => {
+            supervisor.failures += 1;
+            supervisor.last_error = Some(err.clone());
+        }
+    }
+    result
+}
+
+/// Connects to the running privexec broker, starting it elevated if there is none.
+pub(super) fn privexec_broker(app: &AppHandle) -> Result<BrokerClient, String> {
+    connect_privexec_broker(app).or_else(|_| start_privexec_broker(app))
+}
+
+const BROKER_WATCH_INTERVAL: Duration = Duration::from_secs(10);
+/// Failed starts in a row before the supervisor stops, so a declined UAC prompt is not
+/// shown again every few seconds. A launch or re-enabling the setting tries again.
+const MAX_BROKER_START_FAILURES: u32 = 3;
+
+#[derive(Default)]
+pub(super) struct BrokerSupervisorState {
+    /// Times the supervisor brought back a resident broker that had exited.
+    restarts: u32,
+    failures: u32,
+    last_error: Option<String>,
+    seen_running: bool,
+}
+
+/// Keeps the resident broker running while the setting is on, so a cabinet gets one UAC
+/// prompt per boot. Started once from `main`; idle while the setting is off.
+pub fn start_broker_supervisor(app: AppHandle) {
+    if is_running_as_admin() {
+        return;
+    }
+    std::thread::spawn(move || {
+        let state = app.state::<AppState>();
+        loop {
+            if resident_broker_enabled(&app) {
+                if connect_privexec_broker(&app).is_ok() {
+                    state.broker_supervisor().seen_running = true;
+                } else if state.broker_supervisor().failures < MAX_BROKER_START_FAILURES {
+                    let restarting = state.broker_supervisor().seen_running;
+                    if start_privexec_broker(&app).is_ok() && restarting {
+                        state.broker_supervisor().restarts += 1;
+                    }
+                }
+            }
+            std::thread::sleep(BROKER_WATCH_INTERVAL);
+        }
+    });
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrokerStatus {
+    pub running: bool,
+    /// The setting; `resident` below is what the running broker was started with.
+    pub resident_enabled: bool,
+    pub resident: bool,
+    pub pid: Option<u32>,
+    pub port: Option<u16>,
+    pub started_at: Option<String>,
+    pub mounted_games: Vec<String>,
+    pub restarts: u32,
+    pub last_error: Option<String>,
+}
+
+#[command]
+pub fn broker_status_cmd(app: AppHandle, state: State<'_, AppState>) -> ApiResult<BrokerStatus> {
+    let client = connect_privexec_broker(&app).ok();
+    let endpoint = client.as_ref().map(|c| c.endpoint().clone());
+    let mounted_games = client.as_ref().and_then(|c| c.mounted_games().ok()).unwrap_or_default();
+    let supervisor = state.broker_supervisor();
+    Ok(BrokerStatus {
+        running: endpoint.is_some(),
+        resident_enabled: resident_broker_enabled(&app),
+        resident: endpoint.as_ref().is_some_and(|e| e.resident),
+        pid: endpoint.as_ref().map(|e| e.pid),
+        port: endpoint.as_ref().map(|e| e.port),
+        started_at: endpoint.map(|e| e.started_at),
+        mounted_games,
+        restarts: supervisor.restarts,
+        last_error: supervisor.last_error.clone(),
+    })
+}
+
+#[command]
+pub fn set_resident_broker_cmd(app: AppHandle, state: State<'_, AppState>, enabled: bool) -> ApiResult<BrokerStatus> {
+    let mut settings = read_app_settings(&app)?;
+    settings.resident_broker = enabled;
+    write_app_settings(&app, &settings)?;
+    // Turning it on again is the user's answer to earlier declined prompts.
+    state.broker_supervisor().failures = 0;
+    broker_status_cmd(app, state)
+}
+
+#[command]
+pub fn privexec_get_paths_cmd(app: AppHandle, root_dir: Option<String>) -> ApiResult<PrivExecPaths> {
+    let core = build_privexec_core(&app, root_dir.as_deref(), None, None)?;
+    let root = resolve_privexec_root_dir(&app, root_dir.as_deref())?;
+    Ok(PrivExecPaths {
+        root_dir: root.to_string_lossy().to_string(),
+        policy_path: core.policy_path().to_string_lossy().to_string(),
+        audit_log_path: core.audit_log_path().to_string_lossy().to_string(),
+    })
+}
+
+#[command]
+pub fn privexec_execute_cmd(
+    app: AppHandle,
+    request_json: String,
+    root_dir: Option<String>,
+    device_id: Option<String>,
+    bootstrap_public_keys: Option<HashMap<String, String>>,
+) -> ApiResult<PrivExecCommandResponse> {
+    let core = build_privexec_core(
+        &app,
+        root_dir.as_deref(),
+        device_id.as_deref(),
+        bootstrap_public_keys,
+    )?;
+    Ok(core.execute_request_json(&request_json))
+}
+
+/// Runs a signed batch of commands in order, stopping at the first that fails.
+#[command]
+pub fn privexec_execute_batch_cmd(
+    app: AppHandle,
+    request_json: String,
+    root_dir: Option<String>,
+    device_id: Option<String>,
+    bootstrap_public_keys: Option<HashMap<String, String>>,
+) -> ApiResult<PrivExecBatchResponse> {
+    let core = build_privexec_core(
+        &app,
+        root_dir.as_deref(),
+        device_id.as_deref(),
+        bootstrap_public_keys,
+    )?;
+    Ok(core.execute_batch_request_json(&request_json))
+}
+
+#[command]
+pub fn privexec_apply_policy_update_cmd(
+    app: AppHandle,
+    update_json: String,
+    root_dir: Option<String>,
+    device_id: Option<String>,
+    bootstrap_public_keys: Option<HashMap<String, String>>,
+) -> ApiResult<PrivExecPolicyUpdateResponse> {
+    let core = build_privexec_core(
+        &app,
+        root_dir.as_deref(),
+        device_id.as_deref(),
+        bootstrap_public_keys,
+    )?;
+    Ok(core.apply_policy_update_json(&update_json))
+}
+
+fn parent_dir(path: &str) -> Option<String> {
+    Path::new(path.trim())
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .map(|p| p.to_string_lossy().to_string())
+}
+
+/// Folders of the configured games, of their VHD images and of their folder mount points,
+/// for the bootstrap policy's path and mount point rules.
+#[command]
+pub fn privexec_bootstrap_roots_cmd() -> ApiResult<BootstrapRoots> {
+    let mut roots = BootstrapRoots::default();
+    for game in store::list_games().map_err(|e| ApiError::from(e.to_string()))? {
+        if matches!(game.launch_mode, LaunchMode::Vhd) {
+            if let Ok(cfg) = load_vhd_config(&game.id) {
+                let images = [&cfg.app_base_path, &cfg.appdata_path, &cfg.option_path];
+                roots
+                    .vhd_roots
+                    .extend(images.into_iter().chain(&cfg.app_patch_paths).filter_map(|p| parent_dir(p)));
+                roots.mount_roots.extend(cfg.mount_root.filter(|r| !r.trim().is_empty()));
+            }
+            continue;
+        }
+        let dir = game.working_dir.clone().filter(|d| !d.trim().is_empty());
+        roots.game_roots.extend(dir.or_else(|| parent_dir(&game.executable_path)));
+    }
+    for list in [&mut roots.game_roots, &mut roots.vhd_roots, &mut roots.mount_roots] {
+        let mut seen = HashSet::new();
+        list.retain(|root| seen.insert(root.to_lowercase()));
+    }
+    Ok(roots)
+}
+
+/// Sets up a standalone executor: creates the signing key `key_id` (`operator` by default)
+/// sealed under the privexec root, and installs a signed first policy for `roots`, or for
+/// the roots of the configured games. Fails when the executor already has a policy.
+#[command]
+pub fn privexec_bootstrap_policy_cmd(
+    app: AppHandle,
+    root_dir: Option<String>,
+    device_id: Option<String>,
+    key_id: Option<String>,
+    roots: Option<BootstrapRoots>,
+) -> ApiResult<BootstrapReport> {
+    let root = resolve_privexec_root_dir(&app, root_dir.as_deref())?;
+    let key_id = key_id
+        .map(|k| k.trim().to_string())
+        .filter(|k| !k.is_empty())
+        .unwrap_or_else(|| DEFAULT_KEY_ID.to_string());
+    if !key_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(ApiError::new(ErrorCode::InvalidInput, format!("Invalid key id: {}", key_id)));
+    }
+    let roots = match roots {
+        Some(roots) => roots,
+        None => privexec_bootstrap_roots_cmd()?,
+    };
+    let config = PrivExecConfig::new(&root, resolve_privexec_device_id(device_id.as_deref()));
+    let key_path = root.join("keys").join(format!("{}.key", key_id));
+    bootstrap_policy(config, &key_path, &key_id, &roots).map_err(ApiError::from)
+}
+
+fn hosts_overrides() -> ApiResult<Vec<HostsEntry>> {
+    match fs::read_to_string(system_hosts_path()) {
+        Ok(content) => Ok(managed_entries(&content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(ApiError::from(e.to_string())),
+    }
+}
+
+fn hosts_game_id(game_id: Option<String>) -> ApiResult<String> {
+    match game_id.map(|id| id.trim().to_string()).filter(|id| !id.is_empty()) {
+        Some(game_id) => Ok(game_id),
+        None => get_active_game_id()
+            .map_err(|e| ApiError::from(e.to_string()))?
+            .ok_or_else(|| ApiError::from("No active game selected")),
+    }
+}
+
+/// Sends the game's new set of overrides through the broker and journals the change.
+fn update_game_hosts(app: &AppHandle, game_id: &str, entries: &[HostsEntry], message: String) -> ApiResult<Vec<HostsEntry>> {
+    let result = privexec_broker(app).and_then(|broker| broker.update_hosts_entries(Some(game_id), entries));
+    journal::record(game_id, JournalEntry::new("hosts", message).with_outcome(&result));
+    result.map_err(ApiError::from)
+}
+
+/// The launcher's overrides in the system hosts file, with the game each was added for.
+#[command]
+pub fn list_hosts_overrides_cmd() -> ApiResult<Vec<HostsEntry>> {
+    hosts_overrides()
+}
+
+/// Points `host` at `address` in the system hosts file on behalf of a game, the active one
+/// by default. Returns every launcher override afterwards.
+#[command]
+pub fn add_hosts_override_cmd(
+    app: AppHandle,
+    host: String,
+    address: String,
+    game_id: Option<String>,
+) -> ApiResult<Vec<HostsEntry>> {
+    let game_id = hosts_game_id(game_id)?;
+    let entry = HostsEntry {
+        address: address.trim().to_string(),
+        host: host.trim().to_string(),
+        game_id: Some(game_id.clone()),
+    };
+    validate_hosts_entry(&entry).map_err(|e| ApiError::new(ErrorCode::InvalidInput, e))?;
+    let mut entries: Vec<HostsEntry> = hosts_overrides()?
+        .into_iter()
+        .filter(|e| e.game_id.as_deref() == Some(game_id.as_str()) && !e.host.eq_ignore_ascii_case(&entry.host))
+        .collect();
+    let message = format!("Pointed {} at {} in the hosts file", entry.host, entry.address);
+    entries.push(entry);
+    update_game_hosts(&app, &game_id, &entries, message)
+}
+
+#[command]
+pub fn delete_hosts_override_cmd(app: AppHandle, host: String, game_id: Option<String>) -> ApiResult<Vec<HostsEntry>> {
+    let game_id = hosts_game_id(game_id)?;
+    let current = hosts_overrides()?;
+    let (removed, entries): (Vec<HostsEntry>, Vec<HostsEntry>) = current
+        .iter()
+        .filter(|e| e.game_id.as_deref() == Some(game_id.as_str()))
+        .cloned()
+        .partition(|e| e.host.eq_ignore_ascii_case(host.trim()));
+    // Nothing to remove needs no elevation.
+    if removed.is_empty() {
+        return Ok(current);
+    }
+    update_game_hosts(&app, &game_id, &entries, format!("Removed {} from the hosts file", host.trim()))
+}
+
+/// Removes the hosts overrides added for `game_id`. Returns whether there were any.
+pub fn clear_hosts_overrides_for_game(app: &AppHandle, game_id: &str) -> ApiResult<bool> {
+    let owned = hosts_overrides()?.iter().any(|e| e.game_id.as_deref() == Some(game_id));
+    if owned {
+        update_game_hosts(app, game_id, &[], "Removed the game's hosts file overrides".to_string())?;
+    }
+    Ok(owned)
+}
@@ -0,0 +1,456 @@
+use super::config::{
+    active_slot_path, ensure_segatoools_present_sections, gen_profile_id, live_segatoools_or_default,
+    record_segatoools_provenance, sanitize_segatoools_for_game, slot_missing,
+};
+use super::settings::ensure_network_allowed;
+use super::state::AppState;
+use super::system::notify_app;
+use crate::config::{
+    default_segatoools_config, load_segatoools_config,
+    paths::{get_active_game_id, segatoools_path_for_game_id, set_active_game_id},
+    profiles::{save_profile_for_game, ConfigProfile},
+    provenance::KeyOrigin,
+    retain_sections, save_segatoools_config as persist_segatoools_config,
+    segatools::SegatoolsConfig,    slots,
+};
+use crate::games::{detection::canonical_game_key, journal::{self, JournalEntry}, store};
+use crate::error::{ApiError, ApiResult, ErrorCode, Remediation};
+use crate::notifications::{Notification, NotificationSeverity};
+use crate::remote::{RemoteConfigManager, RemoteDefaults, RemoteDirectives, RemoteSyncStatus};
+use crate::presets::{
+    apply_network_preset, apply_server_preset, redact_preset, NetworkPreset, NetworkPresetCatalog, PresetEntry,
+    PresetFeedManager, PresetKind,
+};
+use serde::Serialize;
+use tauri::{command, AppHandle, Manager, State};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+fn remote_config_manager(app: &AppHandle) -> ApiResult<RemoteConfigManager> {
+    let root = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| ApiError::from(e.to_string()))?;
+    RemoteConfigManager::new(root).map_err(|e| ApiError::from(e.to_string()))
+}
+
+fn preset_feed_manager(app: &AppHandle) -> ApiResult<PresetFeedManager> {
+    let root = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| ApiError::from(e.to_string()))?;
+    PresetFeedManager::new(root).map_err(|e| ApiError::from(e.to_string()))
+}
+
+fn network_preset_catalog(app: &AppHandle) -> ApiResult<NetworkPresetCatalog> {
+    let root = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| ApiError::from(e.to_string()))?;
+    NetworkPresetCatalog::new(root).map_err(|e| ApiError::from(e.to_string()))
+}
+
+// Fleet defaults are best-effort: a broken remote cache must not block local operations.
+pub(super) fn remote_defaults(app: &AppHandle) -> RemoteDefaults {
+    remote_config_manager(app)
+        .map(|manager| manager.defaults())
+        .unwrap_or_default()
+}
+
+/// Maintainer directives from the cached remote document. Directives that fail their
+/// signature check are ignored, like a broken cache is for the defaults.
+pub(super) fn remote_directives(app: &AppHandle) -> RemoteDirectives {
+    match remote_config_manager(app).and_then(|manager| manager.directives().map_err(|e| ApiError::from(e.to_string()))) {
+        Ok(directives) => directives,
+        Err(err) => {
+            eprintln!("[remote] ignoring directives: {}", err.message);
+            RemoteDirectives::default()
+        }
+    }
+}
+
+fn directive_refusal(directives: &RemoteDirectives, message: String) -> ApiError {
+    let message = match directives.message.as_deref().map(str::trim).filter(|m| !m.is_empty()) {
+        Some(note) => format!("{} {}", message, note),
+        None => message,
+    };
+    ApiError::new(ErrorCode::FeatureDisabled, message)
+}
+
+/// Refuses `feature` while a maintainer directive disables it.
+pub(super) fn ensure_feature_allowed(app: &AppHandle, feature: &str) -> ApiResult<()> {
+    let directives = remote_directives(app);
+    if directives.is_disabled(feature) {
+        return Err(directive_refusal(
+            &directives,
+            format!("The maintainer has temporarily disabled {}.", feature.replace('_', " ")),
+        ));
+    }
+    Ok(())
+}
+
+/// Refuses launches while the launch kill switch is set or this launcher is older than the
+/// directives' minimum version.
+pub(super) fn ensure_launch_allowed(app: &AppHandle) -> ApiResult<()> {
+    ensure_feature_allowed(app, "launch")?;
+    let directives = remote_directives(app);
+    let version = app.package_info().version.to_string();
+    if directives.requires_update(&version) {
+        let min = directives.min_version.clone().unwrap_or_default();
+        return Err(directive_refusal(
+            &directives,
+            format!("Launcher {} is required to launch games; this is {}. Update before launching.", min.trim(), version),
+        )
+        .with_remediation(Remediation::new("update_app")));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteApplyResult {
+    pub games_applied: usize,
+    pub profiles_applied: usize,
+    pub segatools_applied: usize,
+    pub active_game_id: Option<String>,
+    pub warnings: Vec<String>,
+}
+
+#[command]
+pub fn get_local_override_cmd(app: AppHandle) -> ApiResult<Value> {
+    let manager = remote_config_manager(&app)?;
+    Ok(manager.read_local_override())
+}
+
+#[command]
+pub fn set_local_override_cmd(app: AppHandle, override_json: Value) -> ApiResult<()> {
+    let manager = remote_config_manager(&app)?;
+    manager
+        .write_local_override(&override_json)
+        .map_err(|e| ApiError::from(e.to_string()))
+}
+
+#[command]
+pub fn get_effective_remote_config_cmd(app: AppHandle) -> ApiResult<Value> {
+    let manager = remote_config_manager(&app)?;
+    Ok(manager.effective_config())
+}
+
+/// Directives currently in force, so the UI can explain disabled actions up front.
+#[command]
+pub fn get_remote_directives_cmd(app: AppHandle) -> ApiResult<RemoteDirectives> {
+    Ok(remote_directives(&app))
+}
+
+#[command]
+pub fn sync_remote_config_cmd(app: AppHandle, endpoint: Option<String>) -> ApiResult<RemoteSyncStatus> {
+    ensure_network_allowed(&app)?;
+    let manager = remote_config_manager(&app)?;
+    let before = manager.effective_config();
+    let directives_before = remote_directives(&app);
+    let status = manager.sync_remote(endpoint.as_deref());
+    let directives = remote_directives(&app);
+    if directives != directives_before && directives != RemoteDirectives::default() {
+        let mut lines = Vec::new();
+        if !directives.disabled_features.is_empty() {
+            lines.push(format!("Disabled: {}", directives.disabled_features.join(", ")));
+        }
+        if let Some(min) = directives.min_version.as_deref() {
+            lines.push(format!("Launching requires launcher {} or newer", min));
+        }
+        lines.extend(directives.message.clone());
+        notify_app(
+            &app,
+            Notification::new(
+                "remote_config",
+                NotificationSeverity::Warning,
+                "The maintainer restricted some features",
+                lines.join(". "),
+            )
+            .with_dedupe_key("remote-directives"),
+        );
+    }
+    if status.ok && !status.used_cache && manager.effective_config() != before {
+        notify_app(
+            &app,
+            Notification::new(
+                "remote_config",
+                NotificationSeverity::Info,
+                "Remote configuration changed",
+                format!(
+                    "New settings were fetched from {}",
+                    status.endpoint.as_deref().unwrap_or("the remote endpoint")
+                ),
+            )
+            .with_dedupe_key("remote-config"),
+        );
+    }
+    Ok(status)
+}
+
+#[command]
+pub fn apply_remote_config_cmd(app: AppHandle) -> ApiResult<RemoteApplyResult> {
+    let manager = remote_config_manager(&app)?;
+    let plan = manager.apply_plan().map_err(|e| ApiError::from(e.to_string()))?;
+    let mut result = RemoteApplyResult::default();
+
+    for game in plan.games {
+        store::save_game(game).map_err(|e| ApiError::from(e.to_string()))?;
+        result.games_applied += 1;
+    }
+
+    let mut active_game_id = get_active_game_id().map_err(|e| ApiError::from(e.to_string()))?;
+    if let Some(requested_active) = plan
+        .active_game_id
+        .as_deref()
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+    {
+        set_active_game_id(requested_active).map_err(|e| ApiError::from(e.to_string()))?;
+        active_game_id = Some(requested_active.to_string());
+    }
+    result.active_game_id = active_game_id.clone();
+
+    let games = store::list_games().map_err(|e| ApiError::from(e.to_string()))?;
+    let game_name_by_id: HashMap<String, String> = games
+        .into_iter()
+        .map(|g| (g.id, g.name))
+        .collect();
+
+    for (game_id, profiles) in plan.profiles_by_game {
+        let trimmed = game_id.trim();
+        if trimmed.is_empty() {
+            result.warnings.push("Skipped profilesByGame entry with empty game id".to_string());
+            continue;
+        }
+        let game_name = game_name_by_id.get(trimmed).map(|s| s.as_str());
+        for profile in profiles {
+            let mut profile = profile;
+            ensure_segatoools_present_sections(&mut profile.segatools, game_name);
+            profile.segatools = sanitize_segatoools_for_game(profile.segatools, game_name);
+            save_profile_for_game(&profile, trimmed).map_err(|e| ApiError::from(e.to_string()))?;
+            result.profiles_applied += 1;
+        }
+    }
+
+    if !plan.profiles.is_empty() {
+        if let Some(active_id) = active_game_id.as_deref() {
+            let game_name = game_name_by_id.get(active_id).map(|s| s.as_str());
+            for profile in plan.profiles {
+                let mut profile = profile;
+                ensure_segatoools_present_sections(&mut profile.segatools, game_name);
+                profile.segatools = sanitize_segatoools_for_game(profile.segatools, game_name);
+                save_profile_for_game(&profile, active_id).map_err(|e| ApiError::from(e.to_string()))?;
+                result.profiles_applied += 1;
+            }
+        } else {
+            result.warnings.push("Skipped profiles because no active game is selected".to_string());
+        }
+    }
+
+    for (game_id, cfg) in plan.segatools_by_game {
+        let trimmed = game_id.trim();
+        if trimmed.is_empty() {
+            result.warnings.push("Skipped segatoolsByGame entry with empty game id".to_string());
+            continue;
+        }
+        let game_name = game_name_by_id.get(trimmed).map(|s| s.as_str());
+        let mut cfg = cfg;
+        ensure_segatoools_present_sections(&mut cfg, game_name);
+        let sanitized = sanitize_segatoools_for_game(cfg, game_name);
+        let path = segatoools_path_for_game_id(trimmed).map_err(|e| ApiError::from(e.to_string()))?;
+        persist_segatoools_config(&path, &sanitized).map_err(|e| ApiError::from(e.to_string()))?;
+        result.segatools_applied += 1;
+    }
+
+    if let Some(mut cfg) = plan.segatools {
+        if let Some(active_id) = active_game_id.as_deref() {
+            let game_name = game_name_by_id.get(active_id).map(|s| s.as_str());
+            ensure_segatoools_present_sections(&mut cfg, game_name);
+            let sanitized = sanitize_segatoools_for_game(cfg, game_name);
+            let path = segatoools_path_for_game_id(active_id).map_err(|e| ApiError::from(e.to_string()))?;
+            persist_segatoools_config(&path, &sanitized).map_err(|e| ApiError::from(e.to_string()))?;
+            result.segatools_applied += 1;
+        } else {
+            result.warnings.push("Skipped segatools because no active game is selected".to_string());
+        }
+    }
+
+    Ok(result)
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetBrowseResult {
+    pub fetched_at: Option<String>,
+    pub entries: Vec<PresetEntry>,
+    /// Set when a refresh failed and the cached feed was returned instead.
+    pub error: Option<String>,
+}
+
+#[command]
+pub async fn list_presets_cmd(app: AppHandle, game: Option<String>, refresh: bool) -> ApiResult<PresetBrowseResult> {
+    let manager = preset_feed_manager(&app)?;
+    let mut error = None;
+    if refresh {
+        ensure_network_allowed(&app)?;
+        let mirrors = remote_defaults(&app).segatools_mirrors;
+        let refreshed = tauri::async_runtime::spawn_blocking(move || {
+            let manager = preset_feed_manager(&app)?;
+            manager.refresh(&mirrors).map_err(|e| ApiError::from(e.to_string()))
+        })
+        .await
+        .map_err(|e| ApiError::from(e.to_string()))?;
+        if let Err(err) = refreshed {
+            error = Some(err.message);
+        }
+    }
+    let key = game.as_deref().map(canonical_game_key);
+    Ok(PresetBrowseResult {
+        fetched_at: manager.read_cache().fetched_at,
+        entries: manager.browse(key.as_deref()),
+        error,
+    })
+}
+
+#[command]
+pub fn install_preset_cmd(app: AppHandle, preset_id: String, game_id: String) -> ApiResult<ConfigProfile> {
+    ensure_feature_allowed(&app, "presets")?;
+    let entry = preset_feed_manager(&app)?
+        .find(&preset_id)
+        .map_err(|e| ApiError::from(e.to_string()))?;
+    let games = store::list_games().map_err(|e| ApiError::from(e.to_string()))?;
+    let game = games
+        .into_iter()
+        .find(|g| g.id == game_id)
+        .ok_or_else(|| "Game not found".to_string())?;
+    if !canonical_game_key(&game.name).eq_ignore_ascii_case(&entry.game) {
+        return Err((format!("Preset {} is for {}, not {}", entry.name, entry.game, game.name)).into());
+    }
+    let redacted = redact_preset(&entry).map_err(|e| ApiError::from(e.to_string()))?;
+
+    let segatools = match entry.kind {
+        PresetKind::Profile => redacted,
+        PresetKind::Network => {
+            let seg_path = segatoools_path_for_game_id(&game.id).map_err(|e| ApiError::from(e.to_string()))?;
+            let mut base = if seg_path.exists() {
+                load_segatoools_config(&seg_path).map_err(|e| ApiError::from(e.to_string()))?
+            } else {
+                default_segatoools_config()
+            };
+            // The local keychip must not end up in a profile that might be re-shared.
+            base.keychip.id.clear();
+            apply_network_preset(&mut base, &redacted);
+            base
+        }
+    };
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let description = match (&entry.description, &entry.author) {
+        (Some(desc), Some(author)) => Some(format!("{} ({})", desc, author)),
+        (Some(desc), None) => Some(desc.clone()),
+        (None, Some(author)) => Some(format!("Preset by {}", author)),
+        (None, None) => None,
+    };
+    let profile = ConfigProfile {
+        id: gen_profile_id("preset"),
+        name: entry.name.clone(),
+        description,
+        base_profile_id: None,
+        segatools: sanitize_segatoools_for_game(segatools, Some(game.name.as_str())),
+        segatools_slots: BTreeMap::new(),
+        json_configs: BTreeMap::new(),
+        created_at: now.clone(),
+        updated_at: now,
+    };
+    let result = save_profile_for_game(&profile, &game.id);
+    journal::record(
+        &game.id,
+        JournalEntry::new("preset_install", format!("Installed preset \"{}\"", entry.name))
+            .with_outcome(&result)
+            .with_details(serde_json::json!({ "presetId": entry.id })),
+    );
+    result.map_err(|e| ApiError::from(e.to_string()))?;
+    Ok(profile)
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkPresetBrowseResult {
+    pub fetched_at: Option<String>,
+    pub presets: Vec<NetworkPreset>,
+    /// Set when a refresh failed and the bundled and cached presets were returned instead.
+    pub error: Option<String>,
+}
+
+#[command]
+pub async fn list_network_presets_cmd(
+    app: AppHandle,
+    game: Option<String>,
+    refresh: bool,
+) -> ApiResult<NetworkPresetBrowseResult> {
+    let catalog = network_preset_catalog(&app)?;
+    let mut error = None;
+    if refresh {
+        ensure_network_allowed(&app)?;
+        let mirrors = remote_defaults(&app).segatools_mirrors;
+        let refreshed = tauri::async_runtime::spawn_blocking(move || {
+            let catalog = network_preset_catalog(&app)?;
+            catalog.refresh(&mirrors).map_err(|e| ApiError::from(e.to_string()))
+        })
+        .await
+        .map_err(|e| ApiError::from(e.to_string()))?;
+        if let Err(err) = refreshed {
+            error = Some(err.message);
+        }
+    }
+    let key = game.as_deref().map(canonical_game_key);
+    Ok(NetworkPresetBrowseResult {
+        fetched_at: catalog.read_cache().fetched_at,
+        presets: catalog.list(key.as_deref()),
+        error,
+    })
+}
+
+/// Points the active game's segatools.ini (or one of its slots) at the server of a network
+/// preset. Only `[dns]` and the billing keys of `[keychip]` are written.
+#[command]
+pub fn apply_network_preset_cmd(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    preset_id: String,
+    slot: Option<String>,
+) -> ApiResult<SegatoolsConfig> {
+    ensure_feature_allowed(&app, "presets")?;
+    let preset = network_preset_catalog(&app)?
+        .find(&preset_id)
+        .map_err(|e| ApiError::from(e.to_string()))?;
+    let game = state.active_game()?;
+    let key = canonical_game_key(&game.name);
+    if !preset.supports(&key) {
+        return Err(format!("{} does not serve {}", preset.name, game.name).into());
+    }
+    let path = active_slot_path(slot.as_deref())?;
+    if !path.exists() {
+        return Err(slot_missing(&path));
+    }
+
+    let _guard = state.lock_config();
+    let before = live_segatoools_or_default(&path);
+    let mut cfg = load_segatoools_config(&path).map_err(|e| ApiError::from(e.to_string()))?;
+    apply_server_preset(&mut cfg, &preset);
+    retain_sections(&mut cfg, &["dns".to_string(), "keychip".to_string()]);
+    let result = persist_segatoools_config(&path, &cfg);
+    journal::record(
+        &game.id,
+        JournalEntry::new("network_preset_apply", format!("Applied network preset \"{}\"", preset.name))
+            .with_outcome(&result)
+            .with_details(serde_json::json!({ "presetId": preset.id, "slot": slot })),
+    );
+    result.map_err(|e| ApiError::from(e.to_string()))?;
+    let after = live_segatoools_or_default(&path);
+    if slots::is_default(slot.as_deref()) {
+        record_segatoools_provenance(&game, &before, &after, &KeyOrigin::user());
+    }
+    Ok(after)
+}
@@ -0,0 +1,525 @@
+use super::state::AppState;
+use crate::error::{ApiError, ApiResult, ErrorCode};
+use crate::platform::{self, PlatformCapabilities};
+use crate::vhd::is_running_as_admin;
+use crate::api_tokens::{
+    authorize_api_token, create_api_token, list_api_tokens, revoke_api_token, ApiPermission, ApiScope, ApiToken,
+    IssuedApiToken,
+};
+use serde::{Deserialize, Serialize};
+use tauri::ipc::Invoke;
+use tauri::{command, AppHandle, Manager, Runtime, State};
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::os::windows::process::CommandExt;
+
+const APP_SETTINGS_FILE_NAME: &str = "settings.json";
+const OFFLINE_MODE_BLOCK_MESSAGE: &str =
+    "Offline mode is enabled. Disable it in Settings to use network features.";
+const READ_ONLY_BLOCK_MESSAGE: &str = "Read-only mode is enabled. Changes are disabled on this machine.";
+/// Machine-wide switch for managed cabinets, set the same way as other group policies.
+const READ_ONLY_POLICY_KEY: &str = r"HKLM\SOFTWARE\Policies\ConfigArcLauncher";
+const READ_ONLY_POLICY_VALUE: &str = "ReadOnly";
+const READ_ONLY_ENV: &str = "CONFIGARC_READ_ONLY";
+/// Commands that only read state. Anything not listed here or in
+/// `READ_ONLY_ALLOWED_COMMANDS` counts as a change, so new commands are blocked in read-only
+/// mode until someone classifies them.
+const READ_COMMANDS: &[&str] = &[
+    "get_segatoools_config",
+    "get_game_dir_segatoools_config",
+    "test_network_cmd",
+    "enumerate_serial_ports_cmd",
+    "list_key_names_cmd",
+    "check_key_bindings_cmd",
+    "get_segatoools_provenance_cmd",
+    "get_offline_mode_cmd",
+    "get_read_only_mode_cmd",
+    "get_platform_capabilities_cmd",
+    "get_enabled_features_cmd",
+    "broker_status_cmd",
+    "list_api_tokens_cmd",
+    "get_local_override_cmd",
+    "get_effective_remote_config_cmd",
+    "get_remote_directives_cmd",
+    "list_presets_cmd",
+    "list_network_presets_cmd",
+    "export_profile_cmd",
+    "export_qr_backup_cmd",
+    "parse_qr_backup_cmd",
+    "list_profiles_cmd",
+    "load_profile_cmd",
+    "list_profile_assets_cmd",
+    "load_profile_notes_cmd",
+    "list_games_cmd",
+    "list_detection_rules_cmd",
+    "get_last_gpu_usage_cmd",
+    "list_monitors_cmd",
+    "load_vhd_config_cmd",
+    "list_delta_snapshots_cmd",
+    "get_game_disk_usage_cmd",
+    "get_game_timeline_cmd",
+    "dry_run_launch_cmd",
+    "get_last_failure_report_cmd",
+    "get_launch_queue_cmd",
+    "get_integrity_report_cmd",
+    "cancel_vhd_warmup_cmd",
+    "plan_vhd_migration_cmd",
+    "cancel_appdata_backup_cmd",
+    "list_appdata_backups_cmd",
+    "list_notifications_cmd",
+    "diff_segatoools_configs_cmd",
+    "list_config_slots_cmd",
+    "pick_game_folder_cmd",
+    "pick_game_auto_cmd",
+    "pick_vhd_game_cmd",
+    "pick_decrypt_files_cmd",
+    "default_segatoools_config_cmd",
+    "segatoools_path_cmd",
+    "open_segatoools_folder_cmd",
+    "get_data_paths_cmd",
+    "get_active_game_cmd",
+    "scan_game_vfs_folders_cmd",
+    "cancel_vfs_scan_cmd",
+    "detect_vfs_drift_cmd",
+    "list_json_configs_cmd",
+    "load_json_config_cmd",
+    "validate_json_schema_cmd",
+    "validate_json_config_cmd",
+    "load_amdaemon_config_cmd",
+    "load_icf_cmd",
+    "icf_summary_cmd",
+    "icf_audit_cmd",
+    "propose_icf_options_cmd",
+    "export_icf_json_cmd",
+    "list_icf_backups_cmd",
+    "list_icf_keys_cmd",
+    "list_option_files_cmd",
+    "get_mods_status_cmd",
+    "list_aimes_cmd",
+    "get_active_aime_cmd",
+    "list_io_dlls_cmd",
+    "load_changelog_cmd",
+    "load_structured_changelog_cmd",
+    "list_operations_cmd",
+    "cancel_operation_cmd",
+    "check_mod_conflicts_cmd",
+    "load_fsdecrypt_keys_cmd",
+    "get_fsdecrypt_key_store_cmd",
+    "inspect_container_cmd",
+    "verify_decrypted_output_cmd",
+    "download_order_cancel_cmd",
+    "segatools_trust_status_cmd",
+    "privexec_get_paths_cmd",
+    "list_hosts_overrides_cmd",
+    "privexec_bootstrap_roots_cmd",
+];
+/// What a control API token needs for each command it may call below `Manage`. Commands
+/// missing here need a full token: dialogs, key material, token administration and anything
+/// added later.
+const API_PERMISSIONS: &[(&str, ApiPermission)] = &[
+    ("get_segatoools_config", ApiPermission::Read),
+    ("get_game_dir_segatoools_config", ApiPermission::Read),
+    ("get_segatoools_provenance_cmd", ApiPermission::Read),
+    ("segatools_trust_status_cmd", ApiPermission::Read),
+    ("get_offline_mode_cmd", ApiPermission::Read),
+    ("get_read_only_mode_cmd", ApiPermission::Read),
+    ("get_platform_capabilities_cmd", ApiPermission::Read),
+    ("get_enabled_features_cmd", ApiPermission::Read),
+    ("broker_status_cmd", ApiPermission::Read),
+    ("list_presets_cmd", ApiPermission::Read),
+    ("list_network_presets_cmd", ApiPermission::Read),
+    ("list_profiles_cmd", ApiPermission::Read),
+    ("load_profile_cmd", ApiPermission::Read),
+    ("list_games_cmd", ApiPermission::Read),
+    ("get_active_game_cmd", ApiPermission::Read),
+    ("list_monitors_cmd", ApiPermission::Read),
+    ("get_last_gpu_usage_cmd", ApiPermission::Read),
+    ("get_game_disk_usage_cmd", ApiPermission::Read),
+    ("get_game_timeline_cmd", ApiPermission::Read),
+    ("list_delta_snapshots_cmd", ApiPermission::Read),
+    ("list_appdata_backups_cmd", ApiPermission::Read),
+    ("list_icf_backups_cmd", ApiPermission::Read),
+    ("icf_summary_cmd", ApiPermission::Read),
+    ("dry_run_launch_cmd", ApiPermission::Read),
+    ("get_last_failure_report_cmd", ApiPermission::Read),
+    ("get_launch_queue_cmd", ApiPermission::Read),
+    ("get_integrity_report_cmd", ApiPermission::Read),
+    ("list_notifications_cmd", ApiPermission::Read),
+    ("list_config_slots_cmd", ApiPermission::Read),
+    ("get_mods_status_cmd", ApiPermission::Read),
+    ("check_mod_conflicts_cmd", ApiPermission::Read),
+    ("list_operations_cmd", ApiPermission::Read),
+    ("load_changelog_cmd", ApiPermission::Read),
+    ("load_structured_changelog_cmd", ApiPermission::Read),
+    ("launch_game_cmd", ApiPermission::Launch),
+    ("enqueue_launch_cmd", ApiPermission::Launch),
+    ("clear_launch_queue_cmd", ApiPermission::Launch),
+    ("start_launch_queue_cmd", ApiPermission::Launch),
+    ("stop_launch_queue_cmd", ApiPermission::Launch),
+];
+/// Changes that stay available in read-only mode: launching (which refuses to apply a profile
+/// on its own), browsing between games, and the switch itself.
+const READ_ONLY_ALLOWED_COMMANDS: &[&str] = &[
+    "launch_game_cmd",
+    "set_active_game_cmd",
+    "set_read_only_mode_cmd",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct AppSettings {
+    #[serde(default)]
+    offline_mode: bool,
+    #[serde(default)]
+    read_only_mode: bool,
+    /// Keep the elevated privexec broker running across launcher restarts.
+    #[serde(default)]
+    pub(super) resident_broker: bool,
+}
+
+fn app_settings_path(app: &AppHandle) -> ApiResult<PathBuf> {
+    let root = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| ApiError::from(e.to_string()))?;
+    fs::create_dir_all(&root).map_err(|e| ApiError::from(e.to_string()))?;
+    Ok(root.join(APP_SETTINGS_FILE_NAME))
+}
+
+pub(super) fn load_app_settings_file(app: &AppHandle) -> ApiResult<AppSettings> {
+    let path = app_settings_path(app)?;
+    if !path.exists() {
+        return Ok(AppSettings::default());
+    }
+    let raw = fs::read(&path).map_err(|e| ApiError::from(e.to_string()))?;
+    let parsed = serde_json::from_slice::<AppSettings>(&raw).map_err(|e| ApiError::from(e.to_string()))?;
+    Ok(parsed)
+}
+
+pub(super) fn save_app_settings_file(app: &AppHandle, settings: &AppSettings) -> ApiResult<()> {
+    let path = app_settings_path(app)?;
+    let raw = serde_json::to_vec_pretty(settings).map_err(|e| ApiError::from(e.to_string()))?;
+    fs::write(path, raw).map_err(|e| ApiError::from(e.to_string()))
+}
+
+pub(super) fn read_app_settings(app: &AppHandle) -> ApiResult<AppSettings> {
+    app.state::<AppState>().settings(app)
+}
+
+pub(super) fn write_app_settings(app: &AppHandle, settings: &AppSettings) -> ApiResult<()> {
+    app.state::<AppState>().store_settings(app, settings)
+}
+
+fn is_offline_mode_enabled(app: &AppHandle) -> ApiResult<bool> {
+    Ok(read_app_settings(app)?.offline_mode)
+}
+
+pub(super) fn ensure_network_allowed(app: &AppHandle) -> ApiResult<()> {
+    if is_offline_mode_enabled(app)? {
+        return Err(ApiError::from(OFFLINE_MODE_BLOCK_MESSAGE));
+    }
+    Ok(())
+}
+
+fn read_only_policy_enabled() -> bool {
+    if let Ok(value) = std::env::var(READ_ONLY_ENV) {
+        return matches!(value.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes");
+    }
+    let output = Command::new("reg")
+        .args(["query", READ_ONLY_POLICY_KEY, "/v", READ_ONLY_POLICY_VALUE])
+        .creation_flags(0x08000000)
+        .output();
+    let Ok(output) = output else {
+        return false;
+    };
+    if !output.status.success() {
+        return false;
+    }
+    String::from_utf8_lossy(&output.stdout).lines().any(|line| {
+        line.split_once("REG_DWORD")
+            .map(|(_, value)| value.trim() != "0x0")
+            .unwrap_or(false)
+    })
+}
+
+/// Reads the policy and the stored setting once at startup; the gate then only checks the
+/// flags kept in `AppState`.
+pub fn init_read_only_mode(app: &AppHandle) {
+    let policy = read_only_policy_enabled();
+    let setting = read_app_settings(app).map(|s| s.read_only_mode).unwrap_or(false);
+    app.state::<AppState>().store_read_only_mode(setting, policy);
+}
+
+/// Rejects a change made inside a command that read-only mode otherwise lets through.
+pub(super) fn ensure_not_read_only(state: &AppState) -> ApiResult<()> {
+    if state.read_only_mode().enabled {
+        return Err(ApiError::new(ErrorCode::FeatureDisabled, READ_ONLY_BLOCK_MESSAGE));
+    }
+    Ok(())
+}
+
+fn is_mutating_command(command: &str) -> bool {
+    !READ_COMMANDS.contains(&command) && !READ_ONLY_ALLOWED_COMMANDS.contains(&command)
+}
+
+/// Maps a command to the permission a control API token needs to call it.
+fn api_permission_for_command(command: &str) -> ApiPermission {
+    API_PERMISSIONS
+        .iter()
+        .find(|(name, _)| *name == command)
+        .map_or(ApiPermission::Manage, |(_, permission)| *permission)
+}
+
+/// Wraps the generated invoke handler so mutating commands are rejected with
+/// `FEATURE_DISABLED` while read-only mode is on.
+pub fn read_only_gate<R: Runtime>(
+    handler: impl Fn(Invoke<R>) -> bool + Send + Sync + 'static,
+) -> impl Fn(Invoke<R>) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        let read_only = invoke.message.webview().state::<AppState>().read_only_mode().enabled;
+        if read_only && is_mutating_command(invoke.message.command()) {
+            invoke
+                .resolver
+                .reject(ApiError::new(ErrorCode::FeatureDisabled, READ_ONLY_BLOCK_MESSAGE));
+            return true;
+        }
+        handler(invoke)
+    }
+}
+
+#[command]
+pub fn get_offline_mode_cmd(app: AppHandle) -> ApiResult<bool> {
+    is_offline_mode_enabled(&app)
+}
+
+#[command]
+pub fn set_offline_mode_cmd(app: AppHandle, enabled: bool) -> ApiResult<()> {
+    let mut settings = read_app_settings(&app)?;
+    settings.offline_mode = enabled;
+    write_app_settings(&app, &settings)
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadOnlyStatus {
+    pub enabled: bool,
+    /// Set by machine policy; the in-app setting cannot turn it off.
+    pub enforced_by_policy: bool,
+}
+
+#[command]
+pub fn get_read_only_mode_cmd(state: State<'_, AppState>) -> ApiResult<ReadOnlyStatus> {
+    Ok(state.read_only_mode())
+}
+
+#[command]
+pub fn set_read_only_mode_cmd(app: AppHandle, state: State<'_, AppState>, enabled: bool) -> ApiResult<ReadOnlyStatus> {
+    let current = state.read_only_mode();
+    if current.enforced_by_policy {
+        return Err(ApiError::new(
+            ErrorCode::FeatureDisabled,
+            "Read-only mode is enforced by policy and cannot be changed here.",
+        ));
+    }
+    // Anyone can lock the launcher, but only an administrator can unlock it again.
+    if !enabled && current.enabled && !is_running_as_admin() {
+        return Err(ApiError::new(
+            ErrorCode::FeatureDisabled,
+            "Administrator rights are required to leave read-only mode.",
+        ));
+    }
+    let mut settings = read_app_settings(&app)?;
+    settings.read_only_mode = enabled;
+    write_app_settings(&app, &settings)?;
+    state.store_read_only_mode(enabled, false);
+    Ok(state.read_only_mode())
+}
+
+#[command]
+pub async fn get_platform_capabilities_cmd() -> ApiResult<PlatformCapabilities> {
+    tauri::async_runtime::spawn_blocking(platform::capabilities)
+        .await
+        .map_err(|e| ApiError::from(e.to_string()))
+}
+
+#[command]
+pub fn list_api_tokens_cmd() -> ApiResult<Vec<ApiToken>> {
+    Ok(list_api_tokens()?)
+}
+
+#[command]
+pub fn generate_api_token_cmd(name: String, scope: ApiScope) -> ApiResult<IssuedApiToken> {
+    Ok(create_api_token(&name, scope)?)
+}
+
+#[command]
+pub fn revoke_api_token_cmd(id: String) -> ApiResult<()> {
+    if !revoke_api_token(&id)? {
+        return Err(ApiError::new(ErrorCode::NotFound, format!("API token not found: {}", id)));
+    }
+    Ok(())
+}
+
+/// Answers whether `token` may call `command`, the same check a control API request goes through.
+#[command]
+pub fn check_api_token_cmd(token: String, command: String) -> ApiResult<ApiToken> {
+    Ok(authorize_api_token(Some(&token), api_permission_for_command(&command))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Everything registered that is neither a read nor allowed in read-only mode.
+    const MUTATING_COMMANDS: &[&str] = &[
+        "capture_key_cmd",
+        "save_segatoools_config",
+        "reset_segatoools_key_cmd",
+        "export_segatoools_config_cmd",
+        "import_segatoools_config_cmd",
+        "set_offline_mode_cmd",
+        "set_resident_broker_cmd",
+        "generate_api_token_cmd",
+        "revoke_api_token_cmd",
+        "check_api_token_cmd",
+        "set_local_override_cmd",
+        "sync_remote_config_cmd",
+        "apply_remote_config_cmd",
+        "install_preset_cmd",
+        "apply_network_preset_cmd",
+        "import_profile_cmd",
+        "export_profile_share_cmd",
+        "import_profile_share_cmd",
+        "import_qr_backup_cmd",
+        "save_profile_cmd",
+        "delete_profile_cmd",
+        "add_profile_asset_cmd",
+        "delete_profile_asset_cmd",
+        "save_profile_notes_cmd",
+        "apply_display_preset_cmd",
+        "save_game_cmd",
+        "save_vhd_config_cmd",
+        "restore_delta_snapshot_cmd",
+        "rename_delta_snapshot_cmd",
+        "delete_delta_snapshot_cmd",
+        "export_decrypted_vhd_cmd",
+        "delete_game_cmd",
+        "cleanup_game_cmd",
+        "cleanup_game_caches_cmd",
+        "enqueue_launch_cmd",
+        "clear_launch_queue_cmd",
+        "start_launch_queue_cmd",
+        "stop_launch_queue_cmd",
+        "run_integrity_scrub_cmd",
+        "start_vhd_warmup_cmd",
+        "migrate_game_to_vhd_cmd",
+        "start_appdata_backup_cmd",
+        "restore_appdata_backup_cmd",
+        "prune_appdata_backups_cmd",
+        "mark_notifications_read_cmd",
+        "post_notification_cmd",
+        "apply_profile_to_game_cmd",
+        "apply_profile_sections_cmd",
+        "add_config_slot_cmd",
+        "delete_config_slot_cmd",
+        "apply_vfs_drift_fix_cmd",
+        "save_json_config_cmd",
+        "save_amdaemon_config_cmd",
+        "apply_icf_options_cmd",
+        "import_icf_json_cmd",
+        "restore_icf_backup_cmd",
+        "set_icf_key_cmd",
+        "delete_icf_key_cmd",
+        "import_icf_keys_cmd",
+        "export_icf_keys_cmd",
+        "save_icf_cmd",
+        "set_option_compression_cmd",
+        "delete_option_cmd",
+        "migrate_legacy_aimes_cmd",
+        "generate_aime_cmd",
+        "save_aime_cmd",
+        "update_aime_cmd",
+        "delete_aime_cmd",
+        "apply_aime_to_active_cmd",
+        "store_io_dll_cmd",
+        "add_io_dll_cmd",
+        "activate_io_dll_cmd",
+        "delete_io_dll_cmd",
+        "add_mods_cmd",
+        "delete_mod_cmd",
+        "undo_last_delete_cmd",
+        "import_fsdecrypt_keys_cmd",
+        "set_fsdecrypt_game_key_cmd",
+        "delete_fsdecrypt_game_key_cmd",
+        "clear_fsdecrypt_key_store_cmd",
+        "decrypt_game_files_cmd",
+        "build_option_container_cmd",
+        "download_order_cmd",
+        "download_order_fetch_text_cmd",
+        "download_order_download_files_cmd",
+        "deploy_segatoools_cmd",
+        "rollback_segatoools_cmd",
+        "privexec_execute_cmd",
+        "privexec_execute_batch_cmd",
+        "add_hosts_override_cmd",
+        "delete_hosts_override_cmd",
+        "privexec_apply_policy_update_cmd",
+        "privexec_bootstrap_policy_cmd",
+    ];
+
+    fn registered_commands() -> Vec<&'static str> {
+        let main = include_str!("../main.rs");
+        let marker = "generate_handler![";
+        let start = main.find(marker).expect("generate_handler! in main.rs") + marker.len();
+        let end = start + main[start..].find(']').expect("closing bracket");
+        main[start..end].split(',').map(str::trim).filter(|name| !name.is_empty()).collect()
+    }
+
+    #[test]
+    fn classifies_every_registered_command() {
+        for command in registered_commands() {
+            let lists = [READ_COMMANDS, READ_ONLY_ALLOWED_COMMANDS, MUTATING_COMMANDS];
+            let hits = lists.iter().filter(|list| list.contains(&command)).count();
+            assert_eq!(hits, 1, "{command} must be listed exactly once");
+        }
+    }
+
+    #[test]
+    fn classifies_mutating_commands() {
+        assert!(is_mutating_command("save_game_cmd"));
+        assert!(is_mutating_command("decrypt_game_files_cmd"));
+        assert!(is_mutating_command("privexec_execute_cmd"));
+        assert!(!is_mutating_command("list_games_cmd"));
+        assert!(!is_mutating_command("launch_game_cmd"));
+        assert!(!is_mutating_command("set_active_game_cmd"));
+        assert!(is_mutating_command("start_appdata_backup_cmd"));
+        assert!(is_mutating_command("export_icf_keys_cmd"));
+        assert!(is_mutating_command("start_launch_queue_cmd"));
+        assert!(is_mutating_command("some_future_cmd"));
+    }
+
+    #[test]
+    fn api_reads_are_registered_reads() {
+        let registered = registered_commands();
+        for (command, permission) in API_PERMISSIONS {
+            assert!(registered.contains(command), "{command} is not registered");
+            if *permission == ApiPermission::Read {
+                assert!(READ_COMMANDS.contains(command), "{command} changes state");
+            }
+        }
+    }
+
+    #[test]
+    fn maps_commands_to_api_permissions() {
+        assert_eq!(api_permission_for_command("list_games_cmd"), ApiPermission::Read);
+        assert_eq!(api_permission_for_command("launch_game_cmd"), ApiPermission::Launch);
+        assert_eq!(api_permission_for_command("enqueue_launch_cmd"), ApiPermission::Launch);
+        assert_eq!(api_permission_for_command("save_game_cmd"), ApiPermission::Manage);
+        assert_eq!(api_permission_for_command("set_read_only_mode_cmd"), ApiPermission::Manage);
+        assert_eq!(api_permission_for_command("generate_api_token_cmd"), ApiPermission::Manage);
+        assert_eq!(api_permission_for_command("some_future_cmd"), ApiPermission::Manage);
+        assert_eq!(api_permission_for_command("list_api_tokens_cmd"), ApiPermission::Manage);
+        assert_eq!(api_permission_for_command("list_icf_keys_cmd"), ApiPermission::Manage);
+    }
+}
@@ -0,0 +1,335 @@
+use super::config::gen_profile_id;
+use super::disk_usage::CachedDiskUsage;
+use super::games::CachedVfsScan;
+use super::launch::LaunchQueueState;
+#[cfg(feature = "privexec")]
+use super::privexec::BrokerSupervisorState;
+use super::settings::{load_app_settings_file, save_app_settings_file, AppSettings, ReadOnlyStatus};
+use super::system::ChangelogCache;
+use crate::config::paths::get_active_game_id;
+use crate::games::{model::Game, store};
+#[cfg(feature = "icf")]
+use crate::icf::keys::{load_icf_keyring, IcfKeyring};
+use crate::error::{ApiError, ApiResult, ErrorCode};
+use crate::cancel::CancellationToken;
+use crate::scrub::ScrubReport;
+use serde::Serialize;
+use tauri::AppHandle;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::SystemTime;
+
+/// State shared by the command modules, registered with `manage` in main.rs. Commands take it
+/// as `State<'_, AppState>`; background tasks reach it through `app.state::<AppState>()`.
+#[derive(Default)]
+pub struct AppState {
+    settings: Mutex<Option<AppSettings>>,
+    active_game: Mutex<Option<CachedActiveGame>>,
+    /// Held while a game's segatools.ini and its provenance are rewritten, so two saves
+    /// cannot interleave and attribute keys to the wrong source.
+    config_lock: Mutex<()>,
+    /// Operation currently writing to each game's files, keyed by game id.
+    operations: Arc<Mutex<HashMap<String, &'static str>>>,
+    /// Long-running work that `cancel_operation_cmd` can stop, keyed by operation id.
+    cancellable: CancellableRegistry,
+    /// Unsealed ICF keyring, replaced whenever a key command changes it.
+    #[cfg(feature = "icf")]
+    icf_keyring: Mutex<Option<IcfKeyring>>,
+    /// Checked by `read_only_gate` before every command.
+    read_only: AtomicBool,
+    read_only_policy: AtomicBool,
+    launch_queue: Mutex<LaunchQueueState>,
+    /// Last integrity scrub, whether started by hand or by the background scrubber.
+    integrity_report: Mutex<Option<ScrubReport>>,
+    /// Disk usage reports by game id.
+    disk_usage: Mutex<HashMap<String, CachedDiskUsage>>,
+    /// VFS folder scans by game folder.
+    vfs_scans: Mutex<HashMap<PathBuf, CachedVfsScan>>,
+    changelogs: Mutex<ChangelogCache>,
+    #[cfg(feature = "privexec")]
+    broker_supervisor: Mutex<BrokerSupervisorState>,
+}
+
+type CancellableRegistry = Arc<Mutex<HashMap<String, RunningOperation>>>;
+
+struct RunningOperation {
+    kind: &'static str,
+    started_at: String,
+    token: CancellationToken,
+}
+
+/// A cancellable operation as `list_operations_cmd` reports it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationInfo {
+    pub id: String,
+    /// e.g. "decrypt", "download", "appdata_backup", "integrity_scrub", "vfs_scan",
+    /// "vhd_warmup" or "vhd_migration".
+    pub kind: String,
+    pub started_at: String,
+    pub cancelled: bool,
+}
+
+/// Registration of a cancellable operation; see [`AppState::begin_cancellable`]. The
+/// operation polls [`CancellableOperation::token`] and leaves the registry when this drops.
+pub(super) struct CancellableOperation {
+    registry: CancellableRegistry,
+    id: String,
+    token: CancellationToken,
+}
+
+impl CancellableOperation {
+    pub(super) fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub(super) fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+}
+
+impl Drop for CancellableOperation {
+    fn drop(&mut self) {
+        self.registry.lock().unwrap_or_else(|p| p.into_inner()).remove(&self.id);
+    }
+}
+
+/// Held for the duration of an operation that writes to a game's files; see
+/// [`AppState::begin_operation`].
+pub(super) struct OperationGuard {
+    operations: Arc<Mutex<HashMap<String, &'static str>>>,
+    game_id: String,
+}
+
+impl Drop for OperationGuard {
+    fn drop(&mut self) {
+        self.operations.lock().unwrap_or_else(|p| p.into_inner()).remove(&self.game_id);
+    }
+}
+
+struct CachedActiveGame {
+    games_modified: Option<SystemTime>,
+    game: Game,
+}
+
+impl AppState {
+    pub(super) fn settings(&self, app: &AppHandle) -> ApiResult<AppSettings> {
+        let mut cached = self.settings.lock().unwrap_or_else(|p| p.into_inner());
+        if let Some(settings) = cached.as_ref() {
+            return Ok(settings.clone());
+        }
+        let settings = load_app_settings_file(app)?;
+        *cached = Some(settings.clone());
+        Ok(settings)
+    }
+
+    pub(super) fn store_settings(&self, app: &AppHandle, settings: &AppSettings) -> ApiResult<()> {
+        let mut cached = self.settings.lock().unwrap_or_else(|p| p.into_inner());
+        save_app_settings_file(app, settings)?;
+        *cached = Some(settings.clone());
+        Ok(())
+    }
+
+    /// The selected game, re-read only when the selection or configarc_games.json changes.
+    pub(super) fn active_game(&self) -> ApiResult<Game> {
+        let active_id = get_active_game_id()
+            .map_err(|e| ApiError::from(e.to_string()))?
+            .ok_or_else(|| "No active game selected".to_string())?;
+        let modified = store::games_modified();
+        let mut cached = self.active_game.lock().unwrap_or_else(|p| p.into_inner());
+        if let Some(entry) = cached.as_ref() {
+            if entry.game.id == active_id && modified.is_some() && entry.games_modified == modified {
+                return Ok(entry.game.clone());
+            }
+        }
+        let games = store::list_games().map_err(|e| ApiError::from(e.to_string()))?;
+        let game = games
+            .into_iter()
+            .find(|g| g.id == active_id)
+            .ok_or_else(|| ApiError::from("Active game not found".to_string()))?;
+        *cached = Some(CachedActiveGame {
+            games_modified: modified,
+            game: game.clone(),
+        });
+        Ok(game)
+    }
+
+    /// The ICF keyring, unsealed on first use instead of on every ICF read.
+    #[cfg(feature = "icf")]
+    pub(super) fn icf_keyring(&self) -> ApiResult<IcfKeyring> {
+        let mut cached = self.icf_keyring.lock().unwrap_or_else(|p| p.into_inner());
+        if let Some(keyring) = cached.as_ref() {
+            return Ok(keyring.clone());
+        }
+        let keyring = load_icf_keyring().map_err(|e| ApiError::from(e.to_string()))?;
+        *cached = Some(keyring.clone());
+        Ok(keyring)
+    }
+
+    #[cfg(feature = "icf")]
+    pub(super) fn store_icf_keyring(&self, keyring: &IcfKeyring) {
+        *self.icf_keyring.lock().unwrap_or_else(|p| p.into_inner()) = Some(keyring.clone());
+    }
+
+    pub(super) fn read_only_mode(&self) -> ReadOnlyStatus {
+        ReadOnlyStatus {
+            enabled: self.read_only.load(Ordering::SeqCst),
+            enforced_by_policy: self.read_only_policy.load(Ordering::SeqCst),
+        }
+    }
+
+    /// Read-only mode stays on while `enforced_by_policy`, whatever `enabled` says.
+    pub(super) fn store_read_only_mode(&self, enabled: bool, enforced_by_policy: bool) {
+        self.read_only_policy.store(enforced_by_policy, Ordering::SeqCst);
+        self.read_only.store(enabled || enforced_by_policy, Ordering::SeqCst);
+    }
+
+    pub(super) fn launch_queue(&self) -> MutexGuard<'_, LaunchQueueState> {
+        self.launch_queue.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    pub(super) fn integrity_report(&self) -> Option<ScrubReport> {
+        self.integrity_report.lock().unwrap_or_else(|p| p.into_inner()).clone()
+    }
+
+    pub(super) fn store_integrity_report(&self, report: &ScrubReport) {
+        *self.integrity_report.lock().unwrap_or_else(|p| p.into_inner()) = Some(report.clone());
+    }
+
+    pub(super) fn disk_usage_cache(&self) -> MutexGuard<'_, HashMap<String, CachedDiskUsage>> {
+        self.disk_usage.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    pub(super) fn vfs_scan_cache(&self) -> MutexGuard<'_, HashMap<PathBuf, CachedVfsScan>> {
+        self.vfs_scans.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    pub(super) fn changelog_cache(&self) -> MutexGuard<'_, ChangelogCache> {
+        self.changelogs.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    #[cfg(feature = "privexec")]
+    pub(super) fn broker_supervisor(&self) -> MutexGuard<'_, BrokerSupervisorState> {
+        self.broker_supervisor.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    pub(super) fn active_game_root_dir(&self) -> ApiResult<PathBuf> {
+        let game = self.active_game()?;
+        store::game_root_dir(&game).ok_or_else(|| ApiError::from("Game path missing".to_string()))
+    }
+
+    pub(super) fn lock_config(&self) -> MutexGuard<'_, ()> {
+        self.config_lock.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// Claims `game_id` for `operation` (e.g. "deploy", "launch") so launches, deploys, mod and
+    /// option installs never write the same game at once. Fails with BUSY naming the operation
+    /// already running instead of waiting.
+    pub(super) fn begin_operation(&self, game_id: &str, operation: &'static str) -> ApiResult<OperationGuard> {
+        let mut operations = self.operations.lock().unwrap_or_else(|p| p.into_inner());
+        if let Some(running) = operations.get(game_id) {
+            return Err(ApiError::with_details(
+                ErrorCode::Busy,
+                format!("Cannot start {} while {} is in progress for this game", operation, running),
+                *running,
+            ));
+        }
+        operations.insert(game_id.to_string(), operation);
+        Ok(OperationGuard {
+            operations: Arc::clone(&self.operations),
+            game_id: game_id.to_string(),
+        })
+    }
+
+    /// Registers long-running work of `kind` under `operation_id`, or a generated id when the
+    /// caller did not pick one, so it can be cancelled by id. Fails with BUSY when the id is
+    /// taken, or, with `exclusive`, while another operation of `kind` runs.
+    pub(super) fn begin_cancellable(
+        &self,
+        kind: &'static str,
+        operation_id: Option<String>,
+        exclusive: bool,
+    ) -> ApiResult<CancellableOperation> {
+        let mut running = self.cancellable.lock().unwrap_or_else(|p| p.into_inner());
+        if exclusive && running.values().any(|op| op.kind == kind) {
+            return Err(ApiError::with_details(
+                ErrorCode::Busy,
+                format!("A {} is already running", kind.replace('_', " ")),
+                kind,
+            ));
+        }
+        let id = match operation_id.map(|id| id.trim().to_string()).filter(|id| !id.is_empty()) {
+            Some(id) if running.contains_key(&id) => {
+                return Err(ApiError::new(ErrorCode::Busy, format!("Operation {} is already running", id)));
+            }
+            Some(id) => id,
+            None => {
+                let base = gen_profile_id(kind);
+                let mut id = base.clone();
+                let mut n = 1;
+                while running.contains_key(&id) {
+                    id = format!("{}-{}", base, n);
+                    n += 1;
+                }
+                id
+            }
+        };
+        let token = CancellationToken::new();
+        running.insert(
+            id.clone(),
+            RunningOperation {
+                kind,
+                started_at: chrono::Utc::now().to_rfc3339(),
+                token: token.clone(),
+            },
+        );
+        Ok(CancellableOperation {
+            registry: Arc::clone(&self.cancellable),
+            id,
+            token,
+        })
+    }
+
+    /// Cancels the operation `id`. Returns false when no such operation runs.
+    pub(super) fn cancel_operation(&self, id: &str) -> bool {
+        let running = self.cancellable.lock().unwrap_or_else(|p| p.into_inner());
+        let Some(op) = running.get(id) else {
+            return false;
+        };
+        op.token.cancel();
+        true
+    }
+
+    /// Cancels every running operation of `kind` and returns how many there were.
+    pub(super) fn cancel_operations(&self, kind: &str) -> usize {
+        let running = self.cancellable.lock().unwrap_or_else(|p| p.into_inner());
+        let mut cancelled = 0;
+        for op in running.values().filter(|op| op.kind == kind) {
+            op.token.cancel();
+            cancelled += 1;
+        }
+        cancelled
+    }
+
+    pub(super) fn is_running(&self, kind: &str) -> bool {
+        let running = self.cancellable.lock().unwrap_or_else(|p| p.into_inner());
+        running.values().any(|op| op.kind == kind)
+    }
+
+    pub(super) fn running_operations(&self) -> Vec<OperationInfo> {
+        let running = self.cancellable.lock().unwrap_or_else(|p| p.into_inner());
+        let mut operations: Vec<OperationInfo> = running
+            .iter()
+            .map(|(id, op)| OperationInfo {
+                id: id.clone(),
+                kind: op.kind.to_string(),
+                started_at: op.started_at.clone(),
+                cancelled: op.token.is_cancelled(),
+            })
+            .collect();
+        operations.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+        operations
+    }
+}
@@ -0,0 +1,145 @@
+use super::state::{AppState, OperationInfo};
+use crate::changelog::{
+    locale_candidates, localized_file_name, parse_changelog, Changelog, ChangelogRelease, DEFAULT_CHANGELOG_LOCALE,
+};
+use crate::error::{ApiError, ApiResult};
+use crate::notifications::{
+    list_notifications, mark_notifications_read, notify, post_notification, Notification, NotificationSeverity,
+};
+use tauri::{command, AppHandle, Emitter, State};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Persists `notification` and tells an open window about it; a closed UI picks it up from
+/// `list_notifications_cmd` later.
+pub(super) fn notify_app(app: &AppHandle, notification: Notification) {
+    if let Some(posted) = notify(notification) {
+        let _ = app.emit("notification", &posted);
+    }
+}
+
+#[command]
+pub fn list_notifications_cmd(unread_only: Option<bool>) -> ApiResult<Vec<Notification>> {
+    list_notifications(unread_only.unwrap_or(false)).map_err(ApiError::from)
+}
+
+#[command]
+pub fn mark_notifications_read_cmd(ids: Option<Vec<String>>) -> ApiResult<usize> {
+    mark_notifications_read(ids.as_deref()).map_err(ApiError::from)
+}
+
+/// For events only the frontend sees, such as the updater finding a release.
+#[command]
+pub fn post_notification_cmd(
+    app: AppHandle,
+    source: String,
+    severity: NotificationSeverity,
+    title: String,
+    message: String,
+    dedupe_key: Option<String>,
+) -> ApiResult<Notification> {
+    let mut notification = Notification::new(&source, severity, title, message);
+    notification.dedupe_key = dedupe_key.filter(|key| !key.trim().is_empty());
+    let posted = post_notification(notification).map_err(ApiError::from)?;
+    let _ = app.emit("notification", &posted);
+    Ok(posted)
+}
+
+fn changelog_candidates(file_name: &str) -> Vec<PathBuf> {
+    let mut candidates: Vec<PathBuf> = Vec::new();
+    if let Ok(exe) = std::env::current_exe() {
+        if let Some(dir) = exe.parent() {
+            candidates.push(dir.join(file_name));
+            candidates.push(dir.join("resources").join(file_name));
+        }
+    }
+    if let Ok(cwd) = std::env::current_dir() {
+        candidates.push(cwd.join(file_name));
+    }
+    candidates
+}
+
+fn changelog_path() -> PathBuf {
+    let candidates = changelog_candidates("CHANGELOG.md");
+    for path in &candidates {
+        if path.exists() {
+            return path.to_path_buf();
+        }
+    }
+
+    candidates
+        .first()
+        .cloned()
+        .unwrap_or_else(|| Path::new("CHANGELOG.md").to_path_buf())
+}
+
+/// Picks `CHANGELOG.<locale>.md` for the most specific matching locale, falling back to the
+/// default changelog. Returns the file and the locale it is written in.
+fn localized_changelog_path(locale: Option<&str>) -> (PathBuf, Option<String>) {
+    for candidate in locale.map(locale_candidates).unwrap_or_default() {
+        let file_name = localized_file_name("CHANGELOG.md", &candidate);
+        if let Some(path) = changelog_candidates(&file_name).into_iter().find(|p| p.exists()) {
+            return (path, Some(candidate));
+        }
+        if candidate.eq_ignore_ascii_case(DEFAULT_CHANGELOG_LOCALE) {
+            return (changelog_path(), Some(candidate));
+        }
+    }
+    (changelog_path(), None)
+}
+
+/// Parsed changelogs by path, with the modification time they were read at.
+pub(super) type ChangelogCache = HashMap<PathBuf, (Option<SystemTime>, Vec<ChangelogRelease>)>;
+
+#[command]
+pub fn load_changelog_cmd(locale: Option<String>) -> ApiResult<String> {
+    let (path, _) = localized_changelog_path(locale.as_deref());
+    fs::read_to_string(&path).map_err(|e| ApiError::from(format!("Failed to read changelog: {}", e)))
+}
+
+/// Changelog for the UI language, parsed into releases and typed sections. Parsed documents
+/// are cached until the file changes.
+#[command]
+pub fn load_structured_changelog_cmd(state: State<'_, AppState>, locale: Option<String>) -> ApiResult<Changelog> {
+    let requested = locale.as_deref().map(str::trim).filter(|l| !l.is_empty() && !l.eq_ignore_ascii_case("system"));
+    let (path, served) = localized_changelog_path(requested);
+    let mtime = fs::metadata(&path).and_then(|m| m.modified()).ok();
+    let cached = {
+        let cache = state.changelog_cache();
+        cache
+            .get(&path)
+            .filter(|(cached_mtime, _)| cached_mtime.is_some() && *cached_mtime == mtime)
+            .map(|(_, releases)| releases.clone())
+    };
+    let releases = match cached {
+        Some(releases) => releases,
+        None => {
+            let content = fs::read_to_string(&path)
+                .map_err(|e| ApiError::from(format!("Failed to read changelog: {}", e)))?;
+            let releases = parse_changelog(&content);
+            state.changelog_cache().insert(path, (mtime, releases.clone()));
+            releases
+        }
+    };
+    Ok(Changelog {
+        fallback: requested.is_some() && served.is_none(),
+        locale: served,
+        releases,
+    })
+}
+
+/// Cancellable operations in progress, oldest first.
+#[command]
+pub fn list_operations_cmd(state: State<'_, AppState>) -> ApiResult<Vec<OperationInfo>> {
+    Ok(state.running_operations())
+}
+
+/// Asks the operation `operation_id` to stop. It stops at its next check, deletes what it had
+/// only partly written and fails with "Operation cancelled" (downloads: "Download cancelled").
+/// Returns false when no such operation is running.
+#[command]
+pub fn cancel_operation_cmd(state: State<'_, AppState>, operation_id: String) -> ApiResult<bool> {
+    Ok(state.cancel_operation(operation_id.trim()))
+}
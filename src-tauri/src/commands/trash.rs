@@ -0,0 +1,51 @@
+use super::icf::option_dir;
+use super::state::AppState;
+use crate::games::{
+    journal::{self, JournalEntry},
+    model::Game,
+    trash::{last_trashed, move_to_trash, restore_from_trash, TrashEntry},
+};
+use crate::error::{ApiError, ApiResult, ErrorCode};
+use tauri::{command, AppHandle, Emitter, State};
+use std::path::{Path, PathBuf};
+
+/// Moves `path` of `game` to the trash next to it and tells the UI, which offers an undo
+/// until the entry expires.
+pub(super) fn trash_game_item(app: &AppHandle, game: &Game, path: &Path, kind: &str) -> ApiResult<TrashEntry> {
+    let entry = move_to_trash(&game.id, path, kind)?;
+    journal::record(
+        &game.id,
+        JournalEntry::new(&format!("{}_remove", kind), format!("Moved {} {} to the trash", kind, entry.name)),
+    );
+    let _ = app.emit("item-trashed", &entry);
+    Ok(entry)
+}
+
+/// Folders of the active game whose deletions can be undone: its mods and option folders.
+fn trash_roots(state: &AppState) -> ApiResult<Vec<PathBuf>> {
+    let mut roots = vec![state.active_game_root_dir()?.join("Mods")];
+    if let Ok(dir) = option_dir() {
+        roots.push(dir);
+    }
+    Ok(roots)
+}
+
+/// Restores the active game's most recently deleted mod or option folder while its undo
+/// window lasts.
+#[command]
+pub fn undo_last_delete_cmd(app: AppHandle, state: State<'_, AppState>) -> ApiResult<TrashEntry> {
+    let game = state.active_game()?;
+    let _operation = state.begin_operation(&game.id, "undo delete")?;
+    let (root, last) = trash_roots(&state)?
+        .into_iter()
+        .filter_map(|root| last_trashed(&root).map(|entry| (root, entry)))
+        .max_by(|a, b| a.1.deleted_at.cmp(&b.1.deleted_at))
+        .ok_or_else(|| ApiError::new(ErrorCode::NotFound, "Nothing to undo"))?;
+    let entry = restore_from_trash(&root, &last.id)?;
+    journal::record(
+        &game.id,
+        JournalEntry::new("trash_restore", format!("Restored {} {}", entry.kind, entry.name)),
+    );
+    let _ = app.emit("item-restored", &entry);
+    Ok(entry)
+}
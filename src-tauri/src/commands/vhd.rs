@@ -0,0 +1,1564 @@
+use super::games::{detect_game_in_dir, pick_folder_or_cancel, resolve_with_base, DetectedGameInfo};
+#[cfg(feature = "privexec")]
+use super::privexec::privexec_broker;
+use super::state::AppState;
+use super::system::notify_app;
+use crate::config::{load_segatoools_config, paths::segatoools_path_for_game_id, segatools::SegatoolsConfig};
+use crate::games::{
+    journal::{self, JournalEntry},
+    model::{Game, LaunchMode, SandboxOptions},
+    store,
+    trash::{move_to_trash, TrashEntry},
+    watchdog::WatchdogOptions,
+};
+use crate::gpu::GpuPreference;
+use crate::error::{ApiError, ApiResult, ErrorCode};
+use crate::platform;
+use crate::notifications::{Notification, NotificationSeverity};
+#[cfg(feature = "privexec")]
+use crate::privexec::broker::BrokerSession;
+use crate::vhd::{
+    chain::classify_images,
+    delete_delta_snapshot, is_virtual_disk_path, list_delta_snapshots, load_vhd_config,
+    migration::{migrate_to_vhd, plan_migration, MigrationPlan, MigrationProgress, MigrationSources},
+    mount_vhd_with_elevation, reconcile_stale_mounts, rename_delta_snapshot, resolve_vhd_config, restore_delta_snapshot,
+    save_vhd_config, unmount_vhd_handle, vhd_config_path_for_game_id, warm_up_images, DeltaSnapshots, MountPoint,
+    MountPoints, MountRole, ResolvedVhdConfig, VhdConfig, VhdMountHandle, VirtualDiskFormat,
+};
+#[cfg(feature = "privexec")]
+use crate::vhd::is_running_as_admin;
+use crate::archive::{copy_verified, CopyOptions, CopyProgress};
+use serde::Serialize;
+use tauri::{command, AppHandle, Emitter, State, Window};
+use serde_json::Value;
+use zip::read::ZipArchive;
+use std::cmp::Ordering as CmpOrdering;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::os::windows::process::CommandExt;
+
+/// A launch's VHD set: attached by this process (or its one-shot helper) or held by the broker.
+#[derive(Clone)]
+pub(super) enum LaunchMount {
+    Local(Box<VhdMountHandle>),
+    #[cfg(feature = "privexec")]
+    Broker {
+        app: AppHandle,
+        game_id: String,
+        mount_points: MountPoints,
+    },
+}
+
+impl LaunchMount {
+    pub(super) fn mount_points(&self) -> &MountPoints {
+        match self {
+            LaunchMount::Local(handle) => handle.mount_points(),
+            #[cfg(feature = "privexec")]
+            LaunchMount::Broker { mount_points, .. } => mount_points,
+        }
+    }
+
+    pub(super) fn unmount(&self) -> Result<(), String> {
+        match self {
+            LaunchMount::Local(handle) => unmount_vhd_handle(handle),
+            #[cfg(feature = "privexec")]
+            LaunchMount::Broker { app, game_id, .. } => privexec_broker(app)?.unmount_game_vhd(game_id),
+        }
+    }
+}
+
+/// The privileged part of a launch, from mounting to the end of BitLocker unlock. Through the
+/// broker it runs in one session, opened before mounting and kept alive by heartbeats until
+/// finished, since mounting and unlocking together can outlast the session TTL.
+#[derive(Default)]
+pub(super) enum LaunchSession {
+    /// Nothing opened ahead; mounting picks its route itself.
+    #[default]
+    None,
+    #[cfg(feature = "privexec")]
+    Broker(Box<BrokerSession>),
+    /// No broker could be reached, so mounting goes straight to the elevated helper.
+    #[cfg(feature = "privexec")]
+    Unavailable,
+}
+
+impl LaunchSession {
+    /// Opens the broker session ahead of mounting when the mount will go through the broker.
+    #[cfg(feature = "privexec")]
+    pub(super) fn open(app: &AppHandle) -> Self {
+        if is_running_as_admin() {
+            return LaunchSession::None;
+        }
+        match privexec_broker(app).and_then(|client| client.begin_keepalive_session()) {
+            Ok(session) => LaunchSession::Broker(Box::new(session)),
+            Err(err) => {
+                eprintln!("[launch] privexec broker unavailable, using the elevated helper: {}", err);
+                LaunchSession::Unavailable
+            }
+        }
+    }
+
+    #[cfg(not(feature = "privexec"))]
+    pub(super) fn open(_app: &AppHandle) -> Self {
+        LaunchSession::None
+    }
+
+    /// Stops the keepalive and ends the session, whether the sequence succeeded or not.
+    pub(super) fn finish(self) {
+        #[cfg(feature = "privexec")]
+        if let LaunchSession::Broker(session) = self {
+            session.finish();
+        }
+    }
+}
+
+/// Attaches the set from this process, or through the elevated helper when not an admin.
+fn mount_locally(resolved: &ResolvedVhdConfig) -> Result<LaunchMount, String> {
+    mount_vhd_with_elevation(resolved).map(|handle| LaunchMount::Local(Box::new(handle)))
+}
+
+/// Without admin rights the mount goes through the privexec broker, so only its first start
+/// prompts for elevation. The per-launch helper remains the fallback when no broker can run.
+#[cfg(feature = "privexec")]
+pub(super) fn mount_launch_vhd(
+    app: &AppHandle,
+    game_id: &str,
+    resolved: &ResolvedVhdConfig,
+    session: &LaunchSession,
+) -> Result<LaunchMount, String> {
+    if is_running_as_admin() {
+        return mount_locally(resolved);
+    }
+    let mount_points = match session {
+        LaunchSession::Broker(session) => session.mount_game_vhd(game_id, resolved.delta_enabled)?,
+        LaunchSession::Unavailable => return mount_locally(resolved),
+        LaunchSession::None => match privexec_broker(app) {
+            Ok(client) => client.mount_game_vhd(game_id, resolved.delta_enabled)?,
+            Err(err) => {
+                eprintln!("[launch] privexec broker unavailable, using the elevated helper: {}", err);
+                return mount_locally(resolved);
+            }
+        },
+    };
+    Ok(LaunchMount::Broker {
+        app: app.clone(),
+        game_id: game_id.to_string(),
+        mount_points,
+    })
+}
+
+/// Builds without privexec always mount through the per-launch elevated helper.
+#[cfg(not(feature = "privexec"))]
+pub(super) fn mount_launch_vhd(
+    _app: &AppHandle,
+    _game_id: &str,
+    resolved: &ResolvedVhdConfig,
+    _session: &LaunchSession,
+) -> Result<LaunchMount, String> {
+    mount_locally(resolved)
+}
+
+pub(super) fn detect_game_on_mount(points: &MountPoints) -> ApiResult<DetectedGameInfo> {
+    let app = points.app.root();
+    let candidates = [
+        app.clone(),
+        app.join("Package"),
+        app.join("Package").join("bin"),
+        app.join("app"),
+        app.join("app").join("bin"),
+        app.join("app").join("Package"),
+    ];
+    for dir in candidates.iter() {
+        if dir.exists() {
+            if let Some(detected) = detect_game_in_dir(dir) {
+                return Ok(detected);
+            }
+        }
+    }
+    Err("No supported game executable found on mounted VHD".to_string().into())
+}
+
+#[derive(Debug)]
+pub(super) struct VfsResolved {
+    pub(super) amfs: String,
+    pub(super) appdata: String,
+    pub(super) option: String,
+}
+
+fn find_vfs_dir<F>(base: &Path, predicate: F) -> Option<PathBuf>
+where
+    F: Fn(&Path) -> bool,
+{
+    let entries = fs::read_dir(base).ok()?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+        if predicate(&path) {
+            return Some(path);
+        }
+    }
+    None
+}
+
+fn dir_has_icf(dir: &Path) -> bool {
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            if let Some(name) = entry.file_name().to_str() {
+                if name.starts_with("ICF") {
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+fn dir_has_appdata(dir: &Path) -> bool {
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+            if let Some(name) = entry.file_name().to_str() {
+                if name.len() == 4 && name.starts_with('S') && name.chars().skip(1).all(|c| c.is_ascii_uppercase()) {
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+fn dir_has_option(dir: &Path) -> bool {
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+            if let Some(name) = entry.file_name().to_str() {
+                if name.len() == 4 && (name.starts_with('X') || name.starts_with('A')) {
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+pub(super) fn detect_vfs_paths_on_drive(points: &MountPoints) -> ApiResult<VfsResolved> {
+    let app = points.app.root();
+    let candidates = [
+        app.clone(),
+        app.join("app"),
+        app.join("app").join("bin"),
+        app.join("app").join("Package"),
+    ];
+
+    let direct_amfs = app.join("amfs");
+    let direct_appdata = app.join("appdata");
+    let direct_option = app.join("option");
+    let y_drive = points.appdata.root();
+    let z_drive = points.option.root();
+    let y_amfs = y_drive.join("amfs");
+    let y_appdata = y_drive.join("appdata");
+
+    let mut amfs = if y_amfs.is_dir() {
+        Some(y_amfs)
+    } else if direct_amfs.is_dir() {
+        Some(direct_amfs)
+    } else {
+        None
+    };
+    let mut appdata = if y_appdata.is_dir() {
+        Some(y_appdata)
+    } else if y_drive.is_dir() {
+        Some(y_drive)
+    } else if direct_appdata.is_dir() {
+        Some(direct_appdata)
+    } else {
+        None
+    };
+    let mut option = if z_drive.is_dir() {
+        Some(z_drive)
+    } else if direct_option.is_dir() {
+        Some(direct_option)
+    } else {
+        None
+    };
+
+    for base in candidates.iter() {
+        if !base.exists() {
+            continue;
+        }
+        if amfs.is_none() {
+            amfs = find_vfs_dir(base, dir_has_icf);
+        }
+        if appdata.is_none() {
+            appdata = find_vfs_dir(base, dir_has_appdata);
+        }
+        if option.is_none() {
+            option = find_vfs_dir(base, dir_has_option);
+        }
+    }
+
+    let amfs = amfs.ok_or_else(|| "AMFS path not found on mounted VHD".to_string())?;
+    let appdata = appdata.ok_or_else(|| "APPDATA path not found on mounted VHD".to_string())?;
+    let option = option.ok_or_else(|| "OPTION path not found on mounted VHD".to_string())?;
+
+    Ok(VfsResolved {
+        amfs: amfs.to_string_lossy().to_string(),
+        appdata: appdata.to_string_lossy().to_string(),
+        option: option.to_string_lossy().to_string(),
+    })
+}
+
+pub(super) fn ensure_vfs_keys_present(cfg: &mut SegatoolsConfig) {
+    if !cfg.present_sections.is_empty()
+        && !cfg.present_sections.iter().any(|s| s.eq_ignore_ascii_case("vfs"))
+    {
+        cfg.present_sections.push("vfs".to_string());
+    }
+    if !cfg.present_keys.is_empty() {
+        for key in ["vfs.enable", "vfs.amfs", "vfs.appdata", "vfs.option"] {
+            if !cfg.present_keys.iter().any(|k| k.eq_ignore_ascii_case(key)) {
+                cfg.present_keys.push(key.to_string());
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BitLockerSecretKind {
+    RecoveryPassword,
+    Password,
+}
+
+pub(super) fn run_powershell_capture_with_env(
+    script: &str,
+    envs: Option<&HashMap<String, String>>,
+) -> ApiResult<String> {
+    let mut command = Command::new("powershell");
+    command
+        .args(&["-NoProfile", "-Command", script])
+        .creation_flags(0x08000000);
+    if let Some(envs) = envs {
+        for (key, value) in envs {
+            command.env(key, value);
+        }
+    }
+    let output = command
+        .output()
+        .map_err(|e| ApiError::from(e.to_string()))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        let msg = if !stderr.is_empty() { stderr } else { stdout };
+        return Err(ApiError::from(if msg.is_empty() {
+            "PowerShell command failed".to_string()
+        } else {
+            msg
+        }));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn bitlocker_cmdlets_available() -> bool {
+    if platform::is_wine() || !platform::powershell_support().runs_cmdlets() {
+        return false;
+    }
+    let script = "Get-Command Get-BitLockerVolume -ErrorAction SilentlyContinue | Select-Object -ExpandProperty Name";
+    match run_powershell_capture_with_env(script, None) {
+        Ok(out) => out.trim().eq_ignore_ascii_case("Get-BitLockerVolume"),
+        Err(_) => false,
+    }
+}
+
+fn query_bitlocker_status(mount_point: &str) -> ApiResult<Value> {
+    let escaped = mount_point.replace('\'', "''");
+    let script = format!(
+        "$mountPoint='{}';Get-BitLockerVolume -MountPoint $mountPoint -ErrorAction Stop | Select-Object MountPoint,VolumeStatus,ProtectionStatus,LockStatus,EncryptionPercentage | ConvertTo-Json -Compress",
+        escaped
+    );
+    let out = run_powershell_capture_with_env(&script, None)?;
+    serde_json::from_str::<Value>(&out).map_err(|e| ApiError::from(e.to_string()))
+}
+
+fn resolve_bitlocker_secret_for_mount(
+    mount_letter: char,
+) -> Option<(BitLockerSecretKind, String, String)> {
+    let upper = mount_letter.to_ascii_uppercase();
+    let recovery_keys = vec![
+        format!("CONFIGARC_BITLOCKER_{}_RECOVERY_PASSWORD", upper),
+        "CONFIGARC_BITLOCKER_RECOVERY_PASSWORD".to_string(),
+    ];
+    for key in recovery_keys {
+        if let Ok(value) = std::env::var(&key) {
+            let trimmed = value.trim().to_string();
+            if !trimmed.is_empty() {
+                return Some((BitLockerSecretKind::RecoveryPassword, key, trimmed));
+            }
+        }
+    }
+
+    let password_keys = vec![
+        format!("CONFIGARC_BITLOCKER_{}_PASSWORD", upper),
+        "CONFIGARC_BITLOCKER_PASSWORD".to_string(),
+    ];
+    for key in password_keys {
+        if let Ok(value) = std::env::var(&key) {
+            let trimmed = value.trim().to_string();
+            if !trimmed.is_empty() {
+                return Some((BitLockerSecretKind::Password, key, trimmed));
+            }
+        }
+    }
+    None
+}
+
+fn is_bitlocker_probe_access_denied(err: &ApiError) -> bool {
+    err.message.to_ascii_lowercase().contains("access denied")
+        || err
+            .details
+            .as_deref()
+            .unwrap_or("")
+            .to_ascii_lowercase()
+            .contains("access denied")
+}
+
+pub(super) fn locked_bitlocker_mounts(points: &MountPoints) -> ApiResult<Vec<(MountRole, MountPoint)>> {
+    if !bitlocker_cmdlets_available() {
+        return Ok(Vec::new());
+    }
+
+    let mut locked = Vec::new();
+    for (role, point) in points.iter() {
+        let status = match query_bitlocker_status(&point.volume_name()) {
+            Ok(status) => status,
+            Err(err) if is_bitlocker_probe_access_denied(&err) => continue,
+            Err(err) => return Err(err),
+        };
+        let lock_status = status
+            .get("LockStatus")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        if lock_status == "locked" {
+            locked.push((role, point.clone()));
+        }
+    }
+    Ok(locked)
+}
+
+/// Secrets are looked up by the role's usual letter, so they survive the volume landing elsewhere.
+fn unlock_bitlocker_mount_if_needed(role: MountRole, point: &MountPoint) -> ApiResult<()> {
+    let mount_letter = role.preferred_letter();
+    let mount = point.volume_name();
+    let (kind, env_name, secret) = resolve_bitlocker_secret_for_mount(mount_letter).ok_or_else(|| {
+        ApiError::from(format!(
+            "BitLocker volume {} is locked. Set {} or CONFIGARC_BITLOCKER_{}_PASSWORD.",
+            mount,
+            format!("CONFIGARC_BITLOCKER_{}_RECOVERY_PASSWORD", mount_letter.to_ascii_uppercase()),
+            mount_letter.to_ascii_uppercase()
+        ))
+    })?;
+
+    let mut envs = HashMap::new();
+    envs.insert("CONFIGARC_UNLOCK_SECRET".to_string(), secret);
+    let escaped = mount.replace('\'', "''");
+    let unlock_script = match kind {
+        BitLockerSecretKind::RecoveryPassword => format!(
+            "$mountPoint='{}';$secret=$env:CONFIGARC_UNLOCK_SECRET;Unlock-BitLocker -MountPoint $mountPoint -RecoveryPassword $secret -ErrorAction Stop;Get-BitLockerVolume -MountPoint $mountPoint -ErrorAction Stop | Select-Object MountPoint,LockStatus,ProtectionStatus | ConvertTo-Json -Compress",
+            escaped
+        ),
+        BitLockerSecretKind::Password => format!(
+            "$mountPoint='{}';$secret=$env:CONFIGARC_UNLOCK_SECRET;$secure=ConvertTo-SecureString -String $secret -AsPlainText -Force;Unlock-BitLocker -MountPoint $mountPoint -Password $secure -ErrorAction Stop;Get-BitLockerVolume -MountPoint $mountPoint -ErrorAction Stop | Select-Object MountPoint,LockStatus,ProtectionStatus | ConvertTo-Json -Compress",
+            escaped
+        ),
+    };
+    let out = run_powershell_capture_with_env(&unlock_script, Some(&envs))?;
+    let after = serde_json::from_str::<Value>(&out).map_err(|e| ApiError::from(e.to_string()))?;
+    let after_lock = after
+        .get("LockStatus")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    if after_lock != "unlocked" {
+        return Err(ApiError::from(format!(
+            "BitLocker unlock did not succeed for {} (secret source: {}).",
+            mount, env_name
+        )));
+    }
+    Ok(())
+}
+
+pub(super) fn unlock_mounted_vhd_bitlocker_volumes(volumes: &[(MountRole, MountPoint)]) -> ApiResult<()> {
+    for (role, point) in volumes {
+        unlock_bitlocker_mount_if_needed(*role, point)?;
+    }
+    Ok(())
+}
+
+pub(super) fn lock_mounted_vhd_bitlocker_volumes_best_effort(points: &MountPoints) {
+    if !bitlocker_cmdlets_available() {
+        return;
+    }
+    for (_, point) in points.iter() {
+        let mount = point.volume_name();
+        let escaped = mount.replace('\'', "''");
+        let script = format!(
+            "$mountPoint='{}';try {{ Lock-BitLocker -MountPoint $mountPoint -ForceDismount:$false -ErrorAction Stop | Out-Null }} catch {{ }}",
+            escaped
+        );
+        let _ = run_powershell_capture_with_env(&script, None);
+    }
+}
+
+#[derive(Serialize)]
+pub struct VhdDetectResult {
+    pub game: Game,
+    pub vhd: VhdConfig,
+}
+
+#[derive(Debug, Clone)]
+enum ParsedAppVhdKind {
+    Base,
+    Patch { parent_version: String },
+}
+
+#[derive(Debug, Clone)]
+struct ParsedAppVhdName {
+    prefix: String,
+    version: String,
+    timestamp: String,
+    kind: ParsedAppVhdKind,
+}
+
+fn is_version_token(value: &str) -> bool {
+    let parts = value.split('.').collect::<Vec<_>>();
+    parts.len() >= 2
+        && parts
+            .iter()
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn is_timestamp_token(value: &str) -> bool {
+    value.len() >= 8 && value.chars().all(|c| c.is_ascii_digit())
+}
+
+fn is_patch_marker_token(value: &str) -> bool {
+    value.parse::<u32>().map(|marker| marker >= 1).unwrap_or(false)
+}
+
+fn parse_version_key(value: &str) -> Vec<u32> {
+    value
+        .split('.')
+        .map(|part| part.parse::<u32>().unwrap_or(0))
+        .collect()
+}
+
+fn compare_version_tokens(left: &str, right: &str) -> CmpOrdering {
+    let left_parts = parse_version_key(left);
+    let right_parts = parse_version_key(right);
+    let len = left_parts.len().max(right_parts.len());
+    for index in 0..len {
+        let left_part = *left_parts.get(index).unwrap_or(&0);
+        let right_part = *right_parts.get(index).unwrap_or(&0);
+        match left_part.cmp(&right_part) {
+            CmpOrdering::Equal => continue,
+            other => return other,
+        }
+    }
+    left.cmp(right)
+}
+
+fn parse_app_vhd_name(path: &Path) -> Option<ParsedAppVhdName> {
+    let stem = path.file_stem()?.to_str()?;
+    let parts = stem.split('_').collect::<Vec<_>>();
+    if parts.len() < 4 {
+        return None;
+    }
+
+    let marker = *parts.last()?;
+    if marker == "0" {
+        let version = *parts.get(parts.len().checked_sub(3)?)?;
+        let timestamp = *parts.get(parts.len().checked_sub(2)?)?;
+        if !is_version_token(version) || !is_timestamp_token(timestamp) {
+            return None;
+        }
+        let prefix = parts[..parts.len() - 3].join("_");
+        if prefix.is_empty() {
+            return None;
+        }
+        return Some(ParsedAppVhdName {
+            prefix,
+            version: version.to_string(),
+            timestamp: timestamp.to_string(),
+            kind: ParsedAppVhdKind::Base,
+        });
+    }
+
+    if parts.len() >= 5 && is_patch_marker_token(parts[parts.len() - 2]) {
+        let version = parts[parts.len() - 4];
+        let timestamp = parts[parts.len() - 3];
+        let parent_version = parts[parts.len() - 1];
+        if !is_version_token(version) || !is_version_token(parent_version) || !is_timestamp_token(timestamp) {
+            return None;
+        }
+        let prefix = parts[..parts.len() - 4].join("_");
+        if prefix.is_empty() {
+            return None;
+        }
+        return Some(ParsedAppVhdName {
+            prefix,
+            version: version.to_string(),
+            timestamp: timestamp.to_string(),
+            kind: ParsedAppVhdKind::Patch {
+                parent_version: parent_version.to_string(),
+            },
+        });
+    }
+
+    None
+}
+
+/// App version of the base VHD and the target version of each patch, in chain order, as
+/// their file names state them.
+#[cfg(feature = "icf")]
+pub(super) fn app_chain_versions(cfg: &VhdConfig) -> (Option<String>, Vec<String>) {
+    let base = parse_app_vhd_name(Path::new(cfg.app_base_path.trim())).map(|parsed| parsed.version);
+    let patches = cfg
+        .app_patch_paths
+        .iter()
+        .filter_map(|path| parse_app_vhd_name(Path::new(path.trim())))
+        .map(|parsed| parsed.version)
+        .collect();
+    (base, patches)
+}
+
+fn unpacked_zip_stems_for_parent(path: &Path) -> Vec<String> {
+    let mut stems = Vec::new();
+    if let Some(stem) = path.file_stem().and_then(|value| value.to_str()) {
+        stems.push(format!("{stem}_Unpacked"));
+    }
+
+    if let Some(parsed) = parse_app_vhd_name(path) {
+        stems.push(format!("{}_{}_Unpacked", parsed.prefix, parsed.version));
+        let short_version = parsed
+            .version
+            .split('.')
+            .take(2)
+            .collect::<Vec<_>>()
+            .join(".");
+        if !short_version.is_empty() && short_version != parsed.version {
+            stems.push(format!("{}_{}_Unpacked", parsed.prefix, short_version));
+        }
+    }
+
+    let mut seen = HashSet::new();
+    stems
+        .into_iter()
+        .filter(|stem| seen.insert(stem.to_lowercase()))
+        .collect()
+}
+
+fn find_unpacked_zip_for_parent(parent_path: &Path) -> Option<PathBuf> {
+    let parent_dir = parent_path.parent()?;
+    let candidates = unpacked_zip_stems_for_parent(parent_path)
+        .into_iter()
+        .map(|stem| stem.to_lowercase())
+        .collect::<Vec<_>>();
+
+    let mut entries = fs::read_dir(parent_dir)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|item| item.path()))
+        .filter(|path| {
+            path.is_file()
+                && path.extension().and_then(|ext| ext.to_str()).map(|ext| ext.eq_ignore_ascii_case("zip")).unwrap_or(false)
+        })
+        .collect::<Vec<_>>();
+    entries.sort();
+
+    entries.into_iter().find(|path| {
+        let stem = path.file_stem().and_then(|value| value.to_str()).unwrap_or("").to_lowercase();
+        candidates.iter().any(|candidate| stem == *candidate)
+    })
+}
+
+pub(super) fn find_unpacked_zip_for_chain(app_base_path: &Path, app_patch_paths: &[PathBuf]) -> Option<PathBuf> {
+    app_patch_paths
+        .iter()
+        .rev()
+        .map(PathBuf::as_path)
+        .chain(std::iter::once(app_base_path))
+        .find_map(|path| find_unpacked_zip_for_parent(path))
+}
+
+fn clean_zip_entry_path(name: &str) -> Option<PathBuf> {
+    let mut relative = PathBuf::new();
+    for component in Path::new(name).components() {
+        match component {
+            std::path::Component::Normal(part) => relative.push(part),
+            std::path::Component::CurDir => {}
+            std::path::Component::Prefix(_)
+            | std::path::Component::RootDir
+            | std::path::Component::ParentDir => return None,
+        }
+    }
+    if relative.as_os_str().is_empty() {
+        None
+    } else {
+        Some(relative)
+    }
+}
+
+pub(super) fn apply_unpacked_zip_overlay(mount_root: &Path, zip_path: &Path) -> ApiResult<()> {
+    let file = fs::File::open(zip_path).map_err(|e| ApiError::from(e.to_string()))?;
+    let mut zip = ZipArchive::new(file).map_err(|e| ApiError::from(e.to_string()))?;
+    for index in 0..zip.len() {
+        let mut entry = zip.by_index(index).map_err(|e| ApiError::from(e.to_string()))?;
+        let Some(relative) = clean_zip_entry_path(entry.name()) else {
+            continue;
+        };
+        let target = mount_root.join(relative);
+        if entry.is_dir() {
+            fs::create_dir_all(&target).map_err(|e| ApiError::from(e.to_string()))?;
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| ApiError::from(e.to_string()))?;
+        }
+        let mut out = fs::File::create(&target).map_err(|e| ApiError::from(e.to_string()))?;
+        std::io::copy(&mut entry, &mut out).map_err(|e| ApiError::from(e.to_string()))?;
+    }
+    Ok(())
+}
+
+fn select_base_vhd(app_candidates: &[PathBuf]) -> Option<PathBuf> {
+    let parsed = app_candidates
+        .iter()
+        .filter_map(|path| parse_app_vhd_name(path).map(|meta| (path, meta)))
+        .collect::<Vec<_>>();
+
+    let patch_roots = parsed
+        .iter()
+        .filter_map(|(_, meta)| match &meta.kind {
+            ParsedAppVhdKind::Patch { parent_version } => Some(parent_version.clone()),
+            ParsedAppVhdKind::Base => None,
+        })
+        .collect::<HashSet<_>>();
+    let patch_targets = parsed
+        .iter()
+        .filter_map(|(_, meta)| match &meta.kind {
+            ParsedAppVhdKind::Patch { .. } => Some(meta.version.clone()),
+            ParsedAppVhdKind::Base => None,
+        })
+        .collect::<HashSet<_>>();
+
+    let root_versions = patch_roots
+        .difference(&patch_targets)
+        .cloned()
+        .collect::<HashSet<_>>();
+
+    if root_versions.len() == 1 {
+        let root_version = root_versions.iter().next().cloned().unwrap_or_default();
+        if let Some((path, _)) = parsed.iter().find(|(_, meta)| {
+            matches!(meta.kind, ParsedAppVhdKind::Base) && meta.version == root_version
+        }) {
+            return Some((*path).clone());
+        }
+    }
+
+    parsed
+        .iter()
+        .find(|(_, meta)| matches!(meta.kind, ParsedAppVhdKind::Base))
+        .map(|(path, _)| (*path).clone())
+}
+
+fn order_patch_vhds(base: &Path, patches: Vec<PathBuf>) -> Vec<PathBuf> {
+    #[derive(Clone)]
+    struct PatchEntry {
+        path: PathBuf,
+        meta: Option<ParsedAppVhdName>,
+    }
+
+    let base_meta = parse_app_vhd_name(base);
+    let mut parsed = patches
+        .into_iter()
+        .map(|path| PatchEntry {
+            meta: parse_app_vhd_name(&path),
+            path,
+        })
+        .collect::<Vec<_>>();
+
+    let mut ordered = Vec::new();
+    let mut current_version = base_meta.as_ref().and_then(|meta| match &meta.kind {
+        ParsedAppVhdKind::Base => Some(meta.version.clone()),
+        ParsedAppVhdKind::Patch { .. } => None,
+    });
+    let base_prefix = base_meta.as_ref().map(|meta| meta.prefix.as_str());
+
+    while let Some(version) = current_version.clone() {
+        let next_index = parsed
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| {
+                let meta = entry.meta.as_ref()?;
+                let parent_version = match &meta.kind {
+                    ParsedAppVhdKind::Patch { parent_version } => parent_version,
+                    ParsedAppVhdKind::Base => return None,
+                };
+                if parent_version != &version {
+                    return None;
+                }
+                if let Some(prefix) = base_prefix {
+                    if meta.prefix != prefix {
+                        return None;
+                    }
+                }
+                Some((index, meta))
+            })
+            .min_by(|(_, left), (_, right)| {
+                compare_version_tokens(&left.version, &right.version)
+                    .then_with(|| left.timestamp.cmp(&right.timestamp))
+            })
+            .map(|(index, _)| index);
+
+        let Some(index) = next_index else {
+            break;
+        };
+
+        let next = parsed.remove(index);
+        current_version = next.meta.as_ref().and_then(|meta| match &meta.kind {
+            ParsedAppVhdKind::Patch { .. } => Some(meta.version.clone()),
+            ParsedAppVhdKind::Base => None,
+        });
+        ordered.push(next.path);
+    }
+
+    parsed.sort_by(|left, right| match (&left.meta, &right.meta) {
+        (Some(left_meta), Some(right_meta)) => {
+            let left_parent = match &left_meta.kind {
+                ParsedAppVhdKind::Patch { parent_version } => parent_version.as_str(),
+                ParsedAppVhdKind::Base => "",
+            };
+            let right_parent = match &right_meta.kind {
+                ParsedAppVhdKind::Patch { parent_version } => parent_version.as_str(),
+                ParsedAppVhdKind::Base => "",
+            };
+            compare_version_tokens(left_parent, right_parent)
+                .then_with(|| compare_version_tokens(&left_meta.version, &right_meta.version))
+                .then_with(|| left_meta.timestamp.cmp(&right_meta.timestamp))
+                .then_with(|| left.path.cmp(&right.path))
+        }
+        (Some(_), None) => CmpOrdering::Less,
+        (None, Some(_)) => CmpOrdering::Greater,
+        (None, None) => left.path.cmp(&right.path),
+    });
+    ordered.extend(parsed.into_iter().map(|entry| entry.path));
+    ordered
+}
+
+const APPDATA_VHD_PATTERNS: &[&str] = &["appdata", "app_data"];
+const OPTION_VHD_PATTERNS: &[&str] = &["option", "opt"];
+
+fn file_size(path: &Path) -> u64 {
+    fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+fn file_name_contains(path: &Path, patterns: &[&str]) -> bool {
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+        return false;
+    };
+    let lower = name.to_lowercase();
+    patterns.iter().any(|p| lower.contains(p))
+}
+
+/// Sorts the images in `dir` into app base, patches, AppData and Option. Differencing disks
+/// are placed by the parent links in their headers, so renamed images still line up and a
+/// broken chain is rejected naming the image at fault; only a folder without any differencing
+/// disk falls back to guessing from file names and sizes.
+pub(super) fn detect_vhd_files_in_dir(dir: &Path) -> ApiResult<VhdConfig> {
+    let mut vhds: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| ApiError::from(e.to_string()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.is_file()
+                && is_virtual_disk_path(path)
+                && !path.file_stem().and_then(|s| s.to_str()).map(|s| s.contains("-runtime")).unwrap_or(false)
+        })
+        .collect();
+
+    if vhds.is_empty() {
+        return Err(("No VHD files found in the selected folder.".to_string()).into());
+    }
+
+    vhds.sort_by_key(|p| file_size(p));
+    let (chain, standalone) = classify_images(&vhds).map_err(|e| ApiError::new(ErrorCode::InvalidInput, e))?;
+
+    let appdata = standalone
+        .iter()
+        .find(|p| file_name_contains(p, APPDATA_VHD_PATTERNS))
+        .cloned()
+        .ok_or_else(|| "AppData VHD not found. Please select manually.".to_string())?;
+
+    let option = standalone
+        .iter()
+        .filter(|p| *p != &appdata)
+        .find(|p| file_name_contains(p, OPTION_VHD_PATTERNS))
+        .cloned()
+        .ok_or_else(|| "Option VHD not found. Please select manually.".to_string())?;
+
+    let (base, patches) = match chain {
+        Some(chain) => (chain.base, chain.patches),
+        None => {
+            let mut app_candidates: Vec<PathBuf> = vhds
+                .iter()
+                .filter(|p| *p != &appdata && *p != &option)
+                .cloned()
+                .collect();
+
+            if app_candidates.is_empty() {
+                return Err(
+                    "App base VHD not found. Please ensure folder includes app base, appdata, and option VHDs."
+                        .to_string()
+                        .into(),
+                );
+            }
+
+            app_candidates.sort_by_key(|p| file_size(p));
+
+            let base = select_base_vhd(&app_candidates)
+                .or_else(|| app_candidates.iter().max_by_key(|p| file_size(p)).cloned())
+                .ok_or_else(|| "App base VHD not found. Please select manually.".to_string())?;
+
+            let patches = order_patch_vhds(
+                &base,
+                app_candidates
+                .into_iter()
+                .filter(|p| p != &base)
+                .collect::<Vec<_>>(),
+            );
+            (base, patches)
+        }
+    };
+
+    Ok(VhdConfig {
+        app_base_path: base.to_string_lossy().to_string(),
+        app_patch_paths: patches
+            .into_iter()
+            .map(|patch| patch.to_string_lossy().to_string())
+            .collect(),
+        appdata_path: appdata.to_string_lossy().to_string(),
+        option_path: option.to_string_lossy().to_string(),
+        delta_enabled: true,
+        delta_dir: None,
+        delta_min_free_mb: 2048,
+        delta_snapshots: 0,
+        mount_root: None,
+        letter_wait_secs: 15,
+    })
+}
+
+/// VHD config for app VHDs that did not come from a game folder, e.g. fresh decryption output.
+/// Base and patches are ordered like [`detect_vhd_files_in_dir`] does, by parent links when
+/// every image belongs to one chain; AppData and Option VHDs lying next to the base are picked
+/// up, and stay empty for the user to fill in otherwise.
+#[cfg(feature = "fsdecrypt")]
+pub(super) fn vhd_config_for_app_chain(app_vhds: Vec<PathBuf>) -> Option<VhdConfig> {
+    let linked = match classify_images(&app_vhds) {
+        Ok((Some(chain), standalone)) if standalone.is_empty() => Some(chain),
+        _ => None,
+    };
+    let base = match &linked {
+        Some(chain) => chain.base.clone(),
+        None => select_base_vhd(&app_vhds).or_else(|| app_vhds.iter().max_by_key(|p| file_size(p)).cloned())?,
+    };
+    let neighbours: Vec<PathBuf> = base
+        .parent()
+        .and_then(|dir| fs::read_dir(dir).ok())
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| {
+                    path.is_file()
+                        && is_virtual_disk_path(path)
+                        && !app_vhds.contains(path)
+                        && !path.file_stem().and_then(|s| s.to_str()).is_some_and(|s| s.contains("-runtime"))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let find_role = |patterns: &[&str]| {
+        neighbours
+            .iter()
+            .find(|p| file_name_contains(p, patterns))
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default()
+    };
+    let appdata_path = find_role(APPDATA_VHD_PATTERNS);
+    let option_path = find_role(OPTION_VHD_PATTERNS);
+    let patches = match linked {
+        Some(chain) => chain.patches,
+        None => order_patch_vhds(&base, app_vhds.into_iter().filter(|p| p != &base).collect()),
+    };
+
+    Some(VhdConfig {
+        app_base_path: base.to_string_lossy().to_string(),
+        app_patch_paths: patches
+            .into_iter()
+            .map(|patch| patch.to_string_lossy().to_string())
+            .collect(),
+        appdata_path,
+        option_path,
+        delta_enabled: true,
+        delta_dir: None,
+        delta_min_free_mb: 2048,
+        delta_snapshots: 0,
+        mount_root: None,
+        letter_wait_secs: 15,
+    })
+}
+
+pub(super) fn build_vhd_game(dir: &Path, vhd: &VhdConfig) -> Game {
+    let name = Path::new(&vhd.app_base_path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("VHD Game")
+        .to_string();
+
+    Game {
+        id: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis().to_string(),
+        name,
+        executable_path: vhd.app_base_path.clone(),
+        working_dir: Some(dir.to_string_lossy().to_string()),
+        launch_args: vec![],
+        enabled: true,
+        tags: vec![],
+        launch_mode: LaunchMode::Vhd,
+        sandbox: SandboxOptions::default(),
+        gpu_preference: GpuPreference::default(),
+        display_presets: vec![],
+        display_preset: None,
+        pre_launch: vec![],
+        post_exit: vec![],
+        watchdog: WatchdogOptions::default(),
+        detected_version: None,
+    }
+}
+
+#[command]
+pub async fn pick_vhd_game_cmd() -> ApiResult<VhdDetectResult> {
+    tauri::async_runtime::spawn_blocking(|| {
+        let path = pick_folder_or_cancel()?;
+
+        let dir = Path::new(&path);
+        if !dir.exists() || !dir.is_dir() {
+            return Err(("Invalid directory".to_string()).into());
+        }
+
+        let vhd = detect_vhd_files_in_dir(dir)?;
+        let game = build_vhd_game(dir, &vhd);
+
+        Ok(VhdDetectResult { game, vhd })
+    })
+    .await
+    .map_err(|e| ApiError::from(e.to_string()))?
+}
+
+#[command]
+pub fn load_vhd_config_cmd(game_id: String) -> ApiResult<VhdConfig> {
+    load_vhd_config(&game_id).map_err(|e| ApiError::from(e.to_string()))
+}
+
+#[command]
+pub fn save_vhd_config_cmd(game_id: String, config: VhdConfig) -> ApiResult<()> {
+    save_vhd_config(&game_id, &config).map_err(|e| ApiError::from(e.to_string()))
+}
+
+#[command]
+pub fn list_delta_snapshots_cmd(game_id: String) -> ApiResult<DeltaSnapshots> {
+    list_delta_snapshots(&game_id).map_err(ApiError::from)
+}
+
+/// Selects the snapshot the next launch starts from; `name: null` clears the selection.
+#[command]
+pub fn restore_delta_snapshot_cmd(game_id: String, name: Option<String>) -> ApiResult<DeltaSnapshots> {
+    restore_delta_snapshot(&game_id, name.as_deref()).map_err(ApiError::from)
+}
+
+#[command]
+pub fn rename_delta_snapshot_cmd(game_id: String, name: String, new_name: String) -> ApiResult<DeltaSnapshots> {
+    rename_delta_snapshot(&game_id, &name, &new_name).map_err(ApiError::from)
+}
+
+#[command]
+pub fn delete_delta_snapshot_cmd(game_id: String, name: String) -> ApiResult<DeltaSnapshots> {
+    delete_delta_snapshot(&game_id, &name).map_err(ApiError::from)
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct VhdExportProgress {
+    source: String,
+    #[serde(flatten)]
+    progress: CopyProgress,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VhdExportResult {
+    pub destination: String,
+    pub bytes: u64,
+    /// Hash of the copy, missing when the file was simply renamed on the same volume.
+    pub sha256: Option<String>,
+    /// Config entries of the game that now point at the new location.
+    pub relocated_entries: usize,
+    pub source_removed: bool,
+}
+
+fn same_file_path(left: &Path, right: &Path) -> bool {
+    match (fs::canonicalize(left), fs::canonicalize(right)) {
+        (Ok(left), Ok(right)) => left == right,
+        _ => left.to_string_lossy().eq_ignore_ascii_case(&right.to_string_lossy()),
+    }
+}
+
+/// Points every entry of the game's VHD config that resolves to `from` at `to`.
+fn relocate_vhd_config_entries(game_id: &str, from: &Path, to: &Path) -> ApiResult<usize> {
+    let mut cfg = load_vhd_config(game_id).map_err(ApiError::from)?;
+    // Entries are matched without `resolve_vhd_config`, which rejects the already moved file.
+    let base_dir = vhd_config_path_for_game_id(game_id)
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    let to = to.to_string_lossy().to_string();
+    let mut relocated = 0;
+    let entries = std::iter::once(&mut cfg.app_base_path)
+        .chain(cfg.app_patch_paths.iter_mut())
+        .chain([&mut cfg.appdata_path, &mut cfg.option_path]);
+    for raw in entries {
+        if same_file_path(&resolve_with_base(&base_dir, raw.trim()), from) {
+            *raw = to.clone();
+            relocated += 1;
+        }
+    }
+    if relocated > 0 {
+        save_vhd_config(game_id, &cfg).map_err(ApiError::from)?;
+    }
+    Ok(relocated)
+}
+
+/// Moves a decrypted VHD to an archive folder. Across volumes the file is copied at up to
+/// `max_mb_per_sec`, verified by hash and only then removed from its old place. With
+/// `game_id`, the game's VHD config follows the file to its new location.
+#[command]
+pub async fn export_decrypted_vhd_cmd(
+    window: Window,
+    source: String,
+    destination_dir: String,
+    game_id: Option<String>,
+    max_mb_per_sec: Option<u64>,
+    keep_source: Option<bool>,
+) -> ApiResult<VhdExportResult> {
+    tauri::async_runtime::spawn_blocking(move || -> ApiResult<VhdExportResult> {
+        let src = PathBuf::from(source.trim());
+        if !src.is_file() {
+            return Err(ApiError::from(format!("VHD not found: {}", src.display())));
+        }
+        let file_name = src.file_name().ok_or_else(|| "Invalid VHD path".to_string())?;
+        let dest = PathBuf::from(destination_dir.trim()).join(file_name);
+        if same_file_path(&src, &dest) {
+            return Err(("Source and destination are the same file".to_string()).into());
+        }
+        let keep_source = keep_source.unwrap_or(false);
+
+        let renamed = !keep_source && !dest.exists() && fs::rename(&src, &dest).is_ok();
+        let (bytes, sha256) = if renamed {
+            (fs::metadata(&dest).map(|m| m.len()).unwrap_or(0), None)
+        } else {
+            let options = CopyOptions {
+                max_bytes_per_sec: max_mb_per_sec.map(|mb| mb.saturating_mul(1024 * 1024)),
+                overwrite: false,
+            };
+            let source_label = src.to_string_lossy().to_string();
+            let copy = copy_verified(&src, &dest, &options, |progress| {
+                let _ = window.emit(
+                    "vhd-export-progress",
+                    VhdExportProgress {
+                        source: source_label.clone(),
+                        progress,
+                    },
+                );
+            })
+            .map_err(ApiError::from)?;
+            (copy.bytes, Some(copy.sha256))
+        };
+
+        let relocated_entries = match game_id.as_deref().filter(|id| !id.is_empty()) {
+            Some(id) => relocate_vhd_config_entries(id, &src, &dest)?,
+            None => 0,
+        };
+        let source_removed = renamed || (!keep_source && fs::remove_file(&src).is_ok());
+        if let Some(id) = game_id.as_deref().filter(|id| !id.is_empty()) {
+            journal::record(
+                id,
+                JournalEntry::new("vhd_export", format!("Moved {} to {}", src.display(), dest.display()))
+                    .with_details(serde_json::json!({ "sha256": sha256, "relocatedEntries": relocated_entries })),
+            );
+        }
+        Ok(VhdExportResult {
+            destination: dest.to_string_lossy().to_string(),
+            bytes,
+            sha256,
+            relocated_entries,
+            source_removed,
+        })
+    })
+    .await
+    .map_err(|e| ApiError::from(e.to_string()))?
+}
+
+/// Detaches VHDs and clears runtime deltas left behind by a launcher that exited without
+/// unmounting. Runs once in the background at startup; launches wait for it in `mount_vhd_*`.
+pub fn recover_stale_vhd_mounts(app: AppHandle) {
+    std::thread::spawn(move || {
+        let game_ids: Vec<String> = store::list_games()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|game| matches!(game.launch_mode, LaunchMode::Vhd))
+            .map(|game| game.id)
+            .collect();
+        let report = reconcile_stale_mounts(&game_ids);
+        if report.is_empty() {
+            return;
+        }
+        let mut parts = Vec::new();
+        if let Some(points) = &report.mount_points {
+            parts.push(format!("Detached images left mounted ({})", points.describe()));
+        }
+        if !report.snapshotted_deltas.is_empty() {
+            parts.push(format!("Kept {} runtime delta(s) as snapshots", report.snapshotted_deltas.len()));
+        }
+        if !report.removed_deltas.is_empty() {
+            parts.push(format!("Removed {} orphaned runtime delta(s)", report.removed_deltas.len()));
+        }
+        let severity = if report.errors.is_empty() {
+            NotificationSeverity::Info
+        } else {
+            parts.push(format!("{} cleanup step(s) failed", report.errors.len()));
+            NotificationSeverity::Warning
+        };
+        let details = serde_json::to_value(&report).unwrap_or(Value::Null);
+        notify_app(
+            &app,
+            Notification::new("vhd", severity, "Recovered from an unfinished VHD session", parts.join(". "))
+                .with_details(details),
+        );
+    });
+}
+
+/// Reads a VHD game's images into the OS cache in the background, optionally after
+/// `delay_secs` so it can be started ahead of opening time, and returns the operation id.
+/// Progress is emitted as `vhd-warmup-progress` and the report as `vhd-warmup-finished`.
+#[command]
+pub fn start_vhd_warmup_cmd(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    game_id: String,
+    delay_secs: Option<u64>,
+    operation_id: Option<String>,
+) -> ApiResult<String> {
+    let cfg = load_vhd_config(&game_id).map_err(ApiError::from)?;
+    let resolved = resolve_vhd_config(&game_id, &cfg).map_err(ApiError::from)?;
+    let operation = state.begin_cancellable("vhd_warmup", operation_id, true)?;
+    let id = operation.id().to_string();
+    std::thread::spawn(move || {
+        let deadline = Instant::now() + Duration::from_secs(delay_secs.unwrap_or(0));
+        while Instant::now() < deadline && !operation.token().is_cancelled() {
+            std::thread::sleep(Duration::from_millis(500));
+        }
+        let result = warm_up_images(&resolved.image_paths(), operation.token(), |progress| {
+            let _ = app.emit("vhd-warmup-progress", &progress);
+        });
+        drop(operation);
+        match result {
+            Ok(report) => {
+                let _ = app.emit("vhd-warmup-finished", &report);
+            }
+            Err(err) => notify_app(
+                &app,
+                Notification::new("vhd", NotificationSeverity::Warning, "VHD warm-up failed", err)
+                    .with_dedupe_key(format!("vhd-warmup:{}", game_id)),
+            ),
+        }
+    });
+    Ok(id)
+}
+
+#[command]
+pub fn cancel_vhd_warmup_cmd(state: State<'_, AppState>) -> ApiResult<()> {
+    state.cancel_operations("vhd_warmup");
+    Ok(())
+}
+
+/// Payload of `vhd-migration-progress`.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct MigrationProgressEvent<'a> {
+    game_id: &'a str,
+    #[serde(flatten)]
+    progress: &'a MigrationProgress,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VhdMigrationReport {
+    pub plan: MigrationPlan,
+    /// Game the test mount found on the new app volume.
+    pub detected_game: String,
+    /// Original folders moved to the trash, when asked to remove them.
+    pub trashed: Vec<TrashEntry>,
+}
+
+fn folder_game(game_id: &str) -> ApiResult<Game> {
+    let game = store::list_games()
+        .map_err(ApiError::from)?
+        .into_iter()
+        .find(|g| g.id == game_id)
+        .ok_or_else(|| ApiError::new(ErrorCode::NotFound, format!("Game {} not found", game_id)))?;
+    if matches!(game.launch_mode, LaunchMode::Vhd) {
+        return Err(ApiError::new(ErrorCode::InvalidInput, format!("{} already runs from VHDs", game.name)));
+    }
+    Ok(game)
+}
+
+/// The game folder plus the amfs, appdata and option folders its segatools.ini points at.
+fn migration_sources(game: &Game) -> ApiResult<MigrationSources> {
+    let game_root = store::game_root_dir(game).ok_or_else(|| ApiError::from("Game path missing".to_string()))?;
+    let seg_path = segatoools_path_for_game_id(&game.id).map_err(ApiError::from)?;
+    let cfg = load_segatoools_config(&seg_path).map_err(ApiError::from)?;
+    let vfs_path = |raw: &str| {
+        let raw = raw.trim();
+        (!raw.is_empty()).then(|| resolve_with_base(&game_root, raw))
+    };
+    Ok(MigrationSources {
+        amfs: vfs_path(&cfg.vfs.amfs),
+        appdata: vfs_path(&cfg.vfs.appdata),
+        option: vfs_path(&cfg.vfs.option),
+        game_root,
+    })
+}
+
+fn migration_format(format: Option<&str>) -> ApiResult<VirtualDiskFormat> {
+    match format.map(|f| f.trim().to_ascii_lowercase()).as_deref() {
+        None | Some("") | Some("vhdx") => Ok(VirtualDiskFormat::Vhdx),
+        Some("vhd") => Ok(VirtualDiskFormat::Vhd),
+        Some(other) => Err(ApiError::new(ErrorCode::InvalidInput, format!("Unknown image format: {}", other))),
+    }
+}
+
+/// What converting a folder-mode game to VHDs in `target_dir` would create: one image per
+/// volume with its size and the folders copied onto it. Nothing is written.
+#[command]
+pub fn plan_vhd_migration_cmd(game_id: String, target_dir: String, format: Option<String>) -> ApiResult<MigrationPlan> {
+    let game = folder_game(&game_id)?;
+    let sources = migration_sources(&game)?;
+    plan_migration(&sources, Path::new(target_dir.trim()), migration_format(format.as_deref())?).map_err(ApiError::from)
+}
+
+/// Converts a folder-mode game to a VHD game in one supervised run: creates and fills the
+/// images, switches the game to them, test-mounts them and, with `remove_originals`, moves
+/// the original folders to the trash. A failed test mount switches the game back and leaves
+/// the images for inspection. Progress is emitted as `vhd-migration-progress`.
+#[command]
+pub async fn migrate_game_to_vhd_cmd(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    game_id: String,
+    target_dir: String,
+    format: Option<String>,
+    remove_originals: Option<bool>,
+    operation_id: Option<String>,
+) -> ApiResult<VhdMigrationReport> {
+    let game = folder_game(&game_id)?;
+    let operation = state.begin_operation(&game.id, "VHD migration")?;
+    let cancellable = state.begin_cancellable("vhd_migration", operation_id, false)?;
+    tauri::async_runtime::spawn_blocking(move || -> ApiResult<VhdMigrationReport> {
+        let _operation = operation;
+        let sources = migration_sources(&game)?;
+        let plan = plan_migration(&sources, Path::new(target_dir.trim()), migration_format(format.as_deref())?)
+            .map_err(ApiError::from)?;
+        let result = (|| -> ApiResult<VhdMigrationReport> {
+            migrate_to_vhd(&plan, cancellable.token(), &mut |progress| {
+                let _ = app.emit(
+                    "vhd-migration-progress",
+                    MigrationProgressEvent {
+                        game_id: &game.id,
+                        progress,
+                    },
+                );
+            })
+            .map_err(ApiError::from)?;
+
+            let config = plan.vhd_config();
+            save_vhd_config(&game.id, &config).map_err(ApiError::from)?;
+            let mut converted = game.clone();
+            converted.launch_mode = LaunchMode::Vhd;
+            converted.executable_path = config.app_base_path.clone();
+            converted.working_dir = Some(plan.target_dir.to_string_lossy().into_owned());
+            store::save_game(converted).map_err(ApiError::from)?;
+
+            let tested = (|| -> ApiResult<DetectedGameInfo> {
+                let resolved = resolve_vhd_config(&game.id, &config).map_err(ApiError::from)?;
+                let mounted = mount_launch_vhd(&app, &game.id, &resolved, &LaunchSession::None).map_err(ApiError::from)?;
+                let detected = detect_game_on_mount(mounted.mount_points());
+                let _ = mounted.unmount();
+                detected
+            })();
+            let detected = match tested {
+                Ok(detected) => detected,
+                Err(mut err) => {
+                    let _ = store::save_game(game.clone());
+                    err.message = format!(
+                        "Test mount of the new images failed, the game stays in folder mode and the images are kept in {}: {}",
+                        plan.target_dir.display(),
+                        err.message
+                    );
+                    return Err(err);
+                }
+            };
+
+            let mut trashed = Vec::new();
+            if remove_originals.unwrap_or(false) {
+                for path in plan.original_paths() {
+                    trashed.push(move_to_trash(&game.id, &path, "vhd_migration").map_err(ApiError::from)?);
+                }
+            }
+            Ok(VhdMigrationReport {
+                plan: plan.clone(),
+                detected_game: detected.name,
+                trashed,
+            })
+        })();
+        journal::record(
+            &game.id,
+            JournalEntry::new("vhd_migration", format!("Converted to VHDs in {}", plan.target_dir.display()))
+                .with_outcome(&result.as_ref().map_err(|e| e.message.clone()))
+                .with_details(serde_json::json!({
+                    "images": plan.volumes.iter().map(|v| v.image.to_string_lossy().into_owned()).collect::<Vec<_>>(),
+                    "removeOriginals": remove_originals.unwrap_or(false),
+                })),
+        );
+        result
+    })
+    .await
+    .map_err(|e| ApiError::from(e.to_string()))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn parses_versioned_patch_name() {
+        let parsed = parse_app_vhd_name(&PathBuf::from(
+            "SDGA_1.61.00_20260309140300_1_1.60.00.vhd",
+        ))
+        .unwrap();
+
+        assert_eq!(parsed.prefix, "SDGA");
+        assert_eq!(parsed.version, "1.61.00");
+        assert_eq!(parsed.timestamp, "20260309140300");
+        match parsed.kind {
+            ParsedAppVhdKind::Patch { parent_version } => {
+                assert_eq!(parent_version, "1.60.00");
+            }
+            ParsedAppVhdKind::Base => panic!("expected patch kind"),
+        }
+    }
+
+    #[test]
+    fn parses_versioned_patch_name_with_non_one_marker() {
+        let parsed = parse_app_vhd_name(&PathBuf::from(
+            "SDGA_1.62.00_20260401120000_2_1.61.00.vhd",
+        ))
+        .unwrap();
+
+        assert_eq!(parsed.prefix, "SDGA");
+        assert_eq!(parsed.version, "1.62.00");
+        assert_eq!(parsed.timestamp, "20260401120000");
+        match parsed.kind {
+            ParsedAppVhdKind::Patch { parent_version } => {
+                assert_eq!(parent_version, "1.61.00");
+            }
+            ParsedAppVhdKind::Base => panic!("expected patch kind"),
+        }
+    }
+
+    #[test]
+    fn prefers_chain_root_base_and_orders_patches_by_parent_version() {
+        let base = PathBuf::from("SDGA_1.60.00_20251023171735_0.vhd");
+        let patch_1 = PathBuf::from("SDGA_1.61.00_20260309140300_1_1.60.00.vhd");
+        let patch_2 = PathBuf::from("SDGA_1.62.00_20260401120000_2_1.61.00.vhd");
+        let appdata = PathBuf::from("SDGA_AppData.vhd");
+
+        let selected_base = select_base_vhd(&[
+            patch_2.clone(),
+            appdata,
+            base.clone(),
+            patch_1.clone(),
+        ])
+        .unwrap();
+        assert_eq!(selected_base, base);
+
+        let ordered = order_patch_vhds(
+            &selected_base,
+            vec![patch_2.clone(), patch_1.clone()],
+        );
+        assert_eq!(ordered, vec![patch_1, patch_2]);
+    }
+
+    #[test]
+    fn finds_short_version_unpacked_zip_for_parent() {
+        let temp = TempDir::new().unwrap();
+        let parent = temp
+            .path()
+            .join("SDGA_1.62.00_20260401120000_2_1.61.00.vhd");
+        let overlay = temp.path().join("SDGA_1.62_Unpacked.zip");
+
+        std::fs::write(&parent, b"vhd").unwrap();
+        std::fs::write(&overlay, b"zip").unwrap();
+
+        let found = find_unpacked_zip_for_parent(&parent).unwrap();
+        assert_eq!(found, overlay);
+    }
+
+    #[test]
+    fn falls_back_to_older_unpacked_zip_in_chain() {
+        let temp = TempDir::new().unwrap();
+        let base = temp.path().join("SDGA_1.60.00_20251023171735_0.vhd");
+        let patch_1 = temp
+            .path()
+            .join("SDGA_1.61.00_20260309140300_1_1.60.00.vhd");
+        let patch_2 = temp
+            .path()
+            .join("SDGA_1.62.00_20260401120000_2_1.61.00.vhd");
+        let overlay = temp.path().join("SDGA_1.60_Unpacked.zip");
+
+        std::fs::write(&base, b"vhd").unwrap();
+        std::fs::write(&patch_1, b"vhd").unwrap();
+        std::fs::write(&patch_2, b"vhd").unwrap();
+        std::fs::write(&overlay, b"zip").unwrap();
+
+        let found = find_unpacked_zip_for_chain(&base, &[patch_1, patch_2]).unwrap();
+        assert_eq!(found, overlay);
+    }
+}
@@ -1,4 +1,5 @@
 use serde::Serialize;
+use crate::api_tokens::ApiAuthError;
 use crate::trusted::TrustedError;
 
 pub use configarc_core::error::*;
@@ -9,6 +10,31 @@ pub struct ApiError {
     pub message: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub details: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<Remediation>,
+}
+
+/// Machine-readable fix the frontend can offer as a button, e.g. `{ action: "deploy" }`.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Remediation {
+    pub action: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+}
+
+impl Remediation {
+    pub fn new(action: impl Into<String>) -> Self {
+        Self {
+            action: action.into(),
+            target: None,
+        }
+    }
+
+    pub fn with_target(mut self, target: impl Into<String>) -> Self {
+        self.target = Some(target.into());
+        self
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -28,6 +54,8 @@ pub enum ErrorCode {
     NoFilesSelected,
     NoFolderSelected,
     InvalidDirectory,
+    FeatureDisabled,
+    Busy,
 }
 
 impl ErrorCode {
@@ -48,6 +76,8 @@ impl ErrorCode {
             ErrorCode::NoFilesSelected => "NO_FILES_SELECTED",
             ErrorCode::NoFolderSelected => "NO_FOLDER_SELECTED",
             ErrorCode::InvalidDirectory => "INVALID_DIRECTORY",
+            ErrorCode::FeatureDisabled => "FEATURE_DISABLED",
+            ErrorCode::Busy => "BUSY",
         }
     }
 }
@@ -56,21 +86,31 @@ pub type ApiResult<T> = Result<T, ApiError>;
 
 impl ApiError {
     pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        let message = message.into();
         Self {
             code: code.as_str().to_string(),
-            message: message.into(),
+            remediation: infer_remediation(code, &message),
+            message,
             details: None,
         }
     }
 
     pub fn with_details(code: ErrorCode, message: impl Into<String>, details: impl Into<String>) -> Self {
+        let message = message.into();
         Self {
             code: code.as_str().to_string(),
-            message: message.into(),
+            remediation: infer_remediation(code, &message),
+            message,
             details: Some(details.into()),
         }
     }
 
+    /// Replaces the inferred hint with one supplied by the subsystem that knows better.
+    pub fn with_remediation(mut self, remediation: Remediation) -> Self {
+        self.remediation = Some(remediation);
+        self
+    }
+
     pub fn from_message(message: impl Into<String>) -> Self {
         let message = message.into();
         let code = infer_error_code(&message);
@@ -78,6 +118,44 @@ impl ApiError {
     }
 }
 
+fn infer_remediation(code: ErrorCode, message: &str) -> Option<Remediation> {
+    let lowered = message.to_lowercase();
+    if lowered.contains("offline mode is enabled") {
+        return Some(Remediation::new("disable_offline_mode"));
+    }
+    if let Some(letter) = drive_in_use(&lowered) {
+        return Some(Remediation::new("eject_drive").with_target(letter));
+    }
+    if lowered.contains("vhd not found") {
+        return Some(Remediation::new("edit_vhd_config"));
+    }
+    if lowered.contains("keychip id") {
+        return Some(Remediation::new("open_settings").with_target("keychip"));
+    }
+    if lowered.contains("missing required fields") {
+        return Some(Remediation::new("open_settings"));
+    }
+    match code {
+        ErrorCode::NoActiveGame => Some(Remediation::new("select_game")),
+        ErrorCode::SegatoolsMissing => Some(Remediation::new("deploy")),
+        ErrorCode::Verification => Some(Remediation::new("redeploy")),
+        ErrorCode::Network | ErrorCode::Busy => Some(Remediation::new("retry")),
+        _ => None,
+    }
+}
+
+fn drive_in_use(lowered: &str) -> Option<String> {
+    let idx = lowered.find("drive ")?;
+    let rest = &lowered[idx + "drive ".len()..];
+    let mut chars = rest.chars();
+    let letter = chars.next()?;
+    if letter.is_ascii_alphabetic() && chars.next() == Some(':') && rest.contains("already in use") {
+        Some(letter.to_ascii_uppercase().to_string())
+    } else {
+        None
+    }
+}
+
 fn infer_error_code(message: &str) -> ErrorCode {
     let lowered = message.to_lowercase();
     // Keep IRIS-compatible hinting for active game resolution failures.
@@ -151,8 +229,31 @@ impl From<ConfigError> for ApiError {
             ConfigError::Json(_) => ErrorCode::Json,
             ConfigError::NotFound(_) => ErrorCode::NotFound,
         };
+        let remediation = match &err {
+            ConfigError::NotFound(msg) if msg.contains("segatools.ini") => Some(Remediation::new("deploy")),
+            ConfigError::NotFound(msg) if msg.contains("vhd.json") => Some(Remediation::new("edit_vhd_config")),
+            ConfigError::Parse(_) => Some(Remediation::new("reset_config")),
+            _ => None,
+        };
         let message = err.to_string();
-        ApiError::with_details(code, message.clone(), message)
+        let api = ApiError::with_details(code, message.clone(), message);
+        match remediation {
+            Some(remediation) => api.with_remediation(remediation),
+            None => api,
+        }
+    }
+}
+
+impl From<ShareError> for ApiError {
+    fn from(err: ShareError) -> Self {
+        let code = match err {
+            ShareError::WeakPassphrase(_) | ShareError::Malformed | ShareError::Decrypt | ShareError::TooLarge(_) => {
+                ErrorCode::InvalidInput
+            }
+            ShareError::Crypto(_) => ErrorCode::Unexpected,
+            ShareError::Io(_) => ErrorCode::Io,
+        };
+        ApiError::new(code, err.to_string())
     }
 }
 
@@ -164,8 +265,17 @@ impl From<GameError> for ApiError {
             GameError::NotFound(_) => ErrorCode::NotFound,
             GameError::Launch(_) => ErrorCode::Unexpected,
         };
+        let remediation = match &err {
+            GameError::NotFound(_) => Some(Remediation::new("refresh_games")),
+            GameError::Launch(_) => Some(Remediation::new("edit_game")),
+            _ => None,
+        };
         let message = err.to_string();
-        ApiError::with_details(code, message.clone(), message)
+        let api = ApiError::with_details(code, message.clone(), message);
+        match remediation {
+            Some(remediation) => api.with_remediation(remediation),
+            None => api,
+        }
     }
 }
 
@@ -179,7 +289,26 @@ impl From<TrustedError> for ApiError {
             TrustedError::NotFound(_) => ErrorCode::NotFound,
             TrustedError::Zip(_) => ErrorCode::Zip,
         };
+        let remediation = match &err {
+            TrustedError::NotFound(_) => Some(Remediation::new("deploy")),
+            TrustedError::Verification(_) | TrustedError::Zip(_) => Some(Remediation::new("redeploy")),
+            _ => None,
+        };
         let message = err.to_string();
-        ApiError::with_details(code, message.clone(), message)
+        let api = ApiError::with_details(code, message.clone(), message);
+        match remediation {
+            Some(remediation) => api.with_remediation(remediation),
+            None => api,
+        }
+    }
+}
+
+impl From<ApiAuthError> for ApiError {
+    fn from(err: ApiAuthError) -> Self {
+        match err {
+            ApiAuthError::Store(inner) => ApiError::from(inner),
+            ApiAuthError::Forbidden { .. } => ApiError::new(ErrorCode::FeatureDisabled, err.to_string()),
+            ApiAuthError::Missing | ApiAuthError::Unknown => ApiError::new(ErrorCode::InvalidInput, err.to_string()),
+        }
     }
 }
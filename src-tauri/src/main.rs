@@ -1,47 +1,145 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod api_tokens;
+mod archive;
+mod backup;
+mod cancel;
+mod changelog;
 mod commands;
+mod compression;
 mod config;
+mod display;
 mod error;
+#[cfg(feature = "fsdecrypt")]
 mod fsdecrypt;
 mod games;
+mod gpu;
+#[cfg(feature = "icf")]
 mod icf;
+mod notifications;
+mod platform;
+mod presets;
+#[cfg(feature = "privexec")]
 mod privexec;
 mod remote;
+mod scrub;
+mod serial;
 mod trusted;
 mod vhd;
 
 use commands::*;
 fn main() {
+    #[cfg(feature = "privexec")]
+    if let Some(options) = privexec::broker::BrokerOptions::from_args(std::env::args().skip(1)) {
+        // Elevated broker instance started by a launcher; it has no window of its own.
+        if let Err(err) = privexec::broker::run_broker(options) {
+            eprintln!("[privexec] broker failed: {}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+    let cli_launch = CliLaunch::from_args(std::env::args().skip(1));
     tauri::Builder::default()
+        .manage(AppState::default())
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
-        .invoke_handler(tauri::generate_handler![
+        .invoke_handler(read_only_gate(tauri::generate_handler![
             get_segatoools_config,
             get_game_dir_segatoools_config,
+            test_network_cmd,
+            enumerate_serial_ports_cmd,
+            list_key_names_cmd,
+            capture_key_cmd,
+            check_key_bindings_cmd,
             save_segatoools_config,
+            get_segatoools_provenance_cmd,
+            reset_segatoools_key_cmd,
             export_segatoools_config_cmd,
             import_segatoools_config_cmd,
             get_offline_mode_cmd,
             set_offline_mode_cmd,
+            get_read_only_mode_cmd,
+            get_platform_capabilities_cmd,
+            get_enabled_features_cmd,
+            broker_status_cmd,
+            set_resident_broker_cmd,
+            set_read_only_mode_cmd,
+            list_api_tokens_cmd,
+            generate_api_token_cmd,
+            revoke_api_token_cmd,
+            check_api_token_cmd,
             get_local_override_cmd,
             set_local_override_cmd,
             get_effective_remote_config_cmd,
+            get_remote_directives_cmd,
             sync_remote_config_cmd,
             apply_remote_config_cmd,
+            list_presets_cmd,
+            install_preset_cmd,
+            list_network_presets_cmd,
+            apply_network_preset_cmd,
             export_profile_cmd,
             import_profile_cmd,
+            export_profile_share_cmd,
+            import_profile_share_cmd,
+            export_qr_backup_cmd,
+            parse_qr_backup_cmd,
+            import_qr_backup_cmd,
             list_profiles_cmd,
             load_profile_cmd,
             save_profile_cmd,
             delete_profile_cmd,
+            list_profile_assets_cmd,
+            add_profile_asset_cmd,
+            delete_profile_asset_cmd,
+            load_profile_notes_cmd,
+            save_profile_notes_cmd,
             list_games_cmd,
+            list_detection_rules_cmd,
+            get_last_gpu_usage_cmd,
+            list_monitors_cmd,
+            apply_display_preset_cmd,
             save_game_cmd,
             load_vhd_config_cmd,
             save_vhd_config_cmd,
+            list_delta_snapshots_cmd,
+            restore_delta_snapshot_cmd,
+            rename_delta_snapshot_cmd,
+            delete_delta_snapshot_cmd,
+            export_decrypted_vhd_cmd,
             delete_game_cmd,
+            cleanup_game_cmd,
+            cleanup_game_caches_cmd,
+            get_game_disk_usage_cmd,
+            get_game_timeline_cmd,
             launch_game_cmd,
+            dry_run_launch_cmd,
+            get_last_failure_report_cmd,
+            get_launch_queue_cmd,
+            enqueue_launch_cmd,
+            clear_launch_queue_cmd,
+            start_launch_queue_cmd,
+            stop_launch_queue_cmd,
+            run_integrity_scrub_cmd,
+            get_integrity_report_cmd,
+            start_vhd_warmup_cmd,
+            cancel_vhd_warmup_cmd,
+            plan_vhd_migration_cmd,
+            migrate_game_to_vhd_cmd,
+            start_appdata_backup_cmd,
+            cancel_appdata_backup_cmd,
+            list_appdata_backups_cmd,
+            restore_appdata_backup_cmd,
+            prune_appdata_backups_cmd,
+            list_notifications_cmd,
+            mark_notifications_read_cmd,
+            post_notification_cmd,
             apply_profile_to_game_cmd,
+            apply_profile_sections_cmd,
+            diff_segatoools_configs_cmd,
+            list_config_slots_cmd,
+            add_config_slot_cmd,
+            delete_config_slot_cmd,
             pick_game_folder_cmd,
             pick_game_auto_cmd,
             pick_vhd_game_cmd,
@@ -52,26 +150,67 @@ fn main() {
             get_data_paths_cmd,
             get_active_game_cmd,
             scan_game_vfs_folders_cmd,
+            cancel_vfs_scan_cmd,
+            detect_vfs_drift_cmd,
+            apply_vfs_drift_fix_cmd,
             set_active_game_cmd,
             list_json_configs_cmd,
             load_json_config_cmd,
             save_json_config_cmd,
+            validate_json_schema_cmd,
+            validate_json_config_cmd,
+            load_amdaemon_config_cmd,
+            save_amdaemon_config_cmd,
             load_icf_cmd,
+            icf_summary_cmd,
+            icf_audit_cmd,
+            propose_icf_options_cmd,
+            apply_icf_options_cmd,
+            export_icf_json_cmd,
+            import_icf_json_cmd,
+            list_icf_backups_cmd,
+            restore_icf_backup_cmd,
+            list_icf_keys_cmd,
+            set_icf_key_cmd,
+            delete_icf_key_cmd,
+            import_icf_keys_cmd,
+            export_icf_keys_cmd,
             save_icf_cmd,
             list_option_files_cmd,
+            set_option_compression_cmd,
+            delete_option_cmd,
             get_mods_status_cmd,
             list_aimes_cmd,
+            migrate_legacy_aimes_cmd,
+            generate_aime_cmd,
             save_aime_cmd,
             update_aime_cmd,
             delete_aime_cmd,
             apply_aime_to_active_cmd,
             get_active_aime_cmd,
             store_io_dll_cmd,
+            list_io_dlls_cmd,
+            add_io_dll_cmd,
+            activate_io_dll_cmd,
+            delete_io_dll_cmd,
             load_changelog_cmd,
+            load_structured_changelog_cmd,
+            list_operations_cmd,
+            cancel_operation_cmd,
             add_mods_cmd,
             delete_mod_cmd,
+            undo_last_delete_cmd,
+            check_mod_conflicts_cmd,
             load_fsdecrypt_keys_cmd,
+            get_fsdecrypt_key_store_cmd,
+            import_fsdecrypt_keys_cmd,
+            set_fsdecrypt_game_key_cmd,
+            delete_fsdecrypt_game_key_cmd,
+            clear_fsdecrypt_key_store_cmd,
             decrypt_game_files_cmd,
+            inspect_container_cmd,
+            build_option_container_cmd,
+            verify_decrypted_output_cmd,
             download_order_cmd,
             download_order_fetch_text_cmd,
             download_order_download_files_cmd,
@@ -81,10 +220,22 @@ fn main() {
             rollback_segatoools_cmd,
             privexec_get_paths_cmd,
             privexec_execute_cmd,
-            privexec_apply_policy_update_cmd
-        ])
-        .setup(|app| {
-            app.handle();
+            privexec_execute_batch_cmd,
+            list_hosts_overrides_cmd,
+            add_hosts_override_cmd,
+            delete_hosts_override_cmd,
+            privexec_apply_policy_update_cmd,
+            privexec_bootstrap_roots_cmd,
+            privexec_bootstrap_policy_cmd
+        ]))
+        .setup(move |app| {
+            init_read_only_mode(app.handle());
+            recover_stale_vhd_mounts(app.handle().clone());
+            start_broker_supervisor(app.handle().clone());
+            start_integrity_scrubber(app.handle().clone());
+            if let Some(launch) = cli_launch {
+                start_cli_launch(app.handle(), launch);
+            }
             Ok(())
         })
         .run(tauri::generate_context!())
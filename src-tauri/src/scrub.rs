@@ -0,0 +1 @@
+pub use configarc_core::scrub::*;
@@ -0,0 +1,7 @@
+import { invokeTauri } from './tauriClient';
+import { ApiScope, ApiToken, IssuedApiToken } from '../types/apiTokens';
+
+export const listApiTokens = () => invokeTauri<ApiToken[]>('list_api_tokens_cmd');
+export const generateApiToken = (name: string, scope: ApiScope) =>
+  invokeTauri<IssuedApiToken>('generate_api_token_cmd', { name, scope });
+export const revokeApiToken = (id: string) => invokeTauri<void>('revoke_api_token_cmd', { id });
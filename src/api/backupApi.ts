@@ -0,0 +1,13 @@
+import { invokeTauri } from './tauriClient';
+import { AppdataBackupSummary, AppdataPruneReport, AppdataRestoreReport } from '../types/backup';
+
+// Runs in the background; listen for `appdata-backup-progress` and `appdata-backup-finished`.
+export const startAppdataBackup = (gameId: string, operationId?: string) =>
+  invokeTauri<string>('start_appdata_backup_cmd', { gameId, operationId });
+export const cancelAppdataBackup = () => invokeTauri<void>('cancel_appdata_backup_cmd');
+export const listAppdataBackups = (gameId: string) =>
+  invokeTauri<AppdataBackupSummary[]>('list_appdata_backups_cmd', { gameId });
+export const restoreAppdataBackup = (gameId: string, backupId: string) =>
+  invokeTauri<AppdataRestoreReport>('restore_appdata_backup_cmd', { gameId, backupId });
+export const pruneAppdataBackups = (gameId: string, keepLast: number) =>
+  invokeTauri<AppdataPruneReport>('prune_appdata_backups_cmd', { gameId, keepLast });
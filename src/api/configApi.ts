@@ -1,28 +1,106 @@
 import { invokeTauri } from './tauriClient';
-import { SegatoolsConfig } from '../types/config';
-import { ConfigProfile } from '../types/games';
+import {
+  ConfigChange,
+  ConfigSlot,
+  EndpointProbe,
+  IoDll,
+  IoDllEntry,
+  IoDllKind,
+  KeyConflict,
+  KeyName,
+  KeyProvenance,
+  QrBackup,
+  SegatoolsConfig,
+  SerialPort,
+} from '../types/config';
+import { ConfigProfile, ProfileAsset } from '../types/games';
 
-export const loadSegatoolsConfig = () => invokeTauri<SegatoolsConfig>('get_segatoools_config');
+export const loadSegatoolsConfig = (slot?: string) => invokeTauri<SegatoolsConfig>('get_segatoools_config', { slot });
 export const loadGameDirSegatoolsConfig = () =>
   invokeTauri<SegatoolsConfig>('get_game_dir_segatoools_config');
-export const saveSegatoolsConfig = (config: SegatoolsConfig) => invokeTauri<void>('save_segatoools_config', { config });
+export const testNetwork = (slot?: string, timeoutMs?: number) =>
+  invokeTauri<EndpointProbe[]>('test_network_cmd', { slot, timeoutMs });
+export const enumerateSerialPorts = () => invokeTauri<SerialPort[]>('enumerate_serial_ports_cmd');
+export const listKeyNames = () => invokeTauri<KeyName[]>('list_key_names_cmd');
+export const captureKey = (timeoutMs?: number) => invokeTauri<KeyName | null>('capture_key_cmd', { timeoutMs });
+export const checkKeyBindings = (config?: SegatoolsConfig, slot?: string) =>
+  invokeTauri<KeyConflict[]>('check_key_bindings_cmd', { config, slot });
+export const saveSegatoolsConfig = (config: SegatoolsConfig, slot?: string) =>
+  invokeTauri<void>('save_segatoools_config', { config, slot });
 export const loadDefaultSegatoolsConfig = () => invokeTauri<SegatoolsConfig>('default_segatoools_config_cmd');
+export const getSegatoolsProvenance = () => invokeTauri<KeyProvenance[]>('get_segatoools_provenance_cmd');
+export const resetSegatoolsKey = (section: string, key: string) =>
+  invokeTauri<SegatoolsConfig>('reset_segatoools_key_cmd', { section, key });
+export const diffSegatoolsConfigs = (old: SegatoolsConfig, next: SegatoolsConfig) =>
+  invokeTauri<ConfigChange[]>('diff_segatoools_configs_cmd', { old, new: next });
+export const previewProfileApply = (profileId: string, gameId?: string, slot?: string) =>
+  invokeTauri<ConfigChange[]>('diff_segatoools_configs_cmd', { profileId, gameId, slot });
+export const listConfigSlots = (gameId?: string) => invokeTauri<ConfigSlot[]>('list_config_slots_cmd', { gameId });
+export const addConfigSlot = (name: string, from?: string) =>
+  invokeTauri<ConfigSlot>('add_config_slot_cmd', { name, from });
+export const deleteConfigSlot = (name: string) => invokeTauri<void>('delete_config_slot_cmd', { name });
 export const listProfiles = (gameId?: string) => invokeTauri<ConfigProfile[]>('list_profiles_cmd', { gameId });
 export const loadProfile = (id: string) => invokeTauri<ConfigProfile>('load_profile_cmd', { id });
 export const saveProfile = (profile: ConfigProfile) => invokeTauri<void>('save_profile_cmd', { profile });
 export const deleteProfile = (id: string) => invokeTauri<void>('delete_profile_cmd', { id });
+export const listProfileAssets = (profileId: string, gameId?: string) =>
+  invokeTauri<ProfileAsset[]>('list_profile_assets_cmd', { profileId, gameId });
+export const addProfileAsset = (profileId: string, target: string, source?: string) =>
+  invokeTauri<ProfileAsset>('add_profile_asset_cmd', { profileId, target, source });
+export const deleteProfileAsset = (profileId: string, target: string) =>
+  invokeTauri<void>('delete_profile_asset_cmd', { profileId, target });
+export const loadProfileNotes = (profileId: string) => invokeTauri<string>('load_profile_notes_cmd', { profileId });
+export const saveProfileNotes = (profileId: string, notes: string) =>
+  invokeTauri<void>('save_profile_notes_cmd', { profileId, notes });
 export const getSegatoolsPath = () => invokeTauri<string>('segatoools_path_cmd');
 export const openSegatoolsFolder = () => invokeTauri<void>('open_segatoools_folder_cmd');
-export const exportSegatoolsConfig = () => invokeTauri<string>('export_segatoools_config_cmd');
+export const exportSegatoolsConfig = (slot?: string) => invokeTauri<string>('export_segatoools_config_cmd', { slot });
 export const importSegatoolsConfig = (content: string) => invokeTauri<SegatoolsConfig>('import_segatoools_config_cmd', { content });
 export const exportProfile = (profileId?: string) => invokeTauri<string>('export_profile_cmd', { profileId });
 export const importProfile = (content: string) => invokeTauri<ConfigProfile>('import_profile_cmd', { content });
+export const exportProfileShare = (passphrase: string, profileId?: string) =>
+  invokeTauri<string>('export_profile_share_cmd', { profileId, passphrase });
+export const importProfileShare = (share: string, passphrase: string) =>
+  invokeTauri<ConfigProfile>('import_profile_share_cmd', { share, passphrase });
+export const exportQrBackup = (slot?: string) => invokeTauri<string>('export_qr_backup_cmd', { slot });
+export const parseQrBackup = (payload: string) => invokeTauri<QrBackup>('parse_qr_backup_cmd', { payload });
+export const importQrBackup = (payload: string, slot?: string) =>
+  invokeTauri<SegatoolsConfig>('import_qr_backup_cmd', { payload, slot });
 export const storeIoDll = (path: string) => invokeTauri<string>('store_io_dll_cmd', { path });
 
+export const listIoDlls = (slot?: string) => invokeTauri<IoDllEntry[]>('list_io_dlls_cmd', { slot });
+
+export const addIoDll = (path: string, kind: IoDllKind, label?: string) =>
+  invokeTauri<IoDll>('add_io_dll_cmd', { path, kind, label });
+
+export const activateIoDll = (id: string, slot?: string) =>
+  invokeTauri<SegatoolsConfig>('activate_io_dll_cmd', { id, slot });
+
+export const deleteIoDll = (id: string, slot?: string) => invokeTauri<void>('delete_io_dll_cmd', { id, slot });
+
 export interface VfsScanResult {
   amfs?: string;
   appdata?: string;
   option?: string;
 }
 
-export const scanGameVfsFolders = () => invokeTauri<VfsScanResult>('scan_game_vfs_folders_cmd');
+export const scanGameVfsFolders = (maxDepth?: number, operationId?: string) =>
+  invokeTauri<VfsScanResult>('scan_game_vfs_folders_cmd', { maxDepth, operationId });
+export const cancelVfsScan = () => invokeTauri<void>('cancel_vfs_scan_cmd');
+
+export interface VfsDrift {
+  field: 'amfs' | 'appdata' | 'option';
+  configured: string;
+  reason: 'unset' | 'missing' | 'empty';
+  candidate?: string;
+}
+
+export interface VfsDriftReport {
+  drifts: VfsDrift[];
+  changes: ConfigChange[];
+}
+
+export const detectVfsDrift = (maxDepth?: number) =>
+  invokeTauri<VfsDriftReport>('detect_vfs_drift_cmd', { maxDepth });
+export const applyVfsDriftFix = (maxDepth?: number) =>
+  invokeTauri<VfsDriftReport>('apply_vfs_drift_fix_cmd', { maxDepth });
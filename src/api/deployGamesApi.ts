@@ -1,16 +1,75 @@
 import { invokeTauri } from './tauriClient';
-import { DecryptSummary, KeyStatus } from '../types/deployGames';
+import {
+  ContainerInfo,
+  DecryptSummary,
+  KeyStatus,
+  KeyStoreStatus,
+  OptionBuildResult,
+  OutputVerification,
+  OverwritePolicy,
+} from '../types/deployGames';
 
 export const pickDecryptFiles = () => invokeTauri<string[]>('pick_decrypt_files_cmd');
 
-export const decryptGameFiles = (files: string[], noExtract: boolean, keyUrl?: string) =>
+export const decryptGameFiles = (
+  files: string[],
+  noExtract: boolean,
+  keyUrl?: string,
+  workers?: number,
+  extractTree?: boolean,
+  importGame?: boolean,
+  outputDir?: string,
+  overwrite?: OverwritePolicy,
+  nameTemplate?: string,
+  operationId?: string,
+) =>
   invokeTauri<DecryptSummary>('decrypt_game_files_cmd', {
     files,
     noExtract,
+    extractTree,
     keyUrl,
+    workers,
+    importGame,
+    outputDir,
+    overwrite,
+    nameTemplate,
+    operationId,
   });
 
 export const loadDecryptKeys = (keyUrl?: string) =>
   invokeTauri<KeyStatus>('load_fsdecrypt_keys_cmd', {
     keyUrl,
   });
+
+export const getKeyStore = () => invokeTauri<KeyStoreStatus>('get_fsdecrypt_key_store_cmd');
+
+export const importKeys = (source: string) => invokeTauri<KeyStoreStatus>('import_fsdecrypt_keys_cmd', { source });
+
+export const setGameKey = (gameId: string, key: string, iv?: string) =>
+  invokeTauri<KeyStoreStatus>('set_fsdecrypt_game_key_cmd', {
+    gameId,
+    key,
+    iv,
+  });
+
+export const deleteGameKey = (gameId: string) =>
+  invokeTauri<KeyStoreStatus>('delete_fsdecrypt_game_key_cmd', { gameId });
+
+export const clearKeyStore = () => invokeTauri<KeyStoreStatus>('clear_fsdecrypt_key_store_cmd');
+
+export const inspectContainers = (files: string[], keyUrl?: string) =>
+  invokeTauri<ContainerInfo[]>('inspect_container_cmd', {
+    files,
+    keyUrl,
+  });
+
+export const buildOptionContainer = (folder: string, gameId: string, outputDir?: string, keyUrl?: string) =>
+  invokeTauri<OptionBuildResult>('build_option_container_cmd', {
+    folder,
+    gameId,
+    outputDir,
+    keyUrl,
+  });
+
+export const verifyDecryptedOutput = (output: string) =>
+  invokeTauri<OutputVerification>('verify_decrypted_output_cmd', { output });
@@ -1,13 +1,39 @@
 import { invokeTauri } from './tauriClient';
-import { Game } from '../types/games';
+import { CacheReport, CleanupOptions, CleanupReport, CreditMode, DetectionRule, DiskUsageReport, FailureReport, Game, JournalEntry, LastGpuUsage, LaunchPlan, LaunchQueueItem, LaunchQueueStatus, MonitorInfo } from '../types/games';
 import { AutoDetectResult } from '../types/vhd';
 
 export const listGames = () => invokeTauri<Game[]>('list_games_cmd');
+export const listDetectionRules = () => invokeTauri<DetectionRule[]>('list_detection_rules_cmd');
+export const getLastGpuUsage = (gameId: string) =>
+  invokeTauri<LastGpuUsage | null>('get_last_gpu_usage_cmd', { gameId });
+export const listMonitors = () => invokeTauri<MonitorInfo[]>('list_monitors_cmd');
+export const applyDisplayPreset = (name: string, slot?: string) =>
+  invokeTauri<Game>('apply_display_preset_cmd', { name, slot });
 export const saveGame = (game: Game) => invokeTauri<void>('save_game_cmd', { game });
 export const deleteGame = (id: string) => invokeTauri<void>('delete_game_cmd', { id });
-export const launchGame = (id: string, profileId?: string) => invokeTauri<void>('launch_game_cmd', { id, profileId });
+export const cleanupGame = (gameId: string, options: CleanupOptions, confirm: boolean) =>
+  invokeTauri<CleanupReport>('cleanup_game_cmd', { gameId, options, confirm });
+export const cleanupGameCaches = (confirm: boolean, gameId?: string) =>
+  invokeTauri<CacheReport>('cleanup_game_caches_cmd', { gameId, confirm });
+export const getGameDiskUsage = (gameId: string, refresh?: boolean) =>
+  invokeTauri<DiskUsageReport>('get_game_disk_usage_cmd', { gameId, refresh });
+export const launchGame = (id: string, profileId?: string, creditMode?: CreditMode) =>
+  invokeTauri<void>('launch_game_cmd', { id, profileId, creditMode });
+export const dryRunLaunch = (id: string, profileId?: string, creditMode?: CreditMode) =>
+  invokeTauri<LaunchPlan>('dry_run_launch_cmd', { id, profileId, creditMode });
+export const getLaunchQueue = () => invokeTauri<LaunchQueueStatus>('get_launch_queue_cmd');
+export const enqueueLaunch = (items: LaunchQueueItem[]) =>
+  invokeTauri<LaunchQueueStatus>('enqueue_launch_cmd', { items });
+export const clearLaunchQueue = () => invokeTauri<LaunchQueueStatus>('clear_launch_queue_cmd');
+export const startLaunchQueue = () => invokeTauri<LaunchQueueStatus>('start_launch_queue_cmd');
+export const stopLaunchQueue = () => invokeTauri<LaunchQueueStatus>('stop_launch_queue_cmd');
+export const getLastFailureReport = () => invokeTauri<FailureReport | null>('get_last_failure_report_cmd');
+export const getGameTimeline = (gameId: string, limit?: number) =>
+  invokeTauri<JournalEntry[]>('get_game_timeline_cmd', { gameId, limit });
 export const getActiveGame = () => invokeTauri<string | null>('get_active_game_cmd');
 export const setActiveGame = (id: string, profileId?: string) => invokeTauri<void>('set_active_game_cmd', { id, profileId });
 export const applyProfileToGame = (gameId: string, profileId: string) =>
   invokeTauri<void>('apply_profile_to_game_cmd', { gameId, profileId });
+export const applyProfileSections = (gameId: string, profileId: string, sections: string[], slot?: string) =>
+  invokeTauri<string[]>('apply_profile_sections_cmd', { gameId, profileId, sections, slot });
 export const pickAutoGame = () => invokeTauri<AutoDetectResult>('pick_game_auto_cmd');
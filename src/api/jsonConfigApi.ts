@@ -1,7 +1,14 @@
 import { invokeTauri } from './tauriClient';
-import { JsonConfigData, JsonConfigFileEntry } from '../types/jsonConfig';
+import { AmdaemonConfig, JsonConfigData, JsonConfigFileEntry, JsonConfigIssue, JsonSchemaError } from '../types/jsonConfig';
 
 export const listJsonConfigs = () => invokeTauri<JsonConfigFileEntry[]>('list_json_configs_cmd');
 export const loadJsonConfig = (name: string) => invokeTauri<JsonConfigData>('load_json_config_cmd', { name });
 export const saveJsonConfig = (name: string, content: JsonConfigData) =>
   invokeTauri<void>('save_json_config_cmd', { name, content });
+export const validateJsonSchema = (name: string, content: JsonConfigData) =>
+  invokeTauri<JsonSchemaError[]>('validate_json_schema_cmd', { name, content });
+export const validateJsonConfig = (name: string, content: JsonConfigData) =>
+  invokeTauri<JsonConfigIssue[]>('validate_json_config_cmd', { name, content });
+export const loadAmdaemonConfig = (name: string) => invokeTauri<AmdaemonConfig>('load_amdaemon_config_cmd', { name });
+export const saveAmdaemonConfig = (name: string, config: AmdaemonConfig) =>
+  invokeTauri<void>('save_amdaemon_config_cmd', { name, config });
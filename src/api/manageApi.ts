@@ -1,18 +1,47 @@
 import { invokeTauri } from './tauriClient';
-import { DataPaths, IcfEntry, OptionEntry, ModEntry, ModsStatus, AimeEntry } from '../types/manage';
+import { DataPaths, IcfAudit, IcfBackup, IcfEntry, IcfSummary, OptionEntry, ModEntry, ModsStatus, ModConflictReport, AimeEntry, AimeMigrationReport, TrashEntry } from '../types/manage';
 
 export const getDataPaths = () => invokeTauri<DataPaths>('get_data_paths_cmd');
-export const loadIcf = (kind: string) => invokeTauri<IcfEntry[]>('load_icf_cmd', { kind });
-export const saveIcf = (kind: string, entries: IcfEntry[]) => invokeTauri<void>('save_icf_cmd', { kind, entries });
+// `key`/`iv` (hex) are only needed for ICFs sealed under a key the keyring does not hold.
+export const loadIcf = (kind: string, key?: { key: string; iv: string }) =>
+  invokeTauri<IcfEntry[]>('load_icf_cmd', { kind, ...key });
+export const getIcfSummary = (kind: string) => invokeTauri<IcfSummary>('icf_summary_cmd', { kind });
+export const saveIcf = (kind: string, entries: IcfEntry[], key?: { key: string; iv: string }) =>
+  invokeTauri<void>('save_icf_cmd', { kind, entries, ...key });
+export const auditIcf = () => invokeTauri<IcfAudit>('icf_audit_cmd');
+export const proposeIcfOptions = () => invokeTauri<IcfEntry[]>('propose_icf_options_cmd');
+export const applyIcfOptions = (options: string[]) => invokeTauri<IcfEntry[]>('apply_icf_options_cmd', { options });
+export const exportIcfJson = (kind: string) => invokeTauri<string>('export_icf_json_cmd', { kind });
+export const importIcfJson = (kind: string, content: string) =>
+  invokeTauri<IcfEntry[]>('import_icf_json_cmd', { kind, content });
+export const listIcfBackups = (kind?: string) => invokeTauri<IcfBackup[]>('list_icf_backups_cmd', { kind });
+export const restoreIcfBackup = (fileName: string) =>
+  invokeTauri<IcfBackup>('restore_icf_backup_cmd', { fileName });
+export const listIcfKeys = () => invokeTauri<string[]>('list_icf_keys_cmd');
+export const setIcfKey = (platformId: string, key: string, iv: string) =>
+  invokeTauri<string[]>('set_icf_key_cmd', { platformId, key, iv });
+export const deleteIcfKey = (platformId: string) => invokeTauri<string[]>('delete_icf_key_cmd', { platformId });
+export const importIcfKeys = (path: string, passphrase?: string) =>
+  invokeTauri<string[]>('import_icf_keys_cmd', { path, passphrase });
+export const exportIcfKeys = (path: string, passphrase: string) =>
+  invokeTauri<number>('export_icf_keys_cmd', { path, passphrase });
 export const listOptionFiles = () => invokeTauri<OptionEntry[]>('list_option_files_cmd');
+export const setOptionCompression = (name: string, enabled: boolean) =>
+  invokeTauri<OptionEntry>('set_option_compression_cmd', { name, enabled });
+export const deleteOption = (name: string) => invokeTauri<OptionEntry[]>('delete_option_cmd', { name });
 
 export const getModsStatus = () => invokeTauri<ModsStatus>('get_mods_status_cmd');
 export const addMods = (paths: string[]) => invokeTauri<ModEntry[]>('add_mods_cmd', { paths });
 export const deleteMod = (name: string) => invokeTauri<ModEntry[]>('delete_mod_cmd', { name });
+export const undoLastDelete = () => invokeTauri<TrashEntry>('undo_last_delete_cmd');
+export const checkModConflicts = () => invokeTauri<ModConflictReport>('check_mod_conflicts_cmd');
 
 export const listAimes = () => invokeTauri<AimeEntry[]>('list_aimes_cmd');
+export const generateAime = () => invokeTauri<string>('generate_aime_cmd');
 export const saveAime = (name: string, number: string) => invokeTauri<AimeEntry>('save_aime_cmd', { name, number });
 export const updateAime = (id: string, name: string, number: string) => invokeTauri<AimeEntry>('update_aime_cmd', { id, name, number });
 export const deleteAime = (id: string) => invokeTauri<void>('delete_aime_cmd', { id });
 export const applyAimeToActive = (id: string) => invokeTauri<void>('apply_aime_to_active_cmd', { id });
 export const getActiveAime = () => invokeTauri<string | null>('get_active_aime_cmd');
+export const migrateLegacyAimes = (paths?: string[]) =>
+  invokeTauri<AimeMigrationReport>('migrate_legacy_aimes_cmd', { paths });
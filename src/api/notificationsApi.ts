@@ -0,0 +1,15 @@
+import { invokeTauri } from './tauriClient';
+import { AppNotification, NotificationSeverity } from '../types/notifications';
+
+export const listNotifications = (unreadOnly = false) =>
+  invokeTauri<AppNotification[]>('list_notifications_cmd', { unreadOnly });
+// Omitting `ids` marks every notification read.
+export const markNotificationsRead = (ids?: string[]) =>
+  invokeTauri<number>('mark_notifications_read_cmd', { ids });
+export const postNotification = (
+  source: string,
+  severity: NotificationSeverity,
+  title: string,
+  message: string,
+  dedupeKey?: string
+) => invokeTauri<AppNotification>('post_notification_cmd', { source, severity, title, message, dedupeKey });
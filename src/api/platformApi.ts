@@ -0,0 +1,13 @@
+import { invokeTauri } from './tauriClient';
+import { BuildFeature, OperationInfo, PlatformCapabilities } from '../types/platform';
+
+// Probes PowerShell on first call, so it can take a moment.
+export const getPlatformCapabilities = () => invokeTauri<PlatformCapabilities>('get_platform_capabilities_cmd');
+
+export const getEnabledFeatures = () => invokeTauri<BuildFeature[]>('get_enabled_features_cmd');
+
+export const listOperations = () => invokeTauri<OperationInfo[]>('list_operations_cmd');
+
+// Resolves to false when the operation already finished.
+export const cancelOperation = (operationId: string) =>
+  invokeTauri<boolean>('cancel_operation_cmd', { operationId });
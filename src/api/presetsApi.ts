@@ -0,0 +1,69 @@
+import { invokeTauri } from './tauriClient';
+import { ConfigProfile } from '../types/games';
+import { SegatoolsConfig } from '../types/config';
+
+export type PresetKind = 'profile' | 'network';
+
+export interface PresetEntry {
+  id: string;
+  game: string;
+  kind: PresetKind;
+  name: string;
+  description?: string | null;
+  author?: string | null;
+  tags: string[];
+  segatools: SegatoolsConfig;
+}
+
+export interface PresetBrowseResult {
+  fetchedAt?: string | null;
+  entries: PresetEntry[];
+  error?: string | null;
+}
+
+export type NetworkPresetSource = 'bundled' | 'remote';
+
+export interface NetworkPresetDns {
+  default: string;
+  title: string;
+  router: string;
+  startup: string;
+  billing: string;
+  aimedb: string;
+  replaceHost: boolean;
+  startupPort: number;
+  billingPort: number;
+  aimedbPort: number;
+}
+
+export interface NetworkPresetBilling {
+  billingCa?: string | null;
+  billingPub?: string | null;
+  billingType?: number | null;
+}
+
+export interface NetworkPreset {
+  id: string;
+  name: string;
+  description?: string | null;
+  homepage?: string | null;
+  games: string[];
+  dns: NetworkPresetDns;
+  billing: NetworkPresetBilling;
+  source: NetworkPresetSource;
+}
+
+export interface NetworkPresetBrowseResult {
+  fetchedAt?: string | null;
+  presets: NetworkPreset[];
+  error?: string | null;
+}
+
+export const listPresets = (game?: string, refresh = false) =>
+  invokeTauri<PresetBrowseResult>('list_presets_cmd', { game, refresh });
+export const installPreset = (presetId: string, gameId: string) =>
+  invokeTauri<ConfigProfile>('install_preset_cmd', { presetId, gameId });
+export const listNetworkPresets = (game?: string, refresh = false) =>
+  invokeTauri<NetworkPresetBrowseResult>('list_network_presets_cmd', { game, refresh });
+export const applyNetworkPreset = (presetId: string, slot?: string) =>
+  invokeTauri<SegatoolsConfig>('apply_network_preset_cmd', { presetId, slot });
@@ -1,5 +1,10 @@
 import { invokeTauri } from './tauriClient';
 import {
+  BrokerStatus,
+  HostsEntry,
+  PrivExecBootstrapReport,
+  PrivExecBootstrapRoots,
+  PrivExecBatchResponse,
   PrivExecCommandResponse,
   PrivExecPaths,
   PrivExecPolicyUpdateResponse,
@@ -20,6 +25,17 @@ export const executePrivExecRequest = (
     bootstrapPublicKeys: options?.bootstrapPublicKeys,
   });
 
+export const executePrivExecBatch = (
+  requestJson: string,
+  options?: PrivExecRuntimeOptions,
+) =>
+  invokeTauri<PrivExecBatchResponse>('privexec_execute_batch_cmd', {
+    requestJson,
+    rootDir: options?.rootDir,
+    deviceId: options?.deviceId,
+    bootstrapPublicKeys: options?.bootstrapPublicKeys,
+  });
+
 export const applyPrivExecPolicyUpdate = (
   updateJson: string,
   options?: PrivExecRuntimeOptions,
@@ -30,3 +46,29 @@ export const applyPrivExecPolicyUpdate = (
     deviceId: options?.deviceId,
     bootstrapPublicKeys: options?.bootstrapPublicKeys,
   });
+
+export const getPrivExecBootstrapRoots = () =>
+  invokeTauri<PrivExecBootstrapRoots>('privexec_bootstrap_roots_cmd');
+
+export const bootstrapPrivExecPolicy = (
+  options?: Pick<PrivExecRuntimeOptions, 'rootDir' | 'deviceId'> & { keyId?: string; roots?: PrivExecBootstrapRoots },
+) =>
+  invokeTauri<PrivExecBootstrapReport>('privexec_bootstrap_policy_cmd', {
+    rootDir: options?.rootDir,
+    deviceId: options?.deviceId,
+    keyId: options?.keyId,
+    roots: options?.roots,
+  });
+
+export const getBrokerStatus = () => invokeTauri<BrokerStatus>('broker_status_cmd');
+
+export const setResidentBroker = (enabled: boolean) =>
+  invokeTauri<BrokerStatus>('set_resident_broker_cmd', { enabled });
+
+export const listHostsOverrides = () => invokeTauri<HostsEntry[]>('list_hosts_overrides_cmd');
+
+export const addHostsOverride = (host: string, address: string, gameId?: string) =>
+  invokeTauri<HostsEntry[]>('add_hosts_override_cmd', { host, address, gameId });
+
+export const deleteHostsOverride = (host: string, gameId?: string) =>
+  invokeTauri<HostsEntry[]>('delete_hosts_override_cmd', { host, gameId });
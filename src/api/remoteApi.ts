@@ -16,6 +16,20 @@ export interface RemoteApplyResult {
   warnings: string[];
 }
 
+// Shape of the `defaults` object in the effective remote config.
+export interface RemoteDefaults {
+  fsdecryptKeyUrl?: string | null;
+  segatoolsMirrors?: string[];
+}
+
+// Maintainer-signed kill switch from the remote document.
+export interface RemoteDirectives {
+  disabledFeatures: string[];
+  minVersion?: string | null;
+  message?: string | null;
+  expiresAt?: string | null;
+}
+
 export const getLocalOverride = () => invokeTauri<Record<string, unknown>>('get_local_override_cmd');
 export const setLocalOverride = (overrideJson: Record<string, unknown>) =>
   invokeTauri<void>('set_local_override_cmd', { overrideJson });
@@ -23,3 +37,4 @@ export const getEffectiveRemoteConfig = () => invokeTauri<Record<string, unknown
 export const syncRemoteConfig = (endpoint?: string) =>
   invokeTauri<RemoteSyncStatus>('sync_remote_config_cmd', { endpoint });
 export const applyRemoteConfig = () => invokeTauri<RemoteApplyResult>('apply_remote_config_cmd');
+export const getRemoteDirectives = () => invokeTauri<RemoteDirectives>('get_remote_directives_cmd');
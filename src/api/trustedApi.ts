@@ -1,6 +1,9 @@
 import { invokeTauri } from './tauriClient';
-import { DeployResult, RollbackResult, SegatoolsTrustStatus } from '../types/trusted';
+import { DeployResult, RollbackResult, ScrubReport, SegatoolsTrustStatus } from '../types/trusted';
 
 export const fetchTrustStatus = () => invokeTauri<SegatoolsTrustStatus>('segatools_trust_status_cmd');
 export const deploySegatools = (force: boolean) => invokeTauri<DeployResult>('deploy_segatoools_cmd', { force });
 export const rollbackSegatools = () => invokeTauri<RollbackResult>('rollback_segatoools_cmd');
+export const runIntegrityScrub = (operationId?: string) =>
+  invokeTauri<ScrubReport>('run_integrity_scrub_cmd', { operationId });
+export const getIntegrityReport = () => invokeTauri<ScrubReport | null>('get_integrity_report_cmd');
@@ -1,3 +1,6 @@
 import { invokeTauri } from './tauriClient';
+import { Changelog } from '../types/changelog';
 
-export const loadChangelog = () => invokeTauri<string>('load_changelog_cmd');
+export const loadChangelog = (locale?: string) => invokeTauri<string>('load_changelog_cmd', { locale });
+export const loadStructuredChangelog = (locale?: string) =>
+  invokeTauri<Changelog>('load_structured_changelog_cmd', { locale });
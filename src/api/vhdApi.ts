@@ -1,7 +1,40 @@
 import { invokeTauri } from './tauriClient';
-import { VhdConfig, VhdDetectResult } from '../types/vhd';
+import {
+  DeltaSnapshots,
+  MigrationPlan,
+  VhdConfig,
+  VhdDetectResult,
+  VhdExportResult,
+  VhdMigrationReport,
+} from '../types/vhd';
 
 export const pickVhdGame = () => invokeTauri<VhdDetectResult>('pick_vhd_game_cmd');
 export const loadVhdConfig = (gameId: string) => invokeTauri<VhdConfig>('load_vhd_config_cmd', { gameId });
 export const saveVhdConfig = (gameId: string, config: VhdConfig) =>
   invokeTauri<void>('save_vhd_config_cmd', { gameId, config });
+export const exportDecryptedVhd = (
+  source: string,
+  destinationDir: string,
+  options?: { gameId?: string; maxMbPerSec?: number; keepSource?: boolean }
+) => invokeTauri<VhdExportResult>('export_decrypted_vhd_cmd', { source, destinationDir, ...options });
+// Progress arrives as `vhd-warmup-progress` events and the report as `vhd-warmup-finished`.
+export const startVhdWarmUp = (gameId: string, delaySecs?: number, operationId?: string) =>
+  invokeTauri<string>('start_vhd_warmup_cmd', { gameId, delaySecs, operationId });
+export const cancelVhdWarmUp = () => invokeTauri<void>('cancel_vhd_warmup_cmd');
+export const planVhdMigration = (gameId: string, targetDir: string, format?: 'vhd' | 'vhdx') =>
+  invokeTauri<MigrationPlan>('plan_vhd_migration_cmd', { gameId, targetDir, format });
+// Progress arrives as `vhd-migration-progress` events; needs an elevated launcher.
+export const migrateGameToVhd = (
+  gameId: string,
+  targetDir: string,
+  options?: { format?: 'vhd' | 'vhdx'; removeOriginals?: boolean; operationId?: string }
+) => invokeTauri<VhdMigrationReport>('migrate_game_to_vhd_cmd', { gameId, targetDir, ...options });
+export const listDeltaSnapshots = (gameId: string) =>
+  invokeTauri<DeltaSnapshots>('list_delta_snapshots_cmd', { gameId });
+// Passing `null` goes back to a fresh runtime delta on the next launch.
+export const restoreDeltaSnapshot = (gameId: string, name: string | null) =>
+  invokeTauri<DeltaSnapshots>('restore_delta_snapshot_cmd', { gameId, name });
+export const renameDeltaSnapshot = (gameId: string, name: string, newName: string) =>
+  invokeTauri<DeltaSnapshots>('rename_delta_snapshot_cmd', { gameId, name, newName });
+export const deleteDeltaSnapshot = (gameId: string, name: string) =>
+  invokeTauri<DeltaSnapshots>('delete_delta_snapshot_cmd', { gameId, name });
@@ -307,6 +307,35 @@ const ALL_SECTIONS: SectionSpec[] = [
       { name: 'path', type: 'text', allowDrop: true }
     ]
   },
+  {
+    key: 'cmio',
+    fields: [
+      { name: 'path', type: 'text', allowDrop: true }
+    ]
+  },
+  {
+    key: 'printer',
+    fields: [
+      { name: 'enable', type: 'checkbox' },
+      { name: 'serialNo', type: 'text' },
+      { name: 'mainFwPath', type: 'text' },
+      { name: 'dspFwPath', type: 'text' },
+      { name: 'paramFwPath', type: 'text' },
+      { name: 'printerOutPath', type: 'text' }
+    ]
+  },
+  {
+    key: 'mercuryio',
+    fields: [
+      { name: 'path', type: 'text', allowDrop: true }
+    ]
+  },
+  {
+    key: 'divaio',
+    fields: [
+      { name: 'path', type: 'text', allowDrop: true }
+    ]
+  },
   {
     key: 'io3',
     fields: [
@@ -359,6 +388,21 @@ function allowedSections(gameName?: string): Set<string> {
         ...common,
         'gfx', 'unity', 'led15093', 'led', 'mu3io', 'io4',
       ]));
+    case 'CardMaker':
+      return new Set(filterBlacklisted([
+        ...common,
+        'gfx', 'unity', 'led15093', 'printer', 'cmio', 'io4',
+      ]));
+    case 'Wacca':
+      return new Set(filterBlacklisted([
+        ...common,
+        'mercuryio', 'io4',
+      ]));
+    case 'Diva':
+      return new Set(filterBlacklisted([
+        ...common,
+        'divaio',
+      ]));
     default:
       return new Set(filterBlacklisted(ALL_SECTIONS.map(s => s.key as string)));
   }
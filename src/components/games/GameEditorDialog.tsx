@@ -355,6 +355,11 @@ function GameEditorDialog({ game, onSave, onCancel, initialField, lockMode }: Pr
                       appdata_path: prev?.appdata_path ?? '',
                       option_path: prev?.option_path ?? '',
                       delta_enabled: prev?.delta_enabled ?? true,
+                      delta_dir: prev?.delta_dir,
+                      delta_min_free_mb: prev?.delta_min_free_mb,
+                      delta_snapshots: prev?.delta_snapshots,
+                      mount_root: prev?.mount_root,
+                      letter_wait_secs: prev?.letter_wait_secs,
                     }));
                     update('executable_path', app_base_path);
                   }}
@@ -378,6 +383,11 @@ function GameEditorDialog({ game, onSave, onCancel, initialField, lockMode }: Pr
                       appdata_path: prev?.appdata_path ?? '',
                       option_path: prev?.option_path ?? '',
                       delta_enabled: prev?.delta_enabled ?? true,
+                      delta_dir: prev?.delta_dir,
+                      delta_min_free_mb: prev?.delta_min_free_mb,
+                      delta_snapshots: prev?.delta_snapshots,
+                      mount_root: prev?.mount_root,
+                      letter_wait_secs: prev?.letter_wait_secs,
                     }));
                   }}
                   className="game-editor-input monospace"
@@ -401,6 +411,11 @@ function GameEditorDialog({ game, onSave, onCancel, initialField, lockMode }: Pr
                       appdata_path,
                       option_path: prev?.option_path ?? '',
                       delta_enabled: prev?.delta_enabled ?? true,
+                      delta_dir: prev?.delta_dir,
+                      delta_min_free_mb: prev?.delta_min_free_mb,
+                      delta_snapshots: prev?.delta_snapshots,
+                      mount_root: prev?.mount_root,
+                      letter_wait_secs: prev?.letter_wait_secs,
                     }));
                   }}
                   className="game-editor-input"
@@ -423,6 +438,11 @@ function GameEditorDialog({ game, onSave, onCancel, initialField, lockMode }: Pr
                       appdata_path: prev?.appdata_path ?? '',
                       option_path,
                       delta_enabled: prev?.delta_enabled ?? true,
+                      delta_dir: prev?.delta_dir,
+                      delta_min_free_mb: prev?.delta_min_free_mb,
+                      delta_snapshots: prev?.delta_snapshots,
+                      mount_root: prev?.mount_root,
+                      letter_wait_secs: prev?.letter_wait_secs,
                     }));
                   }}
                   className="game-editor-input"
@@ -442,12 +462,43 @@ function GameEditorDialog({ game, onSave, onCancel, initialField, lockMode }: Pr
                     appdata_path: prev?.appdata_path ?? '',
                     option_path: prev?.option_path ?? '',
                     delta_enabled: e.target.checked,
+                    delta_dir: prev?.delta_dir,
+                    delta_min_free_mb: prev?.delta_min_free_mb,
+                    delta_snapshots: prev?.delta_snapshots,
+                    mount_root: prev?.mount_root,
+                    letter_wait_secs: prev?.letter_wait_secs,
                   }))}
                   style={{ width: 16, height: 16 }}
                 />
                 <span>{t('games.editor.deltaEnabled')}</span>
               </label>
             )}
+            {!isCompact && (
+              <label className="game-editor-label">
+                <div className="game-editor-label-text">{t('games.editor.deltaDir')}</div>
+                <input
+                  value={vhdConfig?.delta_dir ?? ''}
+                  disabled={!isVhd || !(vhdConfig?.delta_enabled ?? true)}
+                  placeholder={t('games.editor.deltaDirPlaceholder')}
+                  onChange={(e) => {
+                    const delta_dir = e.target.value;
+                    setVhdConfig(prev => ({
+                      app_base_path: prev?.app_base_path ?? '',
+                      app_patch_paths: prev?.app_patch_paths ?? [],
+                      appdata_path: prev?.appdata_path ?? '',
+                      option_path: prev?.option_path ?? '',
+                      delta_enabled: prev?.delta_enabled ?? true,
+                      delta_dir: delta_dir.trim() ? delta_dir : null,
+                      delta_min_free_mb: prev?.delta_min_free_mb,
+                      delta_snapshots: prev?.delta_snapshots,
+                      mount_root: prev?.mount_root,
+                      letter_wait_secs: prev?.letter_wait_secs,
+                    }));
+                  }}
+                  className="game-editor-input"
+                />
+              </label>
+            )}
           </div>
           <div ref={folderPanelRef} aria-hidden={isVhd} style={panelStyle(!isVhd)}>
             {showExecPath && (
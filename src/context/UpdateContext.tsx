@@ -4,6 +4,7 @@ import { relaunch } from '@tauri-apps/plugin-process';
 import { AUTO_UPDATE_STORAGE_KEY } from '../constants/storage';
 import { AppError, normalizeError } from '../errors';
 import { useOfflineMode } from '../state/offlineMode';
+import { postNotification } from '../api/notificationsApi';
 
 interface UpdateContextType {
   updateInfo: Update | null;
@@ -49,6 +50,13 @@ export function UpdateProvider({ children }: { children: React.ReactNode }) {
       const update = await check();
       if (update) {
         setUpdateInfo(update);
+        postNotification(
+          'updater',
+          'info',
+          `Update ${update.version} available`,
+          `ConfigArc Launcher ${update.version} is ready to install.`,
+          `update:${update.version}`
+        ).catch(() => {});
         return true;
       } else {
         setUpdateInfo(null);
@@ -6,6 +6,12 @@ export type AppError = {
   details?: string;
   data?: Record<string, unknown>;
   source?: string;
+  remediation?: ErrorRemediation;
+};
+
+export type ErrorRemediation = {
+  action: string;
+  target?: string;
 };
 
 const DEFAULT_CODE = 'UNEXPECTED';
@@ -18,6 +24,13 @@ const isRecord = (value: unknown): value is UnknownRecord =>
 const getString = (value: unknown): string | undefined =>
   typeof value === 'string' ? value : undefined;
 
+const extractRemediation = (value: unknown): ErrorRemediation | undefined => {
+  if (!isRecord(value)) return undefined;
+  const action = getString(value.action);
+  if (!action) return undefined;
+  return { action, target: getString(value.target) };
+};
+
 const extractAppError = (value: UnknownRecord): AppError | null => {
   const code = getString(value.code);
   const message = getString(value.message);
@@ -28,6 +41,7 @@ const extractAppError = (value: UnknownRecord): AppError | null => {
       details: getString(value.details),
       data: isRecord(value.data) ? value.data : undefined,
       source: getString(value.source),
+      remediation: extractRemediation(value.remediation),
     };
   }
   return null;
@@ -3,7 +3,8 @@ import { createPortal } from 'react-dom';
 import { useTranslation } from 'react-i18next';
 import { listen } from '@tauri-apps/api/event';
 import { decryptGameFiles, loadDecryptKeys, pickDecryptFiles } from '../api/deployGamesApi';
-import { DecryptResult, KeyStatus } from '../types/deployGames';
+import { DecryptResult, KeyStatus, OverwritePolicy } from '../types/deployGames';
+import { CryptoAcceleration } from '../types/platform';
 import { useToast, ToastContainer } from '../components/common/Toast';
 import { IconCheck, IconFile, IconFolder, IconKey, IconLink, IconPlay, IconTrash, IconX } from '../components/common/Icons';
 import { FSDECRYPT_KEY_URL_STORAGE_KEY } from '../constants/storage';
@@ -17,8 +18,24 @@ type DecryptProgress = {
   total: number;
   current_file: number;
   total_files: number;
+  stage: 'decrypt' | 'extract' | 'hash';
+  file_processed: number;
+  file_total: number;
 };
 
+const formatBytes = (value: number) => {
+  const units = ['B', 'KB', 'MB', 'GB', 'TB'];
+  let size = value;
+  let unit = 0;
+  while (size >= 1024 && unit < units.length - 1) {
+    size /= 1024;
+    unit += 1;
+  }
+  return `${size.toFixed(unit === 0 ? 0 : 1)} ${units[unit]}`;
+};
+
+const formatMs = (ms: number) => (ms >= 1000 ? `${(ms / 1000).toFixed(1)} s` : `${ms} ms`);
+
 function DeployGamesPage() {
   const { t } = useTranslation();
   const { toasts, showToast } = useToast();
@@ -27,6 +44,12 @@ function DeployGamesPage() {
   const [files, setFiles] = useState<string[]>([]);
   const [keyUrl, setKeyUrl] = useState<string>(() => localStorage.getItem(FSDECRYPT_KEY_URL_STORAGE_KEY) ?? '');
   const [noExtract, setNoExtract] = useState<boolean>(false);
+  const [extractTree, setExtractTree] = useState<boolean>(false);
+  const [importGame, setImportGame] = useState<boolean>(false);
+  const [outputDir, setOutputDir] = useState<string>('');
+  const [overwrite, setOverwrite] = useState<OverwritePolicy>('replace');
+  const [nameTemplate, setNameTemplate] = useState<string>('');
+  const [acceleration, setAcceleration] = useState<CryptoAcceleration | null>(null);
   const [loading, setLoading] = useState<boolean>(false);
   const [results, setResults] = useState<DecryptResult[]>([]);
   const [keyStatus, setKeyStatus] = useState<KeyStatus | null>(null);
@@ -126,10 +149,21 @@ function DeployGamesPage() {
     setLoading(true);
     setDecryptProgress(0);
     try {
-      const res = await decryptGameFiles(files, noExtract, keyUrl.trim() || undefined);
+      const res = await decryptGameFiles(
+        files,
+        noExtract,
+        keyUrl.trim() || undefined,
+        undefined,
+        extractTree,
+        importGame && !noExtract && !extractTree,
+        outputDir.trim() || undefined,
+        overwrite,
+        nameTemplate.trim() || undefined,
+      );
       setResults((prev) => (prev.length > 0 ? prev : res.results));
       setKeyStatus({ key_source: res.key_source, key_game_count: res.key_game_count });
-      showToast(t('deployGames.resultOk'), 'success');
+      setAcceleration(res.acceleration);
+      showToast(t(res.imported_game_id ? 'deployGames.importedGame' : 'deployGames.resultOk'), 'success');
     } catch (err) {
       showToast(formatError(t, err), 'error');
     } finally {
@@ -258,6 +292,32 @@ function DeployGamesPage() {
                 </div>
               ))}
             </div>
+            <div className="output-options">
+              <label>
+                <span>{t('deployGames.outputDir')}</span>
+                <input
+                  value={outputDir}
+                  onChange={(e) => setOutputDir(e.target.value)}
+                  placeholder={t('deployGames.outputDirPlaceholder')}
+                />
+              </label>
+              <label title={t('deployGames.nameTemplateHint')}>
+                <span>{t('deployGames.nameTemplate')}</span>
+                <input
+                  value={nameTemplate}
+                  onChange={(e) => setNameTemplate(e.target.value)}
+                  placeholder="{id}_{version}_{seq}"
+                />
+              </label>
+              <label>
+                <span>{t('deployGames.overwrite')}</span>
+                <select value={overwrite} onChange={(e) => setOverwrite(e.target.value as OverwritePolicy)}>
+                  <option value="replace">{t('deployGames.overwriteReplace')}</option>
+                  <option value="rename">{t('deployGames.overwriteRename')}</option>
+                  <option value="skip">{t('deployGames.overwriteSkip')}</option>
+                </select>
+              </label>
+            </div>
           </div>
         </div>
       </div>
@@ -291,6 +351,24 @@ function DeployGamesPage() {
           />
           <span>{t('deployGames.noExtract')}</span>
         </label>
+        <label className="checkbox-row" title={t('deployGames.extractTreeHint')}>
+          <input
+            type="checkbox"
+            checked={extractTree}
+            disabled={noExtract}
+            onChange={(e) => setExtractTree(e.target.checked)}
+          />
+          <span>{t('deployGames.extractTree')}</span>
+        </label>
+        <label className="checkbox-row" title={t('deployGames.importGameHint')}>
+          <input
+            type="checkbox"
+            checked={importGame}
+            disabled={noExtract || extractTree}
+            onChange={(e) => setImportGame(e.target.checked)}
+          />
+          <span>{t('deployGames.importGame')}</span>
+        </label>
       </div>
 
       <div className="deploy-games-card results-card section-card">
@@ -303,6 +381,17 @@ function DeployGamesPage() {
             <span className="meta-badge error">
               <IconX /> {t('deployGames.resultFailed')}: {failedCount}
             </span>
+            {acceleration && (
+              <span
+                className={`meta-badge ${acceleration.aes && acceleration.crc32 ? 'success' : 'warning'}`}
+                title={t('deployGames.accelerationHint')}
+              >
+                {t('deployGames.acceleration', {
+                  aes: t(acceleration.aes ? 'deployGames.accelerationOn' : 'deployGames.accelerationOff'),
+                  crc32: t(acceleration.crc32 ? 'deployGames.accelerationOn' : 'deployGames.accelerationOff'),
+                })}
+              </span>
+            )}
           </div>
         </div>
         <div className="card-content section-card-body">
@@ -313,7 +402,11 @@ function DeployGamesPage() {
             {results.map((result, idx) => {
               const isFailed = result.failed || Boolean(result.error);
               const statusText = isFailed ? t('deployGames.resultFailed') : t('deployGames.resultOk');
-              const outputText = result.extracted ? t('deployGames.resultExtracted') : t('deployGames.resultWritten');
+              const outputText = result.skipped
+                ? t('deployGames.resultSkipped')
+                : result.extracted
+                  ? t('deployGames.resultExtracted')
+                  : t('deployGames.resultWritten');
               return (
                 <div key={`${result.input}-${idx}`} className={`result-row ${isFailed ? 'error' : 'ok'}`}>
                   <div className="result-info">
@@ -327,6 +420,26 @@ function DeployGamesPage() {
                     <div className="result-meta">
                       {result.container_type && <span className="meta-pill">{result.container_type}</span>}
                       {!isFailed && <span className="meta-pill">{outputText}</span>}
+                      {result.key_id && (
+                        <span className="meta-pill">{t('deployGames.resultKey', { id: result.key_id })}</span>
+                      )}
+                      {result.decrypted_bytes > 0 && (
+                        <span
+                          className="meta-pill"
+                          title={t('deployGames.resultTimings', {
+                            decrypt: formatMs(result.decrypt_ms),
+                            extract: formatMs(result.extract_ms),
+                            hash: formatMs(result.hash_ms),
+                            total: formatMs(result.total_ms),
+                          })}
+                        >
+                          {t('deployGames.resultStats', {
+                            size: formatBytes(result.decrypted_bytes),
+                            time: formatMs(result.decrypt_ms),
+                            speed: formatBytes(result.throughput_bps),
+                          })}
+                        </span>
+                      )}
                     </div>
                     {result.warnings.map((warning, idx) => (
                       <div key={`${result.input}-warn-${idx}`} className="result-warning">{warning}</div>
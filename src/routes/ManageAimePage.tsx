@@ -2,21 +2,12 @@ import { useEffect, useMemo, useRef, useState } from 'react';
 import { useTranslation } from 'react-i18next';
 import { useGamesState } from '../state/gamesStore';
 import { AimeEntry } from '../types/manage';
-import { applyAimeToActive, deleteAime, getActiveAime, listAimes, saveAime, updateAime } from '../api/manageApi';
+import { applyAimeToActive, deleteAime, generateAime, getActiveAime, listAimes, saveAime, updateAime } from '../api/manageApi';
 import { useToast, ToastContainer } from '../components/common/Toast';
 import { formatError } from '../errors';
 import './ManageAimePage.css';
 
 const normalizeAime = (value: string) => value.replace(/\s+/g, '');
-const buildRandomAime = () => {
-  const cryptoObj = globalThis.crypto;
-  if (cryptoObj && typeof cryptoObj.getRandomValues === 'function') {
-    const bytes = new Uint8Array(20);
-    cryptoObj.getRandomValues(bytes);
-    return Array.from(bytes, (b) => (b % 10).toString()).join('');
-  }
-  return Array.from({ length: 20 }, () => Math.floor(Math.random() * 10).toString()).join('');
-};
 
 // Simple SVG Icons
 const Icons = {
@@ -147,8 +138,12 @@ function ManageAimePage() {
     }
   };
 
-  const handleGenerate = () => {
-    setNumber(buildRandomAime());
+  const handleGenerate = async () => {
+    try {
+      setNumber(await generateAime());
+    } catch (err) {
+      showToast(formatError(t, err), 'error');
+    }
   };
 
   const handleEdit = (entry: AimeEntry) => {
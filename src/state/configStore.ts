@@ -3,12 +3,13 @@ import {
   loadSegatoolsConfig,
   saveSegatoolsConfig,
   loadDefaultSegatoolsConfig,
+  listConfigSlots,
   listProfiles,
   saveProfile as saveProfileApi,
   deleteProfile as deleteProfileApi,
   loadProfile as loadProfileApi
 } from '../api/configApi';
-import { SegatoolsConfig } from '../types/config';
+import { ConfigSlot, SegatoolsConfig } from '../types/config';
 import { ConfigProfile } from '../types/games';
 import { getActiveGame } from '../api/gamesApi';
 import { fetchTrustStatus } from '../api/trustedApi';
@@ -65,6 +66,8 @@ export function useConfigState() {
   const [activeGameId, setActiveGameId] = useState<string | null>(null);
   const [trustStatus, setTrustStatus] = useState<SegatoolsTrustStatus | null>(null);
   const [trustLoading, setTrustLoading] = useState<boolean>(false);
+  const [slot, setSlot] = useState<string>('default');
+  const [slots, setSlots] = useState<ConfigSlot[]>([]);
 
   const refreshTrust = useCallback(async (gameId?: string | null) => {
     if (isOfflineModeEnabled()) {
@@ -126,7 +129,11 @@ export function useConfigState() {
       }
       const cachedTrust = active ? readCachedTrustStatus(active) : null;
       setTrustStatus(cachedTrust);
-      const cfg = await loadSegatoolsConfig();
+      const available = await listConfigSlots(active);
+      setSlots(available);
+      const target = available.some((s) => s.name === slot) ? slot : 'default';
+      if (target !== slot) setSlot(target);
+      const cfg = await loadSegatoolsConfig(target);
       setConfig(cfg);
       setError(null);
       void refreshTrust(active);
@@ -136,12 +143,12 @@ export function useConfigState() {
     } finally {
       setLoading(false);
     }
-  }, [refreshTrust]);
+  }, [refreshTrust, slot]);
 
   const save = useCallback(async (cfg: SegatoolsConfig) => {
     setSaving(true);
     try {
-      await saveSegatoolsConfig(cfg);
+      await saveSegatoolsConfig(cfg, slot);
       setConfig(cfg);
       setError(null);
     } catch (err) {
@@ -151,7 +158,7 @@ export function useConfigState() {
     } finally {
       setSaving(false);
     }
-  }, []);
+  }, [slot]);
 
   const resetToDefaults = useCallback(async () => {
     const defaults = await loadDefaultSegatoolsConfig();
@@ -162,7 +169,23 @@ export function useConfigState() {
     reload();
   }, [reload]);
 
-  return { config, setConfig, loading, saving, error, activeGameId, reload, save, resetToDefaults, trustStatus, trustLoading, refreshTrust };
+  return {
+    config,
+    setConfig,
+    loading,
+    saving,
+    error,
+    activeGameId,
+    reload,
+    save,
+    resetToDefaults,
+    trustStatus,
+    trustLoading,
+    refreshTrust,
+    slot,
+    setSlot,
+    slots,
+  };
 }
 
 export function useProfilesState() {
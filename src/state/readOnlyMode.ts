@@ -0,0 +1,54 @@
+import { useEffect, useState } from 'react';
+import { invokeTauri } from '../api/tauriClient';
+
+const READ_ONLY_MODE_CHANGED_EVENT = 'configarc:read-only-mode-changed';
+
+export interface ReadOnlyStatus {
+  enabled: boolean;
+  // Set by machine policy; the in-app toggle cannot turn it off.
+  enforcedByPolicy: boolean;
+}
+
+const DEFAULT_STATUS: ReadOnlyStatus = { enabled: false, enforcedByPolicy: false };
+
+export const getReadOnlyMode = () => invokeTauri<ReadOnlyStatus>('get_read_only_mode_cmd');
+
+export const setReadOnlyMode = async (enabled: boolean) => {
+  const status = await invokeTauri<ReadOnlyStatus>('set_read_only_mode_cmd', { enabled });
+  window.dispatchEvent(
+    new CustomEvent<ReadOnlyStatus>(READ_ONLY_MODE_CHANGED_EVENT, {
+      detail: status,
+    })
+  );
+  return status;
+};
+
+export function useReadOnlyMode() {
+  const [status, setStatus] = useState<ReadOnlyStatus>(DEFAULT_STATUS);
+
+  useEffect(() => {
+    let disposed = false;
+
+    getReadOnlyMode()
+      .then((next) => {
+        if (!disposed) setStatus(next);
+      })
+      .catch(() => {
+        // Commands are still gated by the backend.
+      });
+
+    const handleModeChange = (event: Event) => {
+      const customEvent = event as CustomEvent<ReadOnlyStatus>;
+      if (customEvent.detail) setStatus(customEvent.detail);
+    };
+
+    window.addEventListener(READ_ONLY_MODE_CHANGED_EVENT, handleModeChange);
+
+    return () => {
+      disposed = true;
+      window.removeEventListener(READ_ONLY_MODE_CHANGED_EVENT, handleModeChange);
+    };
+  }, []);
+
+  return status;
+}
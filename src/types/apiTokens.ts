@@ -0,0 +1,15 @@
+export type ApiScope = 'read_only' | 'launch_only' | 'full';
+
+export interface ApiToken {
+  id: string;
+  name: string;
+  scope: ApiScope;
+  createdAt: string;
+  lastUsedAt?: string | null;
+}
+
+// `token` is only ever returned once, when it is generated.
+export interface IssuedApiToken {
+  token: string;
+  info: ApiToken;
+}
@@ -0,0 +1,38 @@
+export interface AppdataBackupSummary {
+  id: string;
+  createdAt: string;
+  // Null for a backup that was interrupted; starting a backup again resumes it.
+  completedAt: string | null;
+  fileCount: number;
+  totalBytes: number;
+}
+
+export interface AppdataBackupProgress {
+  file: string;
+  filesDone: number;
+  fileCount: number;
+  processed: number;
+  total: number;
+}
+
+export interface AppdataBackupReport {
+  snapshotId: string;
+  completed: boolean;
+  files: number;
+  totalBytes: number;
+  unchangedFiles: number;
+  newChunks: number;
+  newBytes: number;
+}
+
+export interface AppdataRestoreReport {
+  snapshotId: string;
+  files: number;
+  bytes: number;
+}
+
+export interface AppdataPruneReport {
+  removedSnapshots: string[];
+  removedChunks: number;
+  freedBytes: number;
+}
@@ -0,0 +1,19 @@
+export type ChangelogSectionKind = 'features' | 'fixes' | 'breaking' | 'other';
+
+export interface ChangelogSection {
+  kind: ChangelogSectionKind;
+  title: string;
+  entries: string[];
+}
+
+export interface ChangelogRelease {
+  version: string;
+  date?: string | null;
+  sections: ChangelogSection[];
+}
+
+export interface Changelog {
+  locale?: string | null;
+  fallback: boolean;
+  releases: ChangelogRelease[];
+}
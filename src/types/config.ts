@@ -35,11 +35,45 @@ export interface SegatoolsConfig {
   io3: Io3Config;
   slider: SliderConfig;
   ir: IrConfig;
+  cmio: CmIoConfig;
+  printer: PrinterConfig;
+  mercuryio: MercuryIoConfig;
+  divaio: DivaIoConfig;
   presentSections?: string[];
   commentedKeys?: string[];
   presentKeys?: string[];
 }
 
+export interface ConfigChange {
+  section: string;
+  key: string;
+  old?: string | null;
+  new?: string | null;
+}
+
+// A segatools.ini slot; 'default' is the game's own segatools.ini, others live beside it as
+// segatools.<name>.ini. The 'amdaemon' slot is handed to amdaemon at launch.
+export interface ConfigSlot {
+  name: string;
+  path: string;
+  exists: boolean;
+}
+
+export type KeySource = 'template' | 'user' | 'profile';
+
+export interface KeyProvenance {
+  section: string;
+  key: string;
+  value?: string | null;
+  // Template value; null when the template leaves the key unset.
+  default?: string | null;
+  modified: boolean;
+  source: KeySource;
+  profileId?: string;
+  profileName?: string;
+  updatedAt?: string;
+}
+
 export interface SliderConfig {
   enable: boolean;
   cell1: number; cell2: number; cell3: number; cell4: number;
@@ -81,6 +115,21 @@ export interface ChuniIoConfig {
 
 export interface Mu3IoConfig { path: string; }
 
+export interface CmIoConfig { path: string; }
+
+export interface PrinterConfig {
+  enable: boolean;
+  serialNo: string;
+  mainFwPath: string;
+  dspFwPath: string;
+  paramFwPath: string;
+  printerOutPath: string;
+}
+
+export interface MercuryIoConfig { path: string; }
+
+export interface DivaIoConfig { path: string; }
+
 export interface Io3Config {
   test: number;
   service: number;
@@ -271,3 +320,71 @@ export interface UnityConfig {
   enable: boolean;
   targetAssembly: string;
 }
+
+export type ProbeStatus =
+  | 'reachable'
+  | 'resolved'
+  | 'no_host'
+  | 'dns_failed'
+  | 'refused'
+  | 'timed_out'
+  | 'unreachable';
+
+// One server from [dns] as seen from this machine: resolved addresses and a TCP connect.
+export interface EndpointProbe {
+  service: 'startup' | 'title' | 'billing' | 'aimedb';
+  host: string;
+  port?: number | null;
+  addresses: string[];
+  status: ProbeStatus;
+  latencyMs?: number;
+  error?: string;
+}
+
+// A COM port for the portNo fields; friendlyName falls back to the port name.
+export interface SerialPort {
+  portName: string;
+  number: number;
+  friendlyName: string;
+  manufacturer?: string;
+  deviceId?: string;
+}
+
+export interface KeyName {
+  code: number;
+  name: string;
+}
+
+// Settings such as 'io4.coin' that share one key.
+export interface KeyConflict {
+  code: number;
+  name?: string;
+  fields: string[];
+}
+
+// A saved card in a QR backup; only the last digits of the number travel.
+export interface CardAlias {
+  name: string;
+  numberSuffix: string;
+}
+
+export interface QrBackup {
+  dns: DnsConfig;
+  gfx: GfxConfig;
+  cards: CardAlias[];
+}
+
+export type IoDllKind = 'aimeio' | 'mai2io' | 'chuniio' | 'mu3io';
+
+export interface IoDll {
+  id: string;
+  label: string;
+  kind: IoDllKind;
+  file: string;
+  sha256: string;
+  size: number;
+  addedAt: string;
+}
+
+// `modified` means the copy in the library no longer matches its recorded hash.
+export type IoDllEntry = IoDll & { active: boolean; modified: boolean };
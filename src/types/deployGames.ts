@@ -1,17 +1,101 @@
+import { CryptoAcceleration } from './platform';
+
+/** What a decrypt run does when a container's output already exists. */
+export type OverwritePolicy = 'skip' | 'rename' | 'replace';
+
 export interface DecryptResult {
   input: string;
   output?: string | null;
   container_type?: string | null;
   extracted: boolean;
+  /** Output already existed and the overwrite policy left it in place. */
+  skipped: boolean;
   warnings: string[];
   failed: boolean;
   error?: string | null;
+  /** Size of the encrypted container. */
+  input_bytes: number;
+  decrypted_bytes: number;
+  decrypt_ms: number;
+  throughput_bps: number;
+  extract_ms: number;
+  hash_ms: number;
+  /** Wall time for the whole file, from opening it to the manifest. */
+  total_ms: number;
+  /** OS or game id of the key used, or `OPTION` for the shared option key. */
+  key_id?: string | null;
+  derived_iv: boolean;
+  /** SHA-256 manifest written beside an extracted output. */
+  manifest?: string | null;
 }
 
 export interface DecryptSummary {
   results: DecryptResult[];
   key_source: string;
   key_game_count: number;
+  /** Hardware paths the run's AES and CRC32 work could use. */
+  acceleration: CryptoAcceleration;
+  workers: number;
+  elapsed_ms: number;
+  decrypted_bytes: number;
+  throughput_bps: number;
+  /** The run was cancelled; unfinished files report it as their error. */
+  cancelled: boolean;
+  /** Game created from the decrypted APP VHDs when an import was requested. */
+  imported_game_id?: string | null;
+}
+
+export interface ContainerInfo {
+  input: string;
+  container_type?: 'OS' | 'APP' | 'OPTION' | 'UNKNOWN' | null;
+  id?: string | null;
+  version?: string | null;
+  source_version?: string | null;
+  timestamp?: string | null;
+  sequence_number?: number | null;
+  output_size?: number | null;
+  output_name?: string | null;
+  key_available: boolean;
+  error?: string | null;
+}
+
+export interface OptionBuildResult {
+  output: string;
+  game_id: string;
+  option_id: string;
+  image_size: number;
+  build_ms: number;
+}
+
+export interface KeyStoreStatus {
+  path: string;
+  exists: boolean;
+  has_bootid_key: boolean;
+  has_option_key: boolean;
+  game_ids: string[];
+}
+
+export interface ManifestDrift {
+  path: string;
+  kind: 'modified' | 'missing' | 'added';
+  expected_size?: number | null;
+  actual_size?: number | null;
+}
+
+export interface OutputVerification {
+  output: string;
+  manifest: string;
+  checked_files: number;
+  checked_bytes: number;
+  /** Empty when the output matches its manifest. */
+  drift: ManifestDrift[];
+}
+
+/** Payload of the `decrypt-verify-progress` event. */
+export interface VerifyProgress {
+  output: string;
+  processed: number;
+  total: number;
 }
 
 export interface KeyStatus {
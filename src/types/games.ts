@@ -1,4 +1,5 @@
 import { SegatoolsConfig } from './config';
+import { VhdConfig } from './vhd';
 
 export interface Game {
   id: string;
@@ -9,13 +10,247 @@ export interface Game {
   enabled: boolean;
   tags: string[];
   launch_mode?: 'folder' | 'vhd';
+  sandbox?: SandboxOptions;
+  gpu_preference?: GpuPreference;
+  display_presets?: DisplayPreset[];
+  display_preset?: string | null;
+  pre_launch?: HookCommand[];
+  post_exit?: HookCommand[];
+  watchdog?: WatchdogOptions;
+  detected_version?: string | null;
+}
+
+export interface WatchdogOptions {
+  enabled: boolean;
+  max_restarts: number;
+  restart_delay_secs: number;
+  stable_after_secs: number;
+}
+
+/** Payload of the `game-crash` event. */
+export interface CrashReport {
+  gameId: string;
+  gameName: string;
+  exitCode: number | null;
+  uptimeSecs: number;
+  detectedAt: string;
+  restartAttempt: number | null;
+  logSource: string | null;
+  logLines: string[];
+}
+
+export interface HookCommand {
+  command: string;
+  args?: string[];
+  working_dir?: string | null;
+  timeout_secs?: number;
+  background?: boolean;
+}
+
+export type GpuPreference = 'unmanaged' | 'auto' | 'power_saving' | 'high_performance';
+
+export type GpuVendor = 'nvidia' | 'amd' | 'intel';
+
+export interface LastGpuUsage {
+  timestamp: string;
+  vendor: GpuVendor;
+  loadedVendors: GpuVendor[];
+  driverModules: string[];
+}
+
+// Sets [gfx] monitor/windowed and -screen-width/-screen-height together when applied.
+export interface DisplayPreset {
+  name: string;
+  monitor: number;
+  windowed?: boolean;
+  width: number;
+  height: number;
+}
+
+export interface MonitorInfo {
+  index: number;
+  deviceName: string;
+  adapter: string;
+  width: number;
+  height: number;
+  refreshRate?: number;
+  primary: boolean;
+}
+
+export interface SandboxOptions {
+  kill_on_close: boolean;
+  deny_amdaemon_children: boolean;
+  max_working_set_mb?: number | null;
+}
+
+export interface DetectionRule {
+  name: string;
+  executables: string[];
+  dataMarkers?: string[];
+  defaultArgs?: string[];
+  hookDll?: string | null;
+  injectTarget?: string | null;
+  versions?: VersionDefaults[];
+}
+
+export interface VersionDefaults {
+  minVersion?: string | null;
+  maxVersion?: string | null;
+  defaultArgs?: string[] | null;
+  extraSections?: string[];
+  removedSections?: string[];
 }
 
 export interface ConfigProfile {
   id: string;
   name: string;
   description?: string | null;
+  base_profile_id?: string | null;
   segatools: SegatoolsConfig;
+  /** Configs for named segatools.ini slots, keyed by slot name. */
+  segatools_slots?: Record<string, SegatoolsConfig>;
+  json_configs?: Record<string, unknown>;
   created_at: string;
   updated_at: string;
 }
+
+export interface ProfileAsset {
+  target: string;
+  source: string;
+  size: number;
+}
+
+export interface CleanupOptions {
+  remove_launcher_state: boolean;
+  remove_segatools_files: boolean;
+  remove_runtime_deltas: boolean;
+  remove_temp_files: boolean;
+  remove_shortcuts?: boolean;
+}
+
+export interface CleanupItem {
+  kind: 'runtime_delta' | 'temp_file' | 'segatools_file' | 'shortcut' | 'launcher_state' | 'game_entry';
+  path: string;
+  error?: string | null;
+}
+
+export interface CleanupReport {
+  game_id: string;
+  confirmed: boolean;
+  removed: CleanupItem[];
+  failed: CleanupItem[];
+}
+
+export interface CacheEntry {
+  game_id: string;
+  kind: 'unity_cache' | 'shader_cache';
+  path: string;
+  size: number;
+  error?: string | null;
+}
+
+export interface CacheReport {
+  confirmed: boolean;
+  total_size: number;
+  entries: CacheEntry[];
+  failed: CacheEntry[];
+}
+
+export type DiskUsageKind =
+  | 'binaries'
+  | 'vhd_base'
+  | 'vhd_patch'
+  | 'vhd_runtime'
+  | 'appdata'
+  | 'options'
+  | 'backups'
+  | 'logs';
+
+export interface DiskUsageEntry {
+  kind: DiskUsageKind;
+  size: number;
+  paths: string[];
+}
+
+export interface DiskUsageReport {
+  game_id: string;
+  total_size: number;
+  /** Largest first. */
+  entries: DiskUsageEntry[];
+  computed_at: string;
+  cached: boolean;
+}
+
+export type CreditMode = 'freeplay' | 'coin';
+
+export interface FailureReport {
+  gameId: string;
+  gameName: string;
+  launchMode: 'folder' | 'vhd';
+  capturedAt: string;
+  errorCode: string;
+  errorMessage: string;
+  osBuild?: string | null;
+  gpus: string[];
+  segatoolsVersion?: string | null;
+  configHash?: string | null;
+  vhdConfig?: VhdConfig | null;
+  logSource?: string | null;
+  logLines: string[];
+}
+
+export interface JournalEntry {
+  timestamp: string;
+  kind: 'deploy' | 'rollback' | 'profile_apply' | 'icf_edit' | 'mod_install' | 'mod_remove' | 'launch' | string;
+  summary: string;
+  success: boolean;
+  error?: string | null;
+  details?: Record<string, unknown> | null;
+}
+
+export interface LaunchQueueItem {
+  gameId: string;
+  profileId?: string | null;
+  creditMode?: CreditMode | null;
+}
+
+export interface LaunchQueueStatus {
+  running: boolean;
+  current?: LaunchQueueItem | null;
+  pending: LaunchQueueItem[];
+  completed: number;
+  failed: number;
+}
+
+export interface LaunchPlanStep {
+  stage: string;
+  description: string;
+  ok: boolean;
+  error?: string;
+}
+
+export interface PredictedVfsPath {
+  key: 'amfs' | 'appdata' | 'option';
+  path: string;
+  source: 'recorded' | 'default';
+}
+
+export interface LaunchPlan {
+  gameId: string;
+  ready: boolean;
+  steps: LaunchPlanStep[];
+  predictedVfs: PredictedVfsPath[];
+}
+
+/** Payload of the `launch-event` channel; `mounting` and `unmounted` only occur for VHD games. */
+export type LaunchEvent = { gameId: string } & (
+  | { stage: 'mounting' | 'configuring' | 'starting-amdaemon' | 'starting-game' | 'running' | 'unmounted' }
+  | { stage: 'exited'; code: number | null }
+);
+
+export interface LaunchQueueProgress {
+  stage: 'starting' | 'running' | 'exited' | 'failed' | 'stopped' | 'finished';
+  gameId?: string | null;
+  error?: string | null;
+  status: LaunchQueueStatus;
+}
@@ -63,3 +63,39 @@ export interface CommonConfigEditable {
   emoney?: CommonEmoneyConfig;
   [key: string]: unknown;
 }
+
+export interface NetworkProperty {
+  dhcp?: boolean;
+  ip_address?: string;
+  subnet_mask?: string;
+  gateway?: string;
+  primary_dns?: string;
+  secondary_dns?: string;
+  [key: string]: unknown;
+}
+
+export interface NetworkConfig {
+  property?: NetworkProperty;
+  [key: string]: unknown;
+}
+
+export interface LinkConfigEditable {
+  network?: NetworkConfig;
+  [key: string]: unknown;
+}
+
+export type AmdaemonConfig =
+  | { kind: 'common'; config: CommonConfigEditable & { network?: NetworkConfig } }
+  | { kind: 'server'; config: LinkConfigEditable }
+  | { kind: 'client'; config: LinkConfigEditable };
+
+export interface JsonConfigIssue {
+  path: string;
+  message: string;
+}
+
+/** Where a config breaks its bundled schema; `pointer` is a JSON pointer, empty for the root. */
+export interface JsonSchemaError {
+  pointer: string;
+  message: string;
+}
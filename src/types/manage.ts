@@ -41,12 +41,70 @@ export type IcfEntry =
       is_prerelease: boolean;
     };
 
+/** Payload of the `item-trashed` and `item-restored` events. */
+export interface TrashEntry {
+  id: string;
+  game_id: string;
+  kind: 'mod' | 'option';
+  name: string;
+  original_path: string;
+  deleted_at: string;
+  expires_at: string;
+}
+
+export interface IcfSummary {
+  app_id?: string | null;
+  platform_id?: string | null;
+  app_version?: string | null;
+  patch_count: number;
+  option_count: number;
+}
+
+export type IcfFindingKind =
+  | 'missing_option'
+  | 'unlisted_option'
+  | 'version_mismatch'
+  | 'missing_patch'
+  | 'uninstalled_patch'
+  | 'orphaned_patch';
+
+export interface IcfFinding {
+  kind: IcfFindingKind;
+  subject: string;
+  message: string;
+}
+
+export interface IcfBackup {
+  kind: string;
+  file_name: string;
+  path: string;
+  created_at: string;
+  size: number;
+}
+
+export interface IcfAudit {
+  app_id?: string | null;
+  icf_version?: string | null;
+  installed_version?: string | null;
+  findings: IcfFinding[];
+}
+
 export interface OptionEntry {
   name: string;
   path: string;
   is_dir: boolean;
   size: number;
   version?: string | null;
+  compression: FolderCompression;
+}
+
+export interface FolderCompression {
+  compressed: boolean;
+  total_files: number;
+  compressed_files: number;
+  logical_size: number;
+  on_disk_size: number;
+  estimated_savings: number;
 }
 
 export interface ModEntry {
@@ -64,8 +122,36 @@ export interface ModsStatus {
   message?: string | null;
 }
 
+export interface ModConflict {
+  kind: 'assembly_patch' | 'asset_path';
+  target: string;
+  mods: string[];
+}
+
+export interface ModConflictReport {
+  scanned: string[];
+  conflicts: ModConflict[];
+  warnings: string[];
+}
+
 export interface AimeEntry {
   id: string;
   name: string;
   number: string;
 }
+
+export interface AimeMigrationSource {
+  path: string;
+  imported: number;
+  duplicates: number;
+  invalid: number;
+  error?: string | null;
+}
+
+export interface AimeMigrationReport {
+  storePath: string;
+  scanned: string[];
+  sources: AimeMigrationSource[];
+  imported: AimeEntry[];
+  total: number;
+}
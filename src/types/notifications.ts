@@ -0,0 +1,15 @@
+export type NotificationSeverity = 'info' | 'warning' | 'error';
+
+export interface AppNotification {
+  id: string;
+  // Subsystem that posted it, e.g. "updater", "trust", "backup", "remote_config".
+  source: string;
+  severity: NotificationSeverity;
+  title: string;
+  message: string;
+  createdAt: string;
+  read: boolean;
+  dedupeKey?: string | null;
+  occurrences: number;
+  details?: Record<string, unknown> | null;
+}
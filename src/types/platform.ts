@@ -0,0 +1,38 @@
+export type PowerShellSupport = 'unavailable' | 'constrained' | 'full';
+export type DialogBackend = 'powershell' | 'native' | 'none';
+
+export interface Capability {
+  supported: boolean;
+  // Why the feature is off; safe to show as a tooltip.
+  reason?: string;
+}
+
+// Optional backend subsystems; a build without one answers its commands with FEATURE_DISABLED.
+export type BuildFeature = 'icf' | 'fsdecrypt' | 'privexec';
+
+// Features that depend on the host, so the UI can hide them under Wine or when PowerShell is blocked.
+export interface PlatformCapabilities {
+  wine: boolean;
+  powershell: PowerShellSupport;
+  dialogs: DialogBackend;
+  processChecks: Capability;
+  vhdMount: Capability;
+  bitlockerUnlock: Capability;
+  gpuDetection: Capability;
+  cryptoAcceleration: CryptoAcceleration;
+}
+
+// CPU instructions the AES and CRC32 code of decryption runs on; software fallback otherwise.
+export interface CryptoAcceleration {
+  aes: boolean;
+  crc32: boolean;
+}
+
+// A cancellable long-running operation; long-running commands take an optional
+// `operationId` so the caller can cancel them with `cancelOperation`.
+export interface OperationInfo {
+  id: string;
+  kind: 'decrypt' | 'download' | 'appdata_backup' | 'integrity_scrub' | 'vfs_scan' | 'vhd_warmup' | 'vhd_migration';
+  startedAt: string;
+  cancelled: boolean;
+}
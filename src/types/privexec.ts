@@ -31,6 +31,51 @@ export interface PrivExecCommandResponse {
   result?: unknown;
 }
 
+// Responses of the steps that ran; a failed step is the last one and its code is the batch's.
+export interface PrivExecBatchResponse {
+  schemaVersion: number;
+  batchId: string;
+  ok: boolean;
+  code: string;
+  message: string;
+  executedAt: string;
+  results: PrivExecCommandResponse[];
+}
+
+// Typed `result`s; statuses are names like 'Locked' or 'GPT' whichever PowerShell ran.
+export interface PrivExecMountVhdResult {
+  schemaVersion: number;
+  imagePath?: string;
+  attached: boolean;
+  gameId?: string;
+  mountPoints?: Record<string, unknown>;
+  alreadyMounted: boolean;
+}
+
+export interface PrivExecBitLockerStatus {
+  schemaVersion: number;
+  mountPoint: string;
+  volumeStatus?: string;
+  protectionStatus?: string;
+  lockStatus?: string;
+  encryptionPercentage?: number;
+  autoUnlockEnabled?: boolean;
+  alreadyUnlocked?: boolean;
+}
+
+export interface PrivExecDiskInfo {
+  number: number;
+  friendlyName?: string;
+  operationalStatus?: string;
+  partitionStyle?: string;
+  size?: number;
+}
+
+export interface PrivExecDiskList {
+  schemaVersion: number;
+  disks: PrivExecDiskInfo[];
+}
+
 export interface PrivExecPolicyUpdateResponse {
   ok: boolean;
   code: string;
@@ -39,6 +84,36 @@ export interface PrivExecPolicyUpdateResponse {
   rolledBack: boolean;
 }
 
+export interface PrivExecPolicyUpdatePayload {
+  schemaVersion: number;
+  version: number;
+  issuedAt: string;
+  policy: Record<string, unknown>;
+}
+
+export interface PrivExecSignedPolicyUpdate {
+  payload: PrivExecPolicyUpdatePayload;
+  signature: PrivExecSignatureEnvelope;
+}
+
+export interface PrivExecBootstrapRoots {
+  gameRoots: string[];
+  vhdRoots: string[];
+  // Folders games mount their volumes below instead of drive letters.
+  mountRoots: string[];
+}
+
+// The first policy installed by the setup wizard; skippedRoots were missing or relative.
+export interface PrivExecBootstrapReport {
+  keyId: string;
+  publicKey: string;
+  keyPath: string;
+  policyPath: string;
+  roots: PrivExecBootstrapRoots;
+  skippedRoots: string[];
+  update: PrivExecSignedPolicyUpdate;
+}
+
 export interface PrivExecPaths {
   rootDir: string;
   policyPath: string;
@@ -50,3 +125,22 @@ export interface PrivExecRuntimeOptions {
   deviceId?: string;
   bootstrapPublicKeys?: Record<string, string>;
 }
+
+export interface BrokerStatus {
+  running: boolean;
+  residentEnabled: boolean;
+  resident: boolean;
+  pid?: number | null;
+  port?: number | null;
+  startedAt?: string | null;
+  mountedGames: string[];
+  restarts: number;
+  lastError?: string | null;
+}
+
+// A line of the launcher's block in the system hosts file.
+export interface HostsEntry {
+  address: string;
+  host: string;
+  gameId?: string;
+}
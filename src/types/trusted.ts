@@ -33,3 +33,20 @@ export interface RollbackResult {
   message?: string | null;
   verification?: SegatoolsTrustStatus;
 }
+
+export interface ScrubFinding {
+  kind: string;
+  gameId: string;
+  location: string;
+  checked: number;
+  corrupted: string[];
+  missing: string[];
+  unverifiable: boolean;
+}
+
+export interface ScrubReport {
+  startedAt: string;
+  finishedAt?: string | null;
+  cancelled: boolean;
+  findings: ScrubFinding[];
+}
@@ -1,4 +1,5 @@
 import { Game } from './games';
+import { TrashEntry } from './manage';
 
 export interface VhdConfig {
   app_base_path: string;
@@ -6,6 +7,29 @@ export interface VhdConfig {
   appdata_path: string;
   option_path: string;
   delta_enabled: boolean;
+  delta_dir?: string | null;
+  delta_min_free_mb?: number;
+  // Finished sessions kept as snapshots instead of deleting the runtime delta; 0 keeps none.
+  delta_snapshots?: number;
+  // Empty NTFS folder whose app/appdata/option subfolders are used instead of drive letters.
+  mount_root?: string | null;
+  // Seconds a launch waits for X:/Y:/Z: to be released by a detaching VHD before using other letters.
+  letter_wait_secs?: number;
+}
+
+export interface LetterHolder {
+  letter: string;
+  image?: string | null;
+  network?: string | null;
+  process?: { pid: number; name: string } | null;
+}
+
+/** Payload of the `vhd-letter-wait` event. */
+export interface LetterWaitEvent {
+  gameId: string;
+  waitedMs: number;
+  timeoutMs: number;
+  holders: LetterHolder[];
 }
 
 export interface VhdDetectResult {
@@ -17,3 +41,85 @@ export interface AutoDetectResult {
   game: Game;
   vhd: VhdConfig | null;
 }
+
+export interface VhdExportProgress {
+  source: string;
+  stage: 'copying' | 'verifying';
+  processed: number;
+  total: number;
+}
+
+export interface VhdExportResult {
+  destination: string;
+  bytes: number;
+  sha256: string | null;
+  relocatedEntries: number;
+  sourceRemoved: boolean;
+}
+
+export interface VhdWarmUpProgress {
+  file: string;
+  fileIndex: number;
+  fileCount: number;
+  processed: number;
+  total: number;
+}
+
+export interface VhdWarmUpReport {
+  files: string[];
+  bytesRead: number;
+  elapsedMs: number;
+  cancelled: boolean;
+}
+
+export interface DeltaSnapshot {
+  name: string;
+  file: string;
+  parentPath: string;
+  createdAt: string;
+  size: number;
+  // Renamed snapshots are not rotated out.
+  pinned: boolean;
+}
+
+export interface DeltaSnapshots {
+  snapshots: DeltaSnapshot[];
+  pendingRestore?: string | null;
+}
+
+export interface MigrationCopy {
+  source: string;
+  dest: string;
+  bytes: number;
+}
+
+export interface MigrationVolume {
+  role: 'app' | 'appdata' | 'option';
+  image: string;
+  sizeMb: number;
+  contentBytes: number;
+  copies: MigrationCopy[];
+}
+
+export interface MigrationPlan {
+  targetDir: string;
+  volumes: MigrationVolume[];
+  requiredBytes: number;
+  freeBytes?: number | null;
+}
+
+// Payload of `vhd-migration-progress`.
+export interface MigrationProgress {
+  gameId: string;
+  role: MigrationVolume['role'];
+  stage: 'creating' | 'copying' | 'detaching';
+  copiedBytes: number;
+  totalBytes: number;
+  current?: string | null;
+}
+
+export interface VhdMigrationReport {
+  plan: MigrationPlan;
+  detectedGame: string;
+  trashed: TrashEntry[];
+}